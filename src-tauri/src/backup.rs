@@ -1,11 +1,21 @@
-use std::path::{Path, PathBuf};
+use serde::Serialize;
+use std::path::{Component, Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::fs;
 
 use crate::error::HibiscusError;
+use crate::time::Timestamp;
 
 const MAX_BACKUPS: usize = 10;
 
+/// Default for the `backup_retention` setting: backups `write_text_file`
+/// keeps per file.
+pub const DEFAULT_FILE_BACKUPS: usize = 5;
+
+/// Folder under `.hibiscus/backups` holding the backups of workspace files,
+/// apart from those of the app's own data files (`create_backup`).
+const FILE_BACKUPS_DIR: &str = "files";
+
 /// Creates a backup of the source file in the .hibiscus/backups directory.
 /// Keeps only the most recent MAX_BACKUPS files.
 pub async fn create_backup(source_path: &Path, root: &Path) -> Result<PathBuf, HibiscusError> {
@@ -17,12 +27,52 @@ pub async fn create_backup(source_path: &Path, root: &Path) -> Result<PathBuf, H
         .file_name()
         .and_then(|n| n.to_str())
         .unwrap_or("unknown");
-        
-    let backup_dir = root.join(".hibiscus").join("backups").join(file_name);
-    
+
+    backup_into(source_path, &backups_dir(root).join(file_name), MAX_BACKUPS).await
+}
+
+/// Backs up a workspace file before it is overwritten, into
+/// `.hibiscus/backups/files/<relative path>/`, and keeps the newest `keep`
+/// backups of it.
+///
+/// # Returns
+/// * `Ok(Some(PathBuf))` - The new backup
+/// * `Ok(None)` - If there is no file to back up yet
+/// * `Err(HibiscusError)` - If `path` is not inside `root`, or the copy failed
+pub async fn backup_file(path: &Path, root: &Path, keep: usize) -> Result<Option<PathBuf>, HibiscusError> {
+    let backup_dir = file_backup_dir(root, path)?;
+    if !path.is_file() {
+        return Ok(None);
+    }
+    backup_into(path, &backup_dir, keep).await.map(Some)
+}
+
+/// The folder holding the backups of `path`: its path relative to `root`,
+/// under `.hibiscus/backups/files`. Paths that are not strictly inside `root`
+/// have none, so backups can never land outside `.hibiscus`.
+pub fn file_backup_dir(root: &Path, path: &Path) -> Result<PathBuf, HibiscusError> {
+    path.strip_prefix(root)
+        .ok()
+        .filter(|rel| {
+            rel.components().next().is_some() && rel.components().all(|c| matches!(c, Component::Normal(_)))
+        })
+        .map(|rel| backups_dir(root).join(FILE_BACKUPS_DIR).join(rel))
+        .ok_or_else(|| {
+            HibiscusError::PathValidation(format!("'{}' is outside the workspace", path.display()))
+        })
+}
+
+/// Copies `source_path` to `<backup_dir>/<name>_<ms>.bak` and prunes the
+/// folder to the newest `keep` backups.
+async fn backup_into(source_path: &Path, backup_dir: &Path, keep: usize) -> Result<PathBuf, HibiscusError> {
+    let file_name = source_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown");
+
     // Create backup directory if it doesn't exist
     if !backup_dir.exists() {
-        fs::create_dir_all(&backup_dir)
+        fs::create_dir_all(backup_dir)
             .await
             .map_err(|e| HibiscusError::Io(format!("Failed to create backup dir: {}", e)))?;
     }
@@ -31,7 +81,7 @@ pub async fn create_backup(source_path: &Path, root: &Path) -> Result<PathBuf, H
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
         .as_millis();
-        
+
     let backup_name = format!("{}_{}.bak", file_name, timestamp);
     let backup_path = backup_dir.join(&backup_name);
 
@@ -40,35 +90,293 @@ pub async fn create_backup(source_path: &Path, root: &Path) -> Result<PathBuf, H
         .map_err(|e| HibiscusError::Io(format!("Failed to create backup: {}", e)))?;
 
     // Prune old backups
-    prune_backups(&backup_dir).await?;
+    prune_backup_dir(backup_dir, keep).await?;
 
     Ok(backup_path)
 }
 
-async fn prune_backups(backup_dir: &Path) -> Result<(), HibiscusError> {
-    let mut entries = fs::read_dir(backup_dir)
+async fn prune_backup_dir(backup_dir: &Path, keep: usize) -> Result<(), HibiscusError> {
+    // Newest first; backups without a time go last
+    let mut backups = read_backup_dir(backup_dir).await?;
+    backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+
+    // Delete older backups exceeding `keep`
+    for backup in backups.into_iter().skip(keep.max(1)) {
+        let _ = fs::remove_file(backup.path).await; // Ignore errors during cleanup
+    }
+
+    Ok(())
+}
+
+/// One backup copy of a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BackupEntry {
+    pub path: String,
+    /// When the backup was taken (from the file name, else its mtime)
+    pub created_at: Option<Timestamp>,
+    pub size: u64,
+}
+
+/// All backups of one original file, newest first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileBackups {
+    /// The original, relative to the root (`/`-separated). The app's own
+    /// data files are listed as `.hibiscus/<file name>`.
+    pub file: String,
+    pub total_bytes: u64,
+    pub backups: Vec<BackupEntry>,
+}
+
+/// Outcome of `prune_to_budget`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PruneReport {
+    pub removed: usize,
+    pub freed_bytes: u64,
+    pub remaining_bytes: u64,
+}
+
+/// Returns the directory holding the backups of the workspace at `root`.
+pub fn backups_dir(root: &Path) -> PathBuf {
+    root.join(".hibiscus").join("backups")
+}
+
+/// Lists every backup in the workspace, grouped by original file.
+pub async fn list_backups(root: &Path) -> Result<Vec<FileBackups>, HibiscusError> {
+    let base = backups_dir(root);
+    let mut groups = Vec::new();
+    if !base.is_dir() {
+        return Ok(groups);
+    }
+
+    // Backups of files in folders sit in nested folders
+    let mut pending = vec![base.clone()];
+    while let Some(dir) = pending.pop() {
+        let mut entries = fs::read_dir(&dir)
+            .await
+            .map_err(|e| HibiscusError::Io(format!("Failed to read backups: {}", e)))?;
+        while let Some(entry) = entries.next_entry().await? {
+            if entry.file_type().await?.is_dir() {
+                pending.push(entry.path());
+            }
+        }
+        if dir == base {
+            continue;
+        }
+
+        let mut backups = read_backup_dir(&dir).await?;
+        if backups.is_empty() {
+            continue;
+        }
+        backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        let rel = dir.strip_prefix(&base).unwrap_or(&dir).to_string_lossy().replace('\\', "/");
+        let file = match rel.strip_prefix(FILE_BACKUPS_DIR).and_then(|rest| rest.strip_prefix('/')) {
+            Some(workspace_file) => workspace_file.to_string(),
+            None => format!(".hibiscus/{}", rel),
+        };
+        groups.push(FileBackups {
+            file,
+            total_bytes: backups.iter().map(|b| b.size).sum(),
+            backups,
+        });
+    }
+
+    groups.sort_by(|a, b| a.file.cmp(&b.file));
+    Ok(groups)
+}
+
+/// Backups of the workspace file at `path`, newest first.
+pub async fn file_backups(root: &Path, path: &Path) -> Result<Vec<BackupEntry>, HibiscusError> {
+    let dir = file_backup_dir(root, path)?;
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut backups = read_backup_dir(&dir).await?;
+    backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(backups)
+}
+
+/// The backup files directly inside `dir`, in no particular order.
+async fn read_backup_dir(dir: &Path) -> Result<Vec<BackupEntry>, HibiscusError> {
+    let mut backups = Vec::new();
+    let mut entries = fs::read_dir(dir)
         .await
         .map_err(|e| HibiscusError::Io(format!("Failed to read backup dir: {}", e)))?;
+    while let Some(entry) = entries.next_entry().await? {
+        let metadata = entry.metadata().await?;
+        if !metadata.is_file() {
+            continue;
+        }
+        let created_at = backup_millis(&entry.file_name().to_string_lossy())
+            .and_then(Timestamp::from_millis)
+            .or_else(|| metadata.modified().ok().map(|m| Timestamp(m.into())));
+        backups.push(BackupEntry {
+            path: entry.path().to_string_lossy().to_string(),
+            created_at,
+            size: metadata.len(),
+        });
+    }
+    Ok(backups)
+}
 
-    let mut backups: Vec<(PathBuf, std::time::SystemTime)> = Vec::new();
+/// Deletes the oldest backups across all files until the total size is at
+/// most `max_total_bytes`.
+pub async fn prune_to_budget(root: &Path, max_total_bytes: u64) -> Result<PruneReport, HibiscusError> {
+    let mut all: Vec<BackupEntry> = list_backups(root)
+        .await?
+        .into_iter()
+        .flat_map(|group| group.backups)
+        .collect();
+    // Oldest first; backups without a time go first
+    all.sort_by(|a, b| a.created_at.cmp(&b.created_at));
 
-    while let Some(entry) = entries.next_entry().await.unwrap_or(None) {
-        if let Ok(metadata) = entry.metadata().await {
-            if let Ok(modified) = metadata.modified() {
-                 backups.push((entry.path(), modified));
-            }
+    let mut report = PruneReport {
+        removed: 0,
+        freed_bytes: 0,
+        remaining_bytes: all.iter().map(|b| b.size).sum(),
+    };
+
+    for backup in all {
+        if report.remaining_bytes <= max_total_bytes {
+            break;
         }
+        fs::remove_file(&backup.path).await.map_err(|e| {
+            HibiscusError::Io(format!("Failed to delete backup '{}': {}", backup.path, e))
+        })?;
+        report.removed += 1;
+        report.freed_bytes += backup.size;
+        report.remaining_bytes -= backup.size;
+    }
+
+    Ok(report)
+}
+
+/// Deletes every backup in the workspace and returns how many there were.
+pub async fn clear_all(root: &Path) -> Result<usize, HibiscusError> {
+    let count = list_backups(root)
+        .await?
+        .iter()
+        .map(|group| group.backups.len())
+        .sum();
+
+    match fs::remove_dir_all(backups_dir(root)).await {
+        Ok(()) => Ok(count),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+        Err(e) => Err(HibiscusError::Io(format!("Failed to clear backups: {}", e))),
+    }
+}
+
+/// Backups of the file at `rel` (relative to the root), oldest first, as
+/// pairs of creation time (milliseconds since the Unix epoch) and path.
+///
+/// Backups whose name carries no timestamp are left out.
+pub fn snapshots(root: &Path, rel: &str) -> Vec<(i64, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(backups_dir(root).join(FILE_BACKUPS_DIR).join(rel)) else {
+        return Vec::new();
+    };
+    let mut snapshots: Vec<(i64, PathBuf)> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let ms = backup_millis(&entry.file_name().to_string_lossy())?;
+            Some((ms, entry.path()))
+        })
+        .collect();
+    snapshots.sort();
+    snapshots
+}
+
+/// Extracts the millisecond timestamp from `<name>_<ms>.bak`.
+fn backup_millis(backup_name: &str) -> Option<i64> {
+    let stem = backup_name.strip_suffix(".bak")?;
+    stem.rsplit_once('_')?.1.parse().ok()
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write_backup(root: &Path, file: &str, ms: i64, size: usize) {
+        let dir = backups_dir(root).join(file);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(format!("{}_{}.bak", file, ms)), vec![b'x'; size]).unwrap();
     }
 
-    // Sort by modification time (newest first)
-    backups.sort_by(|a, b| b.1.cmp(&a.1));
+    #[tokio::test]
+    async fn test_prune_removes_oldest_across_files() {
+        let dir = tempdir().unwrap();
+        write_backup(dir.path(), "workspace.json", 1_000, 40);
+        write_backup(dir.path(), "calendar.json", 2_000, 40);
+        write_backup(dir.path(), "workspace.json", 3_000, 40);
+        write_backup(dir.path(), "calendar.json", 4_000, 40);
+
+        let listed = list_backups(dir.path()).await.unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[1].file, ".hibiscus/workspace.json");
+        assert_eq!(listed[1].total_bytes, 80);
+        assert_eq!(listed[1].backups[0].created_at, Timestamp::from_millis(3_000));
+
+        let report = prune_to_budget(dir.path(), 90).await.unwrap();
+        assert_eq!(report, PruneReport { removed: 2, freed_bytes: 80, remaining_bytes: 80 });
 
-    // Delete older backups exceeding MAX_BACKUPS
-    if backups.len() > MAX_BACKUPS {
-        for (path, _) in backups.into_iter().skip(MAX_BACKUPS) {
-            let _ = fs::remove_file(path).await; // Ignore errors during cleanup
+        let left: Vec<i64> = list_backups(dir.path())
+            .await
+            .unwrap()
+            .into_iter()
+            .flat_map(|group| group.backups)
+            .filter_map(|backup| backup.created_at.map(|ts| ts.as_millis()))
+            .collect();
+        assert_eq!(left, vec![4_000, 3_000]);
+    }
+
+    #[tokio::test]
+    async fn test_file_backups_follow_the_relative_path() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("Notes")).unwrap();
+        let note = root.join("Notes").join("a.md");
+
+        assert_eq!(backup_file(&note, root, 2).await.unwrap(), None);
+        for version in ["one", "two", "three"] {
+            std::fs::write(&note, version).unwrap();
+            backup_file(&note, root, 2).await.unwrap().unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
         }
+        std::fs::write(root.join("a.md"), "same name, other folder").unwrap();
+        backup_file(&root.join("a.md"), root, 2).await.unwrap();
+
+        let kept = file_backups(root, &note).await.unwrap();
+        let contents: Vec<String> = kept.iter().map(|b| std::fs::read_to_string(&b.path).unwrap()).collect();
+        assert_eq!(contents, ["three", "two"]);
+        let folder = backups_dir(root).join(FILE_BACKUPS_DIR).join("Notes").join("a.md");
+        assert!(kept[0].path.starts_with(&*folder.to_string_lossy()));
+
+        // A root-level file named like an app data file keeps its own backups
+        std::fs::write(root.join("workspace.json"), "{}").unwrap();
+        backup_file(&root.join("workspace.json"), root, 2).await.unwrap();
+        write_backup(root, "workspace.json", 1_000, 10);
+
+        let files: Vec<String> = list_backups(root).await.unwrap().into_iter().map(|g| g.file).collect();
+        assert_eq!(files, [".hibiscus/workspace.json", "Notes/a.md", "a.md", "workspace.json"]);
+        assert_eq!(snapshots(root, "Notes/a.md").len(), 2);
+
+        // Nothing outside the workspace gets a backup folder
+        assert!(backup_file(&root.join("../escape.md"), root, 2).await.is_err());
+        assert!(file_backup_dir(root, Path::new("/elsewhere/a.md")).is_err());
     }
 
-    Ok(())
+    #[tokio::test]
+    async fn test_clear_empties_backups() {
+        let dir = tempdir().unwrap();
+        write_backup(dir.path(), "workspace.json", 1_000, 10);
+        write_backup(dir.path(), "calendar.json", 2_000, 10);
+
+        assert_eq!(clear_all(dir.path()).await.unwrap(), 2);
+        assert!(!backups_dir(dir.path()).exists());
+        assert!(list_backups(dir.path()).await.unwrap().is_empty());
+        assert_eq!(clear_all(dir.path()).await.unwrap(), 0);
+    }
 }
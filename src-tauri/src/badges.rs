@@ -0,0 +1,543 @@
+//! ============================================================================
+//! Hibiscus Tree Badges
+//! ============================================================================
+//!
+//! Small per-node markers in the file tree ("M", "3 open tasks", "5
+//! backlinks"), gathered from pluggable providers in one call instead of one
+//! frontend fetch per node and feature.
+//!
+//! DESIGN DECISIONS:
+//! - Each source implements `BadgeProvider` and answers for a batch of node
+//!   ids, from its own cache where it has one. `BadgeRegistry` merges the
+//!   answers in provider order.
+//! - Providers never fail. One that cannot run (no git repository, no
+//!   notes) simply contributes no badges.
+//! - After a file change, each provider names the ids whose badges may have
+//!   moved (`affected`), e.g. the notes a changed note links to. Only those
+//!   are recomputed and pushed as a `badges-updated` event.
+//! - Updates run on one long-lived worker thread. Watcher batches that
+//!   queue up while it is busy are merged, so providers refresh their
+//!   caches (e.g. rebuild the link graph) once for all of them.
+//!
+//! Node ids are canonical ids (see `crate::ids`).
+//! ============================================================================
+
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, LazyLock, Mutex, PoisonError};
+use std::time::SystemTime;
+
+use crate::graph::LinkGraph;
+use crate::ids::{from_canonical_id, to_canonical_id};
+use crate::markdown::is_markdown_extension;
+use crate::tree::{read_dir_recursive, DEFAULT_MAX_DEPTH};
+use crate::workspace::{Node, NodeType};
+
+/// Process-wide registry with the built-in providers.
+pub static BADGES: LazyLock<BadgeRegistry> = LazyLock::new(BadgeRegistry::with_default_providers);
+
+/// One marker on a tree node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Badge {
+    /// Provider that produced the badge, e.g. "git"
+    pub source: String,
+    /// Short text shown on the node, e.g. "M" or "3"
+    pub label: String,
+    /// Longer description for the tooltip
+    pub title: String,
+}
+
+impl Badge {
+    fn new(source: &str, label: impl Into<String>, title: impl Into<String>) -> Self {
+        Self {
+            source: source.to_string(),
+            label: label.into(),
+            title: title.into(),
+        }
+    }
+}
+
+/// A source of tree badges.
+pub trait BadgeProvider: Send + Sync {
+    /// Stable name, used as `Badge::source`.
+    fn name(&self) -> &'static str;
+
+    /// Badges for those of `ids` that have any.
+    fn badges(&self, root: &Path, ids: &[String]) -> HashMap<String, Vec<Badge>>;
+
+    /// Ids whose badges may differ after the files `changed` were modified,
+    /// created or deleted. Providers with caches refresh them here.
+    fn affected(&self, _root: &Path, changed: &[String]) -> Vec<String> {
+        changed.to_vec()
+    }
+}
+
+/// Ordered set of providers.
+pub struct BadgeRegistry {
+    providers: Vec<Box<dyn BadgeProvider>>,
+}
+
+impl BadgeRegistry {
+    pub fn new(providers: Vec<Box<dyn BadgeProvider>>) -> Self {
+        Self { providers }
+    }
+
+    /// Git status, open tasks, backlinks and sync conflicts.
+    pub fn with_default_providers() -> Self {
+        Self::new(vec![
+            Box::new(GitStatusBadges),
+            Box::new(TaskBadges::default()),
+            Box::new(BacklinkBadges::default()),
+            Box::new(ConflictBadges),
+        ])
+    }
+
+    /// Merged badges for `ids`. Ids without badges are absent.
+    pub fn collect(&self, root: &Path, ids: &[String]) -> HashMap<String, Vec<Badge>> {
+        let mut merged: HashMap<String, Vec<Badge>> = HashMap::new();
+        for provider in &self.providers {
+            for (id, badges) in provider.badges(root, ids) {
+                if !badges.is_empty() {
+                    merged.entry(id).or_default().extend(badges);
+                }
+            }
+        }
+        merged
+    }
+
+    /// Badges for every id affected by `changed`, the payload of
+    /// `badges-updated`. Affected ids that lost all badges map to an empty
+    /// list so the frontend clears them.
+    pub fn updates_for(&self, root: &Path, changed: &[String]) -> HashMap<String, Vec<Badge>> {
+        let affected: Vec<String> = self
+            .providers
+            .iter()
+            .flat_map(|provider| provider.affected(root, changed))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+
+        let mut updates = self.collect(root, &affected);
+        for id in affected {
+            updates.entry(id).or_default();
+        }
+        updates
+    }
+}
+
+/// Ids of every file in the tree under `root`.
+pub fn all_file_ids(root: &Path) -> Vec<String> {
+    fn walk(nodes: &[Node], out: &mut Vec<String>) {
+        for node in nodes {
+            match node.node_type {
+                NodeType::File => out.push(node.id.clone()),
+                NodeType::Folder => walk(node.children.as_deref().unwrap_or_default(), out),
+            }
+        }
+    }
+
+    let mut ids = Vec::new();
+    walk(&read_dir_recursive(root, root, DEFAULT_MAX_DEPTH, false), &mut ids);
+    ids
+}
+
+/// Changed paths of one watcher batch, waiting for the badge worker.
+struct BadgeJob {
+    window: tauri::Window,
+    root: PathBuf,
+    changed_paths: Vec<String>,
+}
+
+/// Queue of the badge worker, started on first use.
+static BADGE_JOBS: LazyLock<mpsc::Sender<BadgeJob>> = LazyLock::new(|| {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || run_badge_jobs(receiver));
+    sender
+});
+
+/// Recomputes badges for changed paths in the background and emits
+/// `badges-updated` with the result.
+pub fn queue_badge_updates(window: tauri::Window, root: PathBuf, changed_paths: Vec<String>) {
+    let _ = BADGE_JOBS.send(BadgeJob { window, root, changed_paths });
+}
+
+/// Badge worker loop: merges what queued up since the last round, then
+/// emits one update per window and workspace.
+fn run_badge_jobs(jobs: mpsc::Receiver<BadgeJob>) {
+    use tauri::Emitter;
+
+    while let Ok(first) = jobs.recv() {
+        let mut merged: Vec<BadgeJob> = Vec::new();
+        for job in std::iter::once(first).chain(jobs.try_iter()) {
+            let same = |other: &&mut BadgeJob| other.root == job.root && other.window.label() == job.window.label();
+            match merged.iter_mut().find(same) {
+                Some(other) => other.changed_paths.extend(job.changed_paths),
+                None => merged.push(job),
+            }
+        }
+
+        for job in merged {
+            let changed: Vec<String> = job
+                .changed_paths
+                .iter()
+                .map(|path| to_canonical_id(Path::new(path), &job.root))
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect();
+            let updates = BADGES.updates_for(&job.root, &changed);
+            if !updates.is_empty() {
+                if let Err(e) = job.window.emit("badges-updated", &updates) {
+                    eprintln!("[Hibiscus] Error emitting badge updates: {}", e);
+                }
+            }
+        }
+    }
+}
+
+fn is_note(id: &str) -> bool {
+    Path::new(id)
+        .extension()
+        .is_some_and(|ext| is_markdown_extension(&ext.to_string_lossy()))
+}
+
+// =============================================================================
+// PROVIDERS
+// =============================================================================
+
+/// "M" / "A" / "U" for files git reports as changed.
+pub struct GitStatusBadges;
+
+impl BadgeProvider for GitStatusBadges {
+    fn name(&self) -> &'static str {
+        "git"
+    }
+
+    fn badges(&self, root: &Path, ids: &[String]) -> HashMap<String, Vec<Badge>> {
+        let Some(status) = crate::git::status(root) else {
+            return HashMap::new();
+        };
+
+        ids.iter()
+            .filter_map(|id| {
+                let code = status.get(id)?;
+                let (label, title) = match code.trim() {
+                    "??" => ("U", "Untracked"),
+                    code if code.contains('A') => ("A", "Added"),
+                    code if code.contains('R') => ("R", "Renamed"),
+                    _ => ("M", "Modified"),
+                };
+                Some((id.clone(), vec![Badge::new(self.name(), label, title)]))
+            })
+            .collect()
+    }
+}
+
+/// Number of open `- [ ]` tasks in a note, cached by modification time.
+#[derive(Default)]
+pub struct TaskBadges {
+    cache: Mutex<HashMap<PathBuf, (SystemTime, usize)>>,
+}
+
+impl TaskBadges {
+    fn open_tasks(&self, path: &Path) -> usize {
+        let Ok(modified) = std::fs::metadata(path).and_then(|meta| meta.modified()) else {
+            return 0;
+        };
+        let mut cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some((at, count)) = cache.get(path) {
+            if *at == modified {
+                return *count;
+            }
+        }
+
+        let count = std::fs::read_to_string(path)
+            .map(|content| count_open_tasks(&content))
+            .unwrap_or(0);
+        cache.insert(path.to_path_buf(), (modified, count));
+        count
+    }
+}
+
+impl BadgeProvider for TaskBadges {
+    fn name(&self) -> &'static str {
+        "tasks"
+    }
+
+    fn badges(&self, root: &Path, ids: &[String]) -> HashMap<String, Vec<Badge>> {
+        ids.iter()
+            .filter(|id| is_note(id))
+            .filter_map(|id| {
+                let count = self.open_tasks(&from_canonical_id(id, root));
+                let title = if count == 1 { "1 open task".to_string() } else { format!("{} open tasks", count) };
+                (count > 0).then(|| (id.clone(), vec![Badge::new(self.name(), count.to_string(), title)]))
+            })
+            .collect()
+    }
+}
+
+/// Counts unchecked task list items outside fenced code blocks.
+fn count_open_tasks(markdown: &str) -> usize {
+    let mut in_fence = false;
+    markdown
+        .lines()
+        .map(str::trim_start)
+        .filter(|line| {
+            if line.starts_with("```") || line.starts_with("~~~") {
+                in_fence = !in_fence;
+                return false;
+            }
+            !in_fence
+                && ["- [ ] ", "* [ ] ", "+ [ ] "]
+                    .iter()
+                    .any(|marker| line.starts_with(marker) || *line == marker.trim_end())
+        })
+        .count()
+}
+
+/// Number of notes linking to a note.
+#[derive(Default)]
+pub struct BacklinkBadges {
+    graph: Mutex<Option<(PathBuf, Arc<LinkGraph>)>>,
+}
+
+impl BacklinkBadges {
+    fn graph(&self, root: &Path) -> Arc<LinkGraph> {
+        let mut cached = self.graph.lock().unwrap_or_else(PoisonError::into_inner);
+        match cached.as_ref() {
+            Some((cached_root, graph)) if cached_root == root => graph.clone(),
+            _ => {
+                let graph = Arc::new(LinkGraph::build(root));
+                *cached = Some((root.to_path_buf(), graph.clone()));
+                graph
+            }
+        }
+    }
+}
+
+impl BadgeProvider for BacklinkBadges {
+    fn name(&self) -> &'static str {
+        "backlinks"
+    }
+
+    fn badges(&self, root: &Path, ids: &[String]) -> HashMap<String, Vec<Badge>> {
+        let graph = self.graph(root);
+        ids.iter()
+            .filter_map(|id| {
+                let count = graph.inbound_count(id);
+                let title = if count == 1 { "1 backlink".to_string() } else { format!("{} backlinks", count) };
+                (count > 0).then(|| (id.clone(), vec![Badge::new(self.name(), count.to_string(), title)]))
+            })
+            .collect()
+    }
+
+    fn affected(&self, root: &Path, changed: &[String]) -> Vec<String> {
+        if !changed.iter().any(|id| is_note(id)) {
+            return Vec::new();
+        }
+
+        // Targets the changed notes linked to before and after the change.
+        // Without a cached graph there is no "before" to compare with.
+        let after = Arc::new(LinkGraph::build(root));
+        let before = self
+            .graph
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .replace((root.to_path_buf(), after.clone()))
+            .filter(|(cached_root, _)| cached_root == root)
+            .map(|(_, graph)| graph);
+
+        let mut affected: BTreeSet<String> = changed.iter().filter(|id| is_note(id)).cloned().collect();
+        for graph in before.iter().chain([&after]) {
+            for id in changed {
+                affected.extend(graph.outgoing.get(id).into_iter().flatten().cloned());
+            }
+        }
+        affected.into_iter().collect()
+    }
+}
+
+/// Marks sync-conflict copies (Syncthing, Dropbox) and the notes they
+/// conflict with.
+pub struct ConflictBadges;
+
+impl BadgeProvider for ConflictBadges {
+    fn name(&self) -> &'static str {
+        "conflict"
+    }
+
+    fn badges(&self, root: &Path, ids: &[String]) -> HashMap<String, Vec<Badge>> {
+        let mut by_folder: HashMap<&str, Vec<&String>> = HashMap::new();
+        for id in ids {
+            by_folder.entry(parent_id(id)).or_default().push(id);
+        }
+
+        let mut badges = HashMap::new();
+        for (folder, ids) in by_folder {
+            let Ok(entries) = std::fs::read_dir(from_canonical_id(folder, root)) else {
+                continue;
+            };
+            let originals: BTreeSet<String> = entries
+                .filter_map(|entry| conflict_original(&entry.ok()?.file_name().to_string_lossy()))
+                .collect();
+
+            for id in ids {
+                let name = file_name(id);
+                let badge = if conflict_original(name).is_some() {
+                    Badge::new(self.name(), "!", "Sync conflict copy")
+                } else if originals.contains(name) {
+                    Badge::new(self.name(), "!", "Has sync conflict copies")
+                } else {
+                    continue;
+                };
+                badges.insert(id.clone(), vec![badge]);
+            }
+        }
+        badges
+    }
+
+    fn affected(&self, _root: &Path, changed: &[String]) -> Vec<String> {
+        let mut affected = changed.to_vec();
+        for id in changed {
+            if let Some(original) = conflict_original(file_name(id)) {
+                let folder = parent_id(id);
+                affected.push(if folder.is_empty() { original } else { format!("{}/{}", folder, original) });
+            }
+        }
+        affected
+    }
+}
+
+fn parent_id(id: &str) -> &str {
+    id.rsplit_once('/').map_or("", |(folder, _)| folder)
+}
+
+fn file_name(id: &str) -> &str {
+    id.rsplit_once('/').map_or(id, |(_, name)| name)
+}
+
+/// Name of the file a conflict copy was made from, or `None` if `name` is
+/// not a conflict copy.
+///
+/// - Syncthing: `Note.sync-conflict-20240105-093000-ABCDEFG.md`
+/// - Dropbox: `Note (Alex's conflicted copy 2024-01-05).md`
+fn conflict_original(name: &str) -> Option<String> {
+    if let Some(at) = name.find(".sync-conflict-") {
+        let rest = &name[at + ".sync-conflict-".len()..];
+        let extension = rest.find('.').map_or("", |dot| &rest[dot..]);
+        return Some(format!("{}{}", &name[..at], extension));
+    }
+
+    // The last group, so `Plan (draft) (... conflicted copy ...)` keeps its own
+    let open = name.rfind(" (")?;
+    let close = open + name[open..].find(')')?;
+    name[open..close]
+        .contains("conflicted copy")
+        .then(|| format!("{}{}", &name[..open], &name[close + 1..]))
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    /// Provider with fixed badges that reports extra ids as affected.
+    struct FakeProvider {
+        name: &'static str,
+        badges: Vec<(&'static str, &'static str)>,
+        also_affects: Vec<&'static str>,
+    }
+
+    impl BadgeProvider for FakeProvider {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn badges(&self, _root: &Path, ids: &[String]) -> HashMap<String, Vec<Badge>> {
+            self.badges
+                .iter()
+                .filter(|(id, _)| ids.iter().any(|wanted| wanted == id))
+                .map(|(id, label)| (id.to_string(), vec![Badge::new(self.name, *label, *label)]))
+                .collect()
+        }
+
+        fn affected(&self, _root: &Path, changed: &[String]) -> Vec<String> {
+            let mut affected = changed.to_vec();
+            affected.extend(self.also_affects.iter().map(|id| id.to_string()));
+            affected
+        }
+    }
+
+    fn registry() -> BadgeRegistry {
+        BadgeRegistry::new(vec![
+            Box::new(FakeProvider {
+                name: "first",
+                badges: vec![("a.md", "M"), ("b.md", "A")],
+                also_affects: vec![],
+            }),
+            Box::new(FakeProvider {
+                name: "second",
+                badges: vec![("a.md", "3"), ("c.md", "1")],
+                also_affects: vec!["c.md"],
+            }),
+        ])
+    }
+
+    #[test]
+    fn test_providers_are_merged_in_order() {
+        let ids: Vec<String> = ["a.md", "b.md", "d.md"].map(String::from).to_vec();
+        let badges = registry().collect(Path::new("/vault"), &ids);
+
+        let labels = |id: &str| badges[id].iter().map(|b| (b.source.as_str(), b.label.as_str())).collect::<Vec<_>>();
+        assert_eq!(labels("a.md"), vec![("first", "M"), ("second", "3")]);
+        assert_eq!(labels("b.md"), vec![("first", "A")]);
+        assert!(!badges.contains_key("c.md"), "not requested");
+        assert!(!badges.contains_key("d.md"), "no badges");
+    }
+
+    #[test]
+    fn test_update_covers_affected_ids() {
+        let updates = registry().updates_for(Path::new("/vault"), &["d.md".to_string()]);
+
+        // The changed file has no badges left; the linked one is refreshed
+        assert_eq!(updates.len(), 2);
+        assert!(updates["d.md"].is_empty());
+        assert_eq!(updates["c.md"][0].label, "1");
+    }
+
+    #[test]
+    fn test_builtin_providers_on_disk() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("plan.md"), "- [ ] one\n- [x] done\n```\n- [ ] code\n```\n* [ ] two\n").unwrap();
+        std::fs::write(dir.path().join("hub.md"), "See [[plan]]").unwrap();
+        std::fs::write(dir.path().join("plan.sync-conflict-20240105-093000-ABC.md"), "").unwrap();
+
+        let ids = all_file_ids(dir.path());
+        assert_eq!(ids.len(), 3);
+        let badges = BadgeRegistry::new(vec![
+            Box::new(TaskBadges::default()),
+            Box::new(BacklinkBadges::default()),
+            Box::new(ConflictBadges),
+        ])
+        .collect(dir.path(), &ids);
+
+        let plan: Vec<_> = badges["plan.md"].iter().map(|b| b.title.as_str()).collect();
+        assert_eq!(plan, vec!["2 open tasks", "1 backlink", "Has sync conflict copies"]);
+        assert_eq!(badges["plan.sync-conflict-20240105-093000-ABC.md"][0].title, "Sync conflict copy");
+        assert!(!badges.contains_key("hub.md"));
+
+        assert_eq!(
+            conflict_original("Note (Alex's conflicted copy 2024-01-05).md").as_deref(),
+            Some("Note.md")
+        );
+        assert_eq!(conflict_original("Note (draft).md"), None);
+        assert_eq!(
+            conflict_original("Plan (draft) (Alex's conflicted copy 2024-01-05).md").as_deref(),
+            Some("Plan (draft).md")
+        );
+    }
+}
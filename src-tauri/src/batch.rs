@@ -0,0 +1,379 @@
+//! ============================================================================
+//! Hibiscus Batch Operations
+//! ============================================================================
+//!
+//! Runs a list of typed workspace operations as one unit: either every step
+//! applies, or the steps that already ran are undone and the batch fails.
+//!
+//! DESIGN DECISIONS:
+//! - Operations are a closed, typed enum (`BatchOp`) rather than arbitrary
+//!   commands, so a stored batch (see `crate::macros`) can be validated
+//!   before it runs.
+//! - Paths in operations are workspace-relative ids. Absolute paths and `..`
+//!   segments are rejected, so a batch cannot touch files outside the root.
+//! - Every step journals how to undo each change it makes (previous file
+//!   contents, created files and folders, moves). On failure the journal is
+//!   replayed in reverse.
+//! - Moves are reported to the reference reconciliation pass only after the
+//!   whole batch succeeded, so a rolled-back batch leaves no trace.
+//!
+//! Callers hold WORKSPACE_LOCK while a batch runs.
+//! ============================================================================
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::error::HibiscusError;
+use crate::references::{self, write_json_atomic, FileChange};
+
+/// Note quick captures go to when the operation names none.
+pub const DEFAULT_CAPTURE_FILE: &str = "Inbox.md";
+
+/// One step of a batch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BatchOp {
+    /// Creates a file; fails if it already exists.
+    CreateFile {
+        path: String,
+        #[serde(default)]
+        contents: String,
+    },
+    /// Appends `text` as a line to a file, creating the file if needed.
+    AppendToFile { path: String, text: String },
+    /// Moves a file or folder; the destination must not exist.
+    MoveNode { from: String, to: String },
+    /// Adds an event to `.hibiscus/calendar.json`.
+    AddCalendarEvent {
+        title: String,
+        /// Any date `crate::time::parse_date` accepts; stored as `YYYY-MM-DD`
+        date: String,
+        #[serde(default)]
+        linked_file: Option<String>,
+        #[serde(default)]
+        completed: bool,
+        /// Calendar event type (`exam`, `study`, ...); default `custom`
+        #[serde(default)]
+        event_type: Option<String>,
+    },
+    /// Appends `- text` to the capture note (`DEFAULT_CAPTURE_FILE`).
+    QuickCapture {
+        text: String,
+        #[serde(default)]
+        file: Option<String>,
+    },
+}
+
+impl BatchOp {
+    /// Operation name as written in JSON.
+    pub fn name(&self) -> &'static str {
+        match self {
+            BatchOp::CreateFile { .. } => "create_file",
+            BatchOp::AppendToFile { .. } => "append_to_file",
+            BatchOp::MoveNode { .. } => "move_node",
+            BatchOp::AddCalendarEvent { .. } => "add_calendar_event",
+            BatchOp::QuickCapture { .. } => "quick_capture",
+        }
+    }
+}
+
+/// Outcome of a successful batch.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BatchResult {
+    /// Number of steps applied
+    pub steps: usize,
+    /// Workspace-relative paths created, changed or moved, in step order
+    pub changed: Vec<String>,
+}
+
+/// How to take back one change.
+#[derive(Debug)]
+enum Undo {
+    RemoveFile(PathBuf),
+    RemoveDir(PathBuf),
+    Restore(PathBuf, Vec<u8>),
+    Move { from: PathBuf, to: PathBuf },
+}
+
+/// Runs `ops` against the workspace at `root`, all or nothing.
+pub fn run_batch(root: &Path, ops: &[BatchOp]) -> Result<BatchResult, HibiscusError> {
+    let mut journal = Vec::new();
+    let mut result = BatchResult::default();
+    let mut renames = Vec::new();
+
+    for (index, op) in ops.iter().enumerate() {
+        if let Err(e) = apply(root, op, &mut journal, &mut result, &mut renames) {
+            let rollback = roll_back(journal);
+            let mut message = format!("Step {} ({}) failed: {}", index + 1, op.name(), e);
+            if !rollback.is_empty() {
+                message.push_str(&format!("; rollback incomplete: {}", rollback.join("; ")));
+            }
+            return Err(HibiscusError::Workspace(message));
+        }
+        result.steps += 1;
+    }
+
+    if !renames.is_empty() {
+        // The files are already in place; stale references are recoverable
+        if let Err(e) = references::reconcile(root, &renames) {
+            eprintln!("[Hibiscus] Warning: Failed to reconcile references: {}", e);
+        }
+    }
+
+    Ok(result)
+}
+
+fn apply(
+    root: &Path,
+    op: &BatchOp,
+    journal: &mut Vec<Undo>,
+    result: &mut BatchResult,
+    renames: &mut Vec<FileChange>,
+) -> Result<(), HibiscusError> {
+    match op {
+        BatchOp::CreateFile { path, contents } => {
+            let target = workspace_path(root, path)?;
+            if target.exists() {
+                return Err(HibiscusError::AlreadyExists(path.clone()));
+            }
+            create_parents(&target, journal)?;
+            fs::write(&target, contents)?;
+            journal.push(Undo::RemoveFile(target));
+            result.changed.push(path.clone());
+        }
+        BatchOp::AppendToFile { path, text } => {
+            append_line(root, path, text, journal)?;
+            result.changed.push(path.clone());
+        }
+        BatchOp::QuickCapture { text, file } => {
+            let path = file.as_deref().unwrap_or(DEFAULT_CAPTURE_FILE);
+            append_line(root, path, &format!("- {}", text), journal)?;
+            result.changed.push(path.to_string());
+        }
+        BatchOp::MoveNode { from, to } => {
+            let source = workspace_path(root, from)?;
+            let destination = workspace_path(root, to)?;
+            if !source.exists() {
+                return Err(HibiscusError::FileNotFound(from.clone()));
+            }
+            if destination.exists() {
+                return Err(HibiscusError::AlreadyExists(to.clone()));
+            }
+            create_parents(&destination, journal)?;
+            fs::rename(&source, &destination)?;
+            journal.push(Undo::Move {
+                from: source,
+                to: destination,
+            });
+            renames.push(FileChange::Renamed {
+                from: from.clone(),
+                to: to.clone(),
+            });
+            result.changed.push(to.clone());
+        }
+        BatchOp::AddCalendarEvent {
+            title,
+            date,
+            linked_file,
+            completed,
+            event_type,
+        } => {
+            let date = crate::time::parse_date(date)
+                .ok_or_else(|| HibiscusError::Calendar(format!("Invalid date '{}'", date)))?;
+            let mut event = serde_json::json!({
+                "id": event_id()?,
+                "title": title,
+                "date": date.format("%Y-%m-%d").to_string(),
+                "type": event_type.as_deref().unwrap_or("custom"),
+                "completed": completed,
+            });
+            if let Some(linked_file) = linked_file {
+                event["linkedFile"] = linked_file.clone().into();
+            }
+            add_calendar_event(root, event, journal)?;
+            result.changed.push(".hibiscus/calendar.json".into());
+        }
+    }
+    Ok(())
+}
+
+/// Undoes the journal newest first; returns what could not be undone.
+fn roll_back(journal: Vec<Undo>) -> Vec<String> {
+    let mut failures = Vec::new();
+    for undo in journal.into_iter().rev() {
+        let (path, outcome) = match &undo {
+            Undo::RemoveFile(path) => (path, fs::remove_file(path)),
+            Undo::RemoveDir(path) => (path, fs::remove_dir(path)),
+            Undo::Restore(path, contents) => (path, fs::write(path, contents)),
+            Undo::Move { from, to } => (to, fs::rename(to, from)),
+        };
+        if let Err(e) = outcome {
+            failures.push(format!("'{}': {}", path.display(), e));
+        }
+    }
+    failures
+}
+
+/// Resolves a workspace-relative id, refusing paths that leave the root.
+fn workspace_path(root: &Path, id: &str) -> Result<PathBuf, HibiscusError> {
+    let relative = Path::new(id);
+    let escapes = relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+    if id.trim().is_empty() || escapes {
+        return Err(HibiscusError::PathValidation(format!(
+            "'{}' is not a path inside the workspace",
+            id
+        )));
+    }
+    Ok(crate::ids::from_canonical_id(id, root))
+}
+
+/// Creates missing parent folders, journaling each one.
+fn create_parents(path: &Path, journal: &mut Vec<Undo>) -> Result<(), HibiscusError> {
+    let missing: Vec<&Path> = path
+        .ancestors()
+        .skip(1)
+        .take_while(|dir| !dir.exists())
+        .collect();
+    for dir in missing.into_iter().rev() {
+        fs::create_dir(dir)?;
+        journal.push(Undo::RemoveDir(dir.to_path_buf()));
+    }
+    Ok(())
+}
+
+fn append_line(
+    root: &Path,
+    path: &str,
+    text: &str,
+    journal: &mut Vec<Undo>,
+) -> Result<(), HibiscusError> {
+    let target = workspace_path(root, path)?;
+    let existed = target.is_file();
+    let mut contents = if existed {
+        let previous = fs::read(&target)?;
+        journal.push(Undo::Restore(target.clone(), previous.clone()));
+        previous
+    } else {
+        create_parents(&target, journal)?;
+        Vec::new()
+    };
+
+    if !contents.is_empty() && !contents.ends_with(b"\n") {
+        contents.push(b'\n');
+    }
+    contents.extend_from_slice(text.as_bytes());
+    contents.push(b'\n');
+    fs::write(&target, contents)?;
+    if !existed {
+        journal.push(Undo::RemoveFile(target));
+    }
+    Ok(())
+}
+
+fn add_calendar_event(
+    root: &Path,
+    event: serde_json::Value,
+    journal: &mut Vec<Undo>,
+) -> Result<(), HibiscusError> {
+    let path = root.join(".hibiscus").join("calendar.json");
+    let mut data = if path.is_file() {
+        let previous = fs::read(&path)?;
+        let data = serde_json::from_slice(&previous)
+            .map_err(|e| HibiscusError::Calendar(format!("Invalid calendar format: {}", e)))?;
+        journal.push(Undo::Restore(path.clone(), previous));
+        data
+    } else {
+        create_parents(&path, journal)?;
+        serde_json::json!({ "events": [], "tasks": [] })
+    };
+    crate::migration::migrate_calendar(&mut data);
+
+    match data.get_mut("events").and_then(serde_json::Value::as_array_mut) {
+        Some(events) => events.push(event),
+        None => data["events"] = serde_json::Value::Array(vec![event]),
+    }
+    let created = !path.exists();
+    write_json_atomic(&path, &data)?;
+    if created {
+        journal.push(Undo::RemoveFile(path));
+    }
+    Ok(())
+}
+
+/// Random id in the UUID layout the frontend uses for events.
+fn event_id() -> Result<String, HibiscusError> {
+    let hex = crate::capabilities::random_token()?;
+    Ok(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    ))
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_failed_step_rolls_back_earlier_steps() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("log.md"), "old line").unwrap();
+        fs::write(root.join("note.md"), "note").unwrap();
+        fs::write(root.join("taken.md"), "taken").unwrap();
+
+        let ops = vec![
+            BatchOp::AppendToFile { path: "log.md".into(), text: "new line".into() },
+            BatchOp::CreateFile { path: "new/deep/file.md".into(), contents: "x".into() },
+            BatchOp::MoveNode { from: "note.md".into(), to: "archive/note.md".into() },
+            BatchOp::AddCalendarEvent {
+                title: "Done".into(),
+                date: "2024-3-5".into(),
+                linked_file: None,
+                completed: true,
+                event_type: None,
+            },
+            BatchOp::CreateFile { path: "taken.md".into(), contents: String::new() },
+        ];
+
+        let err = run_batch(root, &ops).unwrap_err().to_string();
+        assert!(err.contains("Step 5 (create_file)"), "{}", err);
+
+        assert_eq!(fs::read_to_string(root.join("log.md")).unwrap(), "old line");
+        assert!(!root.join("new").exists());
+        assert!(!root.join("archive").exists());
+        assert_eq!(fs::read_to_string(root.join("note.md")).unwrap(), "note");
+        assert!(!root.join(".hibiscus").exists());
+
+        // Without the failing step everything applies
+        let result = run_batch(root, &ops[..4]).unwrap();
+        assert_eq!(result.steps, 4);
+        assert_eq!(fs::read_to_string(root.join("log.md")).unwrap(), "old line\nnew line\n");
+        assert!(root.join("archive").join("note.md").is_file());
+        let calendar: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(root.join(".hibiscus/calendar.json")).unwrap())
+                .unwrap();
+        assert_eq!(calendar["events"][0]["date"], "2024-03-05");
+        assert_eq!(calendar["events"][0]["completed"], true);
+    }
+
+    #[test]
+    fn test_paths_outside_root_are_rejected() {
+        let dir = tempdir().unwrap();
+        for path in ["../escape.md", "/etc/passwd", ""] {
+            let op = BatchOp::CreateFile { path: path.into(), contents: String::new() };
+            assert!(run_batch(dir.path(), &[op]).is_err(), "{}", path);
+        }
+    }
+}
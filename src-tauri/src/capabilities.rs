@@ -0,0 +1,242 @@
+//! ============================================================================
+//! Hibiscus Capability Tokens
+//! ============================================================================
+//!
+//! Defense in depth for destructive commands. Even a compromised webview
+//! cannot permanently delete data on its own: gated commands need a token
+//! that only `request_capability` hands out, after the user confirmed the
+//! action in a native dialog the webview cannot script.
+//!
+//! DESIGN DECISIONS:
+//! - Tokens are random, single-use and expire after `TOKEN_TTL`. Presenting a
+//!   token consumes it, even for the wrong kind, so a leaked token is burnt
+//!   on first use.
+//! - `GATED_CAPABILITIES` are always gated. Kinds in `STRICT_CAPABILITIES`
+//!   are only gated when listed in `settings.gated_capabilities`; settings
+//!   can add gates but never remove the built-in ones.
+//! - The dialog goes through the `ConfirmPrompt` trait so the flow can be
+//!   tested without a window.
+//!
+//! Tokens live in memory only and do not survive a restart.
+//! ============================================================================
+
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::error::HibiscusError;
+use crate::workspace::WorkspaceSettings;
+
+/// How long an issued token stays valid.
+pub const TOKEN_TTL: Duration = Duration::from_secs(60);
+
+/// Permanently deleting files or folders (`delete_file`, `delete_folder`).
+pub const PERMANENT_DELETE: &str = "permanent_delete";
+/// Deleting all focus time data (`clear_time_tracking`).
+pub const CLEAR_TIME_TRACKING: &str = "clear_time_tracking";
+
+/// Kinds that always require a token.
+pub const GATED_CAPABILITIES: &[&str] = &[PERMANENT_DELETE];
+
+/// Kinds a workspace can opt into gating via `settings.gated_capabilities`.
+pub const STRICT_CAPABILITIES: &[&str] = &[CLEAR_TIME_TRACKING];
+
+/// Returns the confirmation text shown for `kind`, or `None` if unknown.
+pub fn describe(kind: &str) -> Option<&'static str> {
+    match kind {
+        PERMANENT_DELETE => Some("Permanently delete files or folders. This cannot be undone."),
+        CLEAR_TIME_TRACKING => Some("Delete all recorded focus time for this workspace."),
+        _ => None,
+    }
+}
+
+/// Returns `true` if `kind` needs a token under `settings`.
+pub fn is_gated(kind: &str, settings: &WorkspaceSettings) -> bool {
+    GATED_CAPABILITIES.contains(&kind)
+        || (STRICT_CAPABILITIES.contains(&kind)
+            && settings.gated_capabilities.iter().any(|gated| gated == kind))
+}
+
+/// Native confirmation dialog used by `request`.
+pub trait ConfirmPrompt {
+    /// Shows `message` and returns `true` if the user confirmed.
+    fn confirm(&self, title: &str, message: &str) -> bool;
+}
+
+struct IssuedToken {
+    kind: String,
+    expires_at: Instant,
+}
+
+/// Outstanding tokens, registered as Tauri managed state.
+#[derive(Default)]
+pub struct CapabilityStore {
+    tokens: Mutex<HashMap<String, IssuedToken>>,
+}
+
+impl CapabilityStore {
+    /// Issues a token for `kind`, valid for `TOKEN_TTL`.
+    pub fn issue(&self, kind: &str) -> Result<String, HibiscusError> {
+        self.issue_at(kind, Instant::now())
+    }
+
+    /// Verifies and consumes `token` for `kind`.
+    ///
+    /// Fails with `CapabilityRequired` if the token is missing, unknown,
+    /// expired, already used or was issued for another kind.
+    pub fn consume(&self, kind: &str, token: Option<&str>) -> Result<(), HibiscusError> {
+        self.consume_at(kind, token, Instant::now())
+    }
+
+    /// Consumes a token for `kind` only if `settings` gate it.
+    pub fn require(
+        &self,
+        kind: &str,
+        token: Option<&str>,
+        settings: &WorkspaceSettings,
+    ) -> Result<(), HibiscusError> {
+        if is_gated(kind, settings) {
+            self.consume(kind, token)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn issue_at(&self, kind: &str, now: Instant) -> Result<String, HibiscusError> {
+        let token = random_token()?;
+        let mut tokens = self.tokens.lock().unwrap_or_else(PoisonError::into_inner);
+
+        tokens.retain(|_, issued| issued.expires_at > now);
+        tokens.insert(
+            token.clone(),
+            IssuedToken {
+                kind: kind.to_string(),
+                expires_at: now + TOKEN_TTL,
+            },
+        );
+        Ok(token)
+    }
+
+    fn consume_at(&self, kind: &str, token: Option<&str>, now: Instant) -> Result<(), HibiscusError> {
+        let required = || HibiscusError::CapabilityRequired(kind.to_string());
+
+        let token = token.ok_or_else(required)?;
+        let issued = self
+            .tokens
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(token);
+
+        match issued {
+            Some(issued) if issued.kind == kind && issued.expires_at > now => Ok(()),
+            _ => Err(required()),
+        }
+    }
+}
+
+/// Asks the user to confirm `kind` and issues a token if they do.
+pub fn request(
+    store: &CapabilityStore,
+    prompt: &dyn ConfirmPrompt,
+    kind: &str,
+) -> Result<String, HibiscusError> {
+    let description = describe(kind).ok_or_else(|| {
+        HibiscusError::PathValidation(format!("Unknown capability '{}'", kind))
+    })?;
+
+    if !prompt.confirm("Confirm action", description) {
+        return Err(HibiscusError::CapabilityRequired(kind.to_string()));
+    }
+    store.issue(kind)
+}
+
+/// 128 bits from the OS random source, hex encoded.
+pub(crate) fn random_token() -> Result<String, HibiscusError> {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes)
+        .map_err(|e| HibiscusError::Io(format!("Failed to generate token: {}", e)))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// Prompt that answers with a fixed choice and counts its calls.
+    struct MockPrompt {
+        answer: bool,
+        calls: Cell<usize>,
+    }
+
+    impl ConfirmPrompt for MockPrompt {
+        fn confirm(&self, _title: &str, _message: &str) -> bool {
+            self.calls.set(self.calls.get() + 1);
+            self.answer
+        }
+    }
+
+    #[test]
+    fn test_tokens_are_single_use_and_kind_bound() {
+        let store = CapabilityStore::default();
+        let prompt = MockPrompt { answer: true, calls: Cell::new(0) };
+
+        let token = request(&store, &prompt, PERMANENT_DELETE).unwrap();
+        assert_eq!(prompt.calls.get(), 1);
+        assert!(store.consume(PERMANENT_DELETE, Some(&token)).is_ok());
+        assert!(matches!(
+            store.consume(PERMANENT_DELETE, Some(&token)),
+            Err(HibiscusError::CapabilityRequired(kind)) if kind == PERMANENT_DELETE
+        ));
+
+        // A token for another kind is rejected and burnt
+        let token = request(&store, &prompt, CLEAR_TIME_TRACKING).unwrap();
+        assert!(store.consume(PERMANENT_DELETE, Some(&token)).is_err());
+        assert!(store.consume(CLEAR_TIME_TRACKING, Some(&token)).is_err());
+
+        assert!(store.consume(PERMANENT_DELETE, None).is_err());
+        assert!(store.consume(PERMANENT_DELETE, Some("forged")).is_err());
+    }
+
+    #[test]
+    fn test_tokens_expire() {
+        let store = CapabilityStore::default();
+        let issued = Instant::now();
+
+        let token = store.issue_at(PERMANENT_DELETE, issued).unwrap();
+        assert!(store
+            .consume_at(PERMANENT_DELETE, Some(&token), issued + TOKEN_TTL)
+            .is_err());
+
+        let token = store.issue_at(PERMANENT_DELETE, issued).unwrap();
+        assert!(store
+            .consume_at(PERMANENT_DELETE, Some(&token), issued + TOKEN_TTL / 2)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_declined_prompt_and_strict_settings() {
+        let store = CapabilityStore::default();
+        let declined = MockPrompt { answer: false, calls: Cell::new(0) };
+        assert!(matches!(
+            request(&store, &declined, PERMANENT_DELETE),
+            Err(HibiscusError::CapabilityRequired(_))
+        ));
+        assert!(request(&store, &declined, "format_disk").is_err());
+        assert_eq!(declined.calls.get(), 1);
+
+        // Strict kinds are only gated when the workspace opts in
+        let relaxed = WorkspaceSettings::default();
+        assert!(store.require(CLEAR_TIME_TRACKING, None, &relaxed).is_ok());
+        assert!(store.require(PERMANENT_DELETE, None, &relaxed).is_err());
+
+        let strict = WorkspaceSettings::from_value(Some(&serde_json::json!({
+            "gated_capabilities": [CLEAR_TIME_TRACKING]
+        })));
+        assert!(store.require(CLEAR_TIME_TRACKING, None, &strict).is_err());
+    }
+}
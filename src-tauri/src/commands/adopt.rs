@@ -0,0 +1,461 @@
+// ============================================================================
+// FOLDER ADOPTION
+// ============================================================================
+//
+// Turns a plain folder of markdown (a Zettlr vault, a GitHub repo of notes)
+// into a Hibiscus workspace:
+// - site-root (`/images/x.png`) and absolute-path links whose targets exist
+//   inside the folder become relative links
+// - optionally, `Folder/README.md` becomes the folder note `Folder/Folder.md`
+// - `.hibiscus/workspace.json` is scaffolded; the initial tree is returned
+//   in the report (workspace.json no longer stores it)
+//
+// Every change is recorded in the returned journal (and, unless it is a dry
+// run, in `.hibiscus/adoption-journal.json`).
+// ============================================================================
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::error::HibiscusError;
+use crate::ids::canonicalize_id;
+use crate::markdown::{is_markdown_extension, relative_link, rewrite_link_destinations};
+use crate::migration::WORKSPACE_SCHEMA_VERSION;
+use crate::references::write_json_atomic;
+use crate::tree::{read_dir_recursive, DEFAULT_MAX_DEPTH};
+use crate::workspace::{Node, WorkspaceFile, WorkspaceInfo};
+use super::path::validate_path;
+
+/// Options for `adopt_folder_as_workspace`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AdoptionOptions {
+    /// Plan and report everything without touching the disk
+    pub dry_run: bool,
+    /// Rename `Folder/README.md` to the folder note `Folder/Folder.md`
+    pub readme_to_folder_note: bool,
+}
+
+/// Why a link was rewritten (or could not be).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkCategory {
+    /// `/images/x.png`, relative to the folder root
+    SiteRoot,
+    /// A filesystem path such as `/Users/me/notes/x.md` or `C:\notes\x.md`
+    Absolute,
+    /// A relative link to a README that became a folder note
+    FolderNote,
+}
+
+/// One change made (or planned, in a dry run) while adopting a folder.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum AdoptionAction {
+    Scaffold { path: String },
+    RewriteLink { note: String, from: String, to: String, category: LinkCategory },
+    RenameReadme { from: String, to: String },
+}
+
+/// A site-root or absolute link whose target is not inside the folder.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnresolvedLink {
+    pub note: String,
+    pub target: String,
+    pub category: LinkCategory,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct AdoptionCounts {
+    pub site_root_links: usize,
+    pub absolute_links: usize,
+    pub folder_note_links: usize,
+    pub unresolved_links: usize,
+    pub folder_notes: usize,
+    pub skipped_readmes: usize,
+}
+
+/// Result of adopting a folder.
+#[derive(Debug, Serialize)]
+pub struct AdoptionReport {
+    pub dry_run: bool,
+    pub counts: AdoptionCounts,
+    /// Every change, in the order it was applied
+    pub journal: Vec<AdoptionAction>,
+    pub unresolved: Vec<UnresolvedLink>,
+    /// READMEs left alone, with the reason
+    pub skipped: Vec<String>,
+    /// Where the journal was saved (`None` in a dry run)
+    pub journal_path: Option<String>,
+    /// The initial tree (before README renames in a dry run)
+    pub tree: Vec<Node>,
+}
+
+/// Converts a plain markdown folder into a Hibiscus workspace.
+///
+/// # Arguments
+/// * `root` - Folder to adopt
+/// * `options` - Dry run and README conversion (both off by default)
+///
+/// # Returns
+/// * `Ok(AdoptionReport)` - Journal, per-category counts and the tree
+/// * `Err(HibiscusError)` - If the folder is already a workspace or a file
+///   cannot be rewritten
+#[tauri::command]
+pub async fn adopt_folder_as_workspace(
+    root: String,
+    options: Option<AdoptionOptions>,
+) -> Result<AdoptionReport, HibiscusError> {
+    let root = PathBuf::from(&root);
+
+    // Validate path
+    validate_path(&root)?;
+
+    if !root.is_dir() {
+        return Err(HibiscusError::InvalidPathType {
+            path: root.to_string_lossy().into(),
+            expected: "directory".into(),
+            actual: "file".into(),
+        });
+    }
+
+    let options = options.unwrap_or_default();
+    tokio::task::spawn_blocking(move || adopt_folder(&root, &options))
+        .await
+        .map_err(|e| HibiscusError::Io(format!("Adoption task failed: {}", e)))?
+}
+
+fn adopt_folder(root: &Path, options: &AdoptionOptions) -> Result<AdoptionReport, HibiscusError> {
+    let hibiscus = root.join(".hibiscus");
+    let workspace_path = hibiscus.join("workspace.json");
+    if workspace_path.exists() {
+        return Err(HibiscusError::Workspace(format!(
+            "'{}' is already a Hibiscus workspace",
+            root.display()
+        )));
+    }
+
+    let mut report = AdoptionReport {
+        dry_run: options.dry_run,
+        counts: AdoptionCounts::default(),
+        journal: Vec::new(),
+        unresolved: Vec::new(),
+        skipped: Vec::new(),
+        journal_path: None,
+        tree: Vec::new(),
+    };
+
+    let mut files = Vec::new();
+    collect_files(&read_dir_recursive(root, root, DEFAULT_MAX_DEPTH, false), &mut files);
+    let notes: Vec<&String> = files
+        .iter()
+        .filter(|rel| {
+            Path::new(rel.as_str())
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(is_markdown_extension)
+        })
+        .collect();
+
+    let renames = if options.readme_to_folder_note {
+        plan_folder_notes(root, &notes, &mut report)
+    } else {
+        BTreeMap::new()
+    };
+
+    for note in &notes {
+        let path = root.join(note.as_str());
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| HibiscusError::Io(format!("Failed to read '{}': {}", path.display(), e)))?;
+
+        let note_dir = parent_of(note);
+        let rewritten = rewrite_link_destinations(&content, |dest| {
+            let (target, category) = classify_link(root, note_dir, dest)?;
+            let (target_path, fragment) = split_fragment(&target);
+
+            if !root.join(target_path).exists() {
+                if category != LinkCategory::FolderNote {
+                    report.unresolved.push(UnresolvedLink {
+                        note: note.to_string(),
+                        target: dest.to_string(),
+                        category,
+                    });
+                }
+                return None;
+            }
+
+            let renamed = renames.get(target_path);
+            if category == LinkCategory::FolderNote && renamed.is_none() {
+                return None;
+            }
+            let target_path = renamed.map_or(target_path, String::as_str);
+            let to = format!("{}{}", relative_link(note_dir, target_path), fragment);
+
+            report.journal.push(AdoptionAction::RewriteLink {
+                note: note.to_string(),
+                from: dest.to_string(),
+                to: to.clone(),
+                category,
+            });
+            Some(to)
+        });
+
+        if rewritten != content && !options.dry_run {
+            write_atomic(&path, &rewritten)?;
+        }
+    }
+
+    for (from, to) in &renames {
+        if !options.dry_run {
+            std::fs::rename(root.join(from), root.join(to)).map_err(|e| {
+                HibiscusError::Io(format!("Failed to rename '{}' to '{}': {}", from, to, e))
+            })?;
+        }
+        report.journal.push(AdoptionAction::RenameReadme { from: from.clone(), to: to.clone() });
+    }
+
+    report.tree = read_dir_recursive(root, root, DEFAULT_MAX_DEPTH, false);
+    report.journal.push(AdoptionAction::Scaffold {
+        path: workspace_path.to_string_lossy().to_string(),
+    });
+
+    for action in &report.journal {
+        match action {
+            AdoptionAction::RewriteLink { category: LinkCategory::SiteRoot, .. } => report.counts.site_root_links += 1,
+            AdoptionAction::RewriteLink { category: LinkCategory::Absolute, .. } => report.counts.absolute_links += 1,
+            AdoptionAction::RewriteLink { category: LinkCategory::FolderNote, .. } => report.counts.folder_note_links += 1,
+            AdoptionAction::RenameReadme { .. } => report.counts.folder_notes += 1,
+            AdoptionAction::Scaffold { .. } => {}
+        }
+    }
+    report.counts.unresolved_links = report.unresolved.len();
+    report.counts.skipped_readmes = report.skipped.len();
+
+    if !options.dry_run {
+        std::fs::create_dir_all(&hibiscus)
+            .map_err(|e| HibiscusError::Io(format!("Failed to create .hibiscus: {}", e)))?;
+
+        let workspace = WorkspaceFile {
+            schema_version: WORKSPACE_SCHEMA_VERSION.to_string(),
+            workspace: WorkspaceInfo {
+                id: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_millis().to_string())
+                    .unwrap_or_default(),
+                name: root
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "Hibiscus Workspace".into()),
+                root: root.to_string_lossy().to_string(),
+                created_at: None,
+                updated_at: None,
+            },
+            settings: Some(serde_json::json!({})),
+            tree: Vec::new(),
+            session: None,
+            decorations: Default::default(),
+            manual_order: Default::default(),
+            favorites: Vec::new(),
+        };
+        write_json_atomic(&workspace_path, &serde_json::to_value(&workspace)?)?;
+
+        let journal_path = hibiscus.join("adoption-journal.json");
+        write_json_atomic(&journal_path, &serde_json::to_value(&report.journal)?)?;
+        report.journal_path = Some(journal_path.to_string_lossy().to_string());
+    }
+
+    Ok(report)
+}
+
+/// Maps `Folder/README.md` to `Folder/Folder.md` for every README that has
+/// a folder and no existing note of that name.
+fn plan_folder_notes(
+    root: &Path,
+    notes: &[&String],
+    report: &mut AdoptionReport,
+) -> BTreeMap<String, String> {
+    let mut renames = BTreeMap::new();
+
+    for note in notes {
+        let (dir, name) = match note.rsplit_once('/') {
+            Some(split) => split,
+            None => continue, // the vault's own README has no folder note
+        };
+        if !name.eq_ignore_ascii_case("readme.md") {
+            continue;
+        }
+
+        let folder = dir.rsplit('/').next().unwrap_or(dir);
+        let target = format!("{}/{}.md", dir, folder);
+        if root.join(&target).exists() {
+            report.skipped.push(format!("{}: '{}' already exists", note, target));
+        } else {
+            renames.insert(note.to_string(), target);
+        }
+    }
+
+    renames
+}
+
+/// Resolves a link destination to a root-relative target (with any
+/// `#fragment`), or `None` for links that need no fixing.
+fn classify_link(root: &Path, note_dir: &str, dest: &str) -> Option<(String, LinkCategory)> {
+    if dest.contains("://") || dest.starts_with("mailto:") || dest.starts_with('#') {
+        return None;
+    }
+    let decoded = dest.replace("%20", " ");
+    let (path, fragment) = split_fragment(&decoded);
+
+    let as_path = Path::new(path);
+    if as_path.is_absolute() || path.starts_with('/') {
+        if let Ok(inside) = as_path.strip_prefix(root) {
+            let target = canonicalize_id(&inside.to_string_lossy());
+            return Some((format!("{}{}", target, fragment), LinkCategory::Absolute));
+        }
+        let category = if path.starts_with('/') { LinkCategory::SiteRoot } else { LinkCategory::Absolute };
+        return Some((format!("{}{}", canonicalize_id(path.trim_start_matches('/')), fragment), category));
+    }
+
+    // Relative links only matter when they point at a renamed README
+    let joined = normalize_relative(&format!("{}/{}", note_dir, path))?;
+    Some((format!("{}{}", joined, fragment), LinkCategory::FolderNote))
+}
+
+/// Splits `path#fragment` into `("path", "#fragment")`.
+fn split_fragment(target: &str) -> (&str, &str) {
+    match target.find('#') {
+        Some(at) => target.split_at(at),
+        None => (target, ""),
+    }
+}
+
+/// Resolves `.` and `..` segments; `None` if the path escapes the root.
+fn normalize_relative(path: &str) -> Option<String> {
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            _ => segments.push(segment),
+        }
+    }
+    Some(segments.join("/"))
+}
+
+fn parent_of(rel: &str) -> &str {
+    rel.rsplit_once('/').map_or("", |(dir, _)| dir)
+}
+
+fn collect_files(nodes: &[Node], out: &mut Vec<String>) {
+    for node in nodes {
+        if let Some(path) = &node.path {
+            out.push(path.replace('\\', "/"));
+        }
+        if let Some(children) = &node.children {
+            collect_files(children, out);
+        }
+    }
+}
+
+/// Writes `content` via temp file + rename.
+fn write_atomic(path: &Path, content: &str) -> Result<(), HibiscusError> {
+    let temp_path = path.with_extension("md.tmp");
+    std::fs::write(&temp_path, content)
+        .map_err(|e| HibiscusError::Io(format!("Failed to write '{}': {}", temp_path.display(), e)))?;
+
+    // On Windows, we need to remove existing file before rename
+    #[cfg(target_os = "windows")]
+    if path.exists() {
+        let _ = std::fs::remove_file(path);
+    }
+
+    std::fs::rename(&temp_path, path).map_err(|e| {
+        let _ = std::fs::remove_file(&temp_path);
+        HibiscusError::Io(format!("Failed to finalize '{}': {}", path.display(), e))
+    })
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_site_root_links_become_relative() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("images")).unwrap();
+        std::fs::create_dir_all(root.join("notes")).unwrap();
+        std::fs::write(root.join("images").join("cell diagram.png"), b"png").unwrap();
+        std::fs::write(root.join("images").join("logo.png"), b"png").unwrap();
+        let note = root.join("notes").join("cells.md");
+        let absolute = root.join("images").join("logo.png");
+        let original = format!(
+            "![d](/images/cell%20diagram.png)\n[abs]({})\n![gone](/images/missing.png)\n[rel](../images/cell%20diagram.png)\n",
+            absolute.display()
+        );
+        std::fs::write(&note, &original).unwrap();
+
+        // Dry run reports but changes nothing
+        let options = AdoptionOptions { dry_run: true, ..Default::default() };
+        let planned = adopt_folder_as_workspace(root.to_string_lossy().into(), Some(options))
+            .await
+            .unwrap();
+        assert_eq!(planned.counts.site_root_links, 1);
+        assert_eq!(planned.counts.absolute_links, 1);
+        assert_eq!(planned.counts.unresolved_links, 1);
+        assert_eq!(std::fs::read_to_string(&note).unwrap(), original);
+        assert!(!root.join(".hibiscus").exists());
+
+        let report = adopt_folder_as_workspace(root.to_string_lossy().into(), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&note).unwrap(),
+            "![d](../images/cell%20diagram.png)\n[abs](../images/logo.png)\n![gone](/images/missing.png)\n[rel](../images/cell%20diagram.png)\n"
+        );
+        assert_eq!(report.unresolved[0].target, "/images/missing.png");
+        assert_eq!(report.unresolved[0].category, LinkCategory::SiteRoot);
+        assert!(root.join(".hibiscus").join("workspace.json").is_file());
+        assert!(Path::new(report.journal_path.as_deref().unwrap()).is_file());
+
+        // A second adoption is refused
+        assert!(adopt_folder_as_workspace(root.to_string_lossy().into(), None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_readme_becomes_folder_note() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("Biology")).unwrap();
+        std::fs::write(root.join("README.md"), "See [bio](Biology/README.md#intro)\n").unwrap();
+        std::fs::write(root.join("Biology").join("README.md"), "# Biology\n").unwrap();
+
+        let options = AdoptionOptions { readme_to_folder_note: true, ..Default::default() };
+        let report = adopt_folder_as_workspace(root.to_string_lossy().into(), Some(options))
+            .await
+            .unwrap();
+
+        assert!(root.join("Biology").join("Biology.md").is_file());
+        assert!(!root.join("Biology").join("README.md").exists());
+        // The vault's own README has no folder to become the note of
+        assert!(root.join("README.md").is_file());
+        assert_eq!(
+            std::fs::read_to_string(root.join("README.md")).unwrap(),
+            "See [bio](Biology/Biology.md#intro)\n"
+        );
+        assert_eq!(report.counts.folder_notes, 1);
+        assert_eq!(report.counts.folder_note_links, 1);
+        assert!(report.journal.contains(&AdoptionAction::RenameReadme {
+            from: "Biology/README.md".into(),
+            to: "Biology/Biology.md".into(),
+        }));
+    }
+}
@@ -0,0 +1,220 @@
+// ============================================================================
+// FILE ATTRIBUTES
+// ============================================================================
+//
+// Platform file attributes that the dotfile convention doesn't cover. On
+// Windows a leading dot does not hide anything in Explorer, so internal
+// folders like `.hibiscus` need FILE_ATTRIBUTE_HIDDEN set explicitly.
+// Everywhere else these commands are no-ops.
+//
+// Saves replace a file with a freshly written temp file, which would reset
+// its attributes to the defaults. `copy_file_attributes` carries them over
+// first: the permission bits and (with enough privileges) the owner on
+// Unix, the hidden/system/archive/not-indexed flags on Windows. Alternate
+// data streams are not copied.
+// ============================================================================
+
+use std::path::{Path, PathBuf};
+
+use crate::error::HibiscusError;
+use super::path::validate_path;
+
+/// Sets or clears the hidden attribute on a file or directory.
+///
+/// # Arguments
+/// * `path` - File or directory to update
+/// * `hidden` - `true` to hide, `false` to unhide
+/// * `recursive` - Also apply to everything beneath a directory (default `false`)
+///
+/// # Returns
+/// * `Ok(())` - If the attribute was updated (always on non-Windows)
+/// * `Err(HibiscusError)` - If the path is invalid or the update failed
+///
+/// # Platform Notes
+/// Only Windows has a hidden attribute; on other platforms hidden means a
+/// leading dot, so this is a no-op there.
+#[tauri::command]
+pub async fn set_hidden_attribute(
+    path: String,
+    hidden: bool,
+    recursive: Option<bool>,
+) -> Result<(), HibiscusError> {
+    let path = PathBuf::from(&path);
+
+    // Validate path
+    validate_path(&path)?;
+
+    if !path.exists() {
+        return Err(HibiscusError::FileNotFound(path.to_string_lossy().into()));
+    }
+
+    let recursive = recursive.unwrap_or(false);
+    tokio::task::spawn_blocking(move || apply_hidden(&path, hidden, recursive))
+        .await
+        .map_err(|e| HibiscusError::Io(format!("Attribute task failed: {}", e)))?
+}
+
+/// Applies the hidden attribute to `path`, and its descendants if `recursive`.
+pub(crate) fn apply_hidden(path: &Path, hidden: bool, recursive: bool) -> Result<(), HibiscusError> {
+    set_hidden(path, hidden)?;
+
+    if recursive && path.is_dir() {
+        let entries = std::fs::read_dir(path).map_err(|e| {
+            HibiscusError::Io(format!("Failed to read directory '{}': {}", path.display(), e))
+        })?;
+        for entry in entries.flatten() {
+            apply_hidden(&entry.path(), hidden, true)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn set_hidden(path: &Path, hidden: bool) -> Result<(), HibiscusError> {
+    use std::os::windows::fs::MetadataExt;
+    use windows::core::HSTRING;
+    use windows::Win32::Storage::FileSystem::{
+        SetFileAttributesW, FILE_ATTRIBUTE_HIDDEN, FILE_FLAGS_AND_ATTRIBUTES,
+    };
+
+    // symlink_metadata so we flag a link itself, not its target
+    let current = std::fs::symlink_metadata(path)
+        .map_err(|e| {
+            HibiscusError::Io(format!("Failed to read attributes of '{}': {}", path.display(), e))
+        })?
+        .file_attributes();
+
+    let updated = if hidden {
+        current | FILE_ATTRIBUTE_HIDDEN.0
+    } else {
+        current & !FILE_ATTRIBUTE_HIDDEN.0
+    };
+    if updated == current {
+        return Ok(());
+    }
+
+    // SAFETY: the path is a valid, NUL-terminated wide string for the call.
+    unsafe { SetFileAttributesW(&HSTRING::from(path.as_os_str()), FILE_FLAGS_AND_ATTRIBUTES(updated)) }
+        .map_err(|e| {
+            HibiscusError::Io(format!("Failed to set attributes of '{}': {}", path.display(), e))
+        })
+}
+
+#[cfg(not(target_os = "windows"))]
+fn set_hidden(_path: &Path, _hidden: bool) -> Result<(), HibiscusError> {
+    Ok(())
+}
+
+/// Gives `to` the attributes of `from`, before `to` is renamed over it.
+///
+/// Best effort: a save must not fail because an attribute could not be
+/// copied, so problems are only logged.
+pub(crate) fn copy_file_attributes(from: &Path, to: &Path) {
+    let metadata = match std::fs::metadata(from) {
+        Ok(metadata) => metadata,
+        // A new file keeps the defaults (the process umask on Unix)
+        Err(_) => return,
+    };
+    if let Err(e) = copy_platform_attributes(&metadata, to) {
+        eprintln!(
+            "[Hibiscus] Warning: Could not keep the attributes of '{}': {}",
+            from.display(),
+            e
+        );
+    }
+}
+
+#[cfg(unix)]
+fn copy_platform_attributes(metadata: &std::fs::Metadata, to: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+    std::fs::set_permissions(to, std::fs::Permissions::from_mode(metadata.mode() & 0o7777))?;
+
+    // Changing the owner needs privileges; the group only membership
+    let current = std::fs::metadata(to)?;
+    let uid = (current.uid() != metadata.uid()).then_some(metadata.uid());
+    let gid = (current.gid() != metadata.gid()).then_some(metadata.gid());
+    if uid.is_some() || gid.is_some() {
+        std::os::unix::fs::chown(to, uid, gid)?;
+        // chown may clear setuid/setgid bits
+        std::fs::set_permissions(to, std::fs::Permissions::from_mode(metadata.mode() & 0o7777))?;
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn copy_platform_attributes(metadata: &std::fs::Metadata, to: &Path) -> std::io::Result<()> {
+    use std::os::windows::fs::MetadataExt;
+    use windows::core::HSTRING;
+    use windows::Win32::Storage::FileSystem::{
+        SetFileAttributesW, FILE_ATTRIBUTE_ARCHIVE, FILE_ATTRIBUTE_HIDDEN, FILE_ATTRIBUTE_NOT_CONTENT_INDEXED,
+        FILE_ATTRIBUTE_SYSTEM, FILE_FLAGS_AND_ATTRIBUTES,
+    };
+
+    let kept = FILE_ATTRIBUTE_HIDDEN.0
+        | FILE_ATTRIBUTE_SYSTEM.0
+        | FILE_ATTRIBUTE_ARCHIVE.0
+        | FILE_ATTRIBUTE_NOT_CONTENT_INDEXED.0;
+    let current = std::fs::metadata(to)?.file_attributes();
+    let updated = (current & !kept) | (metadata.file_attributes() & kept);
+    if updated == current {
+        return Ok(());
+    }
+
+    // SAFETY: the path is a valid, NUL-terminated wide string for the call.
+    unsafe { SetFileAttributesW(&HSTRING::from(to.as_os_str()), FILE_FLAGS_AND_ATTRIBUTES(updated)) }
+        .map_err(std::io::Error::other)
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+fn copy_platform_attributes(_metadata: &std::fs::Metadata, _to: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_missing_path_is_rejected() {
+        let dir = tempdir().unwrap();
+        let missing = dir.path().join("missing");
+        let result = set_hidden_attribute(missing.to_string_lossy().into(), true, None).await;
+        assert!(matches!(result, Err(HibiscusError::FileNotFound(_))));
+    }
+
+    #[cfg(target_os = "windows")]
+    #[tokio::test]
+    async fn test_toggle_hidden_attribute() {
+        use std::os::windows::fs::MetadataExt;
+        use windows::Win32::Storage::FileSystem::FILE_ATTRIBUTE_HIDDEN;
+
+        let is_hidden = |p: &Path| {
+            std::fs::metadata(p).unwrap().file_attributes() & FILE_ATTRIBUTE_HIDDEN.0 != 0
+        };
+
+        let dir = tempdir().unwrap();
+        let folder = dir.path().join(".hibiscus");
+        let file = folder.join("workspace.json");
+        std::fs::create_dir(&folder).unwrap();
+        std::fs::write(&file, "{}").unwrap();
+        let folder_str: String = folder.to_string_lossy().into();
+
+        set_hidden_attribute(folder_str.clone(), true, None).await.unwrap();
+        assert!(is_hidden(&folder));
+        assert!(!is_hidden(&file));
+
+        set_hidden_attribute(folder_str.clone(), true, Some(true)).await.unwrap();
+        assert!(is_hidden(&file));
+
+        set_hidden_attribute(folder_str, false, Some(true)).await.unwrap();
+        assert!(!is_hidden(&folder));
+        assert!(!is_hidden(&file));
+    }
+}
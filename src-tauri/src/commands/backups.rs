@@ -0,0 +1,192 @@
+// ============================================================================
+// BACKUP MANAGEMENT
+// ============================================================================
+//
+// Lets users see and cap what backup-on-save keeps in .hibiscus/backups.
+// The per-file limit in `crate::backup` bounds the count; these commands
+// bound the total size.
+//
+// `list_backups` and `restore_backup` serve the "restore previous version"
+// menu of a single file. Restoring backs up the current version first, so
+// a restore can itself be undone from the same menu.
+// ============================================================================
+
+use std::path::PathBuf;
+
+use crate::backup::{self, BackupEntry, FileBackups, PruneReport};
+use crate::error::HibiscusError;
+use crate::time::Timestamp;
+use crate::workspace::WorkspaceSettings;
+use super::files::{save_atomically, WrittenFile};
+use super::path::{find_workspace_root, scope_to_workspace, validate_path};
+
+/// Lists all backups in a workspace, grouped by original file.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+///
+/// # Returns
+/// * `Ok(Vec<FileBackups>)` - Backups per file, newest first (empty if none)
+/// * `Err(HibiscusError)` - If the backups folder cannot be read
+#[tauri::command]
+pub async fn list_all_backups(root: String) -> Result<Vec<FileBackups>, HibiscusError> {
+    let root = PathBuf::from(&root);
+
+    // Validate path
+    validate_path(&root)?;
+
+    backup::list_backups(&root).await
+}
+
+/// Deletes the oldest backups across all files until the total size fits
+/// the budget.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+/// * `max_total_bytes` - Size the remaining backups may take up
+///
+/// # Returns
+/// * `Ok(PruneReport)` - How many backups were removed and what is left
+/// * `Err(HibiscusError)` - If a backup could not be deleted
+#[tauri::command]
+pub async fn prune_backups(root: String, max_total_bytes: u64) -> Result<PruneReport, HibiscusError> {
+    let root = PathBuf::from(&root);
+
+    // Validate path
+    validate_path(&root)?;
+
+    backup::prune_to_budget(&root, max_total_bytes).await
+}
+
+/// Deletes every backup in a workspace.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+///
+/// # Returns
+/// * `Ok(usize)` - Number of backups deleted
+/// * `Err(HibiscusError)` - If the backups folder could not be removed
+#[tauri::command]
+pub async fn clear_backups(root: String) -> Result<usize, HibiscusError> {
+    let root = PathBuf::from(&root);
+
+    // Validate path
+    validate_path(&root)?;
+
+    backup::clear_all(&root).await
+}
+
+/// Lists the backups of one file, for a "restore previous version" menu.
+///
+/// # Arguments
+/// * `path` - Absolute path to the file
+///
+/// # Returns
+/// * `Ok(Vec<BackupEntry>)` - Its backups, newest first (empty if none)
+/// * `Err(HibiscusError)` - If the path is invalid or not in a workspace
+#[tauri::command]
+pub async fn list_backups(path: String) -> Result<Vec<BackupEntry>, HibiscusError> {
+    let (root, path) = workspace_file(&path)?;
+    backup::file_backups(&root, &path).await
+}
+
+/// Replaces a file with one of its backups.
+///
+/// # Arguments
+/// * `path` - Absolute path to the file
+/// * `timestamp` - `created_at` of the backup, as `list_backups` returned it
+///   (RFC3339 or milliseconds since the Unix epoch)
+///
+/// # Returns
+/// * `Ok(WrittenFile)` - The restored file's path, size and new mtime
+/// * `Err(HibiscusError)` - If there is no backup from that time, or the
+///   file cannot be written
+///
+/// # Notes
+/// The current version is backed up first (with the workspace's
+/// `backup_retention`), then replaced with the same atomic write as
+/// `write_text_file`.
+#[tauri::command]
+pub async fn restore_backup(path: String, timestamp: Timestamp) -> Result<WrittenFile, HibiscusError> {
+    let (root, path) = workspace_file(&path)?;
+
+    let backup = backup::file_backups(&root, &path)
+        .await?
+        .into_iter()
+        .find(|backup| backup.created_at.map(|at| at.as_millis()) == Some(timestamp.as_millis()))
+        .ok_or_else(|| {
+            HibiscusError::Io(format!("No backup of '{}' from {}", path.display(), timestamp))
+        })?;
+    let contents = tokio::fs::read(&backup.path)
+        .await
+        .map_err(|e| HibiscusError::Io(format!("Failed to read backup '{}': {}", backup.path, e)))?;
+
+    let keep = WorkspaceSettings::load(&root)
+        .backup_retention
+        .unwrap_or(backup::DEFAULT_FILE_BACKUPS);
+    backup::backup_file(&path, &root, keep).await?;
+
+    save_atomically(&path, &contents).await?;
+    WrittenFile::stat(&path).await
+}
+
+/// Validates an absolute file path and finds the workspace it belongs to.
+fn workspace_file(path: &str) -> Result<(PathBuf, PathBuf), HibiscusError> {
+    let path = PathBuf::from(path);
+    validate_path(&path)?;
+    let path = scope_to_workspace(&path)?;
+    let root = find_workspace_root(&path).ok_or_else(|| {
+        HibiscusError::Workspace(format!("'{}' is not inside a workspace", path.display()))
+    })?;
+    Ok((root, path))
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_saves_with_backups_can_be_restored() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join(".hibiscus")).unwrap();
+        fs::write(root.join(".hibiscus").join("workspace.json"), r#"{"settings":{"backup_retention":3}}"#).unwrap();
+        fs::create_dir_all(root.join("Notes")).unwrap();
+        let note = root.join("Notes").join("a.md");
+        let path = note.to_string_lossy().to_string();
+
+        for version in ["one", "two", "three"] {
+            super::super::write_text_file(path.clone(), version.into(), None, None, None, None, Some(true))
+                .await
+                .unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        let backups = list_backups(path.clone()).await.unwrap();
+        assert_eq!(backups.len(), 2);
+        let backups_dir = backup::backups_dir(root);
+        assert!(backups.iter().all(|backup| std::path::Path::new(&backup.path).starts_with(&backups_dir)));
+        assert_eq!(fs::read_to_string(&backups[0].path).unwrap(), "two");
+
+        let restored = restore_backup(path.clone(), backups[1].created_at.unwrap()).await.unwrap();
+        assert_eq!(restored.size, 3);
+        assert_eq!(fs::read_to_string(&note).unwrap(), "one");
+
+        // The overwritten version is a backup now; retention keeps three
+        let after: Vec<String> = list_backups(path.clone())
+            .await
+            .unwrap()
+            .iter()
+            .map(|backup| fs::read_to_string(&backup.path).unwrap())
+            .collect();
+        assert_eq!(after, ["three", "two", "one"]);
+
+        let missing = restore_backup(path, Timestamp::from_millis(1).unwrap()).await;
+        assert!(missing.is_err());
+    }
+}
@@ -0,0 +1,41 @@
+// ============================================================================
+// TREE BADGES
+// ============================================================================
+//
+// One call for all tree node badges (git status, open tasks, backlinks, sync
+// conflicts). After file changes the watcher pushes `badges-updated` with
+// the badges of the affected nodes only (see `crate::badges`).
+// ============================================================================
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::badges::{all_file_ids, Badge, BADGES};
+use crate::error::HibiscusError;
+use super::path::validate_path;
+
+/// Returns badges for tree nodes, merged from every available provider.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+/// * `node_ids` - Canonical ids to fetch (defaults to every file in the tree)
+///
+/// # Returns
+/// * `Ok(HashMap<String, Vec<Badge>>)` - Badges per node id; nodes without
+///   badges are absent
+/// * `Err(HibiscusError)` - If the root path is invalid
+#[tauri::command]
+pub async fn get_tree_badges(
+    root: String,
+    node_ids: Option<Vec<String>>,
+) -> Result<HashMap<String, Vec<Badge>>, HibiscusError> {
+    let root = PathBuf::from(&root);
+    validate_path(&root)?;
+
+    tokio::task::spawn_blocking(move || {
+        let ids = node_ids.unwrap_or_else(|| all_file_ids(&root));
+        BADGES.collect(&root, &ids)
+    })
+    .await
+    .map_err(|e| HibiscusError::Io(format!("Badge task failed: {}", e)))
+}
@@ -0,0 +1,96 @@
+// ============================================================================
+// BATCH OPERATIONS AND MACROS
+// ============================================================================
+//
+// Tauri entry points for `crate::batch` and `crate::macros`. Batches run on
+// the blocking pool under WORKSPACE_LOCK, since a step may rewrite
+// calendar.json or trigger reference reconciliation of workspace.json.
+// ============================================================================
+
+use chrono::Local;
+use std::path::PathBuf;
+
+use crate::batch::{self, BatchOp, BatchResult};
+use crate::error::HibiscusError;
+use crate::macros::{self, Macro, MacroContext};
+use super::path::validate_path;
+use super::workspace::WORKSPACE_LOCK;
+
+/// Runs a list of operations, rolling all of them back if one fails.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+/// * `ops` - Operations with workspace-relative paths
+///
+/// # Returns
+/// * `Ok(BatchResult)` - Every step applied
+/// * `Err(HibiscusError)` - Naming the failed step; nothing was changed
+#[tauri::command]
+pub async fn run_batch(root: String, ops: Vec<BatchOp>) -> Result<BatchResult, HibiscusError> {
+    let root = PathBuf::from(&root);
+
+    // Validate path
+    validate_path(&root)?;
+
+    let _guard = WORKSPACE_LOCK.lock().await;
+    tokio::task::spawn_blocking(move || batch::run_batch(&root, &ops))
+        .await
+        .map_err(|e| HibiscusError::Io(format!("Batch task failed: {}", e)))?
+}
+
+/// Lists the macros defined in workspace settings.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+///
+/// # Returns
+/// * `Ok(Vec<Macro>)` - Macros in settings order
+/// * `Err(HibiscusError)` - If workspace.json is unreadable or a macro is invalid
+#[tauri::command]
+pub async fn list_macros(root: String) -> Result<Vec<Macro>, HibiscusError> {
+    let root = PathBuf::from(&root);
+
+    // Validate path
+    validate_path(&root)?;
+
+    tokio::task::spawn_blocking(move || macros::load(&root))
+        .await
+        .map_err(|e| HibiscusError::Io(format!("Macro task failed: {}", e)))?
+}
+
+/// Runs a macro by name as one batch.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+/// * `name` - Macro name
+/// * `context` - Active note and answers to the macro's prompted variables
+///
+/// # Returns
+/// * `Ok(BatchResult)` - Every step applied
+/// * `Err(HibiscusError)` - If the macro is unknown, a variable has no value,
+///   or a step failed (earlier steps are rolled back)
+#[tauri::command]
+pub async fn run_macro(
+    root: String,
+    name: String,
+    context: Option<MacroContext>,
+) -> Result<BatchResult, HibiscusError> {
+    let root = PathBuf::from(&root);
+
+    // Validate path
+    validate_path(&root)?;
+
+    let _guard = WORKSPACE_LOCK.lock().await;
+    tokio::task::spawn_blocking(move || {
+        let macros = macros::load(&root)?;
+        let chosen = macros
+            .iter()
+            .find(|m| m.name == name)
+            .ok_or_else(|| HibiscusError::Workspace(format!("Unknown macro '{}'", name)))?;
+
+        let steps = chosen.expand(&context.unwrap_or_default(), Local::now().date_naive())?;
+        batch::run_batch(&root, &steps)
+    })
+    .await
+    .map_err(|e| HibiscusError::Io(format!("Macro task failed: {}", e)))?
+}
@@ -0,0 +1,275 @@
+// ============================================================================
+// WORKSPACE BOOTSTRAP
+// ============================================================================
+//
+// Single startup entry point. Opening a workspace used to take four frontend
+// calls (discover/load, build tree, start watcher, check status) that could
+// interleave with each other; `bootstrap_workspace` runs them in a fixed
+// order and returns one snapshot.
+//
+// Opened roots are also recorded in `recent_workspaces.json` in the app
+// config directory, most recent first.
+// ============================================================================
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{Manager, State};
+use tokio::fs;
+
+use crate::error::HibiscusError;
+use crate::knowledge::queue::KnowledgeState;
+use crate::limits::LimitHit;
+use crate::watcher::{self, WatcherState};
+use crate::workspace::{Node, WorkspaceFile};
+use super::tree::read_tree_report;
+use super::workspace::{discover_workspace, load_workspace};
+
+/// Number of roots kept in `recent_workspaces.json`.
+const MAX_RECENT_WORKSPACES: usize = 10;
+
+/// Everything the frontend needs to show a workspace.
+#[derive(Debug, Serialize)]
+pub struct WorkspaceBootstrap {
+    /// Root after `~` / environment-variable expansion
+    pub root: String,
+    /// Where workspace.json lives (or will be created) for this root
+    pub workspace_path: String,
+    /// The loaded workspace.json, `None` for a folder opened the first time
+    pub workspace: Option<WorkspaceFile>,
+    /// Freshly built tree with decorations applied
+    pub tree: Vec<Node>,
+    /// Folders the depth limit left out of `tree`
+    pub limits_hit: Vec<LimitHit>,
+    /// Whether the file watcher is running after bootstrap
+    pub watching: bool,
+    /// Path the watcher is attached to
+    pub watched_path: Option<String>,
+}
+
+/// Opens a workspace in one step: discovers and loads workspace.json, builds
+/// the tree, records the root as recent and optionally starts the watcher.
+///
+/// # Arguments
+/// * `root` - Workspace root, as typed or picked by the user
+/// * `watch` - Start the file watcher on the root (default true)
+///
+/// # Returns
+/// * `Ok(WorkspaceBootstrap)` - Snapshot of the opened workspace
+/// * `Err(HibiscusError)` - If the root is invalid or workspace.json cannot
+///   be loaded
+///
+/// # Notes
+/// Failing to record the recent workspace is logged, not returned.
+#[tauri::command]
+pub async fn bootstrap_workspace(
+    root: String,
+    watch: Option<bool>,
+    app: tauri::AppHandle,
+    window: tauri::Window,
+    watcher_state: State<'_, WatcherState>,
+    knowledge_state: State<'_, Arc<KnowledgeState>>,
+) -> Result<WorkspaceBootstrap, HibiscusError> {
+    let recent_file = app
+        .path()
+        .app_config_dir()
+        .ok()
+        .map(|dir| dir.join("recent_workspaces.json"));
+
+    let mut snapshot = bootstrap(root, recent_file.as_deref()).await?;
+
+    // An unwatchable storage only leaves `watching` false
+    if watch.unwrap_or(true) {
+        if let Err(e) = watcher::watch_workspace(
+            snapshot.root.clone(),
+            None,
+            None,
+            window,
+            watcher_state.clone(),
+            knowledge_state,
+        ) {
+            eprintln!("[Hibiscus] Warning: {}", e);
+        }
+    }
+    snapshot.watching = watcher::is_watching(watcher_state.clone());
+    snapshot.watched_path = watcher::get_watched_path(watcher_state);
+
+    Ok(snapshot)
+}
+
+/// Returns recently opened workspace roots, most recent first.
+///
+/// # Returns
+/// * `Ok(Vec<String>)` - Recent roots (empty if none were recorded)
+/// * `Err(HibiscusError)` - If the app config directory is unavailable
+#[tauri::command]
+pub async fn get_recent_workspaces(app: tauri::AppHandle) -> Result<Vec<String>, HibiscusError> {
+    let dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| HibiscusError::Io(format!("App config directory unavailable: {}", e)))?;
+
+    Ok(read_recent_workspaces(&dir.join("recent_workspaces.json")).await)
+}
+
+/// Everything in `bootstrap_workspace` except the watcher, which needs a
+/// live window.
+async fn bootstrap(
+    root: String,
+    recent_file: Option<&Path>,
+) -> Result<WorkspaceBootstrap, HibiscusError> {
+    let discovery = discover_workspace(root, None).await?;
+    if discovery.valid == Some(false) {
+        return Err(HibiscusError::Workspace(
+            discovery
+                .problem
+                .unwrap_or_else(|| "workspace.json cannot be loaded".into()),
+        ));
+    }
+
+    // Finish or revert multi-file saves a crash interrupted
+    let recover_root = PathBuf::from(&discovery.root);
+    let problems = tokio::task::spawn_blocking(move || crate::save_group::recover(&recover_root))
+        .await
+        .unwrap_or_default();
+    for problem in problems {
+        eprintln!("[Hibiscus] Warning: {}", problem);
+    }
+
+    let workspace = match discovery.path {
+        Some(path) => Some(load_workspace(path, None).await?),
+        None => None,
+    };
+
+    let tree_root = discovery.root.clone();
+    let tree = tokio::task::spawn_blocking(move || read_tree_report(tree_root, None, None, None))
+        .await
+        .map_err(|e| HibiscusError::Io(format!("Tree build task failed: {}", e)))??;
+
+    if let Some(file) = recent_file {
+        if let Err(e) = record_recent_workspace(file, &discovery.root).await {
+            eprintln!("[Hibiscus] Warning: Failed to record recent workspace: {}", e);
+        }
+    }
+
+    let workspace_path = PathBuf::from(&discovery.root)
+        .join(".hibiscus")
+        .join("workspace.json");
+
+    Ok(WorkspaceBootstrap {
+        root: discovery.root,
+        workspace_path: workspace_path.to_string_lossy().to_string(),
+        workspace,
+        tree: tree.nodes,
+        limits_hit: tree.limits_hit,
+        watching: false,
+        watched_path: None,
+    })
+}
+
+async fn read_recent_workspaces(file: &Path) -> Vec<String> {
+    match fs::read(file).await {
+        Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_default(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Moves `root` to the front of the recent list, dropping the oldest entry
+/// beyond `MAX_RECENT_WORKSPACES`.
+async fn record_recent_workspace(file: &Path, root: &str) -> Result<(), HibiscusError> {
+    let mut recent = read_recent_workspaces(file).await;
+    recent.retain(|entry| entry != root);
+    recent.insert(0, root.to_string());
+    recent.truncate(MAX_RECENT_WORKSPACES);
+
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+
+    // Atomic write: temp file, then rename
+    let temp_path = file.with_extension("json.tmp");
+    fs::write(&temp_path, serde_json::to_vec_pretty(&recent)?).await?;
+
+    // On Windows, we need to remove existing file before rename
+    #[cfg(target_os = "windows")]
+    if file.exists() {
+        fs::remove_file(file).await?;
+    }
+
+    fs::rename(&temp_path, file).await?;
+    Ok(())
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_bootstrap_populates_snapshot_for_real_vault() {
+        let dir = tempdir().unwrap();
+        let vault = dir.path().join("vault");
+        std::fs::create_dir_all(vault.join(".hibiscus")).unwrap();
+        std::fs::create_dir_all(vault.join("Biology")).unwrap();
+        std::fs::write(vault.join("Biology").join("Week1.md"), "# Cells").unwrap();
+        std::fs::write(
+            vault.join(".hibiscus").join("workspace.json"),
+            serde_json::json!({
+                "schema_version": crate::migration::WORKSPACE_SCHEMA_VERSION,
+                "workspace": { "id": "1", "name": "Vault", "root": vault.to_string_lossy() },
+                "tree": [],
+                "session": { "open_nodes": ["Biology/Week1.md"] }
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let recent_file = dir.path().join("config").join("recent_workspaces.json");
+        let snapshot = bootstrap(vault.to_string_lossy().into(), Some(&recent_file))
+            .await
+            .unwrap();
+
+        assert_eq!(snapshot.root, vault.to_string_lossy());
+        assert!(Path::new(&snapshot.workspace_path).is_file());
+        let workspace = snapshot.workspace.expect("workspace.json should be loaded");
+        assert_eq!(workspace.workspace.name, "Vault");
+        assert_eq!(snapshot.tree.len(), 1);
+        assert_eq!(snapshot.tree[0].children.as_ref().map(Vec::len), Some(1));
+        assert_eq!(
+            read_recent_workspaces(&recent_file).await,
+            vec![vault.to_string_lossy().to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_recent_workspaces_dedupes_and_caps() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("recent_workspaces.json");
+
+        for i in 0..12 {
+            record_recent_workspace(&file, &format!("/vault{}", i)).await.unwrap();
+        }
+        record_recent_workspace(&file, "/vault5").await.unwrap();
+
+        let recent = read_recent_workspaces(&file).await;
+        assert_eq!(recent.len(), MAX_RECENT_WORKSPACES);
+        assert_eq!(recent[0], "/vault5");
+        assert_eq!(recent[1], "/vault11");
+        assert_eq!(recent.iter().filter(|r| *r == "/vault5").count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_bootstrap_new_folder_has_no_workspace() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("a.md"), "").unwrap();
+
+        let snapshot = bootstrap(dir.path().to_string_lossy().into(), None).await.unwrap();
+        assert!(snapshot.workspace.is_none());
+        assert_eq!(snapshot.tree.len(), 1);
+        assert!(snapshot.workspace_path.ends_with("workspace.json"));
+    }
+}
@@ -0,0 +1,56 @@
+// ============================================================================
+// CAPABILITY TOKENS
+// ============================================================================
+//
+// Native confirmation dialog in front of destructive commands. The frontend
+// calls `request_capability`, the user confirms, and the returned token is
+// passed as `capability` to the gated command (see `crate::capabilities`).
+// ============================================================================
+
+use tauri::Manager;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::capabilities::{self, CapabilityStore, ConfirmPrompt};
+use crate::error::HibiscusError;
+
+/// Shows the confirmation through the dialog plugin.
+struct DialogPrompt<'a>(&'a tauri::AppHandle);
+
+impl ConfirmPrompt for DialogPrompt<'_> {
+    fn confirm(&self, title: &str, message: &str) -> bool {
+        self.0
+            .dialog()
+            .message(message)
+            .title(title)
+            .kind(MessageDialogKind::Warning)
+            .buttons(MessageDialogButtons::OkCancelCustom(
+                "Continue".into(),
+                "Cancel".into(),
+            ))
+            .blocking_show()
+    }
+}
+
+/// Asks the user to confirm a destructive action and returns a single-use
+/// token for it.
+///
+/// # Arguments
+/// * `kind` - Capability kind, e.g. "permanent_delete"
+///
+/// # Returns
+/// * `Ok(String)` - Token to pass to the gated command within a minute
+/// * `Err(HibiscusError::CapabilityRequired)` - If the user declined
+/// * `Err(HibiscusError)` - If the kind is unknown
+#[tauri::command]
+pub async fn request_capability(
+    kind: String,
+    app: tauri::AppHandle,
+) -> Result<String, HibiscusError> {
+    // The dialog blocks until answered, so keep it off the async runtime
+    tokio::task::spawn_blocking(move || {
+        let store = app.state::<CapabilityStore>();
+        capabilities::request(&store, &DialogPrompt(&app), &kind)
+    })
+    .await
+    .map_err(|e| HibiscusError::Io(format!("Capability prompt task failed: {}", e)))?
+}
@@ -19,9 +19,11 @@ use std::collections::HashSet;
 use std::path::PathBuf;
 use std::sync::Mutex;
 
+use tauri::State;
 use tokio::fs;
 
 use crate::error::HibiscusError;
+use crate::idempotency::IdempotencyStore;
 use super::path::validate_path;
 
 /// Global set of paths currently being created.
@@ -35,6 +37,7 @@ static INFLIGHT_PATHS: std::sync::LazyLock<Mutex<HashSet<PathBuf>>> =
 /// # Arguments
 /// * `path`   - Absolute path to the item to create.
 /// * `is_dir` - If true, creates a directory. If false, creates an empty file.
+/// * `idempotency_key` - Optional replay protection key.
 ///
 /// # Behavior
 /// - Validates the path against traversal attacks and depth limits.
@@ -42,12 +45,27 @@ static INFLIGHT_PATHS: std::sync::LazyLock<Mutex<HashSet<PathBuf>>> =
 /// - Returns an error if the item already exists on disk.
 /// - Uses per-path locking to reject duplicate concurrent requests.
 ///
+/// A repeated `idempotency_key` returns the first result, so a webview
+/// that reloads and re-sends the request does not get "already exists".
+///
 /// # Watcher Integration
 /// After creation, the filesystem watcher will detect the change and
 /// forward it to the knowledge pipeline. This command does NOT manually
 /// trigger indexing -- the watcher is the sole trigger.
 #[tauri::command]
-pub async fn create_item(path: String, is_dir: bool) -> Result<(), HibiscusError> {
+pub async fn create_item(
+    path: String,
+    is_dir: bool,
+    idempotency_key: Option<String>,
+    idempotency: State<'_, IdempotencyStore>,
+) -> Result<(), HibiscusError> {
+    idempotency
+        .run("create_item", idempotency_key.as_deref(), || create_item_locked(path, is_dir))
+        .await
+}
+
+/// Creation under the per-path lock.
+async fn create_item_locked(path: String, is_dir: bool) -> Result<(), HibiscusError> {
     let path = PathBuf::from(&path);
 
     // Validate path safety (traversal, depth).
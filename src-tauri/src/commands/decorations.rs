@@ -0,0 +1,260 @@
+// ============================================================================
+// NODE DECORATIONS
+// ============================================================================
+//
+// Per-node icon and color overrides, stored in the vault's workspace.json
+// under `decorations` (keyed by node id) rather than in browser storage.
+// `build_tree` merges them onto node meta; the reference reconciliation
+// pass remaps or prunes them when nodes are renamed or deleted.
+// ============================================================================
+
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use crate::error::HibiscusError;
+use crate::ids::canonicalize_id;
+use crate::references::write_json_atomic;
+use crate::workspace::NodeDecoration;
+use super::path::validate_path;
+use super::workspace::WORKSPACE_LOCK;
+
+/// Maximum length of an icon identifier.
+const MAX_ICON_LEN: usize = 64;
+
+/// Sets the icon and/or color of a node.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+/// * `node_id` - Id of the node to decorate
+/// * `decoration` - Icon and color; an empty decoration clears the node's
+///
+/// # Returns
+/// * `Ok(())` - If the decoration was stored
+/// * `Err(HibiscusError)` - If validation fails or workspace.json is missing
+///
+/// # Validation
+/// - `color` must be a hex color: `#rgb`, `#rrggbb` or `#rrggbbaa`
+/// - `icon` must be 1-64 characters of `[a-z0-9-_:]`
+#[tauri::command]
+pub async fn set_node_decoration(
+    root: String,
+    node_id: String,
+    decoration: NodeDecoration,
+) -> Result<(), HibiscusError> {
+    let root = PathBuf::from(&root);
+
+    // Validate path
+    validate_path(&root)?;
+    validate_decoration(&decoration)?;
+
+    let value = if decoration == NodeDecoration::default() {
+        None
+    } else {
+        Some(serde_json::to_value(decoration)?)
+    };
+
+    update_decoration(root, node_id, value).await
+}
+
+/// Removes any icon/color override from a node.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+/// * `node_id` - Id of the node to clear
+///
+/// # Returns
+/// * `Ok(())` - If the decoration was removed (or there was none)
+/// * `Err(HibiscusError)` - If workspace.json is missing or unwritable
+#[tauri::command]
+pub async fn clear_node_decoration(root: String, node_id: String) -> Result<(), HibiscusError> {
+    let root = PathBuf::from(&root);
+
+    // Validate path
+    validate_path(&root)?;
+
+    update_decoration(root, node_id, None).await
+}
+
+/// Reads the `decorations` map of a workspace, if it has one.
+///
+/// Missing or malformed files yield an empty map so tree building never
+/// fails because of decorations.
+pub(crate) fn load_decorations(
+    root: &Path,
+) -> std::collections::BTreeMap<String, NodeDecoration> {
+    std::fs::read_to_string(root.join(".hibiscus").join("workspace.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .and_then(|mut doc| doc.get_mut("decorations").map(Value::take))
+        .and_then(|decorations| serde_json::from_value(decorations).ok())
+        .unwrap_or_default()
+}
+
+/// Inserts (`Some`) or removes (`None`) one entry under the workspace lock.
+async fn update_decoration(
+    root: PathBuf,
+    node_id: String,
+    decoration: Option<Value>,
+) -> Result<(), HibiscusError> {
+    let node_id = canonicalize_id(&node_id);
+    let path = root.join(".hibiscus").join("workspace.json");
+    if !path.is_file() {
+        return Err(HibiscusError::FileNotFound(
+            "workspace.json not found".into(),
+        ));
+    }
+
+    let _guard = WORKSPACE_LOCK.lock().await;
+
+    tokio::task::spawn_blocking(move || {
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| HibiscusError::Io(format!("Failed to read workspace.json: {}", e)))?;
+        let mut doc: Value = serde_json::from_str(&content)
+            .map_err(|e| HibiscusError::Workspace(format!("Invalid workspace JSON: {}", e)))?;
+
+        let Some(workspace) = doc.as_object_mut() else {
+            return Err(HibiscusError::Workspace(
+                "workspace.json is not an object".into(),
+            ));
+        };
+
+        let decorations = workspace
+            .entry("decorations")
+            .or_insert_with(|| Value::Object(Default::default()));
+        if !decorations.is_object() {
+            *decorations = Value::Object(Default::default());
+        }
+        let map = decorations.as_object_mut().expect("decorations is an object");
+
+        let changed = match decoration {
+            Some(value) => map.insert(node_id, value.clone()) != Some(value),
+            None => map.remove(&node_id).is_some(),
+        };
+
+        if map.is_empty() {
+            workspace.remove("decorations");
+        }
+
+        if changed {
+            write_json_atomic(&path, &doc)?;
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| HibiscusError::Workspace(format!("Decoration task failed: {}", e)))?
+}
+
+fn validate_decoration(decoration: &NodeDecoration) -> Result<(), HibiscusError> {
+    if let Some(color) = &decoration.color {
+        if !is_hex_color(color) {
+            return Err(HibiscusError::Workspace(format!(
+                "Invalid color '{}': expected #rgb, #rrggbb or #rrggbbaa",
+                color
+            )));
+        }
+    }
+
+    if let Some(icon) = &decoration.icon {
+        if !is_safe_icon(icon) {
+            return Err(HibiscusError::Workspace(format!(
+                "Invalid icon '{}': expected 1-{} characters of a-z, 0-9, '-', '_' or ':'",
+                icon, MAX_ICON_LEN
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+fn is_hex_color(color: &str) -> bool {
+    color.strip_prefix('#').is_some_and(|hex| {
+        matches!(hex.len(), 3 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+fn is_safe_icon(icon: &str) -> bool {
+    !icon.is_empty()
+        && icon.len() <= MAX_ICON_LEN
+        && icon
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_' | ':'))
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::{tempdir, TempDir};
+
+    fn workspace() -> TempDir {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".hibiscus")).unwrap();
+        let doc = serde_json::json!({
+            "schema_version": "1.0",
+            "workspace": { "id": "w", "name": "w", "root": "." },
+            "settings": { "theme": "dark" },
+            "tree": [],
+        });
+        fs::write(dir.path().join(".hibiscus").join("workspace.json"), doc.to_string()).unwrap();
+        dir
+    }
+
+    fn root(dir: &TempDir) -> String {
+        dir.path().to_string_lossy().into()
+    }
+
+    #[tokio::test]
+    async fn test_set_and_clear_roundtrip() {
+        let dir = workspace();
+        let decoration = NodeDecoration {
+            icon: Some("book-open".into()),
+            color: Some("#3366ff".into()),
+        };
+
+        set_node_decoration(root(&dir), "courses/math".into(), decoration.clone())
+            .await
+            .unwrap();
+
+        let loaded = load_decorations(dir.path());
+        assert_eq!(loaded.get("courses/math"), Some(&decoration));
+
+        // Unrelated keys survive the read-modify-write
+        let raw = fs::read_to_string(dir.path().join(".hibiscus").join("workspace.json")).unwrap();
+        let doc: Value = serde_json::from_str(&raw).unwrap();
+        assert_eq!(doc["settings"]["theme"], "dark");
+
+        clear_node_decoration(root(&dir), "courses/math".into())
+            .await
+            .unwrap();
+        assert!(load_decorations(dir.path()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_invalid_values() {
+        let dir = workspace();
+
+        let bad_color = NodeDecoration { icon: None, color: Some("red; x".into()) };
+        assert!(set_node_decoration(root(&dir), "a".into(), bad_color).await.is_err());
+
+        let bad_icon = NodeDecoration { icon: Some("<script>".into()), color: None };
+        assert!(set_node_decoration(root(&dir), "a".into(), bad_icon).await.is_err());
+
+        assert!(load_decorations(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_validation_patterns() {
+        assert!(is_hex_color("#abc"));
+        assert!(is_hex_color("#A0B1C2"));
+        assert!(is_hex_color("#a0b1c2ff"));
+        assert!(!is_hex_color("a0b1c2"));
+        assert!(!is_hex_color("#abcd"));
+        assert!(is_safe_icon("lucide:folder-open"));
+        assert!(!is_safe_icon(""));
+        assert!(!is_safe_icon("Folder"));
+    }
+}
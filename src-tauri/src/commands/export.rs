@@ -0,0 +1,484 @@
+// ============================================================================
+// CORPUS EXPORT
+// ============================================================================
+//
+// Exports the whole vault as a normalized plain-text corpus for external
+// tooling (grep scripts, embedding pipelines).
+//
+// OUTPUT FORMATS:
+// - jsonl:  a single file, one JSON record per note
+// - folder: a mirrored directory tree of .txt files
+//
+// DESIGN:
+// - Notes are streamed one at a time; nothing vault-sized is held in memory
+//   apart from the list of candidate paths.
+// - Markdown is rendered through `crate::markdown::to_plain_text` so link
+//   text survives and code stays greppable.
+// - Attachments and filtered-out notes are listed in the report rather than
+//   silently dropped.
+// - Progress is emitted as `export-progress` events while the export runs.
+// ============================================================================
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tauri::Emitter;
+
+use crate::error::HibiscusError;
+use crate::markdown::{self, PlainTextOptions};
+use crate::limits::{LimitHit, Limits};
+use crate::tree::read_dir_limited;
+use crate::workspace::Node;
+use super::path::validate_path;
+
+/// Emit a progress event every this many processed files.
+const PROGRESS_INTERVAL: usize = 25;
+
+/// Output layout for `export_corpus`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Single JSONL file at `dest_path`.
+    #[default]
+    Jsonl,
+    /// Directory of `.txt` files mirroring the vault layout under `dest_path`.
+    Folder,
+}
+
+/// Filters and formatting switches for `export_corpus`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    /// Only export notes carrying at least one of these tags (if non-empty).
+    pub include_tags: Vec<String>,
+    /// Skip notes carrying any of these tags.
+    pub exclude_tags: Vec<String>,
+    /// Only export notes under one of these workspace-relative folders (if non-empty).
+    pub include_folders: Vec<String>,
+    /// Skip notes under any of these workspace-relative folders.
+    pub exclude_folders: Vec<String>,
+    /// Note extensions to export. Everything else is treated as an attachment.
+    pub extensions: Vec<String>,
+    /// Embed frontmatter fields (JSONL `metadata` / text header).
+    pub embed_frontmatter: bool,
+    /// Append link URLs in parentheses after the link text.
+    pub append_urls: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            format: ExportFormat::Jsonl,
+            include_tags: Vec::new(),
+            exclude_tags: Vec::new(),
+            include_folders: Vec::new(),
+            exclude_folders: Vec::new(),
+            extensions: vec!["md".into(), "markdown".into(), "txt".into()],
+            embed_frontmatter: false,
+            append_urls: false,
+        }
+    }
+}
+
+/// One exported note. Serialized as a single JSONL line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorpusRecord {
+    /// Workspace-relative path with forward slashes.
+    pub path: String,
+    pub title: String,
+    pub tags: Vec<String>,
+    /// Plain-text body with markdown syntax stripped.
+    pub body: String,
+    pub word_count: usize,
+    /// Last modification time in seconds since the Unix epoch.
+    pub mtime: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<BTreeMap<String, String>>,
+}
+
+/// A file that was not exported, and why.
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedFile {
+    pub path: String,
+    pub reason: String,
+}
+
+/// Summary returned once the export finishes.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExportReport {
+    pub exported: usize,
+    pub skipped: Vec<SkippedFile>,
+    /// Folders the depth limit left out of the export
+    pub limits_hit: Vec<LimitHit>,
+}
+
+/// Payload of the `export-progress` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportProgress {
+    pub processed: usize,
+    pub total: usize,
+}
+
+/// Exports every note in the workspace as a plain-text corpus.
+///
+/// # Arguments
+/// * `root` - Workspace root directory path
+/// * `dest_path` - Output JSONL file, or output directory for `folder` format
+/// * `options` - Filters and formatting (defaults: JSONL, md/markdown/txt)
+///
+/// # Events Emitted
+/// * `export-progress` - `{ processed, total }` while the export runs
+///
+/// # Returns
+/// * `Ok(ExportReport)` - Number of exported notes and skipped files
+/// * `Err(HibiscusError)` - If the destination cannot be written
+#[tauri::command]
+pub async fn export_corpus(
+    root: String,
+    dest_path: String,
+    options: Option<ExportOptions>,
+    window: tauri::Window,
+) -> Result<ExportReport, HibiscusError> {
+    let root = PathBuf::from(&root);
+    let dest = PathBuf::from(&dest_path);
+
+    // Validate paths
+    validate_path(&root)?;
+    validate_path(&dest)?;
+
+    if !root.is_dir() {
+        return Err(HibiscusError::InvalidPathType {
+            path: root.to_string_lossy().into(),
+            expected: "directory".into(),
+            actual: "file".into(),
+        });
+    }
+
+    let options = options.unwrap_or_default();
+
+    tokio::task::spawn_blocking(move || {
+        export_corpus_blocking(&root, &dest, &options, |progress| {
+            let _ = window.emit("export-progress", progress);
+        })
+    })
+    .await
+    .map_err(|e| HibiscusError::Io(format!("Export task failed: {}", e)))?
+}
+
+/// Blocking implementation of the corpus export.
+///
+/// `on_progress` is called periodically and once at the end.
+pub fn export_corpus_blocking(
+    root: &Path,
+    dest: &Path,
+    options: &ExportOptions,
+    mut on_progress: impl FnMut(ExportProgress),
+) -> Result<ExportReport, HibiscusError> {
+    let (tree, limits_hit) = read_dir_limited(root, root, Limits::load(root).tree_depth, false);
+    let mut candidates = Vec::new();
+    collect_files(&tree, &mut candidates);
+
+    // Never re-export a previous export that lives inside the vault.
+    let dest_rel = dest
+        .strip_prefix(root)
+        .ok()
+        .map(|p| p.to_string_lossy().replace('\\', "/"));
+    if let Some(dest_rel) = &dest_rel {
+        candidates.retain(|p| !within(p, dest_rel));
+    }
+
+    let mut sink = CorpusSink::open(dest, options.format)?;
+    let mut report = ExportReport {
+        limits_hit,
+        ..Default::default()
+    };
+    let total = candidates.len();
+
+    for (processed, rel) in candidates.iter().enumerate() {
+        if processed % PROGRESS_INTERVAL == 0 {
+            on_progress(ExportProgress { processed, total });
+        }
+
+        match build_record(root, rel, options) {
+            Ok(record) => {
+                sink.write(&record)?;
+                report.exported += 1;
+            }
+            Err(reason) => report.skipped.push(SkippedFile {
+                path: rel.clone(),
+                reason,
+            }),
+        }
+    }
+
+    sink.finish()?;
+    on_progress(ExportProgress {
+        processed: total,
+        total,
+    });
+
+    Ok(report)
+}
+
+/// Flattens the tree into workspace-relative file paths (forward slashes).
+fn collect_files(nodes: &[Node], out: &mut Vec<String>) {
+    for node in nodes {
+        if let Some(path) = &node.path {
+            out.push(path.replace('\\', "/"));
+        }
+        if let Some(children) = &node.children {
+            collect_files(children, out);
+        }
+    }
+}
+
+fn within(path: &str, folder: &str) -> bool {
+    let folder = folder.trim_matches('/');
+    path == folder || path.starts_with(&format!("{}/", folder))
+}
+
+/// Reads and converts a single note, or returns the reason it was skipped.
+fn build_record(root: &Path, rel: &str, options: &ExportOptions) -> Result<CorpusRecord, String> {
+    let ext = Path::new(rel)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    if !options.extensions.iter().any(|e| e.eq_ignore_ascii_case(&ext)) {
+        return Err("attachment".into());
+    }
+
+    if !options.include_folders.is_empty()
+        && !options.include_folders.iter().any(|f| within(rel, f))
+    {
+        return Err("folder not included".into());
+    }
+    if options.exclude_folders.iter().any(|f| within(rel, f)) {
+        return Err("folder excluded".into());
+    }
+
+    let abs = root.join(rel);
+    let content = fs::read_to_string(&abs).map_err(|e| format!("unreadable: {}", e))?;
+    let mtime = fs::metadata(&abs)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let (frontmatter, body) = markdown::split_frontmatter(&content);
+    let frontmatter = frontmatter.unwrap_or_default();
+
+    let has_tag = |wanted: &[String]| {
+        frontmatter
+            .tags
+            .iter()
+            .any(|t| wanted.iter().any(|w| w.eq_ignore_ascii_case(t)))
+    };
+    if !options.include_tags.is_empty() && !has_tag(&options.include_tags) {
+        return Err("tag not included".into());
+    }
+    if has_tag(&options.exclude_tags) {
+        return Err("tag excluded".into());
+    }
+
+    let is_markdown = markdown::is_markdown_extension(&ext);
+    let text = if is_markdown {
+        markdown::to_plain_text(
+            body,
+            PlainTextOptions {
+                append_urls: options.append_urls,
+            },
+        )
+    } else {
+        body.trim_end().to_string()
+    };
+
+    let title = frontmatter
+        .fields
+        .get("title")
+        .cloned()
+        .or_else(|| is_markdown.then(|| markdown::first_heading(body)).flatten())
+        .unwrap_or_else(|| {
+            Path::new(rel)
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default()
+        });
+
+    Ok(CorpusRecord {
+        path: rel.to_string(),
+        title,
+        tags: frontmatter.tags,
+        word_count: markdown::word_count(&text),
+        body: text,
+        mtime,
+        metadata: options.embed_frontmatter.then_some(frontmatter.fields),
+    })
+}
+
+/// Streaming writer for either output format.
+enum CorpusSink {
+    Jsonl(BufWriter<fs::File>),
+    Folder(PathBuf),
+}
+
+impl CorpusSink {
+    fn open(dest: &Path, format: ExportFormat) -> Result<Self, HibiscusError> {
+        match format {
+            ExportFormat::Jsonl => {
+                if let Some(parent) = dest.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                let file = fs::File::create(dest).map_err(|e| {
+                    HibiscusError::Io(format!("Failed to create '{}': {}", dest.display(), e))
+                })?;
+                Ok(CorpusSink::Jsonl(BufWriter::new(file)))
+            }
+            ExportFormat::Folder => {
+                fs::create_dir_all(dest).map_err(|e| {
+                    HibiscusError::Io(format!("Failed to create '{}': {}", dest.display(), e))
+                })?;
+                Ok(CorpusSink::Folder(dest.to_path_buf()))
+            }
+        }
+    }
+
+    fn write(&mut self, record: &CorpusRecord) -> Result<(), HibiscusError> {
+        match self {
+            CorpusSink::Jsonl(writer) => {
+                serde_json::to_writer(&mut *writer, record)?;
+                writer.write_all(b"\n")?;
+            }
+            CorpusSink::Folder(dir) => {
+                let target = dir.join(&record.path).with_extension("txt");
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                let mut text = String::new();
+                if let Some(metadata) = &record.metadata {
+                    for (key, value) in metadata {
+                        text.push_str(&format!("{}: {}\n", key, value));
+                    }
+                    text.push('\n');
+                }
+                text.push_str(&record.body);
+                text.push('\n');
+                fs::write(&target, text).map_err(|e| {
+                    HibiscusError::Io(format!("Failed to write '{}': {}", target.display(), e))
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), HibiscusError> {
+        if let CorpusSink::Jsonl(mut writer) = self {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn fixture() -> tempfile::TempDir {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("notes")).unwrap();
+        fs::write(
+            dir.path().join("notes").join("rust.md"),
+            "---\ntitle: Rust Notes\ntags: [lang, systems]\n---\n# Heading\nRead [the book](https://doc.rust-lang.org).\n```rust\nfn main() {}\n```\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("plain.txt"), "just text").unwrap();
+        fs::write(dir.path().join("image.png"), [0u8, 1, 2]).unwrap();
+        dir
+    }
+
+    fn read_records(path: &Path) -> Vec<CorpusRecord> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_jsonl_schema_and_stripping() {
+        let dir = fixture();
+        let out = tempdir().unwrap();
+        let dest = out.path().join("corpus.jsonl");
+
+        let report =
+            export_corpus_blocking(dir.path(), &dest, &ExportOptions::default(), |_| {}).unwrap();
+        assert_eq!(report.exported, 2);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].reason, "attachment");
+
+        let records = read_records(&dest);
+        let rust = records.iter().find(|r| r.path == "notes/rust.md").unwrap();
+        assert_eq!(rust.title, "Rust Notes");
+        assert_eq!(rust.tags, vec!["lang", "systems"]);
+        assert_eq!(rust.body, "Heading\nRead the book.\nfn main() {}");
+        assert_eq!(rust.word_count, 7);
+        assert!(rust.mtime > 0);
+        assert!(rust.metadata.is_none());
+
+        // Raw JSON uses the documented field names.
+        let line = fs::read_to_string(&dest).unwrap();
+        let first: serde_json::Value = serde_json::from_str(line.lines().next().unwrap()).unwrap();
+        for key in ["path", "title", "tags", "body", "word_count", "mtime"] {
+            assert!(first.get(key).is_some(), "missing {}", key);
+        }
+    }
+
+    #[test]
+    fn test_tag_filter_and_urls() {
+        let dir = fixture();
+        let out = tempdir().unwrap();
+        let dest = out.path().join("corpus.jsonl");
+        let options = ExportOptions {
+            include_tags: vec!["lang".into()],
+            append_urls: true,
+            embed_frontmatter: true,
+            ..Default::default()
+        };
+
+        let report = export_corpus_blocking(dir.path(), &dest, &options, |_| {}).unwrap();
+        assert_eq!(report.exported, 1);
+
+        let records = read_records(&dest);
+        assert!(records[0].body.contains("the book (https://doc.rust-lang.org)"));
+        assert_eq!(records[0].metadata.as_ref().unwrap()["title"], "Rust Notes");
+    }
+
+    #[test]
+    fn test_folder_format_mirrors_layout() {
+        let dir = fixture();
+        let out = tempdir().unwrap();
+        let options = ExportOptions {
+            format: ExportFormat::Folder,
+            exclude_folders: vec!["notes".into()],
+            ..Default::default()
+        };
+
+        let report = export_corpus_blocking(dir.path(), out.path(), &options, |_| {}).unwrap();
+        assert_eq!(report.exported, 1);
+        assert_eq!(
+            fs::read_to_string(out.path().join("plain.txt")).unwrap(),
+            "just text\n"
+        );
+        assert!(!out.path().join("notes").exists());
+    }
+}
@@ -2,37 +2,192 @@
 // FILE OPERATIONS
 // ============================================================================
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tokio::fs;
-use tokio::io::AsyncWriteExt;
+use tauri::{Emitter, State};
 
+use crate::capabilities::{CapabilityStore, PERMANENT_DELETE};
 use crate::error::HibiscusError;
-use super::path::validate_path;
+use crate::format::{pretty_print_json, LineEnding, LineEndingMode, SaveFormat};
+use crate::idempotency::IdempotencyStore;
+use crate::references::FileChange;
+use crate::storage;
+use crate::tree::new_item_node;
+use crate::undo::RenameHistory;
+use crate::watcher::SELF_WRITES;
+use crate::workspace::{FileMetadata, Node, WorkspaceSettings};
+use super::path::{
+    find_workspace_root, resolve_within_root, scope_entry_to_workspace, scope_to_workspace, validate_path,
+};
+use super::references::reconcile_after;
+
+/// Contents returned by `read_text_file`.
+///
+/// UTF-8 files read without `pretty_json` or `with_metadata` serialize
+/// as the plain string, as before. Every other shape reports the file's
+/// dominant `line_ending` and its `version`, to pass back to
+/// `write_text_file`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(untagged)]
+pub enum TextContent {
+    Plain(String),
+    Text {
+        content: String,
+        line_ending: LineEnding,
+        version: FileVersion,
+    },
+    Json {
+        content: String,
+        /// The file on disk is minified; save with `format.minify_json`
+        /// to keep it that way
+        was_minified: bool,
+        line_ending: LineEnding,
+        version: FileVersion,
+    },
+    /// The file is not UTF-8 and was converted
+    Decoded {
+        content: String,
+        /// Encoding the file was read as, e.g. `windows-1252` or `UTF-16LE`
+        encoding: String,
+        /// Some bytes were invalid and replaced with U+FFFD
+        had_errors: bool,
+        line_ending: LineEnding,
+        version: FileVersion,
+    },
+}
+
+/// A file as it was on disk, for noticing edits made by other programs
+/// between a read and a save.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct FileVersion {
+    /// Modification time, milliseconds since the Unix epoch
+    pub modified_ms: Option<u64>,
+    /// Hash of the file's bytes (16 hex digits)
+    pub hash: Option<String>,
+}
+
+impl FileVersion {
+    /// The version of `path`, whose bytes were just read.
+    pub(crate) async fn of(path: &Path, bytes: &[u8]) -> Self {
+        use std::hash::Hasher;
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        hasher.write(bytes);
+        let modified_ms = storage::metadata(path)
+            .await
+            .ok()
+            .and_then(|meta| meta.modified)
+            .and_then(|time| u64::try_from(time.as_millis()).ok());
+
+        Self { modified_ms, hash: Some(format!("{:016x}", hasher.finish())) }
+    }
+
+    /// Whether `self`, the version on disk now, is the `expected` one. The
+    /// hash decides when `expected` has one, since saving identical bytes
+    /// still bumps the modification time.
+    fn matches(&self, expected: &FileVersion) -> bool {
+        match (&expected.hash, expected.modified_ms) {
+            (Some(hash), _) => self.hash.as_ref() == Some(hash),
+            (None, Some(_)) => self.modified_ms == expected.modified_ms,
+            (None, None) => true,
+        }
+    }
+}
+
+/// A file as a save left it, so the frontend can recognise its own save
+/// when the watcher reports the change.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct WrittenFile {
+    /// Path that was written (a symlink's target in scoped workspaces)
+    pub path: String,
+    /// Modification time after the save, RFC3339 in UTC
+    pub mtime: String,
+    pub size: u64,
+}
+
+impl WrittenFile {
+    /// Reads the metadata of `path`, which was just saved.
+    pub(crate) async fn stat(path: &Path) -> Result<Self, HibiscusError> {
+        let metadata = storage::metadata(path)
+            .await
+            .map_err(|e| HibiscusError::Io(format!("Failed to read metadata of '{}': {}", path.display(), e)))?;
+        let modified = metadata
+            .modified
+            .ok_or_else(|| HibiscusError::Io(format!("Failed to read mtime of '{}'", path.display())))?;
+
+        Ok(Self {
+            path: path.to_string_lossy().into(),
+            mtime: modified.to_rfc3339(),
+            size: metadata.size,
+        })
+    }
+}
+
+/// Decodes file contents that are not valid UTF-8.
+///
+/// A UTF-16 (or UTF-8) byte order mark selects that encoding; anything
+/// else is read as Windows-1252, which maps every byte (and covers
+/// Latin-1 text).
+///
+/// # Returns
+/// `(text, encoding name, had_errors)`
+pub(crate) fn decode_legacy_text(bytes: &[u8]) -> (String, &'static str, bool) {
+    let (encoding, bom_length) =
+        encoding_rs::Encoding::for_bom(bytes).unwrap_or((encoding_rs::WINDOWS_1252, 0));
+    let (text, had_errors) = encoding.decode_without_bom_handling(&bytes[bom_length..]);
+    (text.into_owned(), encoding.name(), had_errors)
+}
 
 /// Reads the contents of a text file asynchronously.
 ///
 /// # Arguments
 /// * `path` - Absolute path to the file to read
+/// * `pretty_json` - For `.json` files, return minified content
+///   pretty-printed, with `was_minified` set (see `TextContent`)
+/// * `strict_encoding` - Fail on files that are not valid UTF-8 instead
+///   of converting them (default false)
+/// * `with_metadata` - Return `TextContent::Text` with the file's line
+///   ending and version instead of the plain string
+/// * `max_size` - Largest file to load, in bytes (default 20 MB); pass a
+///   higher limit to open a large file anyway
 ///
 /// # Returns
-/// * `Ok(String)` - The file contents as a string
+/// * `Ok(TextContent)` - The file contents as a string
+/// * `Err(HibiscusError::FileTooLarge)` - If the file is over `max_size`;
+///   nothing is read
 /// * `Err(HibiscusError)` - If the file cannot be read
 ///
+/// # Encodings
+/// Files that are not valid UTF-8 come back as `TextContent::Decoded`
+/// with the encoding they were read as (see `decode_legacy_text`), so the
+/// frontend can say so. Saving them writes UTF-8. `pretty_json` does not
+/// apply to converted files.
+///
 /// # Security
-/// Path is validated to prevent directory traversal attacks.
+/// Path is validated to prevent directory traversal attacks. In workspaces
+/// with `workspace_scoped` set, symlinks must resolve inside the root.
 #[tauri::command]
-pub async fn read_text_file(path: String) -> Result<String, HibiscusError> {
+pub async fn read_text_file(
+    path: String,
+    pretty_json: Option<bool>,
+    strict_encoding: Option<bool>,
+    with_metadata: Option<bool>,
+    max_size: Option<u64>,
+) -> Result<TextContent, HibiscusError> {
     let path = PathBuf::from(&path);
 
     // Validate the path
     validate_path(&path)?;
+    let path = scope_to_workspace(&path)?;
 
     // Check if path exists and is a file
-    if !path.exists() {
-        return Err(HibiscusError::FileNotFound(path.to_string_lossy().into()));
-    }
+    let metadata = storage::metadata(&path).await.map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => HibiscusError::FileNotFound(path.to_string_lossy().into()),
+        _ => HibiscusError::Io(format!("Failed to read file '{}': {}", path.display(), e)),
+    })?;
 
-    if !path.is_file() {
+    if metadata.is_dir {
         return Err(HibiscusError::InvalidPathType {
             path: path.to_string_lossy().into(),
             expected: "file".into(),
@@ -40,12 +195,243 @@ pub async fn read_text_file(path: String) -> Result<String, HibiscusError> {
         });
     }
 
+    // A stray click on a huge log must not load it all into memory
+    let size = metadata.size;
+    let limit = max_size.unwrap_or(DEFAULT_MAX_TEXT_READ_BYTES);
+    if size > limit {
+        return Err(HibiscusError::FileTooLarge { path: path.to_string_lossy().into(), size, limit });
+    }
+
     // Read file asynchronously (non-blocking)
-    let content = fs::read_to_string(&path).await.map_err(|e| {
+    let bytes = storage::read(&path).await.map_err(|e| {
         HibiscusError::Io(format!("Failed to read file '{}': {}", path.display(), e))
     })?;
+    let version = FileVersion::of(&path, &bytes).await;
 
-    Ok(content)
+    let content = match String::from_utf8(bytes) {
+        Ok(content) => content,
+        Err(_) if strict_encoding.unwrap_or(false) => {
+            return Err(HibiscusError::Io(format!(
+                "Failed to read file '{}': stream did not contain valid UTF-8",
+                path.display()
+            )));
+        }
+        Err(e) => {
+            let (content, encoding, had_errors) = decode_legacy_text(e.as_bytes());
+            let line_ending = LineEnding::detect(&content);
+            return Ok(TextContent::Decoded {
+                content,
+                encoding: encoding.into(),
+                had_errors,
+                line_ending,
+                version,
+            });
+        }
+    };
+    let line_ending = LineEnding::detect(&content);
+
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    if !pretty_json.unwrap_or(false) || !is_json {
+        return Ok(match with_metadata.unwrap_or(false) {
+            true => TextContent::Text { content, line_ending, version },
+            false => TextContent::Plain(content),
+        });
+    }
+
+    // Only minified files are reformatted; others keep their own layout
+    Ok(match pretty_print_json(&content) {
+        Some((pretty, true)) => TextContent::Json { content: pretty, was_minified: true, line_ending, version },
+        _ => TextContent::Json { content, was_minified: false, line_ending, version },
+    })
+}
+
+/// A window of lines from a text file, returned by `read_text_file_range`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct TextRange {
+    /// The requested lines that exist, without their line breaks
+    pub lines: Vec<String>,
+    /// 0-based line number of `lines[0]`
+    pub start_line: usize,
+    /// Number of lines in the whole file; a final line without a line
+    /// break counts
+    pub total_lines: usize,
+}
+
+/// Reads lines `start_line..end_line` (0-based, end exclusive) of a text
+/// file, e.g. for a quick preview of a large file.
+///
+/// # Arguments
+/// * `path` - Absolute path to the file to read
+/// * `start_line` - First line to return
+/// * `end_line` - Line after the last one to return; clamped to the file's
+///   length
+///
+/// # Returns
+/// * `Ok(TextRange)` - The lines and the file's total line count
+/// * `Err(HibiscusError::BinaryFile)` - If the file is not text
+/// * `Err(HibiscusError)` - If the file cannot be read
+///
+/// # Notes
+/// The file is streamed: only the requested lines are kept in memory, the
+/// rest is just counted. `\n` and `\r\n` both end a line, so mixed files
+/// split correctly. Invalid UTF-8 inside the window is replaced with U+FFFD.
+/// Files with a UTF-16 byte order mark are decoded as UTF-16.
+///
+/// # Security
+/// Path is validated to prevent directory traversal attacks. In workspaces
+/// with `workspace_scoped` set, symlinks must resolve inside the root.
+#[tauri::command]
+pub async fn read_text_file_range(
+    path: String,
+    start_line: usize,
+    end_line: usize,
+) -> Result<TextRange, HibiscusError> {
+    let path = PathBuf::from(&path);
+
+    // Validate the path
+    validate_path(&path)?;
+    let path = scope_to_workspace(&path)?;
+
+    let metadata = storage::metadata(&path).await.map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => HibiscusError::FileNotFound(path.to_string_lossy().into()),
+        _ => HibiscusError::Io(format!("Failed to read file '{}': {}", path.display(), e)),
+    })?;
+    if metadata.is_dir {
+        return Err(HibiscusError::InvalidPathType {
+            path: path.to_string_lossy().into(),
+            expected: "file".into(),
+            actual: "directory".into(),
+        });
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let file = storage::storage_for(&path)
+            .read(&path)
+            .map_err(|e| HibiscusError::Io(format!("Failed to read file '{}': {}", path.display(), e)))?;
+        match read_line_range(file, start_line..end_line) {
+            Ok(Some(range)) => Ok(range),
+            Ok(None) => Err(HibiscusError::BinaryFile(path.to_string_lossy().into())),
+            Err(e) => Err(HibiscusError::Io(format!("Failed to read file '{}': {}", path.display(), e))),
+        }
+    })
+    .await
+    .map_err(|e| HibiscusError::Io(format!("Range read task failed: {}", e)))?
+}
+
+/// Streams `reader`, keeping the lines in `window` and counting the rest.
+/// Returns `None` if the contents look binary: the first block fails
+/// `looks_binary`, or a NUL byte turns up later.
+///
+/// UTF-16 files are recognized by their byte order mark, like in
+/// `read_text_file`, and decoded before the binary check.
+fn read_line_range(
+    reader: impl std::io::Read,
+    window: std::ops::Range<usize>,
+) -> std::io::Result<Option<TextRange>> {
+    use std::io::BufRead;
+
+    let mut reader = std::io::BufReader::new(reader);
+    match encoding_rs::Encoding::for_bom(reader.fill_buf()?) {
+        Some((encoding, _)) if encoding != encoding_rs::UTF_8 => {
+            let decoded = Utf8Decoder {
+                inner: reader,
+                decoder: encoding.new_decoder_with_bom_removal(),
+                done: false,
+            };
+            scan_line_range(std::io::BufReader::new(decoded), window)
+        }
+        _ => scan_line_range(reader, window),
+    }
+}
+
+/// `read_line_range` on a stream that is UTF-8 (or meant to be).
+fn scan_line_range(
+    mut reader: impl std::io::BufRead,
+    window: std::ops::Range<usize>,
+) -> std::io::Result<Option<TextRange>> {
+    let mut lines = Vec::new();
+    let mut current = Vec::new();
+    let mut line = 0;
+    let mut partial = false;
+    let mut first_block = true;
+
+    loop {
+        let block = reader.fill_buf()?;
+        if block.is_empty() {
+            break;
+        }
+        if (first_block && super::opener::looks_binary(block)) || block.contains(&0) {
+            return Ok(None);
+        }
+        first_block = false;
+
+        let consumed = block.len();
+        let mut rest = block;
+        while !rest.is_empty() {
+            let end = rest.iter().position(|&byte| byte == b'\n');
+            let part = &rest[..end.unwrap_or(rest.len())];
+            if window.contains(&line) {
+                current.extend_from_slice(part);
+            }
+            match end {
+                Some(end) => {
+                    if window.contains(&line) {
+                        lines.push(line_text(&current));
+                        current.clear();
+                    }
+                    line += 1;
+                    partial = false;
+                    rest = &rest[end + 1..];
+                }
+                None => {
+                    partial = true;
+                    rest = &[];
+                }
+            }
+        }
+        reader.consume(consumed);
+    }
+
+    // A last line without a line break
+    if partial {
+        if window.contains(&line) {
+            lines.push(line_text(&current));
+        }
+        line += 1;
+    }
+
+    Ok(Some(TextRange { lines, start_line: window.start.min(line), total_lines: line }))
+}
+
+/// Reads a stream in another encoding as UTF-8, with malformed sequences
+/// replaced by U+FFFD.
+struct Utf8Decoder<R> {
+    inner: R,
+    decoder: encoding_rs::Decoder,
+    done: bool,
+}
+
+impl<R: std::io::BufRead> std::io::Read for Utf8Decoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while !self.done && !buf.is_empty() {
+            let src = self.inner.fill_buf()?;
+            let last = src.is_empty();
+            let (result, read, written, _) = self.decoder.decode_to_utf8(src, buf, last);
+            self.inner.consume(read);
+            self.done = last && result == encoding_rs::CoderResult::InputEmpty;
+            if written > 0 {
+                return Ok(written);
+            }
+        }
+        Ok(0)
+    }
+}
+
+/// A line's text, without the `\r` of a `\r\n` break.
+fn line_text(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes.strip_suffix(b"\r").unwrap_or(bytes)).into_owned()
 }
 
 /// Writes contents to a text file asynchronously.
@@ -53,221 +439,505 @@ pub async fn read_text_file(path: String) -> Result<String, HibiscusError> {
 /// Uses a safe write strategy inspired by modern editors (VS Code, Sublime):
 /// 1. Write to a temporary file with `.hibiscus-save~` suffix
 /// 2. Sync to disk to ensure durability
-/// 3. On Windows: delete target first (Windows can't rename over existing)
-/// 4. Rename temp to target (atomic on most filesystems)
-/// 5. Cleanup temp file on any failure
+/// 3. Copy the target's permissions, owner (Unix, best effort) or
+///    attributes (Windows) to the temp file
+/// 4. On Windows: delete target first (Windows can't rename over existing)
+/// 5. Rename temp to target (atomic on most filesystems)
+/// 6. On Unix: fsync the containing directory so the rename is durable
+/// 7. Cleanup temp file on any failure
+///
+/// # Durability
+/// Once this returns `Ok`, both the new contents and the directory entry
+/// pointing at them have been flushed to disk on Unix, so a power loss
+/// cannot roll the file back to its previous version. On Windows the
+/// directory entry is flushed by the filesystem as part of the rename.
 ///
 /// # Arguments
 /// * `path` - Absolute path to the file to write
 /// * `contents` - The string content to write
+/// * `format` - Optional save-time formatting (see `crate::format`); set
+///   `minify_json` for files `read_text_file` reported as `was_minified`
+/// * `force` - Write even if the contents exceed the workspace's
+///   `max_file_write_bytes` setting. Does not skip the `expected_version`
+///   check; to overwrite external edits anyway, leave that out
+/// * `line_ending` - Rewrite every line break to `lf` or `crlf`, or
+///   `preserve` the dominant one of the file on disk (new files are written
+///   as sent). Applied after `format`. By default contents are written as sent.
+/// * `expected_version` - The `version` `read_text_file` returned; the
+///   write fails if the file on disk is no longer that version. A file that
+///   was deleted meanwhile is simply written.
+/// * `keep_backup` - Copy the file on disk to `.hibiscus/backups` before
+///   replacing it, keeping the workspace's `backup_retention` newest copies
+///   (default 5). Defaults to the `keep_backups` setting. Files outside a
+///   workspace are not backed up.
 ///
 /// # Returns
-/// * `Ok(())` - If the write was successful
+/// * `Ok(WrittenFile)` - The saved file's path, size and new mtime, read
+///   right after the rename
+/// * `Err(HibiscusError::QuotaExceeded)` - If the contents are over the
+///   workspace limit and `force` is not set; nothing is written
+/// * `Err(HibiscusError::Conflict)` - If the file was changed by another
+///   program since `expected_version`; nothing is written
 /// * `Err(HibiscusError)` - If the write failed
 ///
 /// # Security
-/// Path is validated to prevent directory traversal attacks.
+/// Path is validated to prevent directory traversal attacks. In workspaces
+/// with `workspace_scoped` set, symlinks must resolve inside the root and
+/// the write goes to the symlink's target.
 #[tauri::command]
-pub async fn write_text_file(path: String, contents: String) -> Result<(), HibiscusError> {
-    let path = PathBuf::from(&path);
+pub async fn write_text_file(
+    path: String,
+    contents: String,
+    format: Option<SaveFormat>,
+    force: Option<bool>,
+    line_ending: Option<LineEndingMode>,
+    expected_version: Option<FileVersion>,
+    keep_backup: Option<bool>,
+) -> Result<WrittenFile, HibiscusError> {
+    let force = force.unwrap_or(false);
+    let (path, contents) =
+        prepare_write(&path, contents, format.as_ref(), force, line_ending, expected_version.as_ref()).await?;
+
+    if let Some(root) = find_workspace_root(&path) {
+        back_up_before_save(&root, &path, keep_backup).await?;
+    }
+
+    save_atomically(&path, contents.as_bytes()).await?;
+    WrittenFile::stat(&path).await
+}
+
+/// Checks and normalizes one write the way `write_text_file` describes:
+/// path validation and scoping, the conflict check, `format`, line endings
+/// and the quota. Returns the path to write and the final contents.
+async fn prepare_write(
+    path: &str,
+    contents: String,
+    format: Option<&SaveFormat>,
+    force: bool,
+    line_ending: Option<LineEndingMode>,
+    expected_version: Option<&FileVersion>,
+) -> Result<(PathBuf, String), HibiscusError> {
+    let path = PathBuf::from(path);
 
     // Validate the path
     validate_path(&path)?;
+    let path = scope_to_workspace(&path)?;
 
-    // Create parent directories if needed
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).await.map_err(|e| {
-            HibiscusError::Io(format!(
-                "Failed to create parent directories for '{}': {}",
-                path.display(),
-                e
-            ))
-        })?;
+    // Don't clobber edits made in another program since the read
+    if let Some(expected) = expected_version {
+        check_unchanged(&path, expected).await?;
     }
 
-    // ===========================================================================
-    // MODERN EDITOR SAVE STRATEGY
-    // ===========================================================================
-    // Create temp file with .hibiscus-save~ suffix APPENDED to full filename.
-    // Using a unique suffix prevents conflicts with user files.
-    // Example: "notes.txt" -> "notes.txt.hibiscus-save~"
-    // ===========================================================================
-    let temp_filename = format!(
-        "{}.hibiscus-save~",
-        path.file_name()
-            .map(|n| n.to_string_lossy())
-            .unwrap_or_default()
-    );
-    let temp_path = path.with_file_name(&temp_filename);
-
-    // Write to temp file
-    let write_result = async {
-        let mut file = fs::File::create(&temp_path).await.map_err(|e| {
-            HibiscusError::Io(format!(
-                "Failed to create temp file '{}': {}",
-                temp_path.display(),
-                e
-            ))
-        })?;
+    let contents = format.cloned().unwrap_or_default().apply(contents);
+    let contents = apply_line_ending(&path, contents, line_ending).await;
 
-        file.write_all(contents.as_bytes()).await.map_err(|e| {
-            HibiscusError::Io(format!(
-                "Failed to write to temp file '{}': {}",
-                temp_path.display(),
-                e
-            ))
-        })?;
+    // Keep huge files out of (cloud-synced) vaults unless explicitly forced
+    if !force {
+        check_write_quota(&path, contents.len() as u64)?;
+    }
+    Ok((path, contents))
+}
 
-        // Sync to ensure data is on disk before rename
-        file.sync_all().await.map_err(|e| {
-            HibiscusError::Io(format!("Failed to sync file '{}': {}", temp_path.display(), e))
-        })?;
+/// Backs up `path` before a save replaces it, if `keep_backup` (or else
+/// the workspace's `keep_backups` setting) asks for it.
+async fn back_up_before_save(root: &Path, path: &Path, keep_backup: Option<bool>) -> Result<(), HibiscusError> {
+    let settings = WorkspaceSettings::load(root);
+    if keep_backup.unwrap_or(settings.keep_backups) {
+        let keep = settings.backup_retention.unwrap_or(crate::backup::DEFAULT_FILE_BACKUPS);
+        crate::backup::backup_file(path, root, keep).await?;
+    }
+    Ok(())
+}
+
+/// Rewrites the line breaks of `contents` as `mode` asks for `path`.
+async fn apply_line_ending(path: &Path, contents: String, mode: Option<LineEndingMode>) -> String {
+    let ending = match mode {
+        None => return contents,
+        Some(LineEndingMode::Lf) => LineEnding::Lf,
+        Some(LineEndingMode::Crlf) => LineEnding::Crlf,
+        Some(LineEndingMode::Preserve) => match storage::read(path).await {
+            Ok(bytes) => LineEnding::detect(&String::from_utf8_lossy(&bytes)),
+            // Nothing to preserve yet
+            Err(_) => return contents,
+        },
+    };
+    ending.apply(&contents)
+}
+
+/// Fails with `Conflict` if the file at `path` is no longer the
+/// `expected` version. A missing file has nothing to overwrite and passes.
+async fn check_unchanged(path: &Path, expected: &FileVersion) -> Result<(), HibiscusError> {
+    let bytes = match storage::read(path).await {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => {
+            return Err(HibiscusError::Io(format!("Failed to read file '{}': {}", path.display(), e)));
+        }
+    };
 
-        Ok::<(), HibiscusError>(())
+    let current = FileVersion::of(path, &bytes).await;
+    if current.matches(expected) {
+        return Ok(());
     }
-    .await;
+    Err(HibiscusError::Conflict {
+        path: path.to_string_lossy().into(),
+        modified_ms: current.modified_ms.unwrap_or(0),
+        hash: current.hash.unwrap_or_default(),
+    })
+}
 
-    // If write failed, cleanup temp file and return error
-    if let Err(e) = write_result {
-        let _ = fs::remove_file(&temp_path).await; // Ignore cleanup errors
-        return Err(e);
+/// Fails with `QuotaExceeded` if `size` is over the `max_file_write_bytes`
+/// setting of the workspace containing `path`.
+fn check_write_quota(path: &Path, size: u64) -> Result<(), HibiscusError> {
+    let limit = find_workspace_root(path)
+        .and_then(|root| WorkspaceSettings::load(&root).max_file_write_bytes);
+    match limit.filter(|limit| size > *limit) {
+        Some(limit) => Err(HibiscusError::QuotaExceeded { size, limit }),
+        None => Ok(()),
     }
+}
 
-    // ===========================================================================
-    // WINDOWS COMPATIBILITY: Windows doesn't support atomic rename over existing
-    // files. We must delete the target first. This creates a brief window where
-    // the file doesn't exist, but it's the standard approach for Windows.
-    // ===========================================================================
-    #[cfg(target_os = "windows")]
-    if path.exists() {
-        if let Err(e) = fs::remove_file(&path).await {
-            // Cleanup temp and return error
-            let _ = fs::remove_file(&temp_path).await;
-            return Err(HibiscusError::Io(format!(
-                "Failed to remove existing file '{}' before save: {}",
-                path.display(),
-                e
-            )));
+/// One file of a `write_text_files` call.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct WriteEntry {
+    /// Absolute path to the file to write
+    pub path: String,
+    pub contents: String,
+    /// As in `write_text_file`
+    #[serde(default)]
+    pub format: Option<SaveFormat>,
+    /// As in `write_text_file`
+    #[serde(default)]
+    pub force: bool,
+    /// As in `write_text_file`
+    #[serde(default)]
+    pub line_ending: Option<LineEndingMode>,
+    /// As in `write_text_file`
+    #[serde(default)]
+    pub expected_version: Option<FileVersion>,
+}
+
+/// Outcome of one `WriteEntry`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct WriteResult {
+    pub path: String,
+    pub written: bool,
+    pub error: Option<String>,
+}
+
+/// Saves several text files at once, e.g. "save all" across split panes.
+///
+/// # Arguments
+/// * `entries` - The files and their new contents
+/// * `atomic_group` - Save all files or none (see `crate::save_group`):
+///   every temp file is written and synced before the first rename, and a
+///   failed rename rolls the already-saved files back. The group is
+///   journaled in the workspace so crash recovery can finish or revert it.
+///   Otherwise each file is saved like `write_text_file`, independently.
+///   Either way every entry gets the checks and formatting of
+///   `write_text_file` before anything is written.
+///
+/// # Returns
+/// * `Ok(Vec<WriteResult>)` - One result per entry, in order. In a failed
+///   atomic group no file is written, and the entries that did not cause
+///   the failure say so.
+/// * `Err(HibiscusError)` - If an atomic group spans several workspaces or
+///   none, or its task failed
+#[tauri::command]
+pub async fn write_text_files(
+    entries: Vec<WriteEntry>,
+    atomic_group: bool,
+) -> Result<Vec<WriteResult>, HibiscusError> {
+    if !atomic_group {
+        let mut results = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let outcome = write_text_file(
+                entry.path.clone(),
+                entry.contents,
+                entry.format,
+                Some(entry.force),
+                entry.line_ending,
+                entry.expected_version,
+                None,
+            )
+            .await;
+            results.push(WriteResult {
+                path: entry.path,
+                written: outcome.is_ok(),
+                error: outcome.err().map(|e| e.to_string()),
+            });
         }
+        return Ok(results);
     }
 
-    // Rename temp file to target
-    if let Err(e) = fs::rename(&temp_path, &path).await {
-        // Cleanup temp file on rename failure
-        let _ = fs::remove_file(&temp_path).await;
-        return Err(HibiscusError::Io(format!(
-            "Failed to rename '{}' to '{}': {}",
-            temp_path.display(),
-            path.display(),
-            e
-        )));
+    // Check every file before touching any
+    let mut files = Vec::with_capacity(entries.len());
+    let mut roots = Vec::with_capacity(entries.len());
+    for (index, entry) in entries.iter().enumerate() {
+        let prepared = prepare_write(
+            &entry.path,
+            entry.contents.clone(),
+            entry.format.as_ref(),
+            entry.force,
+            entry.line_ending,
+            entry.expected_version.as_ref(),
+        )
+        .await;
+        match prepared {
+            Ok((path, contents)) => {
+                roots.push(find_workspace_root(&path));
+                files.push((path, contents.into_bytes()));
+            }
+            Err(e) => return Ok(group_results(&entries, index, e)),
+        }
     }
 
-    Ok(())
+    let root = match roots.first() {
+        Some(Some(root)) if roots.iter().all(|r| r.as_ref() == Some(root)) => root.clone(),
+        _ => {
+            return Err(HibiscusError::Workspace(
+                "An atomic save group must stay within one workspace".into(),
+            ))
+        }
+    };
+
+    for (index, (path, _)) in files.iter().enumerate() {
+        if let Err(e) = back_up_before_save(&root, path, None).await {
+            return Ok(group_results(&entries, index, e));
+        }
+    }
+
+    let outcome = tokio::task::spawn_blocking(move || crate::save_group::save_group(&root, &files))
+        .await
+        .map_err(|e| HibiscusError::Io(format!("Save group task failed: {}", e)))?;
+
+    Ok(match outcome {
+        Ok(()) => entries
+            .into_iter()
+            .map(|entry| WriteResult { path: entry.path, written: true, error: None })
+            .collect(),
+        Err(failure) => match failure.index {
+            Some(index) => group_results(&entries, index, failure.error),
+            None => entries
+                .into_iter()
+                .map(|entry| WriteResult {
+                    path: entry.path,
+                    written: false,
+                    error: Some(failure.error.to_string()),
+                })
+                .collect(),
+        },
+    })
+}
+
+/// Results of an atomic group that failed at entry `failed`.
+fn group_results(entries: &[WriteEntry], failed: usize, error: HibiscusError) -> Vec<WriteResult> {
+    entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| WriteResult {
+            path: entry.path.clone(),
+            written: false,
+            error: Some(if index == failed {
+                error.to_string()
+            } else {
+                format!("Not saved: '{}' failed", entries[failed].path)
+            }),
+        })
+        .collect()
 }
 
-/// Creates a new empty file at the specified path.
+/// Writes `contents` with the temp-file + sync + rename strategy described
+/// on `write_text_file`, creating parent directories as needed. Goes
+/// through the storage holding `path` (see `crate::storage`).
+pub(crate) async fn save_atomically(path: &Path, contents: &[u8]) -> Result<(), HibiscusError> {
+    storage::write_atomic(path, contents).await.map_err(|e| HibiscusError::Io(e.to_string()))
+}
+
+/// Highest number tried when `auto_rename` picks a free name.
+pub(super) const MAX_AUTO_RENAME: usize = 1000;
+
+/// Creates a new file at the specified path.
 ///
 /// # Arguments
 /// * `path` - Absolute path where the file should be created
+/// * `initial_contents` - Text to write into the new file (default empty)
+/// * `auto_rename` - If the path is taken, create `Name 1.ext`,
+///   `Name 2.ext`, ... instead of failing
+/// * `idempotency_key` - Optional key; a repeated key returns the first
+///   result instead of failing because the file now exists
 ///
 /// # Returns
-/// * `Ok(())` - If the file was created successfully
+/// * `Ok(Node)` - The created file, ready to insert into the tree
+/// * `Err(HibiscusError::AlreadyExists)` - If the path is taken and
+///   `auto_rename` is not set
 /// * `Err(HibiscusError)` - If the file could not be created
+///
+/// # Security
+/// Path is validated to prevent directory traversal attacks. In workspaces
+/// with `workspace_scoped` set, the folder it is created in must resolve inside
+/// the root.
 #[tauri::command]
-pub async fn create_file(path: String) -> Result<(), HibiscusError> {
+pub async fn create_file(
+    path: String,
+    initial_contents: Option<String>,
+    auto_rename: Option<bool>,
+    idempotency_key: Option<String>,
+    idempotency: State<'_, IdempotencyStore>,
+) -> Result<Node, HibiscusError> {
+    idempotency
+        .run("create_file", idempotency_key.as_deref(), || {
+            create_new_file(path, initial_contents, auto_rename.unwrap_or(false))
+        })
+        .await
+}
+
+async fn create_new_file(
+    path: String,
+    initial_contents: Option<String>,
+    auto_rename: bool,
+) -> Result<Node, HibiscusError> {
     let path = PathBuf::from(&path);
     
     // Validate the path
     validate_path(&path)?;
+    let path = scope_to_workspace(&path)?;
     
-    // Check if file already exists
-    if path.exists() {
-        return Err(HibiscusError::Io(format!(
-            "File already exists: '{}'", 
-            path.display()
-        )));
-    }
-    
-    // Create parent directories if needed
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).await.map_err(|e| {
-            HibiscusError::Io(format!(
-                "Failed to create parent directories for '{}': {}",
-                path.display(),
-                e
-            ))
-        })?;
-    }
-    
-    // Create empty file
-    fs::File::create(&path).await.map_err(|e| {
-        HibiscusError::Io(format!(
-            "Failed to create file '{}': {}",
-            path.display(),
-            e
-        ))
-    })?;
+    // The storage's create never overwrites, so a name taken between the
+    // check and the create is kept
+    let contents = initial_contents.unwrap_or_default();
+    let mut attempt = 0;
+    let created = loop {
+        let candidate = numbered_path(&path, attempt);
+        match storage::create_new(&candidate, contents.as_bytes()).await {
+            Ok(()) => break candidate,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                if !auto_rename || attempt == MAX_AUTO_RENAME {
+                    return Err(HibiscusError::AlreadyExists(candidate.display().to_string()));
+                }
+                attempt += 1;
+            }
+            Err(e) => {
+                return Err(HibiscusError::Io(format!(
+                    "Failed to create file '{}': {}",
+                    candidate.display(),
+                    e
+                )))
+            }
+        }
+    };
     
-    Ok(())
+    Ok(created_node(&created))
 }
 
 /// Creates a new directory at the specified path.
 ///
 /// # Arguments
 /// * `path` - Absolute path where the directory should be created
+/// * `auto_rename` - If the path is taken, create `Name 1`, `Name 2`, ...
+///   instead of failing
 ///
 /// # Returns
-/// * `Ok(())` - If the directory was created successfully
+/// * `Ok(Node)` - The created (empty) folder, ready to insert into the tree
+/// * `Err(HibiscusError::AlreadyExists)` - If the path is taken and
+///   `auto_rename` is not set
 /// * `Err(HibiscusError)` - If the directory could not be created
+///
+/// # Security
+/// Path is validated to prevent directory traversal attacks. In workspaces
+/// with `workspace_scoped` set, the folder it is created in must resolve inside
+/// the root.
 #[tauri::command]
-pub async fn create_folder(path: String) -> Result<(), HibiscusError> {
+pub async fn create_folder(path: String, auto_rename: Option<bool>) -> Result<Node, HibiscusError> {
     let path = PathBuf::from(&path);
     
     // Validate the path
     validate_path(&path)?;
+    let path = scope_to_workspace(&path)?;
     
-    // Check if directory already exists
-    if path.exists() {
-        return Err(HibiscusError::Io(format!(
-            "Directory already exists: '{}'", 
-            path.display()
-        )));
-    }
-    
-    // Create directory with parents
-    fs::create_dir_all(&path).await.map_err(|e| {
-        HibiscusError::Io(format!(
-            "Failed to create directory '{}': {}",
-            path.display(),
-            e
-        ))
-    })?;
+    let mut attempt = 0;
+    let created = loop {
+        let candidate = numbered_path(&path, attempt);
+        match storage::create_dir(&candidate).await {
+            Ok(()) => break candidate,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                if !auto_rename.unwrap_or(false) || attempt == MAX_AUTO_RENAME {
+                    return Err(HibiscusError::AlreadyExists(candidate.display().to_string()));
+                }
+                attempt += 1;
+            }
+            Err(e) => {
+                return Err(HibiscusError::Io(format!(
+                    "Failed to create directory '{}': {}",
+                    candidate.display(),
+                    e
+                )))
+            }
+        }
+    };
     
-    Ok(())
+    Ok(created_node(&created))
+}
+
+/// `path` with ` {attempt}` appended to its stem: `Untitled.md` ->
+/// `Untitled 1.md`. Attempt 0 is the path itself.
+pub(super) fn numbered_path(path: &Path, attempt: usize) -> PathBuf {
+    if attempt == 0 {
+        return path.to_path_buf();
+    }
+
+    let stem = path.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+    let name = match path.extension() {
+        Some(ext) => format!("{} {}.{}", stem, attempt, ext.to_string_lossy()),
+        None => format!("{} {}", stem, attempt),
+    };
+    path.with_file_name(name)
+}
+
+/// Tree node for a created item, with ids relative to its workspace.
+fn created_node(path: &Path) -> Node {
+    let base = find_workspace_root(path)
+        .or_else(|| path.parent().map(Path::to_path_buf))
+        .unwrap_or_default();
+    new_item_node(path, &base)
 }
 
-/// Deletes a file at the specified path.
+/// Deletes a file at the specified path, to the OS trash by default.
 ///
 /// # Arguments
 /// * `path` - Absolute path to the file to delete
+/// * `permanent` - Unlink instead of moving to the trash (default false)
+/// * `capability` - Token from `request_capability("permanent_delete")`,
+///   required only for permanent deletes
 ///
 /// # Returns
 /// * `Ok(())` - If the file was deleted successfully
+/// * `Err(HibiscusError::TrashUnavailable)` - If the trash refused the file;
+///   retry with `permanent` to delete it for good
+/// * `Err(HibiscusError::CapabilityRequired)` - If the token is missing or invalid
 /// * `Err(HibiscusError)` - If the file could not be deleted
+///
+/// # Notes
+/// Directories are refused; use `delete_folder` or `delete_path`.
 #[tauri::command]
-pub async fn delete_file(path: String) -> Result<(), HibiscusError> {
+pub async fn delete_file(
+    path: String,
+    permanent: Option<bool>,
+    capability: Option<String>,
+    capabilities: State<'_, CapabilityStore>,
+) -> Result<(), HibiscusError> {
     let path = PathBuf::from(&path);
+    let permanent = permanent.unwrap_or(false);
     
     // Validate the path
     validate_path(&path)?;
     
     // Check if path exists and is a file
-    if !path.exists() {
-        return Err(HibiscusError::FileNotFound(path.to_string_lossy().into()));
-    }
+    let metadata = storage::metadata(&path)
+        .await
+        .map_err(|_| HibiscusError::FileNotFound(path.to_string_lossy().into()))?;
     
-    if !path.is_file() {
+    if metadata.is_dir {
         return Err(HibiscusError::InvalidPathType {
             path: path.to_string_lossy().into(),
             expected: "file".into(),
@@ -275,14 +945,19 @@ pub async fn delete_file(path: String) -> Result<(), HibiscusError> {
         });
     }
     
+    // Only a delete that will be attempted uses up the token
+    if permanent {
+        capabilities.consume(PERMANENT_DELETE, capability.as_deref())?;
+    }
+
     // Delete the file
-    fs::remove_file(&path).await.map_err(|e| {
-        HibiscusError::Io(format!(
-            "Failed to delete file '{}': {}",
-            path.display(),
-            e
-        ))
-    })?;
+    remove_path(&path, !permanent).await?;
+
+    // Drop references to the deleted file (session, favorites, calendar, ...)
+    let change = FileChange::Deleted {
+        path: path.to_string_lossy().into(),
+    };
+    reconcile_after(&path, change).await;
     
     Ok(())
 }
@@ -291,23 +966,29 @@ pub async fn delete_file(path: String) -> Result<(), HibiscusError> {
 ///
 /// # Arguments
 /// * `path` - Absolute path to the directory to delete
+/// * `capability` - Token from `request_capability("permanent_delete")`
 ///
 /// # Returns
 /// * `Ok(())` - If the directory was deleted successfully
+/// * `Err(HibiscusError::CapabilityRequired)` - If the token is missing or invalid
 /// * `Err(HibiscusError)` - If the directory could not be deleted
 #[tauri::command]
-pub async fn delete_folder(path: String) -> Result<(), HibiscusError> {
+pub async fn delete_folder(
+    path: String,
+    capability: Option<String>,
+    capabilities: State<'_, CapabilityStore>,
+) -> Result<(), HibiscusError> {
     let path = PathBuf::from(&path);
     
     // Validate the path
     validate_path(&path)?;
     
     // Check if path exists and is a directory
-    if !path.exists() {
-        return Err(HibiscusError::FileNotFound(path.to_string_lossy().into()));
-    }
+    let metadata = storage::metadata(&path)
+        .await
+        .map_err(|_| HibiscusError::FileNotFound(path.to_string_lossy().into()))?;
     
-    if !path.is_dir() {
+    if !metadata.is_dir {
         return Err(HibiscusError::InvalidPathType {
             path: path.to_string_lossy().into(),
             expected: "directory".into(),
@@ -315,18 +996,128 @@ pub async fn delete_folder(path: String) -> Result<(), HibiscusError> {
         });
     }
     
+    // Only a delete that will be attempted uses up the token
+    capabilities.consume(PERMANENT_DELETE, capability.as_deref())?;
+
     // Delete the directory and all its contents
-    fs::remove_dir_all(&path).await.map_err(|e| {
+    storage::remove(&path).await.map_err(|e| {
         HibiscusError::Io(format!(
             "Failed to delete directory '{}': {}",
             path.display(),
             e
         ))
     })?;
+
+    // Drop references to anything inside the deleted directory
+    let change = FileChange::Deleted {
+        path: path.to_string_lossy().into(),
+    };
+    reconcile_after(&path, change).await;
     
     Ok(())
 }
 
+/// Deletes a file or folder, either to the OS trash or permanently.
+///
+/// # Arguments
+/// * `path` - Absolute path of the file or folder
+/// * `to_trash` - Move to the recycle bin instead of deleting for good
+/// * `capability` - Token from `request_capability("permanent_delete")`,
+///   required only when `to_trash` is false
+///
+/// # Returns
+/// * `Ok(())` - If the item was deleted
+/// * `Err(HibiscusError::PathValidation)` - For the workspace's `.hibiscus` folder
+/// * `Err(HibiscusError::TrashUnavailable)` - If the trash refused the item
+/// * `Err(HibiscusError::CapabilityRequired)` - If a permanent delete has no valid token
+/// * `Err(HibiscusError)` - If the item is missing or could not be deleted
+///
+/// # Notes
+/// The deletion is reported as `fs-changed` right away so the tree refreshes.
+#[tauri::command]
+pub async fn delete_path(
+    path: String,
+    to_trash: bool,
+    capability: Option<String>,
+    capabilities: State<'_, CapabilityStore>,
+    window: tauri::Window,
+) -> Result<(), HibiscusError> {
+    let path = PathBuf::from(&path);
+    check_deletable(&path)?;
+
+    if !to_trash {
+        capabilities.consume(PERMANENT_DELETE, capability.as_deref())?;
+    }
+    remove_path(&path, to_trash).await?;
+
+    // Drop references to the deleted item (session, favorites, calendar, ...)
+    let change = FileChange::Deleted {
+        path: path.to_string_lossy().into(),
+    };
+    reconcile_after(&path, change).await;
+
+    crate::watcher::notify_changed(&window, &[path], crate::watcher::ChangeKind::Removed);
+    Ok(())
+}
+
+/// Validates a path for deletion. The `.hibiscus` folder holds the
+/// workspace's own data and is never deleted from the tree.
+fn check_deletable(path: &Path) -> Result<(), HibiscusError> {
+    validate_path(path)?;
+
+    if path.file_name().is_some_and(|name| name == ".hibiscus") {
+        return Err(HibiscusError::PathValidation(format!(
+            "Refusing to delete the workspace data folder '{}'",
+            path.display()
+        )));
+    }
+    if !storage::storage_for(path).exists(path) {
+        return Err(HibiscusError::FileNotFound(path.to_string_lossy().into()));
+    }
+    Ok(())
+}
+
+async fn remove_path(path: &Path, to_trash: bool) -> Result<(), HibiscusError> {
+    if to_trash {
+        let target = path.to_path_buf();
+        return tokio::task::spawn_blocking(move || trash::delete(&target))
+            .await
+            .map_err(|e| HibiscusError::Io(format!("Trash task failed: {}", e)))?
+            .map_err(|e| trash_error(path, e));
+    }
+
+    storage::remove(path).await.map_err(|e| {
+        HibiscusError::Io(format!("Failed to delete '{}': {}", path.display(), e))
+    })
+}
+
+/// Maps a failed move of `path` to the trash. Only a trash that could not
+/// take the item is `TrashUnavailable` (the frontend then offers a
+/// permanent delete); a missing item or one the filesystem refused to
+/// touch is reported like a failed permanent delete.
+fn trash_error(path: &Path, error: trash::Error) -> HibiscusError {
+    if std::fs::symlink_metadata(path).is_err_and(|e| e.kind() == std::io::ErrorKind::NotFound) {
+        return HibiscusError::FileNotFound(path.to_string_lossy().into());
+    }
+
+    let unavailable = match &error {
+        trash::Error::Unknown { .. } | trash::Error::Os { .. } => true,
+        // The trash folder itself (not the item) could not be used
+        #[cfg(all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android")))]
+        trash::Error::FileSystem { path: failed, .. } => !failed.starts_with(path),
+        _ => false,
+    };
+    if unavailable {
+        HibiscusError::TrashUnavailable(format!(
+            "Could not move '{}' to the trash: {}",
+            path.display(),
+            error
+        ))
+    } else {
+        HibiscusError::Io(format!("Failed to delete '{}': {}", path.display(), error))
+    }
+}
+
 /// Reads the binary contents of a file asynchronously.
 ///
 /// This command is used for reading binary files like PDF and DOCX
@@ -340,20 +1131,23 @@ pub async fn delete_folder(path: String) -> Result<(), HibiscusError> {
 /// * `Err(HibiscusError)` - If the file cannot be read
 ///
 /// # Security
-/// Path is validated to prevent directory traversal attacks.
+/// Path is validated to prevent directory traversal attacks. In workspaces
+/// with `workspace_scoped` set, symlinks must resolve inside the root.
 #[tauri::command]
 pub async fn read_file_binary(path: String) -> Result<Vec<u8>, HibiscusError> {
     let path = PathBuf::from(&path);
 
     // Validate the path
     validate_path(&path)?;
+    let path = scope_to_workspace(&path)?;
 
     // Check if path exists and is a file
-    if !path.exists() {
-        return Err(HibiscusError::FileNotFound(path.to_string_lossy().into()));
-    }
+    let metadata = storage::metadata(&path).await.map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => HibiscusError::FileNotFound(path.to_string_lossy().into()),
+        _ => HibiscusError::Io(format!("Failed to read binary file '{}': {}", path.display(), e)),
+    })?;
 
-    if !path.is_file() {
+    if metadata.is_dir {
         return Err(HibiscusError::InvalidPathType {
             path: path.to_string_lossy().into(),
             expected: "file".into(),
@@ -362,14 +1156,197 @@ pub async fn read_file_binary(path: String) -> Result<Vec<u8>, HibiscusError> {
     }
 
     // Read file as binary data
-    let content = fs::read(&path).await.map_err(|e| {
+    let content = storage::read(&path).await.map_err(|e| {
         HibiscusError::Io(format!("Failed to read binary file '{}': {}", path.display(), e))
     })?;
 
     Ok(content)
 }
 
-/// Moves or renames a file or directory.
+/// Default `max_size` of `read_text_file` (20 MB).
+pub const DEFAULT_MAX_TEXT_READ_BYTES: u64 = 20 * 1024 * 1024;
+
+/// Default for the workspace's `max_binary_read_bytes` (50 MB).
+pub const DEFAULT_MAX_BINARY_READ_BYTES: u64 = 50 * 1024 * 1024;
+
+/// A binary file encoded for display in the webview.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct BinaryFile {
+    /// File contents, base64-encoded
+    pub data: String,
+    /// MIME type from the magic bytes, else the extension
+    pub mime: String,
+    pub size: u64,
+}
+
+/// Reads a binary file (image, PDF, ...) as base64 with its MIME type.
+///
+/// # Arguments
+/// * `path` - Absolute path to the file to read
+///
+/// # Returns
+/// * `Ok(BinaryFile)` - Base64 data, MIME type and size in bytes
+/// * `Err(HibiscusError::FileTooLarge)` - If the file is larger than the
+///   workspace's `max_binary_read_bytes` (default 50 MB); nothing is read
+/// * `Err(HibiscusError)` - If the file cannot be read
+///
+/// # Security
+/// Path is validated to prevent directory traversal attacks. In workspaces
+/// with `workspace_scoped` set, symlinks must resolve inside the root.
+#[tauri::command]
+pub async fn read_binary_file(path: String) -> Result<BinaryFile, HibiscusError> {
+    use base64::Engine;
+
+    let path = PathBuf::from(&path);
+
+    // Validate the path
+    validate_path(&path)?;
+    let path = scope_to_workspace(&path)?;
+
+    let metadata = storage::metadata(&path).await.map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => HibiscusError::FileNotFound(path.to_string_lossy().into()),
+        _ => HibiscusError::Io(format!("Failed to read '{}': {}", path.display(), e)),
+    })?;
+    if metadata.is_dir {
+        return Err(HibiscusError::InvalidPathType {
+            path: path.to_string_lossy().into(),
+            expected: "file".into(),
+            actual: "directory".into(),
+        });
+    }
+
+    // Check the size before loading anything into memory
+    let limit = find_workspace_root(&path)
+        .and_then(|root| WorkspaceSettings::load(&root).max_binary_read_bytes)
+        .unwrap_or(DEFAULT_MAX_BINARY_READ_BYTES);
+    if metadata.size > limit {
+        return Err(HibiscusError::FileTooLarge {
+            path: path.to_string_lossy().into(),
+            size: metadata.size,
+            limit,
+        });
+    }
+
+    let content = storage::read(&path).await.map_err(|e| {
+        HibiscusError::Io(format!("Failed to read binary file '{}': {}", path.display(), e))
+    })?;
+
+    Ok(BinaryFile {
+        mime: detect_mime(&content, &path).to_string(),
+        size: content.len() as u64,
+        data: base64::engine::general_purpose::STANDARD.encode(&content),
+    })
+}
+
+/// Default ceiling for `write_binary_file` when the workspace sets no
+/// `max_file_write_bytes` (50 MB).
+pub const DEFAULT_MAX_BINARY_WRITE_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Writes base64-encoded bytes to a file, e.g. a pasted screenshot or an
+/// attachment.
+///
+/// Uses the same crash-safe strategy as `write_text_file` (temp file, sync,
+/// rename) and creates missing parent directories.
+///
+/// # Arguments
+/// * `path` - Absolute path to the file to write
+/// * `data_base64` - File contents, standard base64
+///
+/// # Returns
+/// * `Ok(u64)` - Number of bytes written
+/// * `Err(HibiscusError::Serialization)` - If `data_base64` is not valid
+///   base64; nothing is written
+/// * `Err(HibiscusError::QuotaExceeded)` - If the data is larger than the
+///   workspace's `max_file_write_bytes` (default 50 MB); nothing is written
+/// * `Err(HibiscusError)` - If the write failed
+///
+/// # Security
+/// Path is validated to prevent directory traversal attacks. In workspaces
+/// with `workspace_scoped` set, symlinks must resolve inside the root and
+/// the write goes to the symlink's target.
+#[tauri::command]
+pub async fn write_binary_file(path: String, data_base64: String) -> Result<u64, HibiscusError> {
+    use base64::Engine;
+
+    let path = PathBuf::from(&path);
+
+    // Validate the path
+    validate_path(&path)?;
+    let path = scope_to_workspace(&path)?;
+
+    // Reject oversized payloads before decoding them; the estimate counts
+    // padding, so it can be up to 2 bytes high
+    let limit = find_workspace_root(&path)
+        .and_then(|root| WorkspaceSettings::load(&root).max_file_write_bytes)
+        .unwrap_or(DEFAULT_MAX_BINARY_WRITE_BYTES);
+    let estimate = base64::decoded_len_estimate(data_base64.len()) as u64;
+    if estimate > limit + 2 {
+        return Err(HibiscusError::QuotaExceeded { size: estimate, limit });
+    }
+
+    let content = base64::engine::general_purpose::STANDARD
+        .decode(data_base64.trim())
+        .map_err(|e| HibiscusError::Serialization(format!("Invalid base64 data: {}", e)))?;
+    let size = content.len() as u64;
+    if size > limit {
+        return Err(HibiscusError::QuotaExceeded { size, limit });
+    }
+
+    save_atomically(&path, &content).await?;
+
+    Ok(size)
+}
+
+/// MIME type of a file from its first bytes, falling back to its extension.
+fn detect_mime(content: &[u8], path: &Path) -> &'static str {
+    const MAGIC: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"%PDF-", "application/pdf"),
+        (b"BM", "image/bmp"),
+        (b"II*\0", "image/tiff"),
+        (b"MM\0*", "image/tiff"),
+    ];
+    if let Some((_, mime)) = MAGIC.iter().find(|(magic, _)| content.starts_with(magic)) {
+        return mime;
+    }
+    if content.len() >= 12 && &content[..4] == b"RIFF" && &content[8..12] == b"WEBP" {
+        return "image/webp";
+    }
+
+    // SVG is text: look for the root element near the start
+    let head = String::from_utf8_lossy(&content[..content.len().min(1024)]);
+    let head = head.trim_start_matches('\u{feff}').trim_start();
+    if (head.starts_with("<svg") || head.starts_with("<?xml")) && head.contains("<svg") {
+        return "image/svg+xml";
+    }
+
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "bmp" => "image/bmp",
+        "tif" | "tiff" => "image/tiff",
+        "ico" => "image/x-icon",
+        "pdf" => "application/pdf",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Moves or renames a file or directory.
 ///
 /// # Arguments
 /// * `source` - Absolute path of the item to move
@@ -377,7 +1354,18 @@ pub async fn read_file_binary(path: String) -> Result<Vec<u8>, HibiscusError> {
 ///
 /// # Returns
 /// * `Ok(())` - If the move was successful
+/// * `Err(HibiscusError::ParentNotFound)` - If the destination's folder is missing
 /// * `Err(HibiscusError)` - If the move failed
+///
+/// # Notes
+/// Works for files and directories. An existing destination is never
+/// overwritten. This is the explorer's `from`/`to` rename; `rename_path`
+/// takes a new name within the same folder and records an undo step.
+///
+/// # Security
+/// Paths are validated to prevent directory traversal attacks. In workspaces
+/// with `workspace_scoped` set, the folders of both paths must resolve inside
+/// the root.
 #[tauri::command]
 pub async fn move_node(source: String, destination: String) -> Result<(), HibiscusError> {
     let source = PathBuf::from(&source);
@@ -386,19 +1374,27 @@ pub async fn move_node(source: String, destination: String) -> Result<(), Hibisc
     // Validate both paths
     validate_path(&source)?;
     validate_path(&destination)?;
+    let source = scope_entry_to_workspace(&source)?;
+    let destination = scope_entry_to_workspace(&destination)?;
     
-    if !source.exists() {
+    let storage = storage::storage_for(&source);
+    if !storage.exists(&source) {
         return Err(HibiscusError::FileNotFound(source.to_string_lossy().into()));
     }
     
-    if destination.exists() {
+    if storage.exists(&destination) {
         return Err(HibiscusError::Io(format!(
             "Destination already exists: '{}'", 
             destination.display()
         )));
     }
+
+    let parent_is_dir = |parent: &Path| storage.metadata(parent).is_ok_and(|meta| meta.is_dir);
+    if let Some(parent) = destination.parent().filter(|p| !parent_is_dir(p)) {
+        return Err(HibiscusError::ParentNotFound(parent.to_string_lossy().into()));
+    }
     
-    fs::rename(&source, &destination).await.map_err(|e| {
+    storage::rename(&source, &destination).await.map_err(|e| {
         HibiscusError::Io(format!(
             "Failed to move '{}' to '{}': {}",
             source.display(),
@@ -406,6 +1402,2113 @@ pub async fn move_node(source: String, destination: String) -> Result<(), Hibisc
             e
         ))
     })?;
+
+    // The watcher will see a remove and a create; neither is external
+    SELF_WRITES.suppress(&source);
+    SELF_WRITES.suppress(&destination);
+
+    // Point stored references at the new location
+    let change = FileChange::Renamed {
+        from: source.to_string_lossy().into(),
+        to: destination.to_string_lossy().into(),
+    };
+    reconcile_after(&destination, change).await;
     
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Moves a file or folder, falling back to copy-then-delete across devices.
+///
+/// # Arguments
+/// * `src` - Absolute path of the item to move
+/// * `dest` - Absolute path of the new location
+/// * `overwrite` - Replace an existing file at `dest` (default false).
+///   Existing folders are never replaced
+///
+/// # Returns
+/// * `Ok(String)` - The absolute path after the move
+/// * `Err(HibiscusError::AlreadyExists)` - If `dest` exists and may not be
+///   replaced
+/// * `Err(HibiscusError::ParentNotFound)` - If the folder of `dest` is missing
+/// * `Err(HibiscusError::PathValidation)` - If a folder would be moved into
+///   one of its own descendants
+/// * `Err(HibiscusError)` - If the move failed
+///
+/// # Notes
+/// Moving an item onto itself does nothing. When `src` and `dest` are on
+/// different mount points a rename fails with EXDEV, so the item is copied
+/// and the source deleted instead; a failed copy is removed again and the
+/// source kept.
+///
+/// # Security
+/// Paths are validated to prevent directory traversal attacks. In workspaces
+/// with `workspace_scoped` set, the folders of both paths must resolve inside
+/// the root.
+#[tauri::command]
+pub async fn move_file(src: String, dest: String, overwrite: Option<bool>) -> Result<String, HibiscusError> {
+    let source = PathBuf::from(&src);
+    let destination = PathBuf::from(&dest);
+
+    // Validate both paths
+    validate_path(&source)?;
+    validate_path(&destination)?;
+    let source = scope_entry_to_workspace(&source)?;
+    let destination = scope_entry_to_workspace(&destination)?;
+
+    if !source.exists() {
+        return Err(HibiscusError::FileNotFound(source.to_string_lossy().into()));
+    }
+    if destination.exists()
+        && is_same_file(&source, &destination)
+        && source.file_name() == destination.file_name()
+    {
+        return Ok(dest);
+    }
+
+    let Some(parent) = destination.parent().filter(|p| p.is_dir()) else {
+        let parent = destination.parent().unwrap_or(&destination);
+        return Err(HibiscusError::ParentNotFound(parent.to_string_lossy().into()));
+    };
+    if source.is_dir() && fs::canonicalize(parent).await?.starts_with(fs::canonicalize(&source).await?) {
+        return Err(HibiscusError::PathValidation(format!(
+            "Cannot move '{}' into itself ('{}')",
+            source.display(),
+            destination.display()
+        )));
+    }
+
+    let case_only = destination.exists() && is_same_file(&source, &destination);
+    let replace = destination.exists() && !case_only;
+    if replace && (!overwrite.unwrap_or(false) || destination.is_dir() || source.is_dir()) {
+        return Err(HibiscusError::AlreadyExists(destination.to_string_lossy().into()));
+    }
+
+    // Windows can't rename over an existing file
+    #[cfg(target_os = "windows")]
+    if replace {
+        fs::remove_file(&destination).await.map_err(|e| {
+            HibiscusError::Io(format!("Failed to replace '{}': {}", destination.display(), e))
+        })?;
+    }
+
+    // The watcher will see a remove and a create; neither is external
+    SELF_WRITES.suppress(&source);
+    SELF_WRITES.suppress(&destination);
+
+    if case_only {
+        rename_via_temp(&source, &destination).await?;
+    } else {
+        match fs::rename(&source, &destination).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+                move_across_devices(&source, &destination).await?;
+            }
+            Err(e) => {
+                return Err(HibiscusError::Io(format!(
+                    "Failed to move '{}' to '{}': {}",
+                    source.display(),
+                    destination.display(),
+                    e
+                )))
+            }
+        }
+    }
+
+    // Point stored references at the new location
+    let change = FileChange::Renamed {
+        from: source.to_string_lossy().into(),
+        to: destination.to_string_lossy().into(),
+    };
+    reconcile_after(&destination, change).await;
+
+    Ok(dest)
+}
+
+/// Moves by copying `source` to `destination` and then deleting `source`,
+/// for moves a rename cannot do (different mount points).
+///
+/// Symlinked folders inside a moved folder are not copied (see `copy_dir`).
+async fn move_across_devices(source: &Path, destination: &Path) -> Result<(), HibiscusError> {
+    if source.is_dir() {
+        if let Err(e) = copy_dir(source, destination).await {
+            let _ = fs::remove_dir_all(destination).await;
+            return Err(e);
+        }
+        fs::remove_dir_all(source).await
+    } else {
+        fs::copy(source, destination).await.map_err(|e| {
+            HibiscusError::Io(format!(
+                "Failed to copy '{}' to '{}': {}",
+                source.display(),
+                destination.display(),
+                e
+            ))
+        })?;
+        fs::remove_file(source).await
+    }
+    .map_err(|e| {
+        HibiscusError::Io(format!(
+            "Copied '{}' to '{}' but failed to remove the original: {}",
+            source.display(),
+            destination.display(),
+            e
+        ))
+    })
+}
+
+/// Result of `rename_path`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RenamedPath {
+    /// The absolute path after the rename
+    pub path: String,
+    /// Token for `undo_rename`
+    pub undo_token: String,
+}
+
+/// Renames a file or directory in place.
+///
+/// # Arguments
+/// * `path` - Absolute path of the item to rename
+/// * `new_name` - New file or folder name (no path separators)
+///
+/// # Returns
+/// * `Ok(RenamedPath)` - The new path and a token that undoes the rename
+/// * `Err(HibiscusError)` - If the name is invalid or the rename failed
+///
+/// # Notes
+/// Delegates to `move_node`, so references are updated and the watcher
+/// events caused by the rename are not reported as external changes.
+#[tauri::command]
+pub async fn rename_path(
+    path: String,
+    new_name: String,
+    history: State<'_, RenameHistory>,
+) -> Result<RenamedPath, HibiscusError> {
+    rename_recorded(&history, path, new_name).await
+}
+
+async fn rename_recorded(
+    history: &RenameHistory,
+    path: String,
+    new_name: String,
+) -> Result<RenamedPath, HibiscusError> {
+    if new_name.is_empty()
+        || new_name == "."
+        || new_name.contains(['/', '\\'])
+    {
+        return Err(HibiscusError::PathValidation(format!(
+            "Invalid name: '{}'",
+            new_name
+        )));
+    }
+
+    let source = PathBuf::from(&path);
+    let parent = source.parent().ok_or_else(|| {
+        HibiscusError::PathValidation(format!("Cannot rename '{}'", path))
+    })?;
+    let destination = parent.join(&new_name);
+
+    move_node(path, destination.to_string_lossy().to_string()).await?;
+    let undo_token = history.record(source, destination.clone())?;
+    Ok(RenamedPath {
+        path: destination.to_string_lossy().to_string(),
+        undo_token,
+    })
+}
+
+/// Reverses a rename made by `rename_path`.
+///
+/// # Arguments
+/// * `token` - The `undo_token` the rename returned
+///
+/// # Returns
+/// * `Ok(String)` - The absolute path the item is back at
+/// * `Err(HibiscusError::FileNotFound)` - If the renamed item has been moved
+///   or deleted since
+/// * `Err(HibiscusError::AlreadyExists)` - If something else now has the
+///   old name
+/// * `Err(HibiscusError)` - If the token is unknown or the move failed
+#[tauri::command]
+pub async fn undo_rename(
+    token: String,
+    history: State<'_, RenameHistory>,
+) -> Result<String, HibiscusError> {
+    undo_recorded(&history, &token).await
+}
+
+async fn undo_recorded(history: &RenameHistory, token: &str) -> Result<String, HibiscusError> {
+    let record = history.get(token)?;
+
+    if !record.to.exists() {
+        return Err(HibiscusError::FileNotFound(record.to.to_string_lossy().into()));
+    }
+    if record.from.exists() && !is_same_file(&record.from, &record.to) {
+        return Err(HibiscusError::AlreadyExists(record.from.to_string_lossy().into()));
+    }
+
+    let restored = record.from.to_string_lossy().to_string();
+    if record.from.exists() {
+        // Case-only rename on a case-insensitive filesystem
+        rename_via_temp(&record.to, &record.from).await?;
+        let change = FileChange::Renamed {
+            from: record.to.to_string_lossy().into(),
+            to: restored.clone(),
+        };
+        reconcile_after(&record.from, change).await;
+    } else {
+        move_node(record.to.to_string_lossy().into(), restored.clone()).await?;
+    }
+    history.remove(token);
+    Ok(restored)
+}
+
+/// Renames (or moves) a file, handling case-only renames.
+///
+/// # Arguments
+/// * `old_path` - Absolute path of the file to rename
+/// * `new_path` - Absolute path of the new name
+/// * `overwrite` - Replace an existing file at `new_path` (default false)
+///
+/// # Returns
+/// * `Ok(String)` - The canonical path after the rename
+/// * `Err(HibiscusError::FileNotFound)` - If `old_path` does not exist
+/// * `Err(HibiscusError::AlreadyExists)` - If `new_path` exists and
+///   `overwrite` is not set (or it is a folder)
+/// * `Err(HibiscusError)` - If the rename failed
+///
+/// # Notes
+/// Missing parent folders of `new_path` are created. On case-insensitive
+/// filesystems `Notes.md` -> `notes.md` finds the source itself at the
+/// target, so it is renamed through a temporary name instead.
+///
+/// # Security
+/// Paths are validated to prevent directory traversal attacks. In workspaces
+/// with `workspace_scoped` set, the folders of both paths must resolve inside
+/// the root.
+#[tauri::command]
+pub async fn rename_file(
+    old_path: String,
+    new_path: String,
+    overwrite: Option<bool>,
+) -> Result<String, HibiscusError> {
+    let source = PathBuf::from(&old_path);
+    let destination = PathBuf::from(&new_path);
+
+    // Validate both paths
+    validate_path(&source)?;
+    validate_path(&destination)?;
+    let source = scope_entry_to_workspace(&source)?;
+    let destination = scope_entry_to_workspace(&destination)?;
+
+    if !source.exists() {
+        return Err(HibiscusError::FileNotFound(source.to_string_lossy().into()));
+    }
+    if !source.is_file() {
+        return Err(HibiscusError::InvalidPathType {
+            path: source.to_string_lossy().into(),
+            expected: "file".into(),
+            actual: "directory".into(),
+        });
+    }
+
+    let case_only = destination.exists() && is_same_file(&source, &destination);
+    if destination.exists()
+        && !case_only
+        && (!overwrite.unwrap_or(false) || !destination.is_file())
+    {
+        return Err(HibiscusError::AlreadyExists(destination.display().to_string()));
+    }
+
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent).await.map_err(|e| {
+            HibiscusError::Io(format!(
+                "Failed to create parent directories for '{}': {}",
+                destination.display(),
+                e
+            ))
+        })?;
+    }
+
+    // The watcher will see a remove and a create; neither is external
+    SELF_WRITES.suppress(&source);
+    SELF_WRITES.suppress(&destination);
+
+    if case_only {
+        rename_via_temp(&source, &destination).await?;
+    } else {
+        fs::rename(&source, &destination).await.map_err(|e| {
+            HibiscusError::Io(format!(
+                "Failed to rename '{}' to '{}': {}",
+                source.display(),
+                destination.display(),
+                e
+            ))
+        })?;
+    }
+
+    // Point stored references at the new location
+    let change = FileChange::Renamed {
+        from: source.to_string_lossy().into(),
+        to: destination.to_string_lossy().into(),
+    };
+    reconcile_after(&destination, change).await;
+
+    let canonical = fs::canonicalize(&destination).await.unwrap_or(destination);
+    Ok(canonical.to_string_lossy().to_string())
+}
+
+/// Returns `true` if both paths name the same file on disk, e.g. two
+/// spellings of one name on a case-insensitive filesystem.
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        match (std::fs::metadata(a), std::fs::metadata(b)) {
+            (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+            _ => false,
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Renames in two steps through a temporary sibling name, so a rename the
+/// filesystem sees as a no-op (case change only) still takes effect. The
+/// temporary name is one that is not taken, so nothing is overwritten.
+async fn rename_via_temp(source: &Path, destination: &Path) -> Result<(), HibiscusError> {
+    let name = source
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let base = source.with_file_name(format!(".{}.rename-tmp", name));
+    let temp = (0..=MAX_AUTO_RENAME)
+        .map(|attempt| numbered_path(&base, attempt))
+        .find(|temp| std::fs::symlink_metadata(temp).is_err())
+        .ok_or_else(|| HibiscusError::AlreadyExists(base.display().to_string()))?;
+    SELF_WRITES.suppress(&temp);
+
+    fs::rename(source, &temp).await.map_err(|e| {
+        HibiscusError::Io(format!("Failed to rename '{}': {}", source.display(), e))
+    })?;
+    if let Err(e) = fs::rename(&temp, destination).await {
+        // Put the file back under its old name
+        let _ = fs::rename(&temp, source).await;
+        return Err(HibiscusError::Io(format!(
+            "Failed to rename '{}' to '{}': {}",
+            source.display(),
+            destination.display(),
+            e
+        )));
+    }
+    Ok(())
+}
+
+/// What a `copy_path` call copied.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct CopyReport {
+    pub files: u64,
+    /// Folders created, including the top-level one for a folder copy
+    pub folders: u64,
+    pub bytes: u64,
+}
+
+/// Copies a file, or a folder with its whole subtree.
+///
+/// # Arguments
+/// * `from` - Absolute path of the file or folder to copy
+/// * `to` - Absolute path of the copy
+///
+/// # Returns
+/// * `Ok(CopyReport)` - Number of files, folders and bytes copied
+/// * `Err(HibiscusError::AlreadyExists)` - If `to` exists
+/// * `Err(HibiscusError::ParentNotFound)` - If the folder of `to` is missing
+/// * `Err(HibiscusError::PathValidation)` - If a folder would be copied
+///   into itself
+/// * `Err(HibiscusError)` - If the copy failed; a partial copy is removed
+///
+/// # Notes
+/// File contents are copied byte for byte. Symlinked files are copied as
+/// the files they point to; symlinked folders are skipped, so a link cycle
+/// cannot make the copy run forever.
+///
+/// # Security
+/// Paths are validated to prevent directory traversal attacks. In workspaces
+/// with `workspace_scoped` set, both paths must resolve inside the root.
+#[tauri::command]
+pub async fn copy_path(from: String, to: String) -> Result<CopyReport, HibiscusError> {
+    let source = PathBuf::from(&from);
+    let destination = PathBuf::from(&to);
+
+    // Validate both paths
+    validate_path(&source)?;
+    validate_path(&destination)?;
+    let source = scope_to_workspace(&source)?;
+    let destination = scope_to_workspace(&destination)?;
+
+    check_copy_target(&source, &destination)?;
+
+    if !source.is_dir() {
+        let bytes = fs::copy(&source, &destination).await.map_err(|e| {
+            HibiscusError::Io(format!(
+                "Failed to copy '{}' to '{}': {}",
+                source.display(),
+                destination.display(),
+                e
+            ))
+        })?;
+        return Ok(CopyReport { files: 1, folders: 0, bytes });
+    }
+
+    let result = copy_dir(&source, &destination).await;
+    if result.is_err() {
+        let _ = fs::remove_dir_all(&destination).await;
+    }
+    result
+}
+
+/// Checks that `source` can be copied to `destination`: the source exists,
+/// the destination is free and its folder exists, and a folder is not being
+/// copied into itself.
+fn check_copy_target(source: &Path, destination: &Path) -> Result<(), HibiscusError> {
+    if !source.exists() {
+        return Err(HibiscusError::FileNotFound(source.to_string_lossy().into()));
+    }
+    if destination.exists() {
+        return Err(HibiscusError::AlreadyExists(destination.to_string_lossy().into()));
+    }
+    let Some(parent) = destination.parent().filter(|p| p.is_dir()) else {
+        let parent = destination.parent().unwrap_or(destination);
+        return Err(HibiscusError::ParentNotFound(parent.to_string_lossy().into()));
+    };
+
+    // Compare resolved paths so `..` or a symlinked parent cannot hide that
+    // the destination lies inside the source
+    if source.is_dir() && parent.canonicalize()?.starts_with(source.canonicalize()?) {
+        return Err(HibiscusError::PathValidation(format!(
+            "Cannot copy '{}' into itself ('{}')",
+            source.display(),
+            destination.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Copies the subtree of `source` into a new folder `destination`.
+async fn copy_dir(source: &Path, destination: &Path) -> Result<CopyReport, HibiscusError> {
+    let mut report = CopyReport::default();
+    let mut pending = vec![(source.to_path_buf(), destination.to_path_buf())];
+
+    while let Some((from, to)) = pending.pop() {
+        fs::create_dir(&to).await.map_err(|e| {
+            HibiscusError::Io(format!("Failed to create directory '{}': {}", to.display(), e))
+        })?;
+        report.folders += 1;
+
+        let mut entries = fs::read_dir(&from).await.map_err(|e| {
+            HibiscusError::Io(format!("Failed to read directory '{}': {}", from.display(), e))
+        })?;
+        while let Some(entry) = entries.next_entry().await? {
+            let file_type = entry.file_type().await?;
+            let target = to.join(entry.file_name());
+
+            if file_type.is_dir() {
+                pending.push((entry.path(), target));
+            } else if file_type.is_symlink() && entry.path().is_dir() {
+                continue;
+            } else {
+                report.bytes += fs::copy(entry.path(), &target).await.map_err(|e| {
+                    HibiscusError::Io(format!(
+                        "Failed to copy '{}': {}",
+                        entry.path().display(),
+                        e
+                    ))
+                })?;
+                report.files += 1;
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Copies a single file.
+///
+/// # Arguments
+/// * `src` - Absolute path of the file to copy
+/// * `dest` - Absolute path of the copy
+///
+/// # Returns
+/// * `Ok(CopyReport)` - One file and its size in bytes
+/// * `Err(HibiscusError::InvalidPathType)` - If `src` is a folder
+/// * `Err(HibiscusError::AlreadyExists)` - If `dest` exists
+/// * `Err(HibiscusError)` - If the copy failed
+///
+/// # Security
+/// Paths are validated to prevent directory traversal attacks. In workspaces
+/// with `workspace_scoped` set, both paths must resolve inside the root.
+#[tauri::command]
+pub async fn copy_file(src: String, dest: String) -> Result<CopyReport, HibiscusError> {
+    let source = PathBuf::from(&src);
+    validate_path(&source)?;
+    let source = scope_to_workspace(&source)?;
+
+    if source.is_dir() {
+        return Err(HibiscusError::InvalidPathType {
+            path: src,
+            expected: "file".into(),
+            actual: "directory".into(),
+        });
+    }
+    copy_path(src, dest).await
+}
+
+/// Longest file name, in bytes, most file systems accept.
+const MAX_NAME_BYTES: usize = 255;
+
+/// Copies a file next to itself as `Name copy.ext`, `Name copy 2.ext`, ...
+///
+/// # Arguments
+/// * `path` - Absolute path of the file to duplicate
+///
+/// # Returns
+/// * `Ok(String)` - Absolute path of the copy
+/// * `Err(HibiscusError::FileNotFound)` - If `path` does not exist
+/// * `Err(HibiscusError::InvalidPathType)` - If `path` is a folder
+/// * `Err(HibiscusError)` - If the copy failed; nothing is left behind
+///
+/// # Notes
+/// Existing files are never overwritten. Names that would get too long
+/// for the file system have their stem shortened to fit the suffix.
+///
+/// # Security
+/// Path is validated to prevent directory traversal attacks. In workspaces
+/// with `workspace_scoped` set, the file must resolve inside the root.
+#[tauri::command]
+pub async fn duplicate_file(path: String) -> Result<String, HibiscusError> {
+    let source = PathBuf::from(&path);
+    validate_path(&source)?;
+    // The copy goes next to the entry; what it reads must be in the workspace
+    let source = scope_entry_to_workspace(&source)?;
+    scope_to_workspace(&source)?;
+
+    if !source.exists() {
+        return Err(HibiscusError::FileNotFound(path));
+    }
+    if source.is_dir() {
+        return Err(HibiscusError::InvalidPathType {
+            path,
+            expected: "file".into(),
+            actual: "directory".into(),
+        });
+    }
+    let name = source
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    // Reserve the name with create_new, so a name taken between the check
+    // and the copy is never overwritten
+    let mut attempt = 1;
+    let copy = loop {
+        let candidate = source.with_file_name(copy_name(&name, attempt));
+        validate_path(&candidate)?;
+        let result = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&candidate)
+            .await;
+        match result {
+            Ok(_) => break candidate,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                if attempt == MAX_AUTO_RENAME {
+                    return Err(HibiscusError::AlreadyExists(candidate.display().to_string()));
+                }
+                attempt += 1;
+            }
+            Err(e) => {
+                return Err(HibiscusError::Io(format!(
+                    "Failed to create '{}': {}",
+                    candidate.display(),
+                    e
+                )))
+            }
+        }
+    };
+
+    if let Err(e) = fs::copy(&source, &copy).await {
+        let _ = fs::remove_file(&copy).await;
+        return Err(HibiscusError::Io(format!(
+            "Failed to copy '{}' to '{}': {}",
+            source.display(),
+            copy.display(),
+            e
+        )));
+    }
+    Ok(copy.to_string_lossy().into_owned())
+}
+
+/// `name` with ` copy` (attempt 1) or ` copy {n}` before its extension:
+/// `notes.draft.md` -> `notes.draft copy 2.md`. The stem is shortened at a
+/// character boundary if the result would exceed `MAX_NAME_BYTES`.
+fn copy_name(name: &str, attempt: usize) -> String {
+    let suffix = if attempt == 1 {
+        " copy".to_string()
+    } else {
+        format!(" copy {}", attempt)
+    };
+    let path = Path::new(name);
+    let stem = path.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+
+    let budget = MAX_NAME_BYTES.saturating_sub(suffix.len() + extension.len());
+    let mut end = stem.len().min(budget);
+    while !stem.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}{}{}", &stem[..end], suffix, extension)
+}
+
+/// Emit a `copy-progress` event every this many copied files.
+const COPY_PROGRESS_INTERVAL: u64 = 25;
+
+/// Payload of the `copy-progress` event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct CopyProgress {
+    pub files_copied: u64,
+    pub files_total: u64,
+    pub bytes_copied: u64,
+    pub bytes_total: u64,
+}
+
+/// An item `copy_folder` could not copy.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct CopyFailure {
+    /// Path relative to the source folder
+    pub path: String,
+    pub error: String,
+}
+
+/// What a `copy_folder` call copied and what it could not.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct FolderCopyReport {
+    pub files: u64,
+    /// Folders created, including the top-level one
+    pub folders: u64,
+    pub bytes: u64,
+    /// Items that failed; everything else was copied
+    pub failed: Vec<CopyFailure>,
+}
+
+/// Copies a folder with its visible contents into a new folder.
+///
+/// Dotfiles and dot-folders are skipped, like in the file tree. Unlike
+/// `copy_path`, a file that cannot be copied does not stop the copy: it is
+/// listed in `failed` and the rest continues.
+///
+/// # Arguments
+/// * `src` - Absolute path of the folder to copy
+/// * `dest` - Absolute path of the new folder
+///
+/// # Events Emitted
+/// * `copy-progress` - `{ files_copied, files_total, bytes_copied,
+///   bytes_total }` while the copy runs, and once at the end
+///
+/// # Returns
+/// * `Ok(FolderCopyReport)` - Counts and per-item failures
+/// * `Err(HibiscusError::PathValidation)` - If `dest` is inside `src`
+/// * `Err(HibiscusError)` - If `src` is not a folder or `dest` is taken or
+///   could not be created; nothing was copied
+///
+/// # Security
+/// Paths are validated to prevent directory traversal attacks. In workspaces
+/// with `workspace_scoped` set, both paths must resolve inside the root.
+#[tauri::command]
+pub async fn copy_folder(
+    src: String,
+    dest: String,
+    window: tauri::Window,
+) -> Result<FolderCopyReport, HibiscusError> {
+    let source = PathBuf::from(&src);
+    let destination = PathBuf::from(&dest);
+
+    // Validate both paths
+    validate_path(&source)?;
+    validate_path(&destination)?;
+    let source = scope_to_workspace(&source)?;
+    let destination = scope_to_workspace(&destination)?;
+
+    if source.exists() && !source.is_dir() {
+        return Err(HibiscusError::InvalidPathType {
+            path: src,
+            expected: "directory".into(),
+            actual: "file".into(),
+        });
+    }
+
+    tokio::task::spawn_blocking(move || {
+        copy_folder_blocking(&source, &destination, |progress| {
+            let _ = window.emit("copy-progress", progress);
+        })
+    })
+    .await
+    .map_err(|e| HibiscusError::Io(format!("Copy task failed: {}", e)))?
+}
+
+/// Blocking implementation of `copy_folder`.
+///
+/// `on_progress` is called periodically and once at the end.
+pub fn copy_folder_blocking(
+    source: &Path,
+    destination: &Path,
+    mut on_progress: impl FnMut(CopyProgress),
+) -> Result<FolderCopyReport, HibiscusError> {
+    check_copy_target(source, destination)?;
+
+    let mut report = FolderCopyReport::default();
+    let mut entries = Vec::new();
+    plan_folder_copy(source, Path::new(""), &mut entries, &mut report.failed);
+
+    let mut progress = CopyProgress {
+        files_copied: 0,
+        files_total: entries.iter().filter(|(_, size)| size.is_some()).count() as u64,
+        bytes_copied: 0,
+        bytes_total: entries.iter().filter_map(|(_, size)| *size).sum(),
+    };
+
+    std::fs::create_dir(destination).map_err(|e| {
+        HibiscusError::Io(format!("Failed to create directory '{}': {}", destination.display(), e))
+    })?;
+    report.folders += 1;
+    on_progress(progress);
+
+    for (relative, size) in entries {
+        let target = destination.join(&relative);
+        let result = match size {
+            None => std::fs::create_dir(&target).map(|_| report.folders += 1),
+            Some(_) => std::fs::copy(source.join(&relative), &target).map(|bytes| {
+                report.files += 1;
+                report.bytes += bytes;
+                progress.files_copied += 1;
+                progress.bytes_copied += bytes;
+            }),
+        };
+
+        if let Err(e) = result {
+            report.failed.push(CopyFailure {
+                path: relative.to_string_lossy().replace('\\', "/"),
+                error: e.to_string(),
+            });
+        } else if size.is_some() && progress.files_copied.is_multiple_of(COPY_PROGRESS_INTERVAL) {
+            on_progress(progress);
+        }
+    }
+
+    on_progress(progress);
+    Ok(report)
+}
+
+/// Lists the visible items under `dir` in copy order (each folder before
+/// its contents): relative path and, for files, the size in bytes.
+fn plan_folder_copy(
+    dir: &Path,
+    relative: &Path,
+    entries: &mut Vec<(PathBuf, Option<u64>)>,
+    failed: &mut Vec<CopyFailure>,
+) {
+    let read = match std::fs::read_dir(dir) {
+        Ok(read) => read,
+        Err(e) => {
+            failed.push(CopyFailure {
+                path: relative.to_string_lossy().replace('\\', "/"),
+                error: e.to_string(),
+            });
+            return;
+        }
+    };
+
+    let mut children: Vec<_> = read.filter_map(Result::ok).collect();
+    children.sort_by_key(|entry| entry.file_name());
+
+    for entry in children {
+        let name = entry.file_name();
+        if name.to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let child = relative.join(&name);
+        let path = entry.path();
+        let is_symlink = entry.file_type().is_ok_and(|t| t.is_symlink());
+
+        match std::fs::metadata(&path) {
+            // Symlinked folders are not followed, so link cycles cannot recurse
+            Ok(meta) if meta.is_dir() && is_symlink => {}
+            Ok(meta) if meta.is_dir() => {
+                entries.push((child.clone(), None));
+                plan_folder_copy(&path, &child, entries, failed);
+            }
+            Ok(meta) => entries.push((child, Some(meta.len()))),
+            Err(e) => failed.push(CopyFailure {
+                path: child.to_string_lossy().replace('\\', "/"),
+                error: e.to_string(),
+            }),
+        }
+    }
+}
+
+/// Metadata for one entry of a `stat_paths` request.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct PathStat {
+    /// The path as given in the request
+    pub path: String,
+    pub exists: bool,
+    /// Size in bytes (files only)
+    pub size: Option<u64>,
+    /// Last modification time, milliseconds since the Unix epoch
+    pub modified_ms: Option<u64>,
+    pub is_dir: Option<bool>,
+    /// Set when the path was invalid or could not be inspected
+    pub error: Option<String>,
+}
+
+/// Reads metadata for many paths in one call.
+///
+/// # Arguments
+/// * `paths` - Absolute paths to inspect
+///
+/// # Returns
+/// * `Ok(Vec<PathStat>)` - One entry per input path, in input order
+///
+/// # Notes
+/// Missing paths are reported with `exists: false`, not as errors. A path
+/// that fails validation or cannot be read carries an `error` instead of
+/// failing the whole batch.
+#[tauri::command]
+pub async fn stat_paths(paths: Vec<String>) -> Result<Vec<PathStat>, HibiscusError> {
+    tokio::task::spawn_blocking(move || paths.into_iter().map(stat_path).collect())
+        .await
+        .map_err(|e| HibiscusError::Io(format!("Stat task failed: {}", e)))
+}
+
+fn stat_path(path: String) -> PathStat {
+    let mut stat = PathStat {
+        path,
+        exists: false,
+        size: None,
+        modified_ms: None,
+        is_dir: None,
+        error: None,
+    };
+
+    // Validate path
+    if let Err(e) = validate_path(Path::new(&stat.path)) {
+        stat.error = Some(e.to_string());
+        return stat;
+    }
+
+    match std::fs::metadata(&stat.path) {
+        Ok(meta) => {
+            stat.exists = true;
+            stat.is_dir = Some(meta.is_dir());
+            stat.size = meta.is_file().then_some(meta.len());
+            stat.modified_ms = meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as u64);
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => stat.error = Some(e.to_string()),
+    }
+
+    stat
+}
+
+/// Reads the metadata of one file or folder: size, modified and created
+/// times, read-only flag and kind.
+///
+/// # Arguments
+/// * `path` - Absolute path to inspect
+///
+/// # Returns
+/// * `Ok(FileMetadata)` - Metadata; symlinks report their target's
+/// * `Err(HibiscusError::FileNotFound)` - If nothing exists at `path`
+/// * `Err(HibiscusError)` - If the path is invalid or cannot be inspected
+#[tauri::command]
+pub async fn get_file_metadata(path: String) -> Result<FileMetadata, HibiscusError> {
+    let path = PathBuf::from(&path);
+
+    // Validate the path
+    validate_path(&path)?;
+
+    tokio::task::spawn_blocking(move || {
+        FileMetadata::read(&path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => HibiscusError::FileNotFound(path.to_string_lossy().into()),
+            _ => HibiscusError::Io(format!("Failed to read metadata of '{}': {}", path.display(), e)),
+        })
+    })
+    .await
+    .map_err(|e| HibiscusError::Io(format!("Metadata task failed: {}", e)))?
+}
+
+/// Symlink status of a path, see `resolve_symlink`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct SymlinkInfo {
+    /// Whether `path` itself is a symbolic link
+    pub is_symlink: bool,
+    /// Final target with all links followed
+    pub resolved: String,
+}
+
+/// Reports whether a path is a symlink and where it leads, refusing targets
+/// outside the workspace.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+/// * `path` - Path inside the workspace
+///
+/// # Returns
+/// * `Ok(SymlinkInfo)` - Link status and resolved target
+/// * `Err(HibiscusError::PathValidation)` - If the target escapes `root`
+#[tauri::command]
+pub async fn resolve_symlink(root: String, path: String) -> Result<SymlinkInfo, HibiscusError> {
+    let root = PathBuf::from(&root);
+    let path = PathBuf::from(&path);
+
+    // Validate paths
+    validate_path(&root)?;
+    validate_path(&path)?;
+
+    let is_symlink = fs::symlink_metadata(&path)
+        .await
+        .is_ok_and(|metadata| metadata.file_type().is_symlink());
+    let resolved = resolve_within_root(&root, &path)?;
+
+    Ok(SymlinkInfo {
+        is_symlink,
+        resolved: resolved.to_string_lossy().into(),
+    })
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::Timestamp;
+    use crate::workspace::NodeType;
+    use tempfile::tempdir;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_write_syncs_parent_directory() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("note.md");
+
+        // Exercises the directory fsync path on both create and overwrite.
+        write_text_file(path.to_string_lossy().to_string(), "one".into(), None, None, None, None, None)
+            .await
+            .unwrap();
+        write_text_file(path.to_string_lossy().to_string(), "two".into(), None, None, None, None, None)
+            .await
+            .unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "two");
+        assert!(crate::storage::sync_parent_dir(&path).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_write_keeps_permission_bits() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+        for expected in [0o644, 0o600] {
+            let path = dir.path().join(format!("note-{:o}.md", expected));
+            std::fs::write(&path, "before").unwrap();
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(expected)).unwrap();
+
+            write_text_file(path.to_string_lossy().to_string(), "after".into(), None, None, None, None, None)
+                .await
+                .unwrap();
+            assert_eq!(mode(&path), expected);
+            assert_eq!(std::fs::read_to_string(&path).unwrap(), "after");
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    #[tokio::test]
+    async fn test_write_keeps_hidden_attribute() {
+        use std::os::windows::fs::MetadataExt;
+        use windows::Win32::Storage::FileSystem::FILE_ATTRIBUTE_HIDDEN;
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("hidden.md");
+        std::fs::write(&path, "before").unwrap();
+        super::super::attributes::apply_hidden(&path, true, false).unwrap();
+
+        write_text_file(path.to_string_lossy().to_string(), "after".into(), None, None, None, None, None)
+            .await
+            .unwrap();
+        assert_ne!(std::fs::metadata(&path).unwrap().file_attributes() & FILE_ATTRIBUTE_HIDDEN.0, 0);
+    }
+
+    #[tokio::test]
+    async fn test_write_returns_saved_metadata() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("note.md");
+
+        let written = write_text_file(path.to_string_lossy().to_string(), "hello".into(), None, None, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(written.size, 5);
+        assert_eq!(PathBuf::from(&written.path), path);
+
+        let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+        let mtime = Timestamp::parse(&written.mtime).unwrap();
+        assert_eq!(mtime, Timestamp(chrono::DateTime::<chrono::Utc>::from(modified)));
+    }
+
+    #[tokio::test]
+    async fn test_create_file_replay_is_idempotent() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("inbox.md").to_string_lossy().to_string();
+        let store = IdempotencyStore::default();
+
+        let create = || {
+            store.run("create_file", Some("reload-1"), || create_new_file(path.clone(), None, false))
+        };
+        assert!(create().await.is_ok());
+        std::fs::write(&path, "typed after create").unwrap();
+
+        // The replay answers like the first call and leaves the file alone
+        assert!(create().await.is_ok());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "typed after create");
+
+        // Without the key the duplicate is still an error
+        let duplicate = store.run("create_file", None, || create_new_file(path.clone(), None, false));
+        assert!(duplicate.await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_create_file_and_folder_with_nested_parents() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("a").join("b").join("new.md");
+        let folder = dir.path().join("c").join("d").join("new");
+
+        create_new_file(file.to_string_lossy().into(), None, false).await.unwrap();
+        create_folder(folder.to_string_lossy().into(), None).await.unwrap();
+
+        assert_eq!(std::fs::metadata(&file).unwrap().len(), 0);
+        assert!(folder.is_dir());
+        assert_eq!(std::fs::read_dir(&folder).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_create_refuses_existing_targets() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("note.md");
+        let folder = dir.path().join("notes");
+        std::fs::write(&file, "keep me").unwrap();
+        std::fs::create_dir(&folder).unwrap();
+
+        let err = create_new_file(file.to_string_lossy().into(), None, false).await.unwrap_err();
+        assert!(matches!(err, HibiscusError::AlreadyExists(_)));
+        let err = create_folder(folder.to_string_lossy().into(), None).await.unwrap_err();
+        assert!(matches!(err, HibiscusError::AlreadyExists(_)));
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "keep me");
+    }
+
+    #[tokio::test]
+    async fn test_create_auto_rename_returns_created_node() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".hibiscus")).unwrap();
+        std::fs::write(dir.path().join(".hibiscus").join("workspace.json"), "{}").unwrap();
+        std::fs::create_dir_all(dir.path().join("inbox")).unwrap();
+        std::fs::write(dir.path().join("inbox").join("Untitled.md"), "first").unwrap();
+        std::fs::write(dir.path().join("inbox").join("Untitled 1.md"), "second").unwrap();
+        let file = dir.path().join("inbox").join("Untitled.md").to_string_lossy().to_string();
+
+        let node = create_new_file(file, Some("# New".into()), true).await.unwrap();
+        assert_eq!(node.id, "inbox/Untitled 2.md");
+        assert_eq!(node.name, "Untitled 2.md");
+        assert!(matches!(node.node_type, NodeType::File));
+        let created = dir.path().join("inbox").join("Untitled 2.md");
+        assert_eq!(std::fs::read_to_string(created).unwrap(), "# New");
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("inbox").join("Untitled.md")).unwrap(),
+            "first"
+        );
+
+        let folder = dir.path().join("inbox").to_string_lossy().to_string();
+        let node = create_folder(folder, Some(true)).await.unwrap();
+        assert_eq!(node.id, "inbox 1");
+        assert!(matches!(node.node_type, NodeType::Folder));
+        assert!(node.children.is_some_and(|children| children.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn test_copy_path_copies_subtree_byte_for_byte() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("notes");
+        std::fs::create_dir_all(source.join("sub").join("empty")).unwrap();
+        std::fs::write(source.join("a.md"), "alpha").unwrap();
+        let binary: Vec<u8> = (0..=255).collect();
+        std::fs::write(source.join("sub").join("img.bin"), &binary).unwrap();
+
+        let copy = dir.path().join("notes copy");
+        let report = copy_path(source.to_string_lossy().into(), copy.to_string_lossy().into())
+            .await
+            .unwrap();
+
+        assert_eq!(report, CopyReport { files: 2, folders: 3, bytes: 5 + 256 });
+        assert_eq!(std::fs::read_to_string(copy.join("a.md")).unwrap(), "alpha");
+        assert_eq!(std::fs::read(copy.join("sub").join("img.bin")).unwrap(), binary);
+        assert!(copy.join("sub").join("empty").is_dir());
+
+        // A single file, and an existing destination
+        let file_copy = dir.path().join("a copy.md");
+        let report = copy_path(
+            source.join("a.md").to_string_lossy().into(),
+            file_copy.to_string_lossy().into(),
+        )
+        .await
+        .unwrap();
+        assert_eq!((report.files, report.bytes), (1, 5));
+        let err = copy_path(source.to_string_lossy().into(), copy.to_string_lossy().into())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, HibiscusError::AlreadyExists(_)));
+    }
+
+    #[tokio::test]
+    async fn test_copy_path_rejects_copy_into_itself() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("notes");
+        std::fs::create_dir_all(source.join("sub")).unwrap();
+
+        for inside in [source.join("copy"), source.join("sub").join("copy")] {
+            let err = copy_path(source.to_string_lossy().into(), inside.to_string_lossy().into())
+                .await
+                .unwrap_err();
+            assert!(matches!(err, HibiscusError::PathValidation(_)));
+            assert!(!inside.exists());
+        }
+
+        // A sibling sharing the name prefix is fine
+        let sibling = dir.path().join("notes-2");
+        copy_path(source.to_string_lossy().into(), sibling.to_string_lossy().into())
+            .await
+            .unwrap();
+        assert!(sibling.join("sub").is_dir());
+    }
+
+    #[tokio::test]
+    async fn test_undo_rename_restores_old_name() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("draft.md");
+        std::fs::write(&path, "text").unwrap();
+        let history = RenameHistory::default();
+
+        let renamed = rename_recorded(&history, path.to_string_lossy().into(), "final.md".into())
+            .await
+            .unwrap();
+        assert!(Path::new(&renamed.path).is_file());
+        assert!(!path.exists());
+
+        let restored = undo_recorded(&history, &renamed.undo_token).await.unwrap();
+        assert_eq!(restored, path.to_string_lossy());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "text");
+        assert!(!Path::new(&renamed.path).exists());
+
+        // The token is spent
+        assert!(undo_recorded(&history, &renamed.undo_token).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_undo_rename_refuses_stale_state() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("draft.md");
+        std::fs::write(&path, "text").unwrap();
+        let history = RenameHistory::default();
+
+        let renamed = rename_recorded(&history, path.to_string_lossy().into(), "final.md".into())
+            .await
+            .unwrap();
+
+        // Moved again: the rename can no longer be reversed
+        let moved = dir.path().join("elsewhere.md");
+        std::fs::rename(&renamed.path, &moved).unwrap();
+        let err = undo_recorded(&history, &renamed.undo_token).await.unwrap_err();
+        assert!(matches!(err, HibiscusError::FileNotFound(_)));
+        assert!(moved.is_file());
+
+        // Back in place but the old name is taken
+        std::fs::rename(&moved, &renamed.path).unwrap();
+        std::fs::write(&path, "new draft").unwrap();
+        let err = undo_recorded(&history, &renamed.undo_token).await.unwrap_err();
+        assert!(matches!(err, HibiscusError::AlreadyExists(_)));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new draft");
+
+        // Once the slot is free again the token still works
+        std::fs::remove_file(&path).unwrap();
+        undo_recorded(&history, &renamed.undo_token).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "text");
+    }
+
+    #[test]
+    fn test_copy_folder_skips_dotfiles_and_reports_progress() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("Semester 1");
+        std::fs::create_dir_all(source.join("week1")).unwrap();
+        std::fs::create_dir_all(source.join(".hibiscus")).unwrap();
+        std::fs::write(source.join(".hibiscus").join("workspace.json"), "{}").unwrap();
+        std::fs::write(source.join(".DS_Store"), "x").unwrap();
+        std::fs::write(source.join("syllabus.md"), "syllabus").unwrap();
+        std::fs::write(source.join("week1").join("lecture.md"), "lecture").unwrap();
+
+        let copy = dir.path().join("Semester 2");
+        let mut events = Vec::new();
+        let report = copy_folder_blocking(&source, &copy, |p| events.push(p)).unwrap();
+
+        assert_eq!((report.files, report.folders, report.bytes), (2, 2, 15));
+        assert!(report.failed.is_empty());
+        assert_eq!(std::fs::read_to_string(copy.join("week1").join("lecture.md")).unwrap(), "lecture");
+        assert!(!copy.join(".hibiscus").exists());
+        assert!(!copy.join(".DS_Store").exists());
+
+        let last = events.last().unwrap();
+        assert_eq!((last.files_copied, last.files_total), (2, 2));
+        assert_eq!((last.bytes_copied, last.bytes_total), (15, 15));
+
+        // Into one of its own descendants
+        let err = copy_folder_blocking(&source, &source.join("week1").join("again"), |_| {})
+            .unwrap_err();
+        assert!(matches!(err, HibiscusError::PathValidation(_)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_copy_folder_lists_failed_items_and_continues() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("notes");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(source.join("a.md"), "a").unwrap();
+        std::fs::write(source.join("locked.md"), "secret").unwrap();
+        std::fs::write(source.join("z.md"), "z").unwrap();
+        let locked = source.join("locked.md");
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o000)).unwrap();
+        if std::fs::read(&locked).is_ok() {
+            // Running as root: permissions are not enforced
+            return;
+        }
+
+        let copy = dir.path().join("copy");
+        let report = copy_folder_blocking(&source, &copy, |_| {}).unwrap();
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        assert_eq!(report.files, 2);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].path, "locked.md");
+        assert!(copy.join("z.md").is_file());
+    }
+
+    #[tokio::test]
+    async fn test_write_respects_workspace_quota() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".hibiscus")).unwrap();
+        std::fs::write(
+            dir.path().join(".hibiscus").join("workspace.json"),
+            r#"{"settings": {"max_file_write_bytes": 8}}"#,
+        )
+        .unwrap();
+        let path = dir.path().join("note.md");
+        let path_str = path.to_string_lossy().to_string();
+
+        // Under the limit
+        write_text_file(path_str.clone(), "small".into(), None, None, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "small");
+
+        // Over the limit fails before touching the file
+        let err = write_text_file(path_str.clone(), "far too large".into(), None, None, None, None, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, HibiscusError::QuotaExceeded { size: 13, limit: 8 }));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "small");
+
+        // Forced writes ignore the limit
+        write_text_file(path_str, "far too large".into(), None, Some(true), None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "far too large");
+    }
+
+    #[tokio::test]
+    async fn test_rename_suppresses_own_watcher_events() {
+        use notify::event::{CreateKind, RemoveKind};
+        use crate::watcher::{relevant_event_paths, WatchIgnores};
+        use notify::{Event, EventKind};
+
+        let dir = tempdir().unwrap();
+        let old = dir.path().join("draft.md");
+        let other = dir.path().join("other.md");
+        std::fs::write(&old, "x").unwrap();
+
+        let history = RenameHistory::default();
+        let new = rename_recorded(&history, old.to_string_lossy().into(), "final.md".into())
+            .await
+            .unwrap()
+            .path;
+        assert!(PathBuf::from(&new).is_file());
+
+        // The events the rename itself causes are suppressed...
+        let removed = Event::new(EventKind::Remove(RemoveKind::File)).add_path(old);
+        let created = Event::new(EventKind::Create(CreateKind::File)).add_path(new.into());
+        assert!(relevant_event_paths(&removed, &SELF_WRITES, &WatchIgnores::default()).is_empty());
+        assert!(relevant_event_paths(&created, &SELF_WRITES, &WatchIgnores::default()).is_empty());
+
+        // ...but an unrelated delete is still reported
+        let unrelated = Event::new(EventKind::Remove(RemoveKind::File)).add_path(other.clone());
+        assert_eq!(relevant_event_paths(&unrelated, &SELF_WRITES, &WatchIgnores::default()), vec![other]);
+    }
+
+    #[tokio::test]
+    async fn test_rename_rejects_separators() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("a.md").to_string_lossy().to_string();
+        let history = RenameHistory::default();
+        assert!(rename_recorded(&history, path, "../b.md".into()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_move_folder_and_missing_parent() {
+        let dir = tempdir().unwrap();
+        let folder = dir.path().join("drafts");
+        std::fs::create_dir(&folder).unwrap();
+        std::fs::write(folder.join("a.md"), "x").unwrap();
+        let folder_str = folder.to_string_lossy().to_string();
+
+        let err = move_node(
+            folder_str.clone(),
+            dir.path().join("missing").join("drafts").to_string_lossy().into(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, HibiscusError::ParentNotFound(_)));
+        assert!(folder.is_dir());
+
+        let history = RenameHistory::default();
+        let renamed = rename_recorded(&history, folder_str, "notes".into()).await.unwrap();
+        assert!(PathBuf::from(renamed.path).join("a.md").is_file());
+        assert!(!folder.exists());
+    }
+
+    #[tokio::test]
+    async fn test_move_file_onto_itself_and_into_descendant() {
+        let dir = tempdir().unwrap();
+        let folder = dir.path().join("drafts");
+        std::fs::create_dir_all(folder.join("week1")).unwrap();
+        std::fs::write(folder.join("a.md"), "x").unwrap();
+        let folder_str = folder.to_string_lossy().to_string();
+
+        // Onto itself: nothing happens
+        let moved = move_file(folder_str.clone(), folder_str.clone(), None).await.unwrap();
+        assert_eq!(moved, folder_str);
+        assert!(folder.join("a.md").is_file());
+
+        // Into a descendant: refused, nothing moved
+        let inside = folder.join("week1").join("drafts").to_string_lossy().to_string();
+        let err = move_file(folder_str.clone(), inside, None).await.unwrap_err();
+        assert!(matches!(err, HibiscusError::PathValidation(_)));
+        assert!(folder.join("a.md").is_file());
+
+        // An existing file is only replaced with overwrite
+        let target = dir.path().join("b.md");
+        std::fs::write(&target, "b").unwrap();
+        let a = folder.join("a.md").to_string_lossy().to_string();
+        let target_str = target.to_string_lossy().to_string();
+        let err = move_file(a.clone(), target_str.clone(), None).await.unwrap_err();
+        assert!(matches!(err, HibiscusError::AlreadyExists(_)));
+        assert_eq!(move_file(a, target_str.clone(), Some(true)).await.unwrap(), target_str);
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "x");
+    }
+
+    #[tokio::test]
+    async fn test_move_across_devices_copies_then_deletes() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("assets");
+        std::fs::create_dir_all(source.join("img")).unwrap();
+        std::fs::write(source.join("img").join("logo.png"), [1, 2, 3]).unwrap();
+        let destination = dir.path().join("mounted");
+
+        move_across_devices(&source, &destination).await.unwrap();
+        assert!(!source.exists());
+        assert_eq!(std::fs::read(destination.join("img").join("logo.png")).unwrap(), [1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_rename_file_overwrite_and_parents() {
+        let dir = tempdir().unwrap();
+        let path = |rel: &str| dir.path().join(rel).to_string_lossy().to_string();
+        std::fs::write(path("a.md"), "a").unwrap();
+        std::fs::write(path("b.md"), "b").unwrap();
+
+        let err = rename_file(path("missing.md"), path("c.md"), None).await.unwrap_err();
+        assert!(matches!(err, HibiscusError::FileNotFound(_)));
+
+        // Existing target is kept unless overwrite is set
+        let err = rename_file(path("a.md"), path("b.md"), None).await.unwrap_err();
+        assert!(matches!(err, HibiscusError::AlreadyExists(taken) if taken == path("b.md")));
+        assert_eq!(std::fs::read_to_string(path("b.md")).unwrap(), "b");
+        rename_file(path("a.md"), path("b.md"), Some(true)).await.unwrap();
+        assert_eq!(std::fs::read_to_string(path("b.md")).unwrap(), "a");
+        assert!(!PathBuf::from(path("a.md")).exists());
+
+        // Missing parents are created; the result is canonical
+        let renamed = rename_file(path("b.md"), path("archive/2024/b.md"), None).await.unwrap();
+        assert_eq!(
+            PathBuf::from(renamed),
+            std::fs::canonicalize(path("archive/2024/b.md")).unwrap()
+        );
+    }
+
+    /// A workspace with `workspace_scoped` set and a `link` folder that is
+    /// a symlink to a folder outside it.
+    #[cfg(unix)]
+    fn scoped_workspace_with_escape() -> (tempfile::TempDir, tempfile::TempDir) {
+        let vault = tempdir().unwrap();
+        let outside = tempdir().unwrap();
+        std::fs::create_dir_all(vault.path().join(".hibiscus")).unwrap();
+        std::fs::write(
+            vault.path().join(".hibiscus").join("workspace.json"),
+            r#"{"settings": {"workspace_scoped": true}}"#,
+        )
+        .unwrap();
+        std::os::unix::fs::symlink(outside.path(), vault.path().join("link")).unwrap();
+        (vault, outside)
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_scoped_create_refuses_symlink_escape() {
+        let (vault, outside) = scoped_workspace_with_escape();
+        let path = |rel: &str| vault.path().join(rel).to_string_lossy().to_string();
+
+        let err = create_new_file(path("link/x.md"), None, false).await.unwrap_err();
+        assert!(matches!(err, HibiscusError::PathValidation(_)));
+        let err = create_folder(path("link/sub"), None).await.unwrap_err();
+        assert!(matches!(err, HibiscusError::PathValidation(_)));
+        assert_eq!(std::fs::read_dir(outside.path()).unwrap().count(), 0);
+
+        // Creating inside the root still works
+        create_new_file(path("inside.md"), None, false).await.unwrap();
+        assert!(vault.path().join("inside.md").is_file());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_scoped_move_refuses_symlink_escape() {
+        let (vault, outside) = scoped_workspace_with_escape();
+        let path = |rel: &str| vault.path().join(rel).to_string_lossy().to_string();
+        std::fs::write(path("a.md"), "a").unwrap();
+
+        let err = rename_file(path("a.md"), path("link/y.md"), None).await.unwrap_err();
+        assert!(matches!(err, HibiscusError::PathValidation(_)));
+        let err = move_file(path("a.md"), path("link/y.md"), None).await.unwrap_err();
+        assert!(matches!(err, HibiscusError::PathValidation(_)));
+        let err = copy_path(path("a.md"), path("link/y.md")).await.unwrap_err();
+        assert!(matches!(err, HibiscusError::PathValidation(_)));
+        assert_eq!(std::fs::read_dir(outside.path()).unwrap().count(), 0);
+        assert_eq!(std::fs::read_to_string(path("a.md")).unwrap(), "a");
+
+        // Files outside can't be pulled in either
+        std::fs::write(outside.path().join("secret.md"), "s").unwrap();
+        let err = copy_path(path("link/secret.md"), path("copy.md")).await.unwrap_err();
+        assert!(matches!(err, HibiscusError::PathValidation(_)));
+        let err = duplicate_file(path("link/secret.md")).await.unwrap_err();
+        assert!(matches!(err, HibiscusError::PathValidation(_)));
+    }
+
+    #[tokio::test]
+    async fn test_case_only_rename() {
+        let dir = tempdir().unwrap();
+        let upper = dir.path().join("Notes.md");
+        let lower = dir.path().join("notes.md");
+        std::fs::write(&upper, "x").unwrap();
+
+        let renamed = rename_file(upper.to_string_lossy().into(), lower.to_string_lossy().into(), None)
+            .await
+            .unwrap();
+        assert!(renamed.ends_with("notes.md"));
+
+        // The two-step path works where the filesystem would see a no-op,
+        // and leaves a file already using the temporary name alone
+        let stray = dir.path().join(".notes.md.rename-tmp");
+        std::fs::write(&stray, "stray").unwrap();
+        rename_via_temp(&lower, &upper).await.unwrap();
+        let mut names: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec![".notes.md.rename-tmp", "Notes.md"]);
+        assert_eq!(std::fs::read_to_string(&stray).unwrap(), "stray");
+        assert!(is_same_file(&upper, &upper));
+    }
+
+    #[test]
+    fn test_delete_refuses_workspace_data_folder() {
+        let dir = tempdir().unwrap();
+        let data = dir.path().join(".hibiscus");
+        std::fs::create_dir_all(&data).unwrap();
+
+        assert!(matches!(
+            check_deletable(&data),
+            Err(HibiscusError::PathValidation(_))
+        ));
+        assert!(matches!(
+            check_deletable(&dir.path().join("missing.md")),
+            Err(HibiscusError::FileNotFound(_))
+        ));
+        assert!(data.is_dir());
+    }
+
+    #[tokio::test]
+    async fn test_permanent_delete_file_and_folder() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("a.md");
+        let folder = dir.path().join("notes");
+        std::fs::write(&file, "x").unwrap();
+        std::fs::create_dir_all(folder.join("nested")).unwrap();
+        std::fs::write(folder.join("nested").join("b.md"), "y").unwrap();
+
+        for path in [&file, &folder] {
+            check_deletable(path).unwrap();
+            remove_path(path, false).await.unwrap();
+            assert!(!path.exists());
+        }
+    }
+
+    /// Trashes into a temporary freedesktop trash instead of the user's.
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_trash_moves_file_out_of_workspace() {
+        let dir = tempdir().unwrap();
+        let data_home = tempdir().unwrap();
+        let _data_home = EnvVar::set("XDG_DATA_HOME", data_home.path());
+
+        let note = dir.path().join("notes").join("draft.md");
+        std::fs::create_dir_all(note.parent().unwrap()).unwrap();
+        std::fs::write(&note, "x").unwrap();
+
+        remove_path(&note, true).await.unwrap();
+        assert!(!note.exists());
+        assert!(data_home.path().join("Trash").join("files").join("draft.md").is_file());
+
+        // A missing item is not a trash failure, so no permanent delete is
+        // offered for it
+        assert!(matches!(
+            remove_path(&note, true).await,
+            Err(HibiscusError::FileNotFound(_))
+        ));
+    }
+
+    /// Sets an environment variable until dropped, then restores it.
+    struct EnvVar {
+        name: &'static str,
+        previous: Option<std::ffi::OsString>,
+    }
+
+    impl EnvVar {
+        fn set(name: &'static str, value: &Path) -> Self {
+            let previous = std::env::var_os(name);
+            std::env::set_var(name, value);
+            Self { name, previous }
+        }
+    }
+
+    impl Drop for EnvVar {
+        fn drop(&mut self) {
+            match &self.previous {
+                Some(value) => std::env::set_var(self.name, value),
+                None => std::env::remove_var(self.name),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_file_metadata_for_files_and_folders() {
+        use crate::workspace::FileKind;
+
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("a.md");
+        std::fs::write(&file, "hello").unwrap();
+        let mut permissions = std::fs::metadata(&file).unwrap().permissions();
+        permissions.set_readonly(true);
+        std::fs::set_permissions(&file, permissions).unwrap();
+
+        let meta = get_file_metadata(file.to_string_lossy().into()).await.unwrap();
+        assert_eq!(meta.kind, FileKind::File);
+        assert_eq!(meta.size, 5);
+        assert!(meta.readonly);
+        assert!(meta.modified.is_some());
+        let json = serde_json::to_value(&meta).unwrap();
+        assert_eq!(json["kind"], "file");
+        assert!(json["modified"].as_str().unwrap().contains('T'));
+
+        let folder = get_file_metadata(dir.path().to_string_lossy().into()).await.unwrap();
+        assert_eq!(folder.kind, FileKind::Folder);
+        assert!(folder.is_dir);
+        assert_eq!(folder.size, 0);
+
+        let missing = dir.path().join("missing.md");
+        let err = get_file_metadata(missing.to_string_lossy().into()).await.unwrap_err();
+        assert!(matches!(err, HibiscusError::FileNotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn test_stat_paths_mixed() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("a.md");
+        std::fs::write(&file, "hello").unwrap();
+        let folder = dir.path().join("sub");
+        std::fs::create_dir(&folder).unwrap();
+        let missing = dir.path().join("missing.md");
+
+        let paths = [&file, &folder, &missing]
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .chain(["/a/../b".to_string()])
+            .collect::<Vec<_>>();
+        let stats = stat_paths(paths.clone()).await.unwrap();
+
+        assert_eq!(stats.len(), 4);
+        assert_eq!(stats[0].path, paths[0]);
+        assert!(stats[0].exists);
+        assert_eq!(stats[0].size, Some(5));
+        assert_eq!(stats[0].is_dir, Some(false));
+        assert!(stats[0].modified_ms.unwrap() > 0);
+
+        assert!(stats[1].exists);
+        assert_eq!(stats[1].is_dir, Some(true));
+        assert_eq!(stats[1].size, None);
+
+        assert!(!stats[2].exists);
+        assert!(stats[2].error.is_none());
+
+        assert!(!stats[3].exists);
+        assert!(stats[3].error.is_some());
+    }
+
+    #[test]
+    fn test_copy_name_suffixes() {
+        assert_eq!(copy_name("Lecture.md", 1), "Lecture copy.md");
+        assert_eq!(copy_name("Lecture.md", 2), "Lecture copy 2.md");
+        assert_eq!(copy_name("notes.draft.md", 1), "notes.draft copy.md");
+        assert_eq!(copy_name("README", 3), "README copy 3");
+        assert_eq!(copy_name(".env", 1), ".env copy");
+
+        // Multi-byte stem cut to fit, keeping the suffix and extension
+        let long = format!("{}.md", "é".repeat(200));
+        let copy = copy_name(&long, 12);
+        assert!(copy.len() <= MAX_NAME_BYTES);
+        assert!(copy.ends_with("é copy 12.md"), "{}", copy);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_file_never_overwrites() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("note.md");
+        std::fs::write(&source, "original").unwrap();
+        std::fs::write(dir.path().join("note copy.md"), "taken").unwrap();
+
+        let copy = duplicate_file(source.to_string_lossy().into()).await.unwrap();
+        assert_eq!(PathBuf::from(&copy), dir.path().join("note copy 2.md"));
+        assert_eq!(std::fs::read_to_string(&copy).unwrap(), "original");
+        assert_eq!(std::fs::read_to_string(dir.path().join("note copy.md")).unwrap(), "taken");
+
+        let folder = dir.path().to_string_lossy().into_owned();
+        assert!(matches!(
+            duplicate_file(folder).await,
+            Err(HibiscusError::InvalidPathType { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_minified_json_is_pretty_for_display_and_saved_minified() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("data.json");
+        let minified = r#"{"z":1,"a":[true,null]}"#;
+        std::fs::write(&path, minified).unwrap();
+        let path_str = path.to_string_lossy().to_string();
+
+        // Plain reads are unchanged
+        let plain = read_text_file(path_str.clone(), None, None, None, None).await.unwrap();
+        assert_eq!(plain, TextContent::Plain(minified.into()));
+        assert_eq!(serde_json::to_value(&plain).unwrap(), minified);
+
+        let TextContent::Json { content, was_minified, .. } =
+            read_text_file(path_str.clone(), Some(true), None, None, None).await.unwrap()
+        else {
+            panic!("expected JSON content");
+        };
+        assert!(was_minified);
+        assert_eq!(content, "{\n  \"z\": 1,\n  \"a\": [\n    true,\n    null\n  ]\n}\n");
+
+        let format = SaveFormat { minify_json: was_minified, ..Default::default() };
+        write_text_file(path_str, content, Some(format), None, None, None, None).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), minified);
+    }
+
+    #[tokio::test]
+    async fn test_legacy_encodings_are_decoded_unless_strict() {
+        let dir = tempdir().unwrap();
+        let latin = dir.path().join("latin.md");
+        // "café – naïve" in Windows-1252
+        std::fs::write(&latin, b"caf\xe9 \x96 na\xefve").unwrap();
+        let wide = dir.path().join("wide.md");
+        std::fs::write(&wide, b"\xff\xfeh\0i\0").unwrap();
+        let latin_str = latin.to_string_lossy().to_string();
+
+        let decoded = read_text_file(latin_str.clone(), None, None, None, None).await.unwrap();
+        let TextContent::Decoded { content, encoding, had_errors, line_ending, .. } = &decoded else {
+            panic!("expected decoded content");
+        };
+        assert_eq!(
+            (content.as_str(), encoding.as_str(), *had_errors, *line_ending),
+            ("café – naïve", "windows-1252", false, LineEnding::Lf)
+        );
+        assert_eq!(serde_json::to_value(&decoded).unwrap()["encoding"], "windows-1252");
+
+        let TextContent::Decoded { content, encoding, .. } =
+            read_text_file(wide.to_string_lossy().to_string(), None, None, None, None).await.unwrap()
+        else {
+            panic!("expected decoded content");
+        };
+        assert_eq!((content.as_str(), encoding.as_str()), ("hi", "UTF-16LE"));
+
+        let strict = read_text_file(latin_str, None, Some(true), None, None).await;
+        assert!(matches!(strict, Err(HibiscusError::Io(message)) if message.contains("valid UTF-8")));
+    }
+
+    #[tokio::test]
+    async fn test_crlf_file_round_trips_through_the_editor() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("windows.md");
+        let original = "# Title\r\n\r\n```\r\ncode\r\n```\r\nend\n";
+        std::fs::write(&path, original).unwrap();
+        let path_str = path.to_string_lossy().to_string();
+
+        let TextContent::Text { content, line_ending, .. } =
+            read_text_file(path_str.clone(), None, None, Some(true), None).await.unwrap()
+        else {
+            panic!("expected text with its line ending");
+        };
+        assert_eq!(line_ending, LineEnding::Crlf);
+
+        // The editor works in \n; preserving restores the file byte for byte
+        let edited = content.replace("\r\n", "\n");
+        write_text_file(path_str.clone(), edited.clone(), None, None, Some(LineEndingMode::Preserve), None, None)
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), original.replace("end\n", "end\r\n"));
+
+        write_text_file(path_str, edited.clone(), None, None, Some(LineEndingMode::Lf), None, None).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), edited);
+    }
+
+    #[tokio::test]
+    async fn test_write_refuses_to_clobber_external_edits() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("note.md");
+        std::fs::write(&path, "from disk").unwrap();
+        let path_str = path.to_string_lossy().to_string();
+
+        let TextContent::Text { version, .. } =
+            read_text_file(path_str.clone(), None, None, Some(true), None).await.unwrap()
+        else {
+            panic!("expected text with its version");
+        };
+        assert_eq!(version.hash.as_ref().map(String::len), Some(16));
+
+        // Unchanged: the save goes through and yields a new version
+        write_text_file(path_str.clone(), "mine".into(), None, None, None, Some(version.clone()), None).await.unwrap();
+
+        // Another program edits the file; the stale version conflicts
+        std::fs::write(&path, "theirs").unwrap();
+        let stale = Some(version.clone());
+        let conflict = write_text_file(path_str.clone(), "mine again".into(), None, None, None, stale, None).await;
+        assert!(matches!(conflict, Err(HibiscusError::Conflict { ref hash, .. }) if hash.len() == 16));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "theirs");
+
+        // Forcing past the quota still respects the version
+        let stale = Some(version);
+        let conflict = write_text_file(path_str.clone(), "mine again".into(), None, Some(true), None, stale, None).await;
+        assert!(matches!(conflict, Err(HibiscusError::Conflict { .. })));
+
+        // Overwrite anyway
+        write_text_file(path_str, "mine again".into(), None, None, None, None, None).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "mine again");
+    }
+
+    #[test]
+    fn test_detect_mime_prefers_magic_bytes() {
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        assert_eq!(detect_mime(png, Path::new("mislabeled.jpg")), "image/png");
+        assert_eq!(detect_mime(b"\xff\xd8\xff\xe0", Path::new("a")), "image/jpeg");
+        assert_eq!(detect_mime(b"GIF89a..", Path::new("a")), "image/gif");
+        assert_eq!(detect_mime(b"RIFF\0\0\0\0WEBPVP8 ", Path::new("a")), "image/webp");
+        assert_eq!(detect_mime(b"%PDF-1.7", Path::new("a")), "application/pdf");
+        assert_eq!(
+            detect_mime(b"<?xml version=\"1.0\"?>\n<svg xmlns=\"...\"/>", Path::new("a")),
+            "image/svg+xml"
+        );
+
+        // Unknown content falls back to the extension
+        assert_eq!(detect_mime(b"\0\x01", Path::new("scan.PDF")), "application/pdf");
+        assert_eq!(detect_mime(b"\0\x01", Path::new("blob")), "application/octet-stream");
+    }
+
+    #[tokio::test]
+    async fn test_read_binary_file_enforces_size_limit() {
+        use base64::Engine;
+
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".hibiscus")).unwrap();
+        std::fs::write(
+            dir.path().join(".hibiscus").join("workspace.json"),
+            r#"{"settings": {"max_binary_read_bytes": 16}}"#,
+        )
+        .unwrap();
+        let small = dir.path().join("dot.gif");
+        std::fs::write(&small, b"GIF89a\x01\0\x01\0").unwrap();
+        let large = dir.path().join("big.png");
+        std::fs::write(&large, [0u8; 17]).unwrap();
+
+        let file = read_binary_file(small.to_string_lossy().into()).await.unwrap();
+        assert_eq!(file.mime, "image/gif");
+        assert_eq!(file.size, 10);
+        let decoded = base64::engine::general_purpose::STANDARD.decode(&file.data).unwrap();
+        assert_eq!(decoded, std::fs::read(&small).unwrap());
+
+        let err = read_binary_file(large.to_string_lossy().into()).await.unwrap_err();
+        assert!(matches!(err, HibiscusError::FileTooLarge { size: 17, limit: 16, .. }));
+    }
+
+    #[tokio::test]
+    async fn test_read_text_file_refuses_files_over_max_size() {
+        let dir = tempdir().unwrap();
+        let log = dir.path().join("app.log");
+        std::fs::write(&log, "x".repeat(30)).unwrap();
+        let path = log.to_string_lossy().to_string();
+
+        let err = read_text_file(path.clone(), None, None, None, Some(16)).await.unwrap_err();
+        match err {
+            HibiscusError::FileTooLarge { path: reported, size, limit } => {
+                assert_eq!((reported, size, limit), (path.clone(), 30, 16));
+            }
+            other => panic!("expected FileTooLarge, got {:?}", other),
+        }
+
+        // "Open anyway" passes a higher limit
+        let content = read_text_file(path, None, None, None, Some(30)).await.unwrap();
+        assert!(matches!(content, TextContent::Plain(ref text) if text.len() == 30));
+    }
+
+    #[tokio::test]
+    async fn test_read_text_file_range_clamps_and_splits_mixed_endings() {
+        let dir = tempdir().unwrap();
+        let note = dir.path().join("log.txt");
+        // A CRLF pair straddling the 8 KB read buffer, and no final break
+        let long = "x".repeat(8191);
+        std::fs::write(&note, format!("{long}\r\nb\nc\r\nd")).unwrap();
+        let path = note.to_string_lossy().to_string();
+
+        let range = read_text_file_range(path.clone(), 0, 2).await.unwrap();
+        assert_eq!(range.lines, [long.as_str(), "b"]);
+        assert_eq!(range.total_lines, 4);
+
+        let range = read_text_file_range(path.clone(), 2, 200).await.unwrap();
+        assert_eq!((range.lines, range.start_line), (vec!["c".to_string(), "d".to_string()], 2));
+        assert!(read_text_file_range(path, 9, 20).await.unwrap().lines.is_empty());
+
+        std::fs::write(&note, b"text\n\0\x01\x02").unwrap();
+        let binary = read_text_file_range(note.to_string_lossy().into(), 0, 1).await;
+        assert!(matches!(binary, Err(HibiscusError::BinaryFile(_))));
+    }
+
+    #[tokio::test]
+    async fn test_read_text_file_range_decodes_utf16() {
+        let dir = tempdir().unwrap();
+        let text = format!("{}\r\nzwei\nüber", "ä".repeat(5000));
+        let utf16 = |bom: [u8; 2], unit: fn(u16) -> [u8; 2]| {
+            let mut bytes = bom.to_vec();
+            bytes.extend(text.encode_utf16().flat_map(unit));
+            bytes
+        };
+
+        for (name, bytes) in [
+            ("le.txt", utf16([0xFF, 0xFE], u16::to_le_bytes)),
+            ("be.txt", utf16([0xFE, 0xFF], u16::to_be_bytes)),
+        ] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, bytes).unwrap();
+            let range = read_text_file_range(path.to_string_lossy().into(), 1, 3).await.unwrap();
+            assert_eq!(range.lines, ["zwei", "über"], "{}", name);
+            assert_eq!(range.total_lines, 3);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_text_files_reports_each_entry() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".hibiscus")).unwrap();
+        std::fs::write(
+            dir.path().join(".hibiscus").join("workspace.json"),
+            r#"{"settings": {"max_file_write_bytes": 8}}"#,
+        )
+        .unwrap();
+        let entry = |name: &str, contents: &str| WriteEntry {
+            path: dir.path().join(name).to_string_lossy().into(),
+            contents: contents.into(),
+            format: None,
+            force: false,
+            line_ending: None,
+            expected_version: None,
+        };
+
+        let results = write_text_files(vec![entry("a.md", "a"), entry("b.md", "b")], true).await.unwrap();
+        assert!(results.iter().all(|result| result.written));
+        assert_eq!(std::fs::read_to_string(dir.path().join("b.md")).unwrap(), "b");
+
+        // One entry over the quota: an atomic group writes nothing...
+        let results = write_text_files(vec![entry("a.md", "A"), entry("b.md", "too long!")], true)
+            .await
+            .unwrap();
+        assert!(results.iter().all(|result| !result.written));
+        assert!(results[0].error.as_deref().unwrap().starts_with("Not saved"));
+        assert_eq!(std::fs::read_to_string(dir.path().join("a.md")).unwrap(), "a");
+
+        // ...while independent writes save what they can
+        let results = write_text_files(vec![entry("a.md", "A"), entry("b.md", "too long!")], false)
+            .await
+            .unwrap();
+        assert!(results[0].written);
+        assert!(!results[1].written);
+        assert_eq!(std::fs::read_to_string(dir.path().join("a.md")).unwrap(), "A");
+
+        // Per-entry format and force apply to atomic groups too
+        let minified = WriteEntry {
+            format: Some(SaveFormat { minify_json: true, ..Default::default() }),
+            ..entry("c.json", "{ \"a\": 1 }")
+        };
+        let forced = WriteEntry { force: true, ..entry("b.md", "too long!") };
+        let results = write_text_files(vec![minified, forced], true).await.unwrap();
+        assert!(results.iter().all(|result| result.written));
+        assert_eq!(std::fs::read_to_string(dir.path().join("c.json")).unwrap(), "{\"a\":1}");
+        assert_eq!(std::fs::read_to_string(dir.path().join("b.md")).unwrap(), "too long!");
+    }
+
+    #[tokio::test]
+    async fn test_write_binary_file_round_trips_and_validates() {
+        use base64::Engine;
+
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".hibiscus")).unwrap();
+        std::fs::write(
+            dir.path().join(".hibiscus").join("workspace.json"),
+            r#"{"settings": {"max_file_write_bytes": 16}}"#,
+        )
+        .unwrap();
+        let target = dir.path().join("attachments").join("paste.png");
+        let bytes = b"\x89PNG\r\n\x1a\n\0\xff";
+        let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
+
+        let written = write_binary_file(target.to_string_lossy().into(), encoded).await.unwrap();
+        assert_eq!(written, 10);
+        assert_eq!(std::fs::read(&target).unwrap(), bytes);
+        assert!(!target.with_file_name("paste.png.hibiscus-save~").exists());
+
+        let other = dir.path().join("other.bin");
+        let err = write_binary_file(other.to_string_lossy().into(), "not base64!".into())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, HibiscusError::Serialization(_)));
+
+        let large = base64::engine::general_purpose::STANDARD.encode([0u8; 17]);
+        let err = write_binary_file(other.to_string_lossy().into(), large).await.unwrap_err();
+        assert!(matches!(err, HibiscusError::QuotaExceeded { size: 17, limit: 16 }));
+        assert!(!other.exists());
+    }
+
+    #[tokio::test]
+    async fn test_file_commands_go_through_mounted_storage() {
+        use crate::storage::tests::MemoryStorage;
+
+        let root = Path::new("/memory/files-test");
+        let vault = MemoryStorage::mount(root);
+        let note = root.join("notes").join("a.md");
+        let path = note.to_string_lossy().to_string();
+
+        let written = write_text_file(path.clone(), "one\r\n".into(), None, None, None, None, None).await.unwrap();
+        assert_eq!(written.size, 5);
+        assert_eq!(vault.storage.contents(&note).unwrap(), b"one\r\n");
+
+        let Ok(TextContent::Text { content, line_ending, version }) =
+            read_text_file(path.clone(), None, None, Some(true), None).await
+        else {
+            panic!("expected text with metadata");
+        };
+        assert_eq!((content.as_str(), line_ending), ("one\r\n", LineEnding::Crlf));
+        write_text_file(path.clone(), "two\n".into(), None, None, Some(LineEndingMode::Preserve), Some(version), None)
+            .await
+            .unwrap();
+        assert_eq!(vault.storage.contents(&note).unwrap(), b"two\r\n");
+
+        let moved = root.join("b.md");
+        move_node(path, moved.to_string_lossy().into()).await.unwrap();
+        assert!(vault.storage.contents(&note).is_none());
+        assert_eq!(read_file_binary(moved.to_string_lossy().into()).await.unwrap(), b"two\r\n");
+        assert!(!note.exists() && !moved.exists());
+    }
+
+    #[tokio::test]
+    async fn test_create_commands_go_through_mounted_storage() {
+        use crate::storage::tests::MemoryStorage;
+
+        let root = Path::new("/memory/create-test");
+        let vault = MemoryStorage::mount(root);
+        let note = root.join("notes").join("Untitled.md");
+        let path = note.to_string_lossy().to_string();
+
+        create_new_file(path.clone(), Some("# Hi".into()), false).await.unwrap();
+        assert_eq!(vault.storage.contents(&note).unwrap(), b"# Hi");
+        let err = create_new_file(path.clone(), None, false).await.unwrap_err();
+        assert!(matches!(err, HibiscusError::AlreadyExists(_)));
+        assert_eq!(vault.storage.contents(&note).unwrap(), b"# Hi");
+        let renamed = create_new_file(path, None, true).await.unwrap();
+        assert_eq!(renamed.name, "Untitled 1.md");
+        assert_eq!(vault.storage.contents(&root.join("notes").join("Untitled 1.md")).unwrap(), b"");
+
+        let folder = root.join("notes").join("drafts");
+        create_folder(folder.to_string_lossy().into(), None).await.unwrap();
+        assert!(storage::metadata(&folder).await.unwrap().is_dir);
+        let err = create_folder(folder.to_string_lossy().into(), None).await.unwrap_err();
+        assert!(matches!(err, HibiscusError::AlreadyExists(_)));
+        assert!(!note.exists() && !folder.exists());
+    }
+}
@@ -0,0 +1,547 @@
+// ============================================================================
+// ATTACHMENTS GALLERY PREFETCH
+// ============================================================================
+//
+// One round-trip per gallery page instead of one per thumbnail: lists the
+// image/PDF attachments of a folder with their metadata and thumbnail cache
+// paths, generating missing thumbnails in parallel.
+//
+// PAGINATION:
+// Items are ordered by mtime (newest first), then name. The cursor encodes
+// the (mtime, name) of the last item returned, so adding or removing other
+// files between pages never shifts or repeats entries the way an index would.
+//
+// TIME CAP:
+// Thumbnail generation is bounded by a per-call deadline. Items still being
+// generated when it expires are returned with `thumbnail_pending: true`;
+// their generation continues in the background and completion is reported
+// through a single `gallery-thumbnails-ready` event.
+// ============================================================================
+
+use serde::Serialize;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+use tauri::Emitter;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::error::HibiscusError;
+use super::path::validate_path;
+
+/// Upper bound on items per page.
+const MAX_PAGE_SIZE: usize = 200;
+
+/// Time spent generating thumbnails before a call returns.
+const THUMBNAIL_TIME_CAP: Duration = Duration::from_millis(400);
+
+/// Concurrent thumbnail jobs across all calls (the gallery's IO budget).
+const THUMBNAIL_CONCURRENCY: usize = 4;
+
+/// Bytes read from the start of an image when looking for its dimensions.
+const HEADER_BYTES: u64 = 64 * 1024;
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "bmp", "svg"];
+
+static THUMBNAIL_PERMITS: std::sync::LazyLock<Arc<Semaphore>> =
+    std::sync::LazyLock::new(|| Arc::new(Semaphore::new(THUMBNAIL_CONCURRENCY)));
+
+/// Produces a thumbnail for `source` at `dest`.
+///
+/// Returns `Ok(false)` when the file type has no thumbnail support.
+pub(crate) type ThumbnailFn = Arc<dyn Fn(&Path, &Path) -> std::io::Result<bool> + Send + Sync>;
+
+/// Attachment kind shown in the gallery.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GalleryKind {
+    Image,
+    Pdf,
+}
+
+/// One attachment in a gallery page.
+#[derive(Debug, Clone, Serialize)]
+pub struct GalleryItem {
+    /// Path relative to the workspace root, with forward slashes
+    pub path: String,
+    pub name: String,
+    pub kind: GalleryKind,
+    /// Size in bytes
+    pub size: u64,
+    /// Modification time in milliseconds since the Unix epoch
+    pub mtime: u64,
+    /// Pixel dimensions, when readable from the file header
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// Absolute path of the cached thumbnail; `None` if the type has none
+    pub thumbnail_path: Option<String>,
+    /// `true` if the thumbnail is still being generated
+    pub thumbnail_pending: bool,
+}
+
+/// A page of gallery items.
+#[derive(Debug, Clone, Serialize)]
+pub struct GalleryPage {
+    pub items: Vec<GalleryItem>,
+    /// Pass back to fetch the next page; `None` on the last page
+    pub next_cursor: Option<String>,
+}
+
+/// Payload of the `gallery-thumbnails-ready` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct GalleryThumbnailsReady {
+    /// Folder the page belongs to, as passed to `prefetch_gallery`
+    pub folder: String,
+    /// Items whose thumbnails finished after the call returned
+    pub items: Vec<ReadyThumbnail>,
+}
+
+/// A thumbnail that finished in the background.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadyThumbnail {
+    pub path: String,
+    /// `None` if generation failed
+    pub thumbnail_path: Option<String>,
+}
+
+/// Lists a page of image/PDF attachments with metadata and thumbnails.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+/// * `folder_rel_path` - Folder to list, relative to the root ("" for root)
+/// * `max_items` - Page size (capped at 200)
+/// * `cursor` - `next_cursor` from the previous page, if any
+/// * `window` - Window that receives `gallery-thumbnails-ready`
+///
+/// # Returns
+/// * `Ok(GalleryPage)` - Items sorted newest first, plus the next cursor
+/// * `Err(HibiscusError)` - If the folder or cursor is invalid
+///
+/// # Notes
+/// Thumbnails are cached under `.hibiscus/thumbnails`, keyed by path, size
+/// and mtime, so edited files get fresh thumbnails.
+#[tauri::command]
+pub async fn prefetch_gallery(
+    root: String,
+    folder_rel_path: String,
+    max_items: usize,
+    cursor: Option<String>,
+    window: tauri::Window,
+) -> Result<GalleryPage, HibiscusError> {
+    let root = PathBuf::from(&root);
+    validate_path(&root)?;
+
+    let folder = folder_rel_path.clone();
+    prefetch_page(
+        &root,
+        &folder_rel_path,
+        max_items,
+        cursor.as_deref(),
+        THUMBNAIL_TIME_CAP,
+        Arc::new(copy_thumbnail),
+        move |items| {
+            let payload = GalleryThumbnailsReady { folder, items };
+            if let Err(e) = window.emit("gallery-thumbnails-ready", payload) {
+                eprintln!("[Hibiscus] Warning: Failed to emit gallery-thumbnails-ready: {}", e);
+            }
+        },
+    )
+    .await
+}
+
+/// Builds a gallery page; see `prefetch_gallery`.
+///
+/// `on_ready` is called at most once, from a background task, with the
+/// thumbnails that were still pending when the page was returned.
+pub(crate) async fn prefetch_page<F>(
+    root: &Path,
+    folder_rel_path: &str,
+    max_items: usize,
+    cursor: Option<&str>,
+    time_cap: Duration,
+    generate: ThumbnailFn,
+    on_ready: F,
+) -> Result<GalleryPage, HibiscusError>
+where
+    F: FnOnce(Vec<ReadyThumbnail>) + Send + 'static,
+{
+    let folder = root.join(folder_rel_path);
+    validate_path(&folder)?;
+    if !folder.is_dir() {
+        return Err(HibiscusError::InvalidPathType {
+            path: folder.to_string_lossy().into(),
+            expected: "directory".into(),
+            actual: "file".into(),
+        });
+    }
+
+    let after = cursor.map(decode_cursor).transpose()?;
+    let page_size = max_items.clamp(1, MAX_PAGE_SIZE);
+
+    let (root_buf, folder_buf) = (root.to_path_buf(), folder.clone());
+    let (mut items, next_cursor) = tokio::task::spawn_blocking(move || {
+        list_page(&root_buf, &folder_buf, page_size, after.as_ref())
+    })
+    .await
+    .map_err(|e| HibiscusError::Io(format!("Gallery listing task failed: {}", e)))??;
+
+    // Start generation for every missing thumbnail.
+    let mut jobs = JoinSet::new();
+    for (index, item) in items.iter_mut().enumerate() {
+        let Some(thumb) = item.thumbnail_path.clone() else {
+            continue;
+        };
+        if Path::new(&thumb).is_file() {
+            continue;
+        }
+
+        item.thumbnail_pending = true;
+        let source = root.join(&item.path);
+        let generate = generate.clone();
+        let permits = THUMBNAIL_PERMITS.clone();
+        jobs.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let dest = PathBuf::from(&thumb);
+            let generated = tokio::task::spawn_blocking(move || {
+                if let Some(parent) = dest.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                generate(&source, &dest)
+            })
+            .await;
+            (index, matches!(generated, Ok(Ok(true))))
+        });
+    }
+
+    // Collect whatever finishes before the deadline.
+    let deadline = tokio::time::Instant::now() + time_cap;
+    while !jobs.is_empty() {
+        match tokio::time::timeout_at(deadline, jobs.join_next()).await {
+            Ok(Some(Ok((index, generated)))) => {
+                items[index].thumbnail_pending = false;
+                if !generated {
+                    items[index].thumbnail_path = None;
+                }
+            }
+            Ok(Some(Err(_))) => {}
+            Ok(None) | Err(_) => break,
+        }
+    }
+
+    // Hand the rest to a background task that reports once all are done.
+    if !jobs.is_empty() {
+        let pending: Vec<(usize, String, Option<String>)> = items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| item.thumbnail_pending)
+            .map(|(i, item)| (i, item.path.clone(), item.thumbnail_path.clone()))
+            .collect();
+
+        tokio::spawn(async move {
+            let mut ready = Vec::new();
+            while let Some(result) = jobs.join_next().await {
+                let Ok((index, generated)) = result else {
+                    continue;
+                };
+                if let Some((_, path, thumb)) = pending.iter().find(|(i, _, _)| *i == index) {
+                    ready.push(ReadyThumbnail {
+                        path: path.clone(),
+                        thumbnail_path: if generated { thumb.clone() } else { None },
+                    });
+                }
+            }
+            on_ready(ready);
+        });
+    }
+
+    Ok(GalleryPage { items, next_cursor })
+}
+
+/// Lists, sorts and slices a folder's attachments.
+fn list_page(
+    root: &Path,
+    folder: &Path,
+    page_size: usize,
+    after: Option<&(u64, String)>,
+) -> Result<(Vec<GalleryItem>, Option<String>), HibiscusError> {
+    let entries = std::fs::read_dir(folder).map_err(|e| {
+        HibiscusError::Io(format!("Failed to read directory '{}': {}", folder.display(), e))
+    })?;
+
+    let mut candidates: Vec<(u64, String, GalleryKind, u64)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') {
+                return None;
+            }
+            let kind = attachment_kind(&name)?;
+            let meta = entry.metadata().ok().filter(|m| m.is_file())?;
+            let mtime = meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0);
+            Some((mtime, name, kind, meta.len()))
+        })
+        .collect();
+
+    // Newest first, then by name for a total order.
+    candidates.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+
+    if let Some((mtime, name)) = after {
+        candidates.retain(|(m, n, _, _)| m < mtime || (m == mtime && n > name));
+    }
+
+    let has_more = candidates.len() > page_size;
+    candidates.truncate(page_size);
+    let next_cursor = has_more
+        .then(|| candidates.last().map(|(m, n, _, _)| encode_cursor(*m, n)))
+        .flatten();
+
+    let items = candidates
+        .into_iter()
+        .map(|(mtime, name, kind, size)| {
+            let abs = folder.join(&name);
+            let rel = abs
+                .strip_prefix(root)
+                .unwrap_or(&abs)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let (width, height) = match kind {
+                GalleryKind::Image => image_dimensions(&abs).unzip(),
+                GalleryKind::Pdf => (None, None),
+            };
+            let thumbnail_path = thumbnail_cache_path(root, &rel, size, mtime);
+            GalleryItem {
+                path: rel,
+                name,
+                kind,
+                size,
+                mtime,
+                width,
+                height,
+                thumbnail_path: Some(thumbnail_path.to_string_lossy().to_string()),
+                thumbnail_pending: false,
+            }
+        })
+        .collect();
+
+    Ok((items, next_cursor))
+}
+
+fn attachment_kind(name: &str) -> Option<GalleryKind> {
+    let ext = Path::new(name).extension()?.to_string_lossy().to_lowercase();
+    if ext == "pdf" {
+        Some(GalleryKind::Pdf)
+    } else if IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+        Some(GalleryKind::Image)
+    } else {
+        None
+    }
+}
+
+/// Cache location for a thumbnail, unique per (path, size, mtime).
+fn thumbnail_cache_path(root: &Path, rel: &str, size: u64, mtime: u64) -> PathBuf {
+    // FNV-1a: stable across builds, unlike std's DefaultHasher
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in format!("{}\0{}\0{}", rel, size, mtime).bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    let ext = Path::new(rel)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    root.join(".hibiscus")
+        .join("thumbnails")
+        .join(format!("{:016x}.{}", hash, ext))
+}
+
+/// Default thumbnail generator.
+///
+/// The backend has no image decoder, so images are cached as-is and scaled
+/// by the frontend; PDFs are reported as unsupported.
+fn copy_thumbnail(source: &Path, dest: &Path) -> std::io::Result<bool> {
+    match attachment_kind(&source.to_string_lossy()) {
+        Some(GalleryKind::Image) => {
+            let temp = dest.with_extension("tmp");
+            std::fs::copy(source, &temp)?;
+            std::fs::rename(&temp, dest)?;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+fn encode_cursor(mtime: u64, name: &str) -> String {
+    format!("{}:{}", mtime, name)
+        .bytes()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn decode_cursor(cursor: &str) -> Result<(u64, String), HibiscusError> {
+    let invalid = || HibiscusError::Workspace("Invalid gallery cursor".into());
+
+    let bytes = cursor
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            let pair = std::str::from_utf8(pair).ok().filter(|p| p.len() == 2);
+            pair.and_then(|p| u8::from_str_radix(p, 16).ok()).ok_or_else(invalid)
+        })
+        .collect::<Result<Vec<u8>, _>>()?;
+    let text = String::from_utf8(bytes).map_err(|_| invalid())?;
+    let (mtime, name) = text.split_once(':').ok_or_else(invalid)?;
+    Ok((mtime.parse().map_err(|_| invalid())?, name.to_string()))
+}
+
+/// Reads pixel dimensions from PNG, GIF or JPEG headers.
+fn image_dimensions(path: &Path) -> Option<(u32, u32)> {
+    let mut header = Vec::new();
+    std::fs::File::open(path)
+        .ok()?
+        .take(HEADER_BYTES)
+        .read_to_end(&mut header)
+        .ok()?;
+
+    let be16 = |i: usize| Some(u16::from_be_bytes([*header.get(i)?, *header.get(i + 1)?]) as u32);
+    let le16 = |i: usize| Some(u16::from_le_bytes([*header.get(i)?, *header.get(i + 1)?]) as u32);
+    let be32 = |i: usize| Some(u32::from_be_bytes(header.get(i..i + 4)?.try_into().ok()?));
+
+    if header.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Some((be32(16)?, be32(20)?));
+    }
+    if header.starts_with(b"GIF87a") || header.starts_with(b"GIF89a") {
+        return Some((le16(6)?, le16(8)?));
+    }
+    if header.starts_with(&[0xff, 0xd8]) {
+        // Walk JPEG segments until a start-of-frame marker.
+        let mut i = 2;
+        while i + 9 < header.len() {
+            if header[i] != 0xff {
+                return None;
+            }
+            let marker = header[i + 1];
+            let is_sof = (0xc0..=0xcf).contains(&marker) && !matches!(marker, 0xc4 | 0xc8 | 0xcc);
+            if is_sof {
+                return Some((be16(i + 7)?, be16(i + 5)?));
+            }
+            i += 2 + be16(i + 2)? as usize;
+        }
+    }
+    None
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::SystemTime;
+    use tempfile::tempdir;
+
+    /// Minimal PNG header with the given dimensions.
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        bytes.extend(width.to_be_bytes());
+        bytes.extend(height.to_be_bytes());
+        bytes
+    }
+
+    /// Writes `name` with an mtime of `secs` after the epoch.
+    fn write_at(dir: &Path, name: &str, secs: u64) {
+        let path = dir.join(name);
+        fs::write(&path, png(2, 3)).unwrap();
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+            .unwrap();
+    }
+
+    fn instant() -> ThumbnailFn {
+        Arc::new(copy_thumbnail)
+    }
+
+    async fn page(root: &Path, cursor: Option<&str>) -> GalleryPage {
+        prefetch_page(root, "img", 2, cursor, Duration::from_secs(5), instant(), |_| {})
+            .await
+            .unwrap()
+    }
+
+    fn names(page: &GalleryPage) -> Vec<&str> {
+        page.items.iter().map(|i| i.name.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_pagination_stable_when_file_added_between_pages() {
+        let dir = tempdir().unwrap();
+        let img = dir.path().join("img");
+        fs::create_dir(&img).unwrap();
+        write_at(&img, "a.png", 400);
+        write_at(&img, "b.png", 300);
+        write_at(&img, "c.png", 200);
+        write_at(&img, "d.png", 100);
+        fs::write(img.join("notes.md"), "not an attachment").unwrap();
+
+        let first = page(dir.path(), None).await;
+        assert_eq!(names(&first), vec!["a.png", "b.png"]);
+        assert_eq!(first.items[0].width, Some(2));
+        assert_eq!(first.items[0].height, Some(3));
+        assert!(first.items.iter().all(|i| !i.thumbnail_pending));
+
+        // A new file lands ahead of the cursor; the next page is unaffected.
+        write_at(&img, "new.png", 500);
+
+        let second = page(dir.path(), first.next_cursor.as_deref()).await;
+        assert_eq!(names(&second), vec!["c.png", "d.png"]);
+        assert!(second.next_cursor.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_time_cap_returns_pending_and_reports_later() {
+        let dir = tempdir().unwrap();
+        let img = dir.path().join("img");
+        fs::create_dir(&img).unwrap();
+        write_at(&img, "slow.png", 100);
+
+        let slow: ThumbnailFn = Arc::new(|source, dest| {
+            std::thread::sleep(Duration::from_millis(300));
+            copy_thumbnail(source, dest)
+        });
+        let (tx, rx) = tokio::sync::oneshot::channel();
+
+        let cap = Duration::from_millis(20);
+        let page = prefetch_page(dir.path(), "img", 10, None, cap, slow, move |ready| {
+            let _ = tx.send(ready);
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(page.items.len(), 1);
+        assert!(page.items[0].thumbnail_pending);
+
+        let ready = tokio::time::timeout(Duration::from_secs(5), rx)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(ready.len(), 1);
+        assert_eq!(ready[0].path, "img/slow.png");
+        assert!(Path::new(ready[0].thumbnail_path.as_ref().unwrap()).is_file());
+    }
+
+    #[test]
+    fn test_cursor_roundtrip_and_rejection() {
+        let cursor = encode_cursor(42, "a:b.png");
+        assert_eq!(decode_cursor(&cursor).unwrap(), (42, "a:b.png".to_string()));
+        assert!(decode_cursor("zz").is_err());
+        assert!(decode_cursor("abc").is_err());
+    }
+}
@@ -0,0 +1,239 @@
+// ============================================================================
+// GIT CHANGE GUTTER
+// ============================================================================
+//
+// Compares a note against its last committed version so the editor can show
+// a "changes since last commit" gutter. Staged and unstaged edits are treated
+// the same: the baseline is always HEAD.
+// ============================================================================
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::diff::{change_blocks, diff_lines};
+use crate::error::HibiscusError;
+use crate::git;
+use super::path::validate_path;
+
+/// How a file relates to git.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GutterStatus {
+    /// The file is not inside a git repository (or git is unavailable).
+    NotRepository,
+    /// The file is in a repository but has no version at HEAD.
+    Untracked,
+    /// The file exists at HEAD and was compared against it.
+    Tracked,
+}
+
+/// An inclusive, 1-based range of lines in the current text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LineRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Line-level changes of the current text relative to HEAD.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GutterDiff {
+    pub status: GutterStatus,
+    /// Lines that did not exist at HEAD
+    pub added: Vec<LineRange>,
+    /// Lines that replace different lines at HEAD
+    pub modified: Vec<LineRange>,
+    /// Deletion markers, anchored to the line above the removed block
+    /// (0 when lines were removed from the top of the file)
+    pub deleted: Vec<usize>,
+}
+
+impl GutterDiff {
+    fn empty(status: GutterStatus) -> Self {
+        Self {
+            status,
+            added: Vec::new(),
+            modified: Vec::new(),
+            deleted: Vec::new(),
+        }
+    }
+}
+
+/// Returns the lines changed in a note since the last commit.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+/// * `path` - Note to compare
+/// * `buffer` - Unsaved editor content; compared instead of the file on disk
+///
+/// # Returns
+/// * `Ok(GutterDiff)` - Added, modified and deleted line markers
+/// * `Err(HibiscusError)` - If a path is invalid or the file cannot be read
+///
+/// # Notes
+/// Workspaces outside a repository return `not_repository` with no markers.
+/// Untracked files are reported as entirely added.
+#[tauri::command]
+pub async fn get_uncommitted_changes(
+    root: String,
+    path: String,
+    buffer: Option<String>,
+) -> Result<GutterDiff, HibiscusError> {
+    let root = PathBuf::from(&root);
+    let path = PathBuf::from(&path);
+
+    // Validate paths
+    validate_path(&root)?;
+    validate_path(&path)?;
+
+    let current = match buffer {
+        Some(buffer) => buffer,
+        None => tokio::fs::read_to_string(&path).await.map_err(|e| {
+            HibiscusError::Io(format!("Failed to read '{}': {}", path.display(), e))
+        })?,
+    };
+
+    tokio::task::spawn_blocking(move || Ok(uncommitted_changes(&path, &current)))
+        .await
+        .map_err(|e| HibiscusError::Io(format!("Git diff task failed: {}", e)))?
+}
+
+/// Computes gutter markers for `current` against the HEAD version of `path`.
+fn uncommitted_changes(path: &Path, current: &str) -> GutterDiff {
+    let Some(file) = git::locate(path) else {
+        return GutterDiff::empty(GutterStatus::NotRepository);
+    };
+
+    let new_lines: Vec<&str> = current.lines().collect();
+
+    let Some(committed) = git::head_contents(&file) else {
+        let mut diff = GutterDiff::empty(GutterStatus::Untracked);
+        if !new_lines.is_empty() {
+            diff.added.push(LineRange {
+                start: 1,
+                end: new_lines.len(),
+            });
+        }
+        return diff;
+    };
+
+    let old_lines: Vec<&str> = committed.lines().collect();
+    let mut diff = GutterDiff::empty(GutterStatus::Tracked);
+
+    for block in change_blocks(&diff_lines(&old_lines, &new_lines)) {
+        if block.new_count == 0 {
+            // `new_start` is the 0-based index after the removal point,
+            // which is also the 1-based number of the line above it.
+            diff.deleted.push(block.new_start);
+            continue;
+        }
+
+        let range = LineRange {
+            start: block.new_start + 1,
+            end: block.new_start + block.new_count,
+        };
+        if block.old_count == 0 {
+            diff.added.push(range);
+        } else {
+            diff.modified.push(range);
+        }
+    }
+
+    diff
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .status()
+            .expect("git must be installed to run these tests");
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    /// Creates a repository with `note.md` committed as `content`.
+    fn repo_with_note(content: &str) -> (TempDir, PathBuf) {
+        let dir = TempDir::new().unwrap();
+        git(dir.path(), &["init", "-q"]);
+        let note = dir.path().join("note.md");
+        fs::write(&note, content).unwrap();
+        git(dir.path(), &["add", "note.md"]);
+        git(dir.path(), &["commit", "-q", "-m", "initial"]);
+        (dir, note)
+    }
+
+    fn range(start: usize, end: usize) -> LineRange {
+        LineRange { start, end }
+    }
+
+    #[test]
+    fn test_staged_and_unstaged_changes_compare_against_head() {
+        let (dir, note) = repo_with_note("one\ntwo\nthree\n");
+
+        // Staged modification of line 2...
+        fs::write(&note, "one\nTWO\nthree\n").unwrap();
+        git(dir.path(), &["add", "note.md"]);
+        // ...plus an unstaged appended line.
+        fs::write(&note, "one\nTWO\nthree\nfour\n").unwrap();
+
+        let diff = uncommitted_changes(&note, &fs::read_to_string(&note).unwrap());
+        assert_eq!(diff.status, GutterStatus::Tracked);
+        assert_eq!(diff.modified, vec![range(2, 2)]);
+        assert_eq!(diff.added, vec![range(4, 4)]);
+        assert!(diff.deleted.is_empty());
+    }
+
+    #[test]
+    fn test_deletions_anchor_to_line_above() {
+        let (_dir, note) = repo_with_note("a\nb\nc\nd\n");
+
+        let diff = uncommitted_changes(&note, "a\nd\n");
+        assert_eq!(diff.deleted, vec![1]);
+
+        let diff = uncommitted_changes(&note, "b\nc\nd\n");
+        assert_eq!(diff.deleted, vec![0]);
+    }
+
+    #[test]
+    fn test_buffer_overrides_disk() {
+        let (_dir, note) = repo_with_note("a\nb\n");
+
+        // Disk is unchanged; the buffer has an unsaved edit.
+        let diff = uncommitted_changes(&note, "a\nb\nc\n");
+        assert_eq!(diff.added, vec![range(3, 3)]);
+    }
+
+    #[test]
+    fn test_untracked_file_is_all_added() {
+        let (dir, _note) = repo_with_note("a\n");
+        let new_note = dir.path().join("new.md");
+        fs::write(&new_note, "x\ny\n").unwrap();
+
+        let diff = uncommitted_changes(&new_note, "x\ny\n");
+        assert_eq!(diff.status, GutterStatus::Untracked);
+        assert_eq!(diff.added, vec![range(1, 2)]);
+    }
+
+    #[test]
+    fn test_outside_repository() {
+        let dir = TempDir::new().unwrap();
+        let note = dir.path().join("note.md");
+        fs::write(&note, "a\n").unwrap();
+
+        let diff = uncommitted_changes(&note, "a\n");
+        assert_eq!(diff, GutterDiff::empty(GutterStatus::NotRepository));
+    }
+}
@@ -0,0 +1,177 @@
+// ============================================================================
+// VAULT INSIGHTS
+// ============================================================================
+//
+// Aggregate statistics over the note link graph (`crate::graph`) for the
+// insights panel.
+// ============================================================================
+
+use serde::Serialize;
+use std::path::PathBuf;
+
+use crate::error::HibiscusError;
+use crate::graph::LinkGraph;
+use crate::limits::LimitHit;
+use super::path::validate_path;
+
+/// Number of entries returned in `most_linked`.
+const MOST_LINKED_LIMIT: usize = 10;
+
+/// A note and how many distinct notes link to it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LinkedNote {
+    pub path: String,
+    pub inbound: usize,
+}
+
+/// Link connectivity of the notes within one folder (including subfolders).
+#[derive(Debug, Clone, Serialize)]
+pub struct FolderLinkMetrics {
+    /// Notes in the folder
+    pub note_count: usize,
+    /// Links between two notes inside the folder
+    pub internal_links: usize,
+    /// Links from notes in the folder to notes outside it
+    pub external_links: usize,
+    /// Links from notes in the folder that resolve to no note
+    pub unresolved_links: usize,
+    /// Notes in the folder that no other note links to
+    pub orphans: Vec<String>,
+    /// Notes in the folder with the most inbound links (from anywhere)
+    pub most_linked: Vec<LinkedNote>,
+    /// Folders the depth limit left out of the counts
+    pub limits_hit: Vec<LimitHit>,
+}
+
+/// Computes link connectivity metrics for a folder's notes.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+/// * `folder_rel` - Folder relative to the root ("" for the whole vault)
+///
+/// # Returns
+/// * `Ok(FolderLinkMetrics)` - Link counts, orphans and most-linked notes
+/// * `Err(HibiscusError)` - If the root or folder is invalid
+///
+/// # Notes
+/// Links are counted once per (source, target) pair. Inbound links are
+/// counted from the whole vault, so a note linked only from another folder
+/// is not an orphan.
+#[tauri::command]
+pub async fn folder_link_metrics(
+    root: String,
+    folder_rel: String,
+) -> Result<FolderLinkMetrics, HibiscusError> {
+    let root = PathBuf::from(&root);
+
+    // Validate paths
+    validate_path(&root)?;
+    let folder_path = root.join(&folder_rel);
+    validate_path(&folder_path)?;
+
+    if !folder_path.is_dir() {
+        return Err(HibiscusError::InvalidPathType {
+            path: folder_path.to_string_lossy().into(),
+            expected: "directory".into(),
+            actual: "file".into(),
+        });
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let graph = LinkGraph::build(&root);
+        Ok(compute_metrics(&graph, &folder_rel))
+    })
+    .await
+    .map_err(|e| HibiscusError::Io(format!("Link metrics task failed: {}", e)))?
+}
+
+fn compute_metrics(graph: &LinkGraph, folder_rel: &str) -> FolderLinkMetrics {
+    let prefix = folder_rel.replace('\\', "/").trim_matches('/').to_string();
+    let in_folder = |note: &str| {
+        prefix.is_empty()
+            || note
+                .strip_prefix(&prefix)
+                .is_some_and(|rest| rest.starts_with('/'))
+    };
+
+    let notes: Vec<&String> = graph.notes.iter().filter(|n| in_folder(n)).collect();
+
+    let mut internal_links = 0;
+    let mut external_links = 0;
+    let mut unresolved_links = 0;
+    for note in &notes {
+        if let Some(targets) = graph.outgoing.get(*note) {
+            let internal = targets.iter().filter(|t| in_folder(t)).count();
+            internal_links += internal;
+            external_links += targets.len() - internal;
+        }
+        unresolved_links += graph.unresolved.get(*note).map_or(0, Vec::len);
+    }
+
+    let orphans = notes
+        .iter()
+        .filter(|n| graph.inbound_count(n) == 0)
+        .map(|n| n.to_string())
+        .collect();
+
+    let mut most_linked: Vec<LinkedNote> = notes
+        .iter()
+        .map(|n| LinkedNote {
+            path: n.to_string(),
+            inbound: graph.inbound_count(n),
+        })
+        .filter(|n| n.inbound > 0)
+        .collect();
+    most_linked.sort_by(|a, b| b.inbound.cmp(&a.inbound).then_with(|| a.path.cmp(&b.path)));
+    most_linked.truncate(MOST_LINKED_LIMIT);
+
+    FolderLinkMetrics {
+        note_count: notes.len(),
+        internal_links,
+        external_links,
+        unresolved_links,
+        orphans,
+        most_linked,
+        limits_hit: graph.limits_hit.clone(),
+    }
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_folder_metrics_on_fixture() {
+        let dir = tempdir().unwrap();
+        let course = dir.path().join("course");
+        fs::create_dir_all(course.join("week1")).unwrap();
+        fs::create_dir_all(dir.path().join("inbox")).unwrap();
+
+        fs::write(course.join("Index.md"), "[[Graphs]] [[Trees]] [[Inbox Note]] [[Nope]]").unwrap();
+        fs::write(course.join("week1").join("Graphs.md"), "[[Trees]]").unwrap();
+        fs::write(course.join("Trees.md"), "").unwrap();
+        fs::write(course.join("Lonely.md"), "no links").unwrap();
+        fs::write(dir.path().join("inbox").join("Inbox Note.md"), "[[Lonely]]").unwrap();
+
+        let metrics = folder_link_metrics(dir.path().to_string_lossy().into(), "course".into())
+            .await
+            .unwrap();
+
+        assert_eq!(metrics.note_count, 4);
+        assert_eq!(metrics.internal_links, 3);
+        assert_eq!(metrics.external_links, 1);
+        assert_eq!(metrics.unresolved_links, 1);
+        // Lonely is linked from outside the folder, so only Index is orphaned
+        assert_eq!(metrics.orphans, vec!["course/Index.md"]);
+        assert_eq!(
+            metrics.most_linked[0],
+            LinkedNote { path: "course/Trees.md".into(), inbound: 2 }
+        );
+    }
+}
@@ -0,0 +1,19 @@
+// ============================================================================
+// BACKGROUND JOB STATUS
+// ============================================================================
+
+use crate::error::HibiscusError;
+use crate::jobs::{JobProgress, JOBS};
+
+/// Returns the latest progress of a background job.
+///
+/// # Arguments
+/// * `job_id` - Id returned by the command that started the job
+///
+/// # Returns
+/// * `Ok(Some(JobProgress))` - Current snapshot
+/// * `Ok(None)` - Unknown id, or a finished job that was pruned
+#[tauri::command]
+pub fn get_job_status(job_id: String) -> Result<Option<JobProgress>, HibiscusError> {
+    Ok(JOBS.get(&job_id))
+}
@@ -0,0 +1,797 @@
+// ============================================================================
+// NOTE CREATION FROM WIKI-LINKS + RELATIVE LINKS
+// ============================================================================
+//
+// Clicking an unresolved `[[link]]` creates the note it points to. The
+// backend decides where the note lives (per workspace setting), turns the
+// link text into a safe filename, fills in the default template, and tells
+// the frontend which link text now resolves to the note.
+//
+// A `[[link]]` naming several files (two notes called "Index") is resolved
+// by `resolve_wikilink`, which lists the candidates for the user to pick.
+//
+// Inserting a link to a file goes through `make_relative_link`; relative
+// markdown link targets are always built by `crate::markdown::relative_link`.
+// Pasted images get a fresh file in the note's attachments folder from
+// `paste_image_target`.
+// ============================================================================
+
+use chrono::{Local, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+use crate::error::HibiscusError;
+use crate::limits::Limits;
+use crate::markdown::{is_markdown_extension, relative_link};
+use crate::tree::read_dir_limited;
+use crate::workspace::{Node, NodeType};
+use crate::workspace::{NewNotePlacement, WorkspaceSettings};
+use super::files::{numbered_path, MAX_AUTO_RENAME};
+use super::path::{find_workspace_root, validate_path, validate_path_within_root};
+
+/// Filename used when the link text sanitizes to nothing.
+const UNTITLED: &str = "Untitled";
+
+/// Characters that are invalid in filenames on at least one platform.
+const FORBIDDEN_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*', '#', '^', '[', ']'];
+
+/// Device names Windows refuses as file stems.
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Per-call overrides for the workspace's new-note settings.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LinkNoteOptions {
+    /// Placement policy (defaults to `settings.new_note_placement`)
+    pub placement: Option<NewNotePlacement>,
+    /// Folder for `default_folder` placement (defaults to `settings.new_note_folder`)
+    pub folder: Option<String>,
+    /// Template path relative to the root (defaults to `settings.new_note_template`)
+    pub template: Option<String>,
+}
+
+/// The note a link now resolves to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CreatedNote {
+    /// Absolute path of the note
+    pub path: String,
+    /// Path relative to the workspace root, with forward slashes
+    pub relative_path: String,
+    /// The link as written, e.g. `[[My Note]]`
+    pub link: String,
+    /// Replacement link when sanitization changed the filename, e.g.
+    /// `[[What is it|What is it?]]`; `None` if `link` already resolves
+    pub rewritten_link: Option<String>,
+    /// `true` if a note with the sanitized name already existed and was
+    /// returned instead of creating a new one
+    pub existed: bool,
+}
+
+/// Creates the note an unresolved wiki-link points to.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+/// * `from_note` - Note containing the link (absolute path)
+/// * `link_text` - Text inside the brackets, e.g. `Topics/Graphs|graphs`
+/// * `options` - Overrides for the workspace's new-note settings
+///
+/// # Returns
+/// * `Ok(CreatedNote)` - The created (or already existing) note
+/// * `Err(HibiscusError)` - If a path is invalid or the note cannot be written
+///
+/// # Behavior
+/// - `#heading` / `^block` suffixes and `|alias` are ignored for naming
+/// - The note is written atomically (temp file + rename)
+/// - An existing file with the sanitized name is returned with `existed`
+#[tauri::command]
+pub async fn create_note_for_link(
+    root: String,
+    from_note: String,
+    link_text: String,
+    options: Option<LinkNoteOptions>,
+) -> Result<CreatedNote, HibiscusError> {
+    let root = PathBuf::from(&root);
+    let from_note = PathBuf::from(&from_note);
+
+    // Validate paths
+    validate_path(&root)?;
+    validate_path_within_root(&from_note, &root)?;
+
+    let options = options.unwrap_or_default();
+    tokio::task::spawn_blocking(move || create_note_blocking(&root, &from_note, &link_text, options))
+        .await
+        .map_err(|e| HibiscusError::Io(format!("Note creation task failed: {}", e)))?
+}
+
+/// A file a wiki-link can point to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LinkCandidate {
+    /// Absolute path of the file
+    pub path: String,
+    /// Path relative to the workspace root, with forward slashes
+    pub relative_path: String,
+}
+
+/// The files a wiki-link matches.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WikilinkResolution {
+    /// The match when there is exactly one
+    pub resolved: Option<LinkCandidate>,
+    /// Every match, closest to the linking note first
+    pub candidates: Vec<LinkCandidate>,
+}
+
+/// Lists the files a wiki-link may point to, so the user can pick one
+/// when several match.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+/// * `link` - Text inside the brackets, e.g. `Index` or `notes/Index|home`
+/// * `from_note` - Note containing the link (absolute path), used to rank
+///   candidates; without it, candidates closer to the root come first
+///
+/// # Returns
+/// * `Ok(WikilinkResolution)` - Matches ranked by folder distance from the
+///   linking note (same folder first), then by path length and name
+/// * `Err(HibiscusError)` - If a path is invalid
+///
+/// # Matching
+/// - A bare name matches markdown notes by file stem (`[[Index]]` ->
+///   `Index.md`) and any file by its full name (`[[chart.png]]`),
+///   ignoring case
+/// - Folders in the link must match the file's last folders
+/// - `#heading` / `^block` suffixes and `|alias` are ignored
+#[tauri::command]
+pub async fn resolve_wikilink(
+    root: String,
+    link: String,
+    from_note: Option<String>,
+) -> Result<WikilinkResolution, HibiscusError> {
+    let root = PathBuf::from(&root);
+
+    // Validate paths
+    validate_path(&root)?;
+    let from_dir = match from_note {
+        Some(from_note) => {
+            let from_note = PathBuf::from(&from_note);
+            validate_path_within_root(&from_note, &root)?;
+            from_note.parent().map(|dir| relative_string(&root, dir)).unwrap_or_default()
+        }
+        None => String::new(),
+    };
+
+    tokio::task::spawn_blocking(move || {
+        let (tree, _) = read_dir_limited(&root, &root, Limits::load(&root).tree_depth, false);
+        let mut files = Vec::new();
+        collect_files(&tree, &mut files);
+
+        let candidates: Vec<LinkCandidate> = rank_link_candidates(&files, &link, &from_dir)
+            .into_iter()
+            .map(|relative_path| LinkCandidate {
+                path: join_segments(&root, relative_path.split('/')).to_string_lossy().to_string(),
+                relative_path,
+            })
+            .collect();
+        let resolved = match candidates.as_slice() {
+            [only] => Some(only.clone()),
+            _ => None,
+        };
+        WikilinkResolution { resolved, candidates }
+    })
+    .await
+    .map_err(|e| HibiscusError::Io(format!("Link resolution task failed: {}", e)))
+}
+
+/// Builds a markdown link from one note to another file, relative to the
+/// note's folder.
+///
+/// # Arguments
+/// * `from_note` - Note the link is inserted into (absolute path)
+/// * `to_file` - File to link to (absolute path)
+/// * `title` - Link text (defaults to the target's file stem)
+///
+/// # Returns
+/// * `Ok(String)` - e.g. `[Diagram](../images/cell%20diagram.png)`
+/// * `Err(HibiscusError)` - If a path is invalid or the two paths share no
+///   root (e.g. different drives)
+#[tauri::command]
+pub async fn make_relative_link(
+    from_note: String,
+    to_file: String,
+    title: Option<String>,
+) -> Result<String, HibiscusError> {
+    let from_note = PathBuf::from(&from_note);
+    let to_file = PathBuf::from(&to_file);
+
+    // Validate paths
+    validate_path(&from_note)?;
+    validate_path(&to_file)?;
+
+    let href = from_note
+        .parent()
+        .and_then(|from_dir| relative_path_link(from_dir, &to_file))
+        .ok_or_else(|| {
+            HibiscusError::PathValidation(format!(
+                "No relative path from '{}' to '{}'",
+                from_note.display(),
+                to_file.display()
+            ))
+        })?;
+
+    let title = title
+        .filter(|title| !title.trim().is_empty())
+        .or_else(|| to_file.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+        .unwrap_or_default();
+    Ok(format!("[{}]({})", title, href))
+}
+
+/// Default for the `attachments_folder` setting.
+const DEFAULT_ATTACHMENTS_FOLDER: &str = "assets";
+
+/// Longest image extension accepted by `paste_image_target`.
+const MAX_EXTENSION_LEN: usize = 10;
+
+/// Where a pasted image goes and how the note links to it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PasteTarget {
+    /// Absolute path of the new, still empty file
+    pub path: String,
+    /// Markdown to insert, e.g. `![](assets/Pasted%20image%2020240301093015.png)`
+    pub link: String,
+}
+
+/// Picks a collision-free file for an image pasted into a note.
+///
+/// The file is `Pasted image YYYYMMDDHHMMSS.<ext>` in the attachments
+/// folder next to the note (`settings.attachments_folder`, default
+/// `assets`), with ` 1`, ` 2`, ... appended when the name is taken. The
+/// folder is created and the file reserved empty, so a second paste in the
+/// same second gets its own name; write the bytes with `write_binary_file`.
+///
+/// # Arguments
+/// * `note_path` - Note the image is pasted into (absolute path)
+/// * `ext` - Image extension, e.g. `png` (a leading dot is ignored)
+///
+/// # Returns
+/// * `Ok(PasteTarget)` - The reserved file and the link to insert
+/// * `Err(HibiscusError)` - If a path, the extension or the configured
+///   folder is invalid, or the file cannot be created
+#[tauri::command]
+pub async fn paste_image_target(note_path: String, ext: String) -> Result<PasteTarget, HibiscusError> {
+    let note_path = PathBuf::from(&note_path);
+
+    // Validate path
+    validate_path(&note_path)?;
+
+    let ext = ext.trim().trim_start_matches('.').to_lowercase();
+    if ext.is_empty() || ext.len() > MAX_EXTENSION_LEN || !ext.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(HibiscusError::PathValidation(format!("Invalid image extension '{}'", ext)));
+    }
+
+    let folder = find_workspace_root(&note_path)
+        .and_then(|root| WorkspaceSettings::load(&root).attachments_folder)
+        .filter(|folder| !folder.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_ATTACHMENTS_FOLDER.to_string());
+
+    let now = Local::now().naive_local();
+    tokio::task::spawn_blocking(move || reserve_paste_target(&note_path, &folder, &ext, now))
+        .await
+        .map_err(|e| HibiscusError::Io(format!("Paste target task failed: {}", e)))?
+}
+
+fn reserve_paste_target(
+    note_path: &Path,
+    folder: &str,
+    ext: &str,
+    now: NaiveDateTime,
+) -> Result<PasteTarget, HibiscusError> {
+    // The folder must stay below the note's folder
+    let folder = Path::new(folder.trim());
+    if !folder.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(HibiscusError::Workspace(format!(
+            "Invalid attachments folder '{}': expected a relative path without '..'",
+            folder.display()
+        )));
+    }
+
+    let note_dir = note_path.parent().unwrap_or(Path::new(""));
+    let dir = note_dir.join(folder);
+    std::fs::create_dir_all(&dir).map_err(|e| {
+        HibiscusError::Io(format!("Failed to create '{}': {}", dir.display(), e))
+    })?;
+
+    // Reserve the name with create_new, so concurrent pastes never share it
+    let name = format!("Pasted image {}.{}", now.format("%Y%m%d%H%M%S"), ext);
+    let mut attempt = 0;
+    let path = loop {
+        let candidate = numbered_path(&dir.join(&name), attempt);
+        match std::fs::OpenOptions::new().write(true).create_new(true).open(&candidate) {
+            Ok(_) => break candidate,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists && attempt < MAX_AUTO_RENAME => {
+                attempt += 1;
+            }
+            Err(e) => {
+                return Err(HibiscusError::Io(format!(
+                    "Failed to create '{}': {}",
+                    candidate.display(),
+                    e
+                )))
+            }
+        }
+    };
+
+    let href = relative_path_link(note_dir, &path).unwrap_or_default();
+    Ok(PasteTarget {
+        path: path.to_string_lossy().into_owned(),
+        link: format!("![]({})", href),
+    })
+}
+
+/// `relative_link` for filesystem paths; `None` if the two paths start
+/// from different roots or drives.
+fn relative_path_link(from_dir: &Path, target: &Path) -> Option<String> {
+    let anchor = |path: &Path| {
+        path.components()
+            .take_while(|c| !matches!(c, Component::Normal(_)))
+            .filter(|c| !matches!(c, Component::CurDir))
+            .map(|c| c.as_os_str().to_os_string())
+            .collect::<Vec<_>>()
+    };
+    if anchor(from_dir) != anchor(target) {
+        return None;
+    }
+
+    let segments = |path: &Path| {
+        path.components()
+            .filter_map(|c| match c {
+                Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    };
+    Some(relative_link(&segments(from_dir), &segments(target)))
+}
+
+/// The files (root-relative, forward slashes) that `link_text` matches,
+/// ranked by folder distance from `from_dir`, then path length and name.
+fn rank_link_candidates(files: &[String], link_text: &str, from_dir: &str) -> Vec<String> {
+    let target = parse_link(link_text).target.replace('\\', "/").to_lowercase();
+    let mut segments: Vec<&str> = target.split('/').map(str::trim).filter(|s| !s.is_empty()).collect();
+    let Some(name) = segments.pop() else {
+        return Vec::new();
+    };
+
+    let mut matches: Vec<(usize, &String)> = files
+        .iter()
+        .filter(|file| {
+            let lower = file.to_lowercase();
+            let mut parts: Vec<&str> = lower.split('/').collect();
+            let file_name = parts.pop().unwrap_or_default();
+            let stem_matches = Path::new(file_name)
+                .extension()
+                .is_some_and(|ext| is_markdown_extension(&ext.to_string_lossy()))
+                && file_name.rsplit_once('.').is_some_and(|(stem, _)| stem == name);
+            (file_name == name || stem_matches) && parts.ends_with(&segments)
+        })
+        .map(|file| (folder_distance(from_dir, file.rsplit_once('/').map_or("", |(dir, _)| dir)), file))
+        .collect();
+
+    matches.sort_by(|(a_distance, a), (b_distance, b)| {
+        a_distance.cmp(b_distance).then_with(|| a.len().cmp(&b.len())).then_with(|| a.cmp(b))
+    });
+    matches.into_iter().map(|(_, file)| file.clone()).collect()
+}
+
+/// Steps up from folder `from` to a common ancestor and down into `to`.
+fn folder_distance(from: &str, to: &str) -> usize {
+    let from: Vec<&str> = from.split('/').filter(|s| !s.is_empty()).collect();
+    let to: Vec<&str> = to.split('/').filter(|s| !s.is_empty()).collect();
+    let shared = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    (from.len() - shared) + (to.len() - shared)
+}
+
+fn collect_files(nodes: &[Node], out: &mut Vec<String>) {
+    for node in nodes {
+        match node.node_type {
+            NodeType::File => out.extend(node.path.as_ref().map(|path| path.replace('\\', "/"))),
+            NodeType::Folder => collect_files(node.children.as_deref().unwrap_or_default(), out),
+        }
+    }
+}
+
+/// A wiki-link split into its parts.
+struct ParsedLink<'a> {
+    /// Target path as written, without heading/block suffix
+    target: &'a str,
+    /// `|alias`, if present
+    alias: Option<&'a str>,
+    /// `#heading` / `^block` suffix, if present (including the marker)
+    suffix: &'a str,
+}
+
+fn parse_link(link_text: &str) -> ParsedLink<'_> {
+    let (target, alias) = match link_text.split_once('|') {
+        Some((target, alias)) => (target, Some(alias)),
+        None => (link_text, None),
+    };
+    let split = target.find(['#', '^']).unwrap_or(target.len());
+    ParsedLink {
+        target: target[..split].trim(),
+        alias,
+        suffix: &target[split..],
+    }
+}
+
+fn create_note_blocking(
+    root: &Path,
+    from_note: &Path,
+    link_text: &str,
+    options: LinkNoteOptions,
+) -> Result<CreatedNote, HibiscusError> {
+    let settings = WorkspaceSettings::load(root);
+    let placement = options.placement.unwrap_or(settings.new_note_placement);
+    let link = parse_link(link_text);
+
+    // Split the target into folder segments and the note name
+    let raw_segments: Vec<&str> = link
+        .target
+        .split(['/', '\\'])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect();
+    let (raw_name, raw_folders) = match raw_segments.split_last() {
+        Some((name, folders)) => (*name, folders),
+        None => (UNTITLED, &[][..]),
+    };
+    let raw_stem = raw_name.strip_suffix(".md").unwrap_or(raw_name);
+
+    let stem = sanitize_segment(raw_stem);
+    let folders: Vec<String> = raw_folders.iter().map(|s| sanitize_segment(s)).collect();
+
+    let folder = match placement {
+        NewNotePlacement::SameFolder => from_note
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| root.to_path_buf()),
+        NewNotePlacement::DefaultFolder => {
+            let folder = options.folder.or(settings.new_note_folder).unwrap_or_default();
+            join_segments(root, folder.split(['/', '\\']))
+        }
+        NewNotePlacement::LinkPath => join_segments(root, &folders),
+    };
+    validate_path_within_root(&folder, root)?;
+
+    let path = folder.join(format!("{}.md", stem));
+    validate_path(&path)?;
+
+    let relative_path = relative_string(root, &path);
+    let existed = path.is_file();
+
+    if !existed {
+        let title = raw_stem.trim();
+        let template = options.template.or(settings.new_note_template);
+        let content = render_template(root, template.as_deref(), title)?;
+        write_new_note(&path, &content)?;
+    }
+
+    // The link keeps resolving if its target still names the file. Link
+    // resolution is by name (or by path for link-path placement).
+    let resolves = raw_stem == stem
+        && match placement {
+            NewNotePlacement::LinkPath => raw_folders.iter().eq(folders.iter()),
+            _ => raw_folders.is_empty(),
+        };
+
+    let original = format!("[[{}]]", link_text);
+    let rewritten_link = (!resolves).then(|| {
+        let target = match placement {
+            NewNotePlacement::LinkPath => relative_path.trim_end_matches(".md").to_string(),
+            _ => stem.clone(),
+        };
+        let display = link.alias.unwrap_or(link.target);
+        format!("[[{}{}|{}]]", target, link.suffix, display)
+    });
+
+    Ok(CreatedNote {
+        path: path.to_string_lossy().to_string(),
+        relative_path,
+        link: original,
+        rewritten_link,
+        existed,
+    })
+}
+
+/// Turns one path segment of link text into a portable file/folder name.
+///
+/// Forbidden characters become spaces, whitespace is collapsed, leading and
+/// trailing dots/spaces are trimmed, `..` runs are collapsed, and Windows
+/// device names get a trailing underscore.
+fn sanitize_segment(segment: &str) -> String {
+    let replaced: String = segment
+        .chars()
+        .map(|c| {
+            if FORBIDDEN_CHARS.contains(&c) || c.is_control() {
+                ' '
+            } else {
+                c
+            }
+        })
+        .collect();
+
+    let mut name = replaced.split_whitespace().collect::<Vec<_>>().join(" ");
+    while name.contains("..") {
+        name = name.replace("..", ".");
+    }
+    let name = name.trim_matches(|c: char| c == '.' || c == ' ').to_string();
+
+    if name.is_empty() {
+        return UNTITLED.to_string();
+    }
+    if RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(&name)) {
+        return format!("{}_", name);
+    }
+    name
+}
+
+/// Joins relative segments onto `root`, dropping empty and `.`/`..` parts.
+fn join_segments<I, S>(root: &Path, segments: I) -> PathBuf
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut path = root.to_path_buf();
+    for segment in segments {
+        let segment = segment.as_ref().trim();
+        if !segment.is_empty() && segment != "." && segment != ".." {
+            path.push(segment);
+        }
+    }
+    path
+}
+
+fn relative_string(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Builds the initial note content. `{{title}}` in the template is replaced
+/// with the link's title; without a template the note is just a heading.
+fn render_template(root: &Path, template: Option<&str>, title: &str) -> Result<String, HibiscusError> {
+    let Some(template) = template else {
+        return Ok(format!("# {}\n", title));
+    };
+
+    let path = join_segments(root, template.split(['/', '\\']));
+    let content = std::fs::read_to_string(&path).map_err(|e| {
+        HibiscusError::Io(format!("Failed to read template '{}': {}", path.display(), e))
+    })?;
+    Ok(content.replace("{{title}}", title))
+}
+
+/// Writes a new note via temp file + rename, creating parent folders.
+fn write_new_note(path: &Path, content: &str) -> Result<(), HibiscusError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| {
+            HibiscusError::Io(format!("Failed to create folder '{}': {}", parent.display(), e))
+        })?;
+    }
+
+    let temp_path = path.with_extension("md.tmp");
+    std::fs::write(&temp_path, content).map_err(|e| {
+        HibiscusError::Io(format!("Failed to write '{}': {}", temp_path.display(), e))
+    })?;
+    std::fs::rename(&temp_path, path).map_err(|e| {
+        let _ = std::fs::remove_file(&temp_path);
+        HibiscusError::Io(format!("Failed to create note '{}': {}", path.display(), e))
+    })
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::{tempdir, TempDir};
+
+    /// Workspace with `settings` and a linking note at `courses/algo/week1.md`.
+    fn workspace(settings: serde_json::Value) -> (TempDir, PathBuf) {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".hibiscus")).unwrap();
+        let doc = serde_json::json!({
+            "schema_version": "1.0",
+            "workspace": { "id": "w", "name": "w", "root": "." },
+            "settings": settings,
+            "tree": [],
+        });
+        fs::write(dir.path().join(".hibiscus").join("workspace.json"), doc.to_string()).unwrap();
+
+        let from = dir.path().join("courses").join("algo").join("week1.md");
+        fs::create_dir_all(from.parent().unwrap()).unwrap();
+        fs::write(&from, "See [[Graphs]]").unwrap();
+        (dir, from)
+    }
+
+    fn create(root: &Path, from: &Path, link: &str) -> CreatedNote {
+        create_note_blocking(root, from, link, LinkNoteOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn test_paste_targets_in_the_same_second_do_not_collide() {
+        let (_dir, from) = workspace(serde_json::json!({}));
+        let now = chrono::NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_opt(9, 30, 15)
+            .unwrap();
+
+        let first = reserve_paste_target(&from, "assets", "png", now).unwrap();
+        let second = reserve_paste_target(&from, "assets", "png", now).unwrap();
+
+        let assets = from.parent().unwrap().join("assets");
+        assert_eq!(PathBuf::from(&first.path), assets.join("Pasted image 20240301093015.png"));
+        assert_eq!(PathBuf::from(&second.path), assets.join("Pasted image 20240301093015 1.png"));
+        assert!(Path::new(&second.path).is_file());
+        assert_eq!(first.link, "![](assets/Pasted%20image%2020240301093015.png)");
+
+        assert!(reserve_paste_target(&from, "../outside", "png", now).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_paste_image_target_uses_configured_folder() {
+        let (_dir, from) = workspace(serde_json::json!({ "attachments_folder": "media/pasted" }));
+
+        let target = paste_image_target(from.to_string_lossy().into(), ".PNG".into()).await.unwrap();
+        assert!(target.path.ends_with(".png"));
+        assert!(target.link.starts_with("![](media/pasted/Pasted%20image%20"));
+        assert!(paste_image_target(from.to_string_lossy().into(), "p/ng".into()).await.is_err());
+    }
+
+    #[test]
+    fn test_same_folder_placement() {
+        let (dir, from) = workspace(serde_json::json!({}));
+        let note = create(dir.path(), &from, "Graphs");
+
+        assert_eq!(note.relative_path, "courses/algo/Graphs.md");
+        assert_eq!(note.link, "[[Graphs]]");
+        assert!(note.rewritten_link.is_none());
+        assert!(!note.existed);
+        assert_eq!(fs::read_to_string(&note.path).unwrap(), "# Graphs\n");
+    }
+
+    #[test]
+    fn test_default_folder_placement_with_template() {
+        let (dir, from) = workspace(serde_json::json!({
+            "new_note_placement": "default_folder",
+            "new_note_folder": "Inbox",
+            "new_note_template": "templates/note.md",
+        }));
+        fs::create_dir_all(dir.path().join("templates")).unwrap();
+        fs::write(dir.path().join("templates").join("note.md"), "---\ntitle: {{title}}\n---\n").unwrap();
+
+        let note = create(dir.path(), &from, "Graphs|graph theory");
+
+        assert_eq!(note.relative_path, "Inbox/Graphs.md");
+        assert_eq!(note.link, "[[Graphs|graph theory]]");
+        assert!(note.rewritten_link.is_none());
+        assert_eq!(fs::read_to_string(&note.path).unwrap(), "---\ntitle: Graphs\n---\n");
+    }
+
+    #[test]
+    fn test_link_path_placement() {
+        let (dir, from) = workspace(serde_json::json!({ "new_note_placement": "link_path" }));
+        let note = create(dir.path(), &from, "Topics/Graphs/BFS#Complexity");
+
+        assert_eq!(note.relative_path, "Topics/Graphs/BFS.md");
+        assert!(note.rewritten_link.is_none());
+        assert!(dir.path().join("Topics").join("Graphs").join("BFS.md").is_file());
+    }
+
+    #[test]
+    fn test_options_override_settings() {
+        let (dir, from) = workspace(serde_json::json!({ "new_note_placement": "link_path" }));
+        let options = LinkNoteOptions {
+            placement: Some(NewNotePlacement::SameFolder),
+            ..Default::default()
+        };
+        let note = create_note_blocking(dir.path(), &from, "Graphs", options).unwrap();
+        assert_eq!(note.relative_path, "courses/algo/Graphs.md");
+    }
+
+    #[test]
+    fn test_sanitization_divergence_offers_rewritten_link() {
+        let (dir, from) = workspace(serde_json::json!({}));
+        let note = create(dir.path(), &from, "What is O(n)?  <fast>");
+
+        assert_eq!(note.relative_path, "courses/algo/What is O(n) fast.md");
+        assert_eq!(note.link, "[[What is O(n)?  <fast>]]");
+        assert_eq!(
+            note.rewritten_link.as_deref(),
+            Some("[[What is O(n) fast|What is O(n)?  <fast>]]")
+        );
+    }
+
+    #[test]
+    fn test_existing_note_is_returned() {
+        let (dir, from) = workspace(serde_json::json!({}));
+        let existing = from.parent().unwrap().join("Graphs.md");
+        fs::write(&existing, "already here").unwrap();
+
+        let note = create(dir.path(), &from, "Graphs");
+        assert!(note.existed);
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "already here");
+    }
+
+    #[test]
+    fn test_sanitize_segment() {
+        assert_eq!(sanitize_segment("a/b:c"), "a b c");
+        assert_eq!(sanitize_segment("..hidden.."), "hidden");
+        assert_eq!(sanitize_segment("con"), "con_");
+        assert_eq!(sanitize_segment("???"), UNTITLED);
+    }
+
+    #[tokio::test]
+    async fn test_make_relative_link() {
+        let dir = tempdir().unwrap();
+        let path = |rel: &str| dir.path().join(rel).to_string_lossy().to_string();
+        let link = |from: &str, to: &str, title: Option<&str>| {
+            make_relative_link(path(from), path(to), title.map(String::from))
+        };
+
+        // Sibling file, title from the file stem
+        assert_eq!(
+            link("notes/week1.md", "notes/week2.md", None).await.unwrap(),
+            "[week2](week2.md)"
+        );
+        // File in a subfolder, spaces encoded
+        assert_eq!(
+            link("notes/week1.md", "notes/images/cell diagram.png", Some("Diagram")).await.unwrap(),
+            "[Diagram](images/cell%20diagram.png)"
+        );
+        // Climbing out of the note's folder
+        assert_eq!(
+            link("courses/algo/week1.md", "notes/graphs.md", Some("Graphs")).await.unwrap(),
+            "[Graphs](../../notes/graphs.md)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_wikilink_unique_and_ambiguous() {
+        let (dir, from) = workspace(serde_json::json!({}));
+        for file in ["Index.md", "courses/Index.md", "courses/algo/Index.md", "courses/Graphs.md", "img/chart.png"] {
+            let path = dir.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, "").unwrap();
+        }
+        let root = dir.path().to_string_lossy().to_string();
+        let from = Some(from.to_string_lossy().to_string());
+        let relative = |resolution: &WikilinkResolution| -> Vec<String> {
+            resolution.candidates.iter().map(|c| c.relative_path.clone()).collect()
+        };
+
+        let unique = resolve_wikilink(root.clone(), "graphs#Intro|graphs".into(), from.clone()).await.unwrap();
+        assert_eq!(relative(&unique), ["courses/Graphs.md"]);
+        assert_eq!(unique.resolved.unwrap().path, dir.path().join("courses").join("Graphs.md").to_string_lossy());
+        let image = resolve_wikilink(root.clone(), "chart.png".into(), None).await.unwrap();
+        assert_eq!(relative(&image), ["img/chart.png"]);
+
+        // Same folder first, then the nearest folders
+        let ambiguous = resolve_wikilink(root.clone(), "Index".into(), from).await.unwrap();
+        assert!(ambiguous.resolved.is_none());
+        assert_eq!(relative(&ambiguous), ["courses/algo/Index.md", "courses/Index.md", "Index.md"]);
+        // Without a linking note the root comes first; folders narrow it down
+        let from_root = resolve_wikilink(root.clone(), "Index".into(), None).await.unwrap();
+        assert_eq!(relative(&from_root)[0], "Index.md");
+        let narrowed = resolve_wikilink(root, "algo/Index".into(), None).await.unwrap();
+        assert_eq!(relative(&narrowed), ["courses/algo/Index.md"]);
+    }
+}
@@ -0,0 +1,282 @@
+// ============================================================================
+// NOTE LINTING
+// ============================================================================
+//
+// Checks notes against the workspace's style rules (`crate::lint`), applies
+// selected fixes, and tallies a whole vault in the background. Rule
+// overrides come from the `lint` workspace setting unless a call passes its
+// own ruleset.
+// ============================================================================
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tauri::Emitter;
+
+use crate::error::HibiscusError;
+use crate::jobs::JOBS;
+use crate::limits::Limits;
+use crate::lint::{apply_fixes, lint, LintContext, LintDiagnostic, LintSettings};
+use crate::tree::read_dir_limited;
+use crate::workspace::WorkspaceSettings;
+use super::files::save_atomically;
+use super::path::{scope_to_workspace, validate_path};
+
+/// Checks a note against the workspace's lint rules.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+/// * `path` - The note, absolute or relative to the root. Needed for fixes
+///   that depend on the note's folder.
+/// * `content` - Text to check instead of the file, e.g. an unsaved buffer
+/// * `ruleset` - Rule overrides to use instead of the `lint` setting
+///
+/// # Returns
+/// * `Ok(Vec<LintDiagnostic>)` - Violations, in document order
+/// * `Err(HibiscusError)` - If neither `path` nor `content` is given, or the
+///   note cannot be read
+#[tauri::command]
+pub async fn lint_note(
+    root: String,
+    path: Option<String>,
+    content: Option<String>,
+    ruleset: Option<LintSettings>,
+) -> Result<Vec<LintDiagnostic>, HibiscusError> {
+    let root = PathBuf::from(&root);
+    validate_path(&root)?;
+    let file = path.map(|path| validate_path(&root.join(path))).transpose()?;
+
+    let content = match (content, &file) {
+        (Some(content), _) => content,
+        (None, Some(file)) => read_note(file).await?,
+        (None, None) => {
+            return Err(HibiscusError::PathValidation("lint_note needs a path or content".into()));
+        }
+    };
+
+    let settings = ruleset.unwrap_or_else(|| WorkspaceSettings::load(&root).lint);
+    let context = lint_context(&root, file.as_deref());
+    Ok(lint(&content, &settings, &context))
+}
+
+/// Outcome of `apply_lint_fixes`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LintFixReport {
+    /// Diagnostics whose fix was written
+    pub applied: Vec<String>,
+    /// Requested ids that were not found, have no fix, or overlapped an
+    /// applied fix; lint again to see what is left
+    pub skipped: Vec<String>,
+}
+
+/// Applies the fixes of selected diagnostics to a note and saves it.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+/// * `path` - The note, absolute or relative to the root
+/// * `diagnostic_ids` - Ids from `lint_note` on the note as saved
+///
+/// # Returns
+/// * `Ok(LintFixReport)` - Which fixes were applied
+/// * `Err(HibiscusError)` - If the note cannot be read or written
+///
+/// # Notes
+/// The note is linted again with the workspace's rules, so ids of a
+/// changed note no longer match and are skipped. The result is saved with
+/// the same atomic write as `write_text_file`, and only if a fix applied.
+#[tauri::command]
+pub async fn apply_lint_fixes(
+    root: String,
+    path: String,
+    diagnostic_ids: Vec<String>,
+) -> Result<LintFixReport, HibiscusError> {
+    let root = PathBuf::from(&root);
+    validate_path(&root)?;
+    let file = validate_path(&root.join(&path))?;
+    let file = scope_to_workspace(&file)?;
+
+    let content = read_note(&file).await?;
+    let diagnostics = lint(&content, &WorkspaceSettings::load(&root).lint, &lint_context(&root, Some(&file)));
+
+    let selected: Vec<&LintDiagnostic> = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic_ids.contains(&diagnostic.id))
+        .collect();
+    let outcome = apply_fixes(&content, &selected);
+
+    let mut skipped = outcome.skipped;
+    skipped.extend(
+        diagnostic_ids
+            .iter()
+            .filter(|id| !selected.iter().any(|diagnostic| diagnostic.id == **id))
+            .cloned(),
+    );
+
+    if !outcome.applied.is_empty() {
+        save_atomically(&file, outcome.content.as_bytes()).await?;
+    }
+    Ok(LintFixReport { applied: outcome.applied, skipped })
+}
+
+/// Diagnostics of one rule across a vault.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RuleTally {
+    pub diagnostics: usize,
+    /// Notes with at least one diagnostic
+    pub notes: usize,
+    /// Diagnostics that come with a fix
+    pub fixable: usize,
+}
+
+/// Payload of `lint-vault-complete`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct VaultLintReport {
+    pub job_id: String,
+    pub notes_checked: usize,
+    /// Rule id -> tally; rules without diagnostics are left out
+    pub rules: BTreeMap<String, RuleTally>,
+}
+
+/// Lints every note of a vault in the background.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+///
+/// # Returns
+/// * `Ok(String)` - Job id. Progress is reported through `job-progress`
+///   (see `get_job_status`); the `VaultLintReport` arrives as
+///   `lint-vault-complete`.
+/// * `Err(HibiscusError)` - If the root is invalid
+#[tauri::command]
+pub async fn lint_vault(root: String, window: tauri::Window) -> Result<String, HibiscusError> {
+    let root = PathBuf::from(&root);
+    validate_path(&root)?;
+
+    let job_id = JOBS.start("lint_vault");
+    let id = job_id.clone();
+    tauri::async_runtime::spawn(async move {
+        let progress_window = window.clone();
+        let progress_id = id.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            lint_vault_report(&root, |done, total| {
+                if let Some(progress) = JOBS.update(&progress_id, "linting", done as u64, total as u64) {
+                    let _ = progress_window.emit("job-progress", progress);
+                }
+            })
+        })
+        .await
+        .map_err(|e| format!("Vault lint task failed: {}", e));
+
+        if let Some(progress) = JOBS.finish(&id, result.as_ref().err().cloned()) {
+            let _ = window.emit("job-progress", progress);
+        }
+        if let Ok(report) = result {
+            let report = VaultLintReport { job_id: id, ..report };
+            if let Err(e) = window.emit("lint-vault-complete", report) {
+                eprintln!("[Hibiscus] Error emitting event: {}", e);
+            }
+        }
+    });
+
+    Ok(job_id)
+}
+
+/// Lints the notes under `root` and tallies the diagnostics per rule.
+/// `on_progress` receives `(notes done, total notes)`.
+fn lint_vault_report(root: &Path, mut on_progress: impl FnMut(usize, usize)) -> VaultLintReport {
+    let (tree, _) = read_dir_limited(root, root, Limits::load(root).tree_depth, false);
+    let mut notes = Vec::new();
+    crate::graph::collect_notes(&tree, &mut notes);
+    let settings = WorkspaceSettings::load(root).lint;
+
+    let mut report = VaultLintReport::default();
+    for (done, note) in notes.iter().enumerate() {
+        let file = root.join(note);
+        if let Ok(content) = std::fs::read_to_string(&file) {
+            report.notes_checked += 1;
+            let mut seen = Vec::new();
+            for diagnostic in lint(&content, &settings, &lint_context(root, Some(&file))) {
+                let tally = report.rules.entry(diagnostic.rule.clone()).or_default();
+                tally.diagnostics += 1;
+                tally.fixable += usize::from(diagnostic.fix.is_some());
+                if !seen.contains(&diagnostic.rule) {
+                    tally.notes += 1;
+                    seen.push(diagnostic.rule);
+                }
+            }
+        }
+        on_progress(done + 1, notes.len());
+    }
+    report
+}
+
+async fn read_note(file: &Path) -> Result<String, HibiscusError> {
+    tokio::fs::read_to_string(file)
+        .await
+        .map_err(|e| HibiscusError::Io(format!("Failed to read file '{}': {}", file.display(), e)))
+}
+
+/// The root and note folder as lint rules expect them.
+fn lint_context(root: &Path, file: Option<&Path>) -> LintContext {
+    let slashes = |path: &Path| path.to_string_lossy().replace('\\', "/");
+    LintContext {
+        root: Some(slashes(root)),
+        note_dir: file
+            .and_then(|file| file.parent()?.strip_prefix(root).ok())
+            .map(slashes),
+    }
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_apply_lint_fixes_writes_selected_fixes() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("Notes")).unwrap();
+        let note = dir.path().join("Notes").join("a.md");
+        let link = format!("[b]({}/b.md)", dir.path().to_string_lossy().replace('\\', "/"));
+        fs::write(&note, format!("# A\ntext\n# B.\n{}\n", link)).unwrap();
+        let root = dir.path().to_string_lossy().to_string();
+
+        let diagnostics = lint_note(root.clone(), Some("Notes/a.md".into()), None, None).await.unwrap();
+        let rules: Vec<&str> = diagnostics.iter().map(|d| d.rule.as_str()).collect();
+        assert_eq!(rules, ["blank-line-before-heading", "single-title", "heading-punctuation", "relative-links"]);
+
+        let ids = vec![diagnostics[1].id.clone(), diagnostics[3].id.clone(), "missing@0".into()];
+        let report = apply_lint_fixes(root.clone(), "Notes/a.md".into(), ids).await.unwrap();
+        assert_eq!(report.applied.len(), 2);
+        assert_eq!(report.skipped, ["missing@0"]);
+        assert_eq!(fs::read_to_string(&note).unwrap(), "# A\ntext\n## B.\n[b](../b.md)\n");
+
+        // Buffers are linted as given, with a ruleset of the caller's choice
+        let disabled = crate::lint::RuleOverride { enabled: Some(false), severity: None };
+        let ruleset = LintSettings { rules: [("blank-line-before-heading".to_string(), disabled)].into() };
+        let buffer = lint_note(root, None, Some("# A\n# B\n".into()), Some(ruleset)).await.unwrap();
+        assert_eq!(buffer.len(), 1);
+        assert_eq!(buffer[0].rule, "single-title");
+    }
+
+    #[test]
+    fn test_vault_report_counts_per_rule() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.md"), "# A\n# B!\n").unwrap();
+        fs::write(dir.path().join("b.md"), "# B.\n").unwrap();
+        fs::write(dir.path().join("c.txt"), "# not a note\n# at all\n").unwrap();
+
+        let mut calls = Vec::new();
+        let report = lint_vault_report(dir.path(), |done, total| calls.push((done, total)));
+        assert_eq!(report.notes_checked, 2);
+        assert_eq!(calls.last(), Some(&(2, 2)));
+        assert_eq!(report.rules["heading-punctuation"], RuleTally { diagnostics: 2, notes: 2, fixable: 2 });
+        assert_eq!(report.rules["single-title"], RuleTally { diagnostics: 1, notes: 1, fixable: 1 });
+        assert!(!report.rules.contains_key("relative-links"));
+    }
+}
@@ -0,0 +1,158 @@
+// ============================================================================
+// CONFLICT MERGING
+// ============================================================================
+//
+// When a save fails with `Conflict`, the frontend can merge instead of
+// choosing between its buffer and the file on disk. `get_merge_preview`
+// runs `crate::merge` on the text loaded into the editor, the buffer and
+// the file as it is now; `write_merged_result` saves the resolution, still
+// refusing if the file changed again since the preview.
+// ============================================================================
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::error::HibiscusError;
+use crate::merge::{merge3, MergeConflict};
+use super::files::{write_text_file, FileVersion, WrittenFile};
+use super::opener::looks_binary;
+use super::path::{scope_to_workspace, validate_path};
+
+/// Largest text, per side, that is merged (4 MB).
+const MAX_MERGE_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Material for resolving a save conflict.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MergePreview {
+    /// Both sides' changes, with the buffer's text in conflicting regions
+    pub merged: String,
+    /// Regions both sides changed differently; empty for a clean merge
+    pub conflicts: Vec<MergeConflict>,
+    /// Modification time of the merged-against file, milliseconds since the
+    /// Unix epoch; pass it to `write_merged_result`
+    pub disk_mtime: u64,
+}
+
+/// Merges the editor buffer with the file's current contents.
+///
+/// # Arguments
+/// * `path` - Absolute path to the file
+/// * `base_content` - The file's contents when the editor loaded it
+/// * `ours_content` - The editor buffer
+///
+/// # Returns
+/// * `Ok(MergePreview)` - The merge, and the disk version it was made against
+/// * `Err(HibiscusError::FileTooLarge)` - If the file or a given text is
+///   over 4 MB
+/// * `Err(HibiscusError::BinaryFile)` - If the file on disk is not text
+/// * `Err(HibiscusError)` - If the file cannot be read
+#[tauri::command]
+pub async fn get_merge_preview(
+    path: String,
+    base_content: String,
+    ours_content: String,
+) -> Result<MergePreview, HibiscusError> {
+    let path = PathBuf::from(&path);
+
+    // Validate the path
+    validate_path(&path)?;
+    let path = scope_to_workspace(&path)?;
+
+    for size in [base_content.len() as u64, ours_content.len() as u64] {
+        check_merge_size(&path, size)?;
+    }
+    let metadata = tokio::fs::metadata(&path).await.map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => HibiscusError::FileNotFound(path.to_string_lossy().into()),
+        _ => HibiscusError::Io(format!("Failed to read file '{}': {}", path.display(), e)),
+    })?;
+    check_merge_size(&path, metadata.len())?;
+
+    let bytes = tokio::fs::read(&path)
+        .await
+        .map_err(|e| HibiscusError::Io(format!("Failed to read file '{}': {}", path.display(), e)))?;
+    if looks_binary(&bytes) {
+        return Err(HibiscusError::BinaryFile(path.to_string_lossy().into()));
+    }
+    let disk_mtime = FileVersion::of(&path, &bytes).await.modified_ms.unwrap_or(0);
+    let theirs = String::from_utf8_lossy(&bytes).into_owned();
+
+    let merged = tokio::task::spawn_blocking(move || merge3(&base_content, &ours_content, &theirs))
+        .await
+        .map_err(|e| HibiscusError::Io(format!("Merge task failed: {}", e)))?;
+
+    Ok(MergePreview { merged: merged.content, conflicts: merged.conflicts, disk_mtime })
+}
+
+/// Saves a resolved merge.
+///
+/// # Arguments
+/// * `path` - Absolute path to the file
+/// * `content` - The resolved text
+/// * `expected_disk_mtime` - `disk_mtime` of the preview the resolution
+///   was made from
+///
+/// # Returns
+/// * `Ok(WrittenFile)` - The saved file's path, size and new mtime
+/// * `Err(HibiscusError::Conflict)` - If the file changed again since the
+///   preview; nothing is written
+/// * `Err(HibiscusError)` - If the write failed or is over the workspace
+///   limit
+///
+/// # Notes
+/// Saves like `write_text_file` without formatting, so backups and the
+/// workspace write limit apply as usual.
+#[tauri::command]
+pub async fn write_merged_result(
+    path: String,
+    content: String,
+    expected_disk_mtime: u64,
+) -> Result<WrittenFile, HibiscusError> {
+    let expected = FileVersion { modified_ms: Some(expected_disk_mtime), hash: None };
+    write_text_file(path, content, None, None, None, Some(expected), None).await
+}
+
+fn check_merge_size(path: &Path, size: u64) -> Result<(), HibiscusError> {
+    if size > MAX_MERGE_BYTES {
+        return Err(HibiscusError::FileTooLarge {
+            path: path.to_string_lossy().into(),
+            size,
+            limit: MAX_MERGE_BYTES,
+        });
+    }
+    Ok(())
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_preview_then_write_resolution() {
+        let dir = tempdir().unwrap();
+        let note = dir.path().join("a.md");
+        fs::write(&note, "# A\nmine\nend\nTHEIRS\n").unwrap();
+        let path = note.to_string_lossy().to_string();
+
+        let base = "# A\nbase\nend\ntheirs\n".to_string();
+        let ours = "# A\nours\nend\ntheirs\n".to_string();
+        let preview = get_merge_preview(path.clone(), base, ours).await.unwrap();
+        assert_eq!(preview.merged, "# A\nours\nend\nTHEIRS\n");
+        assert_eq!(preview.conflicts.len(), 1);
+        assert_eq!(preview.conflicts[0].theirs, "mine\n");
+
+        let stale = write_merged_result(path.clone(), "x\n".into(), preview.disk_mtime + 1).await;
+        assert!(matches!(stale, Err(HibiscusError::Conflict { .. })));
+        write_merged_result(path.clone(), "resolved\n".into(), preview.disk_mtime).await.unwrap();
+        assert_eq!(fs::read_to_string(&note).unwrap(), "resolved\n");
+
+        fs::write(&note, [0u8, 1, 2]).unwrap();
+        let binary = get_merge_preview(path, String::new(), String::new()).await;
+        assert!(matches!(binary, Err(HibiscusError::BinaryFile(_))));
+    }
+}
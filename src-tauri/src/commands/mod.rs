@@ -10,6 +10,39 @@
 // ! - calendar: calendar persistence
 // ! - themes: user theme persistence
 // ! - path: shared path validation utilities
+// ! - references: stored reference reconciliation after renames/deletes
+// ! - export: plain-text corpus export
+// ! - git: changes-since-last-commit gutter
+// ! - opener: per-extension viewer resolution
+// ! - stats: writing statistics, writing progress, the vault statistics CSV
+// !   and word frequency exports
+// ! - decorations: per-node icon/color overrides
+// ! - attributes: platform file attributes (Windows hidden flag)
+// ! - links: note creation from unresolved wiki-links, ambiguous link candidates,
+// !   relative link building, paste targets for images
+// ! - search: literal text search with context lines
+// ! - gallery: paged attachment listing with thumbnail prefetch
+// ! - insights: link-graph statistics
+// ! - time_tracking: focus time recording and queries
+// ! - redact: shareable copies of notes
+// ! - ocr: image text recognition
+// ! - bootstrap: one-call workspace startup and recent workspaces
+// ! - jobs: background job status
+// ! - adopt: converting plain markdown folders into workspaces
+// ! - capabilities: confirmation dialogs that issue capability tokens
+// ! - reload: scroll anchoring after external file changes, unsaved diff stats
+// ! - backups: listing, size-capping and restoring .hibiscus/backups
+// ! - publish: static HTML site from a selection of notes
+// ! - badges: merged tree node badges
+// ! - outline: nested heading outline of a note
+// ! - review: spaced-repetition review scheduling
+// ! - batch: transactional operation batches and workspace macros
+// ! - vault_template: new vaults from template folders
+// ! - variants: language variants of notes for the language switcher
+// ! - lint: note style rules, fixes and vault-wide lint jobs
+// ! - volumes: drives and mount points for the open-folder quick list
+// ! - tidy: on-demand note clean-up (trailing whitespace, blank lines)
+// ! - merge: three-way merge previews for save conflicts
 // ! ============================================================================
 
 mod path;
@@ -20,6 +53,37 @@ mod calendar;
 mod themes;
 mod study;
 mod create_item;
+mod references;
+mod export;
+mod git;
+mod opener;
+mod stats;
+mod decorations;
+mod attributes;
+mod links;
+mod search;
+mod gallery;
+mod insights;
+mod time_tracking;
+mod redact;
+mod ocr;
+mod bootstrap;
+mod jobs;
+mod adopt;
+mod capabilities;
+mod reload;
+mod backups;
+mod publish;
+mod badges;
+mod outline;
+mod review;
+mod batch;
+mod vault_template;
+mod variants;
+mod lint;
+mod volumes;
+mod tidy;
+mod merge;
 
 // Re-export commands so lib.rs can keep using `commands::xyz`
 pub use files::*;
@@ -28,4 +92,35 @@ pub use tree::*;
 pub use calendar::*;
 pub use themes::*;
 pub use study::*;
-pub use create_item::*;
\ No newline at end of file
+pub use create_item::*;
+pub use references::*;
+pub use export::*;
+pub use git::*;
+pub use opener::*;
+pub use stats::*;
+pub use decorations::*;
+pub use attributes::*;
+pub use links::*;
+pub use search::*;
+pub use gallery::*;
+pub use insights::*;
+pub use time_tracking::*;
+pub use redact::*;
+pub use ocr::*;
+pub use bootstrap::*;
+pub use jobs::*;
+pub use adopt::*;
+pub use capabilities::*;
+pub use reload::*;
+pub use backups::*;
+pub use publish::*;
+pub use badges::*;
+pub use outline::*;
+pub use review::*;
+pub use batch::*;
+pub use vault_template::*;
+pub use variants::*;
+pub use lint::*;
+pub use volumes::*;
+pub use tidy::*;
+pub use merge::*;
\ No newline at end of file
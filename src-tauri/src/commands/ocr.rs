@@ -0,0 +1,59 @@
+// ============================================================================
+// IMAGE OCR
+// ============================================================================
+//
+// Tauri entry points for `crate::ocr`. OCR is optional: when tesseract is
+// missing, `get_ocr_status` says so and `extract_image_text` returns an
+// `HibiscusError::Ocr` explaining how to install it.
+// ============================================================================
+
+use std::path::PathBuf;
+
+use crate::error::HibiscusError;
+use crate::ocr::{self, OcrBackend, OcrResult, OcrStatus, TesseractCli};
+use super::path::{validate_path, validate_path_within_root};
+
+/// Reports whether OCR is available and which languages are installed.
+///
+/// # Returns
+/// * `Ok(OcrStatus)` - Availability, version and languages
+/// * `Err(HibiscusError)` - If the check could not run
+#[tauri::command]
+pub async fn get_ocr_status() -> Result<OcrStatus, HibiscusError> {
+    tokio::task::spawn_blocking(|| TesseractCli.status())
+        .await
+        .map_err(|e| HibiscusError::Io(format!("OCR status task failed: {}", e)))
+}
+
+/// Recognizes the text in an image inside a workspace.
+///
+/// # Arguments
+/// * `root` - Workspace root directory (holds the OCR cache)
+/// * `path` - Absolute path to the image
+/// * `language` - Tesseract language spec, e.g. `"eng+deu"` (default `"eng"`)
+///
+/// # Returns
+/// * `Ok(OcrResult)` - The recognized text
+/// * `Err(HibiscusError)` - If the image is missing or OCR is unavailable
+#[tauri::command]
+pub async fn extract_image_text(
+    root: String,
+    path: String,
+    language: Option<String>,
+) -> Result<OcrResult, HibiscusError> {
+    let root = PathBuf::from(&root);
+    let path = PathBuf::from(&path);
+
+    // Validate paths
+    validate_path(&root)?;
+    validate_path_within_root(&path, &root)?;
+
+    if !path.is_file() {
+        return Err(HibiscusError::FileNotFound(path.to_string_lossy().into()));
+    }
+
+    let language = language.unwrap_or_else(|| ocr::DEFAULT_OCR_LANGUAGE.to_string());
+    tokio::task::spawn_blocking(move || ocr::extract_text(&root, &path, &language, &TesseractCli))
+        .await
+        .map_err(|e| HibiscusError::Io(format!("OCR task failed: {}", e)))?
+}
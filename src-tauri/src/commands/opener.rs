@@ -0,0 +1,165 @@
+// ============================================================================
+// OPENER RESOLUTION
+// ============================================================================
+//
+// Decides which viewer should open a file. User-configured associations in
+// workspace settings win; everything else falls back to "text" or "binary"
+// based on a content sniff.
+// ============================================================================
+
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncReadExt;
+
+use crate::error::HibiscusError;
+use crate::workspace::WorkspaceSettings;
+use super::path::{find_workspace_root, validate_path};
+
+/// Viewer id for files that look like text.
+pub const TEXT_OPENER: &str = "text";
+
+/// Viewer id for files that look binary.
+pub const BINARY_OPENER: &str = "binary";
+
+/// Number of leading bytes inspected when classifying a file.
+const SNIFF_BYTES: usize = 8192;
+
+/// Resolves the viewer id for a file.
+///
+/// # Arguments
+/// * `path` - File to open
+///
+/// # Returns
+/// * `Ok(String)` - The configured viewer for the file's extension, or
+///   `"text"` / `"binary"` when no association matches
+/// * `Err(HibiscusError)` - If the path is invalid or cannot be read
+///
+/// # Notes
+/// Associations come from `settings.file_associations` in the enclosing
+/// workspace's `.hibiscus/workspace.json`. Extension keys are matched
+/// case-insensitively, with or without a leading dot.
+#[tauri::command]
+pub async fn resolve_opener(path: String) -> Result<String, HibiscusError> {
+    let path = PathBuf::from(&path);
+
+    // Validate path
+    validate_path(&path)?;
+
+    if !path.is_file() {
+        return Err(HibiscusError::FileNotFound(path.to_string_lossy().into()));
+    }
+
+    if let Some(opener) = configured_opener(&path).await {
+        return Ok(opener);
+    }
+
+    let mut file = tokio::fs::File::open(&path).await.map_err(|e| {
+        HibiscusError::Io(format!("Failed to open '{}': {}", path.display(), e))
+    })?;
+    let mut sample = Vec::with_capacity(SNIFF_BYTES);
+    (&mut file)
+        .take(SNIFF_BYTES as u64)
+        .read_to_end(&mut sample)
+        .await
+        .map_err(|e| HibiscusError::Io(format!("Failed to read '{}': {}", path.display(), e)))?;
+
+    Ok(if looks_binary(&sample) { BINARY_OPENER } else { TEXT_OPENER }.to_string())
+}
+
+/// Looks up the workspace association for the file's extension.
+async fn configured_opener(path: &Path) -> Option<String> {
+    let ext = path.extension()?.to_string_lossy().to_lowercase();
+    let root = find_workspace_root(path)?;
+    let content = tokio::fs::read_to_string(root.join(".hibiscus").join("workspace.json"))
+        .await
+        .ok()?;
+    let raw: serde_json::Value = serde_json::from_str(&content).ok()?;
+    let settings = WorkspaceSettings::from_value(raw.get("settings"));
+
+    settings
+        .file_associations
+        .into_iter()
+        .find(|(key, _)| key.trim_start_matches('.').eq_ignore_ascii_case(&ext))
+        .map(|(_, opener)| opener)
+}
+
+/// Heuristic binary check: NUL bytes or invalid UTF-8 in the sample.
+///
+/// A multi-byte sequence cut off by the end of the sample is not treated as
+/// invalid, since the sample is a prefix of the file.
+pub(crate) fn looks_binary(sample: &[u8]) -> bool {
+    if sample.contains(&0) {
+        return true;
+    }
+    match std::str::from_utf8(sample) {
+        Ok(_) => false,
+        Err(e) => e.error_len().is_some(),
+    }
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn workspace_with_settings(settings: serde_json::Value) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join(".hibiscus")).unwrap();
+        let workspace = serde_json::json!({
+            "schema_version": "1.0",
+            "workspace": { "id": "w", "name": "w", "root": "." },
+            "settings": settings,
+            "tree": [],
+        });
+        fs::write(
+            dir.path().join(".hibiscus").join("workspace.json"),
+            workspace.to_string(),
+        )
+        .unwrap();
+        dir
+    }
+
+    async fn resolve(path: &Path) -> String {
+        resolve_opener(path.to_string_lossy().into()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_configured_association() {
+        let dir = workspace_with_settings(serde_json::json!({
+            "file_associations": { ".CSV": "table", "drawio": "external" }
+        }));
+        let csv = dir.path().join("data.csv");
+        fs::write(&csv, "a,b\n1,2\n").unwrap();
+
+        assert_eq!(resolve(&csv).await, "table");
+    }
+
+    #[tokio::test]
+    async fn test_unconfigured_text_file() {
+        let dir = workspace_with_settings(serde_json::json!({}));
+        let note = dir.path().join("note.md");
+        fs::write(&note, "# Héllo\n").unwrap();
+
+        assert_eq!(resolve(&note).await, TEXT_OPENER);
+    }
+
+    #[tokio::test]
+    async fn test_binary_file() {
+        let dir = TempDir::new().unwrap();
+        let blob = dir.path().join("image.png");
+        fs::write(&blob, [0x89, b'P', b'N', b'G', 0x00, 0x1a]).unwrap();
+
+        assert_eq!(resolve(&blob).await, BINARY_OPENER);
+    }
+
+    #[test]
+    fn test_truncated_utf8_sample_is_text() {
+        // "é" is two bytes; a sample ending mid-character is still text.
+        assert!(!looks_binary(&"abcé".as_bytes()[..4]));
+        assert!(looks_binary(&[0xff, b'a']));
+    }
+}
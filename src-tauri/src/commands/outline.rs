@@ -0,0 +1,36 @@
+// ============================================================================
+// NOTE OUTLINE
+// ============================================================================
+//
+// Heading tree of a note for the outline panel. Each heading carries its
+// slug (the anchor id the HTML renderer gives it) and its line number, so the
+// frontend can either scroll the editor or link to the heading.
+// ============================================================================
+
+use std::path::PathBuf;
+
+use crate::error::HibiscusError;
+use crate::markdown::{self, OutlineHeading};
+use super::path::validate_path;
+
+/// Returns the nested heading outline of a markdown file.
+///
+/// # Arguments
+/// * `path` - Note to outline
+///
+/// # Returns
+/// * `Ok(Vec<OutlineHeading>)` - Top-level headings, each with its children
+/// * `Err(HibiscusError)` - If the path is invalid or unreadable
+#[tauri::command]
+pub async fn build_outline(path: String) -> Result<Vec<OutlineHeading>, HibiscusError> {
+    let path = PathBuf::from(&path);
+
+    // Validate path
+    validate_path(&path)?;
+
+    let content = tokio::fs::read_to_string(&path).await.map_err(|e| {
+        HibiscusError::Io(format!("Failed to read '{}': {}", path.display(), e))
+    })?;
+
+    Ok(markdown::outline(&content))
+}
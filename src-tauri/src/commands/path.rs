@@ -4,9 +4,7 @@
 
 use std::path::{Path, PathBuf};
 use crate::error::HibiscusError;
-
-/// Maximum allowed path depth to prevent deeply nested directory attacks
-const MAX_PATH_DEPTH: usize = 50;
+use crate::limits::{MAX_PATH_DEPTH, PATH_DEPTH};
 
 /// Validates that a path is safe and canonical.
 ///
@@ -34,8 +32,8 @@ pub fn validate_path(path: &Path) -> Result<PathBuf, HibiscusError> {
     let depth = path.components().count();
     if depth > MAX_PATH_DEPTH {
         return Err(HibiscusError::PathValidation(format!(
-            "Path depth {} exceeds maximum {}",
-            depth, MAX_PATH_DEPTH
+            "Path depth {} exceeds maximum {} ({})",
+            depth, MAX_PATH_DEPTH, PATH_DEPTH
         )));
     }
 
@@ -43,6 +41,127 @@ pub fn validate_path(path: &Path) -> Result<PathBuf, HibiscusError> {
     Ok(path.to_path_buf())
 }
 
+/// Finds the workspace root that contains `path`.
+///
+/// Walks up the ancestors of `path` looking for a directory that holds a
+/// `.hibiscus/workspace.json`. Used by commands that only receive absolute
+/// paths but need to update workspace-level metadata.
+///
+/// # Returns
+/// The workspace root directory, or `None` if `path` is not inside one.
+pub fn find_workspace_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|dir| dir.join(".hibiscus").join("workspace.json").is_file())
+        .map(Path::to_path_buf)
+}
+
+/// Expands home-directory and environment-variable aliases in a user-typed path.
+///
+/// Supported forms:
+/// - A leading `~` (alone or followed by a separator) becomes the home directory
+/// - `%VAR%`, `$VAR` and `${VAR}` anywhere in the path become the variable's value
+///
+/// Only apply this to paths the user typed (e.g. a workspace root pasted into
+/// the open dialog). Paths from the file tree are already absolute and may
+/// legitimately contain `$` or `%`.
+///
+/// # Arguments
+/// * `input` - The path as typed by the user
+///
+/// # Returns
+/// * `Ok(PathBuf)` - The expanded path (unchanged if nothing to expand)
+/// * `Err(HibiscusError)` - For `~user` forms or unknown variables
+pub fn expand_user_path(input: &str) -> Result<PathBuf, HibiscusError> {
+    expand_with(input, |name| std::env::var(name).ok())
+}
+
+/// Expansion with an injectable variable lookup (used directly by tests).
+fn expand_with<F>(input: &str, lookup: F) -> Result<PathBuf, HibiscusError>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let lookup_var = |name: &str| {
+        lookup(name).ok_or_else(|| {
+            HibiscusError::PathValidation(format!("Unknown environment variable '{}'", name))
+        })
+    };
+
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+
+    // Leading home alias
+    if let Some(after) = rest.strip_prefix('~') {
+        if after.is_empty() || after.starts_with(['/', '\\']) {
+            let home = lookup("HOME").or_else(|| lookup("USERPROFILE")).ok_or_else(|| {
+                HibiscusError::PathValidation("Cannot expand '~': home directory is unknown".into())
+            })?;
+            out.push_str(&home);
+            rest = after;
+        } else {
+            let user: String = after.chars().take_while(|c| *c != '/' && *c != '\\').collect();
+            return Err(HibiscusError::PathValidation(format!(
+                "Expanding '~{}' is not supported; use the full path instead",
+                user
+            )));
+        }
+    }
+
+    while let Some(pos) = rest.find(['%', '$']) {
+        out.push_str(&rest[..pos]);
+        let tail = &rest[pos..];
+
+        let (name, consumed) = if let Some(body) = tail.strip_prefix('%') {
+            match body.find('%') {
+                Some(end) if is_windows_var_name(&body[..end]) => (&body[..end], end + 2),
+                _ => ("", 1),
+            }
+        } else if let Some(body) = tail.strip_prefix("${") {
+            match body.find('}') {
+                Some(end) if is_var_name(&body[..end]) => (&body[..end], end + 3),
+                _ => ("", 1),
+            }
+        } else {
+            let body = &tail[1..];
+            let len = body
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(body.len());
+            if is_var_name(&body[..len]) {
+                (&body[..len], len + 1)
+            } else {
+                ("", 1)
+            }
+        };
+
+        if name.is_empty() {
+            // Not a variable reference; keep the character literally
+            out.push_str(&tail[..1]);
+        } else {
+            out.push_str(&lookup_var(name)?);
+        }
+        rest = &tail[consumed..];
+    }
+    out.push_str(rest);
+
+    Ok(PathBuf::from(out))
+}
+
+/// `$VAR` names: a letter or underscore followed by letters, digits or underscores.
+fn is_var_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// `%VAR%` names additionally allow parentheses, as in `%ProgramFiles(x86)%`.
+fn is_windows_var_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '(' | ')'))
+}
+
 /// Validates that a path is within a given root directory.
 ///
 /// This is used to ensure users can only access files within their workspace,
@@ -55,10 +174,7 @@ pub fn validate_path(path: &Path) -> Result<PathBuf, HibiscusError> {
 /// # Returns
 /// * `Ok(())` - If the path is within the root
 /// * `Err(HibiscusError)` - If the path is outside the root
-///
-/// Note: Currently unused but kept for future workspace-scoped operations.
-#[allow(dead_code)]
-fn validate_path_within_root(path: &Path, root: &Path) -> Result<(), HibiscusError> {
+pub fn validate_path_within_root(path: &Path, root: &Path) -> Result<(), HibiscusError> {
     // First validate the path itself
     validate_path(path)?;
 
@@ -81,6 +197,75 @@ fn validate_path_within_root(path: &Path, root: &Path) -> Result<(), HibiscusErr
     Ok(())
 }
 
+/// Resolves `path` with all symlinks followed and checks that the final
+/// target is still inside `root`.
+///
+/// Paths that do not exist yet (a file about to be created) are resolved
+/// through their closest existing ancestor, so a symlinked parent folder
+/// pointing outside the root is caught as well.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+/// * `path` - Path inside the workspace, possibly through symlinks
+///
+/// # Returns
+/// * `Ok(PathBuf)` - The canonical target
+/// * `Err(HibiscusError::PathValidation)` - If the target escapes the root
+pub fn resolve_within_root(root: &Path, path: &Path) -> Result<PathBuf, HibiscusError> {
+    validate_path(path)?;
+
+    let canonical_root = root
+        .canonicalize()
+        .map_err(|e| HibiscusError::Io(format!("Failed to canonicalize root: {}", e)))?;
+
+    // Split into the deepest existing ancestor and the not-yet-existing rest
+    let mut existing = path;
+    let mut missing = Vec::new();
+    while existing.symlink_metadata().is_err() {
+        let (Some(parent), Some(name)) = (existing.parent(), existing.file_name()) else {
+            break;
+        };
+        missing.push(name);
+        existing = parent;
+    }
+
+    let mut resolved = existing
+        .canonicalize()
+        .map_err(|e| HibiscusError::Io(format!("Failed to resolve '{}': {}", path.display(), e)))?;
+    resolved.extend(missing.iter().rev());
+
+    if !resolved.starts_with(&canonical_root) {
+        return Err(HibiscusError::PathValidation(format!(
+            "'{}' resolves to '{}', outside the workspace root",
+            path.display(),
+            resolved.display()
+        )));
+    }
+
+    Ok(resolved)
+}
+
+/// Applies `resolve_within_root` when the workspace containing `path` has
+/// `workspace_scoped` enabled; otherwise returns `path` unchanged.
+pub fn scope_to_workspace(path: &Path) -> Result<PathBuf, HibiscusError> {
+    match find_workspace_root(path) {
+        Some(root) if crate::workspace::WorkspaceSettings::load(&root).workspace_scoped => {
+            resolve_within_root(&root, path)
+        }
+        _ => Ok(path.to_path_buf()),
+    }
+}
+
+/// Like `scope_to_workspace`, for a path whose entry itself is used rather
+/// than what it points to (the source or destination of a move): only its
+/// folder is resolved, so a symlink is moved as a link.
+pub fn scope_entry_to_workspace(path: &Path) -> Result<PathBuf, HibiscusError> {
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => Ok(scope_to_workspace(parent)?.join(name)),
+        _ => scope_to_workspace(path),
+    }
+}
+
 // =============================================================================
 // UNIT TESTS
 // =============================================================================
@@ -143,12 +328,122 @@ mod tests {
         assert!(validate_path(path).is_ok());
     }
 
+    // ---- expand_user_path tests ----
+
+    fn env(name: &str) -> Option<String> {
+        match name {
+            "HOME" => Some("/home/ada".into()),
+            "APPDATA" => Some("C:\\Users\\ada\\AppData\\Roaming".into()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_expand_tilde() {
+        assert_eq!(expand_with("~", env).unwrap(), PathBuf::from("/home/ada"));
+        assert_eq!(
+            expand_with("~/Documents/vault", env).unwrap(),
+            PathBuf::from("/home/ada/Documents/vault")
+        );
+    }
+
+    #[test]
+    fn test_expand_other_user_unsupported() {
+        let err = expand_with("~other/vault", env).unwrap_err();
+        assert!(err.to_string().contains("~other"));
+    }
+
+    #[test]
+    fn test_expand_windows_variable() {
+        assert_eq!(
+            expand_with("%APPDATA%\\vault", env).unwrap(),
+            PathBuf::from("C:\\Users\\ada\\AppData\\Roaming\\vault")
+        );
+        assert_eq!(
+            expand_with("$HOME/a/${HOME}", env).unwrap(),
+            PathBuf::from("/home/ada/a//home/ada")
+        );
+    }
+
+    #[test]
+    fn test_expand_unknown_variable_names_it() {
+        let err = expand_with("%NOPE%\\vault", env).unwrap_err();
+        assert!(err.to_string().contains("'NOPE'"));
+        assert!(expand_with("$MISSING/vault", env).is_err());
+    }
+
+    #[test]
+    fn test_expand_leaves_plain_paths_alone() {
+        for input in ["/srv/notes/vault", "C:\\notes\\50% done", "a$", "x~y"] {
+            assert_eq!(expand_with(input, env).unwrap(), PathBuf::from(input));
+        }
+    }
+
     // ---- validate_path_within_root tests ----
 
+    // ---- find_workspace_root tests ----
+
+    #[test]
+    fn test_find_workspace_root_from_nested_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let hibiscus = dir.path().join(".hibiscus");
+        std::fs::create_dir_all(&hibiscus).unwrap();
+        std::fs::write(hibiscus.join("workspace.json"), "{}").unwrap();
+
+        let nested = dir.path().join("notes").join("a.md");
+        assert_eq!(find_workspace_root(&nested), Some(dir.path().to_path_buf()));
+    }
+
+    #[test]
+    fn test_find_workspace_root_outside_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(find_workspace_root(&dir.path().join("a.md")), None);
+    }
+
     #[test]
     fn test_path_within_root_rejects_traversal() {
         let path = Path::new("C:\\workspace\\..\\secrets\\key");
         let root = Path::new("C:\\workspace");
         assert!(validate_path_within_root(path, root).is_err());
     }
+
+    // ---- resolve_within_root tests ----
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_follows_symlink_inside_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join("notes")).unwrap();
+        std::fs::write(root.join("notes").join("real.md"), "x").unwrap();
+        std::os::unix::fs::symlink(root.join("notes").join("real.md"), root.join("link.md")).unwrap();
+        std::os::unix::fs::symlink(root.join("notes"), root.join("alias")).unwrap();
+
+        assert_eq!(
+            resolve_within_root(&root, &root.join("link.md")).unwrap(),
+            root.join("notes").join("real.md")
+        );
+        // Not-yet-existing files resolve through their parent
+        assert_eq!(
+            resolve_within_root(&root, &root.join("alias").join("new.md")).unwrap(),
+            root.join("notes").join("new.md")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_rejects_symlink_escaping_root() {
+        let vault = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        std::fs::write(outside.path().join("secret.txt"), "x").unwrap();
+        std::os::unix::fs::symlink(outside.path().join("secret.txt"), vault.path().join("leak.md")).unwrap();
+        std::os::unix::fs::symlink(outside.path(), vault.path().join("out")).unwrap();
+
+        for path in [vault.path().join("leak.md"), vault.path().join("out").join("new.md")] {
+            assert!(matches!(
+                resolve_within_root(vault.path(), &path),
+                Err(HibiscusError::PathValidation(_))
+            ));
+        }
+    }
 }
\ No newline at end of file
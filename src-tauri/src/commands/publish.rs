@@ -0,0 +1,733 @@
+// ============================================================================
+// SITE PUBLISHING
+// ============================================================================
+//
+// Renders a selection of notes into a static HTML site (a "digital garden").
+//
+// OUTPUT:
+// - One page per note, mirroring the vault layout (`a/Note.md` ->
+//   `a/Note.html`), plus `index.html` and one `tags/<tag>.html` per tag.
+// - Assets referenced by published notes are copied at their vault paths.
+// - `.hibiscus-publish.json` in the destination records what was written,
+//   so the next publish deletes pages of notes that were unpublished or
+//   removed without touching anything else in the folder.
+//
+// DESIGN:
+// - Links to other published notes point at their `.html` pages. Links to
+//   notes outside the selection (or to nothing) become plain text and are
+//   reported as warnings instead of shipping dead links.
+// - Incremental mode skips notes whose content hash matches the manifest;
+//   index and tag pages are always regenerated.
+// - Progress is emitted as `publish-progress` events while notes render.
+// ============================================================================
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::Emitter;
+
+use crate::error::HibiscusError;
+use crate::knowledge::storage::hash_file;
+use crate::markdown::{self, HtmlLinkKind};
+use crate::tree::{read_dir_recursive, DEFAULT_MAX_DEPTH};
+use crate::workspace::Node;
+use super::path::validate_path;
+
+/// Manifest file written into the destination folder.
+const MANIFEST_FILE: &str = ".hibiscus-publish.json";
+
+/// Emit a progress event every this many notes.
+const PROGRESS_INTERVAL: usize = 10;
+
+/// Which notes to publish.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PublishSelection {
+    /// Notes tagged with this tag, in frontmatter or inline (`#publish`)
+    Tag(String),
+    /// Every note under this workspace-relative folder
+    Folder(String),
+    /// Exactly these workspace-relative notes
+    Paths(Vec<String>),
+}
+
+impl Default for PublishSelection {
+    fn default() -> Self {
+        PublishSelection::Tag("publish".into())
+    }
+}
+
+/// Options for `publish_site`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PublishOptions {
+    /// Destination folder for the site
+    pub dest: String,
+    pub selection: PublishSelection,
+    /// Only re-render notes whose content changed since the last publish
+    pub incremental: bool,
+    /// Site title shown on the index page (defaults to the root folder name)
+    pub title: Option<String>,
+}
+
+/// Something the user should look at after publishing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PublishWarning {
+    /// Workspace-relative note the warning is about
+    pub path: String,
+    pub message: String,
+}
+
+/// Summary returned once publishing finishes.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PublishReport {
+    pub dest: String,
+    /// Notes rendered in this run
+    pub published: usize,
+    /// Notes skipped by incremental mode because they did not change
+    pub unchanged: usize,
+    /// Index and tag pages written
+    pub index_pages: usize,
+    /// Assets copied
+    pub assets: usize,
+    /// Previously published files that were deleted
+    pub removed: Vec<String>,
+    pub warnings: Vec<PublishWarning>,
+}
+
+/// Payload of the `publish-progress` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct PublishProgress {
+    pub processed: usize,
+    pub total: usize,
+}
+
+/// What a previous publish wrote, read back from `MANIFEST_FILE`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PublishManifest {
+    /// Note path -> published state
+    #[serde(default)]
+    notes: BTreeMap<String, PublishedNote>,
+    /// Every file written, relative to the destination
+    #[serde(default)]
+    files: BTreeSet<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PublishedNote {
+    hash: String,
+    #[serde(default)]
+    assets: Vec<String>,
+    #[serde(default)]
+    warnings: Vec<String>,
+}
+
+/// A note selected for publishing.
+struct Page {
+    rel: String,
+    output: String,
+    title: String,
+    tags: Vec<String>,
+    content: String,
+}
+
+/// Where a link in a published note goes.
+#[derive(Debug, PartialEq, Eq)]
+enum LinkTarget {
+    /// Another published note: output path and optional anchor
+    Page(String, Option<String>),
+    /// A vault file copied next to the pages
+    Asset(String),
+    /// URL or in-page anchor, kept as is
+    External(String),
+    /// An existing note that is not in the selection
+    Unpublished(String),
+    Missing,
+}
+
+/// Publishes a selection of notes as a static HTML site.
+///
+/// # Arguments
+/// * `root` - Workspace root directory path
+/// * `options` - Destination, selection (default: tag `publish`) and mode
+///
+/// # Events Emitted
+/// * `publish-progress` - `{ processed, total }` while notes render
+///
+/// # Returns
+/// * `Ok(PublishReport)` - Counts, removed files and warnings
+/// * `Err(HibiscusError)` - If a path is invalid or the site cannot be written
+#[tauri::command]
+pub async fn publish_site(
+    root: String,
+    options: PublishOptions,
+    window: tauri::Window,
+) -> Result<PublishReport, HibiscusError> {
+    let root = PathBuf::from(&root);
+    let dest = PathBuf::from(&options.dest);
+
+    // Validate paths
+    validate_path(&root)?;
+    validate_path(&dest)?;
+
+    if !root.is_dir() {
+        return Err(HibiscusError::InvalidPathType {
+            path: root.to_string_lossy().into(),
+            expected: "directory".into(),
+            actual: "file".into(),
+        });
+    }
+
+    tokio::task::spawn_blocking(move || {
+        publish_site_blocking(&root, &dest, &options, |progress| {
+            let _ = window.emit("publish-progress", progress);
+        })
+    })
+    .await
+    .map_err(|e| HibiscusError::Io(format!("Publish task failed: {}", e)))?
+}
+
+/// Blocking implementation of `publish_site`.
+pub fn publish_site_blocking(
+    root: &Path,
+    dest: &Path,
+    options: &PublishOptions,
+    mut on_progress: impl FnMut(PublishProgress),
+) -> Result<PublishReport, HibiscusError> {
+    let mut files = Vec::new();
+    collect_files(&read_dir_recursive(root, root, DEFAULT_MAX_DEPTH, false), &mut files);
+
+    // Never publish a previous site that lives inside the vault
+    if let Ok(dest_rel) = dest.strip_prefix(root) {
+        let dest_rel = dest_rel.to_string_lossy().replace('\\', "/");
+        if !dest_rel.is_empty() {
+            files.retain(|rel| !within(rel, &dest_rel));
+        }
+    }
+
+    let mut pages = Vec::new();
+    for rel in files.iter().filter(|rel| is_note(rel)) {
+        let Ok(content) = fs::read_to_string(root.join(rel)) else {
+            continue;
+        };
+        let (frontmatter, body) = markdown::split_frontmatter(&content);
+        let frontmatter = frontmatter.unwrap_or_default();
+        if !is_selected(&options.selection, rel, &frontmatter.tags, body) {
+            continue;
+        }
+
+        let title = frontmatter
+            .fields
+            .get("title")
+            .cloned()
+            .or_else(|| markdown::first_heading(body))
+            .unwrap_or_else(|| file_stem(rel));
+        pages.push(Page {
+            rel: rel.clone(),
+            output: page_output(rel),
+            title,
+            tags: frontmatter.tags,
+            content,
+        });
+    }
+
+    let published: BTreeMap<&str, &str> =
+        pages.iter().map(|page| (page.rel.as_str(), page.output.as_str())).collect();
+
+    fs::create_dir_all(dest).map_err(|e| {
+        HibiscusError::Io(format!("Failed to create '{}': {}", dest.display(), e))
+    })?;
+    let old = read_manifest(dest);
+    let mut manifest = PublishManifest::default();
+    let mut report = PublishReport {
+        dest: dest.to_string_lossy().into(),
+        ..Default::default()
+    };
+
+    let total = pages.len();
+    for (processed, page) in pages.iter().enumerate() {
+        if processed % PROGRESS_INTERVAL == 0 {
+            on_progress(PublishProgress { processed, total });
+        }
+
+        let hash = hash_file(&root.join(&page.rel).to_string_lossy()).unwrap_or_default();
+        let previous = old.notes.get(&page.rel).filter(|note| {
+            options.incremental && note.hash == hash && dest.join(&page.output).is_file()
+        });
+
+        let state = match previous {
+            Some(previous) => {
+                report.unchanged += 1;
+                previous.clone()
+            }
+            None => {
+                let (html, assets, warnings) = render_page(page, &files, &published);
+                write_output(dest, &page.output, &html)?;
+                report.published += 1;
+                PublishedNote { hash, assets, warnings }
+            }
+        };
+
+        for asset in &state.assets {
+            if copy_asset(root, dest, asset)? {
+                manifest.files.insert(asset.clone());
+                report.assets += 1;
+            }
+        }
+        report.warnings.extend(state.warnings.iter().map(|message| PublishWarning {
+            path: page.rel.clone(),
+            message: message.clone(),
+        }));
+        manifest.files.insert(page.output.clone());
+        manifest.notes.insert(page.rel.clone(), state);
+    }
+
+    for (output, html) in index_pages(root, options, &pages) {
+        write_output(dest, &output, &html)?;
+        manifest.files.insert(output);
+        report.index_pages += 1;
+    }
+
+    // Remove what the previous publish wrote and this one did not
+    for stale in old.files.difference(&manifest.files) {
+        let path = dest.join(stale);
+        if path.is_file() && fs::remove_file(&path).is_ok() {
+            remove_empty_parents(dest, &path);
+            report.removed.push(stale.clone());
+        }
+    }
+
+    let json = serde_json::to_string_pretty(&manifest)?;
+    write_output(dest, MANIFEST_FILE, &json)?;
+
+    on_progress(PublishProgress { processed: total, total });
+    Ok(report)
+}
+
+/// Renders one note into a full HTML page, collecting assets and warnings.
+fn render_page(
+    page: &Page,
+    files: &[String],
+    published: &BTreeMap<&str, &str>,
+) -> (String, Vec<String>, Vec<String>) {
+    let mut assets = BTreeSet::new();
+    let mut warnings = Vec::new();
+    let (_, body) = markdown::split_frontmatter(&page.content);
+
+    let html = markdown::to_html(body, |kind, dest| {
+        match resolve_link(&page.rel, kind, dest, files, published) {
+            LinkTarget::Page(output, anchor) => {
+                let mut href = relative_href(&page.output, &output);
+                if let Some(anchor) = anchor {
+                    href.push('#');
+                    href.push_str(&anchor);
+                }
+                Some(href)
+            }
+            LinkTarget::Asset(rel) => {
+                let href = relative_href(&page.output, &rel);
+                assets.insert(rel);
+                Some(href)
+            }
+            LinkTarget::External(url) => Some(url),
+            LinkTarget::Unpublished(rel) => {
+                warnings.push(format!("Link to unpublished note '{}' was flattened to text", rel));
+                None
+            }
+            LinkTarget::Missing => {
+                warnings.push(format!("Broken link '{}' was flattened to text", dest));
+                None
+            }
+        }
+    });
+
+    let index = relative_href(&page.output, "index.html");
+    let tags: String = page
+        .tags
+        .iter()
+        .map(|tag| {
+            format!(
+                " <a class=\"tag\" href=\"{}\">#{}</a>",
+                markdown::escape_html(&relative_href(&page.output, &tag_output(tag))),
+                markdown::escape_html(tag)
+            )
+        })
+        .collect();
+    let nav = format!(
+        "<nav><a href=\"{}\">Index</a>{}</nav>\n",
+        markdown::escape_html(&index),
+        tags
+    );
+
+    (
+        html_document(&page.title, &format!("{}{}", nav, html)),
+        assets.into_iter().collect(),
+        warnings,
+    )
+}
+
+/// Resolves a link destination found in the note at `from`.
+fn resolve_link(
+    from: &str,
+    kind: HtmlLinkKind,
+    dest: &str,
+    files: &[String],
+    published: &BTreeMap<&str, &str>,
+) -> LinkTarget {
+    if dest.contains("://") || dest.starts_with("mailto:") || dest.starts_with('#') {
+        return LinkTarget::External(dest.to_string());
+    }
+
+    let (target, anchor) = match dest.split_once('#') {
+        Some((target, anchor)) => (target, Some(markdown::heading_slug(anchor))),
+        None => (dest, None),
+    };
+    let target = target.trim().replace("%20", " ");
+
+    // Wiki-links name a note anywhere in the vault; markdown links and
+    // images are relative paths, falling back to a bare file name lookup
+    // for embeds like `![[diagram.png]]`
+    let rel = match kind {
+        HtmlLinkKind::Wiki => find_by_name(&target, files),
+        _ => join_relative(from, &target)
+            .filter(|rel| files.contains(rel))
+            .or_else(|| (!target.contains('/')).then(|| find_by_name(&target, files)).flatten()),
+    };
+    let Some(rel) = rel else {
+        return LinkTarget::Missing;
+    };
+
+    if !is_note(&rel) {
+        return LinkTarget::Asset(rel);
+    }
+    match published.get(rel.as_str()) {
+        Some(output) => LinkTarget::Page(output.to_string(), anchor.filter(|a| !a.is_empty())),
+        None => LinkTarget::Unpublished(rel),
+    }
+}
+
+/// Finds a vault file by wiki-link name: a path with or without the `.md`
+/// extension, or a bare file name / note title anywhere in the vault.
+fn find_by_name(target: &str, files: &[String]) -> Option<String> {
+    let target = target.trim_start_matches('/');
+    let wanted = target.to_lowercase();
+    let matches = |rel: &&String| {
+        let rel_lower = rel.to_lowercase();
+        let name = rel_lower.rsplit('/').next().unwrap_or(&rel_lower);
+        let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+        let rel_stem = rel_lower.rsplit_once('.').map_or(rel_lower.as_str(), |(stem, _)| stem);
+
+        if wanted.contains('/') {
+            rel_lower == wanted || (is_note(rel) && rel_stem == wanted)
+        } else {
+            name == wanted || (is_note(rel) && stem == wanted)
+        }
+    };
+    files.iter().find(matches).cloned()
+}
+
+/// Joins a relative link onto the folder of `from`, resolving `.` and `..`.
+/// A leading `/` means the workspace root. Returns `None` above the root.
+fn join_relative(from: &str, target: &str) -> Option<String> {
+    let mut parts: Vec<&str> = if target.starts_with('/') {
+        Vec::new()
+    } else {
+        from.split('/').collect::<Vec<_>>().split_last().map(|(_, dir)| dir.to_vec())?
+    };
+
+    for segment in target.split(['/', '\\']) {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            segment => parts.push(segment),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+/// Relative URL from the page at `from` to the site file at `to`.
+fn relative_href(from: &str, to: &str) -> String {
+    let mut from_dir: Vec<&str> = from.split('/').collect();
+    from_dir.pop();
+    let to_parts: Vec<&str> = to.split('/').collect();
+    let shared = from_dir
+        .iter()
+        .zip(&to_parts)
+        .take_while(|(a, b)| a == b)
+        .count()
+        .min(to_parts.len().saturating_sub(1));
+
+    let href = format!(
+        "{}{}",
+        "../".repeat(from_dir.len() - shared),
+        to_parts[shared..].join("/")
+    );
+    href.replace(' ', "%20")
+}
+
+/// Builds `index.html` and the per-tag pages.
+fn index_pages(root: &Path, options: &PublishOptions, pages: &[Page]) -> Vec<(String, String)> {
+    let site_title = options.title.clone().unwrap_or_else(|| {
+        root.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Notes".into())
+    });
+
+    let list = |from: &str, pages: &[&Page]| -> String {
+        let mut items: Vec<&&Page> = pages.iter().collect();
+        items.sort_by_key(|page| page.title.to_lowercase());
+        let items: String = items
+            .iter()
+            .map(|page| {
+                format!(
+                    "<li><a href=\"{}\">{}</a></li>\n",
+                    markdown::escape_html(&relative_href(from, &page.output)),
+                    markdown::escape_html(&page.title)
+                )
+            })
+            .collect();
+        format!("<ul>\n{}</ul>\n", items)
+    };
+
+    let mut by_tag: BTreeMap<&str, Vec<&Page>> = BTreeMap::new();
+    for page in pages {
+        for tag in &page.tags {
+            by_tag.entry(tag.as_str()).or_default().push(page);
+        }
+    }
+
+    let all: Vec<&Page> = pages.iter().collect();
+    let mut index_body = format!(
+        "<h1>{}</h1>\n{}",
+        markdown::escape_html(&site_title),
+        list("index.html", &all)
+    );
+    if !by_tag.is_empty() {
+        index_body.push_str("<h2>Tags</h2>\n<ul>\n");
+        for (tag, tagged) in &by_tag {
+            index_body.push_str(&format!(
+                "<li><a href=\"{}\">#{}</a> ({})</li>\n",
+                markdown::escape_html(&tag_output(tag)),
+                markdown::escape_html(tag),
+                tagged.len()
+            ));
+        }
+        index_body.push_str("</ul>\n");
+    }
+
+    let mut out = vec![("index.html".to_string(), html_document(&site_title, &index_body))];
+    for (tag, tagged) in by_tag {
+        let output = tag_output(tag);
+        let body = format!(
+            "<nav><a href=\"../index.html\">Index</a></nav>\n<h1>#{}</h1>\n{}",
+            markdown::escape_html(tag),
+            list(&output, &tagged)
+        );
+        out.push((output, html_document(&format!("#{}", tag), &body)));
+    }
+    out
+}
+
+fn html_document(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n</head>\n<body>\n{}</body>\n</html>\n",
+        markdown::escape_html(title),
+        body
+    )
+}
+
+fn is_selected(selection: &PublishSelection, rel: &str, tags: &[String], body: &str) -> bool {
+    match selection {
+        PublishSelection::Tag(tag) => {
+            let tag = tag.trim_start_matches('#');
+            tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) || has_inline_tag(body, tag)
+        }
+        PublishSelection::Folder(folder) => within(rel, &folder.replace('\\', "/")),
+        PublishSelection::Paths(paths) => paths
+            .iter()
+            .any(|p| p.replace('\\', "/").trim_start_matches("./") == rel),
+    }
+}
+
+/// Finds `#tag` as a whole word outside code.
+fn has_inline_tag(body: &str, tag: &str) -> bool {
+    let plain = body.split('`').step_by(2).collect::<Vec<_>>().join(" ");
+    plain.split_whitespace().any(|word| {
+        word.strip_prefix('#')
+            .map(|rest| rest.trim_end_matches(|c: char| c.is_ascii_punctuation()))
+            .is_some_and(|rest| rest.eq_ignore_ascii_case(tag))
+    })
+}
+
+fn read_manifest(dest: &Path) -> PublishManifest {
+    fs::read_to_string(dest.join(MANIFEST_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_output(dest: &Path, rel: &str, content: &str) -> Result<(), HibiscusError> {
+    let target = dest.join(rel);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&target, content).map_err(|e| {
+        HibiscusError::Io(format!("Failed to write '{}': {}", target.display(), e))
+    })
+}
+
+/// Copies an asset unless an identical-size copy is already there. Returns
+/// `false` if the source vanished.
+fn copy_asset(root: &Path, dest: &Path, rel: &str) -> Result<bool, HibiscusError> {
+    let source = root.join(rel);
+    let Ok(metadata) = fs::metadata(&source) else {
+        return Ok(false);
+    };
+    let target = dest.join(rel);
+    if fs::metadata(&target).is_ok_and(|existing| existing.len() == metadata.len()) {
+        return Ok(true);
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::copy(&source, &target).map_err(|e| {
+        HibiscusError::Io(format!("Failed to copy '{}': {}", source.display(), e))
+    })?;
+    Ok(true)
+}
+
+/// Removes directories emptied by deleting `path`, up to `dest`.
+fn remove_empty_parents(dest: &Path, path: &Path) {
+    let mut dir = path.parent();
+    while let Some(current) = dir {
+        if current == dest || fs::remove_dir(current).is_err() {
+            break;
+        }
+        dir = current.parent();
+    }
+}
+
+/// Flattens the tree into workspace-relative file paths (forward slashes).
+fn collect_files(nodes: &[Node], out: &mut Vec<String>) {
+    for node in nodes {
+        if let Some(path) = &node.path {
+            out.push(path.replace('\\', "/"));
+        }
+        if let Some(children) = &node.children {
+            collect_files(children, out);
+        }
+    }
+}
+
+fn within(path: &str, folder: &str) -> bool {
+    let folder = folder.trim_matches('/');
+    folder.is_empty() || path == folder || path.starts_with(&format!("{}/", folder))
+}
+
+fn is_note(rel: &str) -> bool {
+    Path::new(rel)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(markdown::is_markdown_extension)
+}
+
+fn file_stem(rel: &str) -> String {
+    Path::new(rel)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn page_output(rel: &str) -> String {
+    Path::new(rel).with_extension("html").to_string_lossy().replace('\\', "/")
+}
+
+fn tag_output(tag: &str) -> String {
+    let slug = markdown::heading_slug(tag);
+    format!("tags/{}.html", if slug.is_empty() { "tag" } else { &slug })
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn publish(root: &Path, dest: &Path, incremental: bool) -> PublishReport {
+        let options = PublishOptions {
+            dest: dest.to_string_lossy().into(),
+            incremental,
+            ..Default::default()
+        };
+        publish_site_blocking(root, dest, &options, |_| {}).unwrap()
+    }
+
+    #[test]
+    fn test_unpublished_links_are_flattened_with_warning() {
+        let vault = tempdir().unwrap();
+        let site = tempdir().unwrap();
+        fs::create_dir_all(vault.path().join("garden")).unwrap();
+        fs::write(
+            vault.path().join("garden").join("Public.md"),
+            "---\ntags: [publish]\n---\n# Public\nSee [[Shared]], [[Private]] and ![d](diagram.png).\n",
+        )
+        .unwrap();
+        fs::write(vault.path().join("Shared.md"), "Also #publish here").unwrap();
+        fs::write(vault.path().join("Private.md"), "secret").unwrap();
+        fs::write(vault.path().join("garden").join("diagram.png"), [1u8, 2, 3]).unwrap();
+
+        let report = publish(vault.path(), site.path(), false);
+        assert_eq!(report.published, 2);
+        assert_eq!(report.assets, 1);
+
+        let page = fs::read_to_string(site.path().join("garden").join("Public.html")).unwrap();
+        assert!(page.contains("<a href=\"../Shared.html\">Shared</a>"));
+        assert!(page.contains(", Private and"));
+        assert!(page.contains("<img src=\"diagram.png\" alt=\"d\">"));
+        assert!(site.path().join("garden").join("diagram.png").is_file());
+        assert!(!site.path().join("Private.html").exists());
+        assert!(site.path().join("tags").join("publish.html").is_file());
+
+        assert_eq!(report.warnings.len(), 1);
+        assert_eq!(report.warnings[0].path, "garden/Public.md");
+        assert!(report.warnings[0].message.contains("Private.md"));
+    }
+
+    #[test]
+    fn test_manifest_removes_unpublished_pages_only() {
+        let vault = tempdir().unwrap();
+        let site = tempdir().unwrap();
+        fs::write(vault.path().join("A.md"), "#publish\nfirst").unwrap();
+        fs::write(vault.path().join("B.md"), "#publish\nsecond").unwrap();
+        fs::write(site.path().join("CNAME"), "garden.example").unwrap();
+
+        publish(vault.path(), site.path(), false);
+        assert!(site.path().join("B.html").is_file());
+
+        // B leaves the selection, A is unchanged
+        fs::write(vault.path().join("B.md"), "no longer public").unwrap();
+        let report = publish(vault.path(), site.path(), true);
+
+        assert_eq!(report.removed, vec!["B.html".to_string()]);
+        assert_eq!((report.published, report.unchanged), (0, 1));
+        assert!(!site.path().join("B.html").exists());
+        assert!(site.path().join("A.html").is_file());
+        // Files the publisher never wrote are left alone
+        assert!(site.path().join("CNAME").is_file());
+
+        let index = fs::read_to_string(site.path().join("index.html")).unwrap();
+        assert!(index.contains("A.html") && !index.contains("B.html"));
+
+        // Changed content is re-rendered in incremental mode
+        fs::write(vault.path().join("A.md"), "#publish\nedited").unwrap();
+        assert_eq!(publish(vault.path(), site.path(), true).published, 1);
+    }
+}
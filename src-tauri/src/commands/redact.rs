@@ -0,0 +1,69 @@
+// ============================================================================
+// NOTE REDACTION
+// ============================================================================
+//
+// Produces a shareable copy of a note with private frontmatter fields and
+// `%% comments %%` removed. The note on disk is never modified.
+// ============================================================================
+
+use std::path::PathBuf;
+use tokio::fs;
+
+use crate::error::HibiscusError;
+use crate::markdown;
+use super::path::validate_path;
+
+/// Returns a redacted copy of a note's markdown.
+///
+/// # Arguments
+/// * `path` - Absolute path to the note
+/// * `fields_to_strip` - Top-level frontmatter keys to remove
+///
+/// # Returns
+/// * `Ok(String)` - The redacted markdown
+/// * `Err(HibiscusError)` - If the note cannot be read
+#[tauri::command]
+pub async fn redact_note(
+    path: String,
+    fields_to_strip: Vec<String>,
+) -> Result<String, HibiscusError> {
+    let path = PathBuf::from(&path);
+
+    // Validate path
+    validate_path(&path)?;
+
+    if !path.is_file() {
+        return Err(HibiscusError::FileNotFound(path.to_string_lossy().into()));
+    }
+
+    let content = fs::read_to_string(&path)
+        .await
+        .map_err(|e| HibiscusError::Io(format!("Failed to read '{}': {}", path.display(), e)))?;
+
+    Ok(markdown::redact(&content, &fields_to_strip))
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_redact_note_leaves_original_untouched() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("note.md");
+        let original = "---\nreviewer: bob\ntitle: Draft\n---\nShare %%not this%%me\n";
+        std::fs::write(&path, original).unwrap();
+
+        let redacted = redact_note(path.to_string_lossy().into(), vec!["reviewer".into()])
+            .await
+            .unwrap();
+
+        assert_eq!(redacted, "---\ntitle: Draft\n---\nShare me\n");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), original);
+    }
+}
@@ -0,0 +1,79 @@
+// ============================================================================
+// REFERENCE RECONCILIATION
+// ============================================================================
+//
+// Tauri entry point for the reference reconciliation pass in
+// `crate::references`. The file commands (move/delete) call the same pass
+// automatically, and so does the watcher for external renames and deletes;
+// this command lets the frontend run it for changes it learned about
+// elsewhere.
+// ============================================================================
+
+use std::path::PathBuf;
+
+use crate::error::HibiscusError;
+use crate::references::{self, FileChange, ReconcileReport};
+use super::path::{find_workspace_root, validate_path};
+use super::workspace::WORKSPACE_LOCK;
+
+/// Remaps or prunes stored node references after renames and deletes.
+///
+/// # Arguments
+/// * `root` - Workspace root directory path
+/// * `changes` - Renames and deletes to apply, in order
+///
+/// # Returns
+/// * `Ok(ReconcileReport)` - What was remapped and pruned, per store
+/// * `Err(HibiscusError)` - If a store document could not be read or written
+#[tauri::command]
+pub async fn reconcile_references(
+    root: String,
+    changes: Vec<FileChange>,
+) -> Result<ReconcileReport, HibiscusError> {
+    let root = PathBuf::from(&root);
+
+    // Validate path
+    validate_path(&root)?;
+
+    let _guard = WORKSPACE_LOCK.lock().await;
+    tokio::task::spawn_blocking(move || references::reconcile(&root, &changes))
+        .await
+        .map_err(|e| HibiscusError::Workspace(format!("Reconcile task failed: {}", e)))?
+}
+
+/// Runs reconciliation for a single change made by a file command.
+///
+/// The workspace root is discovered from the changed path. Failures are
+/// logged rather than returned: the filesystem operation has already
+/// succeeded and stale references are recoverable.
+pub(crate) async fn reconcile_after(path: &std::path::Path, change: FileChange) {
+    let Some(root) = find_workspace_root(path) else {
+        return;
+    };
+
+    let _guard = WORKSPACE_LOCK.lock().await;
+    let result =
+        tokio::task::spawn_blocking(move || references::reconcile(&root, &[change])).await;
+
+    match result {
+        Ok(Ok(_)) => {}
+        Ok(Err(e)) => eprintln!("[Hibiscus] Warning: Failed to reconcile references: {}", e),
+        Err(e) => eprintln!("[Hibiscus] Warning: Reconcile task failed: {}", e),
+    }
+}
+
+/// Runs reconciliation for changes the watcher saw under `root`, without
+/// waiting for it. Failures are logged, as in `reconcile_after`.
+pub(crate) fn reconcile_in_background(root: PathBuf, changes: Vec<FileChange>) {
+    tauri::async_runtime::spawn(async move {
+        let _guard = WORKSPACE_LOCK.lock().await;
+        let result =
+            tokio::task::spawn_blocking(move || references::reconcile(&root, &changes)).await;
+
+        match result {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => eprintln!("[Hibiscus] Warning: Failed to reconcile references: {}", e),
+            Err(e) => eprintln!("[Hibiscus] Warning: Reconcile task failed: {}", e),
+        }
+    });
+}
@@ -0,0 +1,194 @@
+// ============================================================================
+// EXTERNAL RELOAD
+// ============================================================================
+//
+// Helpers for reloading an open note after it changed on disk. The editor
+// keeps the old buffer until the reload, so it can ask where its scroll
+// anchor ended up in the new content. It can also ask how far its unsaved
+// buffer is from the file, for a "+12 −3 unsaved" indicator.
+// ============================================================================
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::diff::{diff_lines, map_line, DiffOp};
+use crate::error::HibiscusError;
+use super::files::decode_legacy_text;
+use super::path::{scope_to_workspace, validate_path};
+
+/// Where a scroll anchor lands in reloaded content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ScrollAnchor {
+    /// 1-based line in the new content
+    pub line: usize,
+    /// `false` when the anchor line itself changed or was removed and
+    /// `line` is only the nearest surviving position
+    pub confident: bool,
+}
+
+/// Maps a line of the old buffer to the matching line of the reloaded file.
+///
+/// # Arguments
+/// * `old_content` - Buffer content before the reload
+/// * `new_content` - Content read from disk
+/// * `old_line` - 1-based anchor line in `old_content`
+///
+/// # Returns
+/// * `Ok(ScrollAnchor)` - Best-matching line in `new_content`
+#[tauri::command]
+pub async fn reanchor_after_reload(
+    old_content: String,
+    new_content: String,
+    old_line: usize,
+) -> Result<ScrollAnchor, HibiscusError> {
+    tokio::task::spawn_blocking(move || reanchor(&old_content, &new_content, old_line))
+        .await
+        .map_err(|e| HibiscusError::Io(format!("Reanchor task failed: {}", e)))
+}
+
+fn reanchor(old_content: &str, new_content: &str, old_line: usize) -> ScrollAnchor {
+    let old: Vec<&str> = old_content.lines().collect();
+    let new: Vec<&str> = new_content.lines().collect();
+
+    let (line, confident) = map_line(&diff_lines(&old, &new), old_line.saturating_sub(1));
+    ScrollAnchor {
+        line: line + 1,
+        confident,
+    }
+}
+
+/// How an editor buffer differs from the saved file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct UnsavedDiffStats {
+    pub added_lines: usize,
+    pub removed_lines: usize,
+    /// The buffer differs from the file, line endings aside. Also set for
+    /// changes the line counts miss, like a removed final newline.
+    pub is_dirty: bool,
+}
+
+/// Compares an editor buffer with the version of the file on disk.
+///
+/// # Arguments
+/// * `path` - Absolute path of the file the buffer belongs to
+/// * `buffer_contents` - The buffer's current content
+///
+/// # Returns
+/// * `Ok(UnsavedDiffStats)` - Lines added and removed by the buffer. A file
+///   that does not exist yet counts every buffer line as added and is dirty.
+/// * `Err(HibiscusError)` - If the path is invalid or the file unreadable
+///
+/// # Notes
+/// Read-only. Files that are not UTF-8 are compared as `read_text_file`
+/// decodes them.
+#[tauri::command]
+pub async fn unsaved_diff_stats(path: String, buffer_contents: String) -> Result<UnsavedDiffStats, HibiscusError> {
+    let path = PathBuf::from(&path);
+    validate_path(&path)?;
+    let path = scope_to_workspace(&path)?;
+
+    let saved = match tokio::fs::read(&path).await {
+        Ok(bytes) => Some(match String::from_utf8(bytes) {
+            Ok(text) => text,
+            Err(e) => decode_legacy_text(e.as_bytes()).0,
+        }),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => {
+            return Err(HibiscusError::Io(format!("Failed to read file '{}': {}", path.display(), e)));
+        }
+    };
+
+    tokio::task::spawn_blocking(move || diff_stats(saved.as_deref(), &buffer_contents))
+        .await
+        .map_err(|e| HibiscusError::Io(format!("Diff stats task failed: {}", e)))
+}
+
+fn diff_stats(saved: Option<&str>, buffer: &str) -> UnsavedDiffStats {
+    let Some(saved) = saved else {
+        return UnsavedDiffStats { added_lines: buffer.lines().count(), removed_lines: 0, is_dirty: true };
+    };
+
+    let old: Vec<&str> = saved.lines().collect();
+    let new: Vec<&str> = buffer.lines().collect();
+    let mut stats = UnsavedDiffStats { added_lines: 0, removed_lines: 0, is_dirty: false };
+    for op in diff_lines(&old, &new) {
+        match op {
+            DiffOp::Insert { .. } => stats.added_lines += 1,
+            DiffOp::Delete { .. } => stats.removed_lines += 1,
+            DiffOp::Equal { .. } => {}
+        }
+    }
+    stats.is_dirty = saved.replace("\r\n", "\n") != buffer.replace("\r\n", "\n");
+    stats
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_lines_inserted_above_shift_anchor_down() {
+        let old = "# Title\nintro\nanchor\nmore";
+        let new = "# Title\nnew one\nnew two\nintro\nanchor\nmore";
+
+        assert_eq!(reanchor(old, new, 3), ScrollAnchor { line: 5, confident: true });
+        assert_eq!(reanchor(old, new, 1), ScrollAnchor { line: 1, confident: true });
+    }
+
+    #[test]
+    fn test_deleted_anchor_maps_to_nearest_line() {
+        let old = "a\nb\nanchor\nc\nd";
+
+        // Removed outright: the line that followed it takes its place
+        let removed = reanchor(old, "a\nb\nc\nd", 3);
+        assert_eq!(removed, ScrollAnchor { line: 3, confident: false });
+
+        // Rewritten in place
+        let rewritten = reanchor(old, "a\nb\nchanged\nc\nd", 3);
+        assert_eq!(rewritten, ScrollAnchor { line: 3, confident: false });
+
+        // Tail of the file removed, anchor clamps to the last line
+        let truncated = reanchor(old, "a\nb", 3);
+        assert_eq!(truncated, ScrollAnchor { line: 2, confident: false });
+        assert_eq!(reanchor(old, "", 3).line, 1);
+    }
+
+    #[tokio::test]
+    async fn test_unsaved_diff_stats_count_changed_lines() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("note.md");
+        std::fs::write(&path, "# Title\r\nkeep\r\nold line\r\nend\r\n").unwrap();
+        let path_str = path.to_string_lossy().to_string();
+
+        let stats = unsaved_diff_stats(path_str.clone(), "# Title\nkeep\nnew line\nanother\nend\n".into())
+            .await
+            .unwrap();
+        assert_eq!(stats, UnsavedDiffStats { added_lines: 2, removed_lines: 1, is_dirty: true });
+
+        // Not yet on disk: everything is added
+        let missing = dir.path().join("new.md").to_string_lossy().to_string();
+        let stats = unsaved_diff_stats(missing, "a\nb\n".into()).await.unwrap();
+        assert_eq!(stats, UnsavedDiffStats { added_lines: 2, removed_lines: 0, is_dirty: true });
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_buffer_is_clean() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("note.md");
+        std::fs::write(&path, "one\r\ntwo\r\n").unwrap();
+        let path_str = path.to_string_lossy().to_string();
+
+        let stats = unsaved_diff_stats(path_str.clone(), "one\ntwo\n".into()).await.unwrap();
+        assert_eq!(stats, UnsavedDiffStats { added_lines: 0, removed_lines: 0, is_dirty: false });
+
+        // A dropped final newline is a change without changed lines
+        let stats = unsaved_diff_stats(path_str, "one\ntwo".into()).await.unwrap();
+        assert_eq!(stats, UnsavedDiffStats { added_lines: 0, removed_lines: 0, is_dirty: true });
+    }
+}
@@ -0,0 +1,157 @@
+// ============================================================================
+// NOTE REVIEW SCHEDULING
+// ============================================================================
+//
+// Tauri entry points for `crate::review`. The schedule shares
+// WORKSPACE_LOCK with reference reconciliation, which rewrites the same file
+// when notes are renamed or deleted. "Today" is the local calendar date.
+// ============================================================================
+
+use chrono::{Local, NaiveDate};
+use std::path::{Path, PathBuf};
+
+use crate::error::HibiscusError;
+use crate::ids::to_canonical_id;
+use crate::review::{self, DueNote, EnrollmentScan, ReviewState, MAX_QUALITY};
+use super::path::validate_path;
+use super::workspace::WORKSPACE_LOCK;
+
+/// Records a review of a note and reschedules it (SM-2).
+///
+/// A note that was not enrolled yet is enrolled by its first review.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+/// * `path` - Note path (absolute or relative to the root)
+/// * `quality` - Recall grade from 0 (forgotten) to 5 (perfect)
+///
+/// # Returns
+/// * `Ok(ReviewState)` - The note's new schedule
+/// * `Err(HibiscusError)` - If the grade is out of range or the schedule
+///   could not be saved
+#[tauri::command]
+pub async fn mark_note_reviewed(
+    root: String,
+    path: String,
+    quality: u8,
+) -> Result<ReviewState, HibiscusError> {
+    if quality > MAX_QUALITY {
+        return Err(HibiscusError::Workspace(format!(
+            "Review quality must be between 0 and {}, got {}",
+            MAX_QUALITY, quality
+        )));
+    }
+    let (root, id) = resolve_note(&root, &path)?;
+
+    update_schedule(root, move |data, today| {
+        Ok(data.mark_reviewed(&id, quality, today).clone())
+    })
+    .await
+}
+
+/// Lists notes due for review on or before a date, most overdue first.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+/// * `date` - `YYYY-MM-DD`; omitted = today
+///
+/// # Returns
+/// * `Ok(Vec<DueNote>)` - Due notes
+/// * `Err(HibiscusError)` - If the date is invalid or the schedule unreadable
+#[tauri::command]
+pub async fn get_due_reviews(
+    root: String,
+    date: Option<String>,
+) -> Result<Vec<DueNote>, HibiscusError> {
+    let root = PathBuf::from(&root);
+
+    // Validate path
+    validate_path(&root)?;
+
+    let date = match date {
+        Some(date) => crate::time::parse_date(&date)
+            .ok_or_else(|| HibiscusError::Workspace(format!("Invalid date '{}'", date)))?,
+        None => today(),
+    };
+
+    let data = tokio::task::spawn_blocking(move || review::load(&root))
+        .await
+        .map_err(|e| HibiscusError::Io(format!("Review task failed: {}", e)))??;
+    Ok(data.due_on(date))
+}
+
+/// Adds a note to, or removes it from, the review schedule.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+/// * `path` - Note path (absolute or relative to the root)
+/// * `enrolled` - `true` to schedule the note (due today), `false` to drop
+///   its schedule
+///
+/// # Returns
+/// * `Ok(bool)` - Whether the schedule changed
+/// * `Err(HibiscusError)` - If the schedule could not be saved
+#[tauri::command]
+pub async fn set_review_enrollment(
+    root: String,
+    path: String,
+    enrolled: bool,
+) -> Result<bool, HibiscusError> {
+    let (root, id) = resolve_note(&root, &path)?;
+
+    update_schedule(root, move |data, today| Ok(data.set_enrolled(&id, enrolled, today))).await
+}
+
+/// Applies `review: true` / `review: false` frontmatter flags of every note
+/// in the workspace to the schedule.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+///
+/// # Returns
+/// * `Ok(EnrollmentScan)` - Notes that were enrolled or removed
+/// * `Err(HibiscusError)` - If the schedule could not be saved
+#[tauri::command]
+pub async fn scan_review_flags(root: String) -> Result<EnrollmentScan, HibiscusError> {
+    let root = PathBuf::from(&root);
+
+    // Validate path
+    validate_path(&root)?;
+
+    let scan_root = root.clone();
+    update_schedule(root, move |data, today| {
+        Ok(review::scan_frontmatter(&scan_root, data, today))
+    })
+    .await
+}
+
+fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// Validates a root and note path and returns the note's canonical id.
+fn resolve_note(root: &str, path: &str) -> Result<(PathBuf, String), HibiscusError> {
+    let root = PathBuf::from(root);
+    validate_path(&root)?;
+    validate_path(Path::new(path))?;
+
+    let id = to_canonical_id(Path::new(path), &root);
+    Ok((root, id))
+}
+
+/// Loads the schedule, applies `update` and saves the result.
+async fn update_schedule<T, F>(root: PathBuf, update: F) -> Result<T, HibiscusError>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut review::ReviewData, NaiveDate) -> Result<T, HibiscusError> + Send + 'static,
+{
+    let _guard = WORKSPACE_LOCK.lock().await;
+    tokio::task::spawn_blocking(move || {
+        let mut data = review::load(&root)?;
+        let result = update(&mut data, today())?;
+        review::save(&root, &data)?;
+        Ok(result)
+    })
+    .await
+    .map_err(|e| HibiscusError::Io(format!("Review task failed: {}", e)))?
+}
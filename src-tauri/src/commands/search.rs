@@ -0,0 +1,305 @@
+// ============================================================================
+// WORKSPACE TEXT SEARCH
+// ============================================================================
+//
+// Plain substring search over note files, independent of the knowledge
+// index. Each match can carry surrounding context lines (like ripgrep's
+// `-C`); context windows that overlap or touch are merged into one block so
+// no line is reported twice.
+// ============================================================================
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::error::HibiscusError;
+use crate::tree::{read_dir_recursive, DEFAULT_MAX_DEPTH};
+use crate::variants::VariantConfig;
+use crate::workspace::{Node, NodeType};
+use super::path::validate_path;
+
+/// Search options. All fields are optional from the frontend.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SearchOptions {
+    /// Match case exactly (default: case-insensitive)
+    pub case_sensitive: bool,
+    /// Lines of context before and after each match (default 0)
+    pub context_lines: usize,
+    /// File extensions to search (default md, markdown, txt)
+    pub extensions: Vec<String>,
+    /// Stop after this many matching lines in total
+    pub max_matches: usize,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            case_sensitive: false,
+            context_lines: 0,
+            extensions: vec!["md".into(), "markdown".into(), "txt".into()],
+            max_matches: 1000,
+        }
+    }
+}
+
+/// One line of a match block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchLine {
+    /// 1-based line number
+    pub line_number: usize,
+    pub text: String,
+    /// `true` for matched lines, `false` for context
+    pub is_match: bool,
+}
+
+/// A contiguous run of lines containing one or more matches.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MatchBlock {
+    pub lines: Vec<SearchLine>,
+}
+
+/// All matches within one file.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileMatches {
+    /// Path relative to the workspace root
+    pub path: String,
+    /// Language of the file, if it is a variant of a note
+    /// (e.g. `de` for `topic.de.md`; see `crate::variants`)
+    pub language: Option<String>,
+    pub blocks: Vec<MatchBlock>,
+}
+
+/// Searches note files in a workspace for a literal string.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+/// * `query` - Text to find (literal, not a regex)
+/// * `options` - Case sensitivity, context lines, extensions, match cap
+///
+/// # Returns
+/// * `Ok(Vec<FileMatches>)` - Matches grouped by file, in tree order
+/// * `Err(HibiscusError)` - If the root is invalid
+///
+/// # Notes
+/// Hidden files and `.hibiscus` are skipped. Unreadable or non-UTF-8 files
+/// are ignored.
+#[tauri::command]
+pub async fn search_workspace(
+    root: String,
+    query: String,
+    options: Option<SearchOptions>,
+) -> Result<Vec<FileMatches>, HibiscusError> {
+    let root = PathBuf::from(&root);
+
+    // Validate path
+    validate_path(&root)?;
+
+    if !root.is_dir() {
+        return Err(HibiscusError::InvalidPathType {
+            path: root.to_string_lossy().into(),
+            expected: "directory".into(),
+            actual: "file".into(),
+        });
+    }
+
+    let options = options.unwrap_or_default();
+    tokio::task::spawn_blocking(move || Ok(search_blocking(&root, &query, &options)))
+        .await
+        .map_err(|e| HibiscusError::Io(format!("Search task failed: {}", e)))?
+}
+
+fn search_blocking(root: &Path, query: &str, options: &SearchOptions) -> Vec<FileMatches> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut files = Vec::new();
+    collect_files(&read_dir_recursive(root, root, DEFAULT_MAX_DEPTH, false), &mut files);
+
+    let variants = VariantConfig::load(root);
+    let mut results = Vec::new();
+    let mut remaining = options.max_matches;
+
+    for rel in files {
+        if remaining == 0 {
+            break;
+        }
+
+        let ext = Path::new(&rel)
+            .extension()
+            .map(|e| e.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if !options.extensions.iter().any(|e| e.eq_ignore_ascii_case(&ext)) {
+            continue;
+        }
+
+        let Ok(content) = std::fs::read_to_string(root.join(&rel)) else {
+            continue;
+        };
+
+        let blocks = search_text(&content, query, options, &mut remaining);
+        if !blocks.is_empty() {
+            let language = Path::new(&rel)
+                .file_name()
+                .and_then(|name| variants.split(&name.to_string_lossy()))
+                .map(|(_, lang)| lang);
+            results.push(FileMatches {
+                path: rel.replace('\\', "/"),
+                language,
+                blocks,
+            });
+        }
+    }
+
+    results
+}
+
+fn collect_files(nodes: &[Node], out: &mut Vec<String>) {
+    for node in nodes {
+        match node.node_type {
+            NodeType::File => out.extend(node.path.clone()),
+            NodeType::Folder => collect_files(node.children.as_deref().unwrap_or_default(), out),
+        }
+    }
+}
+
+/// Finds matching lines in `content` and groups them with their context.
+///
+/// Decrements `remaining` for every matched line and stops at zero.
+fn search_text(
+    content: &str,
+    query: &str,
+    options: &SearchOptions,
+    remaining: &mut usize,
+) -> Vec<MatchBlock> {
+    let lines: Vec<&str> = content.lines().collect();
+    let needle = if options.case_sensitive {
+        query.to_string()
+    } else {
+        query.to_lowercase()
+    };
+
+    let matched: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| {
+            if options.case_sensitive {
+                line.contains(&needle)
+            } else {
+                line.to_lowercase().contains(&needle)
+            }
+        })
+        .map(|(i, _)| i)
+        .take(*remaining)
+        .collect();
+    *remaining -= matched.len();
+
+    // Build [start, end] windows, merging any that overlap or touch.
+    let context = options.context_lines;
+    let mut windows: Vec<(usize, usize)> = Vec::new();
+    for &i in &matched {
+        let start = i.saturating_sub(context);
+        let end = i.saturating_add(context).min(lines.len() - 1);
+        match windows.last_mut() {
+            Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+            _ => windows.push((start, end)),
+        }
+    }
+
+    windows
+        .into_iter()
+        .map(|(start, end)| MatchBlock {
+            lines: (start..=end)
+                .map(|i| SearchLine {
+                    line_number: i + 1,
+                    text: lines[i].to_string(),
+                    is_match: matched.binary_search(&i).is_ok(),
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn search(content: &str, query: &str, context_lines: usize) -> Vec<MatchBlock> {
+        let options = SearchOptions {
+            context_lines,
+            ..Default::default()
+        };
+        let mut remaining = usize::MAX;
+        search_text(content, query, &options, &mut remaining)
+    }
+
+    fn numbers(block: &MatchBlock) -> Vec<(usize, bool)> {
+        block.lines.iter().map(|l| (l.line_number, l.is_match)).collect()
+    }
+
+    #[test]
+    fn test_context_lines_included_and_clamped() {
+        let content = "hit\nb\nc\nd\ne\nf\ng\nhit";
+        let blocks = search(content, "HIT", 2);
+
+        assert_eq!(blocks.len(), 2);
+        // Clamped at the start of the file
+        assert_eq!(numbers(&blocks[0]), vec![(1, true), (2, false), (3, false)]);
+        // Clamped at the end of the file
+        assert_eq!(numbers(&blocks[1]), vec![(6, false), (7, false), (8, true)]);
+    }
+
+    #[test]
+    fn test_overlapping_contexts_merge() {
+        let content = "a\nhit one\nb\nhit two\nc\nd\ne";
+        let blocks = search(content, "hit", 1);
+
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(
+            numbers(&blocks[0]),
+            vec![(1, false), (2, true), (3, false), (4, true), (5, false)]
+        );
+    }
+
+    #[test]
+    fn test_adjacent_contexts_merge() {
+        // Windows [1,3] and [4,6] touch, so they form one block.
+        let content = "a\nhit\nb\nc\nhit\nd";
+        let blocks = search(content, "hit", 1);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].lines.len(), 6);
+    }
+
+    #[test]
+    fn test_no_context_by_default() {
+        let blocks = search("a\nhit\nb", "hit", 0);
+        assert_eq!(numbers(&blocks[0]), vec![(2, true)]);
+    }
+
+    #[tokio::test]
+    async fn test_search_workspace_filters_extensions_and_hidden() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("notes")).unwrap();
+        fs::create_dir_all(dir.path().join(".hibiscus")).unwrap();
+        fs::write(dir.path().join("notes").join("a.md"), "needle here").unwrap();
+        fs::write(dir.path().join("b.json"), "needle").unwrap();
+        fs::write(dir.path().join(".hibiscus").join("c.md"), "needle").unwrap();
+        fs::write(dir.path().join("notes").join("topic.de.md"), "needle").unwrap();
+
+        let results = search_workspace(dir.path().to_string_lossy().into(), "needle".into(), None)
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].path, "notes/a.md");
+        assert_eq!(results[0].language, None);
+        // Matches in a language variant say which one
+        assert_eq!(results[1].language.as_deref(), Some("de"));
+    }
+}
@@ -0,0 +1,629 @@
+// ============================================================================
+// WRITING STATISTICS
+// ============================================================================
+//
+// Cheap, delta-oriented counters for writing analytics. The frontend keeps
+// the running totals (per session, per day); the backend only reports how a
+// file changed since the last count it was given.
+//
+// Words written per day and per note over a date range are measured from
+// history snapshots (see `crate::writing_progress`) for the stats dashboard
+// and the activity heatmap.
+//
+// For spreadsheet analysis the whole vault can also be exported as a CSV
+// report with one row per file. The CSV is streamed to a temp file next to
+// the destination and renamed over it, so a failed export never leaves a
+// truncated report behind.
+//
+// For concordance tools, `export_word_frequencies` writes a word, count and
+// documents table of the whole vault the same way, as a background job.
+// Counting is memory-bounded (see `crate::concordance`): its run files live
+// in a folder next to the destination and are removed afterwards.
+// ============================================================================
+
+use serde::Serialize;
+use std::fs;
+use std::io::{BufWriter, Read};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tauri::Emitter;
+
+use crate::concordance::{FrequencyCounter, FrequencyOptions, Tokenizer};
+use crate::error::HibiscusError;
+use crate::jobs::JOBS;
+use crate::limits::{LimitHit, Limits};
+use crate::markdown::{self, PlainTextOptions};
+use crate::time_tracking::DateRange;
+use crate::tree::read_dir_limited;
+use crate::workspace::Node;
+use crate::writing_progress::{self, WritingProgress};
+use super::opener::looks_binary;
+use super::path::validate_path;
+
+/// Header row of the statistics CSV.
+const CSV_HEADER: [&str; 6] = ["path", "extension", "size_bytes", "modified_ms", "word_count", "link_count"];
+
+/// Header row of the word frequency CSV.
+const FREQUENCY_HEADER: [&str; 3] = ["word", "count", "documents"];
+
+/// Distinct words `export_word_frequencies` holds in memory before it
+/// spills counts to disk.
+const MAX_WORDS_IN_MEMORY: usize = 500_000;
+
+/// Bytes inspected to decide whether a file is text.
+const BINARY_SNIFF_BYTES: usize = 8192;
+
+/// Current word count of a file and its change from a previous count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct WordCountDelta {
+    /// Words in the file now
+    pub current: usize,
+    /// `current - previous_count`; negative after deletions
+    pub delta: i64,
+}
+
+/// Computes a file's word count relative to a previously reported count.
+///
+/// # Arguments
+/// * `path` - File to count
+/// * `previous_count` - Count reported at the last autosave
+/// * `strip_markdown` - Ignore markdown syntax and frontmatter in `.md`
+///   files (defaults to `true`)
+///
+/// # Returns
+/// * `Ok(WordCountDelta)` - Current count and signed delta
+/// * `Err(HibiscusError)` - If the path is invalid or unreadable
+#[tauri::command]
+pub async fn word_count_delta(
+    path: String,
+    previous_count: usize,
+    strip_markdown: Option<bool>,
+) -> Result<WordCountDelta, HibiscusError> {
+    let path = PathBuf::from(&path);
+
+    // Validate path
+    validate_path(&path)?;
+
+    let content = tokio::fs::read_to_string(&path).await.map_err(|e| {
+        HibiscusError::Io(format!("Failed to read '{}': {}", path.display(), e))
+    })?;
+
+    let strip = strip_markdown.unwrap_or(true) && is_markdown_file(&path);
+    let current = count_words(&content, strip);
+
+    Ok(WordCountDelta {
+        current,
+        delta: current as i64 - previous_count as i64,
+    })
+}
+
+/// Measures words written per day and per note from history snapshots.
+///
+/// Changes between consecutive backups (or git commits) of each note are
+/// diffed word by word, so deletions are counted separately instead of
+/// cancelling out new writing. Notes without enough snapshots are estimated
+/// and flagged `approximate`.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+/// * `path` - One note (absolute or relative to the root); every markdown
+///   and `.txt` note if omitted
+/// * `range` - Inclusive `from`/`to` dates (`YYYY-MM-DD`); omitted = all time
+/// * `utc_offset_minutes` - Local UTC offset, used to assign local days
+///
+/// # Returns
+/// * `Ok(WritingProgress)` - Totals, per-day and per-note changes
+/// * `Err(HibiscusError)` - If a path is invalid or outside the workspace
+#[tauri::command]
+pub async fn compute_writing_progress(
+    root: String,
+    path: Option<String>,
+    range: Option<DateRange>,
+    utc_offset_minutes: Option<i32>,
+) -> Result<WritingProgress, HibiscusError> {
+    let root = PathBuf::from(&root);
+
+    // Validate paths
+    validate_path(&root)?;
+    let note = match path {
+        Some(path) => {
+            let path = root.join(path);
+            validate_path(&path)?;
+            let rel = path.strip_prefix(&root).map_err(|_| {
+                HibiscusError::PathValidation(format!("'{}' is outside the workspace", path.display()))
+            })?;
+            Some(rel.to_string_lossy().replace('\\', "/"))
+        }
+        None => None,
+    };
+
+    tokio::task::spawn_blocking(move || {
+        let notes = match note {
+            Some(note) => vec![note],
+            None => {
+                let (tree, _) = read_dir_limited(&root, &root, Limits::load(&root).tree_depth, false);
+                let mut files = Vec::new();
+                collect_files(&tree, &mut files);
+                files.retain(|rel| is_markdown_file(Path::new(rel)) || rel.to_lowercase().ends_with(".txt"));
+                files
+            }
+        };
+        writing_progress::writing_progress(
+            &root,
+            &notes,
+            &range.unwrap_or_default(),
+            utc_offset_minutes.unwrap_or(0),
+        )
+    })
+    .await
+    .map_err(|e| HibiscusError::Io(format!("Writing progress task failed: {}", e)))
+}
+
+/// Summary returned once the statistics export finishes.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StatsExportReport {
+    /// Data rows written (one per file)
+    pub rows: usize,
+    /// Folders the depth limit left out of the report
+    pub limits_hit: Vec<LimitHit>,
+}
+
+/// Exports per-file vault statistics as a CSV report.
+///
+/// Columns: `path` (workspace-relative), `extension`, `size_bytes`,
+/// `modified_ms` (Unix epoch), `word_count` and `link_count`. The last two
+/// are left blank for binary files. Dotfiles are skipped like in the tree.
+///
+/// # Arguments
+/// * `root` - Workspace root directory path
+/// * `out_path` - CSV file to write; replaced if it exists
+///
+/// # Returns
+/// * `Ok(StatsExportReport)` - Number of rows written
+/// * `Err(HibiscusError)` - If a path is invalid or the CSV cannot be written
+#[tauri::command]
+pub async fn export_stats_csv(root: String, out_path: String) -> Result<StatsExportReport, HibiscusError> {
+    let root = PathBuf::from(&root);
+    let out = PathBuf::from(&out_path);
+
+    // Validate paths
+    validate_path(&root)?;
+    validate_path(&out)?;
+
+    if !root.is_dir() {
+        return Err(HibiscusError::InvalidPathType {
+            path: root.to_string_lossy().into(),
+            expected: "directory".into(),
+            actual: "file".into(),
+        });
+    }
+
+    tokio::task::spawn_blocking(move || export_stats_csv_blocking(&root, &out))
+        .await
+        .map_err(|e| HibiscusError::Io(format!("Stats export task failed: {}", e)))?
+}
+
+/// Blocking implementation of `export_stats_csv`.
+pub fn export_stats_csv_blocking(root: &Path, out: &Path) -> Result<StatsExportReport, HibiscusError> {
+    let (tree, limits_hit) = read_dir_limited(root, root, Limits::load(root).tree_depth, false);
+    let mut files = Vec::new();
+    collect_files(&tree, &mut files);
+
+    // A previous report inside the vault is not a row of the new one
+    if let Ok(out_rel) = out.strip_prefix(root) {
+        let out_rel = out_rel.to_string_lossy().replace('\\', "/");
+        files.retain(|rel| *rel != out_rel);
+    }
+
+    replace_file(out, |temp_path| write_stats_csv(root, &files, temp_path))?;
+
+    Ok(StatsExportReport {
+        rows: files.len(),
+        limits_hit,
+    })
+}
+
+/// Writes `out` through `write`, which fills a temp file next to it that
+/// then replaces `out`. Nothing is left behind if either step fails.
+fn replace_file(out: &Path, write: impl FnOnce(&Path) -> Result<(), HibiscusError>) -> Result<(), HibiscusError> {
+    let mut temp_name = out.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".hibiscus-save~");
+    let temp_path = out.with_file_name(temp_name);
+
+    let result = write(&temp_path).and_then(|()| {
+        // On Windows, we need to remove existing file before rename
+        #[cfg(target_os = "windows")]
+        if out.exists() {
+            fs::remove_file(out)?;
+        }
+        fs::rename(&temp_path, out).map_err(|e| {
+            HibiscusError::Io(format!("Failed to finalize '{}': {}", out.display(), e))
+        })
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Summary of a word frequency export, the payload of
+/// `word-frequencies-complete`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WordFrequencyReport {
+    pub job_id: String,
+    /// Text files counted
+    pub documents: usize,
+    /// Data rows written (one per word)
+    pub distinct_words: u64,
+    /// Sum of all counts
+    pub total_words: u64,
+    /// Run files the counts were spilled to; 0 if they fit in memory
+    pub spilled_runs: usize,
+    /// Folders the depth limit left out
+    pub limits_hit: Vec<LimitHit>,
+}
+
+/// Exports how often each word occurs in the vault, as a CSV for
+/// concordance tools.
+///
+/// Columns: `word`, `count` and `documents` (files containing the word),
+/// most frequent first. Every text file the tree shows is counted, so the
+/// ignore rules apply; binary files are skipped.
+///
+/// # Arguments
+/// * `root` - Workspace root directory path
+/// * `dest_csv` - CSV file to write; replaced if it exists
+/// * `options` - Lowercasing, markdown stripping and stopwords (see
+///   `FrequencyOptions`); lowercased and stripped by default
+///
+/// # Returns
+/// * `Ok(String)` - Job id. Progress is reported through `job-progress`
+///   (phases `counting`, then `ranking`); the `WordFrequencyReport` arrives
+///   as `word-frequencies-complete`.
+/// * `Err(HibiscusError)` - If a path is invalid
+#[tauri::command]
+pub async fn export_word_frequencies(
+    root: String,
+    dest_csv: String,
+    options: Option<FrequencyOptions>,
+    window: tauri::Window,
+) -> Result<String, HibiscusError> {
+    let root = PathBuf::from(&root);
+    let out = PathBuf::from(&dest_csv);
+
+    // Validate paths
+    validate_path(&root)?;
+    validate_path(&out)?;
+
+    if !root.is_dir() {
+        return Err(HibiscusError::InvalidPathType {
+            path: root.to_string_lossy().into(),
+            expected: "directory".into(),
+            actual: "file".into(),
+        });
+    }
+
+    let job_id = JOBS.start("export_word_frequencies");
+    let id = job_id.clone();
+    tauri::async_runtime::spawn(async move {
+        let progress_window = window.clone();
+        let progress_id = id.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let options = options.unwrap_or_default();
+            export_word_frequencies_blocking(&root, &out, options, MAX_WORDS_IN_MEMORY, |phase, done, total| {
+                if let Some(progress) = JOBS.update(&progress_id, phase, done as u64, total as u64) {
+                    let _ = progress_window.emit("job-progress", progress);
+                }
+            })
+        })
+        .await
+        .map_err(|e| format!("Word frequency task failed: {}", e))
+        .and_then(|result| result.map_err(|e| e.to_string()));
+
+        if let Some(progress) = JOBS.finish(&id, result.as_ref().err().cloned()) {
+            let _ = window.emit("job-progress", progress);
+        }
+        if let Ok(report) = result {
+            let report = WordFrequencyReport { job_id: id, ..report };
+            if let Err(e) = window.emit("word-frequencies-complete", report) {
+                eprintln!("[Hibiscus] Error emitting event: {}", e);
+            }
+        }
+    });
+
+    Ok(job_id)
+}
+
+/// Blocking implementation of `export_word_frequencies`. Holds at most
+/// about `cap` distinct words in memory; `on_progress` receives
+/// `(phase, files done, total files)`.
+fn export_word_frequencies_blocking(
+    root: &Path,
+    out: &Path,
+    options: FrequencyOptions,
+    cap: usize,
+    mut on_progress: impl FnMut(&str, usize, usize),
+) -> Result<WordFrequencyReport, HibiscusError> {
+    let (tree, limits_hit) = read_dir_limited(root, root, Limits::load(root).tree_depth, false);
+    let mut files = Vec::new();
+    collect_files(&tree, &mut files);
+
+    // A previous export inside the vault is not part of the corpus
+    if let Ok(out_rel) = out.strip_prefix(root) {
+        let out_rel = out_rel.to_string_lossy().replace('\\', "/");
+        files.retain(|rel| *rel != out_rel);
+    }
+
+    let mut runs_name = out.file_name().unwrap_or_default().to_os_string();
+    runs_name.push(".hibiscus-runs~");
+    let runs_dir = out.with_file_name(runs_name);
+    fs::create_dir_all(&runs_dir).map_err(|e| {
+        HibiscusError::Io(format!("Failed to create '{}': {}", runs_dir.display(), e))
+    })?;
+
+    let mut report = WordFrequencyReport { limits_hit, ..Default::default() };
+    let result = (|| {
+        let tokenizer = Tokenizer::new(options);
+        let mut counter = FrequencyCounter::new(&runs_dir, cap);
+        for (done, rel) in files.iter().enumerate() {
+            let path = root.join(rel);
+            if let Some(content) = read_text(&path) {
+                counter.add_document(tokenizer.count(&content, is_markdown_file(&path)))?;
+                report.documents += 1;
+            }
+            on_progress("counting", done + 1, files.len());
+        }
+
+        on_progress("ranking", files.len(), files.len());
+        let (stats, spilled_runs) = counter.finish()?;
+        report.spilled_runs = spilled_runs;
+
+        replace_file(out, |temp_path| {
+            let file = fs::File::create(temp_path).map_err(|e| {
+                HibiscusError::Io(format!("Failed to create '{}': {}", temp_path.display(), e))
+            })?;
+            let csv_error = |e: csv::Error| {
+                HibiscusError::Io(format!("Failed to write '{}': {}", temp_path.display(), e))
+            };
+
+            let mut writer = csv::Writer::from_writer(BufWriter::new(file));
+            writer.write_record(FREQUENCY_HEADER).map_err(csv_error)?;
+            for stat in stats {
+                let stat = stat?;
+                report.distinct_words += 1;
+                report.total_words += stat.count;
+                writer
+                    .write_record([stat.word, stat.count.to_string(), stat.documents.to_string()])
+                    .map_err(csv_error)?;
+            }
+
+            let file = writer
+                .into_inner()
+                .map_err(|e| HibiscusError::Io(format!("Failed to write '{}': {}", temp_path.display(), e)))?
+                .into_inner()
+                .map_err(|e| HibiscusError::Io(format!("Failed to write '{}': {}", temp_path.display(), e)))?;
+            file.sync_all()?;
+            Ok(())
+        })
+    })();
+    let _ = fs::remove_dir_all(&runs_dir);
+    result?;
+
+    Ok(report)
+}
+
+/// Streams one CSV row per file into `path` and syncs it to disk.
+fn write_stats_csv(root: &Path, files: &[String], path: &Path) -> Result<(), HibiscusError> {
+    let file = fs::File::create(path).map_err(|e| {
+        HibiscusError::Io(format!("Failed to create '{}': {}", path.display(), e))
+    })?;
+    let csv_error = |e: csv::Error| {
+        HibiscusError::Io(format!("Failed to write '{}': {}", path.display(), e))
+    };
+
+    let mut writer = csv::Writer::from_writer(BufWriter::new(file));
+    writer.write_record(CSV_HEADER).map_err(csv_error)?;
+    for rel in files {
+        writer.write_record(stats_row(root, rel)).map_err(csv_error)?;
+    }
+
+    let file = writer
+        .into_inner()
+        .map_err(|e| HibiscusError::Io(format!("Failed to write '{}': {}", path.display(), e)))?
+        .into_inner()
+        .map_err(|e| HibiscusError::Io(format!("Failed to write '{}': {}", path.display(), e)))?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// Columns of one file's row; unknown values are blank.
+fn stats_row(root: &Path, rel: &str) -> [String; 6] {
+    let path = root.join(rel);
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let metadata = fs::metadata(&path).ok();
+    let size = metadata.as_ref().map(|m| m.len().to_string()).unwrap_or_default();
+    let modified = metadata
+        .and_then(|m| m.modified().ok())
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis().to_string())
+        .unwrap_or_default();
+
+    let (words, links) = match read_text(&path) {
+        Some(content) => (
+            count_words(&content, is_markdown_file(&path)).to_string(),
+            markdown::extract_links(&content).len().to_string(),
+        ),
+        None => (String::new(), String::new()),
+    };
+
+    [rel.to_string(), extension, size, modified, words, links]
+}
+
+/// Reads a file as text, or `None` if it is binary or unreadable.
+fn read_text(path: &Path) -> Option<String> {
+    let mut file = fs::File::open(path).ok()?;
+    let mut sample = Vec::new();
+    (&mut file).take(BINARY_SNIFF_BYTES as u64).read_to_end(&mut sample).ok()?;
+    if looks_binary(&sample) {
+        return None;
+    }
+    file.read_to_end(&mut sample).ok()?;
+    String::from_utf8(sample).ok()
+}
+
+/// Flattens the tree into workspace-relative file paths (forward slashes).
+fn collect_files(nodes: &[Node], out: &mut Vec<String>) {
+    for node in nodes {
+        if let Some(path) = &node.path {
+            out.push(path.replace('\\', "/"));
+        }
+        if let Some(children) = &node.children {
+            collect_files(children, out);
+        }
+    }
+}
+
+fn is_markdown_file(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| markdown::is_markdown_extension(&ext.to_string_lossy()))
+        .unwrap_or(false)
+}
+
+/// Counts words, optionally ignoring frontmatter and markdown syntax.
+fn count_words(content: &str, strip_markdown: bool) -> usize {
+    if !strip_markdown {
+        return markdown::word_count(content);
+    }
+
+    let (_, body) = markdown::split_frontmatter(content);
+    markdown::word_count(&markdown::to_plain_text(body, PlainTextOptions::default()))
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    async fn delta(path: &Path, previous: usize) -> WordCountDelta {
+        word_count_delta(path.to_string_lossy().into(), previous, None)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_delta_positive_after_adding_words() {
+        let dir = tempdir().unwrap();
+        let note = dir.path().join("note.txt");
+
+        fs::write(&note, "one two three").unwrap();
+        let first = delta(&note, 0).await;
+        assert_eq!(first, WordCountDelta { current: 3, delta: 3 });
+
+        fs::write(&note, "one two three four five").unwrap();
+        let second = delta(&note, first.current).await;
+        assert_eq!(second, WordCountDelta { current: 5, delta: 2 });
+    }
+
+    #[tokio::test]
+    async fn test_delta_negative_after_deleting_words() {
+        let dir = tempdir().unwrap();
+        let note = dir.path().join("note.txt");
+        fs::write(&note, "only two").unwrap();
+
+        let result = delta(&note, 5).await;
+        assert_eq!(result, WordCountDelta { current: 2, delta: -3 });
+    }
+
+    #[test]
+    fn test_stats_csv_has_header_and_row_per_file() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("Notes")).unwrap();
+        fs::create_dir_all(root.join(".hibiscus")).unwrap();
+        fs::write(root.join(".hibiscus").join("workspace.json"), "{}").unwrap();
+        fs::write(root.join("Notes").join("a.md"), "# Title\nSee [b](b.md) and [[c]]\n").unwrap();
+        fs::write(root.join("todo.txt"), "one two three").unwrap();
+        fs::write(root.join("logo.png"), [0x89, b'P', b'N', b'G', 0, 1, 2]).unwrap();
+
+        let out = root.join("stats.csv");
+        fs::write(&out, "stale report").unwrap();
+        let report = export_stats_csv_blocking(root, &out).unwrap();
+        assert_eq!(report.rows, 3);
+
+        let csv = fs::read_to_string(&out).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "path,extension,size_bytes,modified_ms,word_count,link_count");
+        assert_eq!(lines.len(), 4);
+
+        let row = |path: &str| {
+            lines[1..]
+                .iter()
+                .map(|l| l.split(',').collect::<Vec<_>>())
+                .find(|cols| cols[0] == path)
+                .unwrap()
+        };
+        assert_eq!(row("Notes/a.md")[4..], ["5", "2"]);
+        assert_eq!(row("todo.txt")[1..3], ["txt", "13"]);
+        assert_eq!(row("logo.png")[4..], ["", ""]);
+        assert!(!row("logo.png")[3].is_empty());
+    }
+
+    #[test]
+    fn test_word_frequencies_spill_to_disk_without_changing_the_table() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("Notes")).unwrap();
+        let note = "---\ntags: [x]\n---\n# Cats\nThe cat sat. The **cat** ran.\n";
+        fs::write(root.join("Notes").join("a.md"), note).unwrap();
+        fs::write(root.join("Notes").join("b.md"), "A dog and a cat and the bird.\n").unwrap();
+        fs::write(root.join("todo.txt"), "Buy bird seed, feed the bird.\n").unwrap();
+        fs::write(root.join("logo.png"), [0x89, b'P', b'N', b'G', 0, 1, 2]).unwrap();
+
+        let out_dir = tempdir().unwrap();
+        let export = |name: &str, cap: usize| {
+            let out = out_dir.path().join(name);
+            let mut phases = Vec::new();
+            let report = export_word_frequencies_blocking(root, &out, FrequencyOptions::default(), cap, |phase, _, _| {
+                phases.push(phase.to_string())
+            })
+            .unwrap();
+            assert_eq!(phases.last().map(String::as_str), Some("ranking"));
+            (report, fs::read_to_string(&out).unwrap())
+        };
+
+        let (in_memory, expected) = export("words.csv", usize::MAX);
+        let (spilled, actual) = export("words-spilled.csv", 3);
+        assert_eq!(in_memory.spilled_runs, 0);
+        assert!(spilled.spilled_runs > 0);
+        assert_eq!(actual, expected);
+        assert_eq!(in_memory.documents, 3);
+        assert_eq!(spilled.distinct_words, in_memory.distinct_words);
+
+        let lines: Vec<&str> = expected.lines().collect();
+        assert_eq!(lines[..4], ["word,count,documents", "the,4,3", "bird,3,2", "cat,3,2"]);
+        assert!(!expected.contains("tags"));
+        assert!(!out_dir.path().join("words.csv.hibiscus-runs~").exists());
+    }
+
+    #[tokio::test]
+    async fn test_markdown_syntax_is_optional() {
+        let dir = tempdir().unwrap();
+        let note = dir.path().join("note.md");
+        fs::write(&note, "---\ntitle: x\n---\n# Heading\n- item").unwrap();
+
+        assert_eq!(delta(&note, 0).await.current, 2);
+
+        let raw = word_count_delta(note.to_string_lossy().into(), 0, Some(false))
+            .await
+            .unwrap();
+        assert_eq!(raw.current, 8);
+    }
+}
@@ -0,0 +1,66 @@
+// ============================================================================
+// NOTE TIDYING
+// ============================================================================
+//
+// The explicit "clean up note" action: trailing whitespace, runs of blank
+// lines and the final line break (`crate::format::tidy`). Fenced code is
+// left alone. The note is rewritten with the same atomic save as
+// `write_text_file`, and only when something changed.
+// ============================================================================
+
+use std::path::PathBuf;
+
+use crate::error::HibiscusError;
+use crate::format::{tidy, TidyOptions};
+use super::files::save_atomically;
+use super::path::{scope_to_workspace, validate_path};
+
+/// Tidies a note on disk.
+///
+/// # Arguments
+/// * `path` - Absolute path to the note
+/// * `options` - Steps to apply; all of them by default
+///
+/// # Returns
+/// * `Ok(usize)` - Lines changed; 0 if the note was already tidy and was
+///   not written
+/// * `Err(HibiscusError)` - If the note cannot be read or written
+#[tauri::command]
+pub async fn tidy_note(path: String, options: Option<TidyOptions>) -> Result<usize, HibiscusError> {
+    let path = PathBuf::from(&path);
+    validate_path(&path)?;
+    let path = scope_to_workspace(&path)?;
+
+    let content = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| HibiscusError::Io(format!("Failed to read file '{}': {}", path.display(), e)))?;
+
+    let tidied = tidy(&content, options.unwrap_or_default());
+    if tidied.content != content {
+        save_atomically(&path, tidied.content.as_bytes()).await?;
+    }
+    Ok(tidied.lines_changed)
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_tidy_note_rewrites_only_untidy_notes() {
+        let dir = tempdir().unwrap();
+        let note = dir.path().join("note.md");
+        fs::write(&note, "Text  \n\n\n~~~\n\n\n~~~\nEnd").unwrap();
+        let path = note.to_string_lossy().to_string();
+
+        assert_eq!(tidy_note(path.clone(), None).await.unwrap(), 3);
+        assert_eq!(fs::read_to_string(&note).unwrap(), "Text\n\n~~~\n\n\n~~~\nEnd\n");
+        assert_eq!(tidy_note(path, None).await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,368 @@
+// ============================================================================
+// FOCUS TIME TRACKING
+// ============================================================================
+//
+// Tauri entry points for `crate::time_tracking`. The frontend reports tab
+// focus changes; the stats dashboard queries per-note and per-day totals.
+// Open durations per file feed the writing-habits dashboard.
+// Recording is skipped while `settings.time_tracking_disabled` is set.
+// ============================================================================
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::State;
+
+use crate::capabilities::{CapabilityStore, CLEAR_TIME_TRACKING};
+use crate::error::HibiscusError;
+use crate::ids::to_canonical_id;
+use crate::time::Timestamp;
+use crate::time_tracking::{self, DailyTotals, DateRange, FocusEvent, FocusKind, MAX_SESSION_MS};
+use crate::workspace::WorkspaceSettings;
+use super::path::validate_path;
+
+/// Serializes appends, compactions and purges of the time-tracking log and
+/// the read-modify-write of `activity.json`.
+static TIME_TRACKING_LOCK: LazyLock<tokio::sync::Mutex<()>> =
+    LazyLock::new(|| tokio::sync::Mutex::new(()));
+
+/// Total time spent on one note.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NoteTime {
+    pub path: String,
+    pub seconds: u64,
+}
+
+/// Total time spent on notes during one local day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DayTime {
+    /// `YYYY-MM-DD`
+    pub date: String,
+    pub seconds: u64,
+}
+
+/// Accumulated open time of one file, as returned by `get_activity`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileActivityEntry {
+    pub node_id: String,
+    pub seconds: u64,
+    pub opens: u64,
+    pub last_opened: Timestamp,
+}
+
+/// Records that a note gained or lost focus.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+/// * `path` - Note path (absolute or relative to the root)
+/// * `event` - `"focus"` or `"blur"`
+/// * `timestamp` - Milliseconds since the Unix epoch
+/// * `utc_offset_minutes` - Local UTC offset, used to assign local days
+///
+/// # Returns
+/// * `Ok(())` - Recorded, or skipped because tracking is disabled
+/// * `Err(HibiscusError)` - If the log could not be written
+#[tauri::command]
+pub async fn record_focus_event(
+    root: String,
+    path: String,
+    event: FocusKind,
+    timestamp: i64,
+    utc_offset_minutes: Option<i32>,
+) -> Result<(), HibiscusError> {
+    let root = PathBuf::from(&root);
+
+    // Validate paths
+    validate_path(&root)?;
+    validate_path(Path::new(&path))?;
+
+    let event = FocusEvent {
+        // Canonical ids survive moving the vault and match across platforms
+        path: to_canonical_id(Path::new(&path), &root),
+        event,
+        timestamp,
+        offset_minutes: utc_offset_minutes.unwrap_or(0),
+    };
+
+    let _guard = TIME_TRACKING_LOCK.lock().await;
+    tokio::task::spawn_blocking(move || {
+        if WorkspaceSettings::load(&root).time_tracking_disabled {
+            return Ok(());
+        }
+        time_tracking::append_event(&root, &event)
+    })
+    .await
+    .map_err(|e| HibiscusError::Io(format!("Time tracking task failed: {}", e)))?
+}
+
+/// Returns time spent per note within a date range, longest first.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+/// * `range` - Inclusive `from`/`to` dates (`YYYY-MM-DD`); omitted = all time
+///
+/// # Returns
+/// * `Ok(Vec<NoteTime>)` - Notes with recorded time
+/// * `Err(HibiscusError)` - If the log could not be read
+#[tauri::command]
+pub async fn get_time_by_note(
+    root: String,
+    range: Option<DateRange>,
+) -> Result<Vec<NoteTime>, HibiscusError> {
+    let totals = load_totals(root, range.unwrap_or_default()).await?;
+
+    let mut by_note: BTreeMap<String, i64> = BTreeMap::new();
+    for notes in totals.into_values() {
+        for (path, ms) in notes {
+            *by_note.entry(path).or_default() += ms;
+        }
+    }
+
+    let mut notes: Vec<NoteTime> = by_note
+        .into_iter()
+        .map(|(path, ms)| NoteTime { path, seconds: to_seconds(ms) })
+        .filter(|note| note.seconds > 0)
+        .collect();
+    notes.sort_by(|a, b| b.seconds.cmp(&a.seconds).then_with(|| a.path.cmp(&b.path)));
+    Ok(notes)
+}
+
+/// Returns time spent per local day within a date range, oldest first.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+/// * `range` - Inclusive `from`/`to` dates (`YYYY-MM-DD`); omitted = all time
+///
+/// # Returns
+/// * `Ok(Vec<DayTime>)` - Days with recorded time
+/// * `Err(HibiscusError)` - If the log could not be read
+#[tauri::command]
+pub async fn get_time_by_day(
+    root: String,
+    range: Option<DateRange>,
+) -> Result<Vec<DayTime>, HibiscusError> {
+    let totals = load_totals(root, range.unwrap_or_default()).await?;
+
+    Ok(totals
+        .into_iter()
+        .map(|(date, notes)| DayTime {
+            date,
+            seconds: to_seconds(notes.values().sum()),
+        })
+        .filter(|day| day.seconds > 0)
+        .collect())
+}
+
+/// Adds one open of a file to its accumulated open time.
+///
+/// # Arguments
+/// * `path` - Workspace root directory
+/// * `node_id` - Id of the file's node
+/// * `seconds` - How long the file was open, capped at MAX_SESSION_MS
+///
+/// # Returns
+/// * `Ok(())` - Recorded, or skipped because tracking is disabled
+/// * `Err(HibiscusError)` - If `activity.json` could not be written
+#[tauri::command]
+pub async fn record_open_duration(
+    path: String,
+    node_id: String,
+    seconds: u64,
+) -> Result<(), HibiscusError> {
+    let root = PathBuf::from(&path);
+
+    // Validate path
+    validate_path(&root)?;
+
+    let seconds = seconds.min(MAX_SESSION_MS as u64 / 1000);
+    let _guard = TIME_TRACKING_LOCK.lock().await;
+    tokio::task::spawn_blocking(move || {
+        if WorkspaceSettings::load(&root).time_tracking_disabled {
+            return Ok(());
+        }
+        let mut log = time_tracking::read_activity(&root)?;
+        log.record(&node_id, seconds, Timestamp::now());
+        time_tracking::write_activity(&root, &log)
+    })
+    .await
+    .map_err(|e| HibiscusError::Io(format!("Time tracking task failed: {}", e)))?
+}
+
+/// Returns the accumulated open time and open count per file, longest first.
+///
+/// # Arguments
+/// * `path` - Workspace root directory
+///
+/// # Returns
+/// * `Ok(Vec<FileActivityEntry>)` - Files with a recorded open
+/// * `Err(HibiscusError)` - If `activity.json` could not be read
+#[tauri::command]
+pub async fn get_activity(path: String) -> Result<Vec<FileActivityEntry>, HibiscusError> {
+    let root = PathBuf::from(&path);
+
+    // Validate path
+    validate_path(&root)?;
+
+    let _guard = TIME_TRACKING_LOCK.lock().await;
+    let log = tokio::task::spawn_blocking(move || time_tracking::read_activity(&root))
+        .await
+        .map_err(|e| HibiscusError::Io(format!("Time tracking task failed: {}", e)))??;
+
+    let mut files: Vec<FileActivityEntry> = log
+        .files
+        .into_iter()
+        .map(|(node_id, activity)| FileActivityEntry {
+            node_id,
+            seconds: activity.seconds,
+            opens: activity.opens,
+            last_opened: activity.last_opened,
+        })
+        .collect();
+    files.sort_by(|a, b| b.seconds.cmp(&a.seconds).then_with(|| a.node_id.cmp(&b.node_id)));
+    Ok(files)
+}
+
+/// Deletes all stored time-tracking data for a workspace.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+/// * `capability` - Token from `request_capability("clear_time_tracking")`,
+///   required when the workspace lists that kind in `gated_capabilities`
+///
+/// # Returns
+/// * `Ok(())` - Data removed (or there was none)
+/// * `Err(HibiscusError::CapabilityRequired)` - If gated and the token is
+///   missing or invalid
+/// * `Err(HibiscusError)` - If the log could not be deleted
+#[tauri::command]
+pub async fn clear_time_tracking(
+    root: String,
+    capability: Option<String>,
+    capabilities: State<'_, CapabilityStore>,
+) -> Result<(), HibiscusError> {
+    let root = PathBuf::from(&root);
+
+    // Validate path
+    validate_path(&root)?;
+
+    let settings = WorkspaceSettings::load(&root);
+    capabilities.require(CLEAR_TIME_TRACKING, capability.as_deref(), &settings)?;
+
+    purge_time_tracking(&root).await
+}
+
+async fn purge_time_tracking(root: &Path) -> Result<(), HibiscusError> {
+    let _guard = TIME_TRACKING_LOCK.lock().await;
+    match tokio::fs::remove_file(time_tracking::log_path(root)).await {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(HibiscusError::Io(format!(
+            "Failed to clear time tracking: {}",
+            e
+        ))),
+    }
+}
+
+async fn load_totals(root: String, range: DateRange) -> Result<DailyTotals, HibiscusError> {
+    let root = PathBuf::from(&root);
+
+    // Validate path
+    validate_path(&root)?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0);
+
+    let _guard = TIME_TRACKING_LOCK.lock().await;
+    tokio::task::spawn_blocking(move || {
+        let records = time_tracking::read_records(&root)?;
+        Ok(time_tracking::daily_totals(&records, &range, now))
+    })
+    .await
+    .map_err(|e| HibiscusError::Io(format!("Time tracking task failed: {}", e)))?
+}
+
+fn to_seconds(ms: i64) -> u64 {
+    (ms.max(0) / 1000) as u64
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_record_query_and_clear() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_string_lossy().to_string();
+        let note = dir.path().join("notes").join("a.md").to_string_lossy().to_string();
+
+        // 2024-01-01 10:00 UTC, ten minutes of focus
+        let start = 1_704_103_200_000;
+        record_focus_event(root.clone(), note.clone(), FocusKind::Focus, start, None)
+            .await
+            .unwrap();
+        record_focus_event(root.clone(), note, FocusKind::Blur, start + 600_000, None)
+            .await
+            .unwrap();
+
+        let by_note = get_time_by_note(root.clone(), None).await.unwrap();
+        assert_eq!(by_note, vec![NoteTime { path: "notes/a.md".into(), seconds: 600 }]);
+
+        let range = DateRange { from: Some("2024-01-01".into()), to: Some("2024-01-01".into()) };
+        let by_day = get_time_by_day(root.clone(), Some(range)).await.unwrap();
+        assert_eq!(by_day, vec![DayTime { date: "2024-01-01".into(), seconds: 600 }]);
+
+        purge_time_tracking(Path::new(&root)).await.unwrap();
+        assert!(get_time_by_note(root, None).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_disabled_setting_skips_recording() {
+        let dir = tempdir().unwrap();
+        let hibiscus = dir.path().join(".hibiscus");
+        std::fs::create_dir_all(&hibiscus).unwrap();
+        std::fs::write(
+            hibiscus.join("workspace.json"),
+            r#"{ "settings": { "time_tracking_disabled": true } }"#,
+        )
+        .unwrap();
+
+        let root = dir.path().to_string_lossy().to_string();
+        record_focus_event(root, "a.md".into(), FocusKind::Focus, 0, None)
+            .await
+            .unwrap();
+        assert!(!time_tracking::log_path(dir.path()).exists());
+    }
+
+    #[tokio::test]
+    async fn test_open_durations_accumulate_and_persist() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_string_lossy().to_string();
+
+        record_open_duration(root.clone(), "notes/a.md".into(), 90).await.unwrap();
+        record_open_duration(root.clone(), "notes/a.md".into(), 30).await.unwrap();
+        record_open_duration(root.clone(), "b.md".into(), 45).await.unwrap();
+
+        let activity = get_activity(root.clone()).await.unwrap();
+        let totals: Vec<(&str, u64, u64)> = activity
+            .iter()
+            .map(|entry| (entry.node_id.as_str(), entry.seconds, entry.opens))
+            .collect();
+        assert_eq!(totals, [("notes/a.md", 120, 2), ("b.md", 45, 1)]);
+
+        // A fresh load of the file sees the same totals
+        let log = time_tracking::read_activity(dir.path()).unwrap();
+        assert_eq!(log.files["notes/a.md"].seconds, 120);
+        assert_eq!(log.files["b.md"].opens, 1);
+        record_open_duration(root.clone(), "b.md".into(), 15).await.unwrap();
+        assert_eq!(get_activity(root).await.unwrap()[1].seconds, 60);
+    }
+}
@@ -2,31 +2,512 @@
 // TREE OPERATIONS
 // ============================================================================
 
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use futures::future::{BoxFuture, FutureExt, Shared};
+use tauri::State;
 
 use crate::error::HibiscusError;
-use crate::tree::read_dir_recursive;
-use crate::workspace::Node;
-use super::path::validate_path;
+use crate::ignore::{self, IgnoreExplanation, IgnoreRules};
+use crate::limits::{LimitHit, Limits, TREE_NODES};
+use crate::references::write_json_atomic;
+use crate::storage::storage_for;
+use crate::tree::{
+    self, apply_decorations, diff_entries, read_dir_limited, read_dir_recursive, read_dir_with, SortMode, TreeDiff, TreeEntry,
+    TreeOptions,
+};
+use crate::variants::{group_variants, VariantConfig};
+use crate::workspace::{Node, WorkspaceSettings};
+use super::decorations::load_decorations;
+use super::path::{find_workspace_root, validate_path};
+use super::workspace::WORKSPACE_LOCK;
 
-/// Maximum depth for recursive directory traversal
-const MAX_TREE_DEPTH: usize = 20;
+/// Default path length threshold for `find_long_paths`.
+/// Windows' legacy MAX_PATH is 260 characters; elsewhere PATH_MAX is 4096.
+#[cfg(target_os = "windows")]
+const DEFAULT_LONG_PATH_THRESHOLD: usize = 260;
+#[cfg(not(target_os = "windows"))]
+const DEFAULT_LONG_PATH_THRESHOLD: usize = 4096;
 
 /// Builds the file tree for a workspace directory.
 ///
 /// # Arguments
 /// * `root` - The root directory to build the tree from
+/// * `include_hidden` - Also list dotfiles and dot-folders (default false)
+/// * `sort` - Order within each folder's folders and files (default
+///   `name_asc`)
+/// * `max_nodes` - Stop after this many nodes in total (default: no cap);
+///   `build_tree_report` tells whether the cap was reached
 ///
 /// # Returns
 /// * `Ok(Vec<Node>)` - The file tree as a list of nodes
 /// * `Err(HibiscusError)` - If tree building fails
 ///
 /// # Features
-/// - Respects depth limits to prevent infinite recursion
-/// - Sorts folders first, then files, both alphabetically
-/// - Ignores hidden files and .hibiscus folder
+/// - Respects the workspace's depth limit to prevent infinite recursion
+/// - Sorts folders first, then files, each group by `sort`
+/// - Ignores hidden files unless `include_hidden`, and always the
+///   .hibiscus folder
+/// - Merges stored node decorations (icon, color) into node meta
+/// - With `settings.note_variants`, groups language variants of a note
+///   into one node (see `crate::variants`)
+/// - Concurrent calls with the same root and options share one walk (see
+///   `TreeBuilds`)
+#[tauri::command]
+pub async fn build_tree(
+    root: String,
+    include_hidden: Option<bool>,
+    sort: Option<SortMode>,
+    max_nodes: Option<usize>,
+    builds: State<'_, TreeBuilds>,
+) -> Result<Vec<Node>, HibiscusError> {
+    build_tree_report(root, include_hidden, sort, max_nodes, builds)
+        .await
+        .map(|report| report.nodes)
+}
+
+/// A built tree and the folders the depth limit left unread.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TreeReport {
+    pub nodes: Vec<Node>,
+    /// Empty unless the tree was cut short
+    pub limits_hit: Vec<LimitHit>,
+    /// Whether `max_nodes` stopped the walk
+    pub truncated: bool,
+    /// Number of the walk that produced this tree; later walks have higher
+    /// numbers, so a response with a lower one than the last applied is
+    /// stale (0 outside `build_tree_report`)
+    pub generation: u64,
+}
+
+/// The root and options of a `build_tree` call; calls with equal keys
+/// share a walk.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TreeBuildKey {
+    root: String,
+    include_hidden: bool,
+    sort: SortMode,
+    max_nodes: Option<usize>,
+}
+
+type TreeBuild = Shared<BoxFuture<'static, Result<TreeReport, HibiscusError>>>;
+
+/// In-flight tree walks, registered as Tauri managed state.
+///
+/// The frontend can ask for the same tree twice in quick succession (mount
+/// and a filesystem change racing). The second call awaits the walk the
+/// first one started instead of reading the disk again, and both get the
+/// same result, error included. A walk is forgotten as soon as it
+/// finishes, so later calls always read the disk afresh.
+#[derive(Default)]
+pub struct TreeBuilds {
+    in_flight: Arc<Mutex<HashMap<TreeBuildKey, TreeBuild>>>,
+    /// Generation of the most recently started walk
+    generation: AtomicU64,
+}
+
+impl TreeBuilds {
+    /// Runs `walk` on a blocking thread, or joins the walk already running
+    /// for `key`.
+    pub async fn run<F>(&self, key: TreeBuildKey, walk: F) -> Result<TreeReport, HibiscusError>
+    where
+        F: FnOnce() -> Result<TreeReport, HibiscusError> + Send + 'static,
+    {
+        let build = {
+            let mut in_flight = self.in_flight.lock().unwrap_or_else(PoisonError::into_inner);
+            match in_flight.get(&key) {
+                Some(build) => build.clone(),
+                None => {
+                    let generation = self.generation.fetch_add(1, Ordering::Relaxed) + 1;
+                    let registry = Arc::clone(&self.in_flight);
+                    let finished_key = key.clone();
+                    let build = async move {
+                        let result = tokio::task::spawn_blocking(walk)
+                            .await
+                            .map_err(|e| HibiscusError::Io(format!("Tree build task failed: {}", e)))
+                            .and_then(|result| result);
+                        // Still registered: no other walk for the key can
+                        // have started in the meantime
+                        registry.lock().unwrap_or_else(PoisonError::into_inner).remove(&finished_key);
+                        result.map(|report| TreeReport { generation, ..report })
+                    }
+                    .boxed()
+                    .shared();
+                    in_flight.insert(key, build.clone());
+                    build
+                }
+            }
+        };
+        build.await
+    }
+}
+
+/// `build_tree`, plus where the depth limit or node cap left folders
+/// unread.
+///
+/// # Arguments
+/// * `root` - The root directory to build the tree from
+/// * `include_hidden` - Also list dotfiles and dot-folders (default false)
+/// * `sort` - Order within each folder's folders and files (default
+///   `name_asc`)
+/// * `max_nodes` - Stop after this many nodes in total (default: no cap)
+///
+/// # Returns
+/// * `Ok(TreeReport)` - The tree and any limit hits
+/// * `Err(HibiscusError)` - If tree building fails
+#[tauri::command]
+pub async fn build_tree_report(
+    root: String,
+    include_hidden: Option<bool>,
+    sort: Option<SortMode>,
+    max_nodes: Option<usize>,
+    builds: State<'_, TreeBuilds>,
+) -> Result<TreeReport, HibiscusError> {
+    let key = TreeBuildKey {
+        root: root.clone(),
+        include_hidden: include_hidden.unwrap_or(false),
+        sort: sort.unwrap_or_default(),
+        max_nodes,
+    };
+    builds
+        .run(key, move || read_tree_report(root, include_hidden, sort, max_nodes))
+        .await
+}
+
+/// The walk behind `build_tree_report`, run directly (generation 0).
+pub(crate) fn read_tree_report(
+    root: String,
+    include_hidden: Option<bool>,
+    sort: Option<SortMode>,
+    max_nodes: Option<usize>,
+) -> Result<TreeReport, HibiscusError> {
+    let root = PathBuf::from(&root);
+
+    // Validate path
+    validate_path(&root)?;
+
+    if !is_storage_dir(&root) {
+        return Err(HibiscusError::InvalidPathType {
+            path: root.to_string_lossy().into(),
+            expected: "directory".into(),
+            actual: "file".into(),
+        });
+    }
+
+    let options = TreeOptions { sort: sort.unwrap_or_default(), max_nodes };
+    let (mut nodes, limits_hit) = read_dir_with(
+        &root,
+        &root,
+        Limits::load(&root).tree_depth,
+        include_hidden.unwrap_or(false),
+        options,
+    );
+    let truncated = limits_hit.iter().any(|hit| hit.limit == TREE_NODES);
+    apply_decorations(&mut nodes, &load_decorations(&root));
+    group_note_variants(&mut nodes, &root);
+
+    Ok(TreeReport { nodes, limits_hit, truncated, generation: 0 })
+}
+
+/// Whether `path` is a folder in the storage holding it.
+fn is_storage_dir(path: &Path) -> bool {
+    storage_for(path).metadata(path).is_ok_and(|metadata| metadata.is_dir)
+}
+
+/// Groups language variants into one node if the workspace asks for it.
+fn group_note_variants(nodes: &mut Vec<Node>, root: &Path) {
+    let settings = WorkspaceSettings::load(root);
+    if settings.note_variants {
+        group_variants(nodes, &VariantConfig::from_settings(&settings));
+    }
+}
+
+/// Lists the immediate children of one folder.
+///
+/// Lets the explorer expand folders on demand instead of reading the whole
+/// workspace up front; coexists with `build_tree`. Folder nodes come
+/// without `children` and with `meta.has_children` set.
+///
+/// # Arguments
+/// * `path` - The folder to list; the workspace root or a folder inside it
+/// * `include_hidden` - Also list dotfiles and dot-folders (default false)
+///
+/// # Returns
+/// * `Ok(Vec<Node>)` - The folder's children, with the same ids as in
+///   `build_tree`
+/// * `Err(HibiscusError)` - If the path is invalid or not a directory
+#[tauri::command]
+pub fn read_dir_shallow(path: String, include_hidden: Option<bool>) -> Result<Vec<Node>, HibiscusError> {
+    let dir = PathBuf::from(&path);
+
+    // Validate path
+    validate_path(&dir)?;
+
+    if !is_storage_dir(&dir) {
+        return Err(HibiscusError::InvalidPathType {
+            path,
+            expected: "directory".into(),
+            actual: "file".into(),
+        });
+    }
+
+    // Ids, ignore patterns and decorations belong to the workspace root
+    let root = find_workspace_root(&dir).unwrap_or_else(|| dir.clone());
+    let mut nodes = tree::read_dir_shallow(&dir, &root, include_hidden.unwrap_or(false));
+    apply_decorations(&mut nodes, &load_decorations(&root));
+    group_note_variants(&mut nodes, &root);
+
+    Ok(nodes)
+}
+
+/// Explains why a path does or does not appear in the tree.
+///
+/// Checks the path and each folder above it against the built-in ignore
+/// list, the hidden-file rule and the workspace's `.hibiscusignore`, in the
+/// order the tree applies them. A support aid for "where's my file?".
+///
+/// # Arguments
+/// * `root` - The workspace root directory
+/// * `path` - Path to explain (absolute or relative to the root)
+/// * `include_hidden` - Explain for a tree built with `include_hidden`
+///
+/// # Returns
+/// * `Ok(IgnoreExplanation)` - The rule that excluded the path, or
+///   "not ignored"
+/// * `Err(HibiscusError)` - If a path is invalid or outside the root
+#[tauri::command]
+pub fn explain_ignored(
+    root: String,
+    path: String,
+    include_hidden: Option<bool>,
+) -> Result<IgnoreExplanation, HibiscusError> {
+    let root = PathBuf::from(&root);
+    let path = root.join(&path);
+
+    // Validate paths
+    validate_path(&root)?;
+    validate_path(&path)?;
+
+    let rel = path.strip_prefix(&root).map_err(|_| {
+        HibiscusError::PathValidation(format!("'{}' is outside the workspace", path.display()))
+    })?;
+    let rel = rel.to_string_lossy().replace('\\', "/");
+
+    Ok(ignore::explain(
+        &ignore::shared_rules(&root),
+        &rel,
+        path.is_dir(),
+        include_hidden.unwrap_or(false),
+    ))
+}
+
+/// Largest number of paths listed in each sample of an `IgnorePreview`.
+const PREVIEW_SAMPLE_LIMIT: usize = 20;
+
+/// What adding (or removing) an ignore pattern would change in the tree.
+#[derive(Debug, serde::Serialize)]
+pub struct IgnorePreview {
+    pub pattern: String,
+    /// Shown paths the pattern would hide
+    pub hidden_count: usize,
+    /// The first `PREVIEW_SAMPLE_LIMIT` of them
+    pub hidden_sample: Vec<String>,
+    /// Hidden paths that would be shown again if the pattern were removed
+    /// from the setting; zero unless it is in the setting
+    pub revealed_count: usize,
+    /// The first `PREVIEW_SAMPLE_LIMIT` of them
+    pub revealed_sample: Vec<String>,
+}
+
+/// Shows what an ignore pattern would hide, for instant preview in the
+/// settings UI.
+///
+/// Runs against the cached tree of the last `build_tree` rather than
+/// walking the disk; the tree is read once if there is none yet. Folders
+/// that are already ignored were not read, so only the folders themselves
+/// can be revealed.
+///
+/// # Arguments
+/// * `root` - The workspace root directory
+/// * `pattern` - Candidate pattern, in `.hibiscusignore` syntax
+///
+/// # Returns
+/// * `Ok(IgnorePreview)` - Paths newly hidden, and those revealed if the
+///   pattern is already in the setting
+/// * `Err(HibiscusError::InvalidPattern)` - If the pattern does not compile
+#[tauri::command]
+pub fn preview_ignore_pattern(root: String, pattern: String) -> Result<IgnorePreview, HibiscusError> {
+    let root = PathBuf::from(&root);
+
+    // Validate path and pattern
+    validate_path(&root)?;
+    ignore::check_pattern(&pattern)?;
+
+    let cached = tree::cached_tree(&root).unwrap_or_else(|| {
+        read_dir_limited(&root, &root, Limits::load(&root).tree_depth, false);
+        tree::cached_tree(&root).unwrap_or_default()
+    });
+
+    let ignore_file = std::fs::read_to_string(root.join(ignore::IGNORE_FILE)).unwrap_or_default();
+    let mut patterns = WorkspaceSettings::load(&root).ignore_patterns;
+    let pattern = pattern.trim().to_string();
+
+    let mut added = patterns.clone();
+    added.push(pattern.clone());
+    let added = IgnoreRules::compile(&ignore_file, &added);
+    let hidden: Vec<&TreeEntry> = cached
+        .visible
+        .iter()
+        .filter(|entry| added.is_path_ignored(&entry.path, entry.is_dir))
+        .collect();
+
+    let revealed: Vec<&TreeEntry> = if patterns.contains(&pattern) {
+        patterns.retain(|p| *p != pattern);
+        let removed = IgnoreRules::compile(&ignore_file, &patterns);
+        cached
+            .ignored
+            .iter()
+            .filter(|entry| !removed.is_path_ignored(&entry.path, entry.is_dir))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let sample = |entries: &[&TreeEntry]| {
+        let mut paths: Vec<String> = entries.iter().map(|entry| entry.path.clone()).collect();
+        paths.sort();
+        paths.truncate(PREVIEW_SAMPLE_LIMIT);
+        paths
+    };
+
+    Ok(IgnorePreview {
+        pattern,
+        hidden_count: hidden.len(),
+        hidden_sample: sample(&hidden),
+        revealed_count: revealed.len(),
+        revealed_sample: sample(&revealed),
+    })
+}
+
+/// The tree after a change, and what changed since the cached one.
+#[derive(Debug, serde::Serialize)]
+pub struct TreeUpdate {
+    pub nodes: Vec<Node>,
+    pub diff: TreeDiff,
+}
+
+/// Replaces the workspace's `ignore_patterns` setting and applies it
+/// right away.
+///
+/// Recompiles the ignore rules shared with the file watcher, which filters
+/// its next events with them without restarting, and re-reads the tree.
+///
+/// # Arguments
+/// * `root` - The workspace root directory
+/// * `patterns` - The full list of patterns, in `.hibiscusignore` syntax
+///
+/// # Returns
+/// * `Ok(TreeUpdate)` - The new tree and the ids added to or removed from
+///   the cached one
+/// * `Err(HibiscusError::InvalidPattern)` - For the first pattern that does
+///   not compile; nothing is saved
+/// * `Err(HibiscusError)` - If workspace.json is missing or unwritable
 #[tauri::command]
-pub fn build_tree(root: String) -> Result<Vec<Node>, HibiscusError> {
+pub async fn apply_ignore_patterns(root: String, patterns: Vec<String>) -> Result<TreeUpdate, HibiscusError> {
+    let root = PathBuf::from(&root);
+
+    // Validate path and patterns
+    validate_path(&root)?;
+    for pattern in &patterns {
+        ignore::check_pattern(pattern)?;
+    }
+    let patterns: Vec<String> = patterns.iter().map(|p| p.trim().to_string()).collect();
+
+    let path = root.join(".hibiscus").join("workspace.json");
+    if !path.is_file() {
+        return Err(HibiscusError::FileNotFound(
+            "workspace.json not found".into(),
+        ));
+    }
+
+    let _guard = WORKSPACE_LOCK.lock().await;
+
+    tokio::task::spawn_blocking(move || {
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| HibiscusError::Io(format!("Failed to read workspace.json: {}", e)))?;
+        let mut doc: serde_json::Value = serde_json::from_str(&content)
+            .map_err(|e| HibiscusError::Workspace(format!("Invalid workspace JSON: {}", e)))?;
+
+        let Some(workspace) = doc.as_object_mut() else {
+            return Err(HibiscusError::Workspace(
+                "workspace.json is not an object".into(),
+            ));
+        };
+        let settings = workspace
+            .entry("settings")
+            .or_insert_with(|| serde_json::Value::Object(Default::default()));
+        if !settings.is_object() {
+            *settings = serde_json::Value::Object(Default::default());
+        }
+        let settings = settings.as_object_mut().expect("settings is an object");
+        if patterns.is_empty() {
+            settings.remove("ignore_patterns");
+        } else {
+            settings.insert("ignore_patterns".into(), patterns.into());
+        }
+        write_json_atomic(&path, &doc)?;
+
+        // The watcher picks the new rules up with its next event
+        ignore::reload_shared_rules(&root);
+
+        let old = tree::cached_tree(&root).unwrap_or_default();
+        let (mut nodes, _) = read_dir_limited(&root, &root, Limits::load(&root).tree_depth, false);
+        let new = tree::cached_tree(&root).unwrap_or_default();
+        apply_decorations(&mut nodes, &load_decorations(&root));
+
+        Ok(TreeUpdate {
+            nodes,
+            diff: diff_entries(&old.visible, &new.visible),
+        })
+    })
+    .await
+    .map_err(|e| HibiscusError::Workspace(format!("Ignore pattern task failed: {}", e)))?
+}
+
+/// A file whose absolute path is longer than the requested threshold.
+#[derive(Debug, serde::Serialize)]
+pub struct LongPath {
+    /// Absolute path of the file
+    pub path: String,
+    /// Path length in characters
+    pub length: usize,
+}
+
+/// Reports files whose absolute path length exceeds `threshold`.
+///
+/// Deeply nested notes can silently break on platforms with path limits
+/// (notably Windows' 260-character MAX_PATH). This lets users find and
+/// shorten them before operations start failing.
+///
+/// # Arguments
+/// * `root` - The workspace root directory
+/// * `threshold` - Maximum acceptable length (defaults to the platform limit)
+///
+/// # Returns
+/// * `Ok(Vec<LongPath>)` - Offending files, longest first
+/// * `Err(HibiscusError)` - If the root is invalid
+///
+/// # Notes
+/// Uses the same traversal as `build_tree`, so hidden files and ignored
+/// paths are skipped.
+#[tauri::command]
+pub fn find_long_paths(
+    root: String,
+    threshold: Option<usize>,
+) -> Result<Vec<LongPath>, HibiscusError> {
     let root = PathBuf::from(&root);
 
     // Validate path
@@ -40,5 +521,275 @@ pub fn build_tree(root: String) -> Result<Vec<Node>, HibiscusError> {
         });
     }
 
-    Ok(read_dir_recursive(&root, &root, MAX_TREE_DEPTH))
-}
\ No newline at end of file
+    let threshold = threshold.unwrap_or(DEFAULT_LONG_PATH_THRESHOLD);
+    let mut long_paths = Vec::new();
+    collect_long_paths(
+        &read_dir_recursive(&root, &root, Limits::load(&root).tree_depth, false),
+        &root,
+        threshold,
+        &mut long_paths,
+    );
+
+    long_paths.sort_by(|a, b| b.length.cmp(&a.length).then_with(|| a.path.cmp(&b.path)));
+    Ok(long_paths)
+}
+
+fn collect_long_paths(nodes: &[Node], root: &Path, threshold: usize, out: &mut Vec<LongPath>) {
+    for node in nodes {
+        if let Some(rel) = &node.path {
+            let path = root.join(rel).to_string_lossy().to_string();
+            let length = path.chars().count();
+            if length > threshold {
+                out.push(LongPath { path, length });
+            }
+        }
+        if let Some(children) = &node.children {
+            collect_long_paths(children, root, threshold, out);
+        }
+    }
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_find_long_paths_flags_deep_file() {
+        let dir = tempdir().unwrap();
+        let deep = dir
+            .path()
+            .join("a_rather_long_folder_name")
+            .join("another_quite_long_folder_name");
+        std::fs::create_dir_all(&deep).unwrap();
+        std::fs::write(deep.join("deeply_nested_note.md"), "").unwrap();
+        std::fs::write(dir.path().join("short.md"), "").unwrap();
+
+        let root_len = dir.path().to_string_lossy().chars().count();
+        let result =
+            find_long_paths(dir.path().to_string_lossy().to_string(), Some(root_len + 40)).unwrap();
+
+        assert_eq!(result.len(), 1);
+        assert!(result[0].path.ends_with("deeply_nested_note.md"));
+        assert!(result[0].length > root_len + 40);
+    }
+
+    #[test]
+    fn test_find_long_paths_ignores_hidden() {
+        let dir = tempdir().unwrap();
+        let hidden = dir.path().join(".hibiscus").join("backups");
+        std::fs::create_dir_all(&hidden).unwrap();
+        std::fs::write(hidden.join("workspace.json_123.bak"), "").unwrap();
+
+        let result = find_long_paths(dir.path().to_string_lossy().to_string(), Some(0)).unwrap();
+        assert!(result.is_empty());
+    }
+
+    fn ignore_workspace() -> tempfile::TempDir {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".hibiscus")).unwrap();
+        std::fs::write(
+            dir.path().join(".hibiscus").join("workspace.json"),
+            r#"{"workspace": {"id": "w", "name": "W"}, "settings": {"ignore_patterns": ["*.tmp"]}}"#,
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.path().join("notes")).unwrap();
+        std::fs::create_dir_all(dir.path().join("build")).unwrap();
+        for file in ["a.tmp", "notes/x.log", "notes/y.log", "notes/todo.md", "build/out.txt"] {
+            std::fs::write(dir.path().join(file), "").unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_preview_ignore_pattern_counts() {
+        let dir = ignore_workspace();
+        let root = dir.path().to_string_lossy().to_string();
+        read_tree_report(root.clone(), None, None, None).unwrap();
+
+        let logs = preview_ignore_pattern(root.clone(), "*.log".into()).unwrap();
+        assert_eq!(logs.hidden_count, 2);
+        assert_eq!(logs.hidden_sample, ["notes/x.log", "notes/y.log"]);
+        assert_eq!(logs.revealed_count, 0);
+
+        // A folder pattern hides the folder and what is in it
+        let build = preview_ignore_pattern(root.clone(), "build/".into()).unwrap();
+        assert_eq!(build.hidden_count, 2);
+
+        // A pattern already in the setting reports what removing it reveals
+        let tmp = preview_ignore_pattern(root.clone(), "*.tmp".into()).unwrap();
+        assert_eq!(tmp.hidden_count, 0);
+        assert_eq!(tmp.revealed_sample, ["a.tmp"]);
+
+        let err = preview_ignore_pattern(root, "notes/[ab".into()).unwrap_err();
+        assert!(matches!(err, HibiscusError::InvalidPattern { position: 6, .. }), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_apply_ignore_patterns_refilters_watcher_events() {
+        use crate::watcher::drop_pattern_ignored;
+
+        let dir = ignore_workspace();
+        let root = dir.path().to_string_lossy().to_string();
+        read_tree_report(root.clone(), None, None, None).unwrap();
+        let log = dir.path().join("notes").join("x.log");
+        let note = dir.path().join("notes").join("todo.md");
+        let events = || vec![log.clone(), note.clone()];
+
+        let rules = ignore::shared_rules(dir.path());
+        assert_eq!(drop_pattern_ignored(events(), dir.path(), &rules).len(), 2);
+
+        let update = apply_ignore_patterns(root.clone(), vec!["*.tmp".into(), " *.log ".into()])
+            .await
+            .unwrap();
+        assert_eq!(update.diff.removed, ["notes/x.log", "notes/y.log"]);
+        assert!(update.diff.added.is_empty());
+
+        // The running watcher sees the new rules without a restart
+        let rules = ignore::shared_rules(dir.path());
+        assert_eq!(drop_pattern_ignored(events(), dir.path(), &rules), vec![note.clone()]);
+        assert_eq!(WorkspaceSettings::load(dir.path()).ignore_patterns, ["*.tmp", "*.log"]);
+
+        // Invalid patterns are rejected before anything is saved
+        assert!(apply_ignore_patterns(root, vec!["{a,b".into()]).await.is_err());
+        assert_eq!(WorkspaceSettings::load(dir.path()).ignore_patterns, ["*.tmp", "*.log"]);
+    }
+
+    #[test]
+    fn test_depth_limit_is_reported_and_configurable() {
+        let dir = tempdir().unwrap();
+        let deep = (0..25).fold(dir.path().to_path_buf(), |path, i| path.join(format!("d{}", i)));
+        std::fs::create_dir_all(&deep).unwrap();
+        std::fs::write(deep.join("bottom.md"), "").unwrap();
+        let root = dir.path().to_string_lossy().to_string();
+
+        fn deepest(nodes: &[Node]) -> usize {
+            nodes
+                .iter()
+                .map(|node| 1 + node.children.as_deref().map_or(0, deepest))
+                .max()
+                .unwrap_or(0)
+        }
+
+        // Default limit: folders below level 20 are cut and reported
+        let report = read_tree_report(root.clone(), None, None, None).unwrap();
+        assert_eq!(deepest(&report.nodes), 20);
+        assert_eq!(report.limits_hit.len(), 1);
+        let hit = &report.limits_hit[0];
+        assert_eq!(hit.limit, crate::limits::TREE_DEPTH);
+        assert_eq!(hit.value, 20);
+        assert_eq!(hit.path.split('/').count(), 20);
+        assert!(hit.path.starts_with("d0/d1/"));
+
+        // Raising the workspace limit brings the bottom note back
+        std::fs::create_dir_all(dir.path().join(".hibiscus")).unwrap();
+        std::fs::write(
+            dir.path().join(".hibiscus").join("workspace.json"),
+            r#"{"settings": {"max_tree_depth": 30}}"#,
+        )
+        .unwrap();
+        let report = read_tree_report(root, None, None, None).unwrap();
+        assert!(report.limits_hit.is_empty());
+        assert_eq!(deepest(&report.nodes), 26);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_builds_share_one_walk() {
+        use std::sync::atomic::AtomicUsize;
+
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("a.md"), "").unwrap();
+        let root = dir.path().to_string_lossy().to_string();
+        let builds = TreeBuilds::default();
+        let walks = Arc::new(AtomicUsize::new(0));
+        let key = |max_nodes| TreeBuildKey {
+            root: root.clone(),
+            include_hidden: false,
+            sort: SortMode::default(),
+            max_nodes,
+        };
+        let counted_walk = |fail: bool| {
+            let (walks, root) = (Arc::clone(&walks), root.clone());
+            move || {
+                walks.fetch_add(1, Ordering::SeqCst);
+                std::thread::sleep(std::time::Duration::from_millis(50));
+                if fail {
+                    return Err(HibiscusError::Io("disk went away".into()));
+                }
+                read_tree_report(root, None, None, None)
+            }
+        };
+
+        let results =
+            futures::future::join_all((0..10).map(|_| builds.run(key(None), counted_walk(false)))).await;
+        assert_eq!(walks.load(Ordering::SeqCst), 1);
+        let first = results[0].as_ref().unwrap();
+        assert_eq!(first.generation, 1);
+        assert_eq!(first.nodes[0].id, "a.md");
+        assert!(results
+            .iter()
+            .all(|r| r.as_ref().is_ok_and(|r| r.generation == 1 && r.nodes.len() == first.nodes.len())));
+        assert!(builds.in_flight.lock().unwrap().is_empty());
+
+        // A failed walk fails every waiter and is not kept either
+        let results =
+            futures::future::join_all((0..10).map(|_| builds.run(key(Some(5)), counted_walk(true)))).await;
+        assert_eq!(walks.load(Ordering::SeqCst), 2);
+        assert!(results.iter().all(|r| matches!(r, Err(HibiscusError::Io(m)) if m == "disk went away")));
+        assert!(builds.in_flight.lock().unwrap().is_empty());
+
+        // The next call walks again, with a newer generation
+        let again = builds.run(key(None), counted_walk(false)).await.unwrap();
+        assert_eq!(walks.load(Ordering::SeqCst), 3);
+        assert_eq!(again.generation, 3);
+    }
+
+    #[test]
+    fn test_max_nodes_truncates_flat_folder() {
+        let dir = tempdir().unwrap();
+        for i in 0..500 {
+            std::fs::write(dir.path().join(format!("file-{i:03}.md")), "").unwrap();
+        }
+        let root = dir.path().to_string_lossy().to_string();
+
+        let report = read_tree_report(root.clone(), None, None, Some(100)).unwrap();
+        assert!(report.truncated);
+        assert_eq!(report.nodes.len(), 100);
+        assert_eq!(report.limits_hit.len(), 1);
+        assert_eq!(report.limits_hit[0].limit, TREE_NODES);
+        assert_eq!(report.limits_hit[0].value, 100);
+        // Sorted, and the same nodes on every call
+        assert!(report.nodes.windows(2).all(|pair| pair[0].name < pair[1].name));
+        let again = read_tree_report(root.clone(), None, None, Some(100)).unwrap();
+        assert!(again.nodes.iter().zip(&report.nodes).all(|(a, b)| a.id == b.id));
+
+        let report = read_tree_report(root, None, None, Some(500)).unwrap();
+        assert!(!report.truncated);
+        assert_eq!(report.nodes.len(), 500);
+    }
+
+    #[test]
+    fn test_tree_reads_mounted_storage() {
+        use crate::storage::tests::MemoryStorage;
+
+        let root = Path::new("/memory/tree-test");
+        let vault = MemoryStorage::mount(root);
+        vault.storage.add_file(&root.join("notes").join("a.md"), b"# A");
+        vault.storage.add_file(&root.join("b.md"), b"");
+        vault.storage.add_file(&root.join(".hidden"), b"");
+
+        let report = read_tree_report(root.to_string_lossy().into(), None, None, None).unwrap();
+        let names: Vec<&str> = report.nodes.iter().map(|node| node.name.as_str()).collect();
+        assert_eq!(names, ["notes", "b.md"]);
+        assert_eq!(report.nodes[0].children.as_ref().unwrap()[0].id, "notes/a.md");
+
+        let shallow = read_dir_shallow(root.to_string_lossy().into(), Some(true)).unwrap();
+        assert_eq!(shallow.len(), 3);
+        let err = read_dir_shallow(root.join("b.md").to_string_lossy().into(), None);
+        assert!(matches!(err, Err(HibiscusError::InvalidPathType { .. })));
+    }
+}
@@ -0,0 +1,90 @@
+// ============================================================================
+// NOTE VARIANTS
+// ============================================================================
+//
+// Language switcher support for notes kept in several languages
+// (`topic.en.md`, `topic.de.md`). Which files count as variants, and which
+// one a link picks, is decided by `crate::variants` from workspace settings.
+// ============================================================================
+
+use std::path::PathBuf;
+
+use crate::error::HibiscusError;
+use crate::ids::to_canonical_id;
+use crate::variants::{sibling_variants, VariantConfig};
+use super::path::validate_path;
+
+/// One language variant of a note.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct NoteVariant {
+    /// Language code, lowercase (e.g. `de`)
+    pub language: String,
+    /// Node id of the variant file
+    pub id: String,
+    /// Absolute path of the variant file
+    pub path: String,
+}
+
+/// Lists the language variants of a note.
+///
+/// # Arguments
+/// * `root` - Workspace root directory
+/// * `path` - A variant (`topic.en.md`) or the logical note (`topic.md`),
+///   absolute or relative to the root
+///
+/// # Returns
+/// * `Ok(Vec<NoteVariant>)` - All variants, including `path` itself, in
+///   the order links prefer them; empty if the note has none
+/// * `Err(HibiscusError)` - If a path is invalid
+#[tauri::command]
+pub fn get_note_variants(root: String, path: String) -> Result<Vec<NoteVariant>, HibiscusError> {
+    let root = PathBuf::from(&root);
+    validate_path(&root)?;
+    let file = validate_path(&root.join(&path))?;
+
+    let config = VariantConfig::load(&root);
+    Ok(sibling_variants(&file, &config)
+        .into_iter()
+        .map(|(language, path)| NoteVariant {
+            language,
+            id: to_canonical_id(&path, &root),
+            path: path.to_string_lossy().into_owned(),
+        })
+        .collect())
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_get_note_variants_lists_siblings_by_preference() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".hibiscus")).unwrap();
+        fs::write(
+            dir.path().join(".hibiscus").join("workspace.json"),
+            r#"{ "settings": { "preferred_languages": ["de"] } }"#,
+        )
+        .unwrap();
+        fs::create_dir(dir.path().join("Notes")).unwrap();
+        for name in ["topic.en.md", "topic.de.md", "topic.md.bak", "other.fr.md"] {
+            fs::write(dir.path().join("Notes").join(name), "").unwrap();
+        }
+        let root = dir.path().to_string_lossy().to_string();
+
+        let variants = get_note_variants(root.clone(), "Notes/topic.en.md".into()).unwrap();
+        let found: Vec<(&str, &str)> = variants.iter().map(|v| (v.language.as_str(), v.id.as_str())).collect();
+        assert_eq!(found, [("de", "Notes/topic.de.md"), ("en", "Notes/topic.en.md")]);
+
+        // The logical name works too, and plain notes have no variants
+        assert_eq!(get_note_variants(root.clone(), "Notes/topic.md".into()).unwrap(), variants);
+        fs::write(dir.path().join("plain.md"), "").unwrap();
+        assert!(get_note_variants(root, "plain.md".into()).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,383 @@
+// ============================================================================
+// VAULT TEMPLATES
+// ============================================================================
+//
+// Creates a new vault from a template folder, so a team can hand out the
+// same starter structure (folders, index notes, settings) to everyone.
+//
+// DESIGN:
+// - Template files are copied with `{{variable}}` placeholders filled in:
+//   `{{name}}` (the vault name), `{{date}}` (today, YYYY-MM-DD) and any
+//   variables the caller passes. Unknown placeholders are left as they are,
+//   and binary files are copied byte for byte.
+// - Dotfiles and dot-folders are not copied, like in the tree. Settings in
+//   the template's own `.hibiscus/workspace.json` are carried over.
+// - The vault is built in a staging folder next to `dest_root` and moved
+//   into place at the end, so a failed init leaves nothing behind.
+// ============================================================================
+
+use chrono::Local;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::capabilities::random_token;
+use crate::error::HibiscusError;
+use crate::migration::WORKSPACE_SCHEMA_VERSION;
+use crate::references::write_json_atomic;
+use crate::time::Timestamp;
+use crate::workspace::{WorkspaceFile, WorkspaceInfo};
+use super::opener::looks_binary;
+use super::path::validate_path;
+
+/// Placeholder filled with the vault name.
+pub const NAME_VARIABLE: &str = "name";
+/// Placeholder filled with the creation date.
+pub const DATE_VARIABLE: &str = "date";
+
+/// Creates a vault at `dest_root` from a template folder.
+///
+/// # Arguments
+/// * `template_dir` - Folder whose contents make up the new vault
+/// * `dest_root` - Root of the new vault; may be missing or an existing
+///   folder that is not a workspace yet
+/// * `name` - Vault name, stored in workspace.json and used for `{{name}}`
+/// * `variables` - Extra `{{variable}}` values for the template files
+///
+/// # Returns
+/// * `Ok(String)` - Path of the new `.hibiscus/workspace.json`
+/// * `Err(HibiscusError::Workspace)` - If `dest_root` is already a workspace
+/// * `Err(HibiscusError::AlreadyExists)` - If a template item would replace
+///   a file already in `dest_root`; nothing is changed
+/// * `Err(HibiscusError)` - If a path is invalid or the copy failed
+#[tauri::command]
+pub async fn init_vault_from_template(
+    template_dir: String,
+    dest_root: String,
+    name: String,
+    variables: Option<HashMap<String, String>>,
+) -> Result<String, HibiscusError> {
+    let template = PathBuf::from(&template_dir);
+    let dest = PathBuf::from(&dest_root);
+
+    // Validate both paths
+    validate_path(&template)?;
+    validate_path(&dest)?;
+
+    if !template.is_dir() {
+        return Err(HibiscusError::InvalidPathType {
+            path: template_dir,
+            expected: "directory".into(),
+            actual: "file".into(),
+        });
+    }
+
+    let mut variables = variables.unwrap_or_default();
+    variables.insert(NAME_VARIABLE.into(), name.clone());
+    variables
+        .entry(DATE_VARIABLE.into())
+        .or_insert_with(|| Local::now().format("%Y-%m-%d").to_string());
+
+    tokio::task::spawn_blocking(move || init_vault(&template, &dest, &name, &variables))
+        .await
+        .map_err(|e| HibiscusError::Io(format!("Vault init task failed: {}", e)))?
+        .map(|path| path.to_string_lossy().to_string())
+}
+
+/// Blocking implementation of `init_vault_from_template`.
+fn init_vault(
+    template: &Path,
+    dest: &Path,
+    name: &str,
+    variables: &HashMap<String, String>,
+) -> Result<PathBuf, HibiscusError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(HibiscusError::PathValidation("Vault name is empty".into()));
+    }
+
+    let workspace_path = dest.join(".hibiscus").join("workspace.json");
+    if workspace_path.exists() {
+        return Err(HibiscusError::Workspace(format!(
+            "'{}' is already a Hibiscus workspace",
+            dest.display()
+        )));
+    }
+    if dest.exists() && !dest.is_dir() {
+        return Err(HibiscusError::InvalidPathType {
+            path: dest.to_string_lossy().into(),
+            expected: "directory".into(),
+            actual: "file".into(),
+        });
+    }
+
+    let Some(parent) = dest.parent().filter(|p| p.is_dir()) else {
+        let parent = dest.parent().unwrap_or(dest);
+        return Err(HibiscusError::ParentNotFound(parent.to_string_lossy().into()));
+    };
+    if parent.canonicalize()?.starts_with(template.canonicalize()?) {
+        return Err(HibiscusError::PathValidation(format!(
+            "Cannot create a vault inside its template ('{}')",
+            template.display()
+        )));
+    }
+
+    let dest_name = dest
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let staging = parent.join(format!(".{}.hibiscus-init~", dest_name));
+    if staging.exists() {
+        // Left behind by an init that crashed
+        fs::remove_dir_all(&staging)?;
+    }
+
+    let result = stage_vault(template, &staging, dest, name, variables)
+        .and_then(|()| move_into_place(&staging, dest));
+    if staging.exists() {
+        let _ = fs::remove_dir_all(&staging);
+    }
+    result.map(|()| workspace_path)
+}
+
+/// Builds the complete vault for `dest` in `staging`.
+fn stage_vault(
+    template: &Path,
+    staging: &Path,
+    dest: &Path,
+    name: &str,
+    variables: &HashMap<String, String>,
+) -> Result<(), HibiscusError> {
+    fs::create_dir(staging).map_err(|e| {
+        HibiscusError::Io(format!("Failed to create '{}': {}", staging.display(), e))
+    })?;
+    copy_template(template, staging, variables)?;
+
+    // Carry over the template's settings, but not its identity or tree
+    let settings = fs::read_to_string(template.join(".hibiscus").join("workspace.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|mut doc| doc.get_mut("settings").map(serde_json::Value::take))
+        .filter(serde_json::Value::is_object)
+        .unwrap_or_else(|| serde_json::json!({}));
+
+    let now = Timestamp::now();
+    let workspace = WorkspaceFile {
+        schema_version: WORKSPACE_SCHEMA_VERSION.to_string(),
+        workspace: WorkspaceInfo {
+            id: random_token()?,
+            name: name.to_string(),
+            root: dest.to_string_lossy().to_string(),
+            created_at: Some(now),
+            updated_at: Some(now),
+        },
+        settings: Some(settings),
+        tree: Vec::new(),
+        session: None,
+        decorations: Default::default(),
+        manual_order: Default::default(),
+        favorites: Vec::new(),
+    };
+
+    let hibiscus = staging.join(".hibiscus");
+    fs::create_dir_all(&hibiscus)?;
+    write_json_atomic(&hibiscus.join("workspace.json"), &serde_json::to_value(&workspace)?)
+}
+
+/// Copies the visible contents of `source` into `target`, filling in
+/// placeholders in text files.
+fn copy_template(
+    source: &Path,
+    target: &Path,
+    variables: &HashMap<String, String>,
+) -> Result<(), HibiscusError> {
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+
+        let from = entry.path();
+        let to = target.join(entry.file_name());
+        let is_symlink = entry.file_type()?.is_symlink();
+        if from.is_dir() {
+            // Symlinked folders are not followed, so link cycles cannot recurse
+            if !is_symlink {
+                fs::create_dir(&to)?;
+                copy_template(&from, &to, variables)?;
+            }
+            continue;
+        }
+
+        let bytes = fs::read(&from).map_err(|e| {
+            HibiscusError::Io(format!("Failed to read template file '{}': {}", from.display(), e))
+        })?;
+        let contents = match String::from_utf8(bytes) {
+            Ok(text) if !looks_binary(text.as_bytes()) => fill_placeholders(&text, variables).into_bytes(),
+            Ok(text) => text.into_bytes(),
+            Err(e) => e.into_bytes(),
+        };
+        fs::write(&to, contents).map_err(|e| {
+            HibiscusError::Io(format!("Failed to write '{}': {}", to.display(), e))
+        })?;
+    }
+    Ok(())
+}
+
+/// Replaces `{{variable}}` placeholders with known values.
+fn fill_placeholders(text: &str, variables: &HashMap<String, String>) -> String {
+    let mut filled = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let end = start + 2 + len + 2;
+        filled.push_str(&rest[..start]);
+        match variables.get(rest[start + 2..start + 2 + len].trim()) {
+            Some(value) => filled.push_str(value),
+            None => filled.push_str(&rest[start..end]),
+        }
+        rest = &rest[end..];
+    }
+    filled.push_str(rest);
+    filled
+}
+
+/// Moves the staged vault to `dest`: a single rename when `dest` is missing,
+/// otherwise entry by entry, undoing the moves if one fails.
+fn move_into_place(staging: &Path, dest: &Path) -> Result<(), HibiscusError> {
+    if !dest.exists() {
+        return fs::rename(staging, dest).map_err(|e| {
+            HibiscusError::Io(format!("Failed to create vault '{}': {}", dest.display(), e))
+        });
+    }
+
+    // `.hibiscus` may exist without a workspace.json; only the file moves
+    let mut entries: Vec<(PathBuf, PathBuf)> = Vec::new();
+    for entry in fs::read_dir(staging)? {
+        let entry = entry?;
+        if entry.file_name() == ".hibiscus" && dest.join(".hibiscus").is_dir() {
+            let file = Path::new(".hibiscus").join("workspace.json");
+            entries.push((staging.join(&file), dest.join(&file)));
+        } else {
+            entries.push((entry.path(), dest.join(entry.file_name())));
+        }
+    }
+    if let Some((_, existing)) = entries.iter().find(|(_, to)| to.exists()) {
+        return Err(HibiscusError::AlreadyExists(existing.to_string_lossy().into()));
+    }
+
+    // The workspace file goes last, so a vault is never half-initialized
+    entries.sort_by_key(|(from, _)| from.starts_with(staging.join(".hibiscus")));
+
+    let mut moved = Vec::new();
+    for (from, to) in &entries {
+        if let Err(e) = fs::rename(from, to) {
+            for (from, to) in moved.into_iter().rev() {
+                let _ = fs::rename(to, from);
+            }
+            return Err(HibiscusError::Io(format!(
+                "Failed to move '{}' into the vault: {}",
+                to.display(),
+                e
+            )));
+        }
+        moved.push((from, to));
+    }
+    Ok(())
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn template(root: &Path) -> PathBuf {
+        let template = root.join("template");
+        fs::create_dir_all(template.join("Lectures")).unwrap();
+        fs::create_dir_all(template.join(".hibiscus")).unwrap();
+        fs::write(
+            template.join(".hibiscus").join("workspace.json"),
+            r#"{ "workspace": { "id": "template", "name": "Template" }, "settings": { "theme": "dark" } }"#,
+        )
+        .unwrap();
+        fs::write(template.join("Index.md"), "# {{name}}\nCourse: {{ course }}, {{unknown}}\n").unwrap();
+        fs::write(template.join("Lectures").join("Week 1.md"), "Started {{date}}\n").unwrap();
+        fs::write(template.join("logo.png"), [0x89, b'{', b'{', 0, b'}', b'}']).unwrap();
+        fs::write(template.join(".DS_Store"), "x").unwrap();
+        template
+    }
+
+    fn init(template: &Path, dest: &Path) -> Result<String, HibiscusError> {
+        let variables = HashMap::from([
+            ("course".to_string(), "Biology".to_string()),
+            (DATE_VARIABLE.to_string(), "2024-03-05".to_string()),
+            (NAME_VARIABLE.to_string(), "Semester 1".to_string()),
+        ]);
+        init_vault(template, dest, "Semester 1", &variables).map(|p| p.to_string_lossy().to_string())
+    }
+
+    #[test]
+    fn test_init_copies_template_with_substitution() {
+        let dir = tempdir().unwrap();
+        let template = template(dir.path());
+        let dest = dir.path().join("Semester 1");
+
+        let workspace_path = init(&template, &dest).unwrap();
+        assert_eq!(PathBuf::from(&workspace_path), dest.join(".hibiscus").join("workspace.json"));
+
+        assert_eq!(
+            fs::read_to_string(dest.join("Index.md")).unwrap(),
+            "# Semester 1\nCourse: Biology, {{unknown}}\n"
+        );
+        assert_eq!(fs::read_to_string(dest.join("Lectures").join("Week 1.md")).unwrap(), "Started 2024-03-05\n");
+        assert_eq!(fs::read(dest.join("logo.png")).unwrap(), [0x89, b'{', b'{', 0, b'}', b'}']);
+        assert!(!dest.join(".DS_Store").exists());
+
+        let workspace: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&workspace_path).unwrap()).unwrap();
+        assert_eq!(workspace["workspace"]["name"], "Semester 1");
+        assert_ne!(workspace["workspace"]["id"], "template");
+        assert_eq!(workspace["workspace"]["root"], dest.to_string_lossy().as_ref());
+        assert_eq!(workspace["settings"]["theme"], "dark");
+
+        // No staging folder is left next to the vault
+        let leftovers: Vec<_> = fs::read_dir(dir.path()).unwrap().filter_map(Result::ok).collect();
+        assert_eq!(leftovers.len(), 2);
+    }
+
+    #[test]
+    fn test_init_refuses_existing_workspace_and_conflicts() {
+        let dir = tempdir().unwrap();
+        let template = template(dir.path());
+
+        let dest = dir.path().join("vault");
+        fs::create_dir_all(dest.join(".hibiscus")).unwrap();
+        fs::write(dest.join(".hibiscus").join("workspace.json"), "{}").unwrap();
+        let err = init(&template, &dest).unwrap_err();
+        assert!(matches!(err, HibiscusError::Workspace(_)));
+        assert!(!dest.join("Index.md").exists());
+
+        // A folder with a file the template would replace is left untouched
+        let notes = dir.path().join("notes");
+        fs::create_dir_all(notes.join(".hibiscus")).unwrap();
+        fs::write(notes.join("Index.md"), "mine").unwrap();
+        let err = init(&template, &notes).unwrap_err();
+        assert!(matches!(err, HibiscusError::AlreadyExists(_)));
+        assert_eq!(fs::read_to_string(notes.join("Index.md")).unwrap(), "mine");
+        assert!(!notes.join("Lectures").exists());
+
+        // Without the conflict the template is merged into the folder
+        fs::remove_file(notes.join("Index.md")).unwrap();
+        fs::write(notes.join("Own.md"), "mine").unwrap();
+        init(&template, &notes).unwrap();
+        assert!(notes.join("Lectures").join("Week 1.md").is_file());
+        assert!(notes.join(".hibiscus").join("workspace.json").is_file());
+        assert_eq!(fs::read_to_string(notes.join("Own.md")).unwrap(), "mine");
+    }
+}
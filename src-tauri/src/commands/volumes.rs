@@ -0,0 +1,110 @@
+// ============================================================================
+// VOLUMES
+// ============================================================================
+//
+// Drives and mount points for the welcome screen's "open folder" quick list.
+// Disks are enumerated with `sysinfo`, which also reports their free space.
+// Pseudo filesystems (proc, sysfs, tmpfs, ...) and mounts that are not
+// folders (e.g. single files bind-mounted into containers) are left out.
+// ============================================================================
+
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::error::HibiscusError;
+
+/// Filesystem types that hold no user files.
+const PSEUDO_FILESYSTEMS: &[&str] = &[
+    "autofs", "binfmt_misc", "bpf", "cgroup", "cgroup2", "configfs", "debugfs", "devpts", "devtmpfs",
+    "efivarfs", "fusectl", "hugetlbfs", "mqueue", "nsfs", "proc", "pstore", "ramfs", "rpc_pipefs",
+    "securityfs", "squashfs", "sysfs", "tmpfs", "tracefs",
+];
+
+/// A drive or mounted volume.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Volume {
+    /// Folder to open, e.g. `C:\` or `/media/usb`
+    pub root: String,
+    /// Volume label; the device name or root where there is none
+    pub label: String,
+    pub file_system: String,
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+    pub removable: bool,
+}
+
+/// Lists the drive roots (Windows) or mount points (Unix, macOS) that can
+/// be opened as a workspace.
+///
+/// # Returns
+/// * `Ok(Vec<Volume>)` - Volumes sorted by root, each root listed once
+/// * `Err(HibiscusError)` - If the enumeration task failed
+#[tauri::command]
+pub async fn list_volumes() -> Result<Vec<Volume>, HibiscusError> {
+    tokio::task::spawn_blocking(read_volumes)
+        .await
+        .map_err(|e| HibiscusError::Io(format!("Volume listing task failed: {}", e)))
+}
+
+fn read_volumes() -> Vec<Volume> {
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    let mut seen = HashSet::new();
+
+    let mut volumes: Vec<Volume> = disks
+        .list()
+        .iter()
+        .filter(|disk| !is_pseudo_filesystem(&disk.file_system().to_string_lossy()))
+        .filter(|disk| disk.mount_point().is_dir())
+        .filter(|disk| seen.insert(disk.mount_point().to_path_buf()))
+        .map(|disk| {
+            let root = disk.mount_point().to_string_lossy().into_owned();
+            Volume {
+                label: volume_label(&disk.name().to_string_lossy(), disk.mount_point()),
+                root,
+                file_system: disk.file_system().to_string_lossy().into_owned(),
+                total_bytes: disk.total_space(),
+                available_bytes: disk.available_space(),
+                removable: disk.is_removable(),
+            }
+        })
+        .collect();
+
+    volumes.sort_by(|a, b| a.root.cmp(&b.root));
+    volumes
+}
+
+fn is_pseudo_filesystem(file_system: &str) -> bool {
+    PSEUDO_FILESYSTEMS.iter().any(|pseudo| pseudo.eq_ignore_ascii_case(file_system))
+}
+
+/// The disk's name, or the mount point when the platform reports none.
+fn volume_label(name: &str, mount_point: &Path) -> String {
+    if name.trim().is_empty() {
+        mount_point.to_string_lossy().into_owned()
+    } else {
+        name.to_string()
+    }
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_list_volumes_returns_openable_roots() {
+        let volumes = list_volumes().await.unwrap();
+        assert!(!volumes.is_empty());
+        for volume in &volumes {
+            let root = Path::new(&volume.root);
+            assert!(root.is_absolute() && root.is_dir(), "{:?}", volume);
+            assert!(!is_pseudo_filesystem(&volume.file_system));
+            assert!(!volume.label.is_empty());
+        }
+        assert_eq!(volume_label("", Path::new("/mnt/usb")), "/mnt/usb");
+    }
+}
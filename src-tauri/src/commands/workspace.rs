@@ -2,36 +2,61 @@
 // WORKSPACE OPERATIONS
 // ============================================================================
 
-use std::path::PathBuf;
-use tokio::fs;
+use std::collections::BTreeSet;
+use std::io::{BufReader, Write};
+use std::path::{Path, PathBuf};
+use tauri::Emitter;
 
 use crate::error::HibiscusError;
-use crate::workspace::WorkspaceFile;
-use super::path::validate_path;
+use crate::limits::Limits;
+use crate::tree::{apply_decorations, apply_manual_order, diff_trees, read_dir_limited, TreeDiff};
+use crate::workspace::{Node, WorkspaceFile};
+use crate::storage::storage_for;
+use crate::migration::{is_newer_workspace_schema, stores_tree, WORKSPACE_SCHEMA_VERSION};
+use super::path::{expand_user_path, find_workspace_root, validate_path};
+
+/// Serializes read-modify-write cycles on workspace.json.
+///
+/// Held by every backend writer of the file (full saves, decoration edits,
+/// reference reconciliation) so concurrent updates cannot drop each other's
+/// changes. Never held across anything slower than the write itself.
+pub(crate) static WORKSPACE_LOCK: std::sync::LazyLock<tokio::sync::Mutex<()>> =
+    std::sync::LazyLock::new(|| tokio::sync::Mutex::new(()));
 
 
 /// Loads a workspace.json file from the specified path.
 ///
 /// # Arguments
 /// * `path` - Path to the workspace.json file
+/// * `include_tree` - Fill `tree` from disk, with decorations and manual
+///   order applied, for frontends that still read it from the workspace
+///   file (default false; use `build_tree` instead)
 ///
 /// # Returns
 /// * `Ok(WorkspaceFile)` - The parsed workspace file
 /// * `Err(HibiscusError)` - If loading or parsing fails
+///
+/// # Notes
+/// Files older than schema 1.2 are migrated on load: their stored tree is
+/// dropped and the ordering, decorations and favorites it carried move to
+/// their own sections. The next save writes the file without a tree.
 #[tauri::command]
-pub async fn load_workspace(path: String) -> Result<WorkspaceFile, HibiscusError> {
+pub async fn load_workspace(
+    path: String,
+    include_tree: Option<bool>,
+) -> Result<WorkspaceFile, HibiscusError> {
     let path = PathBuf::from(&path);
 
     // Validate path
     validate_path(&path)?;
 
-    if !path.exists() {
+    let Ok(metadata) = storage_for(&path).metadata(&path) else {
         return Err(HibiscusError::FileNotFound(
             "workspace.json not found".into(),
         ));
-    }
+    };
 
-    if !path.is_file() {
+    if metadata.is_dir {
         return Err(HibiscusError::InvalidPathType {
             path: path.to_string_lossy().into(),
             expected: "file".into(),
@@ -39,13 +64,42 @@ pub async fn load_workspace(path: String) -> Result<WorkspaceFile, HibiscusError
         });
     }
 
-    // Read file asynchronously
-    let content = fs::read_to_string(&path)
-        .await
+    let include_tree = include_tree.unwrap_or(false);
+    tokio::task::spawn_blocking(move || {
+        let mut workspace = read_workspace_file(&path)?;
+        if include_tree {
+            // workspace.json lives in <root>/.hibiscus
+            if let Some(root) = path.parent().and_then(Path::parent) {
+                workspace.tree = compatibility_tree(root, &workspace);
+            }
+        }
+        Ok(workspace)
+    })
+    .await
+    .map_err(|e| HibiscusError::Workspace(format!("Workspace load task failed: {}", e)))?
+}
+
+/// The tree older frontends expect in `WorkspaceFile::tree`: read from disk,
+/// with the workspace's decorations and manual order applied.
+fn compatibility_tree(root: &Path, workspace: &WorkspaceFile) -> Vec<Node> {
+    let (mut tree, _) = read_dir_limited(root, root, Limits::load(root).tree_depth, false);
+    apply_decorations(&mut tree, &workspace.decorations);
+    apply_manual_order(&mut tree, &workspace.manual_order, "");
+    tree
+}
+
+/// Parses a workspace file straight from a buffered reader.
+///
+/// Reading into a `String` first would keep the raw text alive alongside the
+/// DOM; streaming avoids that copy. The owned DOM is then moved (not cloned)
+/// into the typed struct.
+fn read_workspace_file(path: &Path) -> Result<WorkspaceFile, HibiscusError> {
+    let file = storage_for(path)
+        .read(path)
         .map_err(|e| HibiscusError::Io(format!("Failed to read workspace.json: {}", e)))?;
 
     // Parse into a mutable DOM first for migration
-    let mut raw_json: serde_json::Value = serde_json::from_str(&content)
+    let mut raw_json: serde_json::Value = serde_json::from_reader(BufReader::new(file))
         .map_err(|e| HibiscusError::Workspace(format!("Invalid workspace JSON: {}", e)))?;
 
     // Apply schema migrations if necessary
@@ -58,6 +112,24 @@ pub async fn load_workspace(path: String) -> Result<WorkspaceFile, HibiscusError
     Ok(workspace)
 }
 
+/// Measurements of one workspace.json save, for save metrics.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct WorkspaceSaveStats {
+    pub bytes_written: u64,
+    pub duration_ms: u64,
+    /// Whether the previous file was copied into `.hibiscus/backups`
+    pub backup_rotated: bool,
+}
+
+/// Payload of the `workspace-save-complete` event.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct WorkspaceSaveComplete {
+    pub path: String,
+    /// Present when the save succeeded
+    pub workspace_save_stats: Option<WorkspaceSaveStats>,
+    pub error: Option<String>,
+}
+
 /// Saves a workspace file to disk.
 ///
 /// Uses atomic write to prevent corruption.
@@ -65,23 +137,74 @@ pub async fn load_workspace(path: String) -> Result<WorkspaceFile, HibiscusError
 /// # Arguments
 /// * `path` - Path where to save the workspace.json
 /// * `workspace` - The workspace data to save
+/// * `window` - Tauri window for emitting events
+///
+/// # Events Emitted
+/// * `workspace-save-complete` - After every save, with `workspace_save_stats`
 ///
 /// # Returns
-/// * `Ok(())` - If save was successful
-/// * `Err(HibiscusError)` - If save failed
+/// * `Ok(())` - If the save succeeded
+/// * `Err(HibiscusError)` - If the save failed
+///
+/// # Notes
+/// From schema 1.2 on, `tree` is not written even if the frontend sends
+/// it. Only files declaring an older schema still store the tree.
+/// Decorations, manual order and favorites are kept as stored, and session
+/// entries for deleted items are dropped, so a save from a frontend that
+/// missed a rename or delete does not bring stale references back.
 #[tauri::command]
-pub async fn save_workspace(path: String, workspace: WorkspaceFile) -> Result<(), HibiscusError> {
+pub async fn save_workspace(
+    path: String,
+    mut workspace: WorkspaceFile,
+    window: tauri::Window,
+) -> Result<(), HibiscusError> {
     let path = PathBuf::from(&path);
 
     // Validate path
     validate_path(&path)?;
 
-    // Create parent directories if needed
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).await.map_err(|e| {
-            HibiscusError::Io(format!("Failed to create workspace directory: {}", e))
-        })?;
+    // Reject invalid macros now rather than when they are run
+    crate::macros::parse_macros(workspace.settings.as_ref())?;
+
+    omit_stored_tree(&mut workspace);
+
+    let result = save_workspace_file(&path, workspace).await;
+    emit_save_complete(&window, &path, &result);
+    result.map(|_| ())
+}
+
+/// Drops the tree unless the file's schema still stores it.
+fn omit_stored_tree(workspace: &mut WorkspaceFile) {
+    if !stores_tree(&workspace.schema_version) {
+        workspace.tree = Vec::new();
     }
+}
+
+fn emit_save_complete(
+    window: &tauri::Window,
+    path: &Path,
+    result: &Result<WorkspaceSaveStats, HibiscusError>,
+) {
+    let payload = WorkspaceSaveComplete {
+        path: path.to_string_lossy().to_string(),
+        workspace_save_stats: result.as_ref().ok().cloned(),
+        error: result.as_ref().err().map(|e| e.to_string()),
+    };
+    if let Err(e) = window.emit("workspace-save-complete", payload) {
+        eprintln!("[Hibiscus] Error emitting event: {}", e);
+    }
+}
+
+/// Backs up, writes and atomically replaces workspace.json.
+async fn save_workspace_file(
+    path: &Path,
+    workspace: WorkspaceFile,
+) -> Result<WorkspaceSaveStats, HibiscusError> {
+    let started = std::time::Instant::now();
+
+    // The write creates missing parent directories
+    let storage = storage_for(path);
+    let new_folder = path.parent().filter(|parent| !storage.exists(parent));
 
     // Get root from path for backup purposes
     let root = path
@@ -91,23 +214,103 @@ pub async fn save_workspace(path: String, workspace: WorkspaceFile) -> Result<()
         .unwrap_or_else(|| PathBuf::from("."));
 
     // Create a backup before proceeding to save
-    let _ = crate::backup::create_backup(&path, &root).await;
+    let backup_rotated = matches!(
+        crate::backup::create_backup(path, &root).await,
+        Ok(backup) if backup != path
+    );
+
+    let _guard = WORKSPACE_LOCK.lock().await;
+
+    // Atomic write: stream into the storage's temp file, then replace
+    let target = path.to_path_buf();
+    let mut workspace = workspace;
+    let bytes_written = tokio::task::spawn_blocking(move || {
+        keep_backend_sections(&target, &root, &mut workspace);
+        let mut bytes_written = 0;
+        storage
+            .write_atomic(&target, &mut |writer| {
+                bytes_written = write_workspace_file(writer, &workspace)?;
+                Ok(())
+            })
+            .map(|_| bytes_written)
+    })
+    .await
+    .map_err(|e| HibiscusError::Workspace(format!("Workspace save task failed: {}", e)))?
+    .map_err(|e| HibiscusError::Io(format!("Failed to write workspace.json: {}", e)))?;
+
+    // A freshly created .hibiscus folder should be hidden in Explorer too
+    if let Some(parent) = new_folder.filter(|parent| parent.file_name().is_some_and(|name| name == ".hibiscus")) {
+        if let Err(e) = super::attributes::apply_hidden(parent, true, false) {
+            eprintln!("[Hibiscus] Warning: Failed to hide .hibiscus folder: {}", e);
+        }
+    }
 
-    // Serialize to pretty JSON
-    let json = serde_json::to_string_pretty(&workspace)?;
+    Ok(WorkspaceSaveStats {
+        bytes_written,
+        duration_ms: started.elapsed().as_millis() as u64,
+        backup_rotated,
+    })
+}
 
-    // Atomic write: write to temp file, then rename
-    let temp_path = path.with_extension("json.tmp");
+/// Keeps what the backend maintains in workspace.json over the frontend's
+/// copy of it, which may predate the backend's last change: decorations,
+/// manual order and favorites as stored (they have their own commands and
+/// follow renames through reference reconciliation), and no session
+/// entries for items that are gone, which reconciliation already pruned.
+///
+/// Called with `WORKSPACE_LOCK` held. Without a readable file on disk,
+/// `workspace` is saved as sent.
+fn keep_backend_sections(path: &Path, root: &Path, workspace: &mut WorkspaceFile) {
+    let Ok(stored) = read_workspace_file(path) else {
+        return;
+    };
+    workspace.decorations = stored.decorations;
+    workspace.manual_order = stored.manual_order;
+    workspace.favorites = stored.favorites;
 
-    fs::write(&temp_path, &json)
-        .await
-        .map_err(|e| HibiscusError::Io(format!("Failed to write temp workspace file: {}", e)))?;
+    let storage = storage_for(root);
+    let exists = |id: &str| storage.exists(&root.join(id));
+    if let Some(session) = workspace.session.as_mut() {
+        if let Some(open_nodes) = session.open_nodes.as_mut() {
+            open_nodes.retain(|id| exists(id));
+        }
+        if session.active_node.as_deref().is_some_and(|id| !exists(id)) {
+            session.active_node = None;
+        }
+        if let Some(cursor) = session.cursor.as_mut() {
+            cursor.retain(|id, _| exists(id));
+        }
+    }
+}
 
-    fs::rename(&temp_path, &path)
-        .await
-        .map_err(|e| HibiscusError::Io(format!("Failed to finalize workspace.json: {}", e)))?;
+/// Serializes a workspace as pretty JSON directly into `writer` and
+/// returns the number of bytes written.
+///
+/// The tree is streamed into the storage's writer instead of being rendered
+/// to an intermediate `String`, so peak memory stays close to the size of
+/// the in-memory tree even for very large vaults.
+fn write_workspace_file(writer: &mut dyn Write, workspace: &WorkspaceFile) -> std::io::Result<u64> {
+    let mut writer = CountingWriter { inner: writer, written: 0 };
+    serde_json::to_writer_pretty(&mut writer, workspace)?;
+    Ok(writer.written)
+}
+
+/// Passes writes through and counts the bytes.
+struct CountingWriter<'a> {
+    inner: &'a mut dyn Write,
+    written: u64,
+}
 
-    Ok(())
+impl Write for CountingWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
 }
 
 /// Response type for workspace discovery.
@@ -117,31 +320,273 @@ pub struct WorkspaceDiscovery {
     pub found: bool,
     /// Path to the workspace.json if found
     pub path: Option<String>,
+    /// The root to open: the input after `~` and environment-variable
+    /// expansion, or the owning workspace when found via `search_ancestors`
+    pub root: String,
+    /// Whether the found file can be loaded (`None` if nothing was found)
+    pub valid: Option<bool>,
+    /// `schema_version` from the found file
+    pub schema_version: Option<String>,
+    /// `workspace.name` from the found file
+    pub workspace_name: Option<String>,
+    /// Why the found file cannot be loaded
+    pub problem: Option<String>,
+}
+
+/// Just the workspace.json fields discovery reports; the tree is skipped
+/// without being materialized.
+#[derive(serde::Deserialize)]
+struct WorkspaceHeader {
+    schema_version: Option<String>,
+    workspace: Option<WorkspaceHeaderInfo>,
+}
+
+#[derive(serde::Deserialize)]
+struct WorkspaceHeaderInfo {
+    name: Option<String>,
 }
 
 /// Discovers if a workspace.json exists in the .hibiscus folder of the given root.
 ///
 /// # Arguments
-/// * `root` - The root directory to check for a workspace
+/// * `root` - The root directory to check, as typed or picked by the user
+/// * `search_ancestors` - Also look in parent directories (default false)
 ///
 /// # Returns
-/// * `WorkspaceDiscovery` - Discovery result with found status and path
+/// * `Ok(WorkspaceDiscovery)` - Discovery result with found status, path and
+///   a validity check of the file's header
+/// * `Err(HibiscusError)` - If `root` uses an alias that cannot be expanded
+///
+/// # Notes
+/// The root is expanded with `expand_user_path`, so `~/vault` or
+/// `%USERPROFILE%\vault` work. Callers should use the returned `root` for
+/// every later command instead of the raw input.
 #[tauri::command]
-pub fn discover_workspace(root: String) -> WorkspaceDiscovery {
-    let root = PathBuf::from(root);
+pub async fn discover_workspace(
+    root: String,
+    search_ancestors: Option<bool>,
+) -> Result<WorkspaceDiscovery, HibiscusError> {
+    let mut root = expand_user_path(&root)?;
+    if search_ancestors.unwrap_or(false) {
+        if let Some(owner) = find_workspace_root(&root) {
+            root = owner;
+        }
+    }
+
     let candidate = root.join(".hibiscus").join("workspace.json");
+    let mut discovery = WorkspaceDiscovery {
+        found: false,
+        path: None,
+        root: root.to_string_lossy().to_string(),
+        valid: None,
+        schema_version: None,
+        workspace_name: None,
+        problem: None,
+    };
 
-    if candidate.is_file() {
-        WorkspaceDiscovery {
-            found: true,
-            path: Some(candidate.to_string_lossy().to_string()),
-        }
-    } else {
-        WorkspaceDiscovery {
-            found: false,
-            path: None,
-        }
+    if !storage_for(&candidate).metadata(&candidate).is_ok_and(|metadata| !metadata.is_dir) {
+        return Ok(discovery);
+    }
+    discovery.found = true;
+    discovery.path = Some(candidate.to_string_lossy().to_string());
+
+    let problem = match crate::storage::read(&candidate).await {
+        Err(e) => Some(format!("Failed to read workspace.json: {}", e)),
+        Ok(bytes) => match serde_json::from_slice::<WorkspaceHeader>(&bytes) {
+            Err(e) => Some(format!("workspace.json is not valid JSON: {}", e)),
+            Ok(header) => {
+                discovery.workspace_name = header.workspace.and_then(|w| w.name);
+                discovery.schema_version = header.schema_version;
+                discovery
+                    .schema_version
+                    .as_deref()
+                    .filter(|v| is_newer_workspace_schema(v))
+                    .map(|v| {
+                        format!(
+                            "workspace.json uses schema {}, newer than supported {}",
+                            v, WORKSPACE_SCHEMA_VERSION
+                        )
+                    })
+            }
+        },
+    };
+
+    discovery.valid = Some(problem.is_none());
+    discovery.problem = problem;
+    Ok(discovery)
+}
+
+/// One top-level settings key whose value differs between two workspaces.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct SettingDifference {
+    pub key: String,
+    /// Value in the first workspace (`None` if the key is absent)
+    pub a: Option<serde_json::Value>,
+    /// Value in the second workspace (`None` if the key is absent)
+    pub b: Option<serde_json::Value>,
+}
+
+/// Layout differences between two workspace.json files. "Added" means
+/// present in `b` but not in `a`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct WorkspaceDiff {
+    /// Differing settings keys, sorted by key
+    pub settings: Vec<SettingDifference>,
+    pub open_nodes_added: Vec<String>,
+    pub open_nodes_removed: Vec<String>,
+    /// `(a, b)` active nodes, when they differ
+    pub active_node: Option<(Option<String>, Option<String>)>,
+    pub tree: TreeDiff,
+}
+
+/// Compares two workspace.json files, e.g. from two synced machines.
+///
+/// # Arguments
+/// * `path_a` - First workspace.json
+/// * `path_b` - Second workspace.json
+///
+/// # Returns
+/// * `Ok(WorkspaceDiff)` - Structured differences (settings, session, tree)
+/// * `Err(HibiscusError)` - If either file cannot be loaded
+///
+/// # Notes
+/// Both files are migrated to the current schema before comparing, so a
+/// legacy file and a current one with the same layout compare equal.
+#[tauri::command]
+pub async fn diff_workspaces(path_a: String, path_b: String) -> Result<WorkspaceDiff, HibiscusError> {
+    let a = load_workspace(path_a, None).await?;
+    let b = load_workspace(path_b, None).await?;
+    Ok(compare_workspaces(&a, &b))
+}
+
+fn compare_workspaces(a: &WorkspaceFile, b: &WorkspaceFile) -> WorkspaceDiff {
+    let settings_of = |workspace: &WorkspaceFile| {
+        workspace
+            .settings
+            .as_ref()
+            .and_then(|settings| settings.as_object())
+            .cloned()
+            .unwrap_or_default()
+    };
+    let (settings_a, settings_b) = (settings_of(a), settings_of(b));
+
+    let keys: BTreeSet<&String> = settings_a.keys().chain(settings_b.keys()).collect();
+    let settings = keys
+        .into_iter()
+        .filter(|key| settings_a.get(*key) != settings_b.get(*key))
+        .map(|key| SettingDifference {
+            key: key.clone(),
+            a: settings_a.get(key).cloned(),
+            b: settings_b.get(key).cloned(),
+        })
+        .collect();
+
+    let open_nodes = |workspace: &WorkspaceFile| -> BTreeSet<String> {
+        workspace
+            .session
+            .as_ref()
+            .and_then(|session| session.open_nodes.clone())
+            .unwrap_or_default()
+            .into_iter()
+            .collect()
+    };
+    let (open_a, open_b) = (open_nodes(a), open_nodes(b));
+
+    let active = |workspace: &WorkspaceFile| {
+        workspace.session.as_ref().and_then(|session| session.active_node.clone())
+    };
+    let (active_a, active_b) = (active(a), active(b));
+
+    WorkspaceDiff {
+        settings,
+        open_nodes_added: open_b.difference(&open_a).cloned().collect(),
+        open_nodes_removed: open_a.difference(&open_b).cloned().collect(),
+        active_node: (active_a != active_b).then_some((active_a, active_b)),
+        tree: diff_trees(&a.tree, &b.tree),
+    }
+}
+
+/// Result of `sync_tree_field`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TreeSyncReport {
+    /// Whether the file stores a tree at all (schema older than 1.2)
+    pub stored: bool,
+    /// Stored tree before the sync vs. the tree written
+    pub diff: TreeDiff,
+}
+
+/// Rebuilds the `tree` stored in workspace.json from disk.
+///
+/// For workspace files that still store their tree and have drifted from
+/// the folder, e.g. after files were added or removed outside the app.
+///
+/// # Arguments
+/// * `path` - Path to the workspace.json file
+///
+/// # Returns
+/// * `Ok(TreeSyncReport)` - Nodes added and removed by the sync
+/// * `Err(HibiscusError)` - If the file cannot be read or written
+///
+/// # Notes
+/// From schema 1.2 on the tree is always read from disk and never stored,
+/// so such files are left untouched and reported with `stored: false`.
+/// The rebuilt tree carries the workspace's decorations and manual order,
+/// like the one `load_workspace` returns with `include_tree`. The file is
+/// read and written through the storage holding it (see `crate::storage`).
+#[tauri::command]
+pub async fn sync_tree_field(path: String) -> Result<TreeSyncReport, HibiscusError> {
+    let path = PathBuf::from(&path);
+    validate_path(&path)?;
+    if !storage_for(&path).metadata(&path).is_ok_and(|metadata| !metadata.is_dir) {
+        return Err(HibiscusError::FileNotFound(
+            "workspace.json not found".into(),
+        ));
     }
+
+    let _guard = WORKSPACE_LOCK.lock().await;
+
+    tokio::task::spawn_blocking(move || {
+        let storage = storage_for(&path);
+        let content = storage
+            .read_to_end(&path)
+            .map_err(|e| HibiscusError::Io(format!("Failed to read workspace.json: {}", e)))?;
+        let mut doc: serde_json::Value = serde_json::from_slice(&content)
+            .map_err(|e| HibiscusError::Workspace(format!("Invalid workspace JSON: {}", e)))?;
+
+        let version = doc.get("schema_version").and_then(|v| v.as_str()).unwrap_or("1.0");
+        if !stores_tree(version) {
+            return Ok(TreeSyncReport { stored: false, diff: TreeDiff::default() });
+        }
+
+        // workspace.json lives in <root>/.hibiscus
+        let Some(root) = path.parent().and_then(Path::parent) else {
+            return Err(HibiscusError::Workspace(
+                "workspace.json is not inside a .hibiscus folder".into(),
+            ));
+        };
+
+        // An unreadable stored tree counts as empty: everything is added
+        let old_tree: Vec<Node> = doc
+            .get("tree")
+            .and_then(|tree| serde_json::from_value(tree.clone()).ok())
+            .unwrap_or_default();
+
+        // Decorations and manual order as the migrated file sees them
+        let mut migrated = doc.clone();
+        crate::migration::migrate_workspace(&mut migrated);
+        let workspace: WorkspaceFile = serde_json::from_value(migrated)
+            .map_err(|e| HibiscusError::Workspace(format!("Failed to parse workspace structure: {}", e)))?;
+        let new_tree = compatibility_tree(root, &workspace);
+
+        let diff = diff_trees(&old_tree, &new_tree);
+        doc["tree"] = serde_json::to_value(&new_tree)?;
+        storage
+            .write_atomic(&path, &mut |writer| Ok(serde_json::to_writer_pretty(writer, &doc)?))
+            .map_err(|e| HibiscusError::Io(format!("Failed to write workspace.json: {}", e)))?;
+        Ok(TreeSyncReport { stored: true, diff })
+    })
+    .await
+    .map_err(|e| HibiscusError::Workspace(format!("Tree sync task failed: {}", e)))?
 }
 
 // =============================================================================
@@ -151,48 +596,308 @@ pub fn discover_workspace(root: String) -> WorkspaceDiscovery {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::references::FileChange;
     use tempfile::tempdir;
     use std::fs;
 
-    #[test]
-    fn test_discover_workspace_found() {
+    #[tokio::test]
+    async fn test_discover_workspace_found() {
         let dir = tempdir().unwrap();
         let hibiscus_dir = dir.path().join(".hibiscus");
         fs::create_dir_all(&hibiscus_dir).unwrap();
         fs::write(hibiscus_dir.join("workspace.json"), "{}").unwrap();
 
-        let result = discover_workspace(dir.path().to_string_lossy().to_string());
+        let result = discover_workspace(dir.path().to_string_lossy().to_string(), None)
+            .await
+            .unwrap();
         assert!(result.found);
         assert!(result.path.is_some());
         assert!(result.path.unwrap().contains("workspace.json"));
     }
 
-    #[test]
-    fn test_discover_workspace_not_found() {
+    #[tokio::test]
+    async fn test_discover_workspace_not_found() {
         let dir = tempdir().unwrap();
-        let result = discover_workspace(dir.path().to_string_lossy().to_string());
+        let result = discover_workspace(dir.path().to_string_lossy().to_string(), None)
+            .await
+            .unwrap();
         assert!(!result.found);
         assert!(result.path.is_none());
     }
 
-    #[test]
-    fn test_discover_workspace_empty_hibiscus_dir() {
+    #[tokio::test]
+    async fn test_discover_workspace_empty_hibiscus_dir() {
         let dir = tempdir().unwrap();
         let hibiscus_dir = dir.path().join(".hibiscus");
         fs::create_dir_all(&hibiscus_dir).unwrap();
         // .hibiscus exists but workspace.json doesn't
 
-        let result = discover_workspace(dir.path().to_string_lossy().to_string());
+        let result = discover_workspace(dir.path().to_string_lossy().to_string(), None)
+            .await
+            .unwrap();
         assert!(!result.found);
     }
 
+    #[tokio::test]
+    async fn test_discover_workspace_reports_corrupt_file() {
+        let dir = tempdir().unwrap();
+        let hibiscus_dir = dir.path().join(".hibiscus");
+        fs::create_dir_all(&hibiscus_dir).unwrap();
+        fs::write(hibiscus_dir.join("workspace.json"), "{ \"schema_version\": ").unwrap();
+
+        let result = discover_workspace(dir.path().to_string_lossy().to_string(), None)
+            .await
+            .unwrap();
+        assert!(result.found);
+        assert_eq!(result.valid, Some(false));
+        assert!(result.problem.unwrap().contains("not valid JSON"));
+    }
+
+    #[tokio::test]
+    async fn test_discover_workspace_reports_future_schema() {
+        let dir = tempdir().unwrap();
+        let hibiscus_dir = dir.path().join(".hibiscus");
+        fs::create_dir_all(&hibiscus_dir).unwrap();
+        fs::write(
+            hibiscus_dir.join("workspace.json"),
+            r#"{ "schema_version": "1.10", "workspace": { "name": "Thesis" }, "tree": [] }"#,
+        )
+        .unwrap();
+
+        let result = discover_workspace(dir.path().to_string_lossy().to_string(), None)
+            .await
+            .unwrap();
+        assert_eq!(result.valid, Some(false));
+        assert_eq!(result.schema_version.as_deref(), Some("1.10"));
+        assert_eq!(result.workspace_name.as_deref(), Some("Thesis"));
+        assert!(result.problem.unwrap().contains("newer than supported"));
+    }
+
+    #[tokio::test]
+    async fn test_discover_workspace_searches_ancestors() {
+        let dir = tempdir().unwrap();
+        let hibiscus_dir = dir.path().join(".hibiscus");
+        fs::create_dir_all(&hibiscus_dir).unwrap();
+        fs::write(
+            hibiscus_dir.join("workspace.json"),
+            r#"{ "schema_version": "1.0", "workspace": { "name": "Vault" } }"#,
+        )
+        .unwrap();
+        let nested = dir.path().join("notes").join("week1");
+        fs::create_dir_all(&nested).unwrap();
+        let nested = nested.to_string_lossy().to_string();
+
+        let direct = discover_workspace(nested.clone(), None).await.unwrap();
+        assert!(!direct.found);
+
+        let result = discover_workspace(nested, Some(true)).await.unwrap();
+        assert!(result.found);
+        assert_eq!(result.valid, Some(true));
+        assert_eq!(result.root, dir.path().to_string_lossy());
+        assert_eq!(result.workspace_name.as_deref(), Some("Vault"));
+    }
+
+    #[tokio::test]
+    async fn test_load_migrates_windows_ids_to_canonical() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("Biology")).unwrap();
+        fs::write(dir.path().join("Biology").join("Week1.md"), "").unwrap();
+
+        let hibiscus_dir = dir.path().join(".hibiscus");
+        fs::create_dir_all(&hibiscus_dir).unwrap();
+        let path = hibiscus_dir.join("workspace.json");
+        let legacy = serde_json::json!({
+            "schema_version": "1.0",
+            "workspace": { "id": "1", "name": "Vault", "root": "C:\\Vault" },
+            "tree": [{
+                "id": "Biology", "name": "Biology", "type": "folder",
+                "children": [{
+                    "id": "Biology\\Week1.md", "name": "Week1.md", "type": "file",
+                    "path": "Biology\\Week1.md"
+                }]
+            }],
+            "session": {
+                "open_nodes": ["Biology\\Week1.md"],
+                "active_node": "Biology\\Week1.md",
+                "cursor": { "Biology\\Week1.md": { "line": 3, "column": 1 } }
+            },
+            "settings": {
+                "favorites": [".\\Biology\\Week1.md"],
+                "bookmarks": [{ "path": "Biology\\Week1.md", "label": "w1" }],
+                "manual_order": { "Biology\\": ["Biology\\Week1.md"] }
+            },
+            "decorations": { "Biology\\Week1.md": { "color": "#f00" } }
+        });
+        fs::write(&path, legacy.to_string()).unwrap();
+
+        let loaded = load_workspace(path.to_string_lossy().to_string(), Some(true)).await.unwrap();
+        let id = "Biology/Week1.md";
+
+        assert_eq!(loaded.schema_version, WORKSPACE_SCHEMA_VERSION);
+        let file_node = &loaded.tree[0].children.as_ref().unwrap()[0];
+        assert_eq!(file_node.id, id);
+        let session = loaded.session.unwrap();
+        assert_eq!(session.open_nodes.unwrap(), vec![id]);
+        assert_eq!(session.active_node.as_deref(), Some(id));
+        assert!(session.cursor.unwrap().contains_key(id));
+        assert!(loaded.decorations.contains_key(id));
+
+        let settings = loaded.settings.unwrap();
+        assert_eq!(loaded.favorites, [id]);
+        assert_eq!(settings["bookmarks"][0]["path"], id);
+        assert_eq!(loaded.manual_order["Biology"], [id]);
+
+        // Functional: the ids match a fresh tree scan and resolve on disk
+        let tree = crate::tree::read_dir_recursive(dir.path(), dir.path(), 5, false);
+        assert_eq!(tree[0].children.as_ref().unwrap()[0].id, id);
+        assert!(crate::ids::from_canonical_id(id, dir.path()).is_file());
+    }
+
+    /// A schema 1.1 workspace.json that still stores its tree: `Notes` is
+    /// hand-ordered, one note has an icon and one is a favorite.
+    fn stored_tree_fixture(root: &Path) -> serde_json::Value {
+        let mut notes: Vec<_> = (0..40)
+            .map(|i| {
+                let rel = format!("Notes/note-{i:02}.md");
+                fs::write(root.join(&rel), "").unwrap();
+                serde_json::json!({ "id": rel, "name": format!("note-{i:02}.md"), "type": "file", "path": rel })
+            })
+            .collect();
+        notes.swap(0, 1);
+        notes[0]["meta"] = serde_json::json!({ "icon": "star", "favorite": true });
+
+        serde_json::json!({
+            "schema_version": "1.1",
+            "workspace": { "id": "1", "name": "Vault", "root": root.to_string_lossy() },
+            "settings": { "favorites": ["Notes/note-05.md"], "theme": "dark" },
+            "tree": [{ "id": "Notes", "name": "Notes", "type": "folder", "children": notes }],
+            "session": { "open_nodes": ["Notes/note-01.md"] },
+            "decorations": { "Notes/note-05.md": { "color": "#f00" } }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_load_moves_stored_tree_into_sections() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("Notes")).unwrap();
+        let path = dir.path().join(".hibiscus").join("workspace.json");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, stored_tree_fixture(dir.path()).to_string()).unwrap();
+        let path_str = path.to_string_lossy().to_string();
+
+        let loaded = load_workspace(path_str.clone(), None).await.unwrap();
+        assert_eq!(loaded.schema_version, WORKSPACE_SCHEMA_VERSION);
+        assert!(loaded.tree.is_empty());
+        assert_eq!(loaded.favorites, ["Notes/note-05.md", "Notes/note-01.md"]);
+        assert_eq!(loaded.manual_order["Notes"][..2], ["Notes/note-01.md", "Notes/note-00.md"]);
+        assert!(!loaded.manual_order.contains_key(""));
+        assert_eq!(loaded.decorations["Notes/note-01.md"].icon.as_deref(), Some("star"));
+        assert_eq!(loaded.decorations["Notes/note-05.md"].color.as_deref(), Some("#f00"));
+        let settings = loaded.settings.as_ref().unwrap();
+        assert!(settings.get("favorites").is_none());
+        assert_eq!(settings["theme"], "dark");
+
+        // Older frontends can still ask for a tree, built from disk
+        let compat = load_workspace(path_str, Some(true)).await.unwrap();
+        let notes = compat.tree[0].children.as_ref().unwrap();
+        assert_eq!(notes.len(), 40);
+        assert_eq!(notes[0].id, "Notes/note-01.md");
+        assert_eq!(notes[0].meta.as_ref().unwrap()["icon"], "star");
+        assert_eq!(notes[2].id, "Notes/note-02.md");
+    }
+
+    #[tokio::test]
+    async fn test_save_after_migration_drops_tree() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("Notes")).unwrap();
+        let path = dir.path().join(".hibiscus").join("workspace.json");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let fixture = serde_json::to_string_pretty(&stored_tree_fixture(dir.path())).unwrap();
+        fs::write(&path, &fixture).unwrap();
+        let path_str = path.to_string_lossy().to_string();
+
+        // Even a frontend that sends the tree back does not get it stored
+        let mut workspace = load_workspace(path_str.clone(), Some(true)).await.unwrap();
+        omit_stored_tree(&mut workspace);
+        let stats = save_workspace_file(&path, workspace).await.unwrap();
+
+        let saved: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(saved.get("tree").is_none());
+        assert_eq!(saved["schema_version"], WORKSPACE_SCHEMA_VERSION);
+        assert!((stats.bytes_written as usize) < fixture.len() / 4);
+
+        let reloaded = load_workspace(path_str, None).await.unwrap();
+        assert_eq!(reloaded.manual_order["Notes"][0], "Notes/note-01.md");
+        assert_eq!(reloaded.favorites.len(), 2);
+
+        // Files declaring an older schema keep the tree on save
+        let mut legacy = workspace_with_tree(dir.path(), generated_tree(3));
+        legacy.schema_version = "1.1".into();
+        omit_stored_tree(&mut legacy);
+        assert_eq!(legacy.tree.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_save_keeps_reconciled_references() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("Notes")).unwrap();
+        let path = dir.path().join(".hibiscus").join("workspace.json");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, stored_tree_fixture(dir.path()).to_string()).unwrap();
+
+        // Saved once as 1.2, loaded by the frontend, then a note is renamed
+        // and another deleted behind its back
+        let path_str = path.to_string_lossy().to_string();
+        let migrated = load_workspace(path_str.clone(), None).await.unwrap();
+        save_workspace_file(&path, migrated).await.unwrap();
+        let stale = load_workspace(path_str.clone(), None).await.unwrap();
+        fs::rename(dir.path().join("Notes/note-05.md"), dir.path().join("Notes/renamed.md")).unwrap();
+        fs::remove_file(dir.path().join("Notes/note-01.md")).unwrap();
+        let changes = [
+            FileChange::Renamed { from: "Notes/note-05.md".into(), to: "Notes/renamed.md".into() },
+            FileChange::Deleted { path: "Notes/note-01.md".into() },
+        ];
+        crate::references::reconcile(dir.path(), &changes).unwrap();
+
+        save_workspace_file(&path, stale).await.unwrap();
+
+        let saved = load_workspace(path_str, None).await.unwrap();
+        assert_eq!(saved.favorites, ["Notes/renamed.md"]);
+        assert!(saved.decorations.contains_key("Notes/renamed.md"));
+        assert!(!saved.decorations.contains_key("Notes/note-01.md"));
+        assert!(!saved.manual_order["Notes"].iter().any(|id| id == "Notes/note-01.md"));
+        assert_eq!(saved.session.unwrap().open_nodes, Some(vec![]));
+    }
+
+    #[tokio::test]
+    async fn test_sync_tree_field_adds_file_from_disk() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("Notes")).unwrap();
+        let path = dir.path().join(".hibiscus").join("workspace.json");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, stored_tree_fixture(dir.path()).to_string()).unwrap();
+        fs::write(dir.path().join("Notes").join("late.md"), "").unwrap();
+
+        let report = sync_tree_field(path.to_string_lossy().to_string()).await.unwrap();
+        assert!(report.stored);
+        assert_eq!(report.diff.added, ["Notes/late.md"]);
+        assert!(report.diff.removed.is_empty());
+
+        let saved: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["schema_version"], "1.1");
+        let notes = saved["tree"][0]["children"].as_array().unwrap();
+        assert_eq!(notes.len(), 41);
+        assert!(notes.iter().any(|note| note["id"] == "Notes/late.md"));
+        assert_eq!(notes[0]["id"], "Notes/note-01.md");
+    }
+
     #[tokio::test]
     async fn test_save_and_load_workspace_roundtrip() {
         let dir = tempdir().unwrap();
         let path = dir.path().join(".hibiscus").join("workspace.json");
 
         let workspace = WorkspaceFile {
-            schema_version: "1.0".to_string(),
+            schema_version: WORKSPACE_SCHEMA_VERSION.to_string(),
             workspace: crate::workspace::WorkspaceInfo {
                 id: "test-id".to_string(),
                 name: "Test Workspace".to_string(),
@@ -203,27 +908,250 @@ mod tests {
             settings: None,
             tree: vec![],
             session: None,
+            decorations: Default::default(),
+            manual_order: Default::default(),
+            favorites: Vec::new(),
         };
 
         // Save
-        let save_result = save_workspace(
-            path.to_string_lossy().to_string(),
-            workspace,
-        ).await;
+        let save_result = save_workspace_file(&path, workspace).await;
         assert!(save_result.is_ok());
 
         // Load
-        let load_result = load_workspace(path.to_string_lossy().to_string()).await;
+        let load_result = load_workspace(path.to_string_lossy().to_string(), None).await;
         assert!(load_result.is_ok());
 
         let loaded = load_result.unwrap();
-        assert_eq!(loaded.schema_version, "1.0");
+        assert_eq!(loaded.schema_version, WORKSPACE_SCHEMA_VERSION);
         assert_eq!(loaded.workspace.name, "Test Workspace");
     }
 
+    fn workspace_with_tree(root: &Path, tree: Vec<crate::workspace::Node>) -> WorkspaceFile {
+        WorkspaceFile {
+            schema_version: WORKSPACE_SCHEMA_VERSION.to_string(),
+            workspace: crate::workspace::WorkspaceInfo {
+                id: "test-id".to_string(),
+                name: "Test Workspace".to_string(),
+                root: root.to_string_lossy().to_string(),
+                created_at: None,
+                updated_at: None,
+            },
+            settings: None,
+            tree,
+            session: None,
+            decorations: Default::default(),
+            manual_order: Default::default(),
+            favorites: Vec::new(),
+        }
+    }
+
+    /// Generates `count` file nodes spread over folders of 100.
+    fn generated_tree(count: usize) -> Vec<crate::workspace::Node> {
+        use crate::workspace::{Node, NodeType};
+
+        (0..count.div_ceil(100))
+            .map(|f| {
+                let children = (0..100.min(count - f * 100))
+                    .map(|i| {
+                        let rel = format!("folder-{f}/note-{i}.md");
+                        Node {
+                            id: rel.clone(),
+                            name: format!("note-{i}.md"),
+                            node_type: NodeType::File,
+                            path: Some(rel),
+                            children: None,
+                            meta: None,
+                        }
+                    })
+                    .collect();
+                Node {
+                    id: format!("folder-{f}"),
+                    name: format!("folder-{f}"),
+                    node_type: NodeType::Folder,
+                    path: None,
+                    children: Some(children),
+                    meta: None,
+                }
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_streaming_save_replaces_atomically() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(".hibiscus").join("workspace.json");
+        let path_str = path.to_string_lossy().to_string();
+
+        save_workspace_file(&path, workspace_with_tree(dir.path(), vec![]))
+            .await
+            .unwrap();
+        let stats = save_workspace_file(&path, workspace_with_tree(dir.path(), generated_tree(250)))
+            .await
+            .unwrap();
+        assert!(stats.backup_rotated);
+        assert_eq!(stats.bytes_written, fs::metadata(&path).unwrap().len());
+
+        // Temp file is renamed away, and the output is still pretty-printed
+        assert!(!path.with_extension("json.tmp").exists());
+        assert!(fs::read_to_string(&path).unwrap().contains("\n  \"tree\": ["));
+
+        let loaded = load_workspace(path_str, None).await.unwrap();
+        assert_eq!(loaded.tree.len(), 3);
+        assert_eq!(loaded.tree[2].children.as_ref().unwrap().len(), 50);
+    }
+
+    /// Large-vault memory check. Run on its own so the process peak is
+    /// attributable to this test:
+    /// `cargo test --release large_vault_memory_profile -- --ignored`
+    #[tokio::test]
+    #[ignore]
+    async fn large_vault_memory_profile() {
+        fn peak_rss_kb() -> Option<u64> {
+            let status = fs::read_to_string("/proc/self/status").ok()?;
+            let line = status.lines().find(|l| l.starts_with("VmHWM:"))?;
+            line.split_whitespace().nth(1)?.parse().ok()
+        }
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(".hibiscus").join("workspace.json");
+        let path_str = path.to_string_lossy().to_string();
+        let workspace = workspace_with_tree(dir.path(), generated_tree(60_000));
+        let baseline = peak_rss_kb().expect("VmHWM in /proc/self/status");
+
+        let stats = save_workspace_file(&path, workspace).await.unwrap();
+        let file_kb = stats.bytes_written / 1024;
+        // Streamed: rendering to a String first would add the whole file
+        let after_save = peak_rss_kb().unwrap();
+        assert!(after_save - baseline < file_kb, "save grew peak RSS by {} KiB", after_save - baseline);
+
+        let loaded = load_workspace(path_str, None).await.unwrap();
+        assert_eq!(loaded.tree.len(), 600);
+        // Parsed from the reader: the DOM costs a few times the file, but no
+        // raw text is kept next to it
+        let after_load = peak_rss_kb().unwrap();
+        assert!(
+            after_load - baseline < 6 * file_kb,
+            "load grew peak RSS by {} KiB for a {} KiB file",
+            after_load - baseline,
+            file_kb
+        );
+    }
+
+    #[tokio::test]
+    async fn test_diff_workspaces_reports_setting_and_open_node() {
+        use crate::workspace::SessionState;
+
+        let dir = tempdir().unwrap();
+        let write = |name: &str, theme: &str, open: &[&str]| {
+            let mut workspace = workspace_with_tree(dir.path(), generated_tree(2));
+            workspace.settings = Some(serde_json::json!({ "theme": theme, "font_size": 14 }));
+            workspace.session = Some(SessionState {
+                open_nodes: Some(open.iter().map(|s| s.to_string()).collect()),
+                active_node: None,
+                cursor: None,
+            });
+            let path = dir.path().join(name);
+            std::fs::write(&path, serde_json::to_string(&workspace).unwrap()).unwrap();
+            path.to_string_lossy().to_string()
+        };
+
+        let a = write("a.json", "dark", &["folder-0/note-0.md"]);
+        let b = write("b.json", "light", &["folder-0/note-0.md", "folder-0/note-1.md"]);
+
+        let diff = diff_workspaces(a.clone(), b).await.unwrap();
+        assert_eq!(
+            diff.settings,
+            vec![SettingDifference {
+                key: "theme".into(),
+                a: Some("dark".into()),
+                b: Some("light".into()),
+            }]
+        );
+        assert_eq!(diff.open_nodes_added, ["folder-0/note-1.md"]);
+        assert!(diff.open_nodes_removed.is_empty());
+        assert!(diff.active_node.is_none());
+        assert_eq!(diff.tree, TreeDiff::default());
+
+        let same = diff_workspaces(a.clone(), a).await.unwrap();
+        assert!(same.settings.is_empty() && same.open_nodes_added.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_load_normalizes_legacy_timestamps() {
+        let dir = tempdir().unwrap();
+        let hibiscus_dir = dir.path().join(".hibiscus");
+        fs::create_dir_all(&hibiscus_dir).unwrap();
+        let path = hibiscus_dir.join("workspace.json");
+        fs::write(
+            &path,
+            serde_json::json!({
+                "schema_version": crate::migration::WORKSPACE_SCHEMA_VERSION,
+                "workspace": {
+                    "id": "1", "name": "Vault", "root": dir.path().to_string_lossy(),
+                    "created_at": "2024-01-05 14:30:00+02:00",
+                    "updated_at": "sometime last week"
+                },
+                "tree": []
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let workspace = load_workspace(path.to_string_lossy().to_string(), None).await.unwrap();
+        assert_eq!(
+            workspace.workspace.created_at.map(|ts| ts.to_string()),
+            Some("2024-01-05T12:30:00Z".to_string())
+        );
+        assert!(workspace.workspace.updated_at.is_none());
+
+        let saved = serde_json::to_value(&workspace).unwrap();
+        assert_eq!(saved["workspace"]["created_at"], "2024-01-05T12:30:00Z");
+    }
+
     #[tokio::test]
     async fn test_load_workspace_file_not_found() {
-        let result = load_workspace("C:\\nonexistent\\workspace.json".to_string()).await;
+        let result = load_workspace("C:\\nonexistent\\workspace.json".to_string(), None).await;
         assert!(result.is_err());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_workspace_round_trips_through_mounted_storage() {
+        use crate::storage::tests::MemoryStorage;
+
+        let root = Path::new("/memory/workspace-test");
+        let vault = MemoryStorage::mount(root);
+        vault.storage.add_file(&root.join("Notes").join("a.md"), b"");
+        let path = root.join(".hibiscus").join("workspace.json");
+
+        let stats = save_workspace_file(&path, workspace_with_tree(root, vec![])).await.unwrap();
+        assert_eq!(stats.bytes_written, vault.storage.contents(&path).unwrap().len() as u64);
+
+        let discovery = discover_workspace(root.to_string_lossy().into(), None).await.unwrap();
+        assert_eq!(discovery.valid, Some(true));
+        let loaded = load_workspace(path.to_string_lossy().into(), Some(true)).await.unwrap();
+        assert_eq!(loaded.tree[0].id, "Notes");
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_sync_tree_field_goes_through_mounted_storage() {
+        use crate::storage::tests::MemoryStorage;
+
+        let root = Path::new("/memory/tree-sync-test");
+        let vault = MemoryStorage::mount(root);
+        vault.storage.add_file(&root.join("Notes").join("a.md"), b"");
+        let path = root.join(".hibiscus").join("workspace.json");
+        let stored = serde_json::json!({
+            "schema_version": "1.1",
+            "workspace": { "id": "1", "name": "Vault", "root": root.to_string_lossy() },
+            "tree": []
+        });
+        vault.storage.add_file(&path, stored.to_string().as_bytes());
+
+        let report = sync_tree_field(path.to_string_lossy().into()).await.unwrap();
+        assert!(report.stored);
+        let saved: serde_json::Value = serde_json::from_slice(&vault.storage.contents(&path).unwrap()).unwrap();
+        assert_eq!(saved["tree"][0]["id"], "Notes");
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,386 @@
+//! ============================================================================
+//! Hibiscus Word Frequencies
+//! ============================================================================
+//!
+//! Word counts across a vault for concordance tools: how often each word
+//! occurs and in how many documents.
+//!
+//! TOKENIZING:
+//! - Words are Unicode word boundaries (`unicode_words`), so punctuation
+//!   and whitespace never become words and `don't` or `naïve` stay whole.
+//! - Markdown notes can be reduced to their text first (frontmatter and
+//!   syntax removed), and words can be lowercased.
+//! - Stopwords: the English list of the knowledge index and/or the caller's
+//!   own, compared case-insensitively.
+//!
+//! MEMORY:
+//! A vault can hold more distinct words than fit in memory, so counting is
+//! an external sort with two passes, both bounded by `cap` entries:
+//! 1. Counts accumulate in a hash map. When it reaches `cap` words it is
+//!    sorted by word and spilled to a run file. The runs are merged by word
+//!    at the end, adding up the entries of a word from different runs.
+//! 2. The merged stream is ranked (count descending, then word) the same
+//!    way: chunks of `cap` entries are sorted and spilled, then merged.
+//!
+//! Without a spill both passes stay in memory. A document is always added
+//! whole to one run, so summing `documents` across runs is exact.
+//!
+//! Run files are tab-separated `count`, `documents`, `word` lines in a
+//! folder owned by the caller.
+//! ============================================================================
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Lines, Write};
+use std::path::{Path, PathBuf};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::knowledge::indexer::is_stopword;
+use crate::markdown::{split_frontmatter, to_plain_text, PlainTextOptions};
+
+/// Tokenizer switches for `export_word_frequencies`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct FrequencyOptions {
+    /// Count `The` and `the` as one word
+    pub lowercase: bool,
+    /// Drop frontmatter and markdown syntax from markdown notes
+    pub strip_markdown: bool,
+    /// Leave out common English words (the knowledge index's list)
+    pub exclude_stopwords: bool,
+    /// More words to leave out
+    pub stopwords: Vec<String>,
+}
+
+impl Default for FrequencyOptions {
+    fn default() -> Self {
+        Self {
+            lowercase: true,
+            strip_markdown: true,
+            exclude_stopwords: false,
+            stopwords: Vec::new(),
+        }
+    }
+}
+
+/// Splits documents into counted words according to `FrequencyOptions`.
+pub struct Tokenizer {
+    options: FrequencyOptions,
+    /// `options.stopwords`, lowercased
+    stopwords: HashSet<String>,
+}
+
+impl Tokenizer {
+    pub fn new(options: FrequencyOptions) -> Self {
+        let stopwords = options.stopwords.iter().map(|word| word.to_lowercase()).collect();
+        Self { options, stopwords }
+    }
+
+    /// Occurrences of each word in one document.
+    pub fn count(&self, content: &str, is_markdown: bool) -> HashMap<String, u64> {
+        let text = if is_markdown && self.options.strip_markdown {
+            let (_, body) = split_frontmatter(content);
+            to_plain_text(body, PlainTextOptions::default())
+        } else {
+            content.to_string()
+        };
+
+        let mut counts = HashMap::new();
+        for word in text.unicode_words() {
+            let lower = word.to_lowercase();
+            if self.is_excluded(&lower) {
+                continue;
+            }
+            let word = if self.options.lowercase { lower } else { word.to_string() };
+            *counts.entry(word).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    fn is_excluded(&self, lower: &str) -> bool {
+        (self.options.exclude_stopwords && is_stopword(lower)) || self.stopwords.contains(lower)
+    }
+}
+
+/// One row of the frequency table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WordStat {
+    pub word: String,
+    /// Occurrences across the vault
+    pub count: u64,
+    /// Documents containing the word
+    pub documents: u64,
+}
+
+/// Order of run files and merges.
+type Order = fn(&WordStat, &WordStat) -> Ordering;
+
+fn by_word(a: &WordStat, b: &WordStat) -> Ordering {
+    a.word.cmp(&b.word)
+}
+
+/// Most frequent first; ties alphabetically.
+fn by_rank(a: &WordStat, b: &WordStat) -> Ordering {
+    b.count.cmp(&a.count).then_with(|| a.word.cmp(&b.word))
+}
+
+/// Stream of frequency rows, in rank order once `finish`ed.
+pub type WordStats = Box<dyn Iterator<Item = io::Result<WordStat>>>;
+
+/// Aggregates per-document counts, spilling to run files in `spill_dir`
+/// whenever `cap` distinct words are held (see the module docs).
+pub struct FrequencyCounter {
+    /// Word -> (count, documents)
+    words: HashMap<String, (u64, u64)>,
+    cap: usize,
+    spill_dir: PathBuf,
+    runs: Vec<PathBuf>,
+    /// Run files written so far, by both passes
+    spilled: usize,
+}
+
+impl FrequencyCounter {
+    pub fn new(spill_dir: &Path, cap: usize) -> Self {
+        Self {
+            words: HashMap::new(),
+            cap: cap.max(1),
+            spill_dir: spill_dir.to_path_buf(),
+            runs: Vec::new(),
+            spilled: 0,
+        }
+    }
+
+    /// Adds the word counts of one document.
+    pub fn add_document(&mut self, counts: HashMap<String, u64>) -> io::Result<()> {
+        for (word, count) in counts {
+            let entry = self.words.entry(word).or_insert((0, 0));
+            entry.0 += count;
+            entry.1 += 1;
+        }
+        if self.words.len() >= self.cap {
+            let mut stats = drain_stats(&mut self.words);
+            stats.sort_by(by_word);
+            let run = self.spill(&stats)?;
+            self.runs.push(run);
+        }
+        Ok(())
+    }
+
+    /// Run files written so far.
+    pub fn spilled_runs(&self) -> usize {
+        self.spilled
+    }
+
+    /// Merges the counts and returns them ranked by count, along with the
+    /// number of run files both passes wrote. The run files must stay in
+    /// place until the stream is consumed.
+    pub fn finish(mut self) -> io::Result<(WordStats, usize)> {
+        let mut stats = drain_stats(&mut self.words);
+
+        // Pass 1: one entry per word
+        let merged: WordStats = if self.runs.is_empty() {
+            Box::new(stats.into_iter().map(Ok))
+        } else {
+            if !stats.is_empty() {
+                stats.sort_by(by_word);
+                let run = self.spill(&stats)?;
+                self.runs.push(run);
+            }
+            let runs = std::mem::take(&mut self.runs);
+            Box::new(combine_words(MergedRuns::open(&runs, by_word)?))
+        };
+
+        // Pass 2: rank in chunks of `cap`
+        let mut chunk = Vec::new();
+        let mut ranked_runs = Vec::new();
+        for stat in merged {
+            chunk.push(stat?);
+            if chunk.len() >= self.cap {
+                chunk.sort_by(by_rank);
+                ranked_runs.push(self.spill(&chunk)?);
+                chunk.clear();
+            }
+        }
+        chunk.sort_by(by_rank);
+
+        if ranked_runs.is_empty() {
+            return Ok((Box::new(chunk.into_iter().map(Ok)), self.spilled));
+        }
+        if !chunk.is_empty() {
+            ranked_runs.push(self.spill(&chunk)?);
+        }
+        Ok((Box::new(MergedRuns::open(&ranked_runs, by_rank)?), self.spilled))
+    }
+
+    /// Writes sorted `stats` to a new run file.
+    fn spill(&mut self, stats: &[WordStat]) -> io::Result<PathBuf> {
+        let path = self.spill_dir.join(format!("run-{}.tsv", self.spilled));
+        let mut writer = BufWriter::new(File::create(&path)?);
+        for stat in stats {
+            writeln!(writer, "{}\t{}\t{}", stat.count, stat.documents, stat.word)?;
+        }
+        writer.flush()?;
+        self.spilled += 1;
+        Ok(path)
+    }
+}
+
+fn drain_stats(words: &mut HashMap<String, (u64, u64)>) -> Vec<WordStat> {
+    words
+        .drain()
+        .map(|(word, (count, documents))| WordStat { word, count, documents })
+        .collect()
+}
+
+/// Reads the entries of one run file back.
+struct RunReader {
+    lines: Lines<BufReader<File>>,
+}
+
+impl Iterator for RunReader {
+    type Item = io::Result<WordStat>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let line = match self.lines.next()? {
+            Ok(line) => line,
+            Err(e) => return Some(Err(e)),
+        };
+        let mut fields = line.splitn(3, '\t');
+        let stat = match (fields.next(), fields.next(), fields.next()) {
+            (Some(count), Some(documents), Some(word)) => count
+                .parse()
+                .ok()
+                .zip(documents.parse().ok())
+                .map(|(count, documents)| WordStat { word: word.to_string(), count, documents }),
+            _ => None,
+        };
+        Some(stat.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("Malformed run line '{}'", line))))
+    }
+}
+
+/// K-way merge of sorted run files. The next entry is picked by scanning
+/// the heads; there are few runs, each `cap` entries long.
+struct MergedRuns {
+    readers: Vec<RunReader>,
+    heads: Vec<Option<WordStat>>,
+    order: Order,
+}
+
+impl MergedRuns {
+    fn open(paths: &[PathBuf], order: Order) -> io::Result<Self> {
+        let mut readers = Vec::with_capacity(paths.len());
+        let mut heads = Vec::with_capacity(paths.len());
+        for path in paths {
+            let mut reader = RunReader { lines: BufReader::new(File::open(path)?).lines() };
+            heads.push(reader.next().transpose()?);
+            readers.push(reader);
+        }
+        Ok(Self { readers, heads, order })
+    }
+}
+
+impl Iterator for MergedRuns {
+    type Item = io::Result<WordStat>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self
+            .heads
+            .iter()
+            .enumerate()
+            .filter_map(|(index, head)| head.as_ref().map(|head| (index, head)))
+            .min_by(|a, b| (self.order)(a.1, b.1))
+            .map(|(index, _)| index)?;
+
+        let stat = self.heads[index].take()?;
+        match self.readers[index].next().transpose() {
+            Ok(next) => self.heads[index] = next,
+            Err(e) => return Some(Err(e)),
+        }
+        Some(Ok(stat))
+    }
+}
+
+/// Adds up consecutive entries of the same word in a by-word stream.
+fn combine_words(stats: impl Iterator<Item = io::Result<WordStat>>) -> impl Iterator<Item = io::Result<WordStat>> {
+    let mut stats = stats.peekable();
+    std::iter::from_fn(move || {
+        let mut stat = match stats.next()? {
+            Ok(stat) => stat,
+            Err(e) => return Some(Err(e)),
+        };
+        while let Some(Ok(next)) = stats.peek() {
+            if next.word != stat.word {
+                break;
+            }
+            stat.count += next.count;
+            stat.documents += next.documents;
+            stats.next();
+        }
+        Some(Ok(stat))
+    })
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranked(counter: FrequencyCounter) -> (Vec<WordStat>, usize) {
+        let (stats, runs) = counter.finish().unwrap();
+        (stats.collect::<io::Result<Vec<_>>>().unwrap(), runs)
+    }
+
+    #[test]
+    fn test_spilled_counts_match_in_memory_counts() {
+        let dir = tempfile::tempdir().unwrap();
+        let tokenizer = Tokenizer::new(FrequencyOptions::default());
+        let documents = [
+            "# Title\n\nThe cat sat on the mat.",
+            "A cat, a dog and a bird. The bird sang.",
+            "Dogs chase cats; the cat naps. Naïve dogs don't.",
+            "Mat, mat, mat!",
+        ];
+
+        let mut in_memory = FrequencyCounter::new(dir.path(), usize::MAX);
+        let mut spilling = FrequencyCounter::new(dir.path(), 2);
+        for document in documents {
+            in_memory.add_document(tokenizer.count(document, true)).unwrap();
+            spilling.add_document(tokenizer.count(document, true)).unwrap();
+        }
+        assert_eq!(in_memory.spilled_runs(), 0);
+        assert!(spilling.spilled_runs() > 0);
+
+        let (expected, no_runs) = ranked(in_memory);
+        let (actual, runs) = ranked(spilling);
+        assert_eq!(no_runs, 0);
+        assert!(runs > 4, "both passes should spill, got {} runs", runs);
+        assert_eq!(actual, expected);
+
+        let top: Vec<(&str, u64, u64)> = expected[..3]
+            .iter()
+            .map(|stat| (stat.word.as_str(), stat.count, stat.documents))
+            .collect();
+        assert_eq!(top, [("mat", 4, 2), ("the", 4, 3), ("a", 3, 1)]);
+        assert!(expected.iter().any(|stat| stat.word == "don't"));
+        assert!(expected.iter().any(|stat| stat.word == "naïve"));
+    }
+
+    #[test]
+    fn test_tokenizer_options() {
+        let options = FrequencyOptions {
+            lowercase: false,
+            strip_markdown: false,
+            exclude_stopwords: true,
+            stopwords: vec!["Cat".into()],
+        };
+        let counts = Tokenizer::new(options).count("**The** Cat and the Hat", true);
+        let mut words: Vec<&str> = counts.keys().map(String::as_str).collect();
+        words.sort();
+        assert_eq!(words, ["Hat"]);
+    }
+}
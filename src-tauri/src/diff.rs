@@ -0,0 +1,319 @@
+//! ============================================================================
+//! Hibiscus Line Diff Engine
+//! ============================================================================
+//!
+//! Minimal line-based diff used for editor gutters and change statistics.
+//!
+//! ALGORITHM:
+//! Myers' O(ND) greedy diff over lines, after trimming the common prefix and
+//! suffix. Trimming keeps the typical "edited a few lines in a big note"
+//! case cheap: the quadratic part only ever sees the changed region.
+//!
+//! OUTPUT:
+//! - `diff_lines` returns a flat edit script (`DiffOp`s) in document order.
+//! - `change_blocks` groups consecutive non-equal ops into blocks, which is
+//!   the shape most consumers (gutters, stats, hunks) actually want.
+//! - `map_line` follows one old line through an edit script (scroll anchors).
+//!
+//! Comparison is by exact line equality; callers normalize first if needed.
+//! ============================================================================
+
+/// A single step in a line edit script.
+///
+/// Indices are 0-based positions in the old and new line slices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffOp {
+    /// Line `old` in the old text equals line `new` in the new text.
+    Equal { old: usize, new: usize },
+    /// Line `old` was removed.
+    Delete { old: usize },
+    /// Line `new` was added.
+    Insert { new: usize },
+}
+
+/// A run of consecutive deletions and/or insertions.
+///
+/// `old_start`/`new_start` are the 0-based positions where the block begins
+/// in each text, even when the corresponding count is zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChangeBlock {
+    pub old_start: usize,
+    pub old_count: usize,
+    pub new_start: usize,
+    pub new_count: usize,
+}
+
+/// Computes a line edit script turning `old` into `new`.
+pub fn diff_lines<T: PartialEq>(old: &[T], new: &[T]) -> Vec<DiffOp> {
+    // Common prefix
+    let prefix = old
+        .iter()
+        .zip(new.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    // Common suffix (not overlapping the prefix)
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut ops: Vec<DiffOp> = (0..prefix)
+        .map(|i| DiffOp::Equal { old: i, new: i })
+        .collect();
+
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+    ops.extend(myers(old_mid, new_mid).into_iter().map(|op| match op {
+        DiffOp::Equal { old, new } => DiffOp::Equal {
+            old: old + prefix,
+            new: new + prefix,
+        },
+        DiffOp::Delete { old } => DiffOp::Delete { old: old + prefix },
+        DiffOp::Insert { new } => DiffOp::Insert { new: new + prefix },
+    }));
+
+    let old_tail = old.len() - suffix;
+    let new_tail = new.len() - suffix;
+    ops.extend((0..suffix).map(|i| DiffOp::Equal {
+        old: old_tail + i,
+        new: new_tail + i,
+    }));
+
+    ops
+}
+
+/// Groups an edit script into change blocks.
+pub fn change_blocks(ops: &[DiffOp]) -> Vec<ChangeBlock> {
+    let mut blocks = Vec::new();
+    let mut current: Option<ChangeBlock> = None;
+    // Next positions in each text, tracked so empty sides still get a start.
+    let (mut old_pos, mut new_pos) = (0, 0);
+
+    for op in ops {
+        match *op {
+            DiffOp::Equal { old, new } => {
+                if let Some(block) = current.take() {
+                    blocks.push(block);
+                }
+                old_pos = old + 1;
+                new_pos = new + 1;
+            }
+            DiffOp::Delete { old } => {
+                let block = current.get_or_insert(ChangeBlock {
+                    old_start: old,
+                    old_count: 0,
+                    new_start: new_pos,
+                    new_count: 0,
+                });
+                block.old_count += 1;
+                old_pos = old + 1;
+            }
+            DiffOp::Insert { new } => {
+                let block = current.get_or_insert(ChangeBlock {
+                    old_start: old_pos,
+                    old_count: 0,
+                    new_start: new,
+                    new_count: 0,
+                });
+                block.new_count += 1;
+                new_pos = new + 1;
+            }
+        }
+    }
+
+    if let Some(block) = current {
+        blocks.push(block);
+    }
+
+    blocks
+}
+
+/// Maps line `old` of the old text to its position in the new text.
+///
+/// Returns the new index and whether the line survived unchanged. A line
+/// that was removed maps to the same offset within its replacement, or to
+/// the first line after the removed block when nothing replaced it
+/// (clamped to the last line of the new text).
+pub fn map_line(ops: &[DiffOp], old: usize) -> (usize, bool) {
+    let exact = ops.iter().find_map(|op| match *op {
+        DiffOp::Equal { old: o, new } if o == old => Some(new),
+        _ => None,
+    });
+    if let Some(new) = exact {
+        return (new, true);
+    }
+
+    let new_len = ops
+        .iter()
+        .filter(|op| !matches!(op, DiffOp::Delete { .. }))
+        .count();
+    let last = new_len.saturating_sub(1);
+
+    let block = change_blocks(ops)
+        .into_iter()
+        .find(|block| old >= block.old_start && old < block.old_start + block.old_count);
+
+    let new = match block {
+        Some(block) if block.new_count > 0 => {
+            block.new_start + (old - block.old_start).min(block.new_count - 1)
+        }
+        Some(block) => block.new_start,
+        // Past the end of the old text
+        None => last,
+    };
+
+    (new.min(last), false)
+}
+
+/// Myers' greedy shortest-edit-script algorithm.
+fn myers<T: PartialEq>(a: &[T], b: &[T]) -> Vec<DiffOp> {
+    let n = a.len() as isize;
+    let m = b.len() as isize;
+    let max = n + m;
+    if max == 0 {
+        return Vec::new();
+    }
+
+    let offset = max;
+    let idx = |k: isize| (k + offset) as usize;
+    let mut v = vec![0isize; 2 * max as usize + 2];
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    'search: for d in 0..=max {
+        trace.push(v.clone());
+        let mut k = -d;
+        while k <= d {
+            let mut x = if k == -d || (k != d && v[idx(k - 1)] < v[idx(k + 1)]) {
+                v[idx(k + 1)]
+            } else {
+                v[idx(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[idx(k)] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+            k += 2;
+        }
+    }
+
+    // Walk the trace backwards to recover the path.
+    let mut ops = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for d in (0..trace.len() as isize).rev() {
+        let v = &trace[d as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && v[idx(k - 1)] < v[idx(k + 1)]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = v[idx(prev_k)];
+        let prev_y = prev_x - prev_k;
+
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            ops.push(DiffOp::Equal {
+                old: x as usize,
+                new: y as usize,
+            });
+        }
+
+        if d > 0 {
+            if x == prev_x {
+                ops.push(DiffOp::Insert {
+                    new: (y - 1) as usize,
+                });
+            } else {
+                ops.push(DiffOp::Delete {
+                    old: (x - 1) as usize,
+                });
+            }
+        }
+
+        x = prev_x;
+        y = prev_y;
+    }
+
+    ops.reverse();
+    ops
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(s: &str) -> Vec<&str> {
+        s.lines().collect()
+    }
+
+    /// Applies an edit script to `old` and checks it reproduces `new`.
+    fn assert_script_valid(old: &[&str], new: &[&str], ops: &[DiffOp]) {
+        let mut rebuilt = Vec::new();
+        let mut deleted = 0;
+        for op in ops {
+            match *op {
+                DiffOp::Equal { old: o, new: n } => {
+                    assert_eq!(old[o], new[n]);
+                    rebuilt.push(old[o]);
+                }
+                DiffOp::Insert { new: n } => rebuilt.push(new[n]),
+                DiffOp::Delete { .. } => deleted += 1,
+            }
+        }
+        assert_eq!(rebuilt, new);
+        let inserted = ops
+            .iter()
+            .filter(|op| matches!(op, DiffOp::Insert { .. }))
+            .count();
+        assert_eq!(rebuilt.len() + deleted, old.len() + inserted);
+    }
+
+    #[test]
+    fn test_identical_texts() {
+        let a = lines("a\nb\nc");
+        let ops = diff_lines(&a, &a);
+        assert!(change_blocks(&ops).is_empty());
+        assert_script_valid(&a, &a, &ops);
+    }
+
+    #[test]
+    fn test_insert_delete_and_modify() {
+        let old = lines("a\nb\nc\nd\ne");
+        let new = lines("a\nB\nc\ne\nf");
+        let ops = diff_lines(&old, &new);
+        assert_script_valid(&old, &new, &ops);
+
+        let blocks = change_blocks(&ops);
+        assert_eq!(blocks.len(), 3);
+        // b -> B (modify)
+        assert_eq!((blocks[0].old_count, blocks[0].new_count), (1, 1));
+        // d removed
+        assert_eq!((blocks[1].old_count, blocks[1].new_count), (1, 0));
+        assert_eq!(blocks[1].new_start, 3);
+        // f appended
+        assert_eq!((blocks[2].old_count, blocks[2].new_count), (0, 1));
+        assert_eq!(blocks[2].new_start, 4);
+    }
+
+    #[test]
+    fn test_empty_sides() {
+        let empty: Vec<&str> = Vec::new();
+        let text = lines("x\ny");
+        assert_eq!(change_blocks(&diff_lines(&empty, &text))[0].new_count, 2);
+        assert_eq!(change_blocks(&diff_lines(&text, &empty))[0].old_count, 2);
+        assert!(diff_lines(&empty, &empty).is_empty());
+    }
+}
@@ -19,7 +19,7 @@ use thiserror::Error;
 ///
 /// This enum covers all possible error cases in the application,
 /// providing typed errors instead of stringly-typed error propagation.
-#[derive(Debug, Error)]
+#[derive(Debug, Clone, Error)]
 pub enum HibiscusError {
     /// File or directory was not found at the specified path
     #[error("File not found: {0}")]
@@ -56,6 +56,58 @@ pub enum HibiscusError {
     /// File watcher errors
     #[error("Watcher error: {0}")]
     Watcher(String),
+
+    /// OCR backend missing or failed
+    #[error("OCR error: {0}")]
+    Ocr(String),
+
+    /// A write is larger than the workspace's `max_file_write_bytes`
+    #[error("Write of {size} bytes exceeds the workspace limit of {limit} bytes")]
+    QuotaExceeded { size: u64, limit: u64 },
+
+    /// A file is larger than the limit for reading it into memory; the
+    /// frontend can retry with a higher limit or open it externally
+    #[error("File '{path}' of {size} bytes exceeds the read limit of {limit} bytes")]
+    FileTooLarge { path: String, size: u64, limit: u64 },
+
+    /// A text operation was asked to handle a file that looks binary
+    #[error("Not a text file: {0}")]
+    BinaryFile(String),
+
+    /// An ignore pattern does not compile; `position` is the 0-based
+    /// character index of the offending character
+    #[error("Invalid pattern '{pattern}' at position {position}: {reason}")]
+    InvalidPattern {
+        pattern: String,
+        position: usize,
+        reason: String,
+    },
+
+    /// The OS trash could not take the item (e.g. no trash on this system);
+    /// the frontend can offer a permanent delete instead
+    #[error("Trash unavailable: {0}")]
+    TrashUnavailable(String),
+
+    /// The parent directory of a destination path does not exist
+    #[error("Parent directory not found: {0}")]
+    ParentNotFound(String),
+
+    /// The target of a create already exists
+    #[error("Already exists: {0}")]
+    AlreadyExists(String),
+
+    /// A file changed on disk since the version a write expected; carries
+    /// the version now on disk (`modified_ms` is 0 if unknown)
+    #[error("File changed on disk since it was read: {path} (modified {modified_ms}, hash {hash})")]
+    Conflict {
+        path: String,
+        modified_ms: u64,
+        hash: String,
+    },
+
+    /// A gated command was called without a valid capability token
+    #[error("Confirmation required: this action needs a '{0}' capability token")]
+    CapabilityRequired(String),
 }
 
 /// Implement From<std::io::Error> for convenient error propagation
@@ -79,8 +131,34 @@ impl Serialize for HibiscusError {
     where
         S: serde::Serializer,
     {
-        // Serialize as the error message string for frontend consumption
-        serializer.serialize_str(&self.to_string())
+        use serde::ser::SerializeStruct;
+
+        match self {
+            // Structured, so the frontend can offer "open anyway" with a
+            // higher limit; `message` keeps it displayable like the rest
+            HibiscusError::FileTooLarge { path, size, limit } => {
+                let mut error = serializer.serialize_struct("FileTooLarge", 5)?;
+                error.serialize_field("kind", "file_too_large")?;
+                error.serialize_field("message", &self.to_string())?;
+                error.serialize_field("path", path)?;
+                error.serialize_field("size", size)?;
+                error.serialize_field("limit", limit)?;
+                error.end()
+            }
+            // Structured, so the frontend can offer to merge with the
+            // version on disk instead of overwriting it
+            HibiscusError::Conflict { path, modified_ms, hash } => {
+                let mut error = serializer.serialize_struct("Conflict", 5)?;
+                error.serialize_field("kind", "conflict")?;
+                error.serialize_field("message", &self.to_string())?;
+                error.serialize_field("path", path)?;
+                error.serialize_field("modified_ms", modified_ms)?;
+                error.serialize_field("hash", hash)?;
+                error.end()
+            }
+            // Serialize as the error message string for frontend consumption
+            _ => serializer.serialize_str(&self.to_string()),
+        }
     }
 }
 
@@ -100,4 +178,28 @@ mod tests {
         let err: HibiscusError = io_err.into();
         assert!(err.to_string().contains("test"));
     }
+
+    #[test]
+    fn test_file_too_large_serializes_with_fields() {
+        let err = HibiscusError::FileTooLarge { path: "/logs/app.log".into(), size: 30, limit: 20 };
+        let value = serde_json::to_value(&err).unwrap();
+        assert_eq!(value["kind"], "file_too_large");
+        assert_eq!(value["path"], "/logs/app.log");
+        assert_eq!((value["size"].as_u64(), value["limit"].as_u64()), (Some(30), Some(20)));
+        assert_eq!(value["message"], err.to_string());
+
+        let other = serde_json::to_value(HibiscusError::Io("disk full".into())).unwrap();
+        assert_eq!(other, "IO error: disk full");
+    }
+
+    #[test]
+    fn test_conflict_serializes_with_fields() {
+        let err = HibiscusError::Conflict { path: "/notes/a.md".into(), modified_ms: 1700, hash: "abc123".into() };
+        let value = serde_json::to_value(&err).unwrap();
+        assert_eq!(value["kind"], "conflict");
+        assert_eq!(value["path"], "/notes/a.md");
+        assert_eq!(value["modified_ms"].as_u64(), Some(1700));
+        assert_eq!(value["hash"], "abc123");
+        assert_eq!(value["message"], err.to_string());
+    }
 }
@@ -0,0 +1,408 @@
+//! ============================================================================
+//! Hibiscus Save Formatting
+//! ============================================================================
+//!
+//! Optional text transformations applied by `write_text_file` just before
+//! the contents hit the disk, configured per save through `SaveFormat`.
+//!
+//! Only leading whitespace is ever rewritten. Whitespace inside a line (and
+//! therefore inside strings or code) is left exactly as typed, and line
+//! endings are preserved.
+//!
+//! Line endings are handled separately (`LineEnding`): the editor works in
+//! `\n`, and `write_text_file` can turn every line break back into the
+//! file's own convention. That is a plain normalization of the whole text,
+//! code fences included; a lone `\r` is left alone.
+//!
+//! JSON files can be shown pretty-printed and saved minified again
+//! (`pretty_print_json`, `SaveFormat::minify_json`). Both only change
+//! whitespace outside strings, so key order and number spelling survive the
+//! round trip; invalid JSON is left untouched.
+//!
+//! Notes can also be tidied on demand (`tidy`, `TidyOptions`): trailing
+//! whitespace trimmed, runs of blank lines collapsed and a final line break
+//! ensured. Unlike save formatting this is an explicit action, and it leaves
+//! fenced code blocks exactly as they are.
+//! ============================================================================
+
+use serde::{Deserialize, Serialize};
+
+/// Formatting options for a single save. Every option is off by default.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SaveFormat {
+    /// Rewrite each line's indentation to tabs or spaces
+    pub convert_indentation: Option<IndentConversion>,
+    /// Remove the whitespace between JSON tokens, for files that were
+    /// minified before `read_text_file` pretty-printed them
+    pub minify_json: bool,
+}
+
+/// Target indentation for `SaveFormat::convert_indentation`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct IndentConversion {
+    pub to: IndentStyle,
+    /// Columns per indentation level (tab width); 0 is treated as 1
+    pub size: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IndentStyle {
+    Tabs,
+    Spaces,
+}
+
+/// Line ending convention of a text file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+}
+
+/// The `line_ending` option of `write_text_file`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEndingMode {
+    Lf,
+    Crlf,
+    /// Whatever the file on disk uses
+    Preserve,
+}
+
+impl LineEnding {
+    /// The more common line ending in `text`; `Lf` on a tie, including
+    /// text without any line break.
+    pub fn detect(text: &str) -> Self {
+        let crlf = text.matches("\r\n").count();
+        let lf = text.matches('\n').count() - crlf;
+        if crlf > lf {
+            LineEnding::Crlf
+        } else {
+            LineEnding::Lf
+        }
+    }
+
+    /// Rewrites every line break in `text` to this ending.
+    pub fn apply(self, text: &str) -> String {
+        let normalized = text.replace("\r\n", "\n");
+        match self {
+            LineEnding::Lf => normalized,
+            LineEnding::Crlf => normalized.replace('\n', "\r\n"),
+        }
+    }
+}
+
+impl SaveFormat {
+    /// Applies every enabled option to `contents`.
+    pub fn apply(&self, contents: String) -> String {
+        let contents = match self.convert_indentation {
+            Some(conversion) => convert_indentation(&contents, conversion),
+            None => contents,
+        };
+        if self.minify_json {
+            minify_json(&contents).unwrap_or(contents)
+        } else {
+            contents
+        }
+    }
+}
+
+/// Rewrites the leading whitespace of every line.
+///
+/// The visual width of the indentation is kept: tabs advance to the next
+/// multiple of `size`. When converting to tabs, a width that is not a whole
+/// number of levels keeps its remainder as spaces.
+pub fn convert_indentation(contents: &str, conversion: IndentConversion) -> String {
+    let size = usize::from(conversion.size.max(1));
+    let mut out = String::with_capacity(contents.len());
+
+    for line in contents.split_inclusive('\n') {
+        let rest = line.trim_start_matches([' ', '\t']);
+        let indent = &line[..line.len() - rest.len()];
+
+        let width = indent.chars().fold(0, |width, c| match c {
+            '\t' => (width / size + 1) * size,
+            _ => width + 1,
+        });
+
+        match conversion.to {
+            IndentStyle::Tabs => {
+                out.extend(std::iter::repeat_n('\t', width / size));
+                out.extend(std::iter::repeat_n(' ', width % size));
+            }
+            IndentStyle::Spaces => out.extend(std::iter::repeat_n(' ', width)),
+        }
+        out.push_str(rest);
+    }
+
+    out
+}
+
+/// Indentation of `pretty_print_json` output.
+const JSON_INDENT: &str = "  ";
+
+/// Pretty-prints JSON text with two-space indentation.
+///
+/// Returns the pretty text and whether the input was minified (a single
+/// line), or `None` if `text` is not valid JSON.
+pub fn pretty_print_json(text: &str) -> Option<(String, bool)> {
+    serde_json::from_str::<serde::de::IgnoredAny>(text).ok()?;
+    let was_minified = !text.trim().contains('\n');
+
+    let mut out = String::with_capacity(text.len() * 2);
+    let mut depth = 0;
+    let mut tokens = json_tokens(text).peekable();
+    while let Some(token) = tokens.next() {
+        match token {
+            "{" | "[" => {
+                out.push_str(token);
+                // Keep empty containers on one line
+                if matches!(tokens.peek(), Some(&"}") | Some(&"]")) {
+                    out.push_str(tokens.next().unwrap_or_default());
+                } else {
+                    depth += 1;
+                    push_json_newline(&mut out, depth);
+                }
+            }
+            "}" | "]" => {
+                depth -= 1;
+                push_json_newline(&mut out, depth);
+                out.push_str(token);
+            }
+            "," => {
+                out.push(',');
+                push_json_newline(&mut out, depth);
+            }
+            ":" => out.push_str(": "),
+            _ => out.push_str(token),
+        }
+    }
+    out.push('\n');
+    Some((out, was_minified))
+}
+
+/// Removes all whitespace between JSON tokens, or `None` if `text` is not
+/// valid JSON.
+pub fn minify_json(text: &str) -> Option<String> {
+    serde_json::from_str::<serde::de::IgnoredAny>(text).ok()?;
+    Some(json_tokens(text).collect())
+}
+
+/// Clean-up steps of `tidy`. Every step is on by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct TidyOptions {
+    /// Remove spaces and tabs at the end of lines
+    pub trim_trailing_whitespace: bool,
+    /// Replace several blank lines in a row with one
+    pub collapse_blank_lines: bool,
+    /// End the note with exactly one line break, dropping blank lines at
+    /// the end
+    pub ensure_final_newline: bool,
+}
+
+impl Default for TidyOptions {
+    fn default() -> Self {
+        Self {
+            trim_trailing_whitespace: true,
+            collapse_blank_lines: true,
+            ensure_final_newline: true,
+        }
+    }
+}
+
+/// Output of `tidy`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tidied {
+    pub content: String,
+    /// Lines trimmed or removed, plus one if a final line break was added
+    pub lines_changed: usize,
+}
+
+/// Applies the enabled `TidyOptions` steps to a note.
+///
+/// Lines inside fenced code blocks (including the closing fence) are kept
+/// verbatim, blank runs and trailing whitespace included. Line endings are
+/// kept; an added final line break uses the note's dominant one.
+pub fn tidy(text: &str, options: TidyOptions) -> Tidied {
+    // (content, line break, inside a fence)
+    let mut lines: Vec<(&str, &str, bool)> = Vec::new();
+    let mut lines_changed = 0;
+    let mut in_fence: Option<&str> = None;
+    let mut previous_blank = false;
+
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\r', '\n']);
+        let line_break = &line[content.len()..];
+        let trimmed = content.trim_start();
+
+        if let Some(fence) = in_fence {
+            if trimmed.starts_with(fence) {
+                in_fence = None;
+            }
+            lines.push((content, line_break, true));
+            previous_blank = false;
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = Some(&trimmed[..3]);
+        }
+
+        let blank = trimmed.is_empty();
+        if blank && previous_blank && options.collapse_blank_lines {
+            lines_changed += 1;
+            continue;
+        }
+        previous_blank = blank;
+
+        let kept = if options.trim_trailing_whitespace { content.trim_end() } else { content };
+        if kept.len() != content.len() {
+            lines_changed += 1;
+        }
+        lines.push((kept, line_break, false));
+    }
+
+    if options.ensure_final_newline {
+        while let Some((content, _, false)) = lines.last() {
+            if !content.trim().is_empty() {
+                break;
+            }
+            lines.pop();
+            lines_changed += 1;
+        }
+        if let Some(last) = lines.last_mut().filter(|last| last.1.is_empty()) {
+            last.1 = match LineEnding::detect(text) {
+                LineEnding::Lf => "\n",
+                LineEnding::Crlf => "\r\n",
+            };
+            lines_changed += 1;
+        }
+    }
+
+    let content = lines.iter().flat_map(|(content, line_break, _)| [*content, *line_break]).collect();
+    Tidied { content, lines_changed }
+}
+
+fn push_json_newline(out: &mut String, depth: usize) {
+    out.push('\n');
+    out.extend(std::iter::repeat_n(JSON_INDENT, depth));
+}
+
+/// Splits JSON text into punctuation, strings and other scalars, dropping
+/// the whitespace between them. Strings are kept verbatim, escapes included.
+fn json_tokens(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        rest = rest.trim_start();
+        let first = rest.chars().next()?;
+        let len = match first {
+            '{' | '}' | '[' | ']' | ',' | ':' => 1,
+            '"' => {
+                let mut escaped = false;
+                let end = rest[1..].find(|c| {
+                    let closes = c == '"' && !escaped;
+                    escaped = c == '\\' && !escaped;
+                    closes
+                });
+                end.map_or(rest.len(), |end| end + 2)
+            }
+            _ => rest
+                .find(|c: char| c.is_whitespace() || "{}[],:\"".contains(c))
+                .unwrap_or(rest.len()),
+        };
+        let (token, tail) = rest.split_at(len);
+        rest = tail;
+        Some(token)
+    })
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABS_4: IndentConversion = IndentConversion { to: IndentStyle::Tabs, size: 4 };
+    const SPACES_4: IndentConversion = IndentConversion { to: IndentStyle::Spaces, size: 4 };
+
+    #[test]
+    fn test_four_spaces_to_tabs() {
+        let source = "fn main() {\n    let s = \"a    b\";\n        nested();\n      odd();\n}\n";
+        assert_eq!(
+            convert_indentation(source, TABS_4),
+            "fn main() {\n\tlet s = \"a    b\";\n\t\tnested();\n\t  odd();\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_tabs_to_four_spaces() {
+        let source = "a\r\n\tb\t c\r\n\t\td\r\n  \te\n";
+        assert_eq!(
+            convert_indentation(source, SPACES_4),
+            "a\r\n    b\t c\r\n        d\r\n    e\n"
+        );
+    }
+
+    #[test]
+    fn test_line_endings_are_detected_and_normalized() {
+        assert_eq!(LineEnding::detect("a\r\nb\r\nc\n"), LineEnding::Crlf);
+        assert_eq!(LineEnding::detect("a\r\nb\n"), LineEnding::Lf);
+        assert_eq!(LineEnding::detect("one line"), LineEnding::Lf);
+        assert_eq!(LineEnding::Crlf.apply("a\nb\r\nc\rd"), "a\r\nb\r\nc\rd");
+        assert_eq!(LineEnding::Lf.apply("a\r\nb\n"), "a\nb\n");
+    }
+
+    #[test]
+    fn test_tidy_collapses_blank_lines_outside_fences_only() {
+        let note = "# Title  \n\n\n\nText\t\n```\ncode  \n\n\n\nmore\n```\n\n\nEnd\n\n\n";
+        let tidied = tidy(note, TidyOptions::default());
+        assert_eq!(tidied.content, "# Title\n\nText\n```\ncode  \n\n\n\nmore\n```\n\nEnd\n");
+        // 2 trimmed lines and 5 dropped blank lines
+        assert_eq!(tidied.lines_changed, 7);
+
+        let unchanged = tidy(&tidied.content, TidyOptions::default());
+        assert_eq!(unchanged, Tidied { content: tidied.content, lines_changed: 0 });
+    }
+
+    #[test]
+    fn test_tidy_steps_are_optional() {
+        let only_newline = TidyOptions {
+            trim_trailing_whitespace: false,
+            collapse_blank_lines: false,
+            ensure_final_newline: true,
+        };
+        let tidied = tidy("a  \r\n\r\n\r\nb", only_newline);
+        assert_eq!(tidied.content, "a  \r\n\r\n\r\nb\r\n");
+        assert_eq!(tidied.lines_changed, 1);
+    }
+
+    #[test]
+    fn test_default_format_is_noop() {
+        let source = "  x\n\ty".to_string();
+        assert_eq!(SaveFormat::default().apply(source.clone()), source);
+    }
+
+    #[test]
+    fn test_minified_json_round_trips_through_pretty_print() {
+        let minified = r#"{"b":1.50,"a":[],"list":[1,{"s":"x, y: {\"z\"}"}],"e":{}}"#;
+
+        let (pretty, was_minified) = pretty_print_json(minified).unwrap();
+        assert!(was_minified);
+        assert_eq!(
+            pretty,
+            "{\n  \"b\": 1.50,\n  \"a\": [],\n  \"list\": [\n    1,\n    {\n      \"s\": \"x, y: {\\\"z\\\"}\"\n    }\n  ],\n  \"e\": {}\n}\n"
+        );
+
+        let format = SaveFormat { minify_json: true, ..Default::default() };
+        assert_eq!(format.apply(pretty.clone()), minified);
+
+        // Already pretty input is reported as such; invalid JSON is kept
+        assert!(!pretty_print_json(&pretty).unwrap().1);
+        assert_eq!(pretty_print_json("{\"a\":"), None);
+        assert_eq!(format.apply("{\"a\": ".into()), "{\"a\": ");
+    }
+}
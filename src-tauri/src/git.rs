@@ -0,0 +1,156 @@
+//! ============================================================================
+//! Hibiscus Git Integration
+//! ============================================================================
+//!
+//! Read-only helpers that shell out to the `git` CLI.
+//!
+//! DESIGN DECISIONS:
+//! - We use the user's installed `git` rather than linking libgit2, so the
+//!   app behaves exactly like the user's own tooling (config, attributes).
+//! - Every helper degrades gracefully: if `git` is missing or the path is
+//!   not inside a repository, callers get `None` rather than an error.
+//!
+//! Git is an optional extra for a notes vault, so nothing here is required
+//! for core functionality.
+//! ============================================================================
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Where a file sits relative to git.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoFile {
+    /// Repository top-level directory.
+    pub toplevel: PathBuf,
+    /// File path relative to `toplevel`, with forward slashes.
+    pub relative: String,
+}
+
+/// Runs `git` with `args` in `dir`, returning stdout on success.
+fn run_git(dir: &Path, args: &[&str]) -> Option<Vec<u8>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()?;
+
+    if output.status.success() {
+        Some(output.stdout)
+    } else {
+        None
+    }
+}
+
+/// Canonicalizes a path that may not exist yet by resolving its parent.
+fn canonicalize_lenient(path: &Path) -> Option<PathBuf> {
+    if let Ok(canonical) = path.canonicalize() {
+        return Some(canonical);
+    }
+    let parent = path.parent()?.canonicalize().ok()?;
+    Some(parent.join(path.file_name()?))
+}
+
+/// Returns the repository top-level directory containing `dir`, if any.
+pub fn repo_toplevel(dir: &Path) -> Option<PathBuf> {
+    let stdout = run_git(dir, &["rev-parse", "--show-toplevel"])?;
+    let toplevel = String::from_utf8(stdout).ok()?;
+    PathBuf::from(toplevel.trim()).canonicalize().ok()
+}
+
+/// Locates `path` inside its enclosing repository.
+///
+/// Returns `None` if git is unavailable or the path is not in a repository.
+pub fn locate(path: &Path) -> Option<RepoFile> {
+    let path = canonicalize_lenient(path)?;
+    let dir = if path.is_dir() { &path } else { path.parent()? };
+    let toplevel = repo_toplevel(dir)?;
+    let relative = path.strip_prefix(&toplevel).ok()?;
+
+    let relative = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    Some(RepoFile { toplevel, relative })
+}
+
+/// Returns the contents of `file` at `HEAD`.
+///
+/// Returns `None` if the file is untracked, the repository has no commits
+/// yet, or the blob is not valid UTF-8.
+pub fn head_contents(file: &RepoFile) -> Option<String> {
+    contents_at(file, "HEAD")
+}
+
+/// Returns the contents of `file` at `revision` (a commit hash or ref).
+///
+/// Returns `None` if the file does not exist at that revision or the blob
+/// is not valid UTF-8.
+pub fn contents_at(file: &RepoFile, revision: &str) -> Option<String> {
+    let spec = format!("{}:{}", revision, file.relative);
+    let stdout = run_git(&file.toplevel, &["show", &spec])?;
+    String::from_utf8(stdout).ok()
+}
+
+/// Returns the commits that changed `file`, oldest first, as pairs of
+/// commit time (milliseconds since the Unix epoch) and hash.
+///
+/// Returns `None` if git is unavailable; an untracked file has no commits.
+pub fn file_history(file: &RepoFile) -> Option<Vec<(i64, String)>> {
+    let stdout = run_git(
+        &file.toplevel,
+        &["log", "--format=%ct %H", "--", &file.relative],
+    )?;
+    let mut commits: Vec<(i64, String)> = String::from_utf8_lossy(&stdout)
+        .lines()
+        .filter_map(|line| {
+            let (time, hash) = line.split_once(' ')?;
+            Some((time.parse::<i64>().ok()? * 1000, hash.to_string()))
+        })
+        .collect();
+    commits.reverse();
+    Some(commits)
+}
+
+/// Returns the working-tree status of every changed file under `dir`.
+///
+/// Keys are paths relative to `dir` with forward slashes; values are the
+/// two-letter porcelain code (e.g. `" M"`, `"A "`, `"??"`). Unchanged files
+/// are absent. Returns `None` if git is unavailable or `dir` is not in a
+/// repository.
+pub fn status(dir: &Path) -> Option<HashMap<String, String>> {
+    let repo = locate(dir)?;
+    let stdout = run_git(
+        &repo.toplevel,
+        &["status", "--porcelain=v1", "-z", "--untracked-files=all"],
+    )?;
+
+    // Porcelain paths are relative to the top level, not to `dir`
+    let prefix = if repo.relative.is_empty() {
+        String::new()
+    } else {
+        format!("{}/", repo.relative)
+    };
+
+    let mut changes = HashMap::new();
+    let mut records = stdout.split(|byte| *byte == 0);
+    while let Some(record) = records.next() {
+        if record.len() < 4 {
+            continue;
+        }
+        let code = String::from_utf8_lossy(&record[..2]).to_string();
+        let path = String::from_utf8_lossy(&record[3..]).to_string();
+
+        // Renames and copies are followed by the original path
+        if code.starts_with(['R', 'C']) {
+            records.next();
+        }
+        if let Some(relative) = path.strip_prefix(&prefix) {
+            changes.insert(relative.to_string(), code);
+        }
+    }
+    Some(changes)
+}
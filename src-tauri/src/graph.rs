@@ -0,0 +1,329 @@
+//! ============================================================================
+//! Hibiscus Note Link Graph
+//! ============================================================================
+//!
+//! Builds the directed graph of links between notes in a workspace.
+//!
+//! RESOLUTION (first match wins):
+//! 1. Markdown links are resolved relative to the linking note's folder.
+//! 2. Wiki-links naming a path (`[[dir/Note]]`) are resolved from the root,
+//!    then relative to the linking note's folder.
+//! 3. Bare wiki-links (`[[Note]]`) prefer a note in the same folder, then
+//!    any note with that name (shortest path, then alphabetical).
+//!
+//! 4. Links matching no note are tried against language variants: `[[topic]]`
+//!    resolves to `topic.de.md` or `topic.en.md` by the preferred languages
+//!    (see `crate::variants`), with the same rules as above.
+//!
+//! The `.md` extension is optional in link targets. Links that resolve to
+//! nothing are counted as unresolved rather than dropped silently.
+//! ============================================================================
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+
+use crate::limits::{LimitHit, Limits};
+use crate::markdown::{extract_links, LinkKind};
+use crate::tree::read_dir_limited;
+use crate::variants::VariantConfig;
+use crate::workspace::{Node, NodeType};
+
+/// Directed link graph over the notes of a workspace.
+///
+/// Note ids are root-relative paths with forward slashes. Edges are
+/// de-duplicated per (source, target) pair and exclude self-links.
+#[derive(Debug, Default)]
+pub struct LinkGraph {
+    /// Every note, whether or not it links or is linked
+    pub notes: BTreeSet<String>,
+    /// source -> targets
+    pub outgoing: BTreeMap<String, BTreeSet<String>>,
+    /// target -> sources
+    pub incoming: BTreeMap<String, BTreeSet<String>>,
+    /// source -> raw targets that matched no note
+    pub unresolved: BTreeMap<String, Vec<String>>,
+    /// Folders left out because the workspace's depth limit was reached
+    pub limits_hit: Vec<LimitHit>,
+}
+
+impl LinkGraph {
+    /// Scans every markdown note under `root` and resolves its links.
+    pub fn build(root: &Path) -> Self {
+        let (tree, limits_hit) = read_dir_limited(root, root, Limits::load(root).tree_depth, false);
+        let mut notes = Vec::new();
+        collect_notes(&tree, &mut notes);
+
+        let contents = notes.iter().filter_map(|rel| {
+            std::fs::read_to_string(root.join(rel))
+                .ok()
+                .map(|content| (rel.replace('\\', "/"), content))
+        });
+
+        Self {
+            limits_hit,
+            ..Self::from_contents_with(contents, &VariantConfig::load(root))
+        }
+    }
+
+    /// Builds a graph from (relative path, content) pairs.
+    pub fn from_contents<I>(notes: I) -> Self
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        Self::from_contents_with(notes, &VariantConfig::default())
+    }
+
+    /// `from_contents` with the workspace's variant settings.
+    pub fn from_contents_with<I>(notes: I, variants: &VariantConfig) -> Self
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let notes: Vec<(String, String)> = notes.into_iter().collect();
+        let resolver = Resolver::new(notes.iter().map(|(path, _)| path.as_str()), variants);
+
+        let mut graph = LinkGraph {
+            notes: notes.iter().map(|(path, _)| path.clone()).collect(),
+            ..Default::default()
+        };
+
+        for (source, content) in &notes {
+            for link in extract_links(content) {
+                match resolver.resolve(source, link.kind, &link.target) {
+                    Some(target) if target != *source => {
+                        graph
+                            .outgoing
+                            .entry(source.clone())
+                            .or_default()
+                            .insert(target.clone());
+                        graph
+                            .incoming
+                            .entry(target)
+                            .or_default()
+                            .insert(source.clone());
+                    }
+                    Some(_) => {}
+                    None => graph
+                        .unresolved
+                        .entry(source.clone())
+                        .or_default()
+                        .push(link.target),
+                }
+            }
+        }
+
+        graph
+    }
+
+    /// Number of distinct notes linking to `note`.
+    pub fn inbound_count(&self, note: &str) -> usize {
+        self.incoming.get(note).map_or(0, BTreeSet::len)
+    }
+}
+
+/// Returns whether a path is a markdown note.
+fn is_note(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .is_some_and(|ext| crate::markdown::is_markdown_extension(&ext.to_string_lossy()))
+}
+
+pub(crate) fn collect_notes(nodes: &[Node], out: &mut Vec<String>) {
+    for node in nodes {
+        match node.node_type {
+            NodeType::File => {
+                if let Some(path) = node.path.as_ref().filter(|p| is_note(p)) {
+                    out.push(path.clone());
+                }
+            }
+            NodeType::Folder => collect_notes(node.children.as_deref().unwrap_or_default(), out),
+        }
+    }
+}
+
+/// Link resolution over the notes, then over their logical variant names.
+struct Resolver {
+    notes: Lookup,
+    /// Logical note paths (`topic.md`) -> the variant they resolve to
+    variants: Lookup,
+}
+
+impl Resolver {
+    fn new<'a>(paths: impl Iterator<Item = &'a str>, config: &VariantConfig) -> Self {
+        let paths: Vec<&str> = paths.collect();
+
+        // Logical path -> (language, variant path)
+        let mut groups: HashMap<String, Vec<(String, &str)>> = HashMap::new();
+        for path in &paths {
+            let (folder, name) = path.rsplit_once('/').map_or(("", *path), |(folder, name)| (folder, name));
+            if let Some((logical, lang)) = config.split(name) {
+                let logical = if folder.is_empty() { logical } else { format!("{}/{}", folder, logical) };
+                groups.entry(logical).or_default().push((lang, path));
+            }
+        }
+        let variants = groups.into_iter().filter_map(|(logical, members)| {
+            let lang = config.choose(members.iter().map(|(lang, _)| lang.as_str()))?;
+            let (_, path) = members.iter().find(|(code, _)| code == lang)?;
+            Some((logical, path.to_string()))
+        });
+
+        Self {
+            notes: Lookup::new(paths.iter().map(|path| (path.to_string(), path.to_string()))),
+            variants: Lookup::new(variants),
+        }
+    }
+
+    fn resolve(&self, source: &str, kind: LinkKind, target: &str) -> Option<String> {
+        self.notes
+            .resolve(source, kind, target)
+            .or_else(|| self.variants.resolve(source, kind, target))
+    }
+}
+
+/// Lookup tables for link resolution.
+struct Lookup {
+    /// Lowercased path without extension -> note path
+    by_path: HashMap<String, String>,
+    /// Lowercased file stem -> note paths (sorted: shortest, then alphabetical)
+    by_stem: HashMap<String, Vec<String>>,
+}
+
+impl Lookup {
+    /// Tables over (name the link matches, note it resolves to) pairs.
+    fn new(entries: impl Iterator<Item = (String, String)>) -> Self {
+        let mut by_path = HashMap::new();
+        let mut by_stem: HashMap<String, Vec<(String, String)>> = HashMap::new();
+
+        for (name, path) in entries {
+            by_path.insert(path_key(&name), path.clone());
+            by_stem.entry(stem_key(&name)).or_default().push((name, path));
+        }
+        let by_stem = by_stem
+            .into_iter()
+            .map(|(stem, mut candidates)| {
+                candidates.sort_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+                (stem, candidates.into_iter().map(|(_, path)| path).collect())
+            })
+            .collect();
+
+        Self { by_path, by_stem }
+    }
+
+    fn resolve(&self, source: &str, kind: LinkKind, target: &str) -> Option<String> {
+        let target = target.replace('\\', "/");
+        let source_dir = source.rsplit_once('/').map_or("", |(dir, _)| dir);
+        let relative = join_normalized(source_dir, &target)?;
+
+        match kind {
+            LinkKind::Markdown => self.by_path.get(&path_key(&relative)).cloned(),
+            LinkKind::Wiki if target.contains('/') => {
+                let from_root = join_normalized("", target.trim_start_matches('/'))?;
+                self.by_path
+                    .get(&path_key(&from_root))
+                    .or_else(|| self.by_path.get(&path_key(&relative)))
+                    .cloned()
+            }
+            LinkKind::Wiki => self.by_path.get(&path_key(&relative)).cloned().or_else(|| {
+                self.by_stem
+                    .get(&stem_key(&target))
+                    .and_then(|candidates| candidates.first().cloned())
+            }),
+        }
+    }
+}
+
+/// Joins `target` onto `dir`, resolving `.` and `..`. `None` if it escapes.
+fn join_normalized(dir: &str, target: &str) -> Option<String> {
+    let mut parts: Vec<&str> = dir.split('/').filter(|p| !p.is_empty()).collect();
+    for part in target.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            part => parts.push(part),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+/// Case-insensitive key for a path with any markdown extension removed.
+fn path_key(path: &str) -> String {
+    let lower = path.to_lowercase();
+    lower
+        .strip_suffix(".md")
+        .or_else(|| lower.strip_suffix(".markdown"))
+        .unwrap_or(&lower)
+        .to_string()
+}
+
+fn stem_key(path: &str) -> String {
+    let key = path_key(path);
+    key.rsplit('/').next().unwrap_or(&key).to_string()
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(notes: &[(&str, &str)]) -> LinkGraph {
+        LinkGraph::from_contents(
+            notes
+                .iter()
+                .map(|(path, content)| (path.to_string(), content.to_string())),
+        )
+    }
+
+    #[test]
+    fn test_resolution_rules() {
+        let g = graph(&[
+            ("a/One.md", "[[Two]] [[b/Three]] [rel](../b/Three.md) [[Missing]] [[One]]"),
+            ("a/Two.md", ""),
+            ("b/Two.md", ""),
+            ("b/Three.md", "[[Two]]"),
+        ]);
+
+        let one: Vec<&str> = g.outgoing["a/One.md"].iter().map(String::as_str).collect();
+        assert_eq!(one, vec!["a/Two.md", "b/Three.md"]);
+        // Same-folder note wins for bare names
+        assert!(g.outgoing["b/Three.md"].contains("b/Two.md"));
+        assert_eq!(g.unresolved["a/One.md"], vec!["Missing"]);
+        // Duplicate links count once; self-links are ignored
+        assert_eq!(g.inbound_count("b/Three.md"), 1);
+        assert_eq!(g.inbound_count("a/One.md"), 0);
+    }
+
+    #[test]
+    fn test_variant_less_links_resolve_to_preferred_language() {
+        let notes = [
+            ("index.md", "[[topic]] [[Guides/setup]] [[intro]] [[v1]]"),
+            ("topic.de.md", ""),
+            ("topic.en.md", ""),
+            ("Guides/setup.fr.md", ""),
+            ("intro.md", ""),
+            ("intro.en.md", ""),
+            ("v1.2.md", ""),
+        ];
+        let notes = notes.iter().map(|(path, content)| (path.to_string(), content.to_string()));
+        let settings = crate::workspace::WorkspaceSettings {
+            preferred_languages: vec!["en".into(), "de".into()],
+            ..Default::default()
+        };
+        let g = LinkGraph::from_contents_with(notes, &VariantConfig::from_settings(&settings));
+
+        let targets: Vec<&str> = g.outgoing["index.md"].iter().map(String::as_str).collect();
+        // Fallback to the only variant; a real note beats its variants
+        assert_eq!(targets, vec!["Guides/setup.fr.md", "intro.md", "topic.en.md"]);
+        // `v1.2.md` is not a variant of `v1`
+        assert_eq!(g.unresolved["index.md"], vec!["v1"]);
+    }
+
+    #[test]
+    fn test_escaping_links_are_unresolved() {
+        let g = graph(&[("One.md", "[up](../../etc/passwd.md)")]);
+        assert_eq!(g.unresolved["One.md"].len(), 1);
+    }
+}
@@ -0,0 +1,321 @@
+//! ============================================================================
+//! Hibiscus Idempotency Keys
+//! ============================================================================
+//!
+//! Replay protection for mutating commands. When the webview reloads in the
+//! middle of an operation it may send the same command again; if the call
+//! carries the same `idempotency_key`, the backend answers with the result
+//! of the first run instead of doing the work twice.
+//!
+//! DESIGN DECISIONS:
+//! - Keys are scoped per command name, so the same key sent to two
+//!   different commands never serves one command's result to the other.
+//! - Only successful results are remembered. A failed call normally had no
+//!   side effect, so retrying it with the same key runs it again.
+//! - The cache is a bounded LRU of `MAX_ENTRIES` results, stored as JSON.
+//!   When the store has a file it is rewritten after every new entry, which
+//!   is cheap at this size and lets keys survive a backend restart.
+//! - A key whose first call is still running is not run concurrently: the
+//!   repeat waits for the first call and answers with its result (or runs
+//!   after all if the first call failed, was cancelled or panicked).
+//! - The file is rewritten atomically on a blocking thread, one save at a
+//!   time, so a slow disk never stalls the async runtime.
+//!
+//! Calls without a key behave exactly as before.
+//! ============================================================================
+
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use tokio::sync::Notify;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::error::HibiscusError;
+use crate::references::write_json_atomic;
+
+/// Number of remembered results across all commands.
+pub const MAX_ENTRIES: usize = 256;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    command: String,
+    key: String,
+    result: serde_json::Value,
+}
+
+#[derive(Default)]
+struct Cache {
+    /// Least recently used first.
+    entries: VecDeque<Entry>,
+    /// Keys whose first call is running, woken when it ends.
+    inflight: HashMap<(String, String), Arc<Notify>>,
+}
+
+impl Cache {
+    /// The result remembered for `id`, marked as most recently used.
+    fn replay(&mut self, id: &(String, String)) -> Option<serde_json::Value> {
+        let index = self
+            .entries
+            .iter()
+            .position(|entry| entry.command == id.0 && entry.key == id.1)?;
+        let entry = self.entries.remove(index)?;
+        let result = entry.result.clone();
+        self.entries.push_back(entry);
+        Some(result)
+    }
+}
+
+/// Recently seen keys and their results, registered as Tauri managed state.
+#[derive(Default)]
+pub struct IdempotencyStore {
+    cache: Mutex<Cache>,
+    file: Option<PathBuf>,
+    /// Held while the file is written, so saves happen one at a time.
+    saving: tokio::sync::Mutex<()>,
+}
+
+impl IdempotencyStore {
+    /// Creates a store persisted to `file`, reading entries saved by a
+    /// previous run. A missing or unreadable file starts empty.
+    pub fn load(file: Option<PathBuf>) -> Self {
+        let entries = file
+            .as_ref()
+            .and_then(|file| std::fs::read_to_string(file).ok())
+            .and_then(|json| serde_json::from_str::<VecDeque<Entry>>(&json).ok())
+            .unwrap_or_default();
+
+        Self {
+            cache: Mutex::new(Cache {
+                entries,
+                inflight: HashMap::new(),
+            }),
+            file,
+            saving: Default::default(),
+        }
+    }
+
+    /// Runs `op` once per `(command, key)`.
+    ///
+    /// Without a key `op` always runs. With a key seen before, the cached
+    /// result is returned and `op` is not called. With a key whose first
+    /// call is still running, waits for that call first.
+    pub async fn run<T, F, Fut>(
+        &self,
+        command: &str,
+        key: Option<&str>,
+        op: F,
+    ) -> Result<T, HibiscusError>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, HibiscusError>>,
+    {
+        let Some(key) = key else {
+            return op().await;
+        };
+        let id = (command.to_string(), key.to_string());
+
+        let inflight = loop {
+            let notify;
+            let finished;
+            {
+                let mut cache = self.lock();
+                if let Some(result) = cache.replay(&id) {
+                    return serde_json::from_value(result).map_err(HibiscusError::from);
+                }
+                match cache.inflight.get(&id) {
+                    Some(running) => {
+                        // Created under the lock, so the wakeup can't be missed
+                        notify = running.clone();
+                        finished = notify.notified();
+                    }
+                    None => {
+                        cache.inflight.insert(id.clone(), Arc::default());
+                        break Inflight { store: self, id: &id };
+                    }
+                }
+            }
+            // Then replay its result, or run if it didn't succeed
+            finished.await;
+        };
+
+        let result = op().await;
+
+        let succeeded = match result.as_ref().map(serde_json::to_value) {
+            Ok(Ok(value)) => {
+                let mut cache = self.lock();
+                cache.entries.push_back(Entry {
+                    command: id.0.clone(),
+                    key: id.1.clone(),
+                    result: value,
+                });
+                while cache.entries.len() > MAX_ENTRIES {
+                    cache.entries.pop_front();
+                }
+                true
+            }
+            _ => false,
+        };
+        drop(inflight);
+
+        if succeeded {
+            self.save().await;
+        }
+
+        result
+    }
+
+    /// Writes the remembered results to the store's file, if it has one.
+    async fn save(&self) {
+        let Some(file) = self.file.clone() else {
+            return;
+        };
+        // Snapshot after taking the turn, so an older save never lands last
+        let _saving = self.saving.lock().await;
+        let entries = self.lock().entries.clone();
+
+        let result = tokio::task::spawn_blocking(move || persist(&file, &entries))
+            .await
+            .map_err(|e| HibiscusError::Io(format!("Idempotency save task failed: {}", e)))
+            .and_then(|saved| saved);
+        // Losing the file only loses replay protection across restarts
+        if let Err(e) = result {
+            eprintln!("[Hibiscus] Warning: Failed to save idempotency keys: {}", e);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Cache> {
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Marks a key as running until dropped, also when its call fails, panics
+/// or is cancelled, then wakes the calls waiting for it.
+struct Inflight<'a> {
+    store: &'a IdempotencyStore,
+    id: &'a (String, String),
+}
+
+impl Drop for Inflight<'_> {
+    fn drop(&mut self) {
+        if let Some(finished) = self.store.lock().inflight.remove(self.id) {
+            finished.notify_waiters();
+        }
+    }
+}
+
+fn persist(file: &Path, entries: &VecDeque<Entry>) -> Result<(), HibiscusError> {
+    if let Some(parent) = file.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    write_json_atomic(file, &serde_json::to_value(entries)?)
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tempfile::tempdir;
+
+    /// Counts its runs and answers with the run number.
+    async fn counted(runs: &AtomicUsize) -> Result<usize, HibiscusError> {
+        Ok(runs.fetch_add(1, Ordering::SeqCst) + 1)
+    }
+
+    #[tokio::test]
+    async fn test_replayed_key_runs_once() {
+        let store = IdempotencyStore::default();
+        let runs = AtomicUsize::new(0);
+
+        let first = store.run("quick", Some("k1"), || counted(&runs)).await.unwrap();
+        let second = store.run("quick", Some("k1"), || counted(&runs)).await.unwrap();
+        assert_eq!((first, second), (1, 1));
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+        // Same key on another command is a different request
+        let other = store.run("other", Some("k1"), || counted(&runs)).await.unwrap();
+        assert_eq!(other, 2);
+
+        // No key always runs
+        store.run("quick", None, || counted(&runs)).await.unwrap();
+        store.run("quick", None, || counted(&runs)).await.unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_failures_are_not_cached() {
+        let store = IdempotencyStore::default();
+        let runs = AtomicUsize::new(0);
+
+        let failed: Result<usize, _> = store
+            .run("quick", Some("k"), || async {
+                runs.fetch_add(1, Ordering::SeqCst);
+                Err(HibiscusError::Io("disk full".into()))
+            })
+            .await;
+        assert!(failed.is_err());
+
+        assert_eq!(store.run("quick", Some("k"), || counted(&runs)).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_repeat_waits_for_the_running_call() {
+        let store = IdempotencyStore::default();
+        let runs = AtomicUsize::new(0);
+        let slow = || async {
+            tokio::task::yield_now().await;
+            counted(&runs).await
+        };
+
+        let (first, repeat) = tokio::join!(
+            store.run("quick", Some("k"), slow),
+            store.run("quick", Some("k"), || counted(&runs)),
+        );
+        assert_eq!((first.unwrap(), repeat.unwrap()), (1, 1));
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_call_releases_its_key() {
+        let store = IdempotencyStore::default();
+        let runs = AtomicUsize::new(0);
+
+        // Never finishes; dropped by the timeout like a cancelled command
+        let pending = store.run("quick", Some("k"), std::future::pending::<Result<usize, HibiscusError>>);
+        assert!(tokio::time::timeout(std::time::Duration::from_millis(10), pending).await.is_err());
+
+        assert_eq!(store.run("quick", Some("k"), || counted(&runs)).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_lru_bound_and_persistence() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("idempotency.json");
+        let runs = AtomicUsize::new(0);
+
+        let store = IdempotencyStore::load(Some(file.clone()));
+        for i in 0..=MAX_ENTRIES {
+            store.run("cmd", Some(&i.to_string()), || counted(&runs)).await.unwrap();
+        }
+
+        // A restarted store still knows recent keys; the oldest was evicted
+        let restarted = IdempotencyStore::load(Some(file));
+        let last = MAX_ENTRIES.to_string();
+        assert_eq!(
+            restarted.run("cmd", Some(&last), || counted(&runs)).await.unwrap(),
+            MAX_ENTRIES + 1
+        );
+        assert_eq!(runs.load(Ordering::SeqCst), MAX_ENTRIES + 1);
+
+        restarted.run("cmd", Some("0"), || counted(&runs)).await.unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), MAX_ENTRIES + 2);
+    }
+}
@@ -0,0 +1,111 @@
+//! ============================================================================
+//! Hibiscus Canonical Node Ids
+//! ============================================================================
+//!
+//! Node ids are workspace-relative paths in one platform-independent form,
+//! so data saved on Windows matches the same files on macOS and Linux.
+//!
+//! CANONICAL FORM:
+//! - Segments separated by `/` (never `\`), no empty or `.` segments
+//! - No leading `./`, no trailing `/`; a leading `/` only on absolute paths
+//!   outside the workspace
+//! - Unicode NFC (macOS file APIs report decomposed NFD names)
+//!
+//! Ids are for matching and storage. To touch the filesystem, turn an id
+//! back into a path with `from_canonical_id`.
+//! ============================================================================
+
+use std::path::{Path, PathBuf};
+use unicode_normalization::UnicodeNormalization;
+
+/// Builds the canonical id of `path` relative to `base`.
+///
+/// Paths outside `base` keep their full form (still canonicalized), which
+/// matches how the tree builder has always treated them.
+pub fn to_canonical_id(path: &Path, base: &Path) -> String {
+    canonicalize_id(&path.strip_prefix(base).unwrap_or(path).to_string_lossy())
+}
+
+/// Brings a stored id (possibly legacy, e.g. `Biology\Week1.md`) into
+/// canonical form.
+///
+/// A leading `/` is kept: it only appears on absolute paths that lie outside
+/// the workspace, and stripping it would make them look relative.
+pub fn canonicalize_id(id: &str) -> String {
+    let id = id.replace('\\', "/");
+    let joined = id
+        .split('/')
+        .filter(|segment| !segment.is_empty() && *segment != ".")
+        .collect::<Vec<_>>()
+        .join("/");
+
+    let prefix = if id.starts_with('/') { "/" } else { "" };
+    format!("{}{}", prefix, joined).nfc().collect()
+}
+
+/// Resolves a canonical id to a path under `base`.
+///
+/// On filesystems that store names byte-for-byte, a file created with a
+/// decomposed (NFD) name does not match its NFC id directly; such segments
+/// are found by comparing the directory's entries in NFC form.
+pub fn from_canonical_id(id: &str, base: &Path) -> PathBuf {
+    let mut path = base.to_path_buf();
+
+    for segment in canonicalize_id(id).split('/').filter(|s| !s.is_empty()) {
+        let direct = path.join(segment);
+        if direct.exists() {
+            path = direct;
+            continue;
+        }
+
+        let matching = std::fs::read_dir(&path).ok().and_then(|entries| {
+            entries.flatten().map(|entry| entry.file_name()).find(|name| {
+                name.to_string_lossy().nfc().eq(segment.chars())
+            })
+        });
+        path = match matching {
+            Some(name) => path.join(name),
+            None => direct,
+        };
+    }
+
+    path
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonicalize_legacy_ids() {
+        assert_eq!(canonicalize_id("Biology\\Week1.md"), "Biology/Week1.md");
+        assert_eq!(canonicalize_id("./notes//a.md/"), "notes/a.md");
+        // "e" + combining acute (NFD) becomes a single "é" (NFC)
+        assert_eq!(canonicalize_id("Caf\u{65}\u{301}.md"), "Caf\u{e9}.md");
+    }
+
+    #[test]
+    fn test_to_canonical_id_relative_to_base() {
+        let base = Path::new("/vault");
+        assert_eq!(to_canonical_id(&base.join("Biology").join("Week1.md"), base), "Biology/Week1.md");
+        assert_eq!(to_canonical_id(Path::new("./a.md"), Path::new("")), "a.md");
+        assert_eq!(to_canonical_id(Path::new("/elsewhere/b.md"), base), "/elsewhere/b.md");
+    }
+
+    #[test]
+    fn test_from_canonical_id_finds_decomposed_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let folder = dir.path().join("Biology");
+        std::fs::create_dir(&folder).unwrap();
+        let nfd = folder.join("Caf\u{65}\u{301}.md");
+        std::fs::write(&nfd, "").unwrap();
+
+        let resolved = from_canonical_id("Biology/Caf\u{e9}.md", dir.path());
+        assert!(resolved.is_file());
+        assert_eq!(to_canonical_id(&resolved, dir.path()), "Biology/Caf\u{e9}.md");
+    }
+}
@@ -0,0 +1,468 @@
+//! ============================================================================
+//! Hibiscus Ignore Rules
+//! ============================================================================
+//!
+//! What the tree leaves out, and why (`explain`):
+//! 1. Built-in names (`BUILTIN_IGNORED`): app and tool folders such as
+//!    `.hibiscus`, `.git` and `node_modules`, at any depth, always.
+//! 2. Hidden entries: names starting with `.`, unless the tree is built
+//!    with `include_hidden`.
+//! 3. Patterns from `<workspace>/.hibiscusignore`, e.g. a `build/` folder
+//!    full of generated files, then those of the `ignore_patterns` setting.
+//!
+//! IGNORE FILE SYNTAX (a subset of .gitignore):
+//! - One glob per line; blank lines and lines starting with `#` are skipped
+//! - `*`, `?`, `[abc]` and `{a,b}` stay within one path segment; `**`
+//!   matches any number of folders
+//! - A pattern without a `/` matches at any depth (`*.log`); one with a `/`
+//!   is relative to the workspace root (`docs/drafts`, `/build`)
+//! - A trailing `/` only matches folders (`build/`)
+//! - A leading `!` re-includes a path an earlier pattern ignored
+//!
+//! DESIGN DECISIONS:
+//! - The ignore file and the setting are optional; without them only steps
+//!   1 and 2 apply.
+//! - An ignored folder is not read at all, so its contents cannot be
+//!   re-included (same as git).
+//! - Invalid lines are logged and skipped rather than failing the tree;
+//!   the settings UI validates patterns up front (`check_pattern`).
+//! - The compiled rules are shared per workspace (`shared_rules`) by the
+//!   tree and the file watcher. Changing the setting or the ignore file
+//!   recompiles them in place, so neither needs a restart.
+//!
+//! The tree applies the rules in `tree::read_dir_limited`.
+//! ============================================================================
+
+use globset::{ErrorKind, Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, RwLock};
+
+use crate::error::HibiscusError;
+use crate::workspace::WorkspaceSettings;
+
+/// Name of the ignore file at the workspace root.
+pub const IGNORE_FILE: &str = ".hibiscusignore";
+
+/// Entry names that are never shown in the tree nor reported by the
+/// file watcher.
+pub const BUILTIN_IGNORED: &[&str] = &[
+    ".hibiscus",
+    ".git",
+    ".vscode",
+    "node_modules",
+    "__pycache__",
+    ".DS_Store",
+    "Thumbs.db",
+];
+
+/// One line of the ignore file.
+#[derive(Debug, Clone)]
+struct Rule {
+    /// The line as written
+    pattern: String,
+    /// 1-based line number; `None` for patterns from the settings
+    line: Option<usize>,
+    /// `!pattern`: the path is shown again
+    negated: bool,
+    /// `pattern/`: only folders match
+    dir_only: bool,
+}
+
+/// Compiled patterns of one workspace's ignore file.
+#[derive(Debug, Clone)]
+pub struct IgnoreRules {
+    globs: GlobSet,
+    /// One entry per glob in `globs`, in file order
+    rules: Vec<Rule>,
+}
+
+impl Default for IgnoreRules {
+    fn default() -> Self {
+        Self {
+            globs: GlobSet::empty(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+impl IgnoreRules {
+    /// Compiles the lines of an ignore file.
+    pub fn parse(content: &str) -> Self {
+        Self::compile(content, &[])
+    }
+
+    /// Compiles the lines of an ignore file followed by the patterns from
+    /// the `ignore_patterns` setting, which therefore win over the file.
+    pub fn compile(content: &str, settings_patterns: &[String]) -> Self {
+        let mut builder = GlobSetBuilder::new();
+        let mut rules = Vec::new();
+
+        let file_lines = content.lines().enumerate().map(|(index, line)| (line, Some(index + 1)));
+        let setting_lines = settings_patterns.iter().map(|pattern| (pattern.as_str(), None));
+
+        for (line, number) in file_lines.chain(setting_lines) {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (negated, dir_only, glob) = translate(line);
+            match build_glob(&glob) {
+                Ok(glob) => {
+                    builder.add(glob);
+                    rules.push(Rule {
+                        pattern: line.to_string(),
+                        line: number,
+                        negated,
+                        dir_only,
+                    });
+                }
+                Err(e) => {
+                    eprintln!("[Hibiscus] Warning: Skipping ignore pattern '{}': {}", line, e);
+                }
+            }
+        }
+
+        match builder.build() {
+            Ok(globs) => Self { globs, rules },
+            Err(e) => {
+                eprintln!("[Hibiscus] Warning: Ignoring {}: {}", IGNORE_FILE, e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Rules of the workspace at `root`: its ignore file (if any) and its
+    /// `ignore_patterns` setting.
+    pub fn load(root: &Path) -> Self {
+        let content = std::fs::read_to_string(root.join(IGNORE_FILE)).unwrap_or_default();
+        Self::compile(&content, &WorkspaceSettings::load(root).ignore_patterns)
+    }
+
+    /// Returns whether `rel_path` (root-relative, `/`-separated) is ignored.
+    /// The last matching pattern decides.
+    pub fn is_ignored(&self, rel_path: &str, is_dir: bool) -> bool {
+        self.deciding_rule(rel_path, is_dir).is_some()
+    }
+
+    /// Returns whether `rel_path` or one of the folders it is in is ignored,
+    /// i.e. whether the tree leaves it out because of a pattern.
+    pub fn is_path_ignored(&self, rel_path: &str, is_dir: bool) -> bool {
+        let components: Vec<&str> = rel_path.split('/').filter(|c| !c.is_empty()).collect();
+        (1..=components.len()).any(|end| {
+            self.is_ignored(&components[..end].join("/"), is_dir || end < components.len())
+        })
+    }
+
+    /// The pattern that ignores `rel_path`, if any.
+    fn deciding_rule(&self, rel_path: &str, is_dir: bool) -> Option<&Rule> {
+        if self.rules.is_empty() {
+            return None;
+        }
+        self.globs
+            .matches(rel_path)
+            .into_iter()
+            .rev()
+            .map(|index| &self.rules[index])
+            .find(|rule| is_dir || !rule.dir_only)
+            .filter(|rule| !rule.negated)
+    }
+}
+
+/// Whether an entry name is on the built-in ignore list.
+pub fn is_builtin_ignored(name: &str) -> bool {
+    BUILTIN_IGNORED.contains(&name)
+}
+
+/// Whether an entry name is hidden (starts with a dot).
+pub fn is_hidden(name: &str) -> bool {
+    name.starts_with('.')
+}
+
+/// Splits an ignore line into (negated, dir_only, glob).
+fn translate(line: &str) -> (bool, bool, String) {
+    let (negated, pattern) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    let (dir_only, pattern) = match pattern.strip_suffix('/') {
+        Some(rest) => (true, rest),
+        None => (false, pattern),
+    };
+    let glob = match pattern.strip_prefix('/') {
+        Some(anchored) => anchored.to_string(),
+        None if pattern.contains('/') => pattern.to_string(),
+        None => format!("**/{}", pattern),
+    };
+    (negated, dir_only, glob)
+}
+
+fn build_glob(glob: &str) -> Result<Glob, globset::Error> {
+    GlobBuilder::new(glob).literal_separator(true).build()
+}
+
+/// Checks one pattern for the `ignore_patterns` setting.
+///
+/// # Returns
+/// * `Ok(())` - If the pattern compiles
+/// * `Err(HibiscusError::InvalidPattern)` - With the 0-based character
+///   position of the offending character in `pattern`
+pub fn check_pattern(pattern: &str) -> Result<(), HibiscusError> {
+    let trimmed = pattern.trim_start();
+    let offset = pattern.chars().count() - trimmed.chars().count();
+    let trimmed = trimmed.trim_end();
+
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+        return Err(HibiscusError::InvalidPattern {
+            pattern: pattern.to_string(),
+            position: offset,
+            reason: "a pattern cannot be empty or a comment".into(),
+        });
+    }
+
+    let (_, _, glob) = translate(trimmed);
+    build_glob(&glob).map(|_| ()).map_err(|e| HibiscusError::InvalidPattern {
+        pattern: pattern.to_string(),
+        position: offset + error_position(trimmed, e.kind()),
+        reason: e.kind().to_string(),
+    })
+}
+
+/// Finds the character of `pattern` that globset's `kind` refers to.
+/// Falls back to 0 for errors without a location.
+fn error_position(pattern: &str, kind: &ErrorKind) -> usize {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut class_start = None;
+    let mut open_alternates = Vec::new();
+
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if class_start.is_none() => {
+                if i + 1 == chars.len() {
+                    return i;
+                }
+                i += 1;
+            }
+            '[' if class_start.is_none() => {
+                class_start = Some(i);
+                // `]` right after `[` or `[!` is a literal
+                if chars.get(i + 1) == Some(&'!') {
+                    i += 1;
+                }
+                if chars.get(i + 1) == Some(&']') {
+                    i += 1;
+                }
+            }
+            ']' if class_start.is_some() => class_start = None,
+            '-' if class_start.is_some() => {
+                if let ErrorKind::InvalidRange(start, end) = kind {
+                    if chars[i - 1] == *start && chars.get(i + 1) == Some(end) {
+                        return i - 1;
+                    }
+                }
+            }
+            '{' if class_start.is_none() => open_alternates.push(i),
+            '}' if class_start.is_none() => {
+                let unopened = open_alternates.pop().is_none();
+                if unopened && *kind == ErrorKind::UnopenedAlternates {
+                    return i;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    match kind {
+        ErrorKind::UnclosedClass => class_start.unwrap_or(0),
+        ErrorKind::UnclosedAlternates => open_alternates.last().copied().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// Compiled rules per workspace root, shared by the tree and the file
+/// watcher so new patterns apply to both without a reload.
+static SHARED_RULES: LazyLock<RwLock<HashMap<PathBuf, Arc<IgnoreRules>>>> = LazyLock::new(Default::default);
+
+/// The shared rules of the workspace at `root`, loaded on first use.
+pub fn shared_rules(root: &Path) -> Arc<IgnoreRules> {
+    if let Some(rules) = SHARED_RULES.read().ok().and_then(|map| map.get(root).cloned()) {
+        return rules;
+    }
+    reload_shared_rules(root)
+}
+
+/// Recompiles the shared rules of `root` from its ignore file and settings,
+/// replacing them in place for every reader.
+pub fn reload_shared_rules(root: &Path) -> Arc<IgnoreRules> {
+    let rules = Arc::new(IgnoreRules::load(root));
+    if let Ok(mut map) = SHARED_RULES.write() {
+        map.insert(root.to_path_buf(), rules.clone());
+    }
+    rules
+}
+
+/// Which step of the pipeline left a path out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IgnoreRule {
+    BuiltIn,
+    Hidden,
+    Pattern,
+}
+
+/// Why a path is or is not in the tree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IgnoreExplanation {
+    pub ignored: bool,
+    /// The rule that left it out
+    pub rule: Option<IgnoreRule>,
+    /// Root-relative path of the entry the rule matched: the path itself
+    /// or the folder it is in
+    pub matched_path: Option<String>,
+    /// The built-in name or ignore-file pattern that matched
+    pub matched: Option<String>,
+    /// Human-readable summary, e.g. "not ignored"
+    pub message: String,
+}
+
+/// Runs `rel_path` (root-relative, `/`-separated) through the same checks
+/// as the tree, folder by folder from the root, and reports the first
+/// rule that leaves it or one of its folders out.
+///
+/// `is_dir` tells whether the last component is a folder.
+pub fn explain(rules: &IgnoreRules, rel_path: &str, is_dir: bool, include_hidden: bool) -> IgnoreExplanation {
+    let components: Vec<&str> = rel_path.split('/').filter(|c| !c.is_empty()).collect();
+
+    for (index, name) in components.iter().enumerate() {
+        let prefix = components[..=index].join("/");
+        let prefix_is_dir = is_dir || index + 1 < components.len();
+
+        let (rule, matched, message) = if is_builtin_ignored(name) {
+            (IgnoreRule::BuiltIn, name.to_string(), format!("'{}' is on the built-in ignore list", name))
+        } else if !include_hidden && is_hidden(name) {
+            (IgnoreRule::Hidden, name.to_string(), format!("'{}' is hidden (starts with a dot)", name))
+        } else if let Some(rule) = rules.deciding_rule(&prefix, prefix_is_dir) {
+            let message = match rule.line {
+                Some(line) => format!(
+                    "'{}' matches pattern '{}' on line {} of {}",
+                    prefix, rule.pattern, line, IGNORE_FILE
+                ),
+                None => format!("'{}' matches pattern '{}' in the workspace settings", prefix, rule.pattern),
+            };
+            (IgnoreRule::Pattern, rule.pattern.clone(), message)
+        } else {
+            continue;
+        };
+
+        return IgnoreExplanation {
+            ignored: true,
+            rule: Some(rule),
+            matched_path: Some(prefix),
+            matched: Some(matched),
+            message,
+        };
+    }
+
+    IgnoreExplanation {
+        ignored: false,
+        rule: None,
+        matched_path: None,
+        matched: None,
+        message: "not ignored".to_string(),
+    }
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gitignore_style_patterns() {
+        let rules = IgnoreRules::parse(
+            "# generated\n\nbuild/\n*.log\n/drafts\ndocs/**/*.tmp\n!keep.log\n",
+        );
+
+        // Folder-only pattern at any depth
+        assert!(rules.is_ignored("build", true));
+        assert!(rules.is_ignored("sub/build", true));
+        assert!(!rules.is_ignored("build", false));
+
+        // Unanchored file pattern, with a later negation
+        assert!(rules.is_ignored("a.log", false));
+        assert!(rules.is_ignored("notes/a.log", false));
+        assert!(!rules.is_ignored("notes/keep.log", false));
+
+        // Anchored to the root
+        assert!(rules.is_ignored("drafts", true));
+        assert!(!rules.is_ignored("notes/drafts", true));
+
+        // `**` spans folders, `*` does not
+        assert!(rules.is_ignored("docs/x.tmp", false));
+        assert!(rules.is_ignored("docs/a/b/x.tmp", false));
+        assert!(!rules.is_ignored("other/x.tmp", false));
+
+        assert!(!IgnoreRules::default().is_ignored("build", true));
+    }
+
+    #[test]
+    fn test_check_pattern_reports_position() {
+        assert!(check_pattern("docs/**/*.tmp").is_ok());
+        assert!(check_pattern("[]]x").is_ok());
+
+        let position = |pattern: &str| match check_pattern(pattern) {
+            Err(HibiscusError::InvalidPattern { position, .. }) => position,
+            other => panic!("expected an invalid pattern, got {:?}", other),
+        };
+        assert_eq!(position("notes/[ab"), 6);
+        assert_eq!(position("a}b"), 1);
+        assert_eq!(position("x{a,{b,c}"), 1);
+        assert_eq!(position("file[z-a]"), 5);
+        assert_eq!(position("  end\\"), 5);
+        assert_eq!(position("   "), 3);
+    }
+
+    #[test]
+    fn test_settings_patterns_follow_the_ignore_file() {
+        let rules = IgnoreRules::compile("*.log\n", &["!keep.log".to_string()]);
+        assert!(rules.is_ignored("a.log", false));
+        assert!(!rules.is_ignored("keep.log", false));
+        assert!(rules.is_path_ignored("a.log/inner.md", false));
+
+        let explanation = explain(&IgnoreRules::compile("", &["drafts/".to_string()]), "drafts/a.md", false, false);
+        assert!(explanation.message.contains("workspace settings"), "{}", explanation.message);
+    }
+
+    #[test]
+    fn test_explain_reports_the_deciding_rule() {
+        let rules = IgnoreRules::parse("# generated\nbuild/\n");
+
+        let dotfile = explain(&rules, "config/.env", false, false);
+        assert_eq!(dotfile.rule, Some(IgnoreRule::Hidden));
+        assert_eq!(dotfile.matched_path.as_deref(), Some("config/.env"));
+        assert!(!explain(&rules, "config/.env", false, true).ignored);
+
+        // A built-in folder hides everything below it
+        let module = explain(&rules, "web/node_modules/react/index.js", false, true);
+        assert_eq!(module.rule, Some(IgnoreRule::BuiltIn));
+        assert_eq!(module.matched_path.as_deref(), Some("web/node_modules"));
+        assert_eq!(module.matched.as_deref(), Some("node_modules"));
+
+        let built = explain(&rules, "app/build/out.txt", false, false);
+        assert_eq!(built.rule, Some(IgnoreRule::Pattern));
+        assert_eq!(built.matched.as_deref(), Some("build/"));
+        assert!(built.message.contains("line 2"), "{}", built.message);
+
+        let normal = explain(&rules, "notes/todo.md", false, false);
+        assert!(!normal.ignored);
+        assert_eq!(normal.rule, None);
+        assert_eq!(normal.message, "not ignored");
+    }
+}
@@ -0,0 +1,145 @@
+//! ============================================================================
+//! Hibiscus Background Jobs
+//! ============================================================================
+//!
+//! Registry for long-running operations that return a job id right away and
+//! finish in the background (e.g. saving a huge workspace.json).
+//!
+//! The registry only tracks progress; each operation runs its own task and
+//! emits its own events. The frontend listens to `job-progress` and can poll
+//! `get_job_status` after missing events (e.g. on reload).
+//! ============================================================================
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+
+/// Finished jobs kept for `get_job_status` before the oldest are dropped.
+const MAX_FINISHED_JOBS: usize = 32;
+
+/// Process-wide job registry.
+pub static JOBS: LazyLock<JobRegistry> = LazyLock::new(JobRegistry::default);
+
+/// Snapshot of a job, also the payload of the `job-progress` event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JobProgress {
+    pub job_id: String,
+    /// What the job does, e.g. "lint_vault"
+    pub kind: String,
+    /// Current step, e.g. "serializing" or "finalizing"
+    pub phase: String,
+    pub done: u64,
+    pub total: u64,
+    pub finished: bool,
+    /// Set when the job finished with an error
+    pub error: Option<String>,
+}
+
+#[derive(Default)]
+pub struct JobRegistry {
+    next_id: AtomicU64,
+    /// Keyed by sequence number so the oldest finished jobs prune first
+    jobs: Mutex<BTreeMap<u64, JobProgress>>,
+}
+
+impl JobRegistry {
+    /// Registers a new job and returns its id.
+    pub fn start(&self, kind: &str) -> String {
+        let seq = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let job_id = format!("{}-{}", kind, seq);
+
+        if let Ok(mut jobs) = self.jobs.lock() {
+            let finished: Vec<u64> = jobs
+                .iter()
+                .filter(|(_, job)| job.finished)
+                .map(|(seq, _)| *seq)
+                .collect();
+            for seq in finished.iter().take(finished.len().saturating_sub(MAX_FINISHED_JOBS - 1)) {
+                jobs.remove(seq);
+            }
+
+            jobs.insert(
+                seq,
+                JobProgress {
+                    job_id: job_id.clone(),
+                    kind: kind.to_string(),
+                    phase: "queued".into(),
+                    done: 0,
+                    total: 0,
+                    finished: false,
+                    error: None,
+                },
+            );
+        }
+
+        job_id
+    }
+
+    /// Records progress and returns the updated snapshot.
+    pub fn update(&self, job_id: &str, phase: &str, done: u64, total: u64) -> Option<JobProgress> {
+        self.modify(job_id, |job| {
+            job.phase = phase.to_string();
+            job.done = done;
+            job.total = total;
+        })
+    }
+
+    /// Marks a job finished, successfully if `error` is `None`.
+    pub fn finish(&self, job_id: &str, error: Option<String>) -> Option<JobProgress> {
+        self.modify(job_id, |job| {
+            job.phase = if error.is_some() { "failed" } else { "done" }.into();
+            if error.is_none() {
+                job.done = job.total;
+            }
+            job.finished = true;
+            job.error = error;
+        })
+    }
+
+    /// Returns the latest snapshot of a job.
+    pub fn get(&self, job_id: &str) -> Option<JobProgress> {
+        let jobs = self.jobs.lock().ok()?;
+        jobs.values().find(|job| job.job_id == job_id).cloned()
+    }
+
+    fn modify(&self, job_id: &str, apply: impl FnOnce(&mut JobProgress)) -> Option<JobProgress> {
+        let mut jobs = self.jobs.lock().ok()?;
+        let job = jobs.values_mut().find(|job| job.job_id == job_id)?;
+        apply(job);
+        Some(job.clone())
+    }
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_lifecycle_and_pruning() {
+        let registry = JobRegistry::default();
+        let id = registry.start("save_workspace");
+
+        let progress = registry.update(&id, "serializing", 5, 10).unwrap();
+        assert_eq!((progress.done, progress.total, progress.finished), (5, 10, false));
+
+        let done = registry.finish(&id, None).unwrap();
+        assert_eq!((done.phase.as_str(), done.done, done.finished), ("done", 10, true));
+        assert_eq!(registry.get(&id), Some(done));
+
+        let failed = registry.start("save_workspace");
+        assert_eq!(registry.finish(&failed, Some("disk full".into())).unwrap().phase, "failed");
+
+        // Only the newest finished jobs are kept
+        for _ in 0..MAX_FINISHED_JOBS {
+            let id = registry.start("save_workspace");
+            registry.finish(&id, None);
+        }
+        assert!(registry.get(&id).is_none());
+        assert!(registry.get("missing").is_none());
+    }
+}
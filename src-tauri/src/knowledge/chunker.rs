@@ -20,7 +20,7 @@
 //!   swapping to a real SHA-256 later if needed.
 //! ============================================================================
 
-use crate::knowledge::types::{Chunk, ParsedDocument};
+use crate::knowledge::types::{Chunk, ChunkSource, ParsedDocument};
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 
@@ -86,6 +86,7 @@ pub fn chunk_document(doc: ParsedDocument) -> Vec<Chunk> {
                 word_count: total_words,
                 content: text,
                 hash,
+                source: ChunkSource::Text,
             });
         } else {
             // Section exceeds MAX_WORDS: split into sub-chunks.
@@ -111,6 +112,7 @@ pub fn chunk_document(doc: ParsedDocument) -> Vec<Chunk> {
                     word_count: sub_words.len(),
                     content: text,
                     hash,
+                    source: ChunkSource::Text,
                 });
                 offset = adjusted_end;
             }
@@ -44,7 +44,7 @@ const STOPWORDS: &[&str] = &[
 /// Returns `true` if the word is a stopword.
 ///
 /// Uses binary search on the sorted STOPWORDS array for O(log n) lookups.
-fn is_stopword(word: &str) -> bool {
+pub(crate) fn is_stopword(word: &str) -> bool {
     STOPWORDS.binary_search(&word).is_ok()
 }
 
@@ -219,6 +219,7 @@ pub fn remove_chunks_from_scored_index(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::knowledge::types::ChunkSource;
 
     #[test]
     fn test_stopword_filtering() {
@@ -246,6 +247,7 @@ mod tests {
             content: "Rust programming language is fast".into(),
             word_count: 5,
             hash: "abc".into(),
+            source: ChunkSource::Text,
         };
         add_chunks_to_index(&mut index, &[chunk]);
         assert!(index.get("rust").unwrap().contains(&"c1".to_string()));
@@ -269,6 +271,7 @@ mod tests {
             content: "Rust programming language".into(),
             word_count: 3,
             hash: "h1".into(),
+            source: ChunkSource::Text,
         };
         let chunk2 = Chunk {
             id: "c2".into(),
@@ -277,6 +280,7 @@ mod tests {
             content: "Rust systems programming".into(),
             word_count: 3,
             hash: "h2".into(),
+            source: ChunkSource::Text,
         };
         add_chunks_to_index(&mut index, &[chunk1, chunk2]);
         let scored = rebuild_scored_index(&index, 2);
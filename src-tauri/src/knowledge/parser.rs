@@ -19,8 +19,9 @@
 //! ============================================================================
 
 use crate::knowledge::types::{ParseError, ParsedDocument, Section};
+use crate::ocr::{self, OcrBackend};
 use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 // ---------------------------------------------------------------------------
 // Parser trait
@@ -361,6 +362,60 @@ impl Parser for DocxParser {
     }
 }
 
+// ---------------------------------------------------------------------------
+// OCR parser
+// ---------------------------------------------------------------------------
+
+/// Parses images by running OCR on them, splitting the recognized text on
+/// blank lines like the PDF parser does.
+///
+/// Not part of `parser_for_path`: images are only indexed when the workspace
+/// enables `settings.ocr_in_search`, so the queue builds this parser itself.
+/// Results go through the `.hibiscus/ocr` cache, so re-indexing an unchanged
+/// image never re-runs recognition.
+///
+/// FAILURE HANDLING: A missing OCR backend is returned as
+/// `ParseError::IoError` carrying the backend's install instructions.
+pub struct OcrParser<B: OcrBackend> {
+    pub root: PathBuf,
+    pub language: String,
+    pub backend: B,
+}
+
+impl<B: OcrBackend> Parser for OcrParser<B> {
+    fn supports(&self, ext: &str) -> bool {
+        ocr::is_ocr_image(Path::new(&format!("image.{}", ext)))
+    }
+
+    fn parse(&self, path: &str) -> Result<ParsedDocument, ParseError> {
+        let result = ocr::extract_text(&self.root, Path::new(path), &self.language, &self.backend)
+            .map_err(|e| ParseError::IoError(format!("{}: {}", path, e)))?;
+
+        let mut sections: Vec<Section> = result
+            .text
+            .split("\n\n")
+            .map(str::trim)
+            .filter(|paragraph| !paragraph.is_empty())
+            .map(|paragraph| Section {
+                heading: None,
+                content: paragraph.to_string(),
+            })
+            .collect();
+
+        if sections.is_empty() {
+            sections.push(Section {
+                heading: None,
+                content: String::new(),
+            });
+        }
+
+        Ok(ParsedDocument {
+            file_path: path.to_string(),
+            sections,
+        })
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Parser registry
 // ---------------------------------------------------------------------------
@@ -393,3 +448,41 @@ pub fn parser_for_path(path: &str) -> Option<Box<dyn Parser>> {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::HibiscusError;
+    use crate::ocr::OcrStatus;
+
+    struct FixedText(&'static str);
+
+    impl OcrBackend for FixedText {
+        fn status(&self) -> OcrStatus {
+            OcrStatus { available: true, version: None, languages: vec!["eng".into()], message: None }
+        }
+
+        fn recognize(&self, _image: &Path, _language: &str) -> Result<String, HibiscusError> {
+            Ok(self.0.to_string())
+        }
+    }
+
+    #[test]
+    fn test_ocr_parser_splits_paragraphs() {
+        let dir = tempfile::tempdir().unwrap();
+        let image = dir.path().join("whiteboard.png");
+        std::fs::write(&image, b"png").unwrap();
+
+        let parser = OcrParser {
+            root: dir.path().to_path_buf(),
+            language: "eng".into(),
+            backend: FixedText("Mitochondria\n\n\nATP synthesis\n"),
+        };
+        assert!(parser.supports("PNG"));
+        assert!(!parser.supports("md"));
+
+        let doc = parser.parse(&image.to_string_lossy()).unwrap();
+        let contents: Vec<&str> = doc.sections.iter().map(|s| s.content.as_str()).collect();
+        assert_eq!(contents, ["Mitochondria", "ATP synthesis"]);
+    }
+}
@@ -147,6 +147,7 @@ fn load_search_results(workspace_root: &str, chunk_ids: &[String]) -> Vec<Search
                 heading: chunk.heading,
                 content: chunk.content,
                 word_count: chunk.word_count,
+                source: chunk.source,
             })
         })
         .collect()
@@ -184,6 +185,7 @@ pub async fn get_chunk(
                 heading: chunk.heading,
                 content: chunk.content,
                 word_count: chunk.word_count,
+                source: chunk.source,
             })
             .ok_or_else(|| format!("Chunk not found: {}", chunk_id))
     })
@@ -405,6 +407,7 @@ fn load_ranked_results_from_pairs(
                 heading: chunk.heading,
                 content: chunk.content,
                 word_count: chunk.word_count,
+                source: chunk.source,
                 score: *score,
             })
         })
@@ -29,8 +29,11 @@ use crate::knowledge::indexer;
 use crate::knowledge::parser;
 use crate::knowledge::storage;
 use crate::knowledge::topics;
-use crate::knowledge::types::{FileEvent, FileEventType, LARGE_FILE_THRESHOLD};
+use crate::knowledge::types::{ChunkSource, FileEvent, FileEventType, LARGE_FILE_THRESHOLD};
+use crate::ocr::{self, TesseractCli};
+use crate::workspace::WorkspaceSettings;
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::{mpsc, Mutex, Semaphore};
 
@@ -248,8 +251,10 @@ fn process_file_event(
             println!("[Knowledge] Removed index data for deleted file: {}", file_path);
         }
         FileEventType::Create | FileEventType::Modify => {
-            // Filter: only process supported file types.
-            if !is_indexable_file(file_path) {
+            // Filter: only process supported file types. Images count only
+            // when the workspace has OCR indexing turned on.
+            let ocr_language = ocr_language_for(workspace_root, file_path);
+            if !is_indexable_file(file_path) && ocr_language.is_none() {
                 return Ok(());
             }
 
@@ -264,6 +269,9 @@ fn process_file_event(
                 );
                 return Ok(());
             }
+            if ocr_language.is_some() && fsize > ocr::OCR_MAX_IMAGE_BYTES {
+                return Ok(());
+            }
 
             // Incremental check: compare file hash to stored hash.
             let current_hash = storage::hash_file(file_path)
@@ -280,9 +288,23 @@ fn process_file_event(
             // File is new or changed. Remove old data first.
             remove_file_data(workspace_root, file_path)?;
 
-            // Parse the file.
-            let parser = parser::parser_for_path(file_path)
-                .ok_or_else(|| format!("No parser for: {}", file_path))?;
+            // Parse the file. OCR'd images get their own parser so their
+            // chunks can be marked as OCR text in search results.
+            let (parser, source): (Box<dyn parser::Parser>, ChunkSource) = match ocr_language {
+                Some(language) => (
+                    Box::new(parser::OcrParser {
+                        root: Path::new(workspace_root).to_path_buf(),
+                        language,
+                        backend: TesseractCli,
+                    }),
+                    ChunkSource::Ocr,
+                ),
+                None => (
+                    parser::parser_for_path(file_path)
+                        .ok_or_else(|| format!("No parser for: {}", file_path))?,
+                    ChunkSource::Text,
+                ),
+            };
 
             let doc = match parser.parse(file_path) {
                 Ok(d) => d,
@@ -294,10 +316,13 @@ fn process_file_event(
             };
 
             // Chunk the parsed document.
-            let chunks = chunker::chunk_document(doc);
+            let mut chunks = chunker::chunk_document(doc);
             if chunks.is_empty() {
                 return Ok(());
             }
+            for chunk in &mut chunks {
+                chunk.source = source;
+            }
 
             // Write chunks to disk.
             let chunk_ids: Vec<String> = chunks.iter().map(|c| c.id.clone()).collect();
@@ -406,6 +431,20 @@ fn is_indexable_file(path: &str) -> bool {
         || lower.ends_with(".docx")
 }
 
+/// Returns the OCR language to index `path` with, or `None` if it is not an
+/// image or the workspace has not enabled `settings.ocr_in_search`.
+fn ocr_language_for(workspace_root: &str, path: &str) -> Option<String> {
+    if !ocr::is_ocr_image(Path::new(path)) {
+        return None;
+    }
+    let settings = WorkspaceSettings::load(Path::new(workspace_root));
+    settings.ocr_in_search.then(|| {
+        settings
+            .ocr_language
+            .unwrap_or_else(|| ocr::DEFAULT_OCR_LANGUAGE.to_string())
+    })
+}
+
 /// Produce a UTC ISO-8601 timestamp string without pulling in the `chrono` crate.
 /// Uses `SystemTime` which is available in std.
 fn chrono_now_iso() -> String {
@@ -476,7 +515,9 @@ fn scan_directory(
 
         if path.is_dir() {
             subdirs.push(path_str);
-        } else if is_indexable_file(&path_str) {
+        } else if is_indexable_file(&path_str)
+            || ocr_language_for(workspace_root, &path_str).is_some()
+        {
             files_to_process.push(path_str);
         }
     }
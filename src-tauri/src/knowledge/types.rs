@@ -88,6 +88,20 @@ pub struct Chunk {
     pub word_count: usize,
     /// SHA-256 hex digest of `content` alone, used for change detection.
     pub hash: String,
+    /// Where the text came from. Chunks stored before OCR existed are text.
+    #[serde(default)]
+    pub source: ChunkSource,
+}
+
+/// Origin of a chunk's text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChunkSource {
+    /// Parsed from a text-bearing file (Markdown, TXT, PDF, DOCX)
+    #[default]
+    Text,
+    /// Recognized in an image by OCR; `file` is the image path
+    Ocr,
 }
 
 // ---------------------------------------------------------------------------
@@ -156,6 +170,8 @@ pub struct SearchResult {
     pub heading: Option<String>,
     pub content: String,
     pub word_count: usize,
+    #[serde(default)]
+    pub source: ChunkSource,
 }
 
 /// Cached query result for the lightweight recent-queries cache.
@@ -242,6 +258,8 @@ pub struct RankedSearchResult {
     pub heading: Option<String>,
     pub content: String,
     pub word_count: usize,
+    #[serde(default)]
+    pub source: ChunkSource,
     /// Composite relevance score. Higher is better.
     /// Incorporates TF-IDF score, exact match boost, and prefix match boost.
     pub score: f64,
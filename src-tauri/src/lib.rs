@@ -12,6 +12,33 @@
 //! - watcher: Filesystem change monitoring
 //! - workspace: Workspace data structures
 //! - knowledge: Local-first knowledge indexing system (Phase 1 + Phase 2)
+//! - references: Stored node reference reconciliation
+//! - markdown: Frontmatter parsing and plain-text rendering
+//! - diff: Line diff engine
+//! - git: Read-only git CLI helpers
+//! - graph: Note link graph
+//! - time_tracking: Focus time aggregation
+//! - ids: Canonical node ids
+//! - format: Save-time text formatting
+//! - ocr: Image text recognition
+//! - jobs: Background job registry
+//! - capabilities: Confirmation tokens for destructive commands
+//! - time: RFC3339 timestamps with lenient legacy parsing
+//! - idempotency: Replay protection for mutating commands
+//! - badges: Tree node badges from pluggable providers
+//! - limits: Configurable depth limits and truncation reports
+//! - review: Spaced-repetition scheduling for notes
+//! - undo: Rename history for single-step undo
+//! - batch: All-or-nothing operation batches
+//! - macros: Named operation chains from workspace settings
+//! - ignore: Tree ignore rules (built-in names, dotfiles, .hibiscusignore)
+//! - writing_progress: Words written per day from history snapshots
+//! - save_group: Journaled all-or-nothing multi-file saves
+//! - variants: Language variants of notes
+//! - lint: Note style rules with fix suggestions
+//! - concordance: Memory-bounded vault word frequencies
+//! - merge: Three-way merge of conflicting saves
+//! - storage: Vault storage backends (local filesystem by default)
 //! ============================================================================
 
 mod commands;
@@ -22,8 +49,40 @@ pub mod workspace;
 pub mod migration;
 pub mod backup;
 pub mod knowledge;
+pub mod references;
+pub mod markdown;
+pub mod diff;
+pub mod git;
+pub mod graph;
+pub mod time_tracking;
+pub mod ids;
+pub mod format;
+pub mod ocr;
+pub mod jobs;
+pub mod capabilities;
+pub mod time;
+pub mod idempotency;
+pub mod badges;
+pub mod limits;
+pub mod review;
+pub mod undo;
+pub mod batch;
+pub mod macros;
+pub mod ignore;
+pub mod writing_progress;
+pub mod save_group;
+pub mod variants;
+pub mod lint;
+pub mod concordance;
+pub mod merge;
+pub mod storage;
 
 use watcher::WatcherState;
+use capabilities::CapabilityStore;
+use idempotency::IdempotencyStore;
+use undo::RenameHistory;
+use commands::TreeBuilds;
+use tauri::Manager;
 use knowledge::queue::{KnowledgeState, spawn_knowledge_worker};
 use std::sync::Arc;
 
@@ -54,12 +113,27 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         // Register managed state for watcher
         .manage(WatcherState::default())
+        // Register managed state for capability tokens
+        .manage(CapabilityStore::default())
+        // Register managed state for rename undo tokens
+        .manage(RenameHistory::default())
+        // Register managed state for coalescing concurrent tree builds
+        .manage(TreeBuilds::default())
         // Register managed state for knowledge indexing system.
         // We manage the Arc directly so that Tauri commands receive
         // State<Arc<KnowledgeState>>, which lets us clone the Arc cheaply.
         .manage(knowledge_state.clone())
         // Setup hook: spawn the knowledge background worker.
-        .setup(move |_app| {
+        .setup(move |app| {
+            // Idempotency keys persist next to the app config so a replay
+            // after a backend restart is still recognized.
+            let idempotency_file = app
+                .path()
+                .app_config_dir()
+                .ok()
+                .map(|dir| dir.join("idempotency.json"));
+            app.manage(IdempotencyStore::load(idempotency_file));
+
             // Spawn the async worker that drains the event channel.
             // It will block (at the Tokio task level, not thread level)
             // until events arrive via the sender.
@@ -71,22 +145,68 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             // File operations (async for non-blocking I/O)
             commands::read_text_file,
+            commands::read_text_file_range,
             commands::read_file_binary,
+            commands::read_binary_file,
+            commands::write_binary_file,
             commands::write_text_file,
+            commands::write_text_files,
             commands::create_file,
             commands::create_folder,
             commands::delete_file,
             commands::delete_folder,
+            commands::delete_path,
             commands::move_node,
+            commands::move_file,
+            commands::copy_path,
+            commands::copy_file,
+            commands::duplicate_file,
+            commands::copy_folder,
+            commands::rename_path,
+            commands::undo_rename,
+            commands::rename_file,
+            commands::stat_paths,
+            commands::get_file_metadata,
+            commands::resolve_symlink,
+            commands::set_hidden_attribute,
             // Workspace operations
             commands::load_workspace,
             commands::save_workspace,
             commands::discover_workspace,
+            commands::bootstrap_workspace,
+            commands::diff_workspaces,
+            commands::sync_tree_field,
+            commands::adopt_folder_as_workspace,
+            commands::init_vault_from_template,
+            commands::get_recent_workspaces,
+            commands::list_volumes,
+            // Capability tokens for destructive commands
+            commands::request_capability,
+            // Backup management
+            commands::list_all_backups,
+            commands::prune_backups,
+            commands::clear_backups,
+            commands::list_backups,
+            commands::restore_backup,
+            // Background jobs
+            commands::get_job_status,
             // Tree builder
             commands::build_tree,
+            commands::build_tree_report,
+            commands::read_dir_shallow,
+            commands::find_long_paths,
+            commands::explain_ignored,
+            commands::preview_ignore_pattern,
+            commands::apply_ignore_patterns,
+            // Node decorations
+            commands::set_node_decoration,
+            commands::clear_node_decoration,
+            // Open routing
+            commands::resolve_opener,
             // File watcher controls
             watcher::watch_workspace,
             watcher::stop_watching,
+            watcher::watch_open_files,
             watcher::is_watching,
             watcher::get_watched_path,
             // Calendar operations
@@ -101,6 +221,69 @@ pub fn run() {
             commands::save_study_data,
             // Unified item creation (per-path locked)
             commands::create_item,
+            // Note creation from unresolved wiki-links, link resolution and insertion
+            commands::create_note_for_link,
+            commands::make_relative_link,
+            commands::resolve_wikilink,
+            commands::paste_image_target,
+            // Stored reference reconciliation
+            commands::reconcile_references,
+            // Vault export
+            commands::export_corpus,
+            // Static site publishing
+            commands::publish_site,
+            // Attachments gallery
+            commands::prefetch_gallery,
+            // Workspace text search
+            commands::search_workspace,
+            // Sharing
+            commands::redact_note,
+            // Image OCR
+            commands::get_ocr_status,
+            commands::extract_image_text,
+            // Vault insights
+            commands::folder_link_metrics,
+            // Focus time tracking
+            commands::record_focus_event,
+            commands::get_time_by_note,
+            commands::get_time_by_day,
+            commands::clear_time_tracking,
+            commands::record_open_duration,
+            commands::get_activity,
+            // Writing statistics
+            commands::word_count_delta,
+            commands::compute_writing_progress,
+            commands::export_stats_csv,
+            commands::export_word_frequencies,
+            // Note outline
+            commands::build_outline,
+            // Note language variants
+            commands::get_note_variants,
+            // Note linting
+            commands::lint_note,
+            commands::apply_lint_fixes,
+            commands::lint_vault,
+            // Note tidying
+            commands::tidy_note,
+            // Note review scheduling
+            commands::mark_note_reviewed,
+            commands::get_due_reviews,
+            commands::set_review_enrollment,
+            commands::scan_review_flags,
+            // Batches and macros
+            commands::run_batch,
+            commands::list_macros,
+            commands::run_macro,
+            // External reload scroll anchoring, unsaved changes
+            commands::reanchor_after_reload,
+            commands::unsaved_diff_stats,
+            // Save conflict merging
+            commands::get_merge_preview,
+            commands::write_merged_result,
+            // Tree node badges
+            commands::get_tree_badges,
+            // Git change gutter
+            commands::get_uncommitted_changes,
             // Knowledge indexing system (Phase 1)
             knowledge::search_knowledge,
             knowledge::get_chunk,
@@ -0,0 +1,104 @@
+//! ============================================================================
+//! Hibiscus Limits
+//! ============================================================================
+//!
+//! The limits that bound directory traversal and path validation, in one
+//! place.
+//!
+//! DESIGN DECISIONS:
+//! - A limit that cuts output short is reported, never silent: traversals
+//!   return a `LimitHit` for every place they stopped, and responses carry
+//!   them as `limits_hit` so the frontend can say "some items were not
+//!   loaded (depth limit)".
+//! - The node count cap is per call (`build_tree`'s `max_nodes`), for
+//!   folders too wide to list in full.
+//! - The tree depth is configurable per workspace (`settings.max_tree_depth`)
+//!   but clamped to `MAX_TREE_DEPTH`, which keeps the deepest reachable file
+//!   under `MAX_PATH_DEPTH`.
+//! - `MAX_PATH_DEPTH` is a safety check in `validate_path` and not
+//!   configurable; it fails the call with an error naming the limit.
+//!
+//! Limit names in `LimitHit::limit` are the constants below.
+//! ============================================================================
+
+use serde::Serialize;
+use std::path::Path;
+
+use crate::workspace::WorkspaceSettings;
+
+/// Name of the directory traversal depth limit.
+pub const TREE_DEPTH: &str = "tree_depth";
+/// Name of the tree node count limit (`TreeOptions::max_nodes`).
+pub const TREE_NODES: &str = "tree_nodes";
+/// Name of the path component limit in `validate_path`.
+pub const PATH_DEPTH: &str = "path_depth";
+
+/// Tree depth used when the workspace does not configure one.
+pub const DEFAULT_TREE_DEPTH: usize = 20;
+/// Hard cap on `settings.max_tree_depth`.
+pub const MAX_TREE_DEPTH: usize = 40;
+/// Maximum number of components in a validated path.
+pub const MAX_PATH_DEPTH: usize = 50;
+
+/// A place where a limit cut output short.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LimitHit {
+    /// Which limit, e.g. `TREE_DEPTH`
+    pub limit: String,
+    /// Root-relative id of the folder whose contents were left out
+    pub path: String,
+    /// The limit's value at the time
+    pub value: usize,
+}
+
+/// Effective limits for one workspace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    pub tree_depth: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            tree_depth: DEFAULT_TREE_DEPTH,
+        }
+    }
+}
+
+impl Limits {
+    /// Applies workspace overrides, clamped to the hard caps.
+    pub fn from_settings(settings: &WorkspaceSettings) -> Self {
+        Self {
+            tree_depth: settings
+                .max_tree_depth
+                .unwrap_or(DEFAULT_TREE_DEPTH)
+                .clamp(1, MAX_TREE_DEPTH),
+        }
+    }
+
+    /// Limits of the workspace at `root` (defaults if it has no settings).
+    pub fn load(root: &Path) -> Self {
+        Self::from_settings(&WorkspaceSettings::load(root))
+    }
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_are_clamped_to_hard_caps() {
+        let limits = |value: serde_json::Value| {
+            Limits::from_settings(&WorkspaceSettings::from_value(Some(&value))).tree_depth
+        };
+
+        assert_eq!(limits(serde_json::json!({})), DEFAULT_TREE_DEPTH);
+        assert_eq!(limits(serde_json::json!({"max_tree_depth": 30})), 30);
+        assert_eq!(limits(serde_json::json!({"max_tree_depth": 1000})), MAX_TREE_DEPTH);
+        assert_eq!(limits(serde_json::json!({"max_tree_depth": 0})), 1);
+    }
+}
@@ -0,0 +1,555 @@
+//! ============================================================================
+//! Hibiscus Note Linting
+//! ============================================================================
+//!
+//! Style rules for shared vaults, checked per note with fix suggestions.
+//!
+//! RULES:
+//! - `single-title`: only the first level-one heading (the title) may be one
+//! - `blank-line-before-heading`: a heading follows a blank line, unless it
+//!   opens the note
+//! - `heading-punctuation`: headings do not end in `.,;:!`
+//! - `relative-links`: markdown links to files are relative to the note
+//!
+//! Rules only see the note body: frontmatter and fenced code are skipped,
+//! as is inline code for link checks.
+//!
+//! SETTINGS:
+//! - `lint.rules`: per-rule `enabled` and `severity` overrides, keyed by
+//!   rule id. Every rule is on by default.
+//!
+//! FIXES:
+//! A diagnostic may carry an edit (byte range + replacement in the linted
+//! text). `apply_fixes` applies several in document order and skips any
+//! edit that overlaps one already applied, so a fix never lands on text
+//! another fix has rewritten.
+//!
+//! Diagnostic ids (`rule@offset`) are stable for unchanged content, so the
+//! frontend can name the fixes to apply after showing them.
+//! ============================================================================
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::format::LineEnding;
+use crate::markdown::{relative_link, split_frontmatter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+/// Lint configuration, stored as the `lint` workspace setting.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LintSettings {
+    /// Overrides by rule id; unlisted rules run with their defaults
+    pub rules: BTreeMap<String, RuleOverride>,
+}
+
+/// Changes to one rule's defaults.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuleOverride {
+    pub enabled: Option<bool>,
+    pub severity: Option<Severity>,
+}
+
+/// Replaces the bytes `start..end` of the linted text with `replacement`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LintFix {
+    pub start: usize,
+    pub end: usize,
+    pub replacement: String,
+}
+
+/// One rule violation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LintDiagnostic {
+    /// `rule@start`, unique within a note
+    pub id: String,
+    pub rule: String,
+    pub severity: Severity,
+    /// Byte range of the offending text
+    pub start: usize,
+    pub end: usize,
+    /// 1-based line of `start`
+    pub line: usize,
+    pub message: String,
+    pub fix: Option<LintFix>,
+}
+
+/// Where the linted text lives, for rules whose fixes depend on it.
+#[derive(Debug, Clone, Default)]
+pub struct LintContext {
+    /// Absolute workspace root, `/`-separated
+    pub root: Option<String>,
+    /// Folder of the note relative to the root, `/`-separated
+    pub note_dir: Option<String>,
+}
+
+/// A violation found by a rule, before severity and id are attached.
+struct Finding {
+    start: usize,
+    end: usize,
+    message: String,
+    fix: Option<LintFix>,
+}
+
+trait Rule: Sync {
+    fn id(&self) -> &'static str;
+    fn default_severity(&self) -> Severity;
+    fn check(&self, note: &Note, context: &LintContext, out: &mut Vec<Finding>);
+}
+
+/// Every rule, in the order diagnostics at the same offset are reported.
+static RULES: &[&dyn Rule] = &[&BlankLineBeforeHeading, &SingleTitle, &HeadingPunctuation, &RelativeLinks];
+
+/// Checks `content` against the rules `settings` enables.
+///
+/// Diagnostics are sorted by offset.
+pub fn lint(content: &str, settings: &LintSettings, context: &LintContext) -> Vec<LintDiagnostic> {
+    let note = Note::parse(content);
+    let mut diagnostics = Vec::new();
+
+    for rule in RULES {
+        let overrides = settings.rules.get(rule.id()).copied().unwrap_or_default();
+        if overrides.enabled == Some(false) {
+            continue;
+        }
+        let severity = overrides.severity.unwrap_or_else(|| rule.default_severity());
+
+        let mut findings = Vec::new();
+        rule.check(&note, context, &mut findings);
+        diagnostics.extend(findings.into_iter().map(|finding| LintDiagnostic {
+            id: format!("{}@{}", rule.id(), finding.start),
+            rule: rule.id().to_string(),
+            severity,
+            start: finding.start,
+            end: finding.end,
+            line: content[..finding.start].matches('\n').count() + 1,
+            message: finding.message,
+            fix: finding.fix,
+        }));
+    }
+
+    // Stable: rule order breaks ties
+    diagnostics.sort_by_key(|diagnostic| diagnostic.start);
+    diagnostics
+}
+
+/// Result of `apply_fixes`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixOutcome {
+    pub content: String,
+    /// Ids of the diagnostics whose fix was applied
+    pub applied: Vec<String>,
+    /// Ids without a fix or whose fix overlapped an applied one
+    pub skipped: Vec<String>,
+}
+
+/// Applies the fixes of `diagnostics` to the text they were computed on.
+///
+/// Fixes are applied in order of their start, each at its position in the
+/// text as rewritten so far. A fix overlapping an applied one is skipped;
+/// insertions at the same offset all apply, in the given order.
+pub fn apply_fixes(content: &str, diagnostics: &[&LintDiagnostic]) -> FixOutcome {
+    let mut fixes: Vec<(&str, &LintFix)> = Vec::new();
+    let mut skipped = Vec::new();
+    for diagnostic in diagnostics {
+        match &diagnostic.fix {
+            Some(fix) if fix.start <= fix.end && fix.end <= content.len() => fixes.push((&diagnostic.id, fix)),
+            _ => skipped.push(diagnostic.id.clone()),
+        }
+    }
+    fixes.sort_by_key(|(_, fix)| (fix.start, fix.end));
+
+    let mut out = String::with_capacity(content.len());
+    let mut applied = Vec::new();
+    // End of the last applied fix, in the original text
+    let mut cursor = 0;
+    for (id, fix) in fixes {
+        if fix.start < cursor {
+            skipped.push(id.to_string());
+            continue;
+        }
+        out.push_str(&content[cursor..fix.start]);
+        out.push_str(&fix.replacement);
+        cursor = fix.end;
+        applied.push(id.to_string());
+    }
+    out.push_str(&content[cursor..]);
+
+    FixOutcome { content: out, applied, skipped }
+}
+
+// ---------------------------------------------------------------------------
+// Note structure
+// ---------------------------------------------------------------------------
+
+/// A note split into lines, with fenced code marked.
+struct Note<'a> {
+    lines: Vec<Line<'a>>,
+    /// Line break to insert, matching the note
+    newline: &'static str,
+}
+
+struct Line<'a> {
+    /// Byte offset of the line in the note
+    start: usize,
+    /// The line without its line break
+    text: &'a str,
+    /// Part of a fenced code block, fences included
+    in_code: bool,
+}
+
+impl<'a> Note<'a> {
+    fn parse(content: &'a str) -> Self {
+        let (_, body) = split_frontmatter(content);
+        let mut offset = content.len() - body.len();
+
+        let mut lines = Vec::new();
+        let mut fence: Option<&str> = None;
+        for raw in body.split_inclusive('\n') {
+            let text = raw.trim_end_matches(['\n', '\r']);
+            let trimmed = text.trim_start();
+            let in_code = match fence {
+                Some(marker) => {
+                    if trimmed.starts_with(marker) {
+                        fence = None;
+                    }
+                    true
+                }
+                None if trimmed.starts_with("```") || trimmed.starts_with("~~~") => {
+                    fence = Some(&trimmed[..3]);
+                    true
+                }
+                None => false,
+            };
+            lines.push(Line { start: offset, text, in_code });
+            offset += raw.len();
+        }
+
+        let newline = match LineEnding::detect(content) {
+            LineEnding::Crlf => "\r\n",
+            LineEnding::Lf => "\n",
+        };
+        Self { lines, newline }
+    }
+
+    /// Headings outside code, with their line index.
+    fn headings(&self) -> impl Iterator<Item = (usize, &Line<'a>, Heading)> {
+        self.lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| !line.in_code)
+            .filter_map(|(index, line)| Some((index, line, Heading::parse(line.text)?)))
+    }
+}
+
+/// An ATX heading (`## Text`), as offsets within its line.
+struct Heading {
+    level: usize,
+    /// Offset of the first `#`
+    marker: usize,
+    /// End of the heading text, before closing `#`s and whitespace
+    text_end: usize,
+}
+
+impl Heading {
+    fn parse(line: &str) -> Option<Self> {
+        let marker = line.len() - line.trim_start_matches(' ').len();
+        if marker > 3 {
+            return None;
+        }
+        let rest = &line[marker..];
+        let level = rest.len() - rest.trim_start_matches('#').len();
+        let after = &rest[level..];
+        if !(1..=6).contains(&level) || !(after.is_empty() || after.starts_with([' ', '\t'])) {
+            return None;
+        }
+
+        // Optional closing sequence: `## Text ##`
+        let mut text = after.trim_end();
+        let unclosed = text.trim_end_matches('#');
+        if unclosed.len() < text.len() && (unclosed.is_empty() || unclosed.ends_with([' ', '\t'])) {
+            text = unclosed.trim_end();
+        }
+        Some(Self { level, marker, text_end: marker + level + text.len() })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Rules
+// ---------------------------------------------------------------------------
+
+struct SingleTitle;
+
+impl Rule for SingleTitle {
+    fn id(&self) -> &'static str {
+        "single-title"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, note: &Note, _: &LintContext, out: &mut Vec<Finding>) {
+        let extra_titles = note.headings().filter(|(_, _, heading)| heading.level == 1).skip(1);
+        for (_, line, heading) in extra_titles {
+            let marker = line.start + heading.marker;
+            out.push(Finding {
+                start: marker,
+                end: line.start + line.text.len(),
+                message: "Only the title may be a level-one heading".into(),
+                fix: Some(LintFix { start: marker, end: marker + 1, replacement: "##".into() }),
+            });
+        }
+    }
+}
+
+struct BlankLineBeforeHeading;
+
+impl Rule for BlankLineBeforeHeading {
+    fn id(&self) -> &'static str {
+        "blank-line-before-heading"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, note: &Note, _: &LintContext, out: &mut Vec<Finding>) {
+        for (index, line, _) in note.headings() {
+            let Some(previous) = index.checked_sub(1).map(|i| &note.lines[i]) else {
+                continue;
+            };
+            if previous.text.trim().is_empty() {
+                continue;
+            }
+            out.push(Finding {
+                start: line.start,
+                end: line.start + line.text.len(),
+                message: "Add a blank line before the heading".into(),
+                fix: Some(LintFix { start: line.start, end: line.start, replacement: note.newline.into() }),
+            });
+        }
+    }
+}
+
+/// Characters a heading may not end with.
+const HEADING_PUNCTUATION: &[char] = &['.', ',', ';', ':', '!'];
+
+struct HeadingPunctuation;
+
+impl Rule for HeadingPunctuation {
+    fn id(&self) -> &'static str {
+        "heading-punctuation"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn check(&self, note: &Note, _: &LintContext, out: &mut Vec<Finding>) {
+        for (_, line, heading) in note.headings() {
+            let text = &line.text[..heading.text_end];
+            let kept = text.trim_end_matches(HEADING_PUNCTUATION);
+            // Headings that are nothing but punctuation are left alone
+            if kept.len() == text.len() || kept.trim_end().len() <= heading.marker + heading.level {
+                continue;
+            }
+            let (start, end) = (line.start + kept.len(), line.start + text.len());
+            out.push(Finding {
+                start,
+                end,
+                message: format!("Heading ends with '{}'", &text[kept.len()..]),
+                fix: Some(LintFix { start, end, replacement: String::new() }),
+            });
+        }
+    }
+}
+
+struct RelativeLinks;
+
+impl Rule for RelativeLinks {
+    fn id(&self) -> &'static str {
+        "relative-links"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, note: &Note, context: &LintContext, out: &mut Vec<Finding>) {
+        for line in note.lines.iter().filter(|line| !line.in_code) {
+            for (dest_start, dest) in link_destinations(line.text) {
+                if !is_absolute_link(dest) {
+                    continue;
+                }
+                let start = line.start + dest_start;
+                let end = start + dest.len();
+                out.push(Finding {
+                    start,
+                    end,
+                    message: format!("Link to '{}' is not relative to the note", dest),
+                    fix: relative_replacement(dest, context)
+                        .map(|replacement| LintFix { start, end, replacement }),
+                });
+            }
+        }
+    }
+}
+
+/// `(offset, destination)` of every `[text](dest)` link on a line outside
+/// inline code.
+fn link_destinations(line: &str) -> Vec<(usize, &str)> {
+    let mut in_code = false;
+    let mut found = Vec::new();
+    let mut index = 0;
+    while index < line.len() {
+        let rest = &line[index..];
+        if rest.starts_with('`') {
+            in_code = !in_code;
+        } else if !in_code && rest.starts_with("](") {
+            let open = index + 2;
+            if let Some(close) = line[open..].find(')') {
+                let inner = &line[open..open + close];
+                let dest = inner.split_whitespace().next().unwrap_or("");
+                if !dest.is_empty() {
+                    found.push((open + (inner.len() - inner.trim_start().len()), dest));
+                }
+                index = open + close + 1;
+                continue;
+            }
+        }
+        index += rest.chars().next().map_or(1, char::len_utf8);
+    }
+    found
+}
+
+/// Filesystem-absolute destinations: `/x`, `C:/x`, `C:\x`, `file:` URLs.
+fn is_absolute_link(dest: &str) -> bool {
+    let bytes = dest.as_bytes();
+    let drive = bytes.len() > 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && matches!(bytes[2], b'/' | b'\\');
+    (dest.starts_with('/') && !dest.starts_with("//")) || drive || dest.starts_with("file:")
+}
+
+/// The relative form of an absolute link, if it points into the vault and
+/// the note's folder is known. `/x` is read as relative to the vault root
+/// unless it spells out the root itself.
+fn relative_replacement(dest: &str, context: &LintContext) -> Option<String> {
+    let note_dir = context.note_dir.as_deref()?;
+    let (path, fragment) = dest.find('#').map_or((dest, ""), |split| dest.split_at(split));
+    let path = path.strip_prefix("file://").unwrap_or(path).replace('\\', "/");
+
+    let root = context.root.as_deref().map(|root| root.trim_end_matches('/'));
+    let target = match root.and_then(|root| path.strip_prefix(root)) {
+        Some(inside) if inside.starts_with('/') => inside.to_string(),
+        _ if path.starts_with('/') => path.clone(),
+        _ => return None,
+    };
+    Some(format!("{}{}", relative_link(note_dir, &target), fragment))
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule_hits(content: &str, rule: &str) -> Vec<LintDiagnostic> {
+        let context = LintContext { root: Some("/vault".into()), note_dir: Some("Notes/Daily".into()) };
+        lint(content, &LintSettings::default(), &context)
+            .into_iter()
+            .filter(|diagnostic| diagnostic.rule == rule)
+            .collect()
+    }
+
+    fn fixed(content: &str, diagnostics: &[LintDiagnostic]) -> String {
+        apply_fixes(content, &diagnostics.iter().collect::<Vec<_>>()).content
+    }
+
+    #[test]
+    fn test_single_title_flags_later_h1_outside_code() {
+        let note = "---\ntitle: x\n---\n# Title\n\n```\n# not a heading\n```\n\n# Second\n\n#hashtag\n";
+        let hits = rule_hits(note, "single-title");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].line, 10);
+        assert_eq!(fixed(note, &hits), note.replace("# Second", "## Second"));
+    }
+
+    #[test]
+    fn test_blank_line_before_heading_keeps_line_endings() {
+        let note = "# Title\r\ntext\r\n## Section\r\n\r\n## Fine\r\n~~~\r\ncode\r\n~~~\r\n";
+        let hits = rule_hits(note, "blank-line-before-heading");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].line, 3);
+        assert_eq!(fixed(note, &hits), note.replace("text\r\n", "text\r\n\r\n"));
+    }
+
+    #[test]
+    fn test_heading_punctuation_ignores_closing_hashes() {
+        let note = "# Title\n\n## Done. ##\n\n## Why?\n\n## ...\n";
+        let hits = rule_hits(note, "heading-punctuation");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].message, "Heading ends with '.'");
+        assert_eq!(fixed(note, &hits), note.replace("Done. ##", "Done ##"));
+    }
+
+    #[test]
+    fn test_relative_links_rewrites_vault_paths() {
+        let note = "[a](/vault/Notes/a.md#top) [b](/Other/b.md) [c](C:\\x.md) [d](https://x.org/a) [e](b.md)\n\
+                    `[f](/code.md)`\n";
+        let hits = rule_hits(note, "relative-links");
+        let fixes: Vec<Option<&str>> =
+            hits.iter().map(|hit| hit.fix.as_ref().map(|fix| fix.replacement.as_str())).collect();
+        assert_eq!(fixes, [Some("../a.md#top"), Some("../../Other/b.md"), None]);
+    }
+
+    #[test]
+    fn test_overrides_disable_rules_and_change_severity() {
+        let settings = LintSettings {
+            rules: BTreeMap::from([
+                ("single-title".into(), RuleOverride { enabled: Some(false), severity: None }),
+                ("heading-punctuation".into(), RuleOverride { enabled: None, severity: Some(Severity::Error) }),
+            ]),
+        };
+        let diagnostics = lint("# A\n\n# B.\n", &settings, &LintContext::default());
+        let found: Vec<(&str, Severity)> = diagnostics.iter().map(|d| (d.rule.as_str(), d.severity)).collect();
+        assert_eq!(found, [("heading-punctuation", Severity::Error)]);
+    }
+
+    #[test]
+    fn test_overlapping_fixes_apply_once_in_order() {
+        let note = "# Title\ntext\n# Extra.\n";
+        let diagnostics = lint(note, &LintSettings::default(), &LintContext::default());
+        let rules: Vec<&str> = diagnostics.iter().map(|d| d.rule.as_str()).collect();
+        assert_eq!(rules, ["blank-line-before-heading", "single-title", "heading-punctuation"]);
+
+        // The insertion and both rewrites of the heading line all apply
+        let outcome = apply_fixes(note, &diagnostics.iter().collect::<Vec<_>>());
+        assert_eq!(outcome.content, "# Title\ntext\n\n## Extra\n");
+        assert_eq!(outcome.applied.len(), 3);
+
+        // An edit inside a rewritten range is skipped
+        let inner = LintDiagnostic {
+            id: "inner".into(),
+            fix: Some(LintFix { start: 15, end: 17, replacement: "xx".into() }),
+            ..diagnostics[0].clone()
+        };
+        let wide = LintDiagnostic {
+            id: "wide".into(),
+            fix: Some(LintFix { start: 13, end: 21, replacement: "# Extra".into() }),
+            ..diagnostics[0].clone()
+        };
+        let outcome = apply_fixes(note, &[&inner, &wide]);
+        assert_eq!(outcome.content, "# Title\ntext\n# Extra\n");
+        assert_eq!((outcome.applied, outcome.skipped), (vec!["wide".to_string()], vec!["inner".to_string()]));
+    }
+}
@@ -0,0 +1,330 @@
+//! ============================================================================
+//! Hibiscus Workspace Macros
+//! ============================================================================
+//!
+//! Named chains of batch operations stored in workspace settings, so one
+//! shortcut can e.g. archive a note, log it and mark it done in the calendar.
+//!
+//! STORAGE (`settings.macros` in workspace.json):
+//! ```json
+//! [{ "name": "archive", "variables": ["reason"], "steps": [
+//!     { "op": "move_node", "from": "{{current_note}}", "to": "Archive/{{current_note}}" },
+//!     { "op": "append_to_file", "path": "Archive/log.md", "text": "{{today}}: {{reason}}" }
+//! ]}]
+//! ```
+//!
+//! DESIGN DECISIONS:
+//! - Steps are `crate::batch::BatchOp`s and run through `run_batch`, so a
+//!   failing step rolls back the steps before it.
+//! - `{{name}}` placeholders may appear in any string of a step. Built-ins
+//!   are `current_note` and `today` (`YYYY-MM-DD`); every other name must be
+//!   declared in `variables` and is prompted for by the frontend.
+//! - Macros are validated when workspace.json is saved: unknown operations,
+//!   undeclared variables and duplicate names reject the save instead of
+//!   failing later at run time.
+//! - Macros are read from the raw settings rather than `WorkspaceSettings`,
+//!   so an invalid macro cannot reset the other settings to defaults.
+//!
+//! The Tauri commands live in `commands::batch`.
+//! ============================================================================
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::batch::BatchOp;
+use crate::error::HibiscusError;
+
+/// Placeholder filled with the note the macro was run on.
+pub const CURRENT_NOTE: &str = "current_note";
+/// Placeholder filled with the local date the macro runs on.
+pub const TODAY: &str = "today";
+
+/// A named list of operations.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Macro {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Names the user is prompted for before the macro runs
+    #[serde(default)]
+    pub variables: Vec<String>,
+    pub steps: Vec<BatchOp>,
+}
+
+/// Values for a macro run, supplied by the frontend.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MacroContext {
+    /// Workspace-relative id of the active note
+    pub current_note: Option<String>,
+    /// Answers to the macro's prompted `variables`
+    pub variables: HashMap<String, String>,
+}
+
+impl Macro {
+    /// Checks that every placeholder is a built-in or declared variable.
+    pub fn validate(&self) -> Result<(), HibiscusError> {
+        if self.name.trim().is_empty() {
+            return Err(invalid("a macro has no name"));
+        }
+        if self.steps.is_empty() {
+            return Err(invalid(&format!("macro '{}' has no steps", self.name)));
+        }
+
+        for name in &self.variables {
+            if name == CURRENT_NOTE || name == TODAY {
+                return Err(invalid(&format!(
+                    "macro '{}' redeclares the built-in variable '{}'",
+                    self.name, name
+                )));
+            }
+        }
+
+        for (index, step) in self.steps.iter().enumerate() {
+            for text in strings(&serde_json::to_value(step)?) {
+                for name in placeholders(&text) {
+                    if name != CURRENT_NOTE && name != TODAY && !self.variables.iter().any(|v| v == name) {
+                        return Err(invalid(&format!(
+                            "step {} of macro '{}' uses unknown variable '{}'",
+                            index + 1,
+                            self.name,
+                            name
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the steps with every placeholder filled in.
+    pub fn expand(&self, context: &MacroContext, today: NaiveDate) -> Result<Vec<BatchOp>, HibiscusError> {
+        let mut values: HashMap<&str, String> = HashMap::new();
+        values.insert(TODAY, today.format("%Y-%m-%d").to_string());
+        if let Some(note) = &context.current_note {
+            values.insert(CURRENT_NOTE, note.clone());
+        }
+        for name in &self.variables {
+            if let Some(value) = context.variables.get(name) {
+                values.insert(name, value.clone());
+            }
+        }
+
+        self.steps
+            .iter()
+            .map(|step| {
+                let mut value = serde_json::to_value(step)?;
+                fill(&mut value, &values, &self.name)?;
+                Ok(serde_json::from_value(value)?)
+            })
+            .collect()
+    }
+}
+
+/// Parses and validates `settings.macros`. Missing means no macros.
+pub fn parse_macros(settings: Option<&Value>) -> Result<Vec<Macro>, HibiscusError> {
+    let Some(raw) = settings.and_then(|s| s.get("macros")) else {
+        return Ok(Vec::new());
+    };
+    let macros: Vec<Macro> =
+        serde_json::from_value(raw.clone()).map_err(|e| invalid(&e.to_string()))?;
+
+    let mut names = HashSet::new();
+    for m in &macros {
+        m.validate()?;
+        if !names.insert(m.name.as_str()) {
+            return Err(invalid(&format!("more than one macro is named '{}'", m.name)));
+        }
+    }
+    Ok(macros)
+}
+
+/// Macros of the workspace at `root`.
+pub fn load(root: &Path) -> Result<Vec<Macro>, HibiscusError> {
+    let path = root.join(".hibiscus").join("workspace.json");
+    let doc: Value = match std::fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(HibiscusError::Io(format!(
+                "Failed to read '{}': {}",
+                path.display(),
+                e
+            )))
+        }
+    };
+    parse_macros(doc.get("settings"))
+}
+
+fn invalid(reason: &str) -> HibiscusError {
+    HibiscusError::Workspace(format!("Invalid macro: {}", reason))
+}
+
+/// Names of the `{{name}}` placeholders in `text`.
+fn placeholders(text: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        names.push(rest[start + 2..start + 2 + len].trim());
+        rest = &rest[start + 2 + len + 2..];
+    }
+    names
+}
+
+/// Every string in a JSON value.
+fn strings(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(s) => vec![s.clone()],
+        Value::Array(items) => items.iter().flat_map(strings).collect(),
+        Value::Object(map) => map.values().flat_map(strings).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Replaces placeholders in every string of `value`.
+fn fill(value: &mut Value, values: &HashMap<&str, String>, macro_name: &str) -> Result<(), HibiscusError> {
+    match value {
+        Value::String(text) => {
+            let mut filled = String::new();
+            let mut rest = text.as_str();
+            while let Some(start) = rest.find("{{") {
+                let Some(len) = rest[start + 2..].find("}}") else {
+                    break;
+                };
+                let name = rest[start + 2..start + 2 + len].trim();
+                let replacement = values.get(name).ok_or_else(|| {
+                    HibiscusError::Workspace(format!(
+                        "Macro '{}' needs a value for '{}'",
+                        macro_name, name
+                    ))
+                })?;
+                filled.push_str(&rest[..start]);
+                filled.push_str(replacement);
+                rest = &rest[start + 2 + len + 2..];
+            }
+            filled.push_str(rest);
+            *text = filled;
+        }
+        Value::Array(items) => {
+            for item in items {
+                fill(item, values, macro_name)?;
+            }
+        }
+        Value::Object(map) => {
+            for item in map.values_mut() {
+                fill(item, values, macro_name)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::batch::run_batch;
+    use serde_json::json;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn archive_macro() -> Value {
+        json!({ "macros": [{
+            "name": "archive",
+            "variables": ["reason"],
+            "steps": [
+                { "op": "move_node", "from": "{{current_note}}", "to": "Archive/{{current_note}}" },
+                { "op": "append_to_file", "path": "Archive/log.md", "text": "{{today}} {{current_note}}: {{reason}}" }
+            ]
+        }]})
+    }
+
+    fn context(note: &str, reason: &str) -> MacroContext {
+        MacroContext {
+            current_note: Some(note.into()),
+            variables: HashMap::from([("reason".to_string(), reason.to_string())]),
+        }
+    }
+
+    #[test]
+    fn test_validation_rejects_unknown_ops_and_variables() {
+        assert!(parse_macros(Some(&archive_macro())).is_ok());
+        assert!(parse_macros(Some(&json!({}))).unwrap().is_empty());
+
+        let unknown_op = json!({ "macros": [{ "name": "x", "steps": [{ "op": "format_disk" }] }] });
+        assert!(parse_macros(Some(&unknown_op)).is_err());
+
+        let unknown_var = json!({ "macros": [{ "name": "x", "steps": [
+            { "op": "quick_capture", "text": "{{mood}}" }
+        ]}]});
+        let err = parse_macros(Some(&unknown_var)).unwrap_err().to_string();
+        assert!(err.contains("unknown variable 'mood'"), "{}", err);
+
+        let mut duplicate = archive_macro();
+        let copy = duplicate["macros"][0].clone();
+        duplicate["macros"].as_array_mut().unwrap().push(copy);
+        assert!(parse_macros(Some(&duplicate)).is_err());
+    }
+
+    #[test]
+    fn test_two_step_macro_substitutes_variables() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("Lecture 3.md"), "notes").unwrap();
+
+        let macros = parse_macros(Some(&archive_macro())).unwrap();
+        let today = NaiveDate::from_ymd_opt(2024, 3, 5).unwrap();
+        let steps = macros[0].expand(&context("Lecture 3.md", "exam done"), today).unwrap();
+        assert_eq!(
+            steps[0],
+            BatchOp::MoveNode { from: "Lecture 3.md".into(), to: "Archive/Lecture 3.md".into() }
+        );
+
+        let result = run_batch(root, &steps).unwrap();
+        assert_eq!(result.steps, 2);
+        assert!(root.join("Archive").join("Lecture 3.md").is_file());
+        assert_eq!(
+            fs::read_to_string(root.join("Archive").join("log.md")).unwrap(),
+            "2024-03-05 Lecture 3.md: exam done\n"
+        );
+
+        // A prompted variable without an answer stops the run before any step
+        let missing = MacroContext { current_note: Some("a.md".into()), ..Default::default() };
+        assert!(macros[0].expand(&missing, today).is_err());
+    }
+
+    #[test]
+    fn test_failing_macro_rolls_back() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("a.md"), "a").unwrap();
+        fs::create_dir_all(root.join("Archive")).unwrap();
+        fs::write(root.join("Archive").join("log.md"), "earlier\n").unwrap();
+
+        // The log step works, then the move fails: the log entry is undone
+        let settings = json!({ "macros": [{
+            "name": "log-then-move",
+            "steps": [
+                { "op": "append_to_file", "path": "Archive/log.md", "text": "{{current_note}}" },
+                { "op": "move_node", "from": "{{current_note}}", "to": "Archive/log.md" }
+            ]
+        }]});
+        let macros = parse_macros(Some(&settings)).unwrap();
+        let today = NaiveDate::from_ymd_opt(2024, 3, 5).unwrap();
+        let steps = macros[0].expand(&context("a.md", ""), today).unwrap();
+
+        assert!(run_batch(root, &steps).is_err());
+        assert_eq!(fs::read_to_string(root.join("Archive").join("log.md")).unwrap(), "earlier\n");
+        assert!(root.join("a.md").is_file());
+    }
+}
@@ -0,0 +1,1137 @@
+//! ============================================================================
+//! Hibiscus Markdown Utilities
+//! ============================================================================
+//!
+//! Lightweight, dependency-free helpers for working with note content on the
+//! backend: frontmatter extraction, a plain-text rendering mode that
+//! strips markdown syntax while keeping the readable text, a basic HTML
+//! renderer, link extraction, heading outlines and redaction of private
+//! content for sharing.
+//!
+//! DESIGN DECISIONS:
+//! - Line-oriented, single pass. We only need "good enough" text for search,
+//!   export and statistics, not a spec-compliant CommonMark renderer.
+//! - Link text is always kept; URLs are optionally appended in parentheses.
+//! - Fenced code keeps its content but loses the fence markers, so code
+//!   stays greppable.
+//!
+//! Consumers: corpus export, site publishing, the link graph, note
+//! outlines, note redaction, and any command that needs note text without markdown noise.
+//! ============================================================================
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+// ---------------------------------------------------------------------------
+// Frontmatter
+// ---------------------------------------------------------------------------
+
+/// Parsed YAML-style frontmatter block.
+///
+/// Only flat `key: value` pairs are understood. `tags` is additionally
+/// parsed as either an inline list (`tags: [a, b]`) or a block list
+/// (`tags:` followed by `- a` lines).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Frontmatter {
+    /// Raw scalar fields, in key order.
+    pub fields: BTreeMap<String, String>,
+    /// Tags declared in the `tags` field.
+    pub tags: Vec<String>,
+}
+
+/// Splits a note into its frontmatter and body.
+///
+/// Frontmatter must start on the first line with `---` and end with a
+/// `---` line. Returns `None` for the frontmatter if the block is missing
+/// or unterminated; the body is then the whole input.
+pub fn split_frontmatter(content: &str) -> (Option<Frontmatter>, &str) {
+    match frontmatter_bounds(content) {
+        Some((block, body)) => (Some(parse_frontmatter(&content[block])), &content[body..]),
+        None => (None, content),
+    }
+}
+
+/// Byte range of the frontmatter block (between the fences) and the offset
+/// where the body starts.
+fn frontmatter_bounds(content: &str) -> Option<(std::ops::Range<usize>, usize)> {
+    let start = if content.starts_with("---\n") {
+        4
+    } else if content.starts_with("---\r\n") {
+        5
+    } else {
+        return None;
+    };
+
+    let mut offset = start;
+    for line in content[start..].split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return Some((start..offset, offset + line.len()));
+        }
+        offset += line.len();
+    }
+
+    None
+}
+
+fn parse_frontmatter(block: &str) -> Frontmatter {
+    let mut fm = Frontmatter::default();
+    let mut in_tag_list = false;
+
+    for line in block.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        if in_tag_list {
+            if let Some(tag) = trimmed.strip_prefix("- ") {
+                fm.tags.push(unquote(tag).to_string());
+                continue;
+            }
+            in_tag_list = false;
+        }
+
+        let Some((key, value)) = trimmed.split_once(':') else {
+            continue;
+        };
+        let key = key.trim().to_string();
+        let value = value.trim();
+
+        if key == "tags" {
+            if value.is_empty() {
+                in_tag_list = true;
+            } else {
+                fm.tags.extend(
+                    value
+                        .trim_start_matches('[')
+                        .trim_end_matches(']')
+                        .split(',')
+                        .map(|t| unquote(t.trim()).to_string())
+                        .filter(|t| !t.is_empty()),
+                );
+            }
+            continue;
+        }
+
+        fm.fields.insert(key, unquote(value).to_string());
+    }
+
+    fm
+}
+
+fn unquote(value: &str) -> &str {
+    value.trim_matches(|c| c == '"' || c == '\'')
+}
+
+// ---------------------------------------------------------------------------
+// Plain-text rendering
+// ---------------------------------------------------------------------------
+
+/// Options for `to_plain_text`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlainTextOptions {
+    /// Append link URLs after their text: `[text](url)` -> `text (url)`.
+    pub append_urls: bool,
+}
+
+/// Renders markdown as plain text.
+///
+/// Headings, list markers, blockquotes, emphasis, inline code markers and
+/// HTML tags are removed. Links and images keep their text; wiki-links keep
+/// their alias (or target). Fenced code keeps its content.
+pub fn to_plain_text(markdown: &str, options: PlainTextOptions) -> String {
+    let mut out = String::with_capacity(markdown.len());
+    let mut in_fence: Option<&str> = None;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+
+        // Fenced code blocks: drop fence lines, keep content verbatim.
+        if let Some(fence) = in_fence {
+            if trimmed.starts_with(fence) {
+                in_fence = None;
+            } else {
+                out.push_str(line);
+                out.push('\n');
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = Some(&trimmed[..3]);
+            continue;
+        }
+
+        // Horizontal rules carry no text.
+        if is_horizontal_rule(trimmed) {
+            continue;
+        }
+
+        let text = strip_block_markers(trimmed);
+        out.push_str(&strip_inline(text, options));
+        out.push('\n');
+    }
+
+    out.trim_end().to_string()
+}
+
+fn is_horizontal_rule(line: &str) -> bool {
+    let compact: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3
+        && matches!(compact[0], '-' | '*' | '_')
+        && compact.iter().all(|&c| c == compact[0])
+}
+
+/// Removes heading, blockquote and list prefixes from a line.
+fn strip_block_markers(mut line: &str) -> &str {
+    // Blockquotes may nest ("> > text").
+    while let Some(rest) = line.strip_prefix('>') {
+        line = rest.trim_start();
+    }
+
+    let hashes = line.bytes().take_while(|&b| b == b'#').count();
+    if hashes > 0 && hashes <= 6 {
+        let rest = &line[hashes..];
+        if rest.is_empty() || rest.starts_with(' ') {
+            return rest.trim();
+        }
+    }
+
+    for marker in ["- [ ] ", "- [x] ", "- [X] ", "- ", "* ", "+ "] {
+        if let Some(rest) = line.strip_prefix(marker) {
+            return rest;
+        }
+    }
+
+    let digits = line.bytes().take_while(|b| b.is_ascii_digit()).count();
+    if digits > 0 {
+        if let Some(rest) = line[digits..]
+            .strip_prefix(". ")
+            .or_else(|| line[digits..].strip_prefix(") "))
+        {
+            return rest;
+        }
+    }
+
+    line
+}
+
+/// Removes inline markdown syntax from a single line.
+fn strip_inline(line: &str, options: PlainTextOptions) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut out = String::with_capacity(line.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        // Wiki-links: [[target]] or [[target|alias]]
+        if c == '[' && chars.get(i + 1) == Some(&'[') {
+            if let Some(end) = find_seq(&chars, i + 2, &[']', ']']) {
+                let inner: String = chars[i + 2..end].iter().collect();
+                let shown = inner.rsplit('|').next().unwrap_or(&inner);
+                out.push_str(shown);
+                i = end + 2;
+                continue;
+            }
+        }
+
+        // Links and images: [text](url) / ![alt](url)
+        let is_image = c == '!' && chars.get(i + 1) == Some(&'[');
+        if c == '[' || is_image {
+            let open = if is_image { i + 1 } else { i };
+            if let Some((text_end, url_end)) = find_link(&chars, open) {
+                let text: String = chars[open + 1..text_end].iter().collect();
+                let url: String = chars[text_end + 2..url_end].iter().collect();
+                out.push_str(&strip_inline(&text, options));
+                if options.append_urls && !url.is_empty() {
+                    out.push_str(&format!(" ({})", url));
+                }
+                i = url_end + 1;
+                continue;
+            }
+        }
+
+        // HTML tags
+        if c == '<' {
+            if let Some(end) = chars[i..].iter().position(|&ch| ch == '>') {
+                let next = chars.get(i + 1).copied().unwrap_or(' ');
+                if next.is_ascii_alphabetic() || next == '/' || next == '!' {
+                    i += end + 1;
+                    continue;
+                }
+            }
+        }
+
+        // Emphasis, strikethrough and inline code markers
+        if c == '*' || c == '`' {
+            i += 1;
+            continue;
+        }
+        if (c == '~' || c == '_') && chars.get(i + 1) == Some(&c) {
+            i += 2;
+            continue;
+        }
+
+        out.push(c);
+        i += 1;
+    }
+
+    out
+}
+
+/// Finds the first index >= `from` where `seq` starts.
+fn find_seq(chars: &[char], from: usize, seq: &[char]) -> Option<usize> {
+    (from..chars.len().saturating_sub(seq.len() - 1)).find(|&j| chars[j..].starts_with(seq))
+}
+
+/// Given `chars[open] == '['`, returns `(index of ']', index of ')')` for a
+/// well-formed `[text](url)` link.
+fn find_link(chars: &[char], open: usize) -> Option<(usize, usize)> {
+    let text_end = open + 1 + chars[open + 1..].iter().position(|&c| c == ']')?;
+    if chars.get(text_end + 1) != Some(&'(') {
+        return None;
+    }
+    let url_end = text_end + 2 + chars[text_end + 2..].iter().position(|&c| c == ')')?;
+    Some((text_end, url_end))
+}
+
+/// Counts whitespace-separated words.
+pub fn word_count(text: &str) -> usize {
+    text.split_whitespace().count()
+}
+
+/// Returns whether a file extension denotes markdown.
+pub fn is_markdown_extension(ext: &str) -> bool {
+    ext.eq_ignore_ascii_case("md") || ext.eq_ignore_ascii_case("markdown")
+}
+
+/// Kind of a link found by `extract_links`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    /// `[[target]]`, `[[target|alias]]`, `[[target#heading]]`
+    Wiki,
+    /// `[text](relative/path.md)`; URLs and anchors are not reported
+    Markdown,
+}
+
+/// An outgoing link to another note or file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub kind: LinkKind,
+    /// Link target without alias or `#heading`/`^block` suffix
+    pub target: String,
+}
+
+/// Extracts note links from markdown, skipping fenced and inline code.
+pub fn extract_links(markdown: &str) -> Vec<Link> {
+    let mut links = Vec::new();
+    let mut in_fence: Option<&str> = None;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if let Some(fence) = in_fence {
+            if trimmed.starts_with(fence) {
+                in_fence = None;
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = Some(&trimmed[..3]);
+            continue;
+        }
+
+        // Drop inline code spans so `[[x]]` in code is not a link.
+        let text = line.split('`').step_by(2).collect::<Vec<_>>().join(" ");
+        extract_line_links(&text, &mut links);
+    }
+
+    links
+}
+
+fn extract_line_links(line: &str, links: &mut Vec<Link>) {
+    let chars: Vec<char> = line.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i] == '[' && chars.get(i + 1) == Some(&'[') {
+            if let Some(end) = find_seq(&chars, i + 2, &[']', ']']) {
+                let inner: String = chars[i + 2..end].iter().collect();
+                let target = strip_link_suffix(inner.split('|').next().unwrap_or(""));
+                if !target.is_empty() {
+                    links.push(Link { kind: LinkKind::Wiki, target });
+                }
+                i = end + 2;
+                continue;
+            }
+        }
+
+        if chars[i] == '[' {
+            if let Some((text_end, url_end)) = find_link(&chars, i) {
+                let url: String = chars[text_end + 2..url_end].iter().collect();
+                let url = url.split_whitespace().next().unwrap_or("");
+                let is_image = i > 0 && chars[i - 1] == '!';
+                if !is_image && !url.contains("://") && !url.starts_with("mailto:") {
+                    let target = strip_link_suffix(url);
+                    if !target.is_empty() {
+                        links.push(Link { kind: LinkKind::Markdown, target });
+                    }
+                }
+                i = url_end + 1;
+                continue;
+            }
+        }
+
+        i += 1;
+    }
+}
+
+/// Rewrites the destinations of `[text](dest)` and `![alt](dest)` links.
+///
+/// `rewrite` receives each destination without its optional title and
+/// returns a replacement, or `None` to keep it. Fenced and inline code are
+/// left untouched, as is everything outside the destinations.
+pub fn rewrite_link_destinations(
+    markdown: &str,
+    mut rewrite: impl FnMut(&str) -> Option<String>,
+) -> String {
+    let mut out = String::with_capacity(markdown.len());
+    let mut in_fence: Option<String> = None;
+
+    for line in markdown.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if let Some(fence) = &in_fence {
+            if trimmed.starts_with(fence.as_str()) {
+                in_fence = None;
+            }
+            out.push_str(line);
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = Some(trimmed[..3].to_string());
+            out.push_str(line);
+            continue;
+        }
+
+        let chars: Vec<char> = line.chars().collect();
+        let mut in_code = false;
+        let mut i = 0;
+        while i < chars.len() {
+            if chars[i] == '`' {
+                in_code = !in_code;
+            } else if chars[i] == '[' && !in_code {
+                if let Some((text_end, url_end)) = find_link(&chars, i) {
+                    let url: String = chars[text_end + 2..url_end].iter().collect();
+                    let dest_len = url.split_whitespace().next().map_or(0, str::len);
+                    let dest_start = url.len() - url.trim_start().len();
+                    let dest = &url[dest_start..dest_start + dest_len];
+
+                    out.extend(&chars[i..text_end + 2]);
+                    match rewrite(dest).filter(|_| !dest.is_empty()) {
+                        Some(new) => {
+                            out.push_str(&url[..dest_start]);
+                            out.push_str(&new);
+                            out.push_str(&url[dest_start + dest_len..]);
+                        }
+                        None => out.push_str(&url),
+                    }
+                    out.push(')');
+                    i = url_end + 1;
+                    continue;
+                }
+            }
+            out.push(chars[i]);
+            i += 1;
+        }
+    }
+
+    out
+}
+
+/// Builds a relative link from a note's folder to a root-relative target,
+/// with `..` for each folder to climb and spaces encoded as `%20`.
+pub fn relative_link(from_dir: &str, target: &str) -> String {
+    let from: Vec<&str> = from_dir.split('/').filter(|s| !s.is_empty()).collect();
+    let to: Vec<&str> = target.split('/').filter(|s| !s.is_empty()).collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let mut parts = vec![".."; from.len() - common];
+    parts.extend(&to[common..]);
+    parts.join("/").replace(' ', "%20")
+}
+
+/// Removes `#heading` / `^block` suffixes and surrounding whitespace.
+fn strip_link_suffix(target: &str) -> String {
+    let end = target.find(['#', '^']).unwrap_or(target.len());
+    target[..end].trim().replace("%20", " ")
+}
+
+/// Returns the text of the first level-one heading, if any.
+pub fn first_heading(markdown: &str) -> Option<String> {
+    markdown
+        .lines()
+        .find_map(|line| line.strip_prefix("# "))
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+}
+
+// ---------------------------------------------------------------------------
+// HTML rendering
+// ---------------------------------------------------------------------------
+
+/// What a link passed to the `to_html` resolver points from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HtmlLinkKind {
+    /// `[[target]]` / `[[target|alias]]`; the target keeps any `#heading`
+    Wiki,
+    /// `[text](dest)`
+    Markdown,
+    /// `![alt](src)` or an `![[embed]]`
+    Image,
+}
+
+/// Escapes `&`, `<`, `>` and quotes for use in HTML text and attributes.
+pub fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Renders a note body as an HTML fragment.
+///
+/// Covers the subset notes actually use: headings, paragraphs, flat lists
+/// and task lists, blockquotes, fenced code, rules, emphasis, inline code,
+/// links, images and wiki-links. Raw HTML in the note is escaped.
+///
+/// `resolve` maps every link destination (and wiki/embed target) to an
+/// `href`/`src`; `None` renders the link as its plain text.
+pub fn to_html(
+    markdown: &str,
+    mut resolve: impl FnMut(HtmlLinkKind, &str) -> Option<String>,
+) -> String {
+    #[derive(PartialEq)]
+    enum Open {
+        Nothing,
+        Paragraph,
+        List(&'static str),
+        Quote,
+    }
+
+    fn close(open: &mut Open, out: &mut String) {
+        match std::mem::replace(open, Open::Nothing) {
+            Open::Nothing => {}
+            Open::Paragraph => out.push_str("</p>\n"),
+            Open::List(tag) => out.push_str(&format!("</{}>\n", tag)),
+            Open::Quote => out.push_str("</blockquote>\n"),
+        }
+    }
+
+    let mut out = String::with_capacity(markdown.len() * 2);
+    let mut open = Open::Nothing;
+    let mut in_fence: Option<&str> = None;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+
+        if let Some(fence) = in_fence {
+            if trimmed.starts_with(fence) {
+                in_fence = None;
+                out.push_str("</code></pre>\n");
+            } else {
+                out.push_str(&escape_html(line));
+                out.push('\n');
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            close(&mut open, &mut out);
+            in_fence = Some(&trimmed[..3]);
+            let lang = trimmed[3..].trim();
+            if lang.is_empty() {
+                out.push_str("<pre><code>");
+            } else {
+                out.push_str(&format!("<pre><code class=\"language-{}\">", escape_html(lang)));
+            }
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            close(&mut open, &mut out);
+            continue;
+        }
+        if is_horizontal_rule(trimmed) {
+            close(&mut open, &mut out);
+            out.push_str("<hr>\n");
+            continue;
+        }
+
+        let hashes = trimmed.bytes().take_while(|&b| b == b'#').count();
+        if (1..=6).contains(&hashes) && trimmed[hashes..].starts_with(' ') {
+            close(&mut open, &mut out);
+            let text = trimmed[hashes..].trim();
+            out.push_str(&format!(
+                "<h{level} id=\"{id}\">{text}</h{level}>\n",
+                level = hashes,
+                id = escape_html(&heading_slug(text)),
+                text = inline_html(text, &mut resolve),
+            ));
+            continue;
+        }
+
+        if trimmed.starts_with('>') {
+            if open != Open::Quote {
+                close(&mut open, &mut out);
+                out.push_str("<blockquote>\n");
+                open = Open::Quote;
+            }
+            let text = strip_block_markers(trimmed);
+            out.push_str(&format!("<p>{}</p>\n", inline_html(text, &mut resolve)));
+            continue;
+        }
+
+        if let Some((tag, item)) = list_item(trimmed) {
+            if open != Open::List(tag) {
+                close(&mut open, &mut out);
+                out.push_str(&format!("<{}>\n", tag));
+                open = Open::List(tag);
+            }
+            let (checkbox, item) = match item.strip_prefix("[ ] ") {
+                Some(rest) => ("<input type=\"checkbox\" disabled> ", rest),
+                None => match item.strip_prefix("[x] ").or_else(|| item.strip_prefix("[X] ")) {
+                    Some(rest) => ("<input type=\"checkbox\" checked disabled> ", rest),
+                    None => ("", item),
+                },
+            };
+            out.push_str(&format!("<li>{}{}</li>\n", checkbox, inline_html(item, &mut resolve)));
+            continue;
+        }
+
+        if open == Open::Paragraph {
+            out.push('\n');
+        } else {
+            close(&mut open, &mut out);
+            out.push_str("<p>");
+            open = Open::Paragraph;
+        }
+        out.push_str(&inline_html(trimmed, &mut resolve));
+    }
+
+    if in_fence.is_some() {
+        out.push_str("</code></pre>\n");
+    }
+    close(&mut open, &mut out);
+    out
+}
+
+/// Slug used for heading ids, so `[[Note#Some Heading]]` can link to them.
+pub fn heading_slug(heading: &str) -> String {
+    let mut slug = String::new();
+    for c in to_plain_text(heading, PlainTextOptions::default()).chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.ends_with('-') && !slug.is_empty() {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Returns the list tag and item text for a list line.
+fn list_item(line: &str) -> Option<(&'static str, &str)> {
+    for marker in ["- ", "* ", "+ "] {
+        if let Some(rest) = line.strip_prefix(marker) {
+            return Some(("ul", rest));
+        }
+    }
+    let digits = line.bytes().take_while(|b| b.is_ascii_digit()).count();
+    if digits > 0 {
+        let rest = &line[digits..];
+        if let Some(item) = rest.strip_prefix(". ").or_else(|| rest.strip_prefix(") ")) {
+            return Some(("ol", item));
+        }
+    }
+    None
+}
+
+/// Renders inline markdown of a single line as HTML.
+fn inline_html(line: &str, resolve: &mut dyn FnMut(HtmlLinkKind, &str) -> Option<String>) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut out = String::with_capacity(line.len());
+    // Open emphasis tags, innermost last
+    let mut stack: Vec<&'static str> = Vec::new();
+    let mut toggle = |tag: &'static str, out: &mut String| {
+        if stack.last() == Some(&tag) {
+            stack.pop();
+            out.push_str(&format!("</{}>", tag));
+        } else {
+            stack.push(tag);
+            out.push_str(&format!("<{}>", tag));
+        }
+    };
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        // Inline code
+        if c == '`' {
+            if let Some(len) = chars[i + 1..].iter().position(|&ch| ch == '`') {
+                let code: String = chars[i + 1..i + 1 + len].iter().collect();
+                out.push_str(&format!("<code>{}</code>", escape_html(&code)));
+                i += len + 2;
+                continue;
+            }
+        }
+
+        // Wiki-links and embeds: [[target|alias]] / ![[file]]
+        let is_embed = c == '!' && chars.get(i + 1) == Some(&'[') && chars.get(i + 2) == Some(&'[');
+        if is_embed || (c == '[' && chars.get(i + 1) == Some(&'[')) {
+            let open = if is_embed { i + 1 } else { i };
+            if let Some(end) = find_seq(&chars, open + 2, &[']', ']']) {
+                let inner: String = chars[open + 2..end].iter().collect();
+                let (target, alias) = match inner.split_once('|') {
+                    Some((target, alias)) => (target.trim(), alias.trim()),
+                    None => (inner.trim(), inner.trim()),
+                };
+                let kind = if is_embed { HtmlLinkKind::Image } else { HtmlLinkKind::Wiki };
+                out.push_str(&match resolve(kind, target) {
+                    Some(src) if is_embed => format!(
+                        "<img src=\"{}\" alt=\"{}\">",
+                        escape_html(&src),
+                        escape_html(alias)
+                    ),
+                    Some(href) => format!("<a href=\"{}\">{}</a>", escape_html(&href), escape_html(alias)),
+                    None => escape_html(alias),
+                });
+                i = end + 2;
+                continue;
+            }
+        }
+
+        // Links and images: [text](dest) / ![alt](src)
+        let is_image = c == '!' && chars.get(i + 1) == Some(&'[');
+        if c == '[' || is_image {
+            let open = if is_image { i + 1 } else { i };
+            if let Some((text_end, url_end)) = find_link(&chars, open) {
+                let text: String = chars[open + 1..text_end].iter().collect();
+                let url: String = chars[text_end + 2..url_end].iter().collect();
+                let dest = url.split_whitespace().next().unwrap_or("");
+                let kind = if is_image { HtmlLinkKind::Image } else { HtmlLinkKind::Markdown };
+                out.push_str(&match resolve(kind, dest).filter(|_| !dest.is_empty()) {
+                    Some(src) if is_image => format!(
+                        "<img src=\"{}\" alt=\"{}\">",
+                        escape_html(&src),
+                        escape_html(&text)
+                    ),
+                    Some(href) => format!("<a href=\"{}\">{}</a>", escape_html(&href), inline_html(&text, resolve)),
+                    None if is_image => escape_html(&text),
+                    None => inline_html(&text, resolve),
+                });
+                i = url_end + 1;
+                continue;
+            }
+        }
+
+        // Emphasis: **strong** / __strong__, *em* / _em_ at word edges, ~~del~~
+        let next = chars.get(i + 1).copied();
+        if (c == '*' || c == '_') && next == Some(c) {
+            toggle("strong", &mut out);
+            i += 2;
+            continue;
+        }
+        if c == '~' && next == Some('~') {
+            toggle("del", &mut out);
+            i += 2;
+            continue;
+        }
+        let prev_is_word = i > 0 && chars[i - 1].is_alphanumeric();
+        let next_is_word = next.is_some_and(char::is_alphanumeric);
+        if c == '*' || (c == '_' && !(prev_is_word && next_is_word)) {
+            toggle("em", &mut out);
+            i += 1;
+            continue;
+        }
+
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+        i += 1;
+    }
+
+    // Close anything left open (unbalanced markers)
+    for tag in stack.iter().rev() {
+        out.push_str(&format!("</{}>", tag));
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Outline
+// ---------------------------------------------------------------------------
+
+/// A heading in a note outline, with the headings nested under it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OutlineHeading {
+    /// 1 for `#`, up to 6
+    pub level: u8,
+    /// Heading text as plain text
+    pub text: String,
+    /// Anchor id, as produced by `heading_slug`
+    pub slug: String,
+    /// 1-based line number in the file, frontmatter included
+    pub line: usize,
+    pub children: Vec<OutlineHeading>,
+}
+
+/// Builds the heading tree of a note.
+///
+/// Each heading holds the following headings of a deeper level until one of
+/// the same or a shallower level. A skipped level (an H3 right after an H1)
+/// nests under the nearest shallower heading; headings before any shallower
+/// one are top-level. Headings in frontmatter and fenced code are ignored.
+pub fn outline(markdown: &str) -> Vec<OutlineHeading> {
+    let (skip, body) = match frontmatter_bounds(markdown) {
+        Some((_, body_start)) => (markdown[..body_start].lines().count(), &markdown[body_start..]),
+        None => (0, markdown),
+    };
+
+    let mut flat = Vec::new();
+    let mut in_fence: Option<&str> = None;
+    for (index, line) in body.lines().enumerate() {
+        let trimmed = line.trim_start();
+
+        if let Some(fence) = in_fence {
+            if trimmed.starts_with(fence) {
+                in_fence = None;
+            }
+            continue;
+        }
+        if let Some(fence) = ["```", "~~~"].into_iter().find(|f| trimmed.starts_with(f)) {
+            in_fence = Some(fence);
+            continue;
+        }
+
+        let hashes = trimmed.bytes().take_while(|&b| b == b'#').count();
+        if !(1..=6).contains(&hashes) || !trimmed[hashes..].starts_with(' ') {
+            continue;
+        }
+        // Optional closing sequence: `## Title ##`
+        let raw = trimmed[hashes..].trim();
+        let raw = match raw.trim_end_matches('#') {
+            rest if rest.is_empty() || rest.ends_with(' ') => rest.trim_end(),
+            _ => raw,
+        };
+        if raw.is_empty() {
+            continue;
+        }
+
+        flat.push(OutlineHeading {
+            level: hashes as u8,
+            text: to_plain_text(raw, PlainTextOptions::default()).trim().to_string(),
+            slug: heading_slug(raw),
+            line: skip + index + 1,
+            children: Vec::new(),
+        });
+    }
+
+    let mut headings = flat.into_iter().peekable();
+    nest_headings(&mut headings, 0)
+}
+
+/// Takes headings deeper than `parent_level`, each with its own subtree.
+fn nest_headings(
+    headings: &mut std::iter::Peekable<std::vec::IntoIter<OutlineHeading>>,
+    parent_level: u8,
+) -> Vec<OutlineHeading> {
+    let mut siblings = Vec::new();
+    while let Some(mut heading) = headings.next_if(|h| h.level > parent_level) {
+        heading.children = nest_headings(headings, heading.level);
+        siblings.push(heading);
+    }
+    siblings
+}
+
+// ---------------------------------------------------------------------------
+// Redaction
+// ---------------------------------------------------------------------------
+
+/// Returns a copy of a note with private content removed.
+///
+/// - Top-level frontmatter keys listed in `fields` are dropped together with
+///   their nested or list lines.
+/// - Obsidian comments (`%% ... %%`, inline or spanning lines) are removed
+///   outside fenced code. An unterminated `%%` hides the rest of the note.
+///
+/// Everything else is kept byte-for-byte. Lines left blank only because a
+/// comment was removed are dropped.
+pub fn redact(content: &str, fields: &[String]) -> String {
+    let mut out = String::with_capacity(content.len());
+
+    let body_start = match frontmatter_bounds(content) {
+        Some((block, body)) => {
+            out.push_str(&content[..block.start]);
+            redact_frontmatter(&content[block.clone()], fields, &mut out);
+            out.push_str(&content[block.end..body]);
+            body
+        }
+        None => 0,
+    };
+
+    strip_comments(&content[body_start..], &mut out);
+    out
+}
+
+fn redact_frontmatter(block: &str, fields: &[String], out: &mut String) {
+    let mut dropping = false;
+
+    for line in block.split_inclusive('\n') {
+        let is_top_level_key = !line.starts_with([' ', '\t', '-', '#'])
+            && line.trim_end().contains(':');
+        if is_top_level_key {
+            let key = line.split(':').next().unwrap_or("").trim();
+            dropping = fields.iter().any(|f| f == key);
+        } else if !line.starts_with([' ', '\t', '-']) {
+            // Blank lines and comments end a dropped value
+            dropping = false;
+        }
+
+        if !dropping {
+            out.push_str(line);
+        }
+    }
+}
+
+fn strip_comments(body: &str, out: &mut String) {
+    let mut in_comment = false;
+    let mut in_fence: Option<&str> = None;
+
+    for line in body.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if !in_comment {
+            if let Some(fence) = in_fence {
+                if trimmed.starts_with(fence) {
+                    in_fence = None;
+                }
+                out.push_str(line);
+                continue;
+            }
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = Some(&trimmed[..3]);
+                out.push_str(line);
+                continue;
+            }
+        }
+
+        let content = line.trim_end_matches(['\n', '\r']);
+        let ending = &line[content.len()..];
+
+        let mut kept = String::new();
+        let mut touched = in_comment;
+        for (i, part) in content.split("%%").enumerate() {
+            if i > 0 {
+                in_comment = !in_comment;
+                touched = true;
+            }
+            if !in_comment {
+                kept.push_str(part);
+            }
+        }
+
+        if !touched {
+            out.push_str(line);
+        } else if !kept.trim().is_empty() {
+            out.push_str(&kept);
+            out.push_str(ending);
+        }
+    }
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frontmatter_inline_tags() {
+        let (fm, body) = split_frontmatter("---\ntitle: \"Hello\"\ntags: [a, b]\n---\nBody\n");
+        let fm = fm.unwrap();
+        assert_eq!(fm.fields.get("title").unwrap(), "Hello");
+        assert_eq!(fm.tags, vec!["a", "b"]);
+        assert_eq!(body, "Body\n");
+    }
+
+    #[test]
+    fn test_frontmatter_block_tags() {
+        let (fm, _) = split_frontmatter("---\ntags:\n  - one\n  - two\nstatus: draft\n---\n");
+        let fm = fm.unwrap();
+        assert_eq!(fm.tags, vec!["one", "two"]);
+        assert_eq!(fm.fields.get("status").unwrap(), "draft");
+    }
+
+    #[test]
+    fn test_unterminated_frontmatter_is_body() {
+        let (fm, body) = split_frontmatter("---\ntitle: x\n");
+        assert!(fm.is_none());
+        assert_eq!(body, "---\ntitle: x\n");
+    }
+
+    #[test]
+    fn test_links_keep_text() {
+        let md = "See [the docs](https://example.com) and [[Other Note|alias]].";
+        let opts = PlainTextOptions::default();
+        assert_eq!(to_plain_text(md, opts), "See the docs and alias.");
+
+        let opts = PlainTextOptions { append_urls: true };
+        assert_eq!(
+            to_plain_text(md, opts),
+            "See the docs (https://example.com) and alias."
+        );
+    }
+
+    #[test]
+    fn test_code_fences_keep_content() {
+        let md = "Intro\n```rust\nfn main() {}\n```\nAfter";
+        assert_eq!(
+            to_plain_text(md, PlainTextOptions::default()),
+            "Intro\nfn main() {}\nAfter"
+        );
+    }
+
+    #[test]
+    fn test_block_and_inline_markers_removed() {
+        let md = "# Title\n> quoted **bold**\n- item `code`\n1. first\n---\n~~gone~~ <b>x</b>";
+        assert_eq!(
+            to_plain_text(md, PlainTextOptions::default()),
+            "Title\nquoted bold\nitem code\nfirst\ngone x"
+        );
+    }
+
+    #[test]
+    fn test_extract_links() {
+        let md = "[[Alpha]] and [[dir/Beta#Part|b]]\n\
+                  [doc](notes/Gamma.md) ![img](pic.png) [web](https://x.y)\n\
+                  `[[NotALink]]`\n```\n[[AlsoNot]]\n```";
+        let targets: Vec<(LinkKind, String)> = extract_links(md)
+            .into_iter()
+            .map(|l| (l.kind, l.target))
+            .collect();
+        assert_eq!(
+            targets,
+            vec![
+                (LinkKind::Wiki, "Alpha".to_string()),
+                (LinkKind::Wiki, "dir/Beta".to_string()),
+                (LinkKind::Markdown, "notes/Gamma.md".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_snake_case_is_preserved() {
+        let md = "call my_function here";
+        assert_eq!(to_plain_text(md, PlainTextOptions::default()), md);
+    }
+
+    #[test]
+    fn test_redact_strips_fields_and_comments() {
+        let note = "---\ntitle: Plan\nauthor: me\nsecret:\n  - a\n  - b\ntags: [x]\n---\n\
+# Plan\nPublic %%private aside%% text.\n%%\nhidden\nblock\n%%\nKeep this.\n\
+```\n%% code stays %%\n```\n";
+        let fields = vec!["author".to_string(), "secret".to_string()];
+
+        assert_eq!(
+            redact(note, &fields),
+            "---\ntitle: Plan\ntags: [x]\n---\n\
+# Plan\nPublic  text.\nKeep this.\n```\n%% code stays %%\n```\n"
+        );
+    }
+
+    #[test]
+    fn test_redact_unterminated_comment_hides_rest() {
+        assert_eq!(redact("a\n%% start\nb\n", &[]), "a\n");
+        // Nothing to redact leaves the note untouched
+        let note = "---\r\nk: v\r\n---\r\nBody\r\n";
+        assert_eq!(redact(note, &["other".into()]), note);
+    }
+
+    #[test]
+    fn test_to_html_blocks_inline_and_links() {
+        let note = "# Intro *here*\n\nSee [[Other|the other]] and [gone](gone.md).\n- [x] done\n- `<b>`\n\n```rust\nlet a = 1 < 2;\n```\n![pic](img.png)";
+        let html = to_html(note, |kind, dest| match (kind, dest) {
+            (HtmlLinkKind::Wiki, "Other") => Some("other.html".into()),
+            (HtmlLinkKind::Image, "img.png") => Some("assets/img.png".into()),
+            _ => None,
+        });
+        assert_eq!(
+            html,
+            "<h1 id=\"intro-here\">Intro <em>here</em></h1>\n\
+             <p>See <a href=\"other.html\">the other</a> and gone.</p>\n\
+             <ul>\n<li><input type=\"checkbox\" checked disabled> done</li>\n\
+             <li><code>&lt;b&gt;</code></li>\n</ul>\n\
+             <pre><code class=\"language-rust\">let a = 1 &lt; 2;\n</code></pre>\n\
+             <p><img src=\"assets/img.png\" alt=\"pic\"></p>\n"
+        );
+        assert_eq!(to_html("snake_case_name", |_, _| None), "<p>snake_case_name</p>\n");
+    }
+
+    #[test]
+    fn test_rewrite_link_destinations_keeps_titles_and_code() {
+        let source = "![x](/img/a.png \"A\") and [n]( /n.md )\n`[c](/c.md)`\n```\n[f](/f.md)\n```\n";
+        let rewritten = rewrite_link_destinations(source, |dest| {
+            dest.strip_prefix('/').map(|rest| format!("../{}", rest))
+        });
+        assert_eq!(
+            rewritten,
+            "![x](../img/a.png \"A\") and [n]( ../n.md )\n`[c](/c.md)`\n```\n[f](/f.md)\n```\n"
+        );
+    }
+
+    #[test]
+    fn test_outline_nests_headings() {
+        let md = "---\ntitle: T\n---\n# Intro\ntext\n## Setup ##\n### Install\n## Use *it*\n# Next\n";
+        let tree = outline(md);
+
+        let shape: Vec<_> = tree
+            .iter()
+            .map(|h| (h.text.as_str(), h.line, h.children.len()))
+            .collect();
+        assert_eq!(shape, [("Intro", 4, 2), ("Next", 9, 0)]);
+
+        let setup = &tree[0].children[0];
+        assert_eq!((setup.text.as_str(), setup.slug.as_str(), setup.line), ("Setup", "setup", 6));
+        assert_eq!(setup.children[0].text, "Install");
+        assert_eq!(tree[0].children[1].slug, "use-it");
+    }
+
+    #[test]
+    fn test_outline_skipped_levels_attach_to_nearest_ancestor() {
+        let md = "### Orphan\n# Top\n### Deep\n## Mid\n```\n# not a heading\n```\n#### Leaf\n#hashtag\n";
+        let tree = outline(md);
+
+        let levels = |hs: &[OutlineHeading]| hs.iter().map(|h| h.level).collect::<Vec<_>>();
+        assert_eq!(levels(&tree), [3, 1]);
+        assert!(tree[0].children.is_empty());
+
+        // H3 right under the H1, then the H2 as its sibling
+        assert_eq!(levels(&tree[1].children), [3, 2]);
+        assert!(tree[1].children[0].children.is_empty());
+
+        // H4 after fenced code belongs to the H2
+        let leaf = &tree[1].children[1].children[0];
+        assert_eq!((leaf.text.as_str(), leaf.line), ("Leaf", 8));
+    }
+}
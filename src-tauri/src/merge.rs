@@ -0,0 +1,192 @@
+//! ============================================================================
+//! Hibiscus Three-Way Merge
+//! ============================================================================
+//!
+//! Merges an editor buffer ("ours") with a file changed on disk ("theirs"),
+//! given the text both started from ("base"). Used when a save hits a
+//! `Conflict` and the frontend offers to merge instead of overwriting.
+//!
+//! ALGORITHM:
+//! - Both sides are diffed against the base by line (`crate::diff`).
+//! - Change blocks of the two sides that overlap or touch in the base are
+//!   grouped into one region, as diff3 and git do. Edits on adjacent lines
+//!   therefore conflict; a single unchanged line between them is enough to
+//!   merge cleanly.
+//! - A region changed by one side takes that side. A region both sides
+//!   changed identically takes the change once. Anything else conflicts.
+//!
+//! OUTPUT:
+//! The merged text keeps "ours" in every conflicting region, so it reads
+//! like the buffer with the other side's clean changes applied. Each
+//! conflict records where its region starts in the merged text, plus the
+//! base/ours/theirs text of the region for the conflict resolver.
+//!
+//! Lines keep their line breaks, so the merged text reproduces each side's
+//! endings byte for byte.
+//! ============================================================================
+
+use serde::Serialize;
+
+use crate::diff::{change_blocks, diff_lines, ChangeBlock};
+
+/// A region both sides changed differently.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MergeConflict {
+    /// 0-based line of the merged text where the region (holding `ours`)
+    /// starts
+    pub line: usize,
+    pub base: String,
+    pub ours: String,
+    pub theirs: String,
+}
+
+/// Result of a three-way merge.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Merged {
+    /// Merged text, with `ours` in conflicting regions
+    pub content: String,
+    /// Conflicting regions in document order; empty for a clean merge
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// Merges the changes `ours` and `theirs` made to `base`.
+pub fn merge3(base: &str, ours: &str, theirs: &str) -> Merged {
+    let base: Vec<&str> = base.split_inclusive('\n').collect();
+    let ours: Vec<&str> = ours.split_inclusive('\n').collect();
+    let theirs: Vec<&str> = theirs.split_inclusive('\n').collect();
+    let ours_blocks = change_blocks(&diff_lines(&base, &ours));
+    let theirs_blocks = change_blocks(&diff_lines(&base, &theirs));
+
+    let mut merged = Merged::default();
+    let mut lines = 0;
+    let mut emit = |merged: &mut Merged, text: &[&str]| {
+        text.iter().for_each(|line| merged.content.push_str(line));
+        lines += text.len();
+        lines - text.len()
+    };
+
+    let (mut next_ours, mut next_theirs) = (0, 0);
+    let mut copied = 0;
+    loop {
+        let start = match (ours_blocks.get(next_ours), theirs_blocks.get(next_theirs)) {
+            (None, None) => break,
+            (Some(a), Some(b)) => a.old_start.min(b.old_start),
+            (Some(block), None) | (None, Some(block)) => block.old_start,
+        };
+
+        // Grow the region while a block of either side starts inside it or
+        // right at its end
+        let (first_ours, first_theirs) = (next_ours, next_theirs);
+        let mut end = start;
+        loop {
+            let joins = |block: Option<&ChangeBlock>| block.filter(|block| block.old_start <= end).copied();
+            if let Some(block) = joins(ours_blocks.get(next_ours)) {
+                end = end.max(block.old_start + block.old_count);
+                next_ours += 1;
+            } else if let Some(block) = joins(theirs_blocks.get(next_theirs)) {
+                end = end.max(block.old_start + block.old_count);
+                next_theirs += 1;
+            } else {
+                break;
+            }
+        }
+
+        emit(&mut merged, &base[copied..start]);
+        copied = end;
+
+        let region = &base[start..end];
+        let ours_side = side_text(&ours, &ours_blocks[first_ours..next_ours], start, end).unwrap_or(region);
+        let theirs_side =
+            side_text(&theirs, &theirs_blocks[first_theirs..next_theirs], start, end).unwrap_or(region);
+
+        if ours_side == region || ours_side == theirs_side {
+            emit(&mut merged, theirs_side);
+        } else if theirs_side == region {
+            emit(&mut merged, ours_side);
+        } else {
+            let line = emit(&mut merged, ours_side);
+            merged.conflicts.push(MergeConflict {
+                line,
+                base: region.concat(),
+                ours: ours_side.concat(),
+                theirs: theirs_side.concat(),
+            });
+        }
+    }
+
+    emit(&mut merged, &base[copied..]);
+    merged
+}
+
+/// The lines of `side` covering base lines `start..end`, or `None` if
+/// `blocks` (the side's changes within that range) is empty.
+fn side_text<'a>(side: &'a [&'a str], blocks: &[ChangeBlock], start: usize, end: usize) -> Option<&'a [&'a str]> {
+    let (first, last) = (blocks.first()?, blocks.last()?);
+    // Outside its blocks a side lines up with the base at a fixed offset
+    let side_start = first.new_start - (first.old_start - start);
+    let side_end = last.new_start + last.new_count + (end - (last.old_start + last.old_count));
+    Some(&side[side_start..side_end])
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "title\none\ntwo\nthree\nfour\nfive\n";
+
+    #[test]
+    fn test_clean_merge_takes_both_sides() {
+        let ours = "title\nONE\ntwo\nthree\nfour\nfive\nsix\n";
+        let theirs = "title\none\ntwo\nthree\nFOUR\nfive\nsix\n";
+        let merged = merge3(BASE, ours, theirs);
+        assert!(merged.conflicts.is_empty());
+        // The same line appended on both sides is taken once
+        assert_eq!(merged.content, "title\nONE\ntwo\nthree\nFOUR\nfive\nsix\n");
+
+        // Unchanged on one side: the other side wins, line endings included
+        let crlf = "title\r\none\ntwo\nthree\nfour\nfive\n";
+        assert_eq!(merge3(BASE, BASE, crlf).content, crlf);
+        assert_eq!(merge3(BASE, "", BASE).content, "");
+    }
+
+    #[test]
+    fn test_single_conflict_keeps_ours_in_place() {
+        let ours = "title\none\nTWO (mine)\nthree\nfour\nfive\n";
+        let theirs = "title\nONE\nTWO (theirs)\nthree\nfour\nfive!\n";
+        let merged = merge3(BASE, ours, theirs);
+
+        assert_eq!(merged.content, "title\none\nTWO (mine)\nthree\nfour\nfive!\n");
+        assert_eq!(
+            merged.conflicts,
+            vec![MergeConflict {
+                line: 1,
+                base: "one\ntwo\n".into(),
+                ours: "one\nTWO (mine)\n".into(),
+                theirs: "ONE\nTWO (theirs)\n".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_adjacent_edits_conflict_and_separated_edits_merge() {
+        // Lines 2 and 3 touch: one region, changed differently by each side
+        let adjacent = merge3(BASE, "title\none\nTWO\nthree\nfour\nfive\n", "title\none\ntwo\nTHREE\nfour\nfive\n");
+        assert_eq!(adjacent.conflicts.len(), 1);
+        assert_eq!(adjacent.conflicts[0].base, "two\nthree\n");
+        assert_eq!(adjacent.conflicts[0].theirs, "two\nTHREE\n");
+
+        // Insertions at the same spot conflict too
+        let mine = "title\nmine\none\ntwo\nthree\nfour\nfive\n";
+        let inserted = merge3(BASE, mine, "title\ntheirs\none\ntwo\nthree\nfour\nfive\n");
+        assert_eq!(inserted.conflicts[0].base, "");
+
+        // One unchanged line between the edits is enough
+        let apart = merge3(BASE, "title\none\nTWO\nthree\nfour\nfive\n", "title\none\ntwo\nthree\nFOUR\nfive\n");
+        assert!(apart.conflicts.is_empty());
+        assert_eq!(apart.content, "title\none\nTWO\nthree\nFOUR\nfive\n");
+    }
+}
@@ -1,23 +1,230 @@
-use serde_json::Value;
+use serde_json::{Map, Value};
+
+use crate::ids::canonicalize_id;
+
+/// Current workspace.json schema version
+///
+/// History:
+/// - 1.0: initial schema
+/// - 1.1: node ids and stored references in canonical form (`crate::ids`)
+/// - 1.2: `tree` is no longer stored; manual ordering, decorations and
+///   favorites live in top-level sections keyed by node id
+pub const WORKSPACE_SCHEMA_VERSION: &str = "1.2";
+
+/// Returns whether `version` is newer than this build understands.
+///
+/// Versions compare numerically per dot-separated part ("1.10" > "1.9");
+/// unparsable parts count as 0.
+pub fn is_newer_workspace_schema(version: &str) -> bool {
+    let parts = |v: &str| -> Vec<u64> {
+        v.split('.').map(|p| p.trim().parse().unwrap_or(0)).collect()
+    };
+    let (mut found, mut current) = (parts(version), parts(WORKSPACE_SCHEMA_VERSION));
+    let len = found.len().max(current.len());
+    found.resize(len, 0);
+    current.resize(len, 0);
+    found > current
+}
+
+/// Returns whether files of schema `version` store the tree.
+///
+/// Saves keep the tree only for files that declare an older schema.
+pub fn stores_tree(version: &str) -> bool {
+    let parts: Vec<u64> = version.split('.').map(|p| p.trim().parse().unwrap_or(0)).collect();
+    parts < vec![1, 2]
+}
 
 /// Applies sequential migrations to workspace data
 pub fn migrate_workspace(value: &mut Value) {
     // Current target version for workspace schema
-    const TARGET_VERSION: &str = "1.0";
+    const TARGET_VERSION: &str = WORKSPACE_SCHEMA_VERSION;
 
-    if let Some(version) = value.get("schema_version").and_then(|v| v.as_str()) {
-        if version == TARGET_VERSION {
+    // If no version is found, assume 1.0
+    let mut version = value
+        .get("schema_version")
+        .and_then(|v| v.as_str())
+        .unwrap_or("1.0")
+        .to_string();
+
+    if version == TARGET_VERSION || is_newer_workspace_schema(&version) {
+        return;
+    }
+
+    if version == "1.0" {
+        migrate_workspace_1_0_to_1_1(value);
+        version = "1.1".into();
+    }
+
+    if version == "1.1" {
+        migrate_workspace_1_1_to_1_2(value);
+    }
+
+    if let Some(obj) = value.as_object_mut() {
+        obj.insert("schema_version".to_string(), Value::String(TARGET_VERSION.to_string()));
+    }
+}
+
+/// 1.0 -> 1.1: rewrites legacy ids (e.g. Windows `Biology\Week1.md`) into
+/// canonical form everywhere workspace.json stores them.
+fn migrate_workspace_1_0_to_1_1(value: &mut Value) {
+    fn canonical_string(value: &mut Value) {
+        if let Some(id) = value.as_str() {
+            *value = Value::String(canonicalize_id(id));
+        }
+    }
+
+    fn canonical_strings(value: Option<&mut Value>) {
+        if let Some(items) = value.and_then(Value::as_array_mut) {
+            items.iter_mut().for_each(canonical_string);
+        }
+    }
+
+    fn canonical_keys(value: Option<&mut Value>) {
+        if let Some(map) = value.and_then(Value::as_object_mut) {
+            let entries = std::mem::take(map);
+            map.extend(entries.into_iter().map(|(k, v)| (canonicalize_id(&k), v)));
+        }
+    }
+
+    fn canonical_tree(nodes: Option<&mut Value>) {
+        for node in nodes.and_then(Value::as_array_mut).into_iter().flatten() {
+            if let Some(id) = node.get_mut("id") {
+                canonical_string(id);
+            }
+            if let Some(path) = node.get_mut("path") {
+                canonical_string(path);
+            }
+            canonical_tree(node.get_mut("children"));
+        }
+    }
+
+    canonical_tree(value.get_mut("tree"));
+    canonical_keys(value.get_mut("decorations"));
+
+    if let Some(session) = value.get_mut("session") {
+        canonical_strings(session.get_mut("open_nodes"));
+        if let Some(active) = session.get_mut("active_node") {
+            canonical_string(active);
+        }
+        canonical_keys(session.get_mut("cursor"));
+    }
+
+    if let Some(settings) = value.get_mut("settings") {
+        canonical_strings(settings.get_mut("favorites"));
+        if let Some(bookmarks) = settings.get_mut("bookmarks").and_then(Value::as_array_mut) {
+            for bookmark in bookmarks {
+                if let Some(path) = bookmark.get_mut("path") {
+                    canonical_string(path);
+                }
+            }
+        }
+        if let Some(order) = settings.get_mut("manual_order") {
+            canonical_keys(Some(order));
+            if let Some(map) = order.as_object_mut() {
+                map.values_mut().for_each(|children| canonical_strings(Some(children)));
+            }
+        }
+    }
+}
+
+/// 1.1 -> 1.2: drops the stored tree, keeping what only it recorded.
+///
+/// - `settings.favorites` and `settings.manual_order` move to top-level
+///   `favorites` and `manual_order`.
+/// - A folder whose stored children differ from the default order (folders
+///   first, then case-insensitive by name) gets a `manual_order` entry.
+/// - `icon`/`color` in node meta become `decorations` entries, and
+///   `favorite: true` in node meta a `favorites` entry.
+///
+/// Entries already in the new sections win over ones derived from the tree.
+fn migrate_workspace_1_1_to_1_2(value: &mut Value) {
+    let Some(doc) = value.as_object_mut() else {
+        return;
+    };
+
+    let tree = doc.remove("tree").unwrap_or(Value::Null);
+    let settings = doc.get_mut("settings").and_then(Value::as_object_mut);
+    let (old_favorites, old_order) = match settings {
+        Some(settings) => (settings.remove("favorites"), settings.remove("manual_order")),
+        None => (None, None),
+    };
+
+    let mut favorites: Vec<Value> = old_favorites
+        .and_then(|f| f.as_array().cloned())
+        .unwrap_or_default();
+    let mut manual_order = match old_order {
+        Some(Value::Object(order)) => order,
+        _ => Map::new(),
+    };
+    let mut decorations = match doc.remove("decorations") {
+        Some(Value::Object(decorations)) => decorations,
+        _ => Map::new(),
+    };
+
+    fn walk(
+        nodes: &Value,
+        parent: &str,
+        manual_order: &mut Map<String, Value>,
+        decorations: &mut Map<String, Value>,
+        favorites: &mut Vec<Value>,
+    ) {
+        let Some(nodes) = nodes.as_array() else {
             return;
+        };
+
+        let entry = |node: &Value| {
+            let id = node.get("id").and_then(Value::as_str).unwrap_or_default().to_string();
+            let name = node.get("name").and_then(Value::as_str).unwrap_or_default().to_lowercase();
+            let is_folder = node.get("type").and_then(Value::as_str) == Some("folder");
+            (!is_folder, name, id)
+        };
+        let stored: Vec<_> = nodes.iter().map(entry).collect();
+        let mut sorted = stored.clone();
+        sorted.sort();
+        if stored != sorted && !manual_order.contains_key(parent) {
+            let ids = stored.into_iter().map(|(_, _, id)| Value::String(id)).collect();
+            manual_order.insert(parent.to_string(), Value::Array(ids));
         }
-        
-        // Example: migrate from 1.0 to 1.1 in future:
-        // if version == "1.0" {
-        //     migrate_workspace_1_0_to_1_1(value);
-        // }
-    } else {
-        // If no version is found, assume 1.0 and set it
-        if let Some(obj) = value.as_object_mut() {
-            obj.insert("schema_version".to_string(), Value::String(TARGET_VERSION.to_string()));
+
+        for node in nodes {
+            let Some(id) = node.get("id").and_then(Value::as_str) else {
+                continue;
+            };
+            if let Some(meta) = node.get("meta").and_then(Value::as_object) {
+                let decoration: Map<String, Value> = ["icon", "color"]
+                    .into_iter()
+                    .filter_map(|key| {
+                        let value = meta.get(key).filter(|v| v.is_string())?;
+                        Some((key.to_string(), value.clone()))
+                    })
+                    .collect();
+                if !decoration.is_empty() && !decorations.contains_key(id) {
+                    decorations.insert(id.to_string(), Value::Object(decoration));
+                }
+                let id_value = Value::String(id.to_string());
+                if meta.get("favorite") == Some(&Value::Bool(true)) && !favorites.contains(&id_value) {
+                    favorites.push(id_value);
+                }
+            }
+            if let Some(children) = node.get("children") {
+                walk(children, id, manual_order, decorations, favorites);
+            }
+        }
+    }
+    walk(&tree, "", &mut manual_order, &mut decorations, &mut favorites);
+
+    for (key, section) in [
+        ("decorations", Value::Object(decorations)),
+        ("manual_order", Value::Object(manual_order)),
+        ("favorites", Value::Array(favorites)),
+    ] {
+        let empty = match &section {
+            Value::Object(map) => map.is_empty(),
+            Value::Array(items) => items.is_empty(),
+            _ => true,
+        };
+        if !empty {
+            doc.insert(key.to_string(), section);
         }
     }
 }
@@ -0,0 +1,313 @@
+//! ============================================================================
+//! Hibiscus OCR
+//! ============================================================================
+//!
+//! Optional text extraction from images, used by `extract_image_text` and,
+//! when `settings.ocr_in_search` is on, by the knowledge indexer.
+//!
+//! DESIGN DECISIONS:
+//! - Recognition goes through the `OcrBackend` trait. The shipped backend
+//!   shells out to the user's `tesseract` binary (like `git.rs` does for git),
+//!   so no native OCR library is linked into the app.
+//! - A missing binary or language pack is reported as `HibiscusError::Ocr`
+//!   with instructions, never a panic.
+//! - Results are cached in `.hibiscus/ocr/<content-hash>.json`, so renaming
+//!   or moving an image does not re-run recognition.
+//!
+//! OCR is an optional extra; without tesseract every other feature works.
+//! ============================================================================
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::error::HibiscusError;
+use crate::knowledge::storage::hash_file;
+
+/// Language used when the caller does not pick one.
+pub const DEFAULT_OCR_LANGUAGE: &str = "eng";
+
+/// Images larger than this are not OCR'd by the search indexer.
+pub const OCR_MAX_IMAGE_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Image formats tesseract can read.
+const OCR_IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "tif", "tiff", "bmp", "webp", "gif"];
+
+const TESSERACT_MISSING: &str = "Tesseract is not installed or not on PATH. \
+    Install it (e.g. `brew install tesseract`, `apt install tesseract-ocr`, \
+    or the Windows installer from github.com/UB-Mannheim/tesseract) and restart Hibiscus";
+
+/// Availability of the OCR backend.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OcrStatus {
+    pub available: bool,
+    /// Backend version, e.g. "tesseract 5.3.0"
+    pub version: Option<String>,
+    /// Installed language packs, e.g. ["eng", "deu"]
+    pub languages: Vec<String>,
+    /// Why OCR is unavailable, with what to do about it
+    pub message: Option<String>,
+}
+
+/// Text recognized in one image.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OcrResult {
+    pub path: String,
+    pub language: String,
+    pub text: String,
+    /// Whether the text came from `.hibiscus/ocr` instead of the backend
+    pub cached: bool,
+}
+
+/// An OCR engine.
+pub trait OcrBackend {
+    /// Reports whether the engine can run and which languages it has.
+    fn status(&self) -> OcrStatus;
+
+    /// Recognizes the text in `image` using `language` (e.g. "eng+deu").
+    fn recognize(&self, image: &Path, language: &str) -> Result<String, HibiscusError>;
+}
+
+/// Backend that runs the `tesseract` command-line tool.
+pub struct TesseractCli;
+
+impl TesseractCli {
+    fn run(args: &[&std::ffi::OsStr]) -> Result<std::process::Output, HibiscusError> {
+        Command::new("tesseract").args(args).output().map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                HibiscusError::Ocr(TESSERACT_MISSING.into())
+            } else {
+                HibiscusError::Ocr(format!("Failed to run tesseract: {}", e))
+            }
+        })
+    }
+}
+
+impl OcrBackend for TesseractCli {
+    fn status(&self) -> OcrStatus {
+        let version = match Self::run(&["--version".as_ref()]) {
+            Ok(output) => {
+                // Older releases print the version to stderr
+                let text = if output.stdout.is_empty() { output.stderr } else { output.stdout };
+                String::from_utf8_lossy(&text).lines().next().map(|l| l.trim().to_string())
+            }
+            Err(e) => {
+                return OcrStatus {
+                    available: false,
+                    version: None,
+                    languages: Vec::new(),
+                    message: Some(e.to_string()),
+                }
+            }
+        };
+
+        // The first line is a "List of available languages ..." header
+        let languages: Vec<String> = Self::run(&["--list-langs".as_ref()])
+            .map(|output| {
+                let text = [output.stdout, output.stderr].concat();
+                String::from_utf8_lossy(&text)
+                    .lines()
+                    .skip_while(|line| !line.starts_with("List of"))
+                    .skip(1)
+                    .map(|line| line.trim().to_string())
+                    .filter(|lang| !lang.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let message = languages.is_empty().then(|| {
+            "Tesseract has no language data installed. Install a language pack \
+             (e.g. `tesseract-ocr-eng`) to enable OCR"
+                .to_string()
+        });
+
+        OcrStatus {
+            available: !languages.is_empty(),
+            version,
+            languages,
+            message,
+        }
+    }
+
+    fn recognize(&self, image: &Path, language: &str) -> Result<String, HibiscusError> {
+        let output = Self::run(&[
+            image.as_os_str(),
+            "stdout".as_ref(),
+            "-l".as_ref(),
+            language.as_ref(),
+        ])?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let detail = stderr.lines().find(|l| !l.trim().is_empty()).unwrap_or("unknown error");
+            return Err(HibiscusError::Ocr(format!(
+                "Tesseract failed on {} (is the '{}' language pack installed?): {}",
+                image.display(),
+                language,
+                detail.trim()
+            )));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+/// Cached recognitions of one image, keyed by language.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheEntry {
+    #[serde(default)]
+    languages: BTreeMap<String, String>,
+}
+
+/// Returns the directory holding the OCR cache of the workspace at `root`.
+pub fn cache_dir(root: &Path) -> PathBuf {
+    root.join(".hibiscus").join("ocr")
+}
+
+/// Returns `true` if `path` has an image extension tesseract can read.
+pub fn is_ocr_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| OCR_IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// Checks a tesseract language spec: codes like `eng` joined by `+`.
+pub fn validate_language(language: &str) -> Result<(), HibiscusError> {
+    let valid = !language.is_empty()
+        && language.split('+').all(|code| {
+            !code.is_empty() && code.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+
+    if valid {
+        Ok(())
+    } else {
+        Err(HibiscusError::Ocr(format!("Invalid OCR language '{}'", language)))
+    }
+}
+
+/// Recognizes the text in `image`, reusing a cached result for the same
+/// content and language.
+///
+/// Cache write failures are logged and otherwise ignored; the text is still
+/// returned.
+pub fn extract_text(
+    root: &Path,
+    image: &Path,
+    language: &str,
+    backend: &dyn OcrBackend,
+) -> Result<OcrResult, HibiscusError> {
+    validate_language(language)?;
+
+    let hash = hash_file(&image.to_string_lossy())
+        .ok_or_else(|| HibiscusError::FileNotFound(image.to_string_lossy().into()))?;
+    let cache_path = cache_dir(root).join(format!("{}.json", hash));
+
+    let mut entry: CacheEntry = std::fs::read_to_string(&cache_path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+
+    let result = |text: String, cached: bool| OcrResult {
+        path: image.to_string_lossy().into(),
+        language: language.to_string(),
+        text,
+        cached,
+    };
+
+    if let Some(text) = entry.languages.get(language) {
+        return Ok(result(text.clone(), true));
+    }
+
+    let text = backend.recognize(image, language)?.trim().to_string();
+    entry.languages.insert(language.to_string(), text.clone());
+
+    let written = std::fs::create_dir_all(cache_dir(root)).and_then(|_| {
+        let json = serde_json::to_string_pretty(&entry).map_err(std::io::Error::other)?;
+        std::fs::write(&cache_path, json)
+    });
+    if let Err(e) = written {
+        eprintln!("[Hibiscus] Warning: Failed to cache OCR result for {}: {}", image.display(), e);
+    }
+
+    Ok(result(text, false))
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use tempfile::tempdir;
+
+    /// Backend that returns a fixed text and counts its calls.
+    struct MockBackend {
+        calls: Cell<usize>,
+    }
+
+    impl OcrBackend for MockBackend {
+        fn status(&self) -> OcrStatus {
+            OcrStatus {
+                available: true,
+                version: Some("mock 1.0".into()),
+                languages: vec!["eng".into()],
+                message: None,
+            }
+        }
+
+        fn recognize(&self, _image: &Path, language: &str) -> Result<String, HibiscusError> {
+            self.calls.set(self.calls.get() + 1);
+            Ok(format!("  text in {}\n", language))
+        }
+    }
+
+    #[test]
+    fn test_extract_text_uses_content_hash_cache() {
+        let dir = tempdir().unwrap();
+        let image = dir.path().join("scan.png");
+        std::fs::write(&image, b"fake png bytes").unwrap();
+        let backend = MockBackend { calls: Cell::new(0) };
+
+        let first = extract_text(dir.path(), &image, "eng", &backend).unwrap();
+        assert_eq!(first.text, "text in eng");
+        assert!(!first.cached);
+
+        // Same bytes under another name hit the cache
+        let copy = dir.path().join("copy.png");
+        std::fs::copy(&image, &copy).unwrap();
+        let second = extract_text(dir.path(), &copy, "eng", &backend).unwrap();
+        assert!(second.cached);
+        assert_eq!(second.text, "text in eng");
+        assert_eq!(backend.calls.get(), 1);
+
+        // Another language is a separate recognition
+        let german = extract_text(dir.path(), &image, "deu", &backend).unwrap();
+        assert!(!german.cached);
+        assert_eq!(backend.calls.get(), 2);
+        assert_eq!(std::fs::read_dir(cache_dir(dir.path())).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_rejects_bad_language_and_missing_image() {
+        let dir = tempdir().unwrap();
+        let backend = MockBackend { calls: Cell::new(0) };
+
+        let image = dir.path().join("a.png");
+        std::fs::write(&image, b"x").unwrap();
+        assert!(matches!(
+            extract_text(dir.path(), &image, "--psm", &backend),
+            Err(HibiscusError::Ocr(_))
+        ));
+        assert!(validate_language("eng+deu").is_ok());
+
+        let missing = dir.path().join("missing.png");
+        assert!(matches!(
+            extract_text(dir.path(), &missing, "eng", &backend),
+            Err(HibiscusError::FileNotFound(_))
+        ));
+        assert_eq!(backend.calls.get(), 0);
+    }
+}
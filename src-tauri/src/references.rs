@@ -0,0 +1,707 @@
+//! ============================================================================
+//! Hibiscus Reference Reconciliation
+//! ============================================================================
+//!
+//! Keeps stored node references consistent with the filesystem after files
+//! or folders are renamed or deleted.
+//!
+//! Several features persist workspace-relative paths outside the tree itself
+//! (open tabs, favorites, bookmarks, manual orderings, calendar links). Each
+//! of these is modelled as a `ReferenceStore`. A single reconciliation pass
+//! loads every backing document once, lets each registered store remap or
+//! prune its references, and writes back only the documents that changed.
+//!
+//! REGISTERED STORES:
+//! - session:      workspace.json `session` (open nodes, active node, cursors)
+//! - favorites:    workspace.json `favorites` (array of paths)
+//! - bookmarks:    workspace.json `settings.bookmarks` (array of `{ path }`)
+//! - manual_order: workspace.json `manual_order` (folder -> children)
+//! - decorations:  workspace.json `decorations` (node id -> icon/color)
+//! - calendar:     calendar.json `events[].linkedFile`
+//! - review:       review.json `notes` (note id -> review schedule)
+//!
+//! Adding a new store only requires implementing the trait and listing it
+//! in `default_stores`.
+//! ============================================================================
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::HibiscusError;
+
+// ---------------------------------------------------------------------------
+// Change + report types
+// ---------------------------------------------------------------------------
+
+/// A filesystem change that may invalidate stored references.
+///
+/// Paths may be absolute or workspace-relative; they are normalized against
+/// the workspace root before any store sees them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum FileChange {
+    /// A file or folder moved from `from` to `to`.
+    Renamed { from: String, to: String },
+    /// A file or folder was removed.
+    Deleted { path: String },
+}
+
+impl FileChange {
+    /// Returns this change with both paths made workspace-relative and
+    /// normalized to forward slashes.
+    fn relative_to(&self, root: &Path) -> FileChange {
+        match self {
+            FileChange::Renamed { from, to } => FileChange::Renamed {
+                from: relative_reference(root, from),
+                to: relative_reference(root, to),
+            },
+            FileChange::Deleted { path } => FileChange::Deleted {
+                path: relative_reference(root, path),
+            },
+        }
+    }
+}
+
+/// A single reference that was rewritten to follow a rename.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RemappedReference {
+    pub from: String,
+    pub to: String,
+}
+
+/// What a single store changed during reconciliation.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StoreReport {
+    /// Name of the store (e.g. "session", "calendar").
+    pub store: String,
+    /// References that now point at a renamed location.
+    pub remapped: Vec<RemappedReference>,
+    /// References that were removed because their target was deleted.
+    pub pruned: Vec<String>,
+}
+
+impl StoreReport {
+    fn new(store: &str) -> Self {
+        Self {
+            store: store.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns `true` if the store did not touch any reference.
+    pub fn is_empty(&self) -> bool {
+        self.remapped.is_empty() && self.pruned.is_empty()
+    }
+}
+
+/// Combined result of a reconciliation pass, one entry per registered store.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReconcileReport {
+    pub stores: Vec<StoreReport>,
+}
+
+impl ReconcileReport {
+    /// Looks up the report for a store by name.
+    pub fn store(&self, name: &str) -> Option<&StoreReport> {
+        self.stores.iter().find(|s| s.store == name)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Store trait
+// ---------------------------------------------------------------------------
+
+/// The JSON document a store keeps its references in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoreDocument {
+    /// `.hibiscus/workspace.json`
+    Workspace,
+    /// `.hibiscus/calendar.json`
+    Calendar,
+    /// `.hibiscus/review.json`
+    Review,
+}
+
+impl StoreDocument {
+    const ALL: [StoreDocument; 3] = [
+        StoreDocument::Workspace,
+        StoreDocument::Calendar,
+        StoreDocument::Review,
+    ];
+
+    fn path(self, root: &Path) -> PathBuf {
+        let file = match self {
+            StoreDocument::Workspace => "workspace.json",
+            StoreDocument::Calendar => "calendar.json",
+            StoreDocument::Review => "review.json",
+        };
+        root.join(".hibiscus").join(file)
+    }
+}
+
+/// A feature-owned collection of node references.
+///
+/// Implementations receive the already-parsed backing document and must
+/// record every reference they rewrite or remove in `report`. They never
+/// touch the disk themselves.
+pub trait ReferenceStore: Send + Sync {
+    /// Stable store name used in reports.
+    fn name(&self) -> &'static str;
+
+    /// The document this store lives in.
+    fn document(&self) -> StoreDocument;
+
+    /// Remap or prune references in `doc` according to `changes`.
+    fn apply(&self, doc: &mut Value, changes: &[FileChange], report: &mut StoreReport);
+}
+
+/// Returns every store that participates in reconciliation.
+pub fn default_stores() -> Vec<Box<dyn ReferenceStore>> {
+    vec![
+        Box::new(SessionStore),
+        Box::new(FavoritesStore),
+        Box::new(BookmarksStore),
+        Box::new(ManualOrderStore),
+        Box::new(DecorationsStore),
+        Box::new(CalendarLinkStore),
+        Box::new(ReviewStore),
+    ]
+}
+
+// ---------------------------------------------------------------------------
+// Reconciliation pass
+// ---------------------------------------------------------------------------
+
+/// Applies `changes` to every registered reference store under `root`.
+///
+/// Missing documents are skipped (their stores report no changes). A
+/// document is only rewritten if at least one of its stores changed it.
+pub fn reconcile(root: &Path, changes: &[FileChange]) -> Result<ReconcileReport, HibiscusError> {
+    let changes: Vec<FileChange> = changes.iter().map(|c| c.relative_to(root)).collect();
+    let stores = default_stores();
+    let mut report = ReconcileReport::default();
+
+    for document in StoreDocument::ALL {
+        let doc_stores: Vec<&dyn ReferenceStore> = stores
+            .iter()
+            .map(|s| s.as_ref())
+            .filter(|s| s.document() == document)
+            .collect();
+
+        let path = document.path(root);
+        if !path.is_file() {
+            report
+                .stores
+                .extend(doc_stores.iter().map(|s| StoreReport::new(s.name())));
+            continue;
+        }
+
+        let content = fs::read_to_string(&path).map_err(|e| {
+            HibiscusError::Io(format!("Failed to read '{}': {}", path.display(), e))
+        })?;
+        let mut doc: Value = serde_json::from_str(&content)?;
+
+        let mut dirty = false;
+        for store in doc_stores {
+            let mut store_report = StoreReport::new(store.name());
+            store.apply(&mut doc, &changes, &mut store_report);
+            dirty |= !store_report.is_empty();
+            report.stores.push(store_report);
+        }
+
+        if dirty {
+            write_json_atomic(&path, &doc)?;
+        }
+    }
+
+    Ok(report)
+}
+
+/// Writes `value` as pretty JSON via temp file + rename.
+pub(crate) fn write_json_atomic(path: &Path, value: &Value) -> Result<(), HibiscusError> {
+    let json = serde_json::to_string_pretty(value)?;
+    let temp_path = path.with_extension("json.tmp");
+
+    fs::write(&temp_path, json).map_err(|e| {
+        HibiscusError::Io(format!("Failed to write '{}': {}", temp_path.display(), e))
+    })?;
+
+    #[cfg(target_os = "windows")]
+    if path.exists() {
+        if let Err(e) = fs::remove_file(path) {
+            let _ = fs::remove_file(&temp_path);
+            return Err(HibiscusError::Io(format!(
+                "Failed to remove existing '{}': {}",
+                path.display(),
+                e
+            )));
+        }
+    }
+
+    fs::rename(&temp_path, path).map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        HibiscusError::Io(format!("Failed to finalize '{}': {}", path.display(), e))
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Path resolution helpers
+// ---------------------------------------------------------------------------
+
+/// Outcome of running a single reference through a change list.
+#[derive(Debug, PartialEq, Eq)]
+enum Resolution {
+    Unchanged,
+    Remapped(String),
+    Pruned,
+}
+
+/// Normalizes a stored reference to canonical id form (see `crate::ids`).
+fn normalize(reference: &str) -> String {
+    crate::ids::canonicalize_id(reference)
+}
+
+/// Makes `path` relative to `root` when it lies inside it, then normalizes.
+fn relative_reference(root: &Path, path: &str) -> String {
+    match Path::new(path).strip_prefix(root) {
+        Ok(rel) => normalize(&rel.to_string_lossy()),
+        Err(_) => normalize(path),
+    }
+}
+
+/// If `reference` is `target` or lies beneath it, returns the remainder
+/// (empty, or starting with `/`).
+fn strip_within<'a>(reference: &'a str, target: &str) -> Option<&'a str> {
+    let rest = reference.strip_prefix(target)?;
+    if rest.is_empty() || rest.starts_with('/') {
+        Some(rest)
+    } else {
+        None
+    }
+}
+
+/// Runs a reference through every change in order.
+fn resolve(reference: &str, changes: &[FileChange]) -> Resolution {
+    let mut current = normalize(reference);
+    let mut remapped = false;
+
+    for change in changes {
+        match change {
+            FileChange::Renamed { from, to } => {
+                if let Some(rest) = strip_within(&current, from) {
+                    current = format!("{}{}", to, rest);
+                    remapped = true;
+                }
+            }
+            FileChange::Deleted { path } => {
+                if strip_within(&current, path).is_some() {
+                    return Resolution::Pruned;
+                }
+            }
+        }
+    }
+
+    if remapped {
+        Resolution::Remapped(current)
+    } else {
+        Resolution::Unchanged
+    }
+}
+
+/// Reconciles a single string slot in place.
+///
+/// Returns `false` if the referenced path was deleted and the caller should
+/// drop the slot. Non-string values are left alone.
+fn reconcile_slot(slot: &mut Value, changes: &[FileChange], report: &mut StoreReport) -> bool {
+    let Some(reference) = slot.as_str() else {
+        return true;
+    };
+
+    match resolve(reference, changes) {
+        Resolution::Unchanged => true,
+        Resolution::Remapped(to) => {
+            report.remapped.push(RemappedReference {
+                from: reference.to_string(),
+                to: to.clone(),
+            });
+            *slot = Value::String(to);
+            true
+        }
+        Resolution::Pruned => {
+            report.pruned.push(reference.to_string());
+            false
+        }
+    }
+}
+
+/// Reconciles every string in an array, dropping deleted entries.
+fn reconcile_string_array(array: &mut Value, changes: &[FileChange], report: &mut StoreReport) {
+    if let Some(items) = array.as_array_mut() {
+        items.retain_mut(|item| reconcile_slot(item, changes, report));
+    }
+}
+
+/// Reconciles the keys of a JSON object, dropping entries for deleted paths.
+fn reconcile_keys(
+    map: &mut Map<String, Value>,
+    changes: &[FileChange],
+    report: &mut StoreReport,
+) {
+    let entries = std::mem::take(map);
+    for (key, value) in entries {
+        let mut slot = Value::String(key);
+        if reconcile_slot(&mut slot, changes, report) {
+            if let Value::String(key) = slot {
+                map.insert(key, value);
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Store implementations
+// ---------------------------------------------------------------------------
+
+/// Open tabs, active tab, and per-node cursor positions.
+struct SessionStore;
+
+impl ReferenceStore for SessionStore {
+    fn name(&self) -> &'static str {
+        "session"
+    }
+
+    fn document(&self) -> StoreDocument {
+        StoreDocument::Workspace
+    }
+
+    fn apply(&self, doc: &mut Value, changes: &[FileChange], report: &mut StoreReport) {
+        let Some(session) = doc.get_mut("session").and_then(Value::as_object_mut) else {
+            return;
+        };
+
+        if let Some(open_nodes) = session.get_mut("open_nodes") {
+            reconcile_string_array(open_nodes, changes, report);
+        }
+
+        if let Some(active) = session.get_mut("active_node") {
+            if !reconcile_slot(active, changes, report) {
+                *active = Value::Null;
+            }
+        }
+
+        if let Some(cursor) = session.get_mut("cursor").and_then(Value::as_object_mut) {
+            reconcile_keys(cursor, changes, report);
+        }
+    }
+}
+
+/// Favorited paths in the top-level `favorites` array (`settings.favorites`
+/// in files not yet saved with schema 1.2).
+struct FavoritesStore;
+
+impl ReferenceStore for FavoritesStore {
+    fn name(&self) -> &'static str {
+        "favorites"
+    }
+
+    fn document(&self) -> StoreDocument {
+        StoreDocument::Workspace
+    }
+
+    fn apply(&self, doc: &mut Value, changes: &[FileChange], report: &mut StoreReport) {
+        for pointer in ["/favorites", "/settings/favorites"] {
+            if let Some(favorites) = doc.pointer_mut(pointer) {
+                reconcile_string_array(favorites, changes, report);
+            }
+        }
+    }
+}
+
+/// Bookmarks stored as objects with a `path` field in `settings.bookmarks`.
+/// A bookmark whose target is deleted is removed entirely.
+struct BookmarksStore;
+
+impl ReferenceStore for BookmarksStore {
+    fn name(&self) -> &'static str {
+        "bookmarks"
+    }
+
+    fn document(&self) -> StoreDocument {
+        StoreDocument::Workspace
+    }
+
+    fn apply(&self, doc: &mut Value, changes: &[FileChange], report: &mut StoreReport) {
+        let Some(bookmarks) = doc
+            .pointer_mut("/settings/bookmarks")
+            .and_then(Value::as_array_mut)
+        else {
+            return;
+        };
+
+        bookmarks.retain_mut(|bookmark| match bookmark.get_mut("path") {
+            Some(path) => reconcile_slot(path, changes, report),
+            None => true,
+        });
+    }
+}
+
+/// Manual child ordering in the top-level `manual_order` map (or
+/// `settings.manual_order` before schema 1.2): folder path -> ordered list
+/// of child paths. Both the folder keys and the children are updated.
+struct ManualOrderStore;
+
+impl ReferenceStore for ManualOrderStore {
+    fn name(&self) -> &'static str {
+        "manual_order"
+    }
+
+    fn document(&self) -> StoreDocument {
+        StoreDocument::Workspace
+    }
+
+    fn apply(&self, doc: &mut Value, changes: &[FileChange], report: &mut StoreReport) {
+        for pointer in ["/manual_order", "/settings/manual_order"] {
+            let Some(order) = doc.pointer_mut(pointer).and_then(Value::as_object_mut) else {
+                continue;
+            };
+
+            reconcile_keys(order, changes, report);
+            for children in order.values_mut() {
+                reconcile_string_array(children, changes, report);
+            }
+        }
+    }
+}
+
+/// Node icon/color overrides in the top-level `decorations` map, keyed by
+/// node id (the node's relative path).
+struct DecorationsStore;
+
+impl ReferenceStore for DecorationsStore {
+    fn name(&self) -> &'static str {
+        "decorations"
+    }
+
+    fn document(&self) -> StoreDocument {
+        StoreDocument::Workspace
+    }
+
+    fn apply(&self, doc: &mut Value, changes: &[FileChange], report: &mut StoreReport) {
+        if let Some(decorations) = doc.get_mut("decorations").and_then(Value::as_object_mut) {
+            reconcile_keys(decorations, changes, report);
+        }
+    }
+}
+
+/// `linkedFile` on calendar events. Deleting the target unlinks the event
+/// but keeps the event itself.
+struct CalendarLinkStore;
+
+impl ReferenceStore for CalendarLinkStore {
+    fn name(&self) -> &'static str {
+        "calendar"
+    }
+
+    fn document(&self) -> StoreDocument {
+        StoreDocument::Calendar
+    }
+
+    fn apply(&self, doc: &mut Value, changes: &[FileChange], report: &mut StoreReport) {
+        let Some(events) = doc.get_mut("events").and_then(Value::as_array_mut) else {
+            return;
+        };
+
+        for event in events.iter_mut().filter_map(Value::as_object_mut) {
+            let keep = match event.get_mut("linkedFile") {
+                Some(link) => reconcile_slot(link, changes, report),
+                None => true,
+            };
+            if !keep {
+                event.remove("linkedFile");
+            }
+        }
+    }
+}
+
+/// Review schedules in review.json, keyed by note id. A deleted note leaves
+/// the schedule.
+struct ReviewStore;
+
+impl ReferenceStore for ReviewStore {
+    fn name(&self) -> &'static str {
+        "review"
+    }
+
+    fn document(&self) -> StoreDocument {
+        StoreDocument::Review
+    }
+
+    fn apply(&self, doc: &mut Value, changes: &[FileChange], report: &mut StoreReport) {
+        if let Some(notes) = doc.get_mut("notes").and_then(Value::as_object_mut) {
+            reconcile_keys(notes, changes, report);
+        }
+    }
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn write_fixtures(root: &Path) {
+        let hibiscus = root.join(".hibiscus");
+        fs::create_dir_all(&hibiscus).unwrap();
+        let workspace = json!({
+            "schema_version": "1.0",
+            "workspace": { "id": "ws", "name": "Test", "root": "." },
+            "settings": {
+                "favorites": ["notes/a.md", "other.md"],
+                "bookmarks": [{ "path": "notes/a.md", "line": 3 }],
+                "manual_order": { "notes": ["notes/b.md", "notes/a.md"] }
+            },
+            "tree": [],
+            "decorations": {
+                "notes": { "icon": "folder-star", "color": "#ff8800" },
+                "notes/a.md": { "color": "#00ff00" }
+            },
+            "session": {
+                "open_nodes": ["notes/a.md", "other.md"],
+                "active_node": "notes/a.md",
+                "cursor": { "notes/a.md": { "line": 1, "column": 2 } }
+            }
+        });
+        fs::write(hibiscus.join("workspace.json"), workspace.to_string()).unwrap();
+        let calendar = json!({
+            "events": [
+                { "id": "e1", "title": "Exam", "linkedFile": "notes/a.md" },
+                { "id": "e2", "title": "Other" }
+            ],
+            "tasks": []
+        });
+        fs::write(hibiscus.join("calendar.json"), calendar.to_string()).unwrap();
+        let review = json!({
+            "notes": {
+                "notes/a.md": { "ease": 2.5, "interval_days": 6, "repetitions": 2, "due": "2024-03-08" }
+            }
+        });
+        fs::write(hibiscus.join("review.json"), review.to_string()).unwrap();
+    }
+
+    fn read(root: &Path, file: &str) -> Value {
+        let content = fs::read_to_string(root.join(".hibiscus").join(file)).unwrap();
+        serde_json::from_str(&content).unwrap()
+    }
+
+    #[test]
+    fn test_rename_fans_out_to_multiple_stores() {
+        let dir = tempdir().unwrap();
+        write_fixtures(dir.path());
+
+        let changes = vec![FileChange::Renamed {
+            from: "notes/a.md".into(),
+            to: "notes/renamed.md".into(),
+        }];
+        let report = reconcile(dir.path(), &changes).unwrap();
+
+        assert_eq!(report.store("session").unwrap().remapped.len(), 3);
+        assert_eq!(report.store("favorites").unwrap().remapped.len(), 1);
+        assert_eq!(report.store("calendar").unwrap().remapped.len(), 1);
+
+        let ws = read(dir.path(), "workspace.json");
+        assert_eq!(ws["session"]["active_node"], "notes/renamed.md");
+        assert_eq!(ws["session"]["open_nodes"][0], "notes/renamed.md");
+        assert!(ws["session"]["cursor"]["notes/renamed.md"].is_object());
+        assert_eq!(ws["settings"]["favorites"][0], "notes/renamed.md");
+        assert_eq!(ws["settings"]["bookmarks"][0]["path"], "notes/renamed.md");
+
+        let cal = read(dir.path(), "calendar.json");
+        assert_eq!(cal["events"][0]["linkedFile"], "notes/renamed.md");
+
+        let review = read(dir.path(), "review.json");
+        assert_eq!(review["notes"]["notes/renamed.md"]["interval_days"], 6);
+        assert!(review["notes"].get("notes/a.md").is_none());
+    }
+
+    #[test]
+    fn test_folder_rename_remaps_descendants_and_order_keys() {
+        let dir = tempdir().unwrap();
+        write_fixtures(dir.path());
+
+        let changes = vec![FileChange::Renamed {
+            from: dir.path().join("notes").to_string_lossy().into(),
+            to: dir.path().join("archive").to_string_lossy().into(),
+        }];
+        reconcile(dir.path(), &changes).unwrap();
+
+        let ws = read(dir.path(), "workspace.json");
+        let order = &ws["settings"]["manual_order"]["archive"];
+        assert_eq!(order[0], "archive/b.md");
+        assert_eq!(order[1], "archive/a.md");
+        assert_eq!(ws["settings"]["favorites"][1], "other.md");
+    }
+
+    #[test]
+    fn test_delete_prunes_references() {
+        let dir = tempdir().unwrap();
+        write_fixtures(dir.path());
+
+        let changes = vec![FileChange::Deleted {
+            path: "notes/a.md".into(),
+        }];
+        let report = reconcile(dir.path(), &changes).unwrap();
+        assert_eq!(report.store("bookmarks").unwrap().pruned, vec!["notes/a.md"]);
+
+        let ws = read(dir.path(), "workspace.json");
+        assert!(ws["session"]["active_node"].is_null());
+        assert_eq!(ws["session"]["open_nodes"].as_array().unwrap().len(), 1);
+        assert!(ws["settings"]["bookmarks"].as_array().unwrap().is_empty());
+
+        // The event survives, only its link is removed.
+        let cal = read(dir.path(), "calendar.json");
+        assert_eq!(cal["events"].as_array().unwrap().len(), 2);
+        assert!(cal["events"][0].get("linkedFile").is_none());
+    }
+
+    #[test]
+    fn test_delete_prunes_decorations() {
+        let dir = tempdir().unwrap();
+        write_fixtures(dir.path());
+
+        let changes = vec![FileChange::Deleted {
+            path: "notes/a.md".into(),
+        }];
+        let report = reconcile(dir.path(), &changes).unwrap();
+        assert_eq!(report.store("decorations").unwrap().pruned, vec!["notes/a.md"]);
+
+        let ws = read(dir.path(), "workspace.json");
+        let decorations = ws["decorations"].as_object().unwrap();
+        assert_eq!(decorations.len(), 1);
+        assert_eq!(decorations["notes"]["icon"], "folder-star");
+    }
+
+    #[test]
+    fn test_sibling_with_shared_prefix_is_untouched() {
+        let changes = vec![FileChange::Deleted { path: "notes".into() }];
+        assert_eq!(resolve("notes-old/a.md", &changes), Resolution::Unchanged);
+        assert_eq!(resolve("notes/a.md", &changes), Resolution::Pruned);
+    }
+
+    #[test]
+    fn test_missing_documents_report_empty_stores() {
+        let dir = tempdir().unwrap();
+        let report = reconcile(
+            dir.path(),
+            &[FileChange::Deleted { path: "x.md".into() }],
+        )
+        .unwrap();
+        assert_eq!(report.stores.len(), default_stores().len());
+        assert!(report.stores.iter().all(StoreReport::is_empty));
+    }
+}
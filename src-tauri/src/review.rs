@@ -0,0 +1,345 @@
+//! ============================================================================
+//! Hibiscus Note Review Scheduling
+//! ============================================================================
+//!
+//! Spaced-repetition scheduling for notes the user wants to revisit, stored
+//! in `.hibiscus/review.json` keyed by canonical note id.
+//!
+//! SCHEDULING (SM-2):
+//! - A review is graded 0-5. Grades below `PASSING_QUALITY` restart the
+//!   note: one day until the next review, repetitions back to zero, ease
+//!   unchanged.
+//! - Passing grades grow the interval: 1 day, then 6 days, then the previous
+//!   interval times the ease factor. The ease factor moves by the standard
+//!   SM-2 adjustment and never drops below `MIN_EASE`.
+//! - Dates are local calendar days. Every function takes `today` explicitly,
+//!   so scheduling is deterministic and the commands supply the clock.
+//!
+//! ENROLLMENT:
+//! - Notes join the schedule through `set_review_enrollment`, by being
+//!   reviewed, or through a `review: true` frontmatter flag picked up by
+//!   `scan_frontmatter`. `review: false` removes a note; notes without the
+//!   flag keep whatever was chosen manually.
+//! - Renames and deletes are followed by the reference reconciliation pass
+//!   (`review` store in `crate::references`).
+//!
+//! The Tauri commands live in `commands::review`.
+//! ============================================================================
+
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::HibiscusError;
+use crate::ids::canonicalize_id;
+use crate::limits::Limits;
+use crate::markdown;
+use crate::references::write_json_atomic;
+use crate::tree::read_dir_limited;
+
+/// Highest review grade.
+pub const MAX_QUALITY: u8 = 5;
+/// Lowest grade that counts as remembered.
+pub const PASSING_QUALITY: u8 = 3;
+/// Ease factor of a newly enrolled note.
+pub const DEFAULT_EASE: f64 = 2.5;
+/// Floor for the ease factor.
+pub const MIN_EASE: f64 = 1.3;
+
+/// Frontmatter field that enrolls (`true`) or removes (`false`) a note.
+pub const FRONTMATTER_FLAG: &str = "review";
+
+/// Scheduling state of one enrolled note.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReviewState {
+    pub ease: f64,
+    /// Days between the last review and `due`
+    pub interval_days: u32,
+    /// Passing reviews in a row
+    pub repetitions: u32,
+    /// Next review date
+    pub due: NaiveDate,
+    #[serde(default)]
+    pub last_reviewed: Option<NaiveDate>,
+}
+
+impl ReviewState {
+    /// State of a note enrolled on `today`: due immediately.
+    pub fn new(today: NaiveDate) -> Self {
+        Self {
+            ease: DEFAULT_EASE,
+            interval_days: 0,
+            repetitions: 0,
+            due: today,
+            last_reviewed: None,
+        }
+    }
+
+    /// Returns the state after a review graded `quality` (0-5) on `today`.
+    pub fn reviewed(&self, quality: u8, today: NaiveDate) -> Self {
+        let quality = quality.min(MAX_QUALITY);
+
+        let (ease, interval_days, repetitions) = if quality < PASSING_QUALITY {
+            (self.ease, 1, 0)
+        } else {
+            let miss = f64::from(MAX_QUALITY - quality);
+            let ease = (self.ease + 0.1 - miss * (0.08 + miss * 0.02)).max(MIN_EASE);
+            let interval_days = match self.repetitions {
+                0 => 1,
+                1 => 6,
+                _ => (f64::from(self.interval_days) * ease).round() as u32,
+            };
+            (ease, interval_days, self.repetitions + 1)
+        };
+
+        Self {
+            ease,
+            interval_days,
+            repetitions,
+            due: today + Days::new(u64::from(interval_days)),
+            last_reviewed: Some(today),
+        }
+    }
+}
+
+/// Contents of `.hibiscus/review.json`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReviewData {
+    /// Canonical note id -> scheduling state
+    #[serde(default)]
+    pub notes: BTreeMap<String, ReviewState>,
+}
+
+/// A note whose review is due.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DueNote {
+    pub path: String,
+    pub due: NaiveDate,
+    /// Days past the due date; 0 when due on the queried date
+    pub days_overdue: i64,
+    pub interval_days: u32,
+    pub ease: f64,
+}
+
+impl ReviewData {
+    /// Notes due on or before `date`, most overdue first.
+    pub fn due_on(&self, date: NaiveDate) -> Vec<DueNote> {
+        let mut due: Vec<DueNote> = self
+            .notes
+            .iter()
+            .filter(|(_, state)| state.due <= date)
+            .map(|(path, state)| DueNote {
+                path: path.clone(),
+                due: state.due,
+                days_overdue: (date - state.due).num_days(),
+                interval_days: state.interval_days,
+                ease: state.ease,
+            })
+            .collect();
+        due.sort_by(|a, b| b.days_overdue.cmp(&a.days_overdue).then_with(|| a.path.cmp(&b.path)));
+        due
+    }
+
+    /// Records a review, enrolling the note if it was not scheduled yet.
+    pub fn mark_reviewed(&mut self, path: &str, quality: u8, today: NaiveDate) -> &ReviewState {
+        let state = self
+            .notes
+            .entry(canonicalize_id(path))
+            .or_insert_with(|| ReviewState::new(today));
+        *state = state.reviewed(quality, today);
+        state
+    }
+
+    /// Adds or removes a note. Enrolling a scheduled note keeps its state.
+    /// Returns whether anything changed.
+    pub fn set_enrolled(&mut self, path: &str, enrolled: bool, today: NaiveDate) -> bool {
+        let id = canonicalize_id(path);
+        if !enrolled {
+            return self.notes.remove(&id).is_some();
+        }
+        if self.notes.contains_key(&id) {
+            return false;
+        }
+        self.notes.insert(id, ReviewState::new(today));
+        true
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Frontmatter enrollment
+// ---------------------------------------------------------------------------
+
+/// Notes whose enrollment a frontmatter scan changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct EnrollmentScan {
+    pub enrolled: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// Reads the review flag of a note's frontmatter, if it has one.
+pub fn frontmatter_flag(content: &str) -> Option<bool> {
+    let (frontmatter, _) = markdown::split_frontmatter(content);
+    match frontmatter?.fields.get(FRONTMATTER_FLAG)?.to_ascii_lowercase().as_str() {
+        "true" | "yes" => Some(true),
+        "false" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+/// Applies the review flags of every note under `root` to `data`.
+pub fn scan_frontmatter(root: &Path, data: &mut ReviewData, today: NaiveDate) -> EnrollmentScan {
+    let (tree, _) = read_dir_limited(root, root, Limits::load(root).tree_depth, false);
+    let mut notes = Vec::new();
+    crate::graph::collect_notes(&tree, &mut notes);
+
+    let mut scan = EnrollmentScan::default();
+    for note in notes {
+        let Some(flag) = fs::read_to_string(root.join(&note))
+            .ok()
+            .and_then(|content| frontmatter_flag(&content))
+        else {
+            continue;
+        };
+
+        let id = canonicalize_id(&note);
+        if data.set_enrolled(&id, flag, today) {
+            if flag {
+                scan.enrolled.push(id);
+            } else {
+                scan.removed.push(id);
+            }
+        }
+    }
+
+    scan
+}
+
+// ---------------------------------------------------------------------------
+// Storage
+// ---------------------------------------------------------------------------
+
+/// Location of the review schedule for a workspace.
+pub fn review_path(root: &Path) -> PathBuf {
+    root.join(".hibiscus").join("review.json")
+}
+
+/// Reads the schedule; a missing file is an empty schedule.
+pub fn load(root: &Path) -> Result<ReviewData, HibiscusError> {
+    let path = review_path(root);
+    match fs::read_to_string(&path) {
+        Ok(content) => Ok(serde_json::from_str(&content)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(ReviewData::default()),
+        Err(e) => Err(HibiscusError::Io(format!(
+            "Failed to read '{}': {}",
+            path.display(),
+            e
+        ))),
+    }
+}
+
+/// Writes the schedule via a temp file + rename.
+pub fn save(root: &Path, data: &ReviewData) -> Result<(), HibiscusError> {
+    let path = review_path(root);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    write_json_atomic(&path, &serde_json::to_value(data)?)
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, d).unwrap()
+    }
+
+    #[test]
+    fn test_sm2_intervals_and_ease() {
+        let mut data = ReviewData::default();
+
+        let state = data.mark_reviewed("Notes/A.md", 5, day(1)).clone();
+        assert_eq!((state.interval_days, state.repetitions, state.due), (1, 1, day(2)));
+        assert!((state.ease - 2.6).abs() < 1e-9);
+
+        let state = data.mark_reviewed("Notes/A.md", 4, day(2)).clone();
+        assert_eq!((state.interval_days, state.due), (6, day(8)));
+        assert!((state.ease - 2.6).abs() < 1e-9);
+
+        // Third pass: round(6 * ease), with ease lowered by a hard recall
+        let state = data.mark_reviewed("Notes/A.md", 3, day(8)).clone();
+        assert!((state.ease - 2.46).abs() < 1e-9);
+        assert_eq!((state.interval_days, state.repetitions, state.due), (15, 3, day(23)));
+        assert_eq!(state.last_reviewed, Some(day(8)));
+    }
+
+    #[test]
+    fn test_failed_review_resets_but_keeps_ease() {
+        let mut state = ReviewState::new(day(1));
+        for today in [day(1), day(2), day(8)] {
+            state = state.reviewed(5, today);
+        }
+        assert_eq!(state.repetitions, 3);
+        let ease = state.ease;
+
+        let failed = state.reviewed(2, day(20));
+        assert_eq!((failed.interval_days, failed.repetitions, failed.due), (1, 0, day(21)));
+        assert_eq!(failed.ease, ease);
+
+        // Ease never sinks below the floor
+        let mut state = ReviewState::new(day(1));
+        for _ in 0..20 {
+            state = state.reviewed(3, day(1));
+        }
+        assert_eq!(state.ease, MIN_EASE);
+    }
+
+    #[test]
+    fn test_due_notes_sorted_by_overdue() {
+        let mut data = ReviewData::default();
+        data.set_enrolled("a.md", true, day(10));
+        data.set_enrolled("b.md", true, day(3));
+        data.set_enrolled("c.md", true, day(12));
+        data.set_enrolled("d.md", true, day(3));
+
+        let due = data.due_on(day(10));
+        let shape: Vec<_> = due.iter().map(|d| (d.path.as_str(), d.days_overdue)).collect();
+        assert_eq!(shape, [("b.md", 7), ("d.md", 7), ("a.md", 0)]);
+
+        // Reviewing moves a note out of the due list
+        data.mark_reviewed("b.md", 4, day(10));
+        assert_eq!(data.due_on(day(10)).len(), 2);
+
+        assert!(data.set_enrolled("a.md", false, day(10)));
+        assert!(!data.set_enrolled("a.md", false, day(10)));
+    }
+
+    #[test]
+    fn test_frontmatter_scan_and_storage() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("Notes")).unwrap();
+        fs::write(root.join("Notes/In.md"), "---\nreview: true\n---\nBody\n").unwrap();
+        fs::write(root.join("Out.md"), "---\nreview: false\n---\n").unwrap();
+        fs::write(root.join("Plain.md"), "No frontmatter\n").unwrap();
+
+        let mut data = ReviewData::default();
+        data.set_enrolled("Out.md", true, day(1));
+        data.set_enrolled("Plain.md", true, day(1));
+
+        let scan = scan_frontmatter(root, &mut data, day(5));
+        assert_eq!(scan.enrolled, ["Notes/In.md"]);
+        assert_eq!(scan.removed, ["Out.md"]);
+        assert_eq!(data.notes.keys().collect::<Vec<_>>(), ["Notes/In.md", "Plain.md"]);
+
+        save(root, &data).unwrap();
+        assert_eq!(load(root).unwrap(), data);
+    }
+}
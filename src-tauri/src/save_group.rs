@@ -0,0 +1,378 @@
+//! ============================================================================
+//! Hibiscus Save Groups
+//! ============================================================================
+//!
+//! All-or-nothing saves of several files, for "save all" across split panes
+//! where one note refers to another.
+//!
+//! SEQUENCE:
+//! 1. Write each file's new contents to `<name>.hibiscus-save~` and fsync.
+//!    A failure here removes the temp files; no target has changed.
+//! 2. Record the group in `.hibiscus/save-groups/<id>.json` (the journal).
+//! 3. For each file in order, move the current target aside to
+//!    `<name>.hibiscus-backup~`, then move the temp file onto the target.
+//! 4. Fsync the folders, remove the backups, then the journal.
+//!
+//! If a file in step 3 fails, every file of the group is reverted, newest
+//! first: renamed targets are put back from their backups, leftover temp
+//! files are removed. The group fails as a whole.
+//!
+//! DESIGN DECISIONS:
+//! - Targets are moved aside rather than replaced in place. Windows can't
+//!   rename over an existing file, and the moved-aside file doubles as the
+//!   rollback copy, so every platform takes the same path.
+//! - After a crash, `recover` replays leftover journals: a group whose temp
+//!   files were all renamed is finished (backups removed), any other is
+//!   reverted. `bootstrap_workspace` runs it when a workspace opens.
+//! - All files of a group belong to one workspace, which holds the journal.
+//! - Groups write with `std::fs`, not through `crate::storage`: the journal
+//!   and recovery need several renames on the same disk, which the storage
+//!   seam does not offer. Every path a group touches is added to
+//!   `SELF_WRITES`, so the watcher does not report the moves aside as
+//!   deletes.
+//!
+//! Recovery is best effort: problems are reported, not raised.
+//! ============================================================================
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::capabilities::random_token;
+use crate::error::HibiscusError;
+use crate::watcher::SELF_WRITES;
+
+/// Folder under `.hibiscus` holding the journals of unfinished groups.
+pub const JOURNAL_DIR: &str = "save-groups";
+
+/// Suffix of the file holding new contents until it is renamed into place.
+const TEMP_SUFFIX: &str = ".hibiscus-save~";
+
+/// Suffix of the previous version of a target while its group commits.
+const BACKUP_SUFFIX: &str = ".hibiscus-backup~";
+
+/// One file of a group, as recorded in the journal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GroupFile {
+    target: PathBuf,
+    temp: PathBuf,
+    /// Where the previous version goes; `None` if the target is new
+    backup: Option<PathBuf>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Journal {
+    id: String,
+    files: Vec<GroupFile>,
+}
+
+/// Why a group was not saved.
+#[derive(Debug)]
+pub struct GroupFailure {
+    /// The file that failed; `None` if the group itself failed (journal)
+    pub index: Option<usize>,
+    pub error: HibiscusError,
+}
+
+/// Saves `files` (target, contents) as one group, journaled in `root`.
+///
+/// # Returns
+/// * `Ok(())` - Every target has its new contents
+/// * `Err(GroupFailure)` - No target changed (after rollback)
+pub fn save_group(root: &Path, files: &[(PathBuf, Vec<u8>)]) -> Result<(), GroupFailure> {
+    save_group_with(root, files, &mut |_| Ok(()))
+}
+
+/// `save_group` with a hook called before each file is moved into place,
+/// whose error fails that file. Tests inject failures through it.
+fn save_group_with(
+    root: &Path,
+    files: &[(PathBuf, Vec<u8>)],
+    before_commit: &mut dyn FnMut(usize) -> std::io::Result<()>,
+) -> Result<(), GroupFailure> {
+    // 1. New contents to temp files
+    let mut group = Vec::new();
+    for (index, (target, contents)) in files.iter().enumerate() {
+        group.push(GroupFile {
+            target: target.clone(),
+            temp: sibling(target, TEMP_SUFFIX),
+            backup: target.exists().then(|| sibling(target, BACKUP_SUFFIX)),
+        });
+        SELF_WRITES.suppress(target);
+        SELF_WRITES.suppress(&group[index].temp);
+        SELF_WRITES.suppress(&sibling(target, BACKUP_SUFFIX));
+        if let Err(error) = write_synced(&group[index].temp, contents) {
+            remove_temps(&group);
+            return Err(GroupFailure { index: Some(index), error });
+        }
+        crate::commands::copy_file_attributes(target, &group[index].temp);
+    }
+
+    // 2. Journal
+    let journal = random_token().map(|id| Journal { id, files: group });
+    let journal = match journal {
+        Ok(journal) => journal,
+        Err(error) => return Err(GroupFailure { index: None, error }),
+    };
+    let journal_path = root.join(".hibiscus").join(JOURNAL_DIR).join(format!("{}.json", journal.id));
+    let written = serde_json::to_vec_pretty(&journal)
+        .map_err(HibiscusError::from)
+        .and_then(|bytes| write_synced(&journal_path, &bytes));
+    if let Err(error) = written {
+        remove_temps(&journal.files);
+        return Err(GroupFailure { index: None, error });
+    }
+
+    // 3. Renames, in order
+    for (index, file) in journal.files.iter().enumerate() {
+        if let Err(e) = before_commit(index).and_then(|()| commit_file(file)) {
+            let error = HibiscusError::Io(format!("Failed to save '{}': {}", file.target.display(), e));
+            let problems = revert(&journal.files);
+            if problems.is_empty() {
+                let _ = fs::remove_file(&journal_path);
+            } else {
+                // Keep the journal so `recover` can try again
+                eprintln!("[Hibiscus] Warning: Incomplete save group rollback: {}", problems.join("; "));
+            }
+            return Err(GroupFailure { index: Some(index), error });
+        }
+    }
+
+    // 4. Cleanup
+    finish(&journal.files);
+    let _ = fs::remove_file(&journal_path);
+    Ok(())
+}
+
+/// Finishes or reverts the groups a crash interrupted.
+///
+/// # Returns
+/// What could not be cleaned up; empty if all went well.
+pub fn recover(root: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(root.join(".hibiscus").join(JOURNAL_DIR)) else {
+        return Vec::new();
+    };
+
+    let mut problems = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let journal = fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Journal>(&bytes).ok());
+        let Some(journal) = journal else {
+            problems.push(format!("Unreadable save group journal '{}'", path.display()));
+            continue;
+        };
+
+        // Every temp renamed: the new versions are all in place
+        let group_problems = if journal.files.iter().all(|file| !file.temp.exists()) {
+            finish(&journal.files);
+            Vec::new()
+        } else {
+            revert(&journal.files)
+        };
+
+        if group_problems.is_empty() {
+            let _ = fs::remove_file(&path);
+        }
+        problems.extend(group_problems);
+    }
+    problems
+}
+
+/// Moves the target aside (if any) and the temp file onto it. Puts the
+/// target back if the second move fails.
+fn commit_file(file: &GroupFile) -> std::io::Result<()> {
+    if let Some(backup) = &file.backup {
+        fs::rename(&file.target, backup)?;
+    }
+    if let Err(e) = fs::rename(&file.temp, &file.target) {
+        if let Some(backup) = &file.backup {
+            let _ = fs::rename(backup, &file.target);
+        }
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Puts every file of a group back to its previous state, newest first.
+/// A file whose temp file still exists was not renamed into place.
+fn revert(files: &[GroupFile]) -> Vec<String> {
+    let mut problems = Vec::new();
+    for file in files.iter().rev() {
+        let result = if file.temp.exists() {
+            fs::remove_file(&file.temp).and_then(|()| match &file.backup {
+                // Moved aside but not replaced
+                Some(backup) if backup.exists() && !file.target.exists() => fs::rename(backup, &file.target),
+                _ => Ok(()),
+            })
+        } else {
+            match &file.backup {
+                Some(backup) => replace_file(backup, &file.target),
+                None if file.target.exists() => fs::remove_file(&file.target),
+                None => Ok(()),
+            }
+        };
+        if let Err(e) = result {
+            problems.push(format!("Failed to restore '{}': {}", file.target.display(), e));
+        }
+    }
+    problems
+}
+
+/// Makes the renames durable and drops the backups.
+fn finish(files: &[GroupFile]) {
+    for file in files {
+        if let Some(parent) = file.target.parent() {
+            sync_dir(parent);
+        }
+        if let Some(backup) = &file.backup {
+            let _ = fs::remove_file(backup);
+        }
+    }
+}
+
+fn remove_temps(files: &[GroupFile]) {
+    for file in files {
+        let _ = fs::remove_file(&file.temp);
+    }
+}
+
+/// `path` with `suffix` appended to its file name.
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    path.with_file_name(format!("{}{}", name, suffix))
+}
+
+/// Writes `contents` to `path` (creating parent folders) and fsyncs it.
+fn write_synced(path: &Path, contents: &[u8]) -> Result<(), HibiscusError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| {
+            HibiscusError::Io(format!("Failed to create parent directories for '{}': {}", path.display(), e))
+        })?;
+    }
+    let mut file = fs::File::create(path)
+        .map_err(|e| HibiscusError::Io(format!("Failed to create temp file '{}': {}", path.display(), e)))?;
+    file.write_all(contents)
+        .and_then(|()| file.sync_all())
+        .map_err(|e| HibiscusError::Io(format!("Failed to write temp file '{}': {}", path.display(), e)))
+}
+
+/// Renames `from` onto `to`, removing `to` first on Windows.
+fn replace_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    #[cfg(target_os = "windows")]
+    if to.exists() {
+        fs::remove_file(to)?;
+    }
+    fs::rename(from, to)
+}
+
+/// Flushes a directory entry to disk (Unix only; best effort).
+fn sync_dir(dir: &Path) {
+    #[cfg(unix)]
+    if let Ok(dir) = fs::File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    #[cfg(not(unix))]
+    let _ = dir;
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn journals(root: &Path) -> usize {
+        fs::read_dir(root.join(".hibiscus").join(JOURNAL_DIR)).map_or(0, |dir| dir.count())
+    }
+
+    #[test]
+    fn test_failure_between_renames_restores_every_file() {
+        let dir = tempdir().unwrap();
+        let lecture = dir.path().join("lecture.md");
+        let caption = dir.path().join("captions").join("figure.md");
+        fs::create_dir_all(caption.parent().unwrap()).unwrap();
+        fs::write(&lecture, "old lecture").unwrap();
+        fs::write(&caption, "old caption").unwrap();
+        let fresh = dir.path().join("new.md");
+
+        let files = vec![
+            (lecture.clone(), b"new lecture".to_vec()),
+            (fresh.clone(), b"new file".to_vec()),
+            (caption.clone(), b"new caption".to_vec()),
+        ];
+        let failure = save_group_with(dir.path(), &files, &mut |index| {
+            if index == 2 {
+                Err(std::io::Error::other("injected"))
+            } else {
+                Ok(())
+            }
+        })
+        .unwrap_err();
+
+        assert_eq!(failure.index, Some(2));
+        assert_eq!(fs::read_to_string(&lecture).unwrap(), "old lecture");
+        assert_eq!(fs::read_to_string(&caption).unwrap(), "old caption");
+        assert!(!fresh.exists());
+        assert!(!sibling(&lecture, BACKUP_SUFFIX).exists());
+        assert!(!sibling(&caption, TEMP_SUFFIX).exists());
+        assert_eq!(journals(dir.path()), 0);
+
+        save_group(dir.path(), &files).unwrap();
+        assert_eq!(fs::read_to_string(&lecture).unwrap(), "new lecture");
+        assert_eq!(fs::read_to_string(&caption).unwrap(), "new caption");
+        assert_eq!(journals(dir.path()), 0);
+    }
+
+    #[test]
+    fn test_recover_reverts_or_finishes_interrupted_groups() {
+        let dir = tempdir().unwrap();
+        let a = dir.path().join("a.md");
+        let b = dir.path().join("b.md");
+        let write_journal = |id: &str| {
+            let journal = Journal {
+                id: id.into(),
+                files: [&a, &b]
+                    .iter()
+                    .map(|target| GroupFile {
+                        target: target.to_path_buf(),
+                        temp: sibling(target, TEMP_SUFFIX),
+                        backup: Some(sibling(target, BACKUP_SUFFIX)),
+                    })
+                    .collect(),
+            };
+            let path = dir.path().join(".hibiscus").join(JOURNAL_DIR).join(format!("{}.json", id));
+            write_synced(&path, &serde_json::to_vec(&journal).unwrap()).unwrap();
+        };
+
+        // Crash after `a` was renamed and `b` was moved aside
+        fs::write(&a, "new a").unwrap();
+        fs::write(sibling(&a, BACKUP_SUFFIX), "old a").unwrap();
+        fs::write(sibling(&b, BACKUP_SUFFIX), "old b").unwrap();
+        fs::write(sibling(&b, TEMP_SUFFIX), "new b").unwrap();
+        write_journal("interrupted");
+
+        assert!(recover(dir.path()).is_empty());
+        assert_eq!(fs::read_to_string(&a).unwrap(), "old a");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "old b");
+        assert!(!sibling(&b, TEMP_SUFFIX).exists());
+        assert_eq!(journals(dir.path()), 0);
+
+        // Crash during cleanup: everything renamed, one backup left
+        fs::write(&a, "new a").unwrap();
+        fs::write(&b, "new b").unwrap();
+        fs::write(sibling(&b, BACKUP_SUFFIX), "old b").unwrap();
+        write_journal("cleanup");
+
+        assert!(recover(dir.path()).is_empty());
+        assert_eq!(fs::read_to_string(&a).unwrap(), "new a");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "new b");
+        assert!(!sibling(&b, BACKUP_SUFFIX).exists());
+        assert_eq!(journals(dir.path()), 0);
+    }
+}
@@ -0,0 +1,556 @@
+//! ============================================================================
+//! Hibiscus Vault Storage
+//! ============================================================================
+//!
+//! The seam between commands and the place a vault's files live. Commands
+//! that read, write, list, rename or delete vault files go through the
+//! `VaultStorage` holding the path instead of calling the filesystem
+//! directly, so a remote backend (SFTP, WebDAV, ...) can be mounted later
+//! without rewriting them.
+//!
+//! RESOLUTION:
+//! - `mount` registers a storage for a workspace root; `storage_for` picks
+//!   the mount with the longest root containing a path.
+//! - Paths under no mount use `LocalStorage`, the local filesystem.
+//! - Paths stay absolute everywhere, so commands keep their signatures.
+//!
+//! CONTRACT:
+//! - Methods block; async commands call them through the wrappers at the
+//!   bottom of this file (`read`, `write_atomic`, ...), which run them on a
+//!   blocking thread like `tokio::fs` does.
+//! - Errors are `std::io::Error`s with the usual kinds (`NotFound`, ...),
+//!   so callers can tell a missing file from a failed one.
+//! - `write_atomic` leaves either the old or the new contents, never a mix.
+//! - A storage that cannot report changes says so with `can_watch`; the
+//!   watcher then refuses to start instead of silently showing nothing.
+//!
+//! - `create_new` and `create_dir` fail with `AlreadyExists` instead of
+//!   replacing what is there, so new files and folders never clobber one.
+//!
+//! Not everything goes through the seam yet: trash deletes, copies and
+//! cross-device moves, atomic save groups (`crate::save_group`), workspace
+//! settings lookups and the watcher still use the local filesystem directly.
+//! ============================================================================
+
+use std::fs;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, RwLock};
+
+use crate::workspace::FileMetadata;
+
+/// An entry of a folder listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageEntry {
+    /// Absolute path of the entry
+    pub path: PathBuf,
+    /// Whether the entry (or a symlink's target) is a folder
+    pub is_dir: bool,
+}
+
+/// Where a vault's files live.
+pub trait VaultStorage: Send + Sync {
+    /// Opens a file for reading.
+    fn read(&self, path: &Path) -> io::Result<Box<dyn Read + Send>>;
+
+    /// Replaces (or creates) a file with what `write` produces, creating
+    /// missing parent folders. If `write` fails, the file is left as it was.
+    fn write_atomic(&self, path: &Path, write: &mut dyn FnMut(&mut dyn Write) -> io::Result<()>) -> io::Result<()>;
+
+    /// Creates a file holding `contents`, creating missing parent folders.
+    /// Fails with `AlreadyExists`, never overwriting, if `path` is taken.
+    fn create_new(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+
+    /// Creates an empty folder, creating missing parent folders. Fails with
+    /// `AlreadyExists` if `path` is taken.
+    fn create_dir(&self, path: &Path) -> io::Result<()>;
+
+    /// Lists the entries of a folder, in no particular order.
+    fn list_dir(&self, path: &Path) -> io::Result<Vec<StorageEntry>>;
+
+    /// Reads the metadata of a file or folder.
+    fn metadata(&self, path: &Path) -> io::Result<FileMetadata>;
+
+    /// Moves a file or folder. `from` and `to` must be in this storage.
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+
+    /// Deletes a file, or a folder with everything in it.
+    fn remove(&self, path: &Path) -> io::Result<()>;
+
+    /// Whether the file watcher can follow changes in this storage.
+    fn can_watch(&self) -> bool;
+
+    /// Reads a whole file into memory.
+    fn read_to_end(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.read(path)?.read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Whether a file or folder exists at `path`.
+    fn exists(&self, path: &Path) -> bool {
+        self.metadata(path).is_ok()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Local filesystem
+// ---------------------------------------------------------------------------
+
+/// The local filesystem.
+#[derive(Debug, Default)]
+pub struct LocalStorage;
+
+impl VaultStorage for LocalStorage {
+    fn read(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+        Ok(Box::new(fs::File::open(path)?))
+    }
+
+    /// Writes with the temp-file + sync + rename strategy described on
+    /// `write_text_file`.
+    fn write_atomic(&self, path: &Path, write: &mut dyn FnMut(&mut dyn Write) -> io::Result<()>) -> io::Result<()> {
+        create_parent_dirs(path)?;
+
+        // ===========================================================================
+        // MODERN EDITOR SAVE STRATEGY
+        // ===========================================================================
+        // Create temp file with .hibiscus-save~ suffix APPENDED to full filename.
+        // Using a unique suffix prevents conflicts with user files.
+        // Example: "notes.txt" -> "notes.txt.hibiscus-save~"
+        // ===========================================================================
+        let temp_filename = format!(
+            "{}.hibiscus-save~",
+            path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default()
+        );
+        let temp_path = path.with_file_name(&temp_filename);
+
+        // Write to temp file
+        let write_result = (|| {
+            let file = fs::File::create(&temp_path)
+                .map_err(|e| with_context(e, format!("Failed to create temp file '{}'", temp_path.display())))?;
+            let mut writer = BufWriter::new(file);
+            write(&mut writer)
+                .and_then(|_| writer.flush())
+                .map_err(|e| with_context(e, format!("Failed to write to temp file '{}'", temp_path.display())))?;
+
+            // Sync to ensure data is on disk before rename
+            let file = writer.into_inner().map_err(io::IntoInnerError::into_error)?;
+            file.sync_all()
+                .map_err(|e| with_context(e, format!("Failed to sync file '{}'", temp_path.display())))
+        })();
+
+        // If write failed, cleanup temp file and return error
+        if let Err(e) = write_result {
+            let _ = fs::remove_file(&temp_path); // Ignore cleanup errors
+            return Err(e);
+        }
+
+        // Keep the permissions (and owner, hidden flag, ...) of the file being
+        // replaced; new files get the defaults
+        crate::commands::copy_file_attributes(path, &temp_path);
+
+        // ===========================================================================
+        // WINDOWS COMPATIBILITY: Windows doesn't support atomic rename over existing
+        // files. We must delete the target first. This creates a brief window where
+        // the file doesn't exist, but it's the standard approach for Windows.
+        // ===========================================================================
+        #[cfg(target_os = "windows")]
+        if path.exists() {
+            if let Err(e) = fs::remove_file(path) {
+                // Cleanup temp and return error
+                let _ = fs::remove_file(&temp_path);
+                return Err(with_context(
+                    e,
+                    format!("Failed to remove existing file '{}' before save", path.display()),
+                ));
+            }
+        }
+
+        // Rename temp file to target
+        if let Err(e) = fs::rename(&temp_path, path) {
+            // Cleanup temp file on rename failure
+            let _ = fs::remove_file(&temp_path);
+            return Err(with_context(
+                e,
+                format!("Failed to rename '{}' to '{}'", temp_path.display(), path.display()),
+            ));
+        }
+
+        // Persist the rename itself: without syncing the directory, the new
+        // directory entry can be lost on power failure even though the file
+        // data was synced above.
+        sync_parent_dir(path)
+    }
+
+    fn create_new(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        create_parent_dirs(path)?;
+        // create_new fails on an existing file, so a name taken between a
+        // check and the create is never overwritten
+        let mut file = fs::OpenOptions::new().write(true).create_new(true).open(path)?;
+        file.write_all(contents).map_err(|e| {
+            // Don't leave a half-written file holding the name
+            let _ = fs::remove_file(path);
+            with_context(e, format!("Failed to write '{}'", path.display()))
+        })
+    }
+
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        create_parent_dirs(path)?;
+        fs::create_dir(path)
+    }
+
+    fn list_dir(&self, path: &Path) -> io::Result<Vec<StorageEntry>> {
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(path)? {
+            // Skip entries that can't be read
+            match entry {
+                Ok(entry) => {
+                    let path = entry.path();
+                    entries.push(StorageEntry { is_dir: path.is_dir(), path });
+                }
+                Err(e) => eprintln!("[Hibiscus] Warning: Failed to read entry: {}", e),
+            }
+        }
+        Ok(entries)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        FileMetadata::read(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        fs::rename(from, to)
+    }
+
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        if path.is_dir() {
+            fs::remove_dir_all(path)
+        } else {
+            fs::remove_file(path)
+        }
+    }
+
+    fn can_watch(&self) -> bool {
+        true
+    }
+}
+
+/// Flushes the directory containing `path` to disk (Unix only).
+///
+/// Opening a directory read-only and calling `fsync` on it is the standard
+/// way to make a preceding rename durable on POSIX systems.
+#[cfg(unix)]
+pub(crate) fn sync_parent_dir(path: &Path) -> io::Result<()> {
+    let Some(parent) = path.parent() else {
+        return Ok(());
+    };
+    // An empty parent means "current directory" for relative paths.
+    let parent = if parent.as_os_str().is_empty() { Path::new(".") } else { parent };
+
+    let dir = fs::File::open(parent)
+        .map_err(|e| with_context(e, format!("Failed to open directory '{}'", parent.display())))?;
+    dir.sync_all()
+        .map_err(|e| with_context(e, format!("Failed to sync directory '{}'", parent.display())))
+}
+
+/// Directory fsync is not supported on Windows; the rename is already
+/// journaled by NTFS.
+#[cfg(not(unix))]
+pub(crate) fn sync_parent_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Creates the missing parent folders of `path`.
+fn create_parent_dirs(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(parent) => fs::create_dir_all(parent).map_err(|e| {
+            with_context(e, format!("Failed to create parent directories for '{}'", path.display()))
+        }),
+        None => Ok(()),
+    }
+}
+
+/// `error` with `context` in front of its message, keeping its kind.
+fn with_context(error: io::Error, context: String) -> io::Error {
+    io::Error::new(error.kind(), format!("{}: {}", context, error))
+}
+
+// ---------------------------------------------------------------------------
+// Mounts
+// ---------------------------------------------------------------------------
+
+static LOCAL: LazyLock<Arc<dyn VaultStorage>> = LazyLock::new(|| Arc::new(LocalStorage));
+
+/// A workspace root and the storage serving it.
+type Mount = (PathBuf, Arc<dyn VaultStorage>);
+
+/// Workspace roots with a storage other than the local filesystem.
+static MOUNTS: LazyLock<RwLock<Vec<Mount>>> = LazyLock::new(Default::default);
+
+/// Serves every path under `root` from `storage`, replacing any earlier
+/// mount of the same root.
+pub fn mount(root: &Path, storage: Arc<dyn VaultStorage>) {
+    if let Ok(mut mounts) = MOUNTS.write() {
+        mounts.retain(|(mounted, _)| mounted != root);
+        mounts.push((root.to_path_buf(), storage));
+    }
+}
+
+/// Returns the paths under `root` to the local filesystem.
+pub fn unmount(root: &Path) {
+    if let Ok(mut mounts) = MOUNTS.write() {
+        mounts.retain(|(mounted, _)| mounted != root);
+    }
+}
+
+/// The storage holding `path`: the mount with the longest root containing
+/// it, else the local filesystem.
+pub fn storage_for(path: &Path) -> Arc<dyn VaultStorage> {
+    MOUNTS
+        .read()
+        .ok()
+        .and_then(|mounts| {
+            mounts
+                .iter()
+                .filter(|(root, _)| path.starts_with(root))
+                .max_by_key(|(root, _)| root.components().count())
+                .map(|(_, storage)| storage.clone())
+        })
+        .unwrap_or_else(|| LOCAL.clone())
+}
+
+// ---------------------------------------------------------------------------
+// Async wrappers
+// ---------------------------------------------------------------------------
+
+/// Runs `op` on the storage holding `path`, on a blocking thread.
+async fn run<T, F>(path: &Path, op: F) -> io::Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&dyn VaultStorage, &Path) -> io::Result<T> + Send + 'static,
+{
+    let storage = storage_for(path);
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || op(storage.as_ref(), &path))
+        .await
+        .map_err(|e| io::Error::other(format!("Storage task failed: {}", e)))?
+}
+
+/// Reads a whole file.
+pub async fn read(path: &Path) -> io::Result<Vec<u8>> {
+    run(path, |storage, path| storage.read_to_end(path)).await
+}
+
+/// Replaces (or creates) a file with `contents`; see
+/// `VaultStorage::write_atomic`.
+pub async fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let contents = contents.to_vec();
+    run(path, move |storage, path| storage.write_atomic(path, &mut |writer| writer.write_all(&contents))).await
+}
+
+/// Creates a file holding `contents`; fails if `path` is taken.
+pub async fn create_new(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let contents = contents.to_vec();
+    run(path, move |storage, path| storage.create_new(path, &contents)).await
+}
+
+/// Creates an empty folder; fails if `path` is taken.
+pub async fn create_dir(path: &Path) -> io::Result<()> {
+    run(path, |storage, path| storage.create_dir(path)).await
+}
+
+/// Lists the entries of a folder.
+pub async fn list_dir(path: &Path) -> io::Result<Vec<StorageEntry>> {
+    run(path, |storage, path| storage.list_dir(path)).await
+}
+
+/// Reads the metadata of a file or folder.
+pub async fn metadata(path: &Path) -> io::Result<FileMetadata> {
+    run(path, |storage, path| storage.metadata(path)).await
+}
+
+/// Moves a file or folder within the storage holding `from`.
+pub async fn rename(from: &Path, to: &Path) -> io::Result<()> {
+    let to = to.to_path_buf();
+    run(from, move |storage, from| storage.rename(from, &to)).await
+}
+
+/// Deletes a file, or a folder with everything in it.
+pub async fn remove(path: &Path) -> io::Result<()> {
+    run(path, |storage, path| storage.remove(path)).await
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::time::Timestamp;
+    use crate::workspace::FileKind;
+    use std::collections::BTreeMap;
+    use std::sync::Mutex;
+
+    /// Contents and modification time of a file; `None` for a folder.
+    type MemoryEntry = Option<(Vec<u8>, Timestamp)>;
+
+    /// A vault kept in memory, for checking that commands only touch files
+    /// through the storage seam.
+    #[derive(Default)]
+    pub(crate) struct MemoryStorage {
+        entries: Mutex<BTreeMap<PathBuf, MemoryEntry>>,
+    }
+
+    impl MemoryStorage {
+        /// Mounts an empty vault at `root` (unmounted again on drop).
+        pub(crate) fn mount(root: &Path) -> MountedMemory {
+            let storage = Arc::new(MemoryStorage::default());
+            storage.add_folders(root);
+            mount(root, storage.clone());
+            MountedMemory { root: root.to_path_buf(), storage }
+        }
+
+        pub(crate) fn add_file(&self, path: &Path, contents: &[u8]) {
+            if let Some(parent) = path.parent() {
+                self.add_folders(parent);
+            }
+            let mut entries = self.entries.lock().unwrap();
+            entries.insert(path.to_path_buf(), Some((contents.to_vec(), Timestamp::now())));
+        }
+
+        pub(crate) fn contents(&self, path: &Path) -> Option<Vec<u8>> {
+            self.entries.lock().unwrap().get(path).cloned().flatten().map(|(bytes, _)| bytes)
+        }
+
+        fn add_folders(&self, path: &Path) {
+            let mut entries = self.entries.lock().unwrap();
+            for folder in path.ancestors() {
+                entries.entry(folder.to_path_buf()).or_insert(None);
+            }
+        }
+    }
+
+    /// A mounted `MemoryStorage`.
+    pub(crate) struct MountedMemory {
+        root: PathBuf,
+        pub(crate) storage: Arc<MemoryStorage>,
+    }
+
+    impl Drop for MountedMemory {
+        fn drop(&mut self) {
+            unmount(&self.root);
+        }
+    }
+
+    fn already_exists(path: &Path) -> io::Error {
+        io::Error::new(io::ErrorKind::AlreadyExists, format!("'{}' already in memory storage", path.display()))
+    }
+
+    fn not_found(path: &Path) -> io::Error {
+        io::Error::new(io::ErrorKind::NotFound, format!("'{}' not in memory storage", path.display()))
+    }
+
+    impl VaultStorage for MemoryStorage {
+        fn read(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+            let bytes = self.contents(path).ok_or_else(|| not_found(path))?;
+            Ok(Box::new(io::Cursor::new(bytes)))
+        }
+
+        fn write_atomic(&self, path: &Path, write: &mut dyn FnMut(&mut dyn Write) -> io::Result<()>) -> io::Result<()> {
+            let mut buffer = Vec::new();
+            write(&mut buffer)?;
+            self.add_file(path, &buffer);
+            Ok(())
+        }
+
+        fn create_new(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+            if self.exists(path) {
+                return Err(already_exists(path));
+            }
+            self.add_file(path, contents);
+            Ok(())
+        }
+
+        fn create_dir(&self, path: &Path) -> io::Result<()> {
+            if self.exists(path) {
+                return Err(already_exists(path));
+            }
+            self.add_folders(path);
+            Ok(())
+        }
+
+        fn list_dir(&self, path: &Path) -> io::Result<Vec<StorageEntry>> {
+            let entries = self.entries.lock().unwrap();
+            if !matches!(entries.get(path), Some(None)) {
+                return Err(not_found(path));
+            }
+            Ok(entries
+                .iter()
+                .filter(|(entry, _)| entry.parent() == Some(path))
+                .map(|(entry, contents)| StorageEntry { path: entry.clone(), is_dir: contents.is_none() })
+                .collect())
+        }
+
+        fn metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+            let entries = self.entries.lock().unwrap();
+            let entry = entries.get(path).ok_or_else(|| not_found(path))?;
+            Ok(FileMetadata {
+                size: entry.as_ref().map_or(0, |(bytes, _)| bytes.len() as u64),
+                modified: entry.as_ref().map(|(_, modified)| *modified),
+                created: None,
+                readonly: false,
+                kind: if entry.is_some() { FileKind::File } else { FileKind::Folder },
+                is_dir: entry.is_none(),
+            })
+        }
+
+        fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+            let mut entries = self.entries.lock().unwrap();
+            let moved: Vec<PathBuf> = entries.keys().filter(|path| path.starts_with(from)).cloned().collect();
+            if moved.is_empty() {
+                return Err(not_found(from));
+            }
+            for path in moved {
+                let entry = entries.remove(&path).flatten();
+                let suffix = path.strip_prefix(from).unwrap_or(Path::new(""));
+                entries.insert(to.join(suffix), entry);
+            }
+            Ok(())
+        }
+
+        fn remove(&self, path: &Path) -> io::Result<()> {
+            let mut entries = self.entries.lock().unwrap();
+            let before = entries.len();
+            entries.retain(|entry, _| !entry.starts_with(path));
+            if entries.len() == before {
+                return Err(not_found(path));
+            }
+            Ok(())
+        }
+
+        fn can_watch(&self) -> bool {
+            false
+        }
+    }
+
+    #[tokio::test]
+    async fn test_paths_resolve_to_the_innermost_mount() {
+        let outer = Path::new("/memory/storage-test");
+        let inner = outer.join("nested");
+        let outer_vault = MemoryStorage::mount(outer);
+        let inner_vault = MemoryStorage::mount(&inner);
+
+        write_atomic(&outer.join("a.md"), b"outer").await.unwrap();
+        write_atomic(&inner.join("b.md"), b"inner").await.unwrap();
+        assert_eq!(outer_vault.storage.contents(&outer.join("a.md")).unwrap(), b"outer");
+        assert_eq!(inner_vault.storage.contents(&inner.join("b.md")).unwrap(), b"inner");
+        assert!(outer_vault.storage.contents(&inner.join("b.md")).is_none());
+        assert!(!storage_for(outer).can_watch());
+
+        drop(inner_vault);
+        let err = read(&inner.join("b.md")).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(storage_for(Path::new("/elsewhere")).can_watch());
+    }
+}
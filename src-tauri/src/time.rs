@@ -0,0 +1,235 @@
+//! ============================================================================
+//! Hibiscus Timestamps
+//! ============================================================================
+//!
+//! One representation for points in time stored in `.hibiscus` files.
+//!
+//! DESIGN DECISIONS:
+//! - `Timestamp` wraps `chrono::DateTime<Utc>` and always serializes as
+//!   RFC3339 in UTC (`2024-01-05T09:30:00Z`), so new files sort and compare
+//!   the same way as text and as time.
+//! - Reading is lenient, because older files and the frontend produced
+//!   several formats:
+//!   - RFC3339 with any offset, with `T` or a space as separator
+//!   - date and time without an offset, interpreted as local time
+//!   - a bare date (`2024-01-05`, `2024-1-5`), interpreted as local midnight
+//!   - milliseconds since the Unix epoch (JavaScript `Date.now()`)
+//! - Comparisons go through `Ord` on the wrapped datetime, never through
+//!   string order.
+//!
+//! Calendar dates without a time (e.g. range bounds) use `parse_date`.
+//! ============================================================================
+
+use chrono::{
+    DateTime, Local, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat, TimeZone,
+    Utc,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// Offset-bearing formats accepted besides strict RFC3339.
+const OFFSET_FORMATS: &[&str] = &["%Y-%m-%d %H:%M:%S%.f%:z", "%Y-%m-%d %H:%M:%S%.f%z"];
+
+/// Formats without an offset; the value is taken as local time.
+const NAIVE_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+];
+
+/// A point in time, stored as RFC3339 UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp(pub DateTime<Utc>);
+
+impl Timestamp {
+    /// The current time.
+    pub fn now() -> Self {
+        Self(Utc::now())
+    }
+
+    /// Builds a timestamp from milliseconds since the Unix epoch.
+    pub fn from_millis(ms: i64) -> Option<Self> {
+        DateTime::from_timestamp_millis(ms).map(Self)
+    }
+
+    /// Milliseconds since the Unix epoch.
+    pub fn as_millis(&self) -> i64 {
+        self.0.timestamp_millis()
+    }
+
+    /// Parses any of the accepted formats (see the module docs).
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+
+        if let Ok(parsed) = DateTime::parse_from_rfc3339(value) {
+            return Some(Self(parsed.with_timezone(&Utc)));
+        }
+        for format in OFFSET_FORMATS {
+            if let Ok(parsed) = DateTime::parse_from_str(value, format) {
+                return Some(Self(parsed.with_timezone(&Utc)));
+            }
+        }
+        for format in NAIVE_FORMATS {
+            if let Ok(naive) = NaiveDateTime::parse_from_str(value, format) {
+                return from_local(naive);
+            }
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+            return from_local(date.and_time(NaiveTime::MIN));
+        }
+        if let Ok(ms) = value.parse::<i64>() {
+            return Self::from_millis(ms);
+        }
+        None
+    }
+
+    /// RFC3339 in UTC, with fractional seconds only when present.
+    pub fn to_rfc3339(&self) -> String {
+        self.0.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_rfc3339())
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_rfc3339())
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Text(String),
+            Millis(i64),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Text(text) => Self::parse(&text).ok_or_else(|| {
+                serde::de::Error::custom(format!("invalid timestamp '{}'", text))
+            }),
+            Raw::Millis(ms) => Self::from_millis(ms)
+                .ok_or_else(|| serde::de::Error::custom(format!("timestamp {} out of range", ms))),
+        }
+    }
+}
+
+/// Serde shim for optional timestamps in existing files: a value that cannot
+/// be parsed is logged and read as `None` instead of failing the whole file.
+///
+/// Use with `#[serde(default, deserialize_with = "crate::time::lenient_option")]`.
+pub fn lenient_option<'de, D>(deserializer: D) -> Result<Option<Timestamp>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(value.and_then(|value| match Timestamp::deserialize(&value) {
+        Ok(timestamp) => Some(timestamp),
+        Err(e) => {
+            eprintln!("[Hibiscus] Warning: Ignoring unreadable timestamp: {}", e);
+            None
+        }
+    }))
+}
+
+/// Parses a calendar date (`2024-01-05`, `2024-1-5`), or the local date of
+/// any timestamp `Timestamp::parse` accepts.
+pub fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .ok()
+        .or_else(|| Timestamp::parse(value).map(|ts| ts.0.with_timezone(&Local).date_naive()))
+}
+
+/// Resolves a local wall-clock time, taking the earlier instant when a DST
+/// change makes it ambiguous and skipping forward over a gap.
+fn from_local(naive: NaiveDateTime) -> Option<Timestamp> {
+    let local = match Local.from_local_datetime(&naive) {
+        LocalResult::Single(time) | LocalResult::Ambiguous(time, _) => time,
+        LocalResult::None => Local
+            .from_local_datetime(&(naive + chrono::Duration::hours(1)))
+            .earliest()?,
+    };
+    Some(Timestamp(local.with_timezone(&Utc)))
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_formats_round_trip() {
+        let local_noon = NaiveDate::from_ymd_opt(2024, 1, 5)
+            .unwrap()
+            .and_hms_opt(12, 30, 0)
+            .unwrap();
+        let local_midnight = NaiveDate::from_ymd_opt(2024, 1, 5).unwrap().and_time(NaiveTime::MIN);
+        let utc = |naive: NaiveDateTime| Local.from_local_datetime(&naive).unwrap().with_timezone(&Utc);
+
+        let fixtures = [
+            ("\"2024-01-05T12:30:00Z\"", "2024-01-05T12:30:00Z".to_string()),
+            ("\"2024-01-05T14:30:00+02:00\"", "2024-01-05T12:30:00Z".to_string()),
+            ("\"2024-01-05 12:30:00.250+00:00\"", "2024-01-05T12:30:00.250Z".to_string()),
+            ("\"2024-01-05T12:30:00\"", Timestamp(utc(local_noon)).to_rfc3339()),
+            ("\"2024-01-05 12:30\"", Timestamp(utc(local_noon)).to_rfc3339()),
+            ("\"2024-01-05\"", Timestamp(utc(local_midnight)).to_rfc3339()),
+            ("\"2024-1-5\"", Timestamp(utc(local_midnight)).to_rfc3339()),
+            ("1704457800000", "2024-01-05T12:30:00Z".to_string()),
+        ];
+
+        for (json, expected) in fixtures {
+            let parsed: Timestamp = serde_json::from_str(json).unwrap();
+            let written = serde_json::to_string(&parsed).unwrap();
+            assert_eq!(written, format!("\"{}\"", expected), "fixture {}", json);
+
+            // Normalized output reads back to the same instant
+            assert_eq!(serde_json::from_str::<Timestamp>(&written).unwrap(), parsed);
+        }
+
+        assert!(serde_json::from_str::<Timestamp>("\"next tuesday\"").is_err());
+    }
+
+    #[test]
+    fn test_offsets_compare_by_instant_not_text() {
+        // As text the first sorts after the second; in time it is earlier
+        let (a, b) = ("2024-01-01T10:00:00+02:00", "2024-01-01T09:00:00Z");
+        assert!(a > b);
+        assert!(Timestamp::parse(a).unwrap() < Timestamp::parse(b).unwrap());
+
+        // Non-padded dates sorted before padded ones as text
+        let (c, d) = ("2024-1-5", "2024-01-10");
+        assert!(c > d);
+        assert!(parse_date(c).unwrap() < parse_date(d).unwrap());
+    }
+
+    #[test]
+    fn test_lenient_option_drops_unreadable_values() {
+        #[derive(Deserialize)]
+        struct Doc {
+            #[serde(default, deserialize_with = "lenient_option")]
+            at: Option<Timestamp>,
+        }
+
+        let read = |json: &str| serde_json::from_str::<Doc>(json).unwrap().at;
+        assert_eq!(read(r#"{"at": "garbage"}"#), None);
+        assert_eq!(read(r#"{"at": null}"#), None);
+        assert_eq!(read("{}"), None);
+        assert_eq!(read(r#"{"at": "2024-01-05T12:30:00Z"}"#).unwrap().as_millis(), 1_704_457_800_000);
+    }
+}
@@ -0,0 +1,576 @@
+//! ============================================================================
+//! Hibiscus Focus Time Tracking
+//! ============================================================================
+//!
+//! Turns the frontend's tab focus/blur events into per-note, per-day
+//! durations stored in `.hibiscus/time-tracking.jsonl`.
+//!
+//! STORAGE:
+//! - One JSON record per line, either a raw focus/blur event or a compacted
+//!   daily summary (`date` + `path` + milliseconds).
+//! - Events are appended as they arrive. Once the file grows past
+//!   COMPACT_THRESHOLD_BYTES, every closed session is folded into summaries
+//!   and only a still-open focus is kept as a raw event.
+//!
+//! OPEN DURATIONS:
+//! - `.hibiscus/activity.json` keeps a running total of open time and open
+//!   count per file, for the writing-habits dashboard.
+//! - At most MAX_ACTIVITY_FILES files are kept; the least recently opened
+//!   are dropped first.
+//!
+//! PAIRING RULES:
+//! - Events are sorted by timestamp first, so out-of-order delivery is fine.
+//! - A focus closes whatever session is open and starts a new one.
+//! - A blur closes the open session only if it names the same note; stray or
+//!   duplicate blurs are ignored.
+//! - No session lasts longer than MAX_SESSION_MS, which bounds the damage of
+//!   a missing blur (crash, sleep, closed laptop).
+//!
+//! Days are local calendar days, using the UTC offset the frontend sent with
+//! the focus event that opened the session.
+//! ============================================================================
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::error::HibiscusError;
+use crate::time::Timestamp;
+
+/// Longest duration credited to a single focus session (2 hours).
+pub const MAX_SESSION_MS: i64 = 2 * 60 * 60 * 1000;
+
+/// File size above which the log is compacted after an append.
+const COMPACT_THRESHOLD_BYTES: u64 = 64 * 1024;
+
+const MS_PER_DAY: i64 = 24 * 60 * 60 * 1000;
+
+/// Whether a note gained or lost focus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FocusKind {
+    Focus,
+    Blur,
+}
+
+/// A single focus change reported by the frontend.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FocusEvent {
+    /// Note path relative to the workspace root
+    pub path: String,
+    pub event: FocusKind,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: i64,
+    /// Local offset from UTC in minutes when the event happened
+    #[serde(default)]
+    pub offset_minutes: i32,
+}
+
+/// One line of the time-tracking log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Record {
+    Event(FocusEvent),
+    Summary { date: String, path: String, ms: i64 },
+}
+
+/// A closed stretch of time spent on one note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub path: String,
+    pub start: i64,
+    pub end: i64,
+    pub offset_minutes: i32,
+}
+
+/// Result of pairing a list of events.
+#[derive(Debug, Default)]
+pub struct Pairing {
+    pub sessions: Vec<Session>,
+    /// Focus event with no closing event yet
+    pub open: Option<FocusEvent>,
+}
+
+impl Pairing {
+    /// Closes the open session as of `now` (capped), for reporting.
+    pub fn close_open(&mut self, now: i64) {
+        if let Some(open) = self.open.take() {
+            self.sessions.push(close(&open, now));
+        }
+    }
+}
+
+/// Pairs focus and blur events into sessions according to the module rules.
+pub fn pair_events(events: &[FocusEvent]) -> Pairing {
+    let mut sorted: Vec<&FocusEvent> = events.iter().collect();
+    // Blur sorts before focus at the same instant, so a tab switch reported
+    // with one timestamp closes the old note before opening the new one.
+    sorted.sort_by_key(|e| (e.timestamp, e.event == FocusKind::Focus));
+
+    let mut pairing = Pairing::default();
+    for event in sorted {
+        match event.event {
+            FocusKind::Focus => {
+                if let Some(open) = pairing.open.take() {
+                    pairing.sessions.push(close(&open, event.timestamp));
+                }
+                pairing.open = Some(event.clone());
+            }
+            FocusKind::Blur => {
+                if let Some(open) = pairing.open.take_if(|open| open.path == event.path) {
+                    pairing.sessions.push(close(&open, event.timestamp));
+                }
+            }
+        }
+    }
+
+    pairing
+}
+
+fn close(open: &FocusEvent, at: i64) -> Session {
+    Session {
+        path: open.path.clone(),
+        start: open.timestamp,
+        end: at.clamp(open.timestamp, open.timestamp + MAX_SESSION_MS),
+        offset_minutes: open.offset_minutes,
+    }
+}
+
+/// Splits a session at local midnights into (date, ms) parts.
+pub fn split_by_day(session: &Session) -> Vec<(String, i64)> {
+    let offset_ms = i64::from(session.offset_minutes) * 60_000;
+    let mut parts = Vec::new();
+    let mut start = session.start;
+
+    while start < session.end {
+        let day = (start + offset_ms).div_euclid(MS_PER_DAY);
+        let next_midnight = (day + 1) * MS_PER_DAY - offset_ms;
+        let end = session.end.min(next_midnight);
+        parts.push((format_date(day), end - start));
+        start = end;
+    }
+
+    parts
+}
+
+/// Local `YYYY-MM-DD` date of a millisecond timestamp.
+pub fn local_date(ms: i64, offset_minutes: i32) -> String {
+    let offset_ms = i64::from(offset_minutes) * 60_000;
+    format_date((ms + offset_ms).div_euclid(MS_PER_DAY))
+}
+
+/// Formats days since the Unix epoch as `YYYY-MM-DD`.
+fn format_date(days: i64) -> String {
+    // Howard Hinnant's civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Inclusive date range (`YYYY-MM-DD`); a missing bound is unbounded.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct DateRange {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+impl DateRange {
+    /// Compares as dates, so unpadded bounds like `2024-1-5` work. Bounds
+    /// that are not dates at all are treated as missing.
+    pub fn contains(&self, date: &str) -> bool {
+        let bound = |value: &Option<String>| value.as_deref().and_then(crate::time::parse_date);
+        let (from, to) = (bound(&self.from), bound(&self.to));
+        if from.is_none() && to.is_none() {
+            return true;
+        }
+
+        crate::time::parse_date(date).is_some_and(|date| {
+            from.is_none_or(|from| date >= from) && to.is_none_or(|to| date <= to)
+        })
+    }
+
+    /// Whether `date` lies before the start of the range.
+    pub fn is_before(&self, date: &str) -> bool {
+        let from = self.from.as_deref().and_then(crate::time::parse_date);
+        from.zip(crate::time::parse_date(date)).is_some_and(|(from, date)| date < from)
+    }
+}
+
+/// Per-day, per-note totals in milliseconds: date -> path -> ms.
+pub type DailyTotals = BTreeMap<String, BTreeMap<String, i64>>;
+
+/// Aggregates summaries and events into daily totals within `range`.
+///
+/// A session that is still open is counted up to `now` (capped).
+pub fn daily_totals(records: &[Record], range: &DateRange, now: i64) -> DailyTotals {
+    let (mut totals, events) = split_records(records, range);
+
+    let mut pairing = pair_events(&events);
+    pairing.close_open(now);
+    add_sessions(&mut totals, &pairing.sessions, range);
+
+    totals
+}
+
+/// Folds closed sessions into summaries, keeping an open focus as an event.
+pub fn compact(records: &[Record]) -> Vec<Record> {
+    let all = DateRange::default();
+    let (mut totals, events) = split_records(records, &all);
+
+    let pairing = pair_events(&events);
+    add_sessions(&mut totals, &pairing.sessions, &all);
+
+    let mut compacted: Vec<Record> = totals
+        .into_iter()
+        .flat_map(|(date, notes)| {
+            notes.into_iter().filter(|(_, ms)| *ms > 0).map(move |(path, ms)| Record::Summary {
+                date: date.clone(),
+                path,
+                ms,
+            })
+        })
+        .collect();
+    compacted.extend(pairing.open.map(Record::Event));
+    compacted
+}
+
+/// Sums summaries within `range` and collects the raw events.
+fn split_records(records: &[Record], range: &DateRange) -> (DailyTotals, Vec<FocusEvent>) {
+    let mut totals = DailyTotals::new();
+    let mut events = Vec::new();
+
+    for record in records {
+        match record {
+            Record::Event(event) => events.push(event.clone()),
+            Record::Summary { date, path, ms } => {
+                if range.contains(date) {
+                    *totals.entry(date.clone()).or_default().entry(path.clone()).or_default() += ms;
+                }
+            }
+        }
+    }
+
+    (totals, events)
+}
+
+fn add_sessions(totals: &mut DailyTotals, sessions: &[Session], range: &DateRange) {
+    for session in sessions {
+        for (date, ms) in split_by_day(session) {
+            if range.contains(&date) {
+                *totals.entry(date).or_default().entry(session.path.clone()).or_default() += ms;
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Storage
+// ---------------------------------------------------------------------------
+
+/// Location of the time-tracking log for a workspace.
+pub fn log_path(root: &Path) -> PathBuf {
+    root.join(".hibiscus").join("time-tracking.jsonl")
+}
+
+/// Reads every record; malformed lines are skipped.
+pub fn read_records(root: &Path) -> Result<Vec<Record>, HibiscusError> {
+    let path = log_path(root);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(HibiscusError::Io(format!(
+                "Failed to read '{}': {}",
+                path.display(),
+                e
+            )))
+        }
+    };
+
+    Ok(content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Appends an event, compacting the log if it has grown too large.
+pub fn append_event(root: &Path, event: &FocusEvent) -> Result<(), HibiscusError> {
+    let path = log_path(root);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let line = serde_json::to_string(&Record::Event(event.clone()))?;
+    let mut file = fs::OpenOptions::new().create(true).append(true).open(&path)?;
+    writeln!(file, "{}", line)?;
+    let size = file.metadata()?.len();
+    drop(file);
+
+    if size > COMPACT_THRESHOLD_BYTES {
+        let records = read_records(root)?;
+        write_records(&path, &compact(&records))?;
+    }
+    Ok(())
+}
+
+/// Replaces the log with `records` via a temp file + rename.
+fn write_records(path: &Path, records: &[Record]) -> Result<(), HibiscusError> {
+    let mut content = String::new();
+    for record in records {
+        content.push_str(&serde_json::to_string(record)?);
+        content.push('\n');
+    }
+    replace_file(path, "jsonl.tmp", &content)
+}
+
+fn replace_file(path: &Path, temp_extension: &str, content: &str) -> Result<(), HibiscusError> {
+    let temp_path = path.with_extension(temp_extension);
+    fs::write(&temp_path, content).map_err(|e| {
+        HibiscusError::Io(format!("Failed to write '{}': {}", temp_path.display(), e))
+    })?;
+
+    #[cfg(target_os = "windows")]
+    if path.exists() {
+        let _ = fs::remove_file(path);
+    }
+
+    fs::rename(&temp_path, path).map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        HibiscusError::Io(format!("Failed to finalize '{}': {}", path.display(), e))
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Open durations
+// ---------------------------------------------------------------------------
+
+/// Most files kept in `activity.json`.
+pub const MAX_ACTIVITY_FILES: usize = 5_000;
+
+/// Accumulated open time of one file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileActivity {
+    pub seconds: u64,
+    /// Number of recorded opens
+    pub opens: u64,
+    pub last_opened: Timestamp,
+}
+
+/// Contents of `activity.json`, keyed by node id.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivityLog {
+    #[serde(default)]
+    pub files: BTreeMap<String, FileActivity>,
+}
+
+impl ActivityLog {
+    /// Adds one open of `seconds` to a file's totals, then drops the least
+    /// recently opened files beyond MAX_ACTIVITY_FILES.
+    pub fn record(&mut self, node_id: &str, seconds: u64, now: Timestamp) {
+        let entry = self
+            .files
+            .entry(node_id.to_string())
+            .or_insert(FileActivity { seconds: 0, opens: 0, last_opened: now });
+        entry.seconds = entry.seconds.saturating_add(seconds);
+        entry.opens += 1;
+        entry.last_opened = now;
+
+        if self.files.len() > MAX_ACTIVITY_FILES {
+            let mut by_age: Vec<(Timestamp, String)> = self
+                .files
+                .iter()
+                .map(|(id, activity)| (activity.last_opened, id.clone()))
+                .collect();
+            by_age.sort();
+            let excess = self.files.len() - MAX_ACTIVITY_FILES;
+            for (_, id) in by_age.into_iter().take(excess) {
+                self.files.remove(&id);
+            }
+        }
+    }
+}
+
+/// Location of the open-duration totals for a workspace.
+pub fn activity_path(root: &Path) -> PathBuf {
+    root.join(".hibiscus").join("activity.json")
+}
+
+/// Reads the open-duration totals; a missing or malformed file reads as empty.
+pub fn read_activity(root: &Path) -> Result<ActivityLog, HibiscusError> {
+    let path = activity_path(root);
+    match fs::read_to_string(&path) {
+        Ok(content) => Ok(serde_json::from_str(&content).unwrap_or_else(|e| {
+            eprintln!("[Hibiscus] Ignoring malformed '{}': {}", path.display(), e);
+            ActivityLog::default()
+        })),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(ActivityLog::default()),
+        Err(e) => Err(HibiscusError::Io(format!("Failed to read '{}': {}", path.display(), e))),
+    }
+}
+
+/// Replaces the open-duration totals via a temp file + rename.
+pub fn write_activity(root: &Path, log: &ActivityLog) -> Result<(), HibiscusError> {
+    let path = activity_path(root);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    replace_file(&path, "json.tmp", &serde_json::to_string_pretty(log)?)
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIN: i64 = 60_000;
+
+    fn ev(path: &str, event: FocusKind, minute: i64) -> FocusEvent {
+        FocusEvent {
+            path: path.into(),
+            event,
+            timestamp: minute * MIN,
+            offset_minutes: 0,
+        }
+    }
+
+    fn durations(pairing: &Pairing) -> Vec<(&str, i64)> {
+        pairing
+            .sessions
+            .iter()
+            .map(|s| (s.path.as_str(), (s.end - s.start) / MIN))
+            .collect()
+    }
+
+    #[test]
+    fn test_out_of_order_events_are_sorted() {
+        use FocusKind::*;
+        let events = [ev("a.md", Blur, 10), ev("b.md", Focus, 10), ev("a.md", Focus, 0), ev("b.md", Blur, 15)];
+        let pairing = pair_events(&events);
+        assert_eq!(durations(&pairing), vec![("a.md", 10), ("b.md", 5)]);
+        assert!(pairing.open.is_none());
+    }
+
+    #[test]
+    fn test_focus_without_blur_closed_by_next_focus() {
+        use FocusKind::*;
+        let events = [ev("a.md", Focus, 0), ev("b.md", Focus, 7), ev("a.md", Focus, 9)];
+        let pairing = pair_events(&events);
+        assert_eq!(durations(&pairing), vec![("a.md", 7), ("b.md", 2)]);
+        assert_eq!(pairing.open.as_ref().unwrap().path, "a.md");
+    }
+
+    #[test]
+    fn test_stray_and_duplicate_blurs_ignored() {
+        use FocusKind::*;
+        let events = [
+            ev("x.md", Blur, 0),
+            ev("a.md", Focus, 1),
+            ev("b.md", Blur, 2),
+            ev("a.md", Blur, 4),
+            ev("a.md", Blur, 6),
+        ];
+        let pairing = pair_events(&events);
+        assert_eq!(durations(&pairing), vec![("a.md", 3)]);
+    }
+
+    #[test]
+    fn test_sessions_capped() {
+        use FocusKind::*;
+        let events = [ev("a.md", Focus, 0), ev("a.md", Blur, 600)];
+        assert_eq!(durations(&pair_events(&events)), vec![("a.md", MAX_SESSION_MS / MIN)]);
+
+        // An open session is reported up to `now`, but never past the cap
+        let mut pairing = pair_events(&[ev("b.md", Focus, 0)]);
+        pairing.close_open(1_000 * MIN);
+        assert_eq!(durations(&pairing), vec![("b.md", MAX_SESSION_MS / MIN)]);
+    }
+
+    #[test]
+    fn test_split_at_local_midnight() {
+        // 2024-01-01T23:30 local in UTC+2 is 21:30 UTC
+        let start = 19_723 * MS_PER_DAY + (21 * 60 + 30) * MIN;
+        let session = Session {
+            path: "a.md".into(),
+            start,
+            end: start + 60 * MIN,
+            offset_minutes: 120,
+        };
+        assert_eq!(
+            split_by_day(&session),
+            vec![("2024-01-01".into(), 30 * MIN), ("2024-01-02".into(), 30 * MIN)]
+        );
+    }
+
+    #[test]
+    fn test_compact_preserves_totals_and_open_focus() {
+        use FocusKind::*;
+        let records: Vec<Record> = [
+            ev("a.md", Focus, 0),
+            ev("a.md", Blur, 5),
+            ev("b.md", Focus, 6),
+        ]
+        .into_iter()
+        .map(Record::Event)
+        .chain([Record::Summary { date: "1970-01-01".into(), path: "a.md".into(), ms: MIN }])
+        .collect();
+
+        let compacted = compact(&records);
+        assert!(compacted.contains(&Record::Summary {
+            date: "1970-01-01".into(),
+            path: "a.md".into(),
+            ms: 6 * MIN
+        }));
+        assert_eq!(compacted.last(), Some(&Record::Event(ev("b.md", Focus, 6))));
+
+        let now = 8 * MIN;
+        let all = DateRange::default();
+        assert_eq!(daily_totals(&records, &all, now), daily_totals(&compacted, &all, now));
+    }
+
+    #[test]
+    fn test_range_filters_days() {
+        let records = vec![
+            Record::Summary { date: "2024-03-01".into(), path: "a.md".into(), ms: 1 },
+            Record::Summary { date: "2024-03-05".into(), path: "a.md".into(), ms: 2 },
+        ];
+        let range = DateRange { from: Some("2024-03-02".into()), to: None };
+        let totals = daily_totals(&records, &range, 0);
+        assert_eq!(totals.keys().collect::<Vec<_>>(), vec!["2024-03-05"]);
+
+        // Unpadded bounds compare as dates ("2024-3-2" > "2024-03-05" as text)
+        let range = DateRange { from: Some("2024-3-2".into()), to: Some("2024-3-9".into()) };
+        let totals = daily_totals(&records, &range, 0);
+        assert_eq!(totals.keys().collect::<Vec<_>>(), vec!["2024-03-05"]);
+    }
+
+    #[test]
+    fn test_activity_keeps_most_recently_opened_files() {
+        let at = |seconds: i64| Timestamp::from_millis(seconds * 1000).unwrap();
+        let mut log = ActivityLog::default();
+        for i in 0..MAX_ACTIVITY_FILES {
+            log.record(&format!("{}.md", i), 1, at(i as i64 + 10));
+        }
+        // Reopening the oldest file keeps it; the next oldest goes instead
+        log.record("0.md", 5, at(100_000));
+        log.record("new.md", 1, at(100_001));
+
+        assert_eq!(log.files.len(), MAX_ACTIVITY_FILES);
+        assert!(!log.files.contains_key("1.md"));
+        assert_eq!(log.files["0.md"].seconds, 6);
+        assert_eq!(log.files["0.md"].opens, 2);
+    }
+}
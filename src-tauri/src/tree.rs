@@ -5,27 +5,48 @@
 //! Recursive directory tree builder for workspace file navigation.
 //!
 //! FEATURES:
-//! - Recursive directory traversal with depth limits
-//! - Alphabetical sorting (folders first, then files)
-//! - Hidden file filtering (.hibiscus, dotfiles)
+//! - Recursive directory traversal with depth limits, reporting where the
+//!   limit left folders unread (`read_dir_limited`)
+//! - Optional cap on the total node count for very wide folders
+//!   (`TreeOptions::max_nodes`)
+//! - Subfolders are read in parallel (rayon); output order is the same as
+//!   a sequential walk
+//! - One-level reads for expanding folders on demand (`read_dir_shallow`)
+//! - Folders first, then files; each group sorted by name (default),
+//!   modified time or size (`SortMode`)
+//! - Hidden file filtering (dotfiles unless `include_hidden`), plus the
+//!   built-in ignore list and the workspace's `.hibiscusignore` patterns
+//!   (see `crate::ignore`)
+//! - File size, modified time and symlink flag in each node's `meta`
+//! - Folders are listed through the workspace's storage (`crate::storage`)
 //! - Robust error handling (no panics)
 //!
 //! DESIGN DECISIONS:
 //! - Uses iterative approach with controlled recursion depth
+//! - Each folder is listed and sorted first, then its children are built
+//!   in parallel and collected in that sorted order
 //! - Silently skips unreadable files/directories instead of failing
 //! - Represents tree structure as nested Nodes for frontend consumption
 //! ============================================================================
 
-use std::fs;
-use std::path::Path;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::{Arc, LazyLock, Mutex};
 
-use crate::workspace::{Node, NodeType};
+use rayon::prelude::*;
+
+use crate::ids::to_canonical_id;
+use crate::ignore::{is_builtin_ignored, is_hidden, shared_rules, IgnoreRules};
+use crate::limits::{LimitHit, DEFAULT_TREE_DEPTH, TREE_DEPTH, TREE_NODES};
+use crate::storage::{storage_for, StorageEntry, VaultStorage};
+use crate::workspace::{FileMetadata, Node, NodeDecoration, NodeMeta, NodeType};
 
 /// Default maximum recursion depth for directory traversal.
 /// This prevents infinite recursion and excessive memory usage
 /// for very deeply nested directory structures.
-#[allow(dead_code)]
-pub const DEFAULT_MAX_DEPTH: usize = 20;
+pub const DEFAULT_MAX_DEPTH: usize = DEFAULT_TREE_DEPTH;
 
 /// Recursively reads a directory and builds a tree of Nodes.
 ///
@@ -36,6 +57,8 @@ pub const DEFAULT_MAX_DEPTH: usize = 20;
 /// * `root` - The directory to read
 /// * `base` - The base path for computing relative paths (typically workspace root)
 /// * `max_depth` - Maximum recursion depth (use DEFAULT_MAX_DEPTH for normal usage)
+/// * `include_hidden` - Also list dotfiles and dot-folders; built-in
+///   ignored names such as `.hibiscus` are skipped either way
 ///
 /// # Returns
 /// A vector of Nodes representing the directory contents.
@@ -48,19 +71,251 @@ pub const DEFAULT_MAX_DEPTH: usize = 20;
 ///
 /// # Sorting
 /// Results are sorted with folders first, then files.
-/// Both groups are sorted alphabetically (case-insensitive).
-pub fn read_dir_recursive(root: &Path, base: &Path, max_depth: usize) -> Vec<Node> {
+/// Both groups are sorted alphabetically (case-insensitive); see
+/// `read_dir_sorted` for other orders.
+pub fn read_dir_recursive(
+    root: &Path,
+    base: &Path,
+    max_depth: usize,
+    include_hidden: bool,
+) -> Vec<Node> {
+    read_dir_limited(root, base, max_depth, include_hidden).0
+}
+
+/// `read_dir_recursive` that also reports every folder left unread
+/// because `max_depth` was reached.
+///
+/// Patterns from `base`'s `.hibiscusignore` apply at every level.
+pub fn read_dir_limited(
+    root: &Path,
+    base: &Path,
+    max_depth: usize,
+    include_hidden: bool,
+) -> (Vec<Node>, Vec<LimitHit>) {
+    read_dir_with(root, base, max_depth, include_hidden, TreeOptions::default())
+}
+
+/// Optional behavior of `read_dir_with`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TreeOptions {
+    pub sort: SortMode,
+    /// Stop once the tree has this many nodes (folders and files, at any
+    /// depth). Each folder whose listing was cut short gets a `TREE_NODES`
+    /// limit hit.
+    pub max_nodes: Option<usize>,
+}
+
+/// How the entries of each folder are ordered.
+///
+/// Folders always come before files; the mode orders each group.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortMode {
+    /// Alphabetical, case-insensitive
+    #[default]
+    NameAsc,
+    /// Reverse alphabetical, case-insensitive
+    NameDesc,
+    /// Most recently modified first
+    ModifiedDesc,
+    /// Largest first. Folders have no size, so they stay alphabetical.
+    SizeDesc,
+}
+
+impl SortMode {
+    /// Compares two entries of the same group. Ties, and entries whose
+    /// metadata can't be read, fall back to the name order.
+    fn compare(self, a: &PendingEntry, b: &PendingEntry) -> Ordering {
+        // Case-insensitive, then by exact name so names differing only in
+        // case keep a stable order
+        let by_name = || a.name.to_lowercase().cmp(&b.name.to_lowercase()).then_with(|| a.name.cmp(&b.name));
+        let modified = |entry: &PendingEntry| entry.metadata.as_ref().and_then(|m| m.modified);
+        let size = |entry: &PendingEntry| entry.metadata.as_ref().map(|m| m.size);
+
+        match self {
+            SortMode::NameAsc => by_name(),
+            SortMode::NameDesc => by_name().reverse(),
+            SortMode::ModifiedDesc => modified(b).cmp(&modified(a)).then_with(by_name),
+            SortMode::SizeDesc => size(b).cmp(&size(a)).then_with(by_name),
+        }
+    }
+}
+
+/// `read_dir_limited` with a sort order and an optional node cap.
+///
+/// # Node cap
+/// Folders are listed in directory order, so with `max_nodes` the entries
+/// kept in the folder where the cap is reached are the first ones listed,
+/// sorted. Capped walks run sequentially, which keeps the kept nodes the
+/// same from one run to the next.
+pub fn read_dir_with(
+    root: &Path,
+    base: &Path,
+    max_depth: usize,
+    include_hidden: bool,
+    options: TreeOptions,
+) -> (Vec<Node>, Vec<LimitHit>) {
+    let (nodes, walk) = walk_tree(root, base, max_depth, include_hidden, options);
+    let truncated = walk.hits.iter().any(|hit| hit.limit == TREE_NODES);
+
+    // Only whole trees are worth keeping for later checks
+    if root == base && !truncated {
+        if let Ok(mut cache) = TREE_CACHE.lock() {
+            cache.insert(base.to_path_buf(), walk.tree);
+        }
+    }
+
+    (nodes, walk.hits)
+}
+
+/// An entry of a built tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeEntry {
+    /// Node id (canonical relative path)
+    pub id: String,
+    /// Relative path with the on-disk spelling, as ignore patterns see it
+    pub path: String,
+    pub is_dir: bool,
+}
+
+/// The last whole tree read for a workspace.
+///
+/// Lets checks such as `preview_ignore_pattern` run without walking the
+/// disk again. Contents of pattern-ignored folders are not read, so only
+/// the folders themselves are in `ignored`.
+#[derive(Debug, Clone, Default)]
+pub struct CachedTree {
+    /// Entries the tree showed
+    pub visible: Vec<TreeEntry>,
+    /// Entries left out by ignore patterns
+    pub ignored: Vec<TreeEntry>,
+}
+
+static TREE_CACHE: LazyLock<Mutex<HashMap<PathBuf, CachedTree>>> = LazyLock::new(Default::default);
+
+/// The cached tree of the workspace at `root`, if one was read since
+/// startup.
+pub fn cached_tree(root: &Path) -> Option<CachedTree> {
+    TREE_CACHE.lock().ok().and_then(|cache| cache.get(root).cloned())
+}
+
+fn walk_tree(
+    root: &Path,
+    base: &Path,
+    max_depth: usize,
+    include_hidden: bool,
+    options: TreeOptions,
+) -> (Vec<Node>, Walk) {
+    let filter = EntryFilter {
+        storage: storage_for(base),
+        ignore: shared_rules(base),
+        include_hidden,
+        sort: options.sort,
+        max_nodes: options.max_nodes,
+        nodes_left: AtomicUsize::new(options.max_nodes.unwrap_or(0)),
+    };
+    let mut walk = Walk::default();
+    let nodes = read_level(root, base, max_depth, max_depth, &filter, &mut walk);
+    (nodes, walk)
+}
+
+/// What `read_level` collects besides the nodes.
+#[derive(Default)]
+struct Walk {
+    hits: Vec<LimitHit>,
+    tree: CachedTree,
+}
+
+impl Walk {
+    /// Appends what a subtree's walk collected.
+    fn merge(&mut self, other: Walk) {
+        self.hits.extend(other.hits);
+        self.tree.visible.extend(other.tree.visible);
+        self.tree.ignored.extend(other.tree.ignored);
+    }
+}
+
+/// Reads only the immediate children of `dir`, for expanding the tree on
+/// demand.
+///
+/// Folder nodes have `children: None`; `meta.has_children` tells the
+/// explorer whether to draw an expand arrow. Ids, ignore patterns and
+/// sorting are the same as `read_dir_recursive` with `base` as the root.
+pub fn read_dir_shallow(dir: &Path, base: &Path, include_hidden: bool) -> Vec<Node> {
+    // One level deep, the depth limit reports exactly the folders that
+    // have visible entries
+    let (mut nodes, walk) = walk_tree(dir, base, 1, include_hidden, TreeOptions::default());
+    let hits = walk.hits;
+
+    for node in nodes.iter_mut().filter(|node| node.children.is_some()) {
+        node.children = None;
+        let has_children = hits.iter().any(|hit| hit.path == node.id);
+        let meta = node
+            .meta
+            .get_or_insert_with(|| serde_json::Value::Object(Default::default()));
+        if let Some(meta) = meta.as_object_mut() {
+            meta.insert("has_children".into(), has_children.into());
+        }
+    }
+
+    nodes
+}
+
+/// Which entries `read_level` skips, how it orders the rest, and how many
+/// it may still add.
+struct EntryFilter {
+    /// Where the workspace's files live
+    storage: Arc<dyn VaultStorage>,
+    ignore: Arc<IgnoreRules>,
+    include_hidden: bool,
+    sort: SortMode,
+    max_nodes: Option<usize>,
+    /// Nodes the walk may still add (only used with `max_nodes`)
+    nodes_left: AtomicUsize,
+}
+
+impl EntryFilter {
+    /// Whether an entry is hidden by its name alone.
+    fn hides_name(&self, file_name: &str) -> bool {
+        is_builtin_ignored(file_name) || (!self.include_hidden && is_hidden(file_name))
+    }
+
+    /// Counts one more node against `max_nodes`; `false` once it is spent.
+    fn take_node(&self) -> bool {
+        self.max_nodes.is_none()
+            || self
+                .nodes_left
+                .fetch_update(AtomicOrdering::Relaxed, AtomicOrdering::Relaxed, |left| left.checked_sub(1))
+                .is_ok()
+    }
+}
+
+fn read_level(
+    root: &Path,
+    base: &Path,
+    remaining: usize,
+    max_depth: usize,
+    filter: &EntryFilter,
+    walk: &mut Walk,
+) -> Vec<Node> {
     // Prevent infinite recursion
-    if max_depth == 0 {
+    if remaining == 0 {
+        if has_visible_entries(root, filter) {
+            walk.hits.push(LimitHit {
+                limit: TREE_DEPTH.to_string(),
+                path: to_canonical_id(root, base),
+                value: max_depth,
+            });
+        }
         return Vec::new();
     }
 
     // Separate containers for folders and files to enable sorted output
-    let mut folders: Vec<Node> = Vec::new();
-    let mut files: Vec<Node> = Vec::new();
+    let mut folders: Vec<PendingEntry> = Vec::new();
+    let mut files: Vec<PendingEntry> = Vec::new();
 
     // Attempt to read directory, return empty on failure
-    let entries = match fs::read_dir(root) {
+    let entries = match filter.storage.list_dir(root) {
         Ok(entries) => entries,
         Err(e) => {
             // Log error but don't fail - just return empty
@@ -73,18 +328,8 @@ pub fn read_dir_recursive(root: &Path, base: &Path, max_depth: usize) -> Vec<Nod
         }
     };
 
-    // Process each directory entry
-    for entry_result in entries {
-        // Skip entries that can't be read
-        let entry = match entry_result {
-            Ok(e) => e,
-            Err(e) => {
-                eprintln!("[Hibiscus] Warning: Failed to read entry: {}", e);
-                continue;
-            }
-        };
-
-        let path = entry.path();
+    // Process each directory entry (unreadable ones are already skipped)
+    for StorageEntry { path, is_dir } in entries {
 
         // Get the file name, skip if it can't be determined
         let file_name = match path.file_name() {
@@ -104,78 +349,268 @@ pub fn read_dir_recursive(root: &Path, base: &Path, max_depth: usize) -> Vec<Nod
             }
         };
 
-        // Skip hidden files and directories (starting with .)
-        // This includes .hibiscus, .git, .vscode, etc.
-        if file_name.starts_with('.') {
+        // Skip built-in ignored names (.hibiscus, node_modules, ...) and,
+        // unless asked for, hidden entries (starting with .)
+        if filter.hides_name(&file_name) {
             continue;
         }
 
-        // Compute relative path from base
+        // Compute relative path from base, with portable separators
         let rel_path = match path.strip_prefix(base) {
-            Ok(p) => p.to_string_lossy().to_string(),
+            Ok(p) => p.to_string_lossy().replace('\\', "/"),
             Err(_) => {
                 // Path is outside base - use full path as fallback
                 path.to_string_lossy().to_string()
             }
         };
 
-        // Use relative path as ID for consistency
-        let id = rel_path.clone();
+        // The canonical form of the relative path is the node ID. `path`
+        // keeps the on-disk spelling (not NFC-normalized) so it always opens.
+        let id = to_canonical_id(&path, base);
+
+        // Skip paths matched by .hibiscusignore or the settings
+        let entry = TreeEntry { id: id.clone(), path: rel_path.clone(), is_dir };
+        if filter.ignore.is_ignored(&rel_path, is_dir) {
+            walk.tree.ignored.push(entry);
+            continue;
+        }
+
+        // Stop listing once the node cap is reached
+        if !filter.take_node() {
+            walk.hits.push(LimitHit {
+                limit: TREE_NODES.to_string(),
+                path: to_canonical_id(root, base),
+                value: filter.max_nodes.unwrap_or(0),
+            });
+            break;
+        }
+        walk.tree.visible.push(entry);
+
+        // Add to appropriate collection
+        // Read once: sorting may need it, and it becomes the node's meta
+        let metadata = filter.storage.metadata(&path).ok();
+        let pending = PendingEntry { id, name: file_name, path, rel_path, metadata };
+        if is_dir {
+            folders.push(pending);
+        } else {
+            files.push(pending);
+        }
+    }
 
-        // Determine if this is a file or directory
-        let is_dir = path.is_dir();
+    // Sort each group; folders stay ahead of files whatever the mode
+    folders.sort_by(|a, b| filter.sort.compare(a, b));
+    files.sort_by(|a, b| filter.sort.compare(a, b));
 
-        // Build the node
+    // Build the nodes in parallel (unless capped), subfolders included.
+    // `collect` keeps the sorted order, and each subtree's findings are
+    // merged in that order, so the output does not depend on scheduling.
+    let folder_count = folders.len();
+    let build = |(index, entry): (usize, PendingEntry)| {
+        let is_dir = index < folder_count;
+        let mut subtree = Walk::default();
         let node = Node {
-            id,
-            name: file_name,
+            id: entry.id,
+            name: entry.name,
             node_type: if is_dir {
                 NodeType::Folder
             } else {
                 NodeType::File
             },
             // Files get a path for opening, folders don't need one
-            path: if is_dir { None } else { Some(rel_path) },
+            path: if is_dir { None } else { Some(entry.rel_path) },
             // Recursively process subdirectories (with decremented depth)
             children: if is_dir {
-                Some(read_dir_recursive(&path, base, max_depth - 1))
+                Some(read_level(&entry.path, base, remaining - 1, max_depth, filter, &mut subtree))
             } else {
                 None
             },
-            // Meta is reserved for future use (file size, modified time, etc.)
-            meta: None,
+            // Size and modified time; None if metadata can't be read
+            meta: entry.metadata.as_ref().and_then(meta_value),
         };
+        (node, subtree)
+    };
+    let built: Vec<(Node, Walk)> = if filter.max_nodes.is_some() {
+        // Subtrees spending one shared cap in parallel would each get a
+        // share that depends on scheduling
+        folders.into_iter().chain(files).enumerate().map(build).collect()
+    } else {
+        folders.into_par_iter().chain(files).enumerate().map(build).collect()
+    };
 
-        // Add to appropriate collection
-        if is_dir {
-            folders.push(node);
-        } else {
-            files.push(node);
+    // Combine: folders first, then files
+    built
+        .into_iter()
+        .map(|(node, subtree)| {
+            walk.merge(subtree);
+            node
+        })
+        .collect()
+}
+
+/// An entry of a folder that passed the filters, before it becomes a node.
+struct PendingEntry {
+    id: String,
+    name: String,
+    path: PathBuf,
+    rel_path: String,
+    metadata: Option<FileMetadata>,
+}
+
+/// Builds the node for an item that was just created.
+///
+/// New folders have no children yet, so the node can be inserted into an
+/// existing tree without another traversal.
+pub fn new_item_node(path: &Path, base: &Path) -> Node {
+    let metadata = storage_for(path).metadata(path).ok();
+    let is_dir = metadata.as_ref().is_some_and(|metadata| metadata.is_dir);
+    let rel_path = match path.strip_prefix(base) {
+        Ok(p) => p.to_string_lossy().replace('\\', "/"),
+        Err(_) => path.to_string_lossy().to_string(),
+    };
+
+    Node {
+        id: to_canonical_id(path, base),
+        name: path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        node_type: if is_dir { NodeType::Folder } else { NodeType::File },
+        path: if is_dir { None } else { Some(rel_path) },
+        children: if is_dir { Some(Vec::new()) } else { None },
+        // Size and modified time; None if metadata can't be read
+        meta: metadata.as_ref().and_then(meta_value),
+    }
+}
+
+/// Builds the `meta` of a node from its filesystem metadata.
+fn meta_value(metadata: &FileMetadata) -> Option<serde_json::Value> {
+    serde_json::to_value(NodeMeta::from(metadata)).ok()
+}
+
+/// Returns whether a folder has entries the tree would show.
+fn has_visible_entries(dir: &Path, filter: &EntryFilter) -> bool {
+    filter.storage.list_dir(dir).is_ok_and(|entries| {
+        entries.iter().any(|entry| {
+            entry.path.file_name().is_some_and(|name| !filter.hides_name(&name.to_string_lossy()))
+        })
+    })
+}
+
+/// Merges stored decorations onto matching nodes' `meta`.
+///
+/// `icon` and `color` are written as top-level keys of the node's meta
+/// object, so the tree payload carries everything the explorer renders.
+/// Decorations for ids not present in the tree are ignored.
+pub fn apply_decorations(nodes: &mut [Node], decorations: &BTreeMap<String, NodeDecoration>) {
+    if decorations.is_empty() {
+        return;
+    }
+
+    for node in nodes {
+        if let Some(decoration) = decorations.get(&node.id) {
+            let meta = node
+                .meta
+                .get_or_insert_with(|| serde_json::Value::Object(Default::default()));
+            if let Some(meta) = meta.as_object_mut() {
+                if let Some(icon) = &decoration.icon {
+                    meta.insert("icon".into(), icon.clone().into());
+                }
+                if let Some(color) = &decoration.color {
+                    meta.insert("color".into(), color.clone().into());
+                }
+            }
+        }
+
+        if let Some(children) = node.children.as_mut() {
+            apply_decorations(children, decorations);
         }
     }
+}
+
+/// Reorders children by the stored manual order of their folder.
+///
+/// `order` maps a folder id (`""` for the root) to child ids. Children not
+/// listed keep their sorted position after the listed ones.
+pub fn apply_manual_order(nodes: &mut [Node], order: &BTreeMap<String, Vec<String>>, folder: &str) {
+    if let Some(ids) = order.get(folder) {
+        nodes.sort_by_key(|node| ids.iter().position(|id| *id == node.id).unwrap_or(ids.len()));
+    }
 
-    // Sort folders alphabetically (case-insensitive)
-    folders.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    for node in nodes {
+        if let Some(children) = node.children.as_mut() {
+            apply_manual_order(children, order, &node.id);
+        }
+    }
+}
 
-    // Sort files alphabetically (case-insensitive)
-    files.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+/// Node ids that differ between two trees.
+///
+/// Nodes are matched by id (the canonical relative path), so a moved or
+/// renamed file shows up as one removal plus one addition.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct TreeDiff {
+    /// Ids only in the new tree
+    pub added: Vec<String>,
+    /// Ids only in the old tree
+    pub removed: Vec<String>,
+    /// Ids in both trees whose type changed (file <-> folder)
+    pub retyped: Vec<String>,
+}
 
-    // Combine: folders first, then files
-    folders.extend(files);
+/// Compares two trees by node id. Each list is sorted.
+pub fn diff_trees(old: &[Node], new: &[Node]) -> TreeDiff {
+    fn flatten<'a>(nodes: &'a [Node], out: &mut BTreeMap<&'a str, bool>) {
+        for node in nodes {
+            out.insert(&node.id, matches!(node.node_type, NodeType::Folder));
+            if let Some(children) = &node.children {
+                flatten(children, out);
+            }
+        }
+    }
 
-    folders
+    let (mut old_ids, mut new_ids) = (BTreeMap::new(), BTreeMap::new());
+    flatten(old, &mut old_ids);
+    flatten(new, &mut new_ids);
+    diff_ids(&old_ids, &new_ids)
+}
+
+/// `diff_trees` over the flat entries of two cached trees.
+pub fn diff_entries(old: &[TreeEntry], new: &[TreeEntry]) -> TreeDiff {
+    fn ids(entries: &[TreeEntry]) -> BTreeMap<&str, bool> {
+        entries.iter().map(|entry| (entry.id.as_str(), entry.is_dir)).collect()
+    }
+    diff_ids(&ids(old), &ids(new))
+}
+
+/// Id -> is_folder maps of two trees compared.
+fn diff_ids(old_ids: &BTreeMap<&str, bool>, new_ids: &BTreeMap<&str, bool>) -> TreeDiff {
+    let mut diff = TreeDiff::default();
+    for (id, is_folder) in old_ids {
+        match new_ids.get(id) {
+            None => diff.removed.push(id.to_string()),
+            Some(new_is_folder) if new_is_folder != is_folder => diff.retyped.push(id.to_string()),
+            Some(_) => {}
+        }
+    }
+    diff.added = new_ids
+        .keys()
+        .filter(|id| !old_ids.contains_key(*id))
+        .map(|id| id.to_string())
+        .collect();
+
+    diff
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs::File;
+    use std::fs::{self, File};
     use tempfile::tempdir;
 
     #[test]
     fn test_empty_directory() {
         let dir = tempdir().unwrap();
-        let result = read_dir_recursive(dir.path(), dir.path(), DEFAULT_MAX_DEPTH);
+        let result = read_dir_recursive(dir.path(), dir.path(), DEFAULT_MAX_DEPTH, false);
         assert!(result.is_empty());
     }
 
@@ -183,31 +618,223 @@ mod tests {
     fn test_depth_limit() {
         let dir = tempdir().unwrap();
         // With depth 0, should return empty immediately
-        let result = read_dir_recursive(dir.path(), dir.path(), 0);
+        let result = read_dir_recursive(dir.path(), dir.path(), 0, false);
         assert!(result.is_empty());
     }
 
+    #[test]
+    fn test_file_meta_reports_size() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("notes.md"), vec![b'x'; 1234]).unwrap();
+        fs::create_dir(dir.path().join("folder")).unwrap();
+
+        let result = read_dir_recursive(dir.path(), dir.path(), DEFAULT_MAX_DEPTH, false);
+        let meta: NodeMeta = serde_json::from_value(result[1].meta.clone().unwrap()).unwrap();
+        assert_eq!(meta.size, Some(1234));
+        assert!(meta.modified.is_some());
+        assert!(!meta.is_symlink);
+
+        // Folders carry no size
+        let folder = result[0].meta.as_ref().unwrap();
+        assert!(folder.get("size").is_none());
+    }
+
     #[test]
     fn test_hidden_files_skipped() {
         let dir = tempdir().unwrap();
         File::create(dir.path().join(".hidden")).unwrap();
         File::create(dir.path().join("visible.txt")).unwrap();
 
-        let result = read_dir_recursive(dir.path(), dir.path(), DEFAULT_MAX_DEPTH);
+        let result = read_dir_recursive(dir.path(), dir.path(), DEFAULT_MAX_DEPTH, false);
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].name, "visible.txt");
     }
 
+    #[test]
+    fn test_include_hidden_lists_dotfiles_but_not_data_dir() {
+        let dir = tempdir().unwrap();
+        File::create(dir.path().join(".env")).unwrap();
+        File::create(dir.path().join("visible.txt")).unwrap();
+        std::fs::create_dir_all(dir.path().join(".hibiscus")).unwrap();
+        std::fs::create_dir_all(dir.path().join(".config")).unwrap();
+        File::create(dir.path().join(".config").join("app.toml")).unwrap();
+
+        let names = |nodes: &[Node]| nodes.iter().map(|n| n.name.clone()).collect::<Vec<_>>();
+
+        let hidden = read_dir_recursive(dir.path(), dir.path(), DEFAULT_MAX_DEPTH, false);
+        assert_eq!(names(&hidden), ["visible.txt"]);
+
+        let all = read_dir_recursive(dir.path(), dir.path(), DEFAULT_MAX_DEPTH, true);
+        assert_eq!(names(&all), [".config", ".env", "visible.txt"]);
+        assert_eq!(names(all[0].children.as_ref().unwrap()), ["app.toml"]);
+    }
+
+    #[test]
+    fn test_ignore_file_filters_nested_paths() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join(".hibiscusignore"), "build/\n*.log\n").unwrap();
+        std::fs::create_dir_all(dir.path().join("build").join("out")).unwrap();
+        std::fs::create_dir_all(dir.path().join("notes").join("build")).unwrap();
+        File::create(dir.path().join("notes").join("a.md")).unwrap();
+        File::create(dir.path().join("notes").join("run.log")).unwrap();
+
+        let result = read_dir_recursive(dir.path(), dir.path(), DEFAULT_MAX_DEPTH, false);
+        assert_eq!(result.len(), 1);
+        let notes = result[0].children.as_ref().unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].name, "a.md");
+    }
+
+    #[test]
+    fn test_read_dir_shallow_flags_folders_with_children() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("notes").join("deep")).unwrap();
+        std::fs::create_dir_all(dir.path().join("empty")).unwrap();
+        File::create(dir.path().join("empty").join(".hidden")).unwrap();
+        File::create(dir.path().join("todo.md")).unwrap();
+
+        let result = read_dir_shallow(dir.path(), dir.path(), false);
+        assert_eq!(result.len(), 3);
+        assert!(result.iter().all(|node| node.children.is_none()));
+        let has_children = |node: &Node| node.meta.as_ref().and_then(|meta| meta.get("has_children")).cloned();
+        assert_eq!(result[0].name, "empty");
+        assert_eq!(has_children(&result[0]), Some(false.into()));
+        assert_eq!(has_children(&result[1]), Some(true.into()));
+        assert_eq!(has_children(&result[2]), None);
+
+        // Expanding a folder keeps ids relative to the workspace root
+        let notes = read_dir_shallow(&dir.path().join("notes"), dir.path(), false);
+        assert_eq!(notes[0].id, "notes/deep");
+        assert_eq!(has_children(&notes[0]), Some(false.into()));
+    }
+
+    #[test]
+    fn test_parallel_walk_of_wide_tree_is_deterministic() {
+        let dir = tempdir().unwrap();
+        for i in 0..300 {
+            // Mixed case so the case-insensitive sort matters
+            let name = if i % 2 == 0 { format!("Folder{:03}", i) } else { format!("folder{:03}", i) };
+            let folder = dir.path().join(&name);
+            std::fs::create_dir_all(folder.join("inner")).unwrap();
+            File::create(folder.join("inner").join("leaf.md")).unwrap();
+            File::create(folder.join(format!("{}.md", i))).unwrap();
+        }
+        File::create(dir.path().join("readme.md")).unwrap();
+
+        let (first, hits) = read_dir_limited(dir.path(), dir.path(), 2, false);
+        let names: Vec<String> = first.iter().map(|n| n.name.to_lowercase()).collect();
+        let mut expected: Vec<String> = (0..300).map(|i| format!("folder{:03}", i)).collect();
+        expected.push("readme.md".into());
+        assert_eq!(names, expected);
+
+        // Each folder: "inner" (cut by the depth limit) before its file
+        let children = first[7].children.as_ref().unwrap();
+        assert_eq!(children[0].name, "inner");
+        assert!(children[0].children.as_ref().unwrap().is_empty());
+        assert_eq!(children[1].name, "7.md");
+        assert_eq!(hits.len(), 300);
+        assert_eq!(hits[0].path, "Folder000/inner");
+        assert_eq!(hits[299].path, "folder299/inner");
+
+        // Same output on every run despite parallel scheduling
+        for _ in 0..3 {
+            let (again, again_hits) = read_dir_limited(dir.path(), dir.path(), 2, false);
+            assert_eq!(diff_trees(&first, &again), TreeDiff::default());
+            let ids = |nodes: &[Node]| nodes.iter().map(|n| n.id.clone()).collect::<Vec<_>>();
+            assert_eq!(ids(&again), ids(&first));
+            assert_eq!(ids(again[150].children.as_ref().unwrap()), ids(first[150].children.as_ref().unwrap()));
+            assert_eq!(
+                again_hits.iter().map(|h| &h.path).collect::<Vec<_>>(),
+                hits.iter().map(|h| &h.path).collect::<Vec<_>>()
+            );
+        }
+    }
+
     #[test]
     fn test_folders_sorted_before_files() {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("aaa.txt")).unwrap();
         std::fs::create_dir(dir.path().join("zzz_folder")).unwrap();
 
-        let result = read_dir_recursive(dir.path(), dir.path(), DEFAULT_MAX_DEPTH);
+        let result = read_dir_recursive(dir.path(), dir.path(), DEFAULT_MAX_DEPTH, false);
         assert_eq!(result.len(), 2);
         // Folder should come first even though 'z' > 'a'
         assert_eq!(result[0].name, "zzz_folder");
         assert_eq!(result[1].name, "aaa.txt");
     }
+
+    #[test]
+    fn test_sort_modes_keep_folders_first() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("b_folder")).unwrap();
+        fs::create_dir(dir.path().join("a_folder")).unwrap();
+        let epoch = std::time::SystemTime::UNIX_EPOCH;
+        for (name, size, age_days) in [("notes.md", 300, 1), ("archive.md", 20, 3), ("todo.md", 1, 2)] {
+            let file = File::create(dir.path().join(name)).unwrap();
+            file.set_len(size).unwrap();
+            file.set_modified(epoch + std::time::Duration::from_secs(86_400 * (10 - age_days))).unwrap();
+        }
+
+        let names = |sort| {
+            let (nodes, _) = read_dir_with(dir.path(), dir.path(), DEFAULT_MAX_DEPTH, false, TreeOptions { sort, max_nodes: None });
+            nodes.into_iter().map(|node| node.name).collect::<Vec<_>>()
+        };
+        assert_eq!(names(SortMode::NameAsc), ["a_folder", "b_folder", "archive.md", "notes.md", "todo.md"]);
+        assert_eq!(names(SortMode::NameDesc), ["b_folder", "a_folder", "todo.md", "notes.md", "archive.md"]);
+        assert_eq!(names(SortMode::ModifiedDesc)[2..], ["notes.md", "todo.md", "archive.md"]);
+        assert_eq!(names(SortMode::SizeDesc), ["a_folder", "b_folder", "notes.md", "archive.md", "todo.md"]);
+
+        let default_order = read_dir_recursive(dir.path(), dir.path(), DEFAULT_MAX_DEPTH, false);
+        assert_eq!(default_order.into_iter().map(|node| node.name).collect::<Vec<_>>(), names(SortMode::NameAsc));
+    }
+
+    #[test]
+    fn test_apply_decorations_merges_into_meta() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("course")).unwrap();
+        File::create(dir.path().join("course").join("week1.md")).unwrap();
+
+        let mut tree = read_dir_recursive(dir.path(), dir.path(), DEFAULT_MAX_DEPTH, false);
+        let child_id = tree[0].children.as_ref().unwrap()[0].id.clone();
+
+        let mut decorations = BTreeMap::new();
+        decorations.insert(
+            "course".to_string(),
+            NodeDecoration { icon: Some("book".into()), color: Some("#ff0000".into()) },
+        );
+        decorations.insert(
+            child_id,
+            NodeDecoration { icon: None, color: Some("#00ff00".into()) },
+        );
+
+        apply_decorations(&mut tree, &decorations);
+
+        let folder_meta = tree[0].meta.as_ref().unwrap();
+        assert_eq!(folder_meta["icon"], "book");
+        assert_eq!(folder_meta["color"], "#ff0000");
+        let child_meta = tree[0].children.as_ref().unwrap()[0].meta.as_ref().unwrap();
+        assert_eq!(child_meta["color"], "#00ff00");
+        assert!(child_meta.get("icon").is_none());
+    }
+
+    #[test]
+    fn test_diff_trees_by_id() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("notes")).unwrap();
+        File::create(dir.path().join("notes").join("a.md")).unwrap();
+        File::create(dir.path().join("b.md")).unwrap();
+        let old = read_dir_recursive(dir.path(), dir.path(), DEFAULT_MAX_DEPTH, false);
+
+        std::fs::remove_file(dir.path().join("b.md")).unwrap();
+        std::fs::create_dir(dir.path().join("b.md")).unwrap();
+        File::create(dir.path().join("notes").join("c.md")).unwrap();
+        std::fs::remove_file(dir.path().join("notes").join("a.md")).unwrap();
+        let new = read_dir_recursive(dir.path(), dir.path(), DEFAULT_MAX_DEPTH, false);
+
+        let diff = diff_trees(&old, &new);
+        assert_eq!(diff.added, ["notes/c.md"]);
+        assert_eq!(diff.removed, ["notes/a.md"]);
+        assert_eq!(diff.retyped, ["b.md"]);
+        assert_eq!(diff_trees(&new, &new), TreeDiff::default());
+    }
 }
@@ -0,0 +1,102 @@
+//! ============================================================================
+//! Hibiscus Rename Undo
+//! ============================================================================
+//!
+//! Single-step undo for renames. `rename_path` records every rename it makes
+//! and hands the frontend an opaque token; `undo_rename` looks the token up
+//! and moves the item back.
+//!
+//! DESIGN DECISIONS:
+//! - Tokens are random and say nothing about the paths, so the frontend
+//!   keeps no rename state of its own.
+//! - The history is a bounded queue of `MAX_HISTORY` renames; the oldest
+//!   token stops working first.
+//! - An undo only applies while the rename is still the last thing that
+//!   happened to both paths: the renamed item must still be where the rename
+//!   put it, and its old name must still be free. Otherwise the token is
+//!   kept and the undo fails, since the state may become valid again.
+//! - A token is consumed by a successful undo.
+//!
+//! History lives in memory only and does not survive a restart.
+//! ============================================================================
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::{Mutex, PoisonError};
+
+use crate::capabilities::random_token;
+use crate::error::HibiscusError;
+
+/// Number of renames that can still be undone.
+pub const MAX_HISTORY: usize = 50;
+
+/// One recorded rename.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenameRecord {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// Recent renames by undo token, registered as Tauri managed state.
+#[derive(Default)]
+pub struct RenameHistory {
+    /// Oldest first.
+    entries: Mutex<VecDeque<(String, RenameRecord)>>,
+}
+
+impl RenameHistory {
+    /// Records a rename and returns its undo token.
+    pub fn record(&self, from: PathBuf, to: PathBuf) -> Result<String, HibiscusError> {
+        let token = random_token()?;
+        let mut entries = self.lock();
+        entries.push_back((token.clone(), RenameRecord { from, to }));
+        while entries.len() > MAX_HISTORY {
+            entries.pop_front();
+        }
+        Ok(token)
+    }
+
+    /// Returns the rename recorded under `token`.
+    pub fn get(&self, token: &str) -> Result<RenameRecord, HibiscusError> {
+        self.lock()
+            .iter()
+            .find(|(t, _)| t == token)
+            .map(|(_, record)| record.clone())
+            .ok_or_else(|| {
+                HibiscusError::PathValidation("Unknown or expired undo token".into())
+            })
+    }
+
+    /// Forgets `token` after its rename was undone.
+    pub fn remove(&self, token: &str) {
+        self.lock().retain(|(t, _)| t != token);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<(String, RenameRecord)>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_is_bounded() {
+        let history = RenameHistory::default();
+        let tokens: Vec<String> = (0..=MAX_HISTORY)
+            .map(|i| history.record(format!("a{}", i).into(), format!("b{}", i).into()).unwrap())
+            .collect();
+
+        assert!(history.get(&tokens[0]).is_err());
+        let last = history.get(&tokens[MAX_HISTORY]).unwrap();
+        assert_eq!(last.to, PathBuf::from(format!("b{}", MAX_HISTORY)));
+
+        history.remove(&tokens[MAX_HISTORY]);
+        assert!(history.get(&tokens[MAX_HISTORY]).is_err());
+    }
+}
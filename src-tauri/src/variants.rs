@@ -0,0 +1,301 @@
+//! ============================================================================
+//! Hibiscus Note Variants
+//! ============================================================================
+//!
+//! Language variants of one logical note, e.g. `topic.en.md` and
+//! `topic.de.md` for the note `topic.md`.
+//!
+//! RULES:
+//! - A file is a variant if its name matches the variant pattern (default
+//!   `.{lang}.md`) and the language is in the known list. `v1.2.md` is not
+//!   a variant: `2` is not a language.
+//! - The tree groups two or more variants of one note into a single node,
+//!   unless a real file already has the logical name. Each variant keeps
+//!   its own id, listed in the group's `meta.variants`.
+//! - A link to the logical note (`[[topic]]`) resolves to the first of the
+//!   `preferred_languages` that exists, then to the first variant in the
+//!   order of the language list.
+//!
+//! SETTINGS:
+//! - `note_variants`: group variants in the tree (default off)
+//! - `variant_pattern`: file name suffix with a `{lang}` placeholder
+//! - `variant_languages`: known language codes (default `DEFAULT_LANGUAGES`)
+//! - `preferred_languages`: languages links resolve to, most preferred first
+//!
+//! A malformed pattern falls back to the default one.
+//! ============================================================================
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::workspace::{Node, NodeType, WorkspaceSettings};
+
+/// Pattern used when `variant_pattern` is unset or malformed.
+pub const DEFAULT_VARIANT_PATTERN: &str = ".{lang}.md";
+
+/// Language codes recognized when `variant_languages` is unset.
+pub const DEFAULT_LANGUAGES: &[&str] = &[
+    "ar", "cs", "da", "de", "el", "en", "es", "fi", "fr", "he", "hi", "hu", "id", "it", "ja", "ko", "nl",
+    "no", "pl", "pt", "ro", "ru", "sv", "th", "tr", "uk", "vi", "zh",
+];
+
+/// Placeholder for the language in a variant pattern.
+const LANG_PLACEHOLDER: &str = "{lang}";
+
+/// How a workspace names and picks note variants.
+#[derive(Debug, Clone)]
+pub struct VariantConfig {
+    /// Pattern text before the language, e.g. `.`
+    before: String,
+    /// Pattern text after the language, e.g. `.md`
+    after: String,
+    /// Known languages, lowercase
+    languages: Vec<String>,
+    /// Languages links resolve to, most preferred first, lowercase
+    preferred: Vec<String>,
+}
+
+impl Default for VariantConfig {
+    fn default() -> Self {
+        Self::from_settings(&WorkspaceSettings::default())
+    }
+}
+
+impl VariantConfig {
+    /// Reads the variant settings of the workspace at `root`.
+    pub fn load(root: &Path) -> Self {
+        Self::from_settings(&WorkspaceSettings::load(root))
+    }
+
+    pub fn from_settings(settings: &WorkspaceSettings) -> Self {
+        let pattern = settings.variant_pattern.as_deref().unwrap_or(DEFAULT_VARIANT_PATTERN);
+        let (before, after) = split_pattern(pattern).unwrap_or_else(|| {
+            eprintln!("[Hibiscus] Warning: Invalid variant pattern '{}', using the default", pattern);
+            split_pattern(DEFAULT_VARIANT_PATTERN).expect("default pattern is valid")
+        });
+
+        let lowercase = |codes: &[String]| codes.iter().map(|code| code.to_lowercase()).collect::<Vec<_>>();
+        let languages = if settings.variant_languages.is_empty() {
+            DEFAULT_LANGUAGES.iter().map(|code| code.to_string()).collect()
+        } else {
+            lowercase(&settings.variant_languages)
+        };
+
+        Self {
+            before,
+            after,
+            languages,
+            preferred: lowercase(&settings.preferred_languages),
+        }
+    }
+
+    /// Splits a variant's file name into the logical note name and the
+    /// language: `topic.en.md` -> (`topic.md`, `en`). `None` if the name is
+    /// not a variant.
+    pub fn split(&self, file_name: &str) -> Option<(String, String)> {
+        let rest = file_name.strip_suffix(&self.after)?;
+        let (stem, lang) = rest.rsplit_once(&self.before)?;
+        let lang = lang.to_lowercase();
+        if stem.is_empty() || !self.languages.contains(&lang) {
+            return None;
+        }
+        Some((format!("{}{}", stem, self.after), lang))
+    }
+
+    /// The language a link to the logical note resolves to, out of the
+    /// languages it exists in.
+    pub fn choose<'a>(&self, languages: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+        languages.into_iter().min_by_key(|lang| self.rank(lang))
+    }
+
+    /// Sort key: preferred languages first, then the language list order.
+    fn rank(&self, lang: &str) -> (usize, usize, String) {
+        let position = |list: &[String]| list.iter().position(|code| code == lang).unwrap_or(list.len());
+        (position(&self.preferred), position(&self.languages), lang.to_string())
+    }
+}
+
+/// `(before, after)` of a pattern with exactly one `{lang}`, both non-empty
+/// and without path separators.
+fn split_pattern(pattern: &str) -> Option<(String, String)> {
+    let (before, after) = pattern.split_once(LANG_PLACEHOLDER)?;
+    let valid = |part: &str| !part.is_empty() && !part.contains(['/', '\\']) && !part.contains(LANG_PLACEHOLDER);
+    (valid(before) && valid(after)).then(|| (before.to_string(), after.to_string()))
+}
+
+/// Groups the variants of each note in a tree into one node.
+///
+/// The group node has the logical name and id, opens the chosen variant
+/// (`path`, `meta` as that variant's) and adds `meta.language` and
+/// `meta.variants` (language -> `{ id, path }`). It takes the place of the
+/// first variant. Apply after decorations so each variant's are kept.
+pub fn group_variants(nodes: &mut Vec<Node>, config: &VariantConfig) {
+    for node in nodes.iter_mut() {
+        if let Some(children) = node.children.as_mut() {
+            group_variants(children, config);
+        }
+    }
+
+    let names: HashSet<&str> = nodes.iter().map(|node| node.name.as_str()).collect();
+    let mut groups: BTreeMap<String, Vec<(String, usize)>> = BTreeMap::new();
+    for (index, node) in nodes.iter().enumerate() {
+        if !matches!(node.node_type, NodeType::File) {
+            continue;
+        }
+        if let Some((logical, lang)) = config.split(&node.name) {
+            groups.entry(logical).or_default().push((lang, index));
+        }
+    }
+    // A lone variant stays as it is, and a real file keeps its name
+    groups.retain(|logical, members| members.len() > 1 && !names.contains(logical.as_str()));
+    if groups.is_empty() {
+        return;
+    }
+
+    let mut slots: Vec<Option<Node>> = std::mem::take(nodes).into_iter().map(Some).collect();
+    let mut grouped: BTreeMap<usize, Node> = BTreeMap::new();
+    for (logical, members) in groups {
+        let chosen = config
+            .choose(members.iter().map(|(lang, _)| lang.as_str()))
+            .map(str::to_string)
+            .unwrap_or_default();
+
+        let mut variants = serde_json::Map::new();
+        let mut chosen_node = None;
+        for (lang, index) in &members {
+            let Some(node) = slots[*index].take() else { continue };
+            variants.insert(lang.clone(), serde_json::json!({ "id": node.id, "path": node.path }));
+            if *lang == chosen {
+                chosen_node = Some(node);
+            }
+        }
+        let Some(chosen_node) = chosen_node else { continue };
+
+        let id = match chosen_node.id.rsplit_once('/') {
+            Some((folder, _)) => format!("{}/{}", folder, logical),
+            None => logical.clone(),
+        };
+        let mut meta = chosen_node.meta.unwrap_or_else(|| serde_json::Value::Object(Default::default()));
+        if let Some(meta) = meta.as_object_mut() {
+            meta.insert("language".into(), chosen.into());
+            meta.insert("variants".into(), variants.into());
+        }
+
+        grouped.insert(
+            members[0].1,
+            Node {
+                id,
+                name: logical,
+                node_type: NodeType::File,
+                path: chosen_node.path,
+                children: None,
+                meta: Some(meta),
+            },
+        );
+    }
+
+    *nodes = slots
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, slot)| grouped.remove(&index).or(slot))
+        .collect();
+}
+
+/// The variants of the note `file` belongs to, in preference order.
+///
+/// `file` may be a variant (`topic.en.md`) or the logical note
+/// (`topic.md`, which need not exist). Empty if the note has no variants.
+pub fn sibling_variants(file: &Path, config: &VariantConfig) -> Vec<(String, PathBuf)> {
+    let (Some(folder), Some(name)) = (file.parent(), file.file_name()) else {
+        return Vec::new();
+    };
+    let name = name.to_string_lossy();
+    let logical = config.split(&name).map_or_else(|| name.to_string(), |(logical, _)| logical);
+
+    let Ok(entries) = std::fs::read_dir(folder) else {
+        return Vec::new();
+    };
+    let mut variants: Vec<(String, PathBuf)> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| {
+            let (entry_logical, lang) = config.split(&entry.file_name().to_string_lossy())?;
+            (entry_logical == logical).then(|| (lang, entry.path()))
+        })
+        .collect();
+    variants.sort_by_key(|(lang, _)| config.rank(lang));
+    variants
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(id: &str) -> Node {
+        Node {
+            id: id.into(),
+            name: id.rsplit('/').next().unwrap().into(),
+            node_type: NodeType::File,
+            path: Some(id.into()),
+            children: None,
+            meta: None,
+        }
+    }
+
+    #[test]
+    fn test_groups_variants_but_not_dotted_names() {
+        let settings = WorkspaceSettings { preferred_languages: vec!["de".into()], ..Default::default() };
+        let config = VariantConfig::from_settings(&settings);
+        let mut nodes = vec![Node {
+            id: "Notes".into(),
+            name: "Notes".into(),
+            node_type: NodeType::Folder,
+            path: None,
+            children: Some(vec![
+                file("Notes/release.v1.2.md"),
+                file("Notes/release.v1.3.md"),
+                file("Notes/solo.fr.md"),
+                file("Notes/topic.de.md"),
+                file("Notes/topic.en.md"),
+                file("Notes/v1.2.md"),
+            ]),
+            meta: None,
+        }];
+
+        group_variants(&mut nodes, &config);
+
+        let children = nodes[0].children.as_ref().unwrap();
+        let ids: Vec<&str> = children.iter().map(|node| node.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            ["Notes/release.v1.2.md", "Notes/release.v1.3.md", "Notes/solo.fr.md", "Notes/topic.md", "Notes/v1.2.md"]
+        );
+        let topic = &children[3];
+        assert_eq!(topic.path.as_deref(), Some("Notes/topic.de.md"));
+        let meta = topic.meta.as_ref().unwrap();
+        assert_eq!(meta["language"], "de");
+        assert_eq!(meta["variants"]["en"]["id"], "Notes/topic.en.md");
+        assert_eq!(meta["variants"]["de"]["path"], "Notes/topic.de.md");
+
+        assert_eq!(config.split("v1.2.md"), None);
+        assert_eq!(config.split("Topic.EN.md"), Some(("Topic.md".into(), "en".into())));
+    }
+
+    #[test]
+    fn test_invalid_pattern_falls_back_and_custom_languages_apply() {
+        let settings = WorkspaceSettings {
+            variant_pattern: Some("{lang}".into()),
+            variant_languages: vec!["EN".into(), "tlh".into()],
+            ..Default::default()
+        };
+        let config = VariantConfig::from_settings(&settings);
+        assert_eq!(config.split("topic.tlh.md"), Some(("topic.md".into(), "tlh".into())));
+        assert_eq!(config.split("topic.de.md"), None);
+        // No preference: the language list order decides
+        assert_eq!(config.choose(["tlh", "en"]), Some("en"));
+    }
+}
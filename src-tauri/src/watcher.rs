@@ -7,12 +7,32 @@
 //!
 //! FEATURES:
 //! - Graceful shutdown mechanism (stop_watching command)
-//! - Event filtering (ignores .hibiscus folder changes)
+//! - Event filtering (ignores .hibiscus folder changes, extra patterns passed
+//!   to `watch_workspace`, and paths hidden by the workspace's ignore
+//!   patterns, re-read when they change)
 //! - Debounced events to prevent event storms
-//! - Error recovery and logging
+//! - Typed changes: `fs-changed` carries `{ path, kind, from, to }` entries
+//!   (`FsChange`), so renames are told apart from a delete plus a create.
+//!   Changes to one path within the debounce window are merged.
+//! - Error recovery: errors are classified (`WatcherErrorClass`) and sent to
+//!   the frontend with a stable `code` and a recommended action. Transient I/O errors restart
+//!   the watcher, hitting the OS watch limit switches to polling when the
+//!   `watcher_polling_fallback` setting allows it, and permission errors
+//!   stop it.
 //! - Restartable (can switch workspaces)
+//! - Local only: workspaces mounted on a storage that can't be watched
+//!   (`VaultStorage::can_watch`) get an error instead of a silent watcher
 //! - Knowledge indexing integration: forwards Create/Modify/Delete events
 //!   to the knowledge queue for incremental indexing.
+//! - Self-write suppression: paths the app itself just changed (e.g. by a
+//!   rename) are dropped so they don't look like external changes.
+//! - Reference reconciliation: external renames and deletes are applied to
+//!   stored references (`crate::references`), like the file commands do.
+//! - Open documents: external renames of files open in the editor (set with
+//!   `watch_open_files`) also emit `open-file-renamed` with `{ from, to }`.
+//! - Calendar sync: `.hibiscus/calendar.json` is ignored by the main watcher
+//!   but has its own watcher that emits `calendar-changed` with the reloaded
+//!   calendar, so other windows and sync tools' edits show up.
 //!
 //! ARCHITECTURE:
 //! - Uses AtomicBool for thread-safe shutdown signaling
@@ -22,13 +42,21 @@
 //!   an mpsc channel (fire-and-forget, non-blocking send)
 //! ============================================================================
 
+use crate::error::HibiscusError;
+use crate::ignore::{is_builtin_ignored, reload_shared_rules, shared_rules, IgnoreRules, IGNORE_FILE};
 use crate::knowledge::types::{FileEvent, FileEventType};
 use crate::knowledge::queue::KnowledgeState;
+use crate::references::FileChange;
+use crate::storage::storage_for;
+use notify::event::{ModifyKind, RenameMode};
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc::{channel, RecvTimeoutError};
-use std::sync::Arc;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
 use tauri::{Emitter, State};
 
@@ -41,6 +69,15 @@ pub struct WatcherState {
     pub running: Arc<AtomicBool>,
     /// Path currently being watched (for logging)
     pub current_path: std::sync::Mutex<Option<String>>,
+    /// Flag to signal the calendar.json watcher thread to stop
+    pub calendar_running: Arc<AtomicBool>,
+    /// Debounce window of the current watcher, in milliseconds
+    pub debounce_ms: AtomicU64,
+    /// Extra ignore patterns of the current watcher
+    pub ignore_patterns: Mutex<Vec<String>>,
+    /// Threads of the current watcher (main and calendar), joined before
+    /// the next one starts
+    pub threads: Mutex<Vec<JoinHandle<()>>>,
 }
 
 impl Default for WatcherState {
@@ -48,40 +85,395 @@ impl Default for WatcherState {
         Self {
             running: Arc::new(AtomicBool::new(false)),
             current_path: std::sync::Mutex::new(None),
+            calendar_running: Arc::new(AtomicBool::new(false)),
+            debounce_ms: AtomicU64::new(DEBOUNCE_MS),
+            ignore_patterns: Mutex::new(Vec::new()),
+            threads: Mutex::new(Vec::new()),
         }
     }
 }
 
 /// Debounce duration for filesystem events.
 /// Events within this window are coalesced into a single notification.
+/// Default for `watch_workspace`'s `debounce_ms`.
 const DEBOUNCE_MS: u64 = 300;
 
+/// Longest debounce window `watch_workspace` accepts; longer requests are
+/// clamped to it.
+const MAX_DEBOUNCE_MS: u64 = 5000;
+
+/// The debounce window to use for a requested `debounce_ms`.
+fn debounce_window(requested: Option<u64>) -> u64 {
+    match requested {
+        None => DEBOUNCE_MS,
+        Some(ms) if ms > MAX_DEBOUNCE_MS => {
+            eprintln!(
+                "[Hibiscus] Warning: Debounce of {}ms is too long, using {}ms",
+                ms, MAX_DEBOUNCE_MS
+            );
+            MAX_DEBOUNCE_MS
+        }
+        Some(ms) => ms,
+    }
+}
+
 /// Timeout for checking shutdown signal.
 /// Shorter timeouts mean faster shutdown response.
 const RECV_TIMEOUT_MS: u64 = 100;
 
-/// Paths to ignore when processing filesystem events.
-/// These are patterns that should not trigger a refresh.
-const IGNORED_PATHS: &[&str] = &[
-    ".hibiscus",
-    ".git",
-    ".vscode",
-    "node_modules",
-    "__pycache__",
-    ".DS_Store",
-    "Thumbs.db",
-];
-
-/// Checks if a path should be ignored based on the IGNORED_PATHS list.
+/// What the watcher leaves out before the workspace's ignore patterns are
+/// applied: entries named on the built-in list (`BUILTIN_IGNORED`) and
+/// paths matching the extra patterns given to `watch_workspace`.
+///
+/// The extra patterns use the ignore file syntax and are matched against
+/// paths relative to `root`, so `target/` skips every `target` folder and
+/// `/dist` only the one at the root.
+#[derive(Default)]
+pub(crate) struct WatchIgnores {
+    root: PathBuf,
+    extra: IgnoreRules,
+}
+
+impl WatchIgnores {
+    pub(crate) fn new(root: &Path, patterns: &[String]) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            extra: IgnoreRules::compile("", patterns),
+        }
+    }
+}
+
+/// Checks if a path should be ignored by the watcher.
+///
+/// Built-in names are compared with whole path components, so
+/// `my_node_modules_notes.txt` is not mistaken for `node_modules`.
 ///
 /// # Arguments
 /// * `path` - The path to check
+/// * `ignores` - Built-in names and extra patterns of the watcher
 ///
 /// # Returns
-/// `true` if the path contains any ignored pattern
-fn should_ignore_path(path: &PathBuf) -> bool {
-    let path_str = path.to_string_lossy();
-    IGNORED_PATHS.iter().any(|pattern| path_str.contains(pattern))
+/// `true` if a component is on the built-in list or an extra pattern
+/// matches the path or one of its folders
+fn should_ignore_path(path: &Path, ignores: &WatchIgnores) -> bool {
+    let rel = path.strip_prefix(&ignores.root).unwrap_or(path);
+    if rel
+        .components()
+        .any(|component| is_builtin_ignored(&component.as_os_str().to_string_lossy()))
+    {
+        return true;
+    }
+    ignores
+        .extra
+        .is_path_ignored(&rel.to_string_lossy().replace('\\', "/"), path.is_dir())
+}
+
+/// How long a self-written path stays suppressed.
+/// Must comfortably exceed DEBOUNCE_MS so late platform events are covered.
+const SELF_WRITE_TTL_MS: u64 = 2000;
+
+/// Paths recently changed by Hibiscus itself.
+///
+/// Entries expire after SELF_WRITE_TTL_MS rather than on first match, since a
+/// single rename produces several events (remove, create, rename-both).
+/// A suppressed directory also suppresses everything beneath it.
+#[derive(Default)]
+pub struct SelfWriteSuppression {
+    entries: Mutex<HashMap<PathBuf, Instant>>,
+}
+
+impl SelfWriteSuppression {
+    /// Suppresses watcher events for `path` for the next SELF_WRITE_TTL_MS.
+    pub fn suppress(&self, path: &Path) {
+        let expires = Instant::now() + Duration::from_millis(SELF_WRITE_TTL_MS);
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(normalize_event_path(path), expires);
+        }
+    }
+
+    /// Returns whether `path` (or one of its ancestors) is suppressed.
+    pub fn is_suppressed(&self, path: &Path) -> bool {
+        let Ok(mut entries) = self.entries.lock() else {
+            return false;
+        };
+        let now = Instant::now();
+        entries.retain(|_, expires| *expires > now);
+
+        let path = normalize_event_path(path);
+        entries.keys().any(|suppressed| path.starts_with(suppressed))
+    }
+}
+
+/// Process-wide suppression set shared by file commands and the watcher thread.
+pub static SELF_WRITES: LazyLock<SelfWriteSuppression> = LazyLock::new(Default::default);
+
+/// Documents open in the editor (set with `watch_open_files`).
+///
+/// When one is renamed or moved by another program, the watcher emits
+/// `open-file-renamed` so the editor can follow it instead of showing it as
+/// deleted. The set follows the rename, so a second rename is caught too.
+/// Renames done by Hibiscus itself are suppressed before they get here.
+#[derive(Default)]
+pub struct OpenFiles {
+    /// Path as the editor knows it, and its normalized form for matching
+    paths: Mutex<Vec<(String, PathBuf)>>,
+}
+
+/// Payload of the `open-file-renamed` event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OpenFileRenamed {
+    /// The open document's path before the rename
+    pub from: String,
+    /// Its path now
+    pub to: String,
+}
+
+impl OpenFiles {
+    /// Replaces the set of open documents.
+    pub fn set(&self, paths: Vec<String>) {
+        if let Ok(mut open) = self.paths.lock() {
+            *open = paths
+                .into_iter()
+                .map(|path| {
+                    let normalized = normalize_event_path(Path::new(&path));
+                    (path, normalized)
+                })
+                .collect();
+        }
+    }
+
+    /// The open documents moved by the complete renames in `changes`, the
+    /// document itself or a folder above it. The set is updated to the new
+    /// paths.
+    pub(crate) fn follow_renames(&self, changes: &[FsChange]) -> Vec<OpenFileRenamed> {
+        let Ok(mut open) = self.paths.lock() else {
+            return Vec::new();
+        };
+
+        let mut renamed = Vec::new();
+        for change in changes.iter().filter(|change| change.kind == ChangeKind::Renamed) {
+            let (Some(from), Some(to)) = (&change.from, &change.to) else {
+                continue;
+            };
+            let from = normalize_event_path(Path::new(from));
+            for (path, normalized) in open.iter_mut() {
+                let Ok(rest) = normalized.strip_prefix(&from) else {
+                    continue;
+                };
+                let new_path = if rest.as_os_str().is_empty() { PathBuf::from(to) } else { Path::new(to).join(rest) };
+                renamed.push(OpenFileRenamed {
+                    from: std::mem::replace(path, new_path.to_string_lossy().to_string()),
+                    to: new_path.to_string_lossy().to_string(),
+                });
+                *normalized = normalize_event_path(&new_path);
+            }
+        }
+        renamed
+    }
+}
+
+/// Open documents shared by `watch_open_files` and the watcher thread.
+pub static OPEN_FILES: LazyLock<OpenFiles> = LazyLock::new(Default::default);
+
+/// Resolves symlinked ancestors (e.g. `/var` -> `/private/var` on macOS) so
+/// command paths and watcher paths compare equal. Only existing ancestors are
+/// canonicalized; the rest (such as the old side of a rename) is kept as-is.
+fn normalize_event_path(path: &Path) -> PathBuf {
+    for ancestor in path.ancestors().skip(1) {
+        if let (Ok(canonical), Ok(rest)) = (ancestor.canonicalize(), path.strip_prefix(ancestor)) {
+            return canonical.join(rest);
+        }
+    }
+    path.to_path_buf()
+}
+
+/// Returns the paths of `event` that should be reported to the frontend.
+///
+/// Drops access/noise events, ignored paths and self-written paths.
+pub(crate) fn relevant_event_paths(
+    event: &Event,
+    suppression: &SelfWriteSuppression,
+    ignores: &WatchIgnores,
+) -> Vec<PathBuf> {
+    match event.kind {
+        EventKind::Access(_) | EventKind::Other => return Vec::new(),
+        _ => {}
+    }
+    event
+        .paths
+        .iter()
+        .filter(|p| !should_ignore_path(p, ignores) && !suppression.is_suppressed(p))
+        .cloned()
+        .collect()
+}
+
+/// Drops the paths under `root` that the workspace's ignore patterns hide
+/// from the tree.
+///
+/// Takes the rules as they are now, so patterns applied while the watcher
+/// runs take effect on the next event without a restart.
+pub(crate) fn drop_pattern_ignored(paths: Vec<PathBuf>, root: &Path, rules: &IgnoreRules) -> Vec<PathBuf> {
+    paths
+        .into_iter()
+        .filter(|path| match path.strip_prefix(root) {
+            Ok(rel) => !rules.is_path_ignored(&rel.to_string_lossy().replace('\\', "/"), path.is_dir()),
+            Err(_) => true,
+        })
+        .collect()
+}
+
+/// What happened to a path, as reported in `fs-changed`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Created,
+    Modified,
+    Removed,
+    Renamed,
+}
+
+impl ChangeKind {
+    /// The net effect of `self` followed by `next` on the same path.
+    fn then(self, next: ChangeKind) -> ChangeKind {
+        match (self, next) {
+            // Still new (or newly named) to the frontend
+            (ChangeKind::Created | ChangeKind::Renamed, ChangeKind::Modified) => self,
+            // Replaced by a save that deletes and recreates the file
+            (ChangeKind::Removed, ChangeKind::Created | ChangeKind::Modified) => ChangeKind::Modified,
+            _ => next,
+        }
+    }
+}
+
+/// One entry of the `fs-changed` payload.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FsChange {
+    /// The changed path; for renames, the new path when it is known
+    pub path: String,
+    pub kind: ChangeKind,
+    /// Old path of a rename, when the platform reports it
+    pub from: Option<String>,
+    /// New path of a rename, when the platform reports it
+    pub to: Option<String>,
+}
+
+impl FsChange {
+    fn new(path: &Path, kind: ChangeKind) -> Self {
+        Self {
+            path: path.to_string_lossy().to_string(),
+            kind,
+            from: None,
+            to: None,
+        }
+    }
+}
+
+/// Types the `kept` paths of `event` (those that passed the filters).
+///
+/// A rename reported with both sides becomes one `Renamed` change with
+/// `from` and `to`. If the filters dropped one side, it is a create (moved
+/// in) or a remove (moved out) as far as the workspace is concerned.
+pub(crate) fn typed_changes(event: &Event, kept: &[PathBuf]) -> Vec<FsChange> {
+    let each = |kind| kept.iter().map(|path| FsChange::new(path, kind)).collect();
+    match event.kind {
+        EventKind::Create(_) => each(ChangeKind::Created),
+        EventKind::Remove(_) => each(ChangeKind::Removed),
+        EventKind::Modify(ModifyKind::Name(mode)) => match (mode, event.paths.as_slice()) {
+            (RenameMode::Both, [from, to]) => match (kept.contains(from), kept.contains(to)) {
+                (true, true) => vec![FsChange {
+                    from: Some(from.to_string_lossy().to_string()),
+                    to: Some(to.to_string_lossy().to_string()),
+                    ..FsChange::new(to, ChangeKind::Renamed)
+                }],
+                (false, true) => vec![FsChange::new(to, ChangeKind::Created)],
+                (true, false) => vec![FsChange::new(from, ChangeKind::Removed)],
+                (false, false) => Vec::new(),
+            },
+            (RenameMode::From, _) => kept
+                .iter()
+                .map(|path| FsChange {
+                    from: Some(path.to_string_lossy().to_string()),
+                    ..FsChange::new(path, ChangeKind::Renamed)
+                })
+                .collect(),
+            (RenameMode::To, _) => kept
+                .iter()
+                .map(|path| FsChange {
+                    to: Some(path.to_string_lossy().to_string()),
+                    ..FsChange::new(path, ChangeKind::Renamed)
+                })
+                .collect(),
+            // Platforms that report each side without saying which
+            _ => each(ChangeKind::Renamed),
+        },
+        _ => each(ChangeKind::Modified),
+    }
+}
+
+/// The changes collected during one debounce window, merged per path in
+/// the order they first appeared.
+#[derive(Debug, Default)]
+pub(crate) struct ChangeBatch {
+    changes: Vec<FsChange>,
+}
+
+impl ChangeBatch {
+    pub(crate) fn push(&mut self, change: FsChange) {
+        // A complete rename replaces the half reported for its old path
+        if let (ChangeKind::Renamed, Some(from)) = (change.kind, &change.from) {
+            if change.to.is_some() {
+                self.changes
+                    .retain(|c| !(c.kind == ChangeKind::Renamed && c.path == *from && c.to.is_none()));
+            }
+        }
+
+        match self.changes.iter_mut().find(|c| c.path == change.path) {
+            Some(existing) => {
+                existing.kind = existing.kind.then(change.kind);
+                existing.from = change.from.or(existing.from.take());
+                existing.to = change.to.or(existing.to.take());
+            }
+            None => self.changes.push(change),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub(crate) fn drain(&mut self) -> Vec<FsChange> {
+        std::mem::take(&mut self.changes)
+    }
+}
+
+/// Every path a batch touches, old sides of renames included.
+fn touched_paths(changes: &[FsChange]) -> Vec<String> {
+    let mut paths: Vec<String> = Vec::new();
+    for change in changes {
+        for path in std::iter::once(&change.path).chain(&change.from) {
+            if !paths.contains(path) {
+                paths.push(path.clone());
+            }
+        }
+    }
+    paths
+}
+
+/// The renames and removals in `changes`, as reference reconciliation takes
+/// them. App-initiated changes never get here: `SELF_WRITES` drops them,
+/// and the file commands reconcile those themselves.
+pub(crate) fn reference_changes(changes: &[FsChange]) -> Vec<FileChange> {
+    changes
+        .iter()
+        .filter_map(|change| match (change.kind, &change.from) {
+            (ChangeKind::Renamed, Some(from)) => Some(FileChange::Renamed {
+                from: from.clone(),
+                to: change.path.clone(),
+            }),
+            (ChangeKind::Removed, _) => Some(FileChange::Deleted { path: change.path.clone() }),
+            _ => None,
+        })
+        .collect()
 }
 
 /// Starts watching a workspace directory for filesystem changes.
@@ -91,24 +483,41 @@ fn should_ignore_path(path: &PathBuf) -> bool {
 ///
 /// # Arguments
 /// * `path` - The directory path to watch
+/// * `debounce_ms` - Window in which events are coalesced, 0 to 5000
+///   (default 300); longer values are clamped. Raise it for network drives
+///   that produce event storms.
+/// * `ignore_patterns` - Extra patterns to leave out, in the ignore file
+///   syntax (e.g. `target/`, `dist/`, `*.tmp`), on top of the built-in
+///   names and the workspace's ignore patterns
 /// * `window` - Tauri window handle for emitting events
 /// * `state` - Managed state for controlling the watcher
 ///
 /// # Events Emitted
 /// * `fs-changed` - Emitted when relevant filesystem changes occur
-///   Payload: Array of changed file paths
+///   Payload: Array of `FsChange` (`{ path, kind, from, to }`)
+/// * `fs-watcher-error` - Emitted when the watcher fails
+///   Payload: `WatcherErrorPayload` (class, message, paths, action, recovery)
+///
+/// # Returns
+/// * `Ok(())` - The watcher was started
+/// * `Err(HibiscusError::Watcher)` - If the workspace's storage cannot be
+///   watched (see `crate::storage`); the old watcher is still stopped
 ///
 /// # Notes
-/// - Calling this while a watcher is running will stop the old watcher first
+/// - Calling this while a watcher is running stops the old watcher first
+///   and waits for its threads to exit
 /// - The watcher filters out changes to .hibiscus and other ignored paths
 /// - Events are debounced to prevent excessive updates
-#[tauri::command]
+/// - Runs off the main thread, since stopping the old watcher waits for it
+#[tauri::command(async)]
 pub fn watch_workspace(
     path: String,
+    debounce_ms: Option<u64>,
+    ignore_patterns: Option<Vec<String>>,
     window: tauri::Window,
     state: State<WatcherState>,
     knowledge_state: State<Arc<KnowledgeState>>,
-) {
+) -> Result<(), HibiscusError> {
     // Clone the knowledge sender so the watcher thread can forward events.
     // This is a lightweight clone (Arc under the hood).
     let knowledge_sender = knowledge_state.sender.clone();
@@ -129,17 +538,40 @@ pub fn watch_workspace(
             }
         });
     }
-    // Stop any existing watcher
-    state.running.store(false, Ordering::SeqCst);
+    // Stop any existing watchers, and wait until they have exited so two
+    // never run at once
+    stop_and_join(&state);
 
-    // Small delay to let the old watcher thread notice the shutdown
-    std::thread::sleep(Duration::from_millis(RECV_TIMEOUT_MS * 2));
+    // Only the local filesystem reports changes for now
+    check_watchable(Path::new(&path))?;
+
+    // Dedicated calendar.json watcher, tracked by its own flag
+    state.calendar_running.store(true, Ordering::SeqCst);
+    let calendar_window = window.clone();
+    let calendar_thread = watch_calendar(
+        PathBuf::from(&path),
+        state.calendar_running.clone(),
+        move |calendar| {
+            if let Err(e) = calendar_window.emit("calendar-changed", calendar) {
+                eprintln!("[Hibiscus] Error emitting calendar change: {}", e);
+            }
+        },
+    );
 
     // Update current path for logging
     if let Ok(mut current) = state.current_path.lock() {
         *current = Some(path.clone());
     }
 
+    let debounce_ms = debounce_window(debounce_ms);
+    state.debounce_ms.store(debounce_ms, Ordering::SeqCst);
+    let debounce = Duration::from_millis(debounce_ms);
+
+    let ignore_patterns = ignore_patterns.unwrap_or_default();
+    if let Ok(mut patterns) = state.ignore_patterns.lock() {
+        *patterns = ignore_patterns.clone();
+    }
+
     // Set running flag for new watcher
     let running = state.running.clone();
     running.store(true, Ordering::SeqCst);
@@ -148,109 +580,503 @@ pub fn watch_workspace(
     let knowledge_tx = knowledge_sender;
 
     // Spawn watcher thread
-    std::thread::spawn(move || {
+    let watcher_thread = std::thread::spawn(move || {
         println!("[Hibiscus] Starting file watcher for: {}", watch_path);
 
-        // Create channel for receiving filesystem events
-        let (tx, rx) = channel();
+        let ignores = WatchIgnores::new(Path::new(&watch_path), &ignore_patterns);
+        let mut supervisor = Supervisor {
+            polling_allowed: crate::workspace::WorkspaceSettings::load(Path::new(&watch_path))
+                .watcher_polling_fallback,
+            ..Default::default()
+        };
+
+        // Recreate the watcher until it is stopped or an error is unrecoverable
+        loop {
+            match run_watcher(&watch_path, debounce, &ignores, &supervisor, &running, &window, &knowledge_tx) {
+                None => break,
+                Some(WatcherRecovery::Restart) => {
+                    supervisor.restarts += 1;
+                    let delay = RESTART_DELAY_MS << (supervisor.restarts - 1);
+                    if !sleep_while_running(Duration::from_millis(delay), &running) {
+                        break;
+                    }
+                    println!("[Hibiscus] Restarting file watcher (attempt {})", supervisor.restarts);
+                }
+                Some(WatcherRecovery::SwitchToPolling) => {
+                    supervisor.polling = true;
+                    println!("[Hibiscus] Switching file watcher to polling");
+                }
+                Some(WatcherRecovery::GiveUp) | Some(WatcherRecovery::Continue) => {
+                    running.store(false, Ordering::SeqCst);
+                    break;
+                }
+            }
+        }
+
+        println!("[Hibiscus] File watcher stopped for: {}", watch_path);
+    });
+
+    if let Ok(mut threads) = state.threads.lock() {
+        threads.extend([calendar_thread, watcher_thread]);
+    }
+    Ok(())
+}
+
+/// Signals the current watcher threads to stop and waits until they have
+/// exited. They wait at most `RECV_TIMEOUT_MS` between checks of their
+/// flag, so this returns within about that long.
+fn stop_and_join(state: &WatcherState) {
+    state.running.store(false, Ordering::SeqCst);
+    state.calendar_running.store(false, Ordering::SeqCst);
+
+    let threads = match state.threads.lock() {
+        Ok(mut threads) => std::mem::take(&mut *threads),
+        Err(_) => return,
+    };
+    for thread in threads {
+        if thread.join().is_err() {
+            eprintln!("[Hibiscus] Warning: A file watcher thread panicked");
+        }
+    }
+}
+
+/// Sleeps for `duration`, waking up early once `running` is cleared.
+/// Returns whether the watcher is still running.
+fn sleep_while_running(duration: Duration, running: &AtomicBool) -> bool {
+    let deadline = Instant::now() + duration;
+    while running.load(Ordering::SeqCst) {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return true;
+        }
+        std::thread::sleep(left.min(Duration::from_millis(RECV_TIMEOUT_MS)));
+    }
+    false
+}
+
+/// Fails if the storage holding `path` cannot report changes.
+fn check_watchable(path: &Path) -> Result<(), HibiscusError> {
+    if storage_for(path).can_watch() {
+        return Ok(());
+    }
+    Err(HibiscusError::Watcher(format!(
+        "Watching unavailable for this storage: '{}'",
+        path.display()
+    )))
+}
+
+/// Creates a watcher on `watch_path` and forwards its events until it is
+/// stopped (`None`) or fails in a way that needs the watcher recreated
+/// (the recovery to apply). Errors are reported to the frontend here.
+fn run_watcher(
+    watch_path: &str,
+    debounce: Duration,
+    ignores: &WatchIgnores,
+    supervisor: &Supervisor,
+    running: &AtomicBool,
+    window: &tauri::Window,
+    knowledge_tx: &tokio::sync::mpsc::UnboundedSender<FileEvent>,
+) -> Option<WatcherRecovery> {
+    let root = Path::new(watch_path);
+
+    // Create channel for receiving filesystem events
+    let (tx, rx) = channel::<notify::Result<Event>>();
+
+    // Create the watcher and start watching the path
+    let watcher = create_watcher(tx, supervisor.polling).and_then(|mut watcher| {
+        watcher.watch(watch_path.as_ref(), RecursiveMode::Recursive)?;
+        Ok(watcher)
+    });
+    let watcher = match watcher {
+        Ok(w) => w,
+        Err(e) => {
+            eprintln!("[Hibiscus] Error: Failed to watch path '{}': {}", watch_path, e);
+            return Some(report_watcher_error(window, &e, supervisor, false));
+        }
+    };
+
+    println!("[Hibiscus] File watcher started successfully");
+
+    let recovery = pump_events(
+        &rx,
+        root,
+        debounce,
+        ignores,
+        running,
+        |e| report_watcher_error(window, e, supervisor, true),
+        |changes| {
+            let paths = touched_paths(&changes);
+            if let Err(e) = window.emit("fs-changed", &changes) {
+                eprintln!("[Hibiscus] Error emitting event: {}", e);
+            }
+            // Let the editor follow open documents renamed elsewhere
+            for renamed in OPEN_FILES.follow_renames(&changes) {
+                if let Err(e) = window.emit("open-file-renamed", &renamed) {
+                    eprintln!("[Hibiscus] Error emitting event: {}", e);
+                }
+            }
+            // Refresh tree badges of the affected nodes
+            crate::badges::queue_badge_updates(window.clone(), PathBuf::from(watch_path), paths.clone());
+            // Keep stored references (tabs, favorites, ...) following
+            // files renamed or deleted outside the app
+            let moved = reference_changes(&changes);
+            if !moved.is_empty() {
+                crate::commands::reconcile_in_background(PathBuf::from(watch_path), moved);
+            }
+            // Forward events to the knowledge indexing queue.
+            // We classify all debounced events as Modify since
+            // the debounce window may have coalesced Create+Modify.
+            // The knowledge pipeline handles this correctly: it
+            // uses hash-based change detection regardless of
+            // event type for Create/Modify.
+            for p in &paths {
+                let _ = knowledge_tx.send(FileEvent {
+                    path: p.clone(),
+                    event_type: FileEventType::Modify,
+                });
+            }
+        },
+    );
+
+    // Cleanup
+    drop(watcher);
+    recovery
+}
+
+/// Filters, types and batches the events from `rx`, handing each batch to
+/// `flush` once no event arrived for `debounce`, until `running` is cleared
+/// (`None`) or `on_error` returns a recovery other than `Continue`.
+///
+/// Waits at most `RECV_TIMEOUT_MS` at a time, also while a batch is
+/// pending, so a stop is noticed promptly whatever the debounce window.
+fn pump_events(
+    rx: &Receiver<notify::Result<Event>>,
+    root: &Path,
+    debounce: Duration,
+    ignores: &WatchIgnores,
+    running: &AtomicBool,
+    mut on_error: impl FnMut(&notify::Error) -> WatcherRecovery,
+    mut flush: impl FnMut(Vec<FsChange>),
+) -> Option<WatcherRecovery> {
+    // Accumulator for debouncing events
+    let mut accumulated = ChangeBatch::default();
+    let mut last_event_time = Option::<Instant>::None;
+
+    // Main event loop
+    while running.load(Ordering::SeqCst) {
+        // Wait for the next event, or until the batch is due
+        let timeout = match last_event_time {
+            Some(time) if !accumulated.is_empty() => debounce.saturating_sub(time.elapsed()),
+            _ => Duration::from_millis(RECV_TIMEOUT_MS),
+        };
+
+        match rx.recv_timeout(timeout.min(Duration::from_millis(RECV_TIMEOUT_MS))) {
+            Ok(Ok(event)) => {
+                // An edited ignore file changes what is filtered from now on
+                if event.paths.iter().any(|p| p.file_name() == Some(IGNORE_FILE.as_ref())) {
+                    reload_shared_rules(root);
+                }
+
+                // Filter, type and accumulate events
+                let paths = relevant_event_paths(&event, &SELF_WRITES, ignores);
+                let kept = drop_pattern_ignored(paths, root, &shared_rules(root));
+                for change in typed_changes(&event, &kept) {
+                    accumulated.push(change);
+                }
+                if !accumulated.is_empty() {
+                    last_event_time = Some(Instant::now());
+                }
+            }
+            Ok(Err(e)) => {
+                eprintln!("[Hibiscus] Warning: Watcher error: {}", e);
+                match on_error(&e) {
+                    WatcherRecovery::Continue => {}
+                    recovery => return Some(recovery),
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                // Flush the accumulated events once the window has passed
+                if last_event_time.is_some_and(|time| time.elapsed() >= debounce) && !accumulated.is_empty() {
+                    flush(accumulated.drain());
+                    last_event_time = None;
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                eprintln!("[Hibiscus] Warning: Watcher channel disconnected");
+                return None;
+            }
+        }
+    }
+
+    None
+}
+
+/// Creates the platform watcher, or a polling one when `polling` is set.
+fn create_watcher(
+    tx: std::sync::mpsc::Sender<notify::Result<Event>>,
+    polling: bool,
+) -> notify::Result<Box<dyn Watcher + Send>> {
+    if polling {
+        let config = notify::Config::default().with_poll_interval(Duration::from_millis(POLL_INTERVAL_MS));
+        Ok(Box::new(notify::PollWatcher::new(tx, config)?))
+    } else {
+        Ok(Box::new(notify::recommended_watcher(tx)?))
+    }
+}
+
+/// Classifies `error`, decides the recovery and emits `fs-watcher-error`.
+fn report_watcher_error(
+    window: &tauri::Window,
+    error: &notify::Error,
+    supervisor: &Supervisor,
+    running: bool,
+) -> WatcherRecovery {
+    let payload = error_payload(error, supervisor, running);
+    if let Err(e) = window.emit("fs-watcher-error", &payload) {
+        eprintln!("[Hibiscus] Error emitting watcher error: {}", e);
+    }
+    payload.recovery
+}
+
+/// Classifies `error` and decides the recovery, as reported in
+/// `fs-watcher-error`.
+fn error_payload(error: &notify::Error, supervisor: &Supervisor, running: bool) -> WatcherErrorPayload {
+    let class = WatcherErrorClass::classify(error);
+    let paths: Vec<String> = error.paths.iter().map(|p| p.to_string_lossy().to_string()).collect();
+    WatcherErrorPayload {
+        code: class.code(),
+        class,
+        message: error.to_string(),
+        path: paths.first().cloned(),
+        paths,
+        action: class.action().to_string(),
+        recovery: supervisor.decide(class, running),
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Error classification and recovery
+// -----------------------------------------------------------------------------
+
+/// Restarts attempted after transient errors before giving up.
+const MAX_WATCHER_RESTARTS: u32 = 3;
+
+/// Delay before the first restart; doubles with each further attempt.
+const RESTART_DELAY_MS: u64 = 500;
+
+/// How often the polling fallback rescans the workspace.
+const POLL_INTERVAL_MS: u64 = 2000;
+
+/// Kind of watcher failure, sent to the frontend in `fs-watcher-error`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatcherErrorClass {
+    /// The process ran out of file descriptors or watcher instances
+    /// (EMFILE/ENFILE)
+    WatchLimitReached,
+    /// The watched folder disappeared
+    PathNotFound,
+    PermissionDenied,
+    /// Any other I/O error, usually transient
+    Io,
+    /// The system limit on watched folders is reached (inotify's
+    /// `max_user_watches`, reported as ENOSPC)
+    MaxFilesWatch,
+    Generic,
+}
+
+/// What the watcher does after an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatcherRecovery {
+    /// Keep watching; only part of the workspace is affected
+    Continue,
+    /// Recreate the watcher after a short delay
+    Restart,
+    /// Recreate it as a polling watcher, which has no OS watch limits
+    SwitchToPolling,
+    /// Stop watching; changes made outside Hibiscus need a manual refresh
+    GiveUp,
+}
+
+/// Payload of the `fs-watcher-error` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct WatcherErrorPayload {
+    /// Stable code to branch on (see `WatcherErrorClass::code`)
+    pub code: &'static str,
+    pub class: WatcherErrorClass,
+    pub message: String,
+    /// First path the error is about, if known
+    pub path: Option<String>,
+    /// Paths the error is about, if known
+    pub paths: Vec<String>,
+    /// What the user can do about it
+    pub action: String,
+    /// What the watcher did about it
+    pub recovery: WatcherRecovery,
+}
+
+#[cfg(unix)]
+const ENFILE: i32 = 23;
+#[cfg(unix)]
+const EMFILE: i32 = 24;
+#[cfg(unix)]
+const ENOSPC: i32 = 28;
+
+impl WatcherErrorClass {
+    /// Maps a `notify` error to its class.
+    pub fn classify(error: &notify::Error) -> Self {
+        match &error.kind {
+            notify::ErrorKind::MaxFilesWatch => Self::MaxFilesWatch,
+            notify::ErrorKind::PathNotFound => Self::PathNotFound,
+            notify::ErrorKind::Io(io) => {
+                #[cfg(unix)]
+                match io.raw_os_error() {
+                    Some(ENOSPC) => return Self::MaxFilesWatch,
+                    Some(EMFILE) | Some(ENFILE) => return Self::WatchLimitReached,
+                    _ => {}
+                }
+                match io.kind() {
+                    std::io::ErrorKind::NotFound => Self::PathNotFound,
+                    std::io::ErrorKind::PermissionDenied => Self::PermissionDenied,
+                    _ => Self::Io,
+                }
+            }
+            notify::ErrorKind::Generic(_)
+            | notify::ErrorKind::WatchNotFound
+            | notify::ErrorKind::InvalidConfig(_) => Self::Generic,
+        }
+    }
+
+    /// Error code of `fs-watcher-error`. Failures without a more specific
+    /// code are `watch_failed`.
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::WatchLimitReached => "watch_limit_reached",
+            Self::MaxFilesWatch => "max_files_watch",
+            Self::PathNotFound => "path_not_found",
+            Self::PermissionDenied => "permission_denied",
+            Self::Io | Self::Generic => "watch_failed",
+        }
+    }
+
+    /// Recommended action shown to the user.
+    pub fn action(self) -> &'static str {
+        match self {
+            Self::WatchLimitReached => {
+                "Too many files are open. Close other apps that watch files, or enable the polling fallback in workspace settings."
+            }
+            Self::MaxFilesWatch => {
+                "The system limit on watched folders is reached. Raise fs.inotify.max_user_watches, or enable the polling fallback in workspace settings."
+            }
+            Self::PathNotFound => "The workspace folder was moved or deleted. Reopen it from its new location.",
+            Self::PermissionDenied => "Hibiscus cannot read this folder. Check its permissions, then reopen the workspace.",
+            Self::Io => "A temporary file system error occurred. The watcher restarts automatically.",
+            Self::Generic => "Changes made outside Hibiscus may not show up until you refresh the file tree.",
+        }
+    }
+}
+
+/// State the recovery decisions depend on.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Supervisor {
+    /// `watcher_polling_fallback` workspace setting
+    pub polling_allowed: bool,
+    /// Whether the current watcher is already polling
+    pub polling: bool,
+    /// Restarts so far
+    pub restarts: u32,
+}
 
-        // Create the watcher
+impl Supervisor {
+    /// Decides how to recover from an error of `class`. `running` is whether
+    /// the watcher was already delivering events, rather than failing to start.
+    pub fn decide(&self, class: WatcherErrorClass, running: bool) -> WatcherRecovery {
+        use WatcherErrorClass::*;
+        match class {
+            WatchLimitReached | MaxFilesWatch if self.polling_allowed && !self.polling => {
+                WatcherRecovery::SwitchToPolling
+            }
+            // Folders watched so far keep working, only new ones are missed
+            WatchLimitReached | MaxFilesWatch | PathNotFound | PermissionDenied if running => {
+                WatcherRecovery::Continue
+            }
+            WatchLimitReached | MaxFilesWatch | PathNotFound | PermissionDenied => WatcherRecovery::GiveUp,
+            Io | Generic if self.restarts < MAX_WATCHER_RESTARTS => WatcherRecovery::Restart,
+            Io | Generic => WatcherRecovery::GiveUp,
+        }
+    }
+}
+
+/// Watches `<root>/.hibiscus/calendar.json` and calls `on_change` with the
+/// reloaded (migrated) calendar after each debounced change, until
+/// `running` is cleared.
+///
+/// The folder is watched rather than the file, because saves replace the
+/// file by rename. Content that does not parse (e.g. caught mid-write by a
+/// sync tool) is skipped; the write that completes it triggers again.
+/// Hibiscus' own saves are reported too, so every window sees them.
+/// Returns the watching thread, to join once `running` is cleared.
+pub fn watch_calendar<F>(root: PathBuf, running: Arc<AtomicBool>, on_change: F) -> JoinHandle<()>
+where
+    F: Fn(serde_json::Value) + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let data_dir = root.join(".hibiscus");
+        let calendar_path = data_dir.join("calendar.json");
+
+        let (tx, rx) = channel();
         let mut watcher: RecommendedWatcher = match notify::recommended_watcher(tx) {
             Ok(w) => w,
             Err(e) => {
-                eprintln!("[Hibiscus] Error: Failed to create file watcher: {}", e);
-                running.store(false, Ordering::SeqCst);
-                // Emit error event to frontend
-                let _ = window.emit("fs-watcher-error", e.to_string());
+                eprintln!("[Hibiscus] Error: Failed to create calendar watcher: {}", e);
                 return;
             }
         };
-
-        // Start watching the path
-        if let Err(e) = watcher.watch(watch_path.as_ref(), RecursiveMode::Recursive) {
-            eprintln!("[Hibiscus] Error: Failed to watch path '{}': {}", watch_path, e);
-            running.store(false, Ordering::SeqCst);
-            let _ = window.emit("fs-watcher-error", e.to_string());
+        if let Err(e) = watcher.watch(&data_dir, RecursiveMode::NonRecursive) {
+            // No .hibiscus folder yet, so no calendar to keep in sync
+            eprintln!("[Hibiscus] Warning: Not watching calendar in '{}': {}", data_dir.display(), e);
             return;
         }
 
-        println!("[Hibiscus] File watcher started successfully");
-
-        // Accumulator for debouncing events
-        let mut accumulated_paths = std::collections::HashSet::new();
         let mut last_event_time = Option::<Instant>::None;
-
-        // Main event loop
         while running.load(Ordering::SeqCst) {
-            // Determine timeout based on accumulation state
-            let timeout = if accumulated_paths.is_empty() {
-                Duration::from_millis(RECV_TIMEOUT_MS)
-            } else {
-                let elapsed = last_event_time.unwrap_or_else(Instant::now).elapsed();
-                let debounce = Duration::from_millis(DEBOUNCE_MS);
-                if elapsed >= debounce {
-                    Duration::from_millis(0)
-                } else {
-                    debounce - elapsed
-                }
-            };
-
-            match rx.recv_timeout(timeout) {
+            match rx.recv_timeout(Duration::from_millis(RECV_TIMEOUT_MS)) {
                 Ok(Ok(event)) => {
-                    // Filter and accumulate events
-                    match event.kind {
-                        EventKind::Access(_) | EventKind::Other => continue,
-                        _ => {}
-                    }
-                    for path in event.paths {
-                        if !should_ignore_path(&path) {
-                            accumulated_paths.insert(path.to_string_lossy().to_string());
-                        }
-                    }
-                    if !accumulated_paths.is_empty() {
+                    let touches_calendar = !matches!(event.kind, EventKind::Access(_))
+                        && event
+                            .paths
+                            .iter()
+                            .any(|p| p.file_name().is_some_and(|name| name == "calendar.json"));
+                    if touches_calendar {
                         last_event_time = Some(Instant::now());
                     }
                 }
                 Ok(Err(e)) => {
-                    eprintln!("[Hibiscus] Warning: Watcher error: {}", e);
-                }
-                Err(RecvTimeoutError::Timeout) => {
-                    // Check if we need to flush accumulated events
-                    if !accumulated_paths.is_empty() {
-                        if let Some(time) = last_event_time {
-                            if time.elapsed() >= Duration::from_millis(DEBOUNCE_MS) {
-                                let paths: Vec<String> = accumulated_paths.drain().collect();
-                                if let Err(e) = window.emit("fs-changed", &paths) {
-                                    eprintln!("[Hibiscus] Error emitting event: {}", e);
-                                }
-                                // Forward events to the knowledge indexing queue.
-                                // We classify all debounced events as Modify since
-                                // the debounce window may have coalesced Create+Modify.
-                                // The knowledge pipeline handles this correctly: it
-                                // uses hash-based change detection regardless of
-                                // event type for Create/Modify.
-                                for p in &paths {
-                                    let _ = knowledge_tx.send(FileEvent {
-                                        path: p.clone(),
-                                        event_type: FileEventType::Modify,
-                                    });
-                                }
-                                last_event_time = None;
-                            }
-                        }
-                    }
+                    eprintln!("[Hibiscus] Warning: Calendar watcher error: {}", e);
                 }
-                Err(RecvTimeoutError::Disconnected) => {
-                    eprintln!("[Hibiscus] Warning: Watcher channel disconnected");
-                    break;
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+
+            if last_event_time.is_some_and(|time| time.elapsed() >= Duration::from_millis(DEBOUNCE_MS)) {
+                last_event_time = None;
+                if let Some(calendar) = load_calendar(&calendar_path) {
+                    on_change(calendar);
                 }
             }
         }
 
-        // Cleanup
-        println!("[Hibiscus] File watcher stopped for: {}", watch_path);
         drop(watcher);
-    });
+    })
+}
+
+/// Reads and migrates calendar.json; `None` if missing or unparseable.
+fn load_calendar(path: &Path) -> Option<serde_json::Value> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let mut calendar = serde_json::from_str(&content).ok()?;
+    crate::migration::migrate_calendar(&mut calendar);
+    Some(calendar)
 }
 
 /// Processes a filesystem event and emits notifications as needed.
@@ -276,10 +1102,11 @@ fn process_event(
     }
 
     // Check if all paths should be ignored
-    let relevant_paths: Vec<&PathBuf> = event
+    let relevant_paths: Vec<PathBuf> = event
         .paths
         .iter()
-        .filter(|p| !should_ignore_path(p))
+        .filter(|p| !should_ignore_path(p, &WatchIgnores::default()))
+        .cloned()
         .collect();
 
     if relevant_paths.is_empty() {
@@ -297,20 +1124,25 @@ fn process_event(
     // Update last emit time
     *last_emit = Instant::now();
 
-    // Convert paths to strings for the frontend
-    let path_strings: Vec<String> = relevant_paths
-        .iter()
-        .map(|p| p.to_string_lossy().to_string())
-        .collect();
-
-    // Emit the event to the frontend
+    // Emit the typed changes to the frontend
     window
-        .emit("fs-changed", &path_strings)
+        .emit("fs-changed", typed_changes(event, &relevant_paths))
         .map_err(|e| format!("Failed to emit event: {}", e))?;
 
     Ok(())
 }
 
+/// Reports a change the backend made itself, in the same `fs-changed`
+/// event the watcher sends, so the tree refreshes right away instead of
+/// after the debounce (or never, if no watcher is running).
+pub fn notify_changed(window: &tauri::Window, paths: &[PathBuf], kind: ChangeKind) {
+    let changes: Vec<FsChange> = paths.iter().map(|path| FsChange::new(path, kind)).collect();
+
+    if let Err(e) = window.emit("fs-changed", &changes) {
+        eprintln!("[Hibiscus] Error emitting event: {}", e);
+    }
+}
+
 /// Stops the current file watcher.
 ///
 /// This command signals the watcher thread to stop gracefully.
@@ -321,6 +1153,7 @@ fn process_event(
 #[tauri::command]
 pub fn stop_watching(state: State<WatcherState>) {
     let was_running = state.running.swap(false, Ordering::SeqCst);
+    state.calendar_running.store(false, Ordering::SeqCst);
 
     if was_running {
         if let Ok(current) = state.current_path.lock() {
@@ -331,6 +1164,19 @@ pub fn stop_watching(state: State<WatcherState>) {
     }
 }
 
+/// Sets the documents open in the editor, replacing the previous set.
+///
+/// While the watcher runs, an external rename or move of one of them (or of
+/// a folder containing it) emits `open-file-renamed` with `{ from, to }`,
+/// on top of the usual `fs-changed`. Call again whenever tabs open or close.
+///
+/// # Arguments
+/// * `paths` - Absolute paths of the open documents
+#[tauri::command]
+pub fn watch_open_files(paths: Vec<String>) {
+    OPEN_FILES.set(paths);
+}
+
 /// Checks if a watcher is currently running.
 ///
 /// # Arguments
@@ -358,3 +1204,387 @@ pub fn get_watched_path(state: State<WatcherState>) -> Option<String> {
         None
     }
 }
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, DataChange, RemoveKind};
+    use notify::ErrorKind;
+    use std::fs;
+
+    #[test]
+    fn test_suppressed_rename_events_are_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = dir.path().join("old.md");
+        let new = dir.path().join("new.md");
+        let suppression = SelfWriteSuppression::default();
+        suppression.suppress(&old);
+        suppression.suppress(&new);
+
+        let events = [
+            Event::new(EventKind::Remove(RemoveKind::File)).add_path(old.clone()),
+            Event::new(EventKind::Create(CreateKind::File)).add_path(new.clone()),
+            Event::new(EventKind::Modify(ModifyKind::Name(RenameMode::Both)))
+                .add_path(old)
+                .add_path(new),
+        ];
+        for event in &events {
+            assert!(relevant_event_paths(event, &suppression, &WatchIgnores::default()).is_empty());
+        }
+
+        let other = dir.path().join("other.md");
+        let unrelated = Event::new(EventKind::Remove(RemoveKind::File)).add_path(other.clone());
+        assert_eq!(relevant_event_paths(&unrelated, &suppression, &WatchIgnores::default()), vec![other]);
+    }
+
+    #[test]
+    fn test_suppressed_folder_covers_children() {
+        let dir = tempfile::tempdir().unwrap();
+        let suppression = SelfWriteSuppression::default();
+        suppression.suppress(&dir.path().join("notes"));
+
+        assert!(suppression.is_suppressed(&dir.path().join("notes").join("a.md")));
+        assert!(!suppression.is_suppressed(&dir.path().join("notes-2")));
+    }
+
+    #[test]
+    fn test_calendar_change_fires_dedicated_event() {
+        let dir = tempfile::tempdir().unwrap();
+        let data_dir = dir.path().join(".hibiscus");
+        std::fs::create_dir_all(&data_dir).unwrap();
+        std::fs::write(data_dir.join("calendar.json"), r#"{"events": []}"#).unwrap();
+
+        let running = Arc::new(AtomicBool::new(true));
+        let (tx, rx) = channel();
+        watch_calendar(dir.path().to_path_buf(), running.clone(), move |calendar| {
+            let _ = tx.send(calendar);
+        });
+        std::thread::sleep(Duration::from_millis(RECV_TIMEOUT_MS * 2));
+
+        // Edit from "another window": atomic replace, like save_calendar_data
+        let temp = data_dir.join("calendar.json.tmp");
+        std::fs::write(&temp, r#"{"events": [{"id": "e1", "title": "Exam"}]}"#).unwrap();
+        std::fs::rename(&temp, data_dir.join("calendar.json")).unwrap();
+
+        let calendar = rx.recv_timeout(Duration::from_secs(5)).expect("calendar-changed");
+        assert_eq!(calendar["events"][0]["title"], "Exam");
+
+        // Other files in .hibiscus do not trigger it
+        std::fs::write(data_dir.join("workspace.json"), "{}").unwrap();
+        assert!(rx.recv_timeout(Duration::from_millis(DEBOUNCE_MS * 3)).is_err());
+
+        running.store(false, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_stop_and_join_waits_for_old_threads() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".hibiscus")).unwrap();
+        let state = WatcherState::default();
+        state.calendar_running.store(true, Ordering::SeqCst);
+        state.running.store(true, Ordering::SeqCst);
+
+        let calendar = watch_calendar(dir.path().to_path_buf(), state.calendar_running.clone(), |_| {});
+        let running = state.running.clone();
+        let restarting = std::thread::spawn(move || {
+            assert!(!sleep_while_running(Duration::from_secs(30), &running));
+        });
+        state.threads.lock().unwrap().extend([calendar, restarting]);
+
+        let started = Instant::now();
+        stop_and_join(&state);
+        assert!(state.threads.lock().unwrap().is_empty());
+        // A watcher waiting to restart wakes up instead of sleeping it out
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_stop_with_pending_batch_does_not_wait_for_debounce() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        let state = WatcherState::default();
+        state.running.store(true, Ordering::SeqCst);
+
+        let (tx, rx) = channel();
+        let running = state.running.clone();
+        let flushed = Arc::new(AtomicBool::new(false));
+        let flushed_in_thread = flushed.clone();
+        let watcher = std::thread::spawn(move || {
+            let ignores = WatchIgnores::new(&root, &[]);
+            let debounce = Duration::from_millis(MAX_DEBOUNCE_MS);
+            let recovery = pump_events(&rx, &root, debounce, &ignores, &running, |_| WatcherRecovery::Continue, |_| {
+                flushed_in_thread.store(true, Ordering::SeqCst);
+            });
+            assert!(recovery.is_none());
+        });
+        state.threads.lock().unwrap().push(watcher);
+
+        let event = Event::new(EventKind::Create(CreateKind::File)).add_path(dir.path().join("a.md"));
+        tx.send(Ok(event)).unwrap();
+        std::thread::sleep(Duration::from_millis(RECV_TIMEOUT_MS * 2));
+
+        let started = Instant::now();
+        stop_and_join(&state);
+        assert!(started.elapsed() < Duration::from_millis(RECV_TIMEOUT_MS * 4));
+        assert!(!flushed.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_watcher_errors_are_classified() {
+        use std::io;
+        let classify = |e: notify::Error| WatcherErrorClass::classify(&e);
+
+        assert_eq!(classify(notify::Error::new(ErrorKind::MaxFilesWatch)), WatcherErrorClass::MaxFilesWatch);
+        assert_eq!(classify(notify::Error::path_not_found()), WatcherErrorClass::PathNotFound);
+        assert_eq!(classify(notify::Error::generic("boom")), WatcherErrorClass::Generic);
+        assert_eq!(classify(notify::Error::watch_not_found()), WatcherErrorClass::Generic);
+
+        let io_error = |kind| notify::Error::io(io::Error::from(kind));
+        assert_eq!(classify(io_error(io::ErrorKind::NotFound)), WatcherErrorClass::PathNotFound);
+        assert_eq!(classify(io_error(io::ErrorKind::PermissionDenied)), WatcherErrorClass::PermissionDenied);
+        assert_eq!(classify(io_error(io::ErrorKind::Interrupted)), WatcherErrorClass::Io);
+
+        #[cfg(unix)]
+        {
+            let os_error = |code| notify::Error::io(io::Error::from_raw_os_error(code));
+            assert_eq!(classify(os_error(EMFILE)), WatcherErrorClass::WatchLimitReached);
+            assert_eq!(classify(os_error(ENFILE)), WatcherErrorClass::WatchLimitReached);
+            assert_eq!(classify(os_error(ENOSPC)), WatcherErrorClass::MaxFilesWatch);
+        }
+
+        let payload = serde_json::to_value(WatcherErrorPayload {
+            code: WatcherErrorClass::WatchLimitReached.code(),
+            class: WatcherErrorClass::WatchLimitReached,
+            message: String::new(),
+            path: None,
+            paths: Vec::new(),
+            action: WatcherErrorClass::WatchLimitReached.action().to_string(),
+            recovery: WatcherRecovery::SwitchToPolling,
+        })
+        .unwrap();
+        assert_eq!(payload["class"], "watch_limit_reached");
+        assert_eq!(payload["recovery"], "switch_to_polling");
+
+        // Codes the frontend branches on, with the path the error is about
+        let denied = io_error(io::ErrorKind::PermissionDenied).add_path(PathBuf::from("/vault/private"));
+        let payload = serde_json::to_value(error_payload(&denied, &Supervisor::default(), true)).unwrap();
+        assert_eq!(payload["code"], "permission_denied");
+        assert_eq!(payload["path"], "/vault/private");
+        let missing = error_payload(&notify::Error::path_not_found(), &Supervisor::default(), true);
+        assert_eq!((missing.code, missing.path), ("path_not_found", None));
+        assert_eq!(error_payload(&notify::Error::generic("boom"), &Supervisor::default(), true).code, "watch_failed");
+    }
+
+    #[test]
+    fn test_supervisor_recovery_decisions() {
+        use WatcherErrorClass::*;
+        let native = Supervisor::default();
+        let allowed = Supervisor { polling_allowed: true, ..Default::default() };
+        let polling = Supervisor { polling_allowed: true, polling: true, restarts: 0 };
+
+        // Watch limits switch to polling only when allowed and not already polling
+        assert_eq!(allowed.decide(WatchLimitReached, false), WatcherRecovery::SwitchToPolling);
+        assert_eq!(allowed.decide(MaxFilesWatch, true), WatcherRecovery::SwitchToPolling);
+        assert_eq!(native.decide(MaxFilesWatch, false), WatcherRecovery::GiveUp);
+        assert_eq!(native.decide(MaxFilesWatch, true), WatcherRecovery::Continue);
+        assert_eq!(polling.decide(WatchLimitReached, false), WatcherRecovery::GiveUp);
+
+        // Transient errors restart a bounded number of times
+        assert_eq!(native.decide(Io, true), WatcherRecovery::Restart);
+        assert_eq!(native.decide(Generic, false), WatcherRecovery::Restart);
+        let exhausted = Supervisor { restarts: MAX_WATCHER_RESTARTS, ..Default::default() };
+        assert_eq!(exhausted.decide(Io, true), WatcherRecovery::GiveUp);
+
+        // Permission errors never retry
+        assert_eq!(allowed.decide(PermissionDenied, false), WatcherRecovery::GiveUp);
+        assert_eq!(native.decide(PermissionDenied, true), WatcherRecovery::Continue);
+        assert_eq!(native.decide(PathNotFound, false), WatcherRecovery::GiveUp);
+    }
+
+    #[test]
+    fn test_renames_are_typed_and_batched() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = dir.path().join("old.md");
+        let new = dir.path().join("new.md");
+        let event = |kind, paths: &[&PathBuf]| {
+            paths.iter().fold(Event::new(kind), |event, path| event.add_path((*path).clone()))
+        };
+        let rename = EventKind::Modify(ModifyKind::Name(RenameMode::Both));
+
+        let changes = typed_changes(&event(rename, &[&old, &new]), &[old.clone(), new.clone()]);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, ChangeKind::Renamed);
+        assert_eq!(changes[0].path, new.to_string_lossy());
+        assert_eq!(changes[0].from.as_deref(), Some(&*old.to_string_lossy()));
+        // Moved in from an ignored location: a create
+        let moved_in = typed_changes(&event(rename, &[&old, &new]), std::slice::from_ref(&new));
+        assert_eq!(moved_in[0].kind, ChangeKind::Created);
+
+        let mut batch = ChangeBatch::default();
+        let from = EventKind::Modify(ModifyKind::Name(RenameMode::From));
+        for change in typed_changes(&event(from, &[&old]), std::slice::from_ref(&old)) {
+            batch.push(change);
+        }
+        for change in changes {
+            batch.push(change);
+        }
+        let data = EventKind::Modify(ModifyKind::Data(DataChange::Content));
+        for change in typed_changes(&event(data, &[&new]), std::slice::from_ref(&new)) {
+            batch.push(change);
+        }
+        let batched = batch.drain();
+        assert_eq!(batched.len(), 1);
+        assert_eq!(batched[0].kind, ChangeKind::Renamed);
+        assert_eq!(batched[0].from.as_deref(), Some(&*old.to_string_lossy()));
+        assert_eq!(touched_paths(&batched).len(), 2);
+    }
+
+    #[test]
+    fn test_external_rename_of_open_file_is_reported_with_both_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let old = root.join("draft.md");
+        let new = root.join("final.md");
+        std::fs::write(&old, "# Draft").unwrap();
+
+        let open = OpenFiles::default();
+        open.set(vec![old.to_string_lossy().to_string(), root.join("other.md").to_string_lossy().to_string()]);
+
+        let (tx, rx) = channel();
+        let mut watcher = notify::recommended_watcher(tx).unwrap();
+        watcher.watch(&root, RecursiveMode::Recursive).unwrap();
+        std::fs::rename(&old, &new).unwrap();
+
+        // Same filtering, typing and batching as the watcher loop
+        let (ignores, suppression) = (WatchIgnores::new(&root, &[]), SelfWriteSuppression::default());
+        let mut batch = ChangeBatch::default();
+        while let Ok(Ok(event)) = rx.recv_timeout(Duration::from_millis(DEBOUNCE_MS)) {
+            for change in typed_changes(&event, &relevant_event_paths(&event, &suppression, &ignores)) {
+                batch.push(change);
+            }
+        }
+
+        let renamed = open.follow_renames(&batch.drain());
+        assert_eq!(
+            serde_json::to_value(&renamed).unwrap(),
+            serde_json::json!([{ "from": old.to_string_lossy(), "to": new.to_string_lossy() }])
+        );
+
+        // The set follows the file, including into a renamed folder
+        let moved = FsChange {
+            from: Some(root.to_string_lossy().to_string()),
+            to: Some(root.join("archive").to_string_lossy().to_string()),
+            ..FsChange::new(&root.join("archive"), ChangeKind::Renamed)
+        };
+        let renamed = open.follow_renames(&[moved]);
+        assert_eq!(renamed.len(), 2);
+        assert_eq!(renamed[0].from, new.to_string_lossy());
+        assert_eq!(renamed[0].to, root.join("archive").join("final.md").to_string_lossy());
+    }
+
+    #[test]
+    fn test_external_rename_updates_stored_references() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join(".hibiscus")).unwrap();
+        fs::write(root.join(".hibiscus").join("workspace.json"), r#"{"favorites": ["draft.md", "gone.md"]}"#).unwrap();
+        fs::write(root.join("draft.md"), "# Draft").unwrap();
+        fs::write(root.join("gone.md"), "# Gone").unwrap();
+
+        let (tx, rx) = channel();
+        let mut watcher = notify::recommended_watcher(tx).unwrap();
+        watcher.watch(&root, RecursiveMode::Recursive).unwrap();
+        fs::rename(root.join("draft.md"), root.join("final.md")).unwrap();
+        fs::remove_file(root.join("gone.md")).unwrap();
+
+        // Run the watcher loop until the batch is flushed
+        let running = AtomicBool::new(true);
+        let ignores = WatchIgnores::new(&root, &[]);
+        let debounce = Duration::from_millis(DEBOUNCE_MS);
+        pump_events(&rx, &root, debounce, &ignores, &running, |_| WatcherRecovery::Continue, |changes| {
+            crate::references::reconcile(&root, &reference_changes(&changes)).unwrap();
+            running.store(false, Ordering::SeqCst);
+        });
+
+        let ws: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(root.join(".hibiscus").join("workspace.json")).unwrap()).unwrap();
+        assert_eq!(ws["favorites"], serde_json::json!(["final.md"]));
+    }
+
+    #[test]
+    fn test_create_then_modify_stays_created() {
+        let mut batch = ChangeBatch::default();
+        let path = Path::new("/w/a.md");
+        batch.push(FsChange::new(path, ChangeKind::Created));
+        batch.push(FsChange::new(path, ChangeKind::Modified));
+        batch.push(FsChange::new(Path::new("/w/b.md"), ChangeKind::Removed));
+        batch.push(FsChange::new(Path::new("/w/b.md"), ChangeKind::Created));
+        let kinds: Vec<ChangeKind> = batch.drain().iter().map(|c| c.kind).collect();
+        assert_eq!(kinds, [ChangeKind::Created, ChangeKind::Modified]);
+        assert!(batch.is_empty());
+    }
+
+    #[test]
+    fn test_debounce_window_defaults_and_clamps() {
+        assert_eq!(debounce_window(None), DEBOUNCE_MS);
+        assert_eq!(debounce_window(Some(0)), 0);
+        assert_eq!(debounce_window(Some(1500)), 1500);
+        assert_eq!(debounce_window(Some(60_000)), MAX_DEBOUNCE_MS);
+    }
+
+    #[test]
+    fn test_ignores_match_components_and_extra_patterns() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("target/debug")).unwrap();
+        fs::create_dir_all(root.join("notes/dist")).unwrap();
+        let ignores = WatchIgnores::new(root, &["target/".to_string(), "/dist".to_string()]);
+
+        assert!(should_ignore_path(&root.join("node_modules/pkg/index.js"), &ignores));
+        assert!(!should_ignore_path(&root.join("my_node_modules_notes.txt"), &ignores));
+        assert!(should_ignore_path(&root.join("target/debug/app"), &ignores));
+        assert!(should_ignore_path(&root.join("target"), &ignores));
+        // `/dist` is anchored at the root
+        assert!(!should_ignore_path(&root.join("notes/dist/a.md"), &ignores));
+        assert!(!should_ignore_path(&root.join("notes/a.md"), &ignores));
+    }
+
+    #[test]
+    fn test_builtin_names_only_match_whole_components() {
+        let ignores = WatchIgnores::new(Path::new("/vaults/node_modules/notes"), &[]);
+        let path = |rel: &str| Path::new("/vaults/node_modules/notes").join(rel);
+
+        for legitimate in [
+            "notes_about_git.md",
+            "my.gitignore_tutorial/file.txt",
+            "node_modules_backup/a.md",
+            ".github/workflow.yml",
+            ".hibiscus-old/x.md",
+            "drafts/Thumbs.db.md",
+        ] {
+            assert!(!should_ignore_path(&path(legitimate), &ignores), "{} was ignored", legitimate);
+        }
+        for ignored in [".git/HEAD", "a/node_modules/b.js", ".hibiscus/workspace.json", "pics/.DS_Store"] {
+            assert!(should_ignore_path(&path(ignored), &ignores), "{} was not ignored", ignored);
+        }
+    }
+
+    #[test]
+    fn test_unwatchable_storage_is_refused() {
+        let root = Path::new("/memory/watcher-test");
+        let vault = crate::storage::tests::MemoryStorage::mount(root);
+        let err = check_watchable(&root.join("notes")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            HibiscusError::Watcher("Watching unavailable for this storage: '/memory/watcher-test/notes'".into())
+                .to_string()
+        );
+
+        drop(vault);
+        assert!(check_watchable(root).is_ok());
+    }
+}
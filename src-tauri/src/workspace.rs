@@ -1,13 +1,27 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::time::Timestamp;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WorkspaceFile {
     pub schema_version: String,
     pub workspace: WorkspaceInfo,
     pub settings: Option<serde_json::Value>,
+    /// Not stored since schema 1.2: the tree is read from disk. Filled only
+    /// when `load_workspace` is asked for it (older frontends)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tree: Vec<Node>,
     pub session: Option<SessionState>,
+    /// Per-node icon/color overrides, keyed by node id
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub decorations: BTreeMap<String, NodeDecoration>,
+    /// Folder id ("" for the root) -> child ids in user-chosen order
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub manual_order: BTreeMap<String, Vec<String>>,
+    /// Favorited node ids
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub favorites: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -15,10 +29,11 @@ pub struct WorkspaceInfo {
     pub id: String,
     pub name: String,
     pub root: String,
-    // pub created_at: Option<chrono::DateTime<chrono::Utc>>, TODO Future implementation
-    // pub updated_at: Option<chrono::DateTime<chrono::Utc>>, TODO Future implementation
-    pub created_at: Option<String>,
-    pub updated_at: Option<String>
+    /// Legacy formats are normalized to RFC3339 on the next save
+    #[serde(default, deserialize_with = "crate::time::lenient_option")]
+    pub created_at: Option<Timestamp>,
+    #[serde(default, deserialize_with = "crate::time::lenient_option")]
+    pub updated_at: Option<Timestamp>,
 }
 
 /**
@@ -26,7 +41,7 @@ pub struct WorkspaceInfo {
  * 
  * TODO Add extra Node Types as the project deepens and grows
  */
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum NodeType {
     File,
@@ -34,7 +49,7 @@ pub enum NodeType {
 }
 
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Node {
     pub id: String,
     pub name: String,
@@ -47,6 +62,107 @@ pub struct Node {
     pub meta: Option<serde_json::Value>,
 }
 
+/**
+ * Filesystem details of a tree node, serialized into `Node::meta`.
+ *
+ * Symlinks report the size and modified time of their target.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeMeta {
+    /// Size in bytes; files only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    pub modified: Option<Timestamp>,
+    pub is_symlink: bool,
+}
+
+impl From<&FileMetadata> for NodeMeta {
+    fn from(metadata: &FileMetadata) -> Self {
+        Self {
+            size: (!metadata.is_dir).then_some(metadata.size),
+            modified: metadata.modified,
+            is_symlink: metadata.kind == FileKind::Symlink,
+        }
+    }
+}
+
+/**
+ * What a path on disk is.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileKind {
+    File,
+    Folder,
+    Symlink,
+}
+
+/**
+ * Filesystem metadata of a file or folder, returned by `get_file_metadata`
+ * and the source of `NodeMeta`.
+ *
+ * Symlinks report the size, times and permissions of their target; a
+ * dangling link reports the link itself.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileMetadata {
+    /// Size in bytes; 0 for folders
+    pub size: u64,
+    pub modified: Option<Timestamp>,
+    /// `None` where the platform or filesystem doesn't record it
+    pub created: Option<Timestamp>,
+    pub readonly: bool,
+    /// The path itself: a symlink is `Symlink` whatever it points to
+    pub kind: FileKind,
+    /// Whether the path, or the symlink's target, is a folder
+    pub is_dir: bool,
+}
+
+impl FileMetadata {
+    /// Reads the metadata of `path`, following symlinks.
+    pub fn read(path: &std::path::Path) -> std::io::Result<Self> {
+        let link_meta = std::fs::symlink_metadata(path)?;
+        let is_symlink = link_meta.file_type().is_symlink();
+        let metadata = if is_symlink {
+            std::fs::metadata(path).unwrap_or(link_meta)
+        } else {
+            link_meta
+        };
+
+        let timestamp = |time: std::io::Result<std::time::SystemTime>| {
+            time.ok().map(|time| Timestamp(chrono::DateTime::<chrono::Utc>::from(time)))
+        };
+        Ok(Self {
+            size: if metadata.is_dir() { 0 } else { metadata.len() },
+            modified: timestamp(metadata.modified()),
+            created: timestamp(metadata.created()),
+            readonly: metadata.permissions().readonly(),
+            kind: if is_symlink {
+                FileKind::Symlink
+            } else if metadata.is_dir() {
+                FileKind::Folder
+            } else {
+                FileKind::File
+            },
+            is_dir: metadata.is_dir(),
+        })
+    }
+}
+
+/**
+ * User-chosen icon and color for a tree node.
+ *
+ * Stored in the vault (workspace.json) so decorations travel with it, and
+ * merged onto `Node::meta` when the tree is built.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeDecoration {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
 /**
  * Helper struct to get optional cursor position based on schema
  */
@@ -63,3 +179,139 @@ pub struct SessionState {
     pub active_node: Option<String>,
     pub cursor: Option<HashMap<String, CursorPosition>>,
 }
+
+/**
+ * Typed view over the known keys of `WorkspaceFile::settings`.
+ *
+ * Settings stay a free-form JSON object on disk so the frontend can add keys
+ * without a backend change; the backend only reads the keys it acts on.
+ */
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct WorkspaceSettings {
+    /// Extension (without dot, lowercase) -> viewer id, e.g. "csv" -> "table"
+    #[serde(default)]
+    pub file_associations: HashMap<String, String>,
+
+    /// Where notes created from unresolved wiki-links are placed
+    #[serde(default)]
+    pub new_note_placement: NewNotePlacement,
+
+    /// Folder (relative to the root) used by `NewNotePlacement::DefaultFolder`
+    #[serde(default)]
+    pub new_note_folder: Option<String>,
+
+    /// Template (relative to the root) used for notes created from links
+    #[serde(default)]
+    pub new_note_template: Option<String>,
+
+    /// Turns off focus time tracking; existing data is purged separately
+    #[serde(default)]
+    pub time_tracking_disabled: bool,
+
+    /// Indexes text recognized in images so knowledge search can find it
+    #[serde(default)]
+    pub ocr_in_search: bool,
+
+    /// Tesseract language spec for indexed images (default "eng")
+    #[serde(default)]
+    pub ocr_language: Option<String>,
+
+    /// Largest file `write_text_file` may write without `force`
+    #[serde(default)]
+    pub max_file_write_bytes: Option<u64>,
+
+    /// Largest file `read_binary_file` loads (default 50 MB)
+    #[serde(default)]
+    pub max_binary_read_bytes: Option<u64>,
+
+    /// Extra capability kinds that need confirmation (see `crate::capabilities`)
+    #[serde(default)]
+    pub gated_capabilities: Vec<String>,
+
+    /// Refuse file reads/writes whose symlink target leaves the workspace
+    #[serde(default)]
+    pub workspace_scoped: bool,
+
+    /// Folder depth the tree is read to (see `crate::limits`)
+    #[serde(default)]
+    pub max_tree_depth: Option<usize>,
+
+    /// Let the file watcher fall back to polling when the OS watch limit
+    /// is reached (see `crate::watcher`)
+    #[serde(default)]
+    pub watcher_polling_fallback: bool,
+
+    /// Folder pasted images are saved to, relative to the note
+    /// (default "assets")
+    #[serde(default)]
+    pub attachments_folder: Option<String>,
+
+    /// Extra ignore patterns, in `.hibiscusignore` syntax, applied after
+    /// the ignore file (see `crate::ignore`)
+    #[serde(default)]
+    pub ignore_patterns: Vec<String>,
+
+    /// Group language variants of a note (`topic.en.md`, `topic.de.md`)
+    /// into one tree node (see `crate::variants`)
+    #[serde(default)]
+    pub note_variants: bool,
+
+    /// Variant file name suffix with a `{lang}` placeholder
+    /// (default ".{lang}.md")
+    #[serde(default)]
+    pub variant_pattern: Option<String>,
+
+    /// Language codes that make a file a variant (default: a built-in list)
+    #[serde(default)]
+    pub variant_languages: Vec<String>,
+
+    /// Languages links to a logical note resolve to, most preferred first
+    #[serde(default)]
+    pub preferred_languages: Vec<String>,
+
+    /// Note lint rule overrides (see `crate::lint`)
+    #[serde(default)]
+    pub lint: crate::lint::LintSettings,
+
+    /// Back up files in `.hibiscus/backups` before saves overwrite them
+    /// (see `crate::backup::backup_file`)
+    #[serde(default)]
+    pub keep_backups: bool,
+
+    /// Backups kept per file by saves (default 5)
+    #[serde(default)]
+    pub backup_retention: Option<usize>,
+}
+
+/**
+ * Placement policy for notes created from unresolved wiki-links.
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NewNotePlacement {
+    /// Next to the note containing the link
+    #[default]
+    SameFolder,
+    /// In `new_note_folder` (the root if unset)
+    DefaultFolder,
+    /// In the folder named by the link's path segments, relative to the root
+    LinkPath,
+}
+
+impl WorkspaceSettings {
+    /// Extracts the known settings, ignoring unknown or malformed keys.
+    pub fn from_value(settings: Option<&serde_json::Value>) -> Self {
+        settings
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// Reads the settings of the workspace at `root` (defaults if unreadable).
+    pub fn load(root: &std::path::Path) -> Self {
+        std::fs::read_to_string(root.join(".hibiscus").join("workspace.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+            .map(|doc| Self::from_value(doc.get("settings")))
+            .unwrap_or_default()
+    }
+}
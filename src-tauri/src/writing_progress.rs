@@ -0,0 +1,378 @@
+//! ============================================================================
+//! Hibiscus Writing Progress
+//! ============================================================================
+//!
+//! Words written per day and per note, measured between successive
+//! versions ("snapshots") of each note.
+//!
+//! SNAPSHOTS (per note, oldest first):
+//! - The note's backups in `.hibiscus/backups/files/<relative path>/` (see
+//!   `crate::backup::backup_file`), followed by the file as it is now (at
+//!   its modified time).
+//! - If that gives fewer than two versions and the note is tracked by git,
+//!   its commits are used instead, plus the working copy if it differs.
+//! - Otherwise the note is estimated from its timestamps and marked
+//!   `approximate`: a note created in the range counts all its words as
+//!   written on its modified day, any other note counts nothing.
+//!
+//! COUNTING:
+//! - Consecutive snapshots are diffed word by word with `crate::diff`, after
+//!   frontmatter and markdown syntax are stripped, so formatting changes do
+//!   not count as writing.
+//! - `words_added` is gross writing and `words_removed` counts deletions on
+//!   their own; `net` is the difference. Rewriting a sentence therefore
+//!   shows up as words written instead of cancelling out.
+//! - A change is credited to the local day of the later snapshot. The last
+//!   snapshot before the range is the baseline, so earlier work never counts.
+//!
+//! Days are local calendar days for the UTC offset the frontend passes, as
+//! in `crate::time_tracking`.
+//! ============================================================================
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::diff::{diff_lines, DiffOp};
+use crate::markdown::{self, PlainTextOptions};
+use crate::time_tracking::{local_date, DateRange};
+
+/// One version of a note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    /// Milliseconds since the Unix epoch
+    pub at: i64,
+    pub text: String,
+}
+
+/// Words added and removed over some period.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct WordChange {
+    /// Gross words written
+    pub words_added: u64,
+    pub words_removed: u64,
+    /// `words_added - words_removed`
+    pub net: i64,
+}
+
+impl WordChange {
+    pub fn new(words_added: u64, words_removed: u64) -> Self {
+        Self {
+            words_added,
+            words_removed,
+            net: words_added as i64 - words_removed as i64,
+        }
+    }
+
+    fn add(&mut self, other: WordChange) {
+        *self = Self::new(
+            self.words_added + other.words_added,
+            self.words_removed + other.words_removed,
+        );
+    }
+}
+
+/// Change during one local day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DayProgress {
+    /// `YYYY-MM-DD`
+    pub date: String,
+    #[serde(flatten)]
+    pub change: WordChange,
+}
+
+/// Change of one note over the range.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NoteProgress {
+    /// Path relative to the workspace root
+    pub path: String,
+    #[serde(flatten)]
+    pub change: WordChange,
+    /// Days with changes, oldest first
+    pub days: Vec<DayProgress>,
+    /// Estimated from timestamps; the note had too few snapshots
+    pub approximate: bool,
+}
+
+/// Writing progress of a set of notes over a date range.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct WritingProgress {
+    pub total: WordChange,
+    /// Days with changes across all notes, oldest first
+    pub days: Vec<DayProgress>,
+    /// Notes changed in the range
+    pub notes: Vec<NoteProgress>,
+    /// Whether any note is estimated
+    pub approximate: bool,
+}
+
+/// Words of `text` that count as writing.
+pub fn prose_words(text: &str, markdown: bool) -> Vec<String> {
+    let plain = if markdown {
+        let (_, body) = markdown::split_frontmatter(text);
+        markdown::to_plain_text(body, PlainTextOptions::default())
+    } else {
+        text.to_string()
+    };
+    plain.split_whitespace().map(str::to_string).collect()
+}
+
+/// Words added and removed between two versions.
+pub fn word_change(old: &[String], new: &[String]) -> WordChange {
+    let (mut added, mut removed) = (0, 0);
+    for op in diff_lines(old, new) {
+        match op {
+            DiffOp::Insert { .. } => added += 1,
+            DiffOp::Delete { .. } => removed += 1,
+            DiffOp::Equal { .. } => {}
+        }
+    }
+    WordChange::new(added, removed)
+}
+
+/// Credits the change between each pair of consecutive snapshots to the
+/// local day of the later one, keeping days inside `range`.
+///
+/// `snapshots` must be sorted oldest first.
+pub fn progress_by_day(
+    snapshots: &[Snapshot],
+    markdown: bool,
+    range: &DateRange,
+    offset_minutes: i32,
+) -> BTreeMap<String, WordChange> {
+    let mut days: BTreeMap<String, WordChange> = BTreeMap::new();
+    let mut previous: Option<Vec<String>> = None;
+
+    for snapshot in snapshots {
+        let words = prose_words(&snapshot.text, markdown);
+        let date = local_date(snapshot.at, offset_minutes);
+        if let Some(old) = previous.filter(|_| range.contains(&date)) {
+            let change = word_change(&old, &words);
+            if change != WordChange::default() {
+                days.entry(date).or_default().add(change);
+            }
+        }
+        previous = Some(words);
+    }
+
+    days
+}
+
+/// Writing progress of `notes` (paths relative to `root`) within `range`.
+pub fn writing_progress(
+    root: &Path,
+    notes: &[String],
+    range: &DateRange,
+    offset_minutes: i32,
+) -> WritingProgress {
+    let mut progress = WritingProgress::default();
+    let mut days: BTreeMap<String, WordChange> = BTreeMap::new();
+
+    for rel in notes {
+        let Some(note) = note_progress(root, rel, range, offset_minutes) else {
+            continue;
+        };
+        for day in &note.days {
+            days.entry(day.date.clone()).or_default().add(day.change);
+        }
+        progress.total.add(note.change);
+        progress.approximate |= note.approximate;
+        progress.notes.push(note);
+    }
+
+    progress.days = into_days(days);
+    progress
+}
+
+/// Progress of one note, or `None` if it did not change within `range`.
+fn note_progress(root: &Path, rel: &str, range: &DateRange, offset_minutes: i32) -> Option<NoteProgress> {
+    let path = root.join(rel);
+    let markdown = path
+        .extension()
+        .is_some_and(|ext| markdown::is_markdown_extension(&ext.to_string_lossy()));
+    let metadata = fs::metadata(&path).ok()?;
+    let current = Snapshot {
+        at: millis(metadata.modified().ok()?),
+        text: fs::read_to_string(&path).ok()?,
+    };
+
+    let (days, approximate) = match note_snapshots(root, &path, current.clone(), range, offset_minutes) {
+        Some(snapshots) => (progress_by_day(&snapshots, markdown, range, offset_minutes), false),
+        None => {
+            let modified = local_date(current.at, offset_minutes);
+            if !range.contains(&modified) {
+                return None;
+            }
+            let created_in_range = metadata
+                .created()
+                .is_ok_and(|created| range.contains(&local_date(millis(created), offset_minutes)));
+            let written = if created_in_range {
+                prose_words(&current.text, markdown).len() as u64
+            } else {
+                0
+            };
+            (BTreeMap::from([(modified, WordChange::new(written, 0))]), true)
+        }
+    };
+
+    if days.is_empty() {
+        return None;
+    }
+    let mut change = WordChange::default();
+    for day in days.values() {
+        change.add(*day);
+    }
+    Some(NoteProgress {
+        path: rel.to_string(),
+        change,
+        days: into_days(days),
+        approximate,
+    })
+}
+
+/// At least two snapshots of the note at `path`, oldest first, or `None`
+/// if there are not enough.
+fn note_snapshots(
+    root: &Path,
+    path: &Path,
+    current: Snapshot,
+    range: &DateRange,
+    offset_minutes: i32,
+) -> Option<Vec<Snapshot>> {
+    let rel = path.strip_prefix(root).ok()?.to_string_lossy().replace('\\', "/");
+    let mut snapshots: Vec<Snapshot> = crate::backup::snapshots(root, &rel)
+        .into_iter()
+        .filter_map(|(at, backup)| Some(Snapshot { at, text: fs::read_to_string(backup).ok()? }))
+        .collect();
+    if !snapshots.is_empty() {
+        snapshots.push(current);
+        snapshots.sort_by_key(|snapshot| snapshot.at);
+        return Some(snapshots);
+    }
+
+    // Git history; only commits from the last one before the range on are read
+    let repo_file = crate::git::locate(path)?;
+    let commits = crate::git::file_history(&repo_file)?;
+    let start = commits
+        .iter()
+        .position(|(at, _)| !range.is_before(&local_date(*at, offset_minutes)))
+        .unwrap_or(commits.len())
+        .saturating_sub(1);
+
+    for (at, hash) in &commits[start..] {
+        if let Some(text) = crate::git::contents_at(&repo_file, hash) {
+            snapshots.push(Snapshot { at: *at, text });
+        }
+    }
+    // The first commit added the file, so all of its words are new
+    if start == 0 {
+        if let Some(first) = snapshots.first() {
+            snapshots.insert(0, Snapshot { at: first.at, text: String::new() });
+        }
+    }
+    if snapshots.last().is_some_and(|last| last.text != current.text) {
+        snapshots.push(Snapshot { at: current.at.max(snapshots.last()?.at), text: current.text });
+    }
+
+    (snapshots.len() >= 2).then_some(snapshots)
+}
+
+fn into_days(days: BTreeMap<String, WordChange>) -> Vec<DayProgress> {
+    days.into_iter().map(|(date, change)| DayProgress { date, change }).collect()
+}
+
+fn millis(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_millis() as i64).unwrap_or(0)
+}
+
+// =============================================================================
+// UNIT TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    /// 2024-03-04 10:00 UTC
+    const MONDAY: i64 = 1_709_546_400_000;
+    const HOUR: i64 = 60 * 60 * 1000;
+
+    fn snapshot(at: i64, text: &str) -> Snapshot {
+        Snapshot { at, text: text.into() }
+    }
+
+    fn range(from: &str, to: &str) -> DateRange {
+        DateRange { from: Some(from.into()), to: Some(to.into()) }
+    }
+
+    #[test]
+    fn test_word_diff_counts_added_and_removed_words() {
+        let words = |text| prose_words(text, true);
+
+        // Markdown syntax is not writing
+        let old = words("---\ntags: [a]\n---\n# Draft\n\nHello world");
+        let new = words("---\ntags: [a, b, c]\n---\n# Draft\n\n**Hello** brave new [world](w.md)");
+        assert_eq!(word_change(&old, &new), WordChange::new(2, 0));
+
+        // A rewrite is gross writing with zero net change
+        let change = word_change(&words("the cat sat down"), &words("the dog sat up"));
+        assert_eq!(change, WordChange { words_added: 2, words_removed: 2, net: 0 });
+    }
+
+    #[test]
+    fn test_changes_are_bucketed_by_local_day() {
+        let snapshots = [
+            // Baseline before the range: earlier work is not counted
+            snapshot(MONDAY - 48 * HOUR, "one"),
+            snapshot(MONDAY, "one two three"),
+            snapshot(MONDAY + HOUR, "one two three four five"),
+            // 23:30 UTC Monday is already Tuesday at UTC+1
+            snapshot(MONDAY + 13 * HOUR + 30 * 60_000, "one two four five six"),
+            // After the range
+            snapshot(MONDAY + 72 * HOUR, "one"),
+        ];
+
+        let utc = progress_by_day(&snapshots, true, &range("2024-03-04", "2024-03-05"), 0);
+        assert_eq!(
+            utc,
+            BTreeMap::from([("2024-03-04".to_string(), WordChange::new(5, 1))])
+        );
+
+        let plus_one = progress_by_day(&snapshots, true, &range("2024-03-04", "2024-03-05"), 60);
+        assert_eq!(
+            plus_one,
+            BTreeMap::from([
+                ("2024-03-04".to_string(), WordChange::new(4, 0)),
+                ("2024-03-05".to_string(), WordChange::new(1, 1)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_backups_give_exact_progress_and_others_are_estimated() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("novel.md"), "It was a dark and stormy night").unwrap();
+        std::fs::write(root.join("other.md"), "two words").unwrap();
+
+        let backups = crate::backup::file_backup_dir(root, &root.join("novel.md")).unwrap();
+        std::fs::create_dir_all(&backups).unwrap();
+        std::fs::write(backups.join(format!("novel.md_{}.bak", MONDAY)), "It was").unwrap();
+
+        let notes = ["novel.md".to_string(), "other.md".to_string()];
+        let progress = writing_progress(root, &notes, &DateRange::default(), 0);
+
+        let novel = &progress.notes[0];
+        assert_eq!(novel.path, "novel.md");
+        assert!(!novel.approximate);
+        assert_eq!(novel.change, WordChange::new(5, 0));
+
+        let other = &progress.notes[1];
+        assert!(other.approximate);
+        assert!(progress.approximate);
+        assert_eq!(progress.total.words_added, 5 + other.change.words_added);
+    }
+}
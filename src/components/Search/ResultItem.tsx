@@ -5,6 +5,7 @@ interface SearchResult {
   content: string
   word_count: number
   score: number
+  source?: 'text' | 'ocr'
 }
 
 interface ResultItemProps {
@@ -86,6 +87,9 @@ export function ResultItem({ result, onOpenFile, onNavigate, isSelected }: Resul
       
       <div className="search-item-meta">
         <span className="search-item-words">{result.word_count} words</span>
+        {result.source === 'ocr' && (
+          <span className="search-item-source" title="Text recognized in this image">OCR</span>
+        )}
       </div>
     </div>
   )
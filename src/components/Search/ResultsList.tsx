@@ -8,6 +8,7 @@ interface SearchResult {
   content: string
   word_count: number
   score: number
+  source?: 'text' | 'ocr'
 }
 
 interface ResultsListProps {
@@ -12,6 +12,7 @@ interface SearchResult {
   content: string
   word_count: number
   score: number
+  source?: 'text' | 'ocr'
 }
 
 interface TopicMap {
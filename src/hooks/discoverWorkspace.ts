@@ -3,10 +3,19 @@ import { invoke } from "@tauri-apps/api/core"
 export type WorkspaceDiscovery = {
   found: boolean
   path?: string
+  /** Root after ~ / environment-variable expansion */
+  root: string
+  /** Whether the found workspace.json can be loaded (absent if not found) */
+  valid?: boolean
+  schema_version?: string
+  workspace_name?: string
+  /** Why the found workspace.json cannot be loaded */
+  problem?: string
 }
 
 export async function discoverWorkspace(
-    root: string
+    root: string,
+    searchAncestors = false
 ): Promise<WorkspaceDiscovery> {
-  return invoke("discover_workspace", { root })
+  return invoke("discover_workspace", { root, searchAncestors })
 }
@@ -12,7 +12,8 @@ export function useDebouncedSave(delay = 500) {
     timer.current = window.setTimeout(() => {
       invoke("write_text_file", {
         path,
-        contents: content
+        contents: content,
+        lineEnding: "preserve"
       }).catch(console.error)
     }, delay)
   }
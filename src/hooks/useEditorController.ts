@@ -123,7 +123,7 @@ export function useEditorController(workspaceRoot: string | null) {
 
         isSavingRef.current = true
         try {
-          await invoke("write_text_file", { path, contents: content })
+          await invoke("write_text_file", { path, contents: content, lineEnding: "preserve" })
 
           // Update buffer to mark as saved
           const buffer = buffersRef.current.get(path)
@@ -513,6 +513,7 @@ export function useEditorController(workspaceRoot: string | null) {
             await invoke("write_text_file", {
               path,
               contents: buffer.content,
+              lineEnding: "preserve",
             })
           } catch (e) {
             console.error("[Hibiscus] Failed to save on unmount:", e)
@@ -533,8 +534,8 @@ export function useEditorController(workspaceRoot: string | null) {
 
     let unlisten: (() => void) | null = null
 
-    listen<string[]>("fs-changed", async (event) => {
-      const changedPaths = event.payload
+    listen<{ path: string; kind: string }[]>("fs-changed", async (event) => {
+      const changedPaths = event.payload.map((change) => change.path)
 
       // Check each buffer to see if it was modified externally
       for (const [filePath, buffer] of buffersRef.current.entries()) {
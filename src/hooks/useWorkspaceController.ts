@@ -4,14 +4,28 @@ import { listen } from "@tauri-apps/api/event"
 import { open } from "@tauri-apps/plugin-dialog"
 
 import { WorkspaceFile, Node } from "../types/workspace"
-import { discoverWorkspace } from "./discoverWorkspace"
 import { persistWorkspace } from "./useWorkspacePersistence"
 import { pickWorkspaceRoot, getLastWorkspaceRoot } from "./useWorkspaceRoot"
 import { updateSession } from "../state/session"
 import { useRecentFiles } from "./useRecentFiles"
 
+/** Result of the `bootstrap_workspace` command */
+type WorkspaceBootstrap = {
+  root: string
+  workspace_path: string
+  workspace?: WorkspaceFile | null
+  tree: Node[]
+  /** Folders the depth limit left out of `tree` */
+  limits_hit: { limit: string; path: string; value: number }[]
+  watching: boolean
+  watched_path?: string | null
+}
+
+/** Must match `WORKSPACE_SCHEMA_VERSION` in src-tauri/src/migration.rs */
+const WORKSPACE_SCHEMA_VERSION = "1.2"
+
 const emptyWorkspace: WorkspaceFile = {
-  schema_version: "1.0",
+  schema_version: WORKSPACE_SCHEMA_VERSION,
   workspace: { id: "", name: "", root: "" },
   tree: [],
   session: {},
@@ -29,23 +43,23 @@ export function useWorkspaceController() {
     : null
 
   // ---- core loader ----
-  const loadWorkspace = async (root: string) => {
+  const loadWorkspace = async (input: string) => {
+    // One backend call discovers, loads, builds the tree and starts the
+    // watcher, so startup steps cannot interleave. Roots with ~ or
+    // environment variables are expanded by the backend.
+    const snapshot = await invoke<WorkspaceBootstrap>("bootstrap_workspace", {
+      root: input,
+      watch: true,
+    })
+    const { root, tree } = snapshot
     setWorkspaceRoot(root)
 
-    const tree = await invoke<Node[]>("build_tree", { root })
-    const discovery = await discoverWorkspace(root)
-
-    if (discovery.found && discovery.path) {
-      // Load existing workspace configuration
-      const loaded = await invoke<WorkspaceFile>("load_workspace", {
-        path: discovery.path,
-      })
-
-      setWorkspace({ ...loaded, tree })
+    if (snapshot.workspace) {
+      setWorkspace({ ...snapshot.workspace, tree })
     } else {
       // Create fresh workspace for new directories
       const fresh: WorkspaceFile = {
-        schema_version: "1.0",
+        schema_version: WORKSPACE_SCHEMA_VERSION,
         workspace: {
           id: Date.now().toString(),
           name: "Hibiscus Workspace",
@@ -56,17 +70,9 @@ export function useWorkspaceController() {
         session: {},
       }
 
-      const path = `${root}/.hibiscus/workspace.json`
-      await persistWorkspace(path, fresh)
+      await persistWorkspace(snapshot.workspace_path, fresh)
       setWorkspace(fresh)
     }
-
-    // =========================================================================
-    // BUG FIX: Start file watcher for ALL workspaces (new AND existing)
-    // Previously this was only inside the else-block, causing autosave and
-    // external file change detection to fail for existing workspaces.
-    // =========================================================================
-    await invoke("watch_workspace", { path: root })
   }
 
   // ---- workspace switch ----
@@ -1,11 +1,17 @@
 import { invoke } from "@tauri-apps/api/core"
 import { WorkspaceFile } from "../types/workspace"
 
+/**
+ * Saves workspace.json.
+ *
+ * Resolves once the file is on disk; `workspace-save-complete` follows with
+ * the save's stats. The tree is not stored (schema 1.2), so saves are small.
+ */
 export function persistWorkspace(
   path: string,
   workspace: WorkspaceFile
 ) {
-  return invoke("save_workspace", {
+  return invoke<void>("save_workspace", {
     path,
     workspace
   })
@@ -4,6 +4,7 @@ export interface WorkspaceFile {
   settings?: Record<string, any>
   tree: Node[]
   session?: SessionState
+  decorations?: Record<string, NodeDecoration>
 }
 
 export interface WorkspaceInfo {
@@ -32,6 +33,11 @@ export interface Node {
   meta?: Record<string, any>
 }
 
+/** Per-node icon/color override, keyed by node id in `decorations`. */
+export interface NodeDecoration {
+  icon?: string
+  color?: string
+}
 
 export interface CursorPosition {
   line: number